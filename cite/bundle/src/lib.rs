@@ -0,0 +1,526 @@
+//! Cite Bundle - Citations of the integrity of a directory of committed files
+//!
+//! A bundle is the set of files under a root directory matching some glob patterns, e.g. the
+//! fixtures of a validation suite or an asset directory. Its root hash combines the sha256
+//! digest of each file, in a deterministic order, so that the citation breaks as soon as any
+//! file of the bundle is modified, added or removed.
+//!
+//! While the bundle matches its expected root hash, the digests of its files are recorded under
+//! `.cite/bundles`. When the root hash later differs, the files are hashed again and compared
+//! against the recording, to report which of them changed.
+
+pub mod ui;
+
+use cite_core::{Comparison, Content, Current, Diff, Id, Referenced, Source, SourceError};
+use sha2::{Digest, Sha256};
+use std::fmt::Display;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory of the cite directory under which the file digests of bundles are recorded
+pub const BUNDLE_SUBDIR: &str = "bundles";
+
+/// Errors thrown when hashing a bundle
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+	#[error("Invalid glob pattern '{pattern}': {error}")]
+	InvalidPattern {
+		pattern: String,
+		#[source]
+		error: glob::PatternError,
+	},
+
+	#[error("Failed to list bundle files: {0}")]
+	Glob(#[from] glob::GlobError),
+
+	#[error("Failed to read bundle file {path}: {error}")]
+	ReadFile {
+		path: String,
+		#[source]
+		error: std::io::Error,
+	},
+
+	#[error("Bundle has no files matching {0}")]
+	Empty(String),
+
+	#[error("Invalid bundle recording line: {0}")]
+	InvalidRecording(String),
+}
+
+impl From<BundleError> for SourceError {
+	fn from(error: BundleError) -> Self {
+		SourceError::ContentParsing(error.to_string())
+	}
+}
+
+/// Digest of a single file of a bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDigest {
+	/// Path of the file relative to the root of the bundle, with `/` separators
+	pub path: String,
+	/// The sha256 digest of the file, as a lowercase hex string
+	pub digest: String,
+}
+
+/// Digests of the files of a bundle, ordered by path
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BundleDigest {
+	files: Vec<FileDigest>,
+}
+
+impl BundleDigest {
+	/// Hash the files under the root directory matching any of the patterns
+	///
+	/// Patterns are relative to the root. Files are ordered by their relative path, so the root
+	/// hash does not depend on the order of the patterns or of the directory listing.
+	pub fn compute(root: &Path, patterns: &[String]) -> Result<Self, BundleError> {
+		// The root is taken literally, even if its name contains glob metacharacters
+		let escaped_root = PathBuf::from(glob::Pattern::escape(&root.to_string_lossy()));
+		let mut paths = Vec::new();
+		for pattern in patterns {
+			let full_pattern = escaped_root.join(pattern);
+			let entries = glob::glob(&full_pattern.to_string_lossy())
+				.map_err(|error| BundleError::InvalidPattern { pattern: pattern.clone(), error })?;
+			for entry in entries {
+				let path = entry?;
+				if path.is_file() {
+					paths.push(path);
+				}
+			}
+		}
+
+		let mut files = paths
+			.into_iter()
+			.map(|path| {
+				let relative = relative_path(root, &path);
+				let bytes = std::fs::read(&path)
+					.map_err(|error| BundleError::ReadFile { path: relative.clone(), error })?;
+				Ok(FileDigest { path: relative, digest: hex::encode(Sha256::digest(&bytes)) })
+			})
+			.collect::<Result<Vec<_>, BundleError>>()?;
+		files.sort_by(|a, b| a.path.cmp(&b.path));
+		files.dedup_by(|a, b| a.path == b.path);
+
+		if files.is_empty() {
+			return Err(BundleError::Empty(patterns.join(", ")));
+		}
+		Ok(Self { files })
+	}
+
+	/// Parse a recording, one `<digest>  <path>` line per file as written by `sha256sum`
+	pub fn parse(recording: &str) -> Result<Self, BundleError> {
+		let mut files = recording
+			.lines()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| match line.split_once("  ") {
+				Some((digest, path)) if !digest.is_empty() && !path.is_empty() => {
+					Ok(FileDigest { path: path.to_string(), digest: digest.to_lowercase() })
+				}
+				_ => Err(BundleError::InvalidRecording(line.to_string())),
+			})
+			.collect::<Result<Vec<_>, BundleError>>()?;
+		files.sort_by(|a, b| a.path.cmp(&b.path));
+		Ok(Self { files })
+	}
+
+	/// Render as a recording, one `<digest>  <path>` line per file
+	pub fn to_recording_string(&self) -> String {
+		self.files.iter().fold(String::new(), |mut recording, file| {
+			recording.push_str(&format!("{}  {}\n", file.digest, file.path));
+			recording
+		})
+	}
+
+	/// Get the digests of the files, ordered by path
+	pub fn files(&self) -> &[FileDigest] {
+		&self.files
+	}
+
+	/// Compute the root hash, the sha256 digest of the recording
+	///
+	/// Both the paths and the contents of the files contribute, so renaming a file changes the
+	/// root hash as well.
+	pub fn root_hash(&self) -> String {
+		hex::encode(Sha256::digest(self.to_recording_string().as_bytes()))
+	}
+
+	/// List the changes of the files from an earlier digest of the bundle to this one
+	pub fn changes_from(&self, earlier: &BundleDigest) -> Vec<FileChange> {
+		let find = |digest: &BundleDigest, path: &str| {
+			digest
+				.files
+				.iter()
+				.find(|file| file.path == path)
+				.map(|file| file.digest.clone())
+		};
+
+		let mut changes = earlier
+			.files
+			.iter()
+			.filter_map(|file| match find(self, &file.path) {
+				None => Some(FileChange::Removed(file.path.clone())),
+				Some(digest) if digest != file.digest => {
+					Some(FileChange::Modified(file.path.clone()))
+				}
+				Some(_) => None,
+			})
+			.chain(
+				self.files
+					.iter()
+					.filter(|file| find(earlier, &file.path).is_none())
+					.map(|file| FileChange::Added(file.path.clone())),
+			)
+			.collect::<Vec<_>>();
+		changes.sort_by(|a, b| a.path().cmp(b.path()));
+		changes
+	}
+}
+
+/// Get the path of a file relative to the root, with `/` separators on every platform
+fn relative_path(root: &Path, path: &Path) -> String {
+	let relative = path.strip_prefix(root).unwrap_or(path);
+	relative
+		.components()
+		.filter(|component| !matches!(component, Component::CurDir))
+		.map(|component| component.as_os_str().to_string_lossy())
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+/// Change of a single file of a bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+	/// The file is new to the bundle
+	Added(String),
+	/// The file is no longer part of the bundle
+	Removed(String),
+	/// The content of the file changed
+	Modified(String),
+}
+
+impl FileChange {
+	/// Get the path of the changed file
+	pub fn path(&self) -> &str {
+		match self {
+			FileChange::Added(path) | FileChange::Removed(path) | FileChange::Modified(path) => {
+				path
+			}
+		}
+	}
+}
+
+impl Display for FileChange {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FileChange::Added(path) => write!(f, "added {}", path),
+			FileChange::Removed(path) => write!(f, "removed {}", path),
+			FileChange::Modified(path) => write!(f, "modified {}", path),
+		}
+	}
+}
+
+/// Source citing the root hash of the files under a directory matching some glob patterns
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleSource {
+	id: Id,
+	root: PathBuf,
+	patterns: Vec<String>,
+	expected: Option<String>,
+	cite_dir: PathBuf,
+}
+
+impl BundleSource {
+	/// Create a source over the files under the root matching any of the patterns, e.g.
+	/// `fixtures/**/*.json`
+	///
+	/// Relative roots are resolved from the working directory, like the `.cite` directory.
+	pub fn new(root: impl Into<PathBuf>, patterns: Vec<String>) -> Self {
+		let root = root.into();
+		let id = Id::new(format!(
+			"bundle_{}_{}",
+			cache_key(&root.to_string_lossy()),
+			cache_key(&patterns.join("_"))
+		));
+		Self { id, root, patterns, expected: None, cite_dir: PathBuf::from(".cite") }
+	}
+
+	/// Set the expected root hash of the bundle
+	///
+	/// Without one, the citation reports the current root hash to be pinned.
+	pub fn with_expected(mut self, expected: &str) -> Self {
+		self.expected = Some(expected.trim().to_lowercase());
+		self
+	}
+
+	/// Set the cite directory under which the file digests are recorded
+	pub fn with_cite_dir(mut self, cite_dir: impl Into<PathBuf>) -> Self {
+		self.cite_dir = cite_dir.into();
+		self
+	}
+
+	/// Get the root directory of the bundle
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Get the glob patterns selecting the files of the bundle
+	pub fn patterns(&self) -> &[String] {
+		&self.patterns
+	}
+
+	/// Get the expected root hash, if pinned
+	pub fn expected(&self) -> Option<&str> {
+		self.expected.as_deref()
+	}
+
+	/// Get the path of the recording of the file digests of the bundle
+	pub fn recording_path(&self) -> PathBuf {
+		self.cite_dir.join(BUNDLE_SUBDIR).join(format!("{}.sha256", self.id.as_str()))
+	}
+
+	/// Read the recorded file digests, if they were recorded for the expected root hash
+	fn recorded(&self) -> Option<BundleDigest> {
+		let expected = self.expected.as_deref()?;
+		let recording = std::fs::read_to_string(self.recording_path()).ok()?;
+		BundleDigest::parse(&recording)
+			.ok()
+			.filter(|recorded| recorded.root_hash() == expected)
+	}
+
+	/// Record the file digests of the bundle, unless they are recorded already
+	fn record(&self, digest: &BundleDigest) -> Result<(), SourceError> {
+		let path = self.recording_path();
+		let recording = digest.to_recording_string();
+		if std::fs::read_to_string(&path).is_ok_and(|existing| existing == recording) {
+			return Ok(());
+		}
+
+		let write = || {
+			if let Some(parent) = path.parent() {
+				std::fs::create_dir_all(parent)?;
+			}
+			std::fs::write(&path, &recording)
+		};
+		write().map_err(|e| {
+			SourceError::ContentParsing(format!(
+				"Failed to record bundle digests to {}: {}",
+				path.display(),
+				e
+			))
+		})
+	}
+}
+
+/// Replace the characters that are unsafe in file names
+fn cache_key(value: &str) -> String {
+	value
+		.chars()
+		.map(|c| match c {
+			'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+			_ => '_',
+		})
+		.collect()
+}
+
+/// Referenced bundle: the expected root hash, with the file digests recorded for it if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferencedBundle {
+	/// The pinned root hash, empty if the author has not filled it in yet
+	pub root_hash: String,
+	/// The file digests recorded while the bundle matched the pinned root hash
+	pub recorded: Option<BundleDigest>,
+}
+
+impl Content for ReferencedBundle {}
+impl Referenced for ReferencedBundle {}
+
+/// Current bundle: the digests of the files as they are now
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrentBundle {
+	pub digest: BundleDigest,
+}
+
+impl Content for CurrentBundle {}
+
+/// Diff between the expected and the current root hash of a bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleDiff {
+	/// The pinned root hash, empty if the author has not filled it in yet
+	pub expected: String,
+	/// The root hash of the current files
+	pub current: String,
+	/// The changed files, if the file digests were recorded for the pinned root hash
+	pub changes: Option<Vec<FileChange>>,
+}
+
+impl BundleDiff {
+	/// Check if no root hash has been pinned yet, i.e., the author wants the current one printed
+	pub fn is_unpinned(&self) -> bool {
+		self.expected.is_empty()
+	}
+}
+
+impl Diff for BundleDiff {
+	fn is_empty(&self) -> bool {
+		self.expected == self.current
+	}
+}
+
+impl Current<ReferencedBundle, BundleDiff> for CurrentBundle {
+	fn diff(&self, other: &ReferencedBundle) -> Result<BundleDiff, SourceError> {
+		let current = self.digest.root_hash();
+		let changes = (current != other.root_hash)
+			.then(|| other.recorded.as_ref().map(|recorded| self.digest.changes_from(recorded)))
+			.flatten();
+		Ok(BundleDiff { expected: other.root_hash.clone(), current, changes })
+	}
+}
+
+impl Source<ReferencedBundle, CurrentBundle, BundleDiff> for BundleSource {
+	fn id(&self) -> &Id {
+		&self.id
+	}
+
+	fn target_description(&self) -> String {
+		format!("Bundle: {}\nFiles: {}", self.root.display(), self.patterns.join(", "))
+	}
+
+	fn supports_offline(&self) -> bool {
+		true
+	}
+
+	fn refresh(&self) -> Result<(), SourceError> {
+		Err(SourceError::ManualUpdate(
+			"bundle citations pin a root hash; update `sha256` in the citation".to_string(),
+		))
+	}
+
+	fn get_referenced(&self) -> Result<ReferencedBundle, SourceError> {
+		Ok(ReferencedBundle {
+			root_hash: self.expected.clone().unwrap_or_default(),
+			recorded: self.recorded(),
+		})
+	}
+
+	fn get_current(&self) -> Result<CurrentBundle, SourceError> {
+		Ok(CurrentBundle { digest: BundleDigest::compute(&self.root, &self.patterns)? })
+	}
+
+	fn get(&self) -> Result<Comparison<ReferencedBundle, CurrentBundle, BundleDiff>, SourceError> {
+		let _span = cite_core::trace_span!("bundle", root = %self.root.display());
+
+		let referenced = self.get_referenced()?;
+		let current = self.get_current()?;
+		let diff = current.diff(&referenced)?;
+
+		// Keep the file digests of the intact bundle, to explain later changes to it
+		if diff.is_empty() {
+			self.record(&current.digest)?;
+		}
+		Ok(Comparison::new(referenced, current, diff))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bundle");
+
+	/// Copy the fixture bundle to a temporary directory, which the test may modify
+	fn fixture() -> Result<TempDir, anyhow::Error> {
+		let dir = TempDir::new()?;
+		let bundle = dir.path().join("bundle");
+		std::fs::create_dir_all(bundle.join("schemas"))?;
+		for file in ["README.md", "schemas/package.json", "schemas/tags.json"] {
+			std::fs::copy(Path::new(FIXTURE).join(file), bundle.join(file))?;
+		}
+		Ok(dir)
+	}
+
+	fn patterns(patterns: &[&str]) -> Vec<String> {
+		patterns.iter().map(|pattern| String::from(*pattern)).collect()
+	}
+
+	#[test]
+	fn test_bundle_digest_is_deterministic() -> Result<(), anyhow::Error> {
+		let digest = BundleDigest::compute(Path::new(FIXTURE), &patterns(&["**/*"]))?;
+		let paths = digest.files().iter().map(|file| file.path.as_str()).collect::<Vec<_>>();
+		assert_eq!(paths, ["README.md", "schemas/package.json", "schemas/tags.json"]);
+
+		// The order and overlap of patterns do not matter
+		let reordered =
+			BundleDigest::compute(Path::new(FIXTURE), &patterns(&["schemas/*.json", "**/*"]))?;
+		assert_eq!(reordered.root_hash(), digest.root_hash());
+
+		// The recording round trips
+		let parsed = BundleDigest::parse(&digest.to_recording_string())?;
+		assert_eq!(parsed, digest);
+
+		assert!(BundleDigest::compute(Path::new(FIXTURE), &patterns(&["*.toml"])).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_unmodified_bundle() -> Result<(), anyhow::Error> {
+		let dir = fixture()?;
+		let root_hash =
+			BundleDigest::compute(&dir.path().join("bundle"), &patterns(&["**/*"]))?.root_hash();
+
+		let source = BundleSource::new(dir.path().join("bundle"), patterns(&["**/*"]))
+			.with_expected(&root_hash)
+			.with_cite_dir(dir.path().join(".cite"));
+		let comparison = source.get()?;
+		assert!(comparison.is_same());
+		assert!(source.recording_path().exists());
+
+		// Without a pinned root hash, the current one is reported
+		let unpinned = BundleSource::new(dir.path().join("bundle"), patterns(&["**/*"]))
+			.with_cite_dir(dir.path().join(".cite"));
+		let comparison = unpinned.get()?;
+		assert!(!comparison.is_same());
+		assert!(comparison.diff().is_unpinned());
+		assert_eq!(comparison.diff().current, root_hash);
+		Ok(())
+	}
+
+	#[test]
+	fn test_modified_bundle_reports_changed_files() -> Result<(), anyhow::Error> {
+		let dir = fixture()?;
+		let bundle = dir.path().join("bundle");
+		let root_hash = BundleDigest::compute(&bundle, &patterns(&["**/*"]))?.root_hash();
+		let source = BundleSource::new(&bundle, patterns(&["**/*"]))
+			.with_expected(&root_hash)
+			.with_cite_dir(dir.path().join(".cite"));
+
+		// The intact bundle is recorded when validated
+		assert!(source.get()?.is_same());
+
+		std::fs::write(bundle.join("schemas/tags.json"), "{ \"type\": \"array\" }\n")?;
+		std::fs::remove_file(bundle.join("README.md"))?;
+		std::fs::write(bundle.join("schemas/extra.json"), "{}\n")?;
+
+		let comparison = source.get()?;
+		assert!(!comparison.is_same());
+		assert_eq!(comparison.diff().expected, root_hash);
+		assert_eq!(
+			comparison.diff().changes,
+			Some(vec![
+				FileChange::Removed("README.md".to_string()),
+				FileChange::Added("schemas/extra.json".to_string()),
+				FileChange::Modified("schemas/tags.json".to_string()),
+			])
+		);
+
+		// A modified bundle does not overwrite the recording of the intact one
+		assert_eq!(
+			BundleDigest::parse(&std::fs::read_to_string(source.recording_path())?)?.root_hash(),
+			root_hash
+		);
+
+		// Without a recording, the changed files are unknown
+		std::fs::remove_file(source.recording_path())?;
+		assert_eq!(source.get()?.diff().changes, None);
+		Ok(())
+	}
+}
@@ -0,0 +1,118 @@
+use crate::{BundleDiff, BundleSource, CurrentBundle, ReferencedBundle};
+use cite_core::ui::{AboveDocAttr, SourceUi, SourceUiError};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+impl SourceUi<ReferencedBundle, CurrentBundle, BundleDiff> for BundleSource {
+	fn from_kwarg_json(kwargs: &HashMap<String, Value>) -> Result<Self, SourceUiError> {
+		let root = match kwargs.get("root") {
+			Some(value) => value.as_str().ok_or_else(|| {
+				SourceUiError::InvalidParameter("root parameter must be a string".to_string())
+			})?,
+			None => ".",
+		};
+		let patterns = parse_patterns(
+			kwargs
+				.get("glob")
+				.ok_or_else(|| SourceUiError::MissingParameter("glob".to_string()))?,
+		)?;
+
+		let source = BundleSource::new(root, patterns);
+		match kwargs.get("sha256") {
+			Some(value) => {
+				let expected = value.as_str().ok_or_else(|| {
+					SourceUiError::InvalidParameter("sha256 parameter must be a string".to_string())
+				})?;
+				Ok(source.with_expected(expected))
+			}
+			None => Ok(source),
+		}
+	}
+
+	fn to_standard_json(&self) -> Result<Map<String, Value>, SourceUiError> {
+		let mut map = Map::new();
+		map.insert("src".to_string(), Value::String("bundle".to_string()));
+		map.insert("root".to_string(), Value::String(self.root().display().to_string()));
+		map.insert(
+			"glob".to_string(),
+			Value::Array(self.patterns().iter().cloned().map(Value::String).collect()),
+		);
+		if let Some(expected) = self.expected() {
+			map.insert("sha256".to_string(), Value::String(expected.to_string()));
+		}
+		Ok(map)
+	}
+
+	fn to_above_doc_attr(&self) -> Result<AboveDocAttr, SourceUiError> {
+		let json_map = self.to_standard_json()?;
+		let json_content = serde_json::to_string_pretty(&json_map).map_err(|e| {
+			SourceUiError::Serialization(format!("Failed to serialize to JSON: {}", e))
+		})?;
+
+		Ok(AboveDocAttr::new(json_content, "bundle".to_string()))
+	}
+
+	fn is_valid_attr_key(attr_key: &str) -> bool {
+		match attr_key {
+			// Bundle fields
+			"root" | "glob" | "sha256" |
+			// Citation-level fields
+			"src" | "reason" | "level" | "annotation" => true,
+			_ => false,
+		}
+	}
+}
+
+/// Parse a `glob` kwarg: a pattern, or a non-empty tuple of patterns
+fn parse_patterns(value: &Value) -> Result<Vec<String>, SourceUiError> {
+	let invalid = || {
+		SourceUiError::InvalidParameter(
+			"glob parameter must be a pattern or a non-empty tuple of patterns".to_string(),
+		)
+	};
+
+	let patterns = match value {
+		Value::String(pattern) => vec![pattern.clone()],
+		Value::Array(patterns) => patterns
+			.iter()
+			.map(|pattern| pattern.as_str().map(|s| s.to_string()).ok_or_else(invalid))
+			.collect::<Result<Vec<_>, _>>()?,
+		_ => return Err(invalid()),
+	};
+	if patterns.is_empty() {
+		return Err(invalid());
+	}
+	Ok(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_from_kwarg_json() -> Result<(), SourceUiError> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("root".to_string(), json!("tests/bundle"));
+		kwargs.insert("glob".to_string(), json!(["schemas/*.json", "README.md"]));
+		kwargs.insert("sha256".to_string(), json!("ABC123"));
+
+		let source = BundleSource::from_kwarg_json(&kwargs)?;
+		assert_eq!(source.root(), std::path::Path::new("tests/bundle"));
+		assert_eq!(source.patterns(), ["schemas/*.json", "README.md"]);
+		assert_eq!(source.expected(), Some("abc123"));
+		assert_eq!(source.to_standard_json()?.get("src"), Some(&json!("bundle")));
+
+		// A single pattern under the working directory, not yet pinned
+		let mut kwargs = HashMap::new();
+		kwargs.insert("glob".to_string(), json!("assets/**/*"));
+		let source = BundleSource::from_kwarg_json(&kwargs)?;
+		assert_eq!(source.root(), std::path::Path::new("."));
+		assert_eq!(source.expected(), None);
+
+		assert!(BundleSource::from_kwarg_json(&HashMap::new()).is_err());
+		kwargs.insert("glob".to_string(), json!([]));
+		assert!(BundleSource::from_kwarg_json(&kwargs).is_err());
+		Ok(())
+	}
+}
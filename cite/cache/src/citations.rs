@@ -0,0 +1,222 @@
+use crate::CacheError;
+use cite_core::{ChangeClass, CitationLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Directory of the citation registry within the cite directory
+pub const CITATION_REGISTRY_DIR: &str = "citations";
+
+/// A citation expanded by the `#[cite]` macro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CitationRecord {
+	/// The crate whose compilation expanded the citation
+	#[serde(rename = "crate")]
+	pub krate: String,
+	/// Path of the file declaring the citation, relative to the workspace root, where the
+	/// compiler tells proc macros
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub file: Option<String>,
+	/// 1-based line of the `#[cite]` attribute, or 0 if unknown
+	#[serde(default)]
+	pub line: usize,
+	/// The source type, e.g. `http`
+	pub src: String,
+	/// The resolved kwargs of the citation
+	pub kwargs: BTreeMap<String, serde_json::Value>,
+	/// The level drift is reported at as the citation was expanded, or `None` if it was not
+	/// validated, e.g. as it requires a disabled feature
+	#[serde(default)]
+	pub level: Option<CitationLevel>,
+	/// The severity of the drift found as the citation was expanded, if it drifted and its
+	/// source classifies the change
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub change: Option<ChangeClass>,
+}
+
+/// The citations expanded in a workspace, as recorded by the `#[cite]` macro
+///
+/// Each compilation unit writes the citations it expanded to a file of its own, named by its
+/// crate, build and process. The first write of a crate in a build removes the files earlier
+/// builds wrote for it, so that the registry follows citations as they are edited, while the
+/// citations of crates that were not recompiled stay recorded.
+///
+/// The registry is derived from the sources, so it ignores itself in git.
+#[derive(Debug, Clone)]
+pub struct CitationRegistry {
+	dir: PathBuf,
+}
+
+impl CitationRegistry {
+	/// Open the registry in the given directory
+	pub fn new(dir: PathBuf) -> Self {
+		Self { dir }
+	}
+
+	/// The directory of the registry
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	/// Record a citation expanded by this process in the given build
+	pub fn record(&self, build: &str, record: CitationRecord) -> Result<(), CacheError> {
+		std::fs::create_dir_all(&self.dir).map_err(CacheError::WriteCacheFile)?;
+		let gitignore = self.dir.join(".gitignore");
+		if !gitignore.exists() {
+			std::fs::write(&gitignore, "*\n").map_err(CacheError::WriteCacheFile)?;
+		}
+
+		let path = self.unit_path(&record.krate, build);
+		let mut records = match path.exists() {
+			true => read_records(&path)?,
+			false => {
+				self.remove_stale(&record.krate, build)?;
+				Vec::new()
+			}
+		};
+		if records.contains(&record) {
+			return Ok(());
+		}
+		records.push(record);
+
+		let content = serde_json::to_string_pretty(&records)
+			.map_err(|e| CacheError::Serialize(Box::new(e)))?;
+		let temp_path = path.with_extension("json.tmp");
+		std::fs::write(&temp_path, content).map_err(CacheError::WriteCacheFile)?;
+		std::fs::rename(&temp_path, &path).map_err(CacheError::WriteCacheFile)
+	}
+
+	/// Load every recorded citation, ordered by file and line
+	///
+	/// A citation expanded by several compilation units, e.g. a library and its tests, is
+	/// listed once.
+	pub fn load(&self) -> Result<Vec<CitationRecord>, CacheError> {
+		if !self.dir.exists() {
+			return Ok(Vec::new());
+		}
+
+		let mut citations: Vec<CitationRecord> = Vec::new();
+		for path in self.unit_paths()? {
+			for record in read_records(&path)? {
+				if !citations.contains(&record) {
+					citations.push(record);
+				}
+			}
+		}
+		citations.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+		Ok(citations)
+	}
+
+	fn unit_path(&self, krate: &str, build: &str) -> PathBuf {
+		self.dir.join(format!(
+			"{}.{}.{}.json",
+			sanitize(krate),
+			sanitize(build),
+			std::process::id()
+		))
+	}
+
+	fn unit_paths(&self) -> Result<Vec<PathBuf>, CacheError> {
+		let mut paths = std::fs::read_dir(&self.dir)
+			.map_err(CacheError::ReadCacheFile)?
+			.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+			.filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+			.collect::<Vec<_>>();
+		paths.sort();
+		Ok(paths)
+	}
+
+	/// Remove the files of the crate written by other builds
+	fn remove_stale(&self, krate: &str, build: &str) -> Result<(), CacheError> {
+		let krate = sanitize(krate);
+		let build = sanitize(build);
+		for path in self.unit_paths()? {
+			let name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+			let mut parts = name.as_deref().unwrap_or_default().split('.');
+			if parts.next() == Some(krate.as_str()) && parts.next() != Some(build.as_str()) {
+				match std::fs::remove_file(&path) {
+					Ok(()) => {}
+					// Another unit of the crate may have removed it first
+					Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+					Err(e) => return Err(CacheError::DeleteCacheFile(e)),
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+fn read_records(path: &Path) -> Result<Vec<CitationRecord>, CacheError> {
+	let content = std::fs::read_to_string(path).map_err(CacheError::ReadCacheFile)?;
+	serde_json::from_str(&content).map_err(|e| CacheError::Deserialize(Box::new(e)))
+}
+
+/// Keep names to characters that cannot be confused with the separators of file names
+fn sanitize(name: &str) -> String {
+	name.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	fn record(krate: &str, file: &str, line: usize, url: &str) -> CitationRecord {
+		CitationRecord {
+			krate: krate.to_string(),
+			file: Some(file.to_string()),
+			line,
+			src: "http".to_string(),
+			kwargs: BTreeMap::from([("url".to_string(), serde_json::json!(url))]),
+			level: Some(CitationLevel::Warn),
+			change: None,
+		}
+	}
+
+	#[test]
+	fn test_registry_follows_builds() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let registry = CitationRegistry::new(temp_dir.path().join(CITATION_REGISTRY_DIR));
+		assert!(registry.load()?.is_empty());
+
+		// Citations recorded twice, e.g. by a library and its tests, are listed once
+		registry.record("cargo-1", record("docs", "src/lib.rs", 9, "https://b.example"))?;
+		registry.record("cargo-1", record("docs", "src/lib.rs", 3, "https://a.example"))?;
+		registry.record("cargo-1", record("docs", "src/lib.rs", 3, "https://a.example"))?;
+		registry.record("cargo-1", record("api", "api/lib.rs", 1, "https://c.example"))?;
+		assert_eq!(
+			registry.load()?,
+			vec![
+				record("api", "api/lib.rs", 1, "https://c.example"),
+				record("docs", "src/lib.rs", 3, "https://a.example"),
+				record("docs", "src/lib.rs", 9, "https://b.example"),
+			]
+		);
+
+		// A later build of a crate replaces its citations; other crates keep theirs
+		registry.record("cargo-2", record("docs", "src/lib.rs", 4, "https://a.example"))?;
+		assert_eq!(
+			registry.load()?,
+			vec![
+				record("api", "api/lib.rs", 1, "https://c.example"),
+				record("docs", "src/lib.rs", 4, "https://a.example"),
+			]
+		);
+
+		// The severity of a drift is recorded with the citation
+		let drifted = CitationRecord {
+			change: Some(ChangeClass::Comment),
+			..record("api", "api/lib.rs", 1, "https://c.example")
+		};
+		registry.record("cargo-2", drifted.clone())?;
+		let content = std::fs::read_to_string(registry.unit_path("api", "cargo-2"))?;
+		assert!(content.contains("\"change\": \"comment\""), "{}", content);
+		assert!(registry.load()?.contains(&drifted));
+
+		// The registry ignores itself in git
+		assert_eq!(std::fs::read_to_string(registry.dir().join(".gitignore"))?, "*\n");
+		Ok(())
+	}
+}
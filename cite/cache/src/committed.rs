@@ -0,0 +1,151 @@
+use crate::CacheError;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+/// Process-wide memo of the uncommitted paths of each cite directory
+///
+/// A compilation unit checks the committed references once per citation, so `git status` only
+/// runs again once the cite directory has been written to.
+fn statuses() -> &'static Mutex<HashMap<PathBuf, Vec<String>>> {
+	static STATUSES: OnceLock<Mutex<HashMap<PathBuf, Vec<String>>>> = OnceLock::new();
+	STATUSES.get_or_init(Default::default)
+}
+
+/// Drop the memoized status of a cite directory, e.g. once a reference in it is written
+pub fn forget(cite_dir: &Path) {
+	if let Ok(mut statuses) = statuses().lock() {
+		statuses.remove(cite_dir);
+	}
+}
+
+/// List the files under the cite directory that differ from what is committed, as reported by
+/// `git status`: modified, added, deleted and untracked files alike
+///
+/// Paths are relative to the root of the repository. The status is memoized until the cite
+/// directory is written to through the cache; see [forget].
+pub fn uncommitted_paths(cite_dir: &Path) -> Result<Vec<String>, CacheError> {
+	let memoized = statuses().lock().ok().and_then(|statuses| statuses.get(cite_dir).cloned());
+	if let Some(paths) = memoized {
+		return Ok(paths);
+	}
+
+	let paths = git_status(cite_dir)?;
+	if let Ok(mut statuses) = statuses().lock() {
+		statuses.insert(cite_dir.to_path_buf(), paths.clone());
+	}
+	Ok(paths)
+}
+
+fn git_status(cite_dir: &Path) -> Result<Vec<String>, CacheError> {
+	if !cite_dir.exists() {
+		return Ok(Vec::new());
+	}
+
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(cite_dir)
+		.args(["status", "--porcelain", "-z", "--untracked-files=all", "--", "."])
+		.output()
+		.map_err(|e| CacheError::GitStatus(format!("git could not be run: {}", e)))?;
+	if !output.status.success() {
+		return Err(CacheError::GitStatus(format!(
+			"git status failed in {}: {}",
+			cite_dir.display(),
+			String::from_utf8_lossy(&output.stderr).trim()
+		)));
+	}
+
+	Ok(parse_porcelain(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the paths of `git status --porcelain -z`
+///
+/// Each entry is a two-letter status, a space and the path, terminated by a NUL. Renames and
+/// copies are followed by the path they came from, which is not reported.
+fn parse_porcelain(output: &str) -> Vec<String> {
+	let mut paths = Vec::new();
+	let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+	while let Some(entry) = entries.next() {
+		let (Some(status), Some(path)) = (entry.get(..2), entry.get(3..)) else {
+			continue;
+		};
+		if status.contains(['R', 'C']) {
+			entries.next();
+		}
+		paths.push(path.to_string());
+	}
+	paths
+}
+
+/// Run git in a directory with a fixed identity, returning its trimmed output, e.g. to commit
+/// the fixture repositories of tests
+#[cfg(any(test, feature = "test-utils"))]
+pub fn git(dir: &Path, args: &[&str]) -> std::io::Result<String> {
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(dir)
+		.args(["-c", "user.name=cite", "-c", "user.email=cite@example.com"])
+		.args(args)
+		.output()?;
+	if !output.status.success() {
+		return Err(std::io::Error::other(String::from_utf8_lossy(&output.stderr).into_owned()));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	#[test]
+	fn test_uncommitted_paths() -> Result<(), anyhow::Error> {
+		let repo = TempDir::new()?;
+		let cite_dir = repo.path().join(".cite");
+		std::fs::create_dir_all(cite_dir.join("cache"))?;
+		std::fs::write(cite_dir.join("cache").join("http_a.json"), "{}")?;
+		std::fs::write(repo.path().join("lib.rs"), "")?;
+		git(repo.path(), &["init", "-q"])?;
+		git(repo.path(), &["add", "-A"])?;
+		git(repo.path(), &["commit", "-qm", "Add references"])?;
+		assert!(uncommitted_paths(&cite_dir)?.is_empty());
+
+		// Refreshed and new references are reported once the memoized status is dropped;
+		// changes outside the cite directory are not
+		std::fs::write(cite_dir.join("cache").join("http_a.json"), "{\"content\": 1}")?;
+		std::fs::write(cite_dir.join("cache").join("http_b.json"), "{}")?;
+		std::fs::write(repo.path().join("lib.rs"), "fn main() {}")?;
+		assert!(uncommitted_paths(&cite_dir)?.is_empty());
+		forget(&cite_dir);
+		assert_eq!(
+			uncommitted_paths(&cite_dir)?,
+			vec![".cite/cache/http_a.json".to_string(), ".cite/cache/http_b.json".to_string()]
+		);
+
+		// Outside a repository there is no committed state to compare against
+		let detached = TempDir::new()?;
+		assert!(matches!(uncommitted_paths(detached.path()), Err(CacheError::GitStatus(_))));
+		Ok(())
+	}
+
+	#[test]
+	fn test_renamed_references_are_reported_by_new_path() -> Result<(), anyhow::Error> {
+		let repo = TempDir::new()?;
+		let cite_dir = repo.path().join(".cite");
+		std::fs::create_dir_all(&cite_dir)?;
+		std::fs::write(cite_dir.join("old name.json"), "{}")?;
+		git(repo.path(), &["init", "-q"])?;
+		git(repo.path(), &["add", "-A"])?;
+		git(repo.path(), &["commit", "-qm", "Add references"])?;
+
+		git(repo.path(), &["mv", ".cite/old name.json", ".cite/new name.json"])?;
+		assert_eq!(git_status(&cite_dir)?, vec![".cite/new name.json".to_string()]);
+		assert_eq!(
+			parse_porcelain("R  .cite/b.json\0.cite/a.json\0?? .cite/c.json\0"),
+			vec![".cite/b.json".to_string(), ".cite/c.json".to_string()]
+		);
+		Ok(())
+	}
+}
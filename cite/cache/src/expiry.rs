@@ -0,0 +1,94 @@
+use crate::CacheError;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Extension of the files recording when a cache entry was written
+pub const ENTRY_META_EXTENSION: &str = "meta";
+
+/// Read when a cache entry was written, from its meta file or else from the modification time
+/// of the entry itself
+///
+/// Only entries cached with a time to live have meta files, keeping other caches free of them.
+/// Returns `None` if the entry does not exist.
+pub fn read_written_at(
+	entry_path: &Path,
+	meta_path: &Path,
+) -> Result<Option<SystemTime>, CacheError> {
+	if meta_path.exists() {
+		let content = std::fs::read_to_string(meta_path).map_err(CacheError::ReadCacheFile)?;
+		let millis: u64 = content.trim().parse().map_err(|e| {
+			CacheError::Deserialize(
+				format!("Invalid write time in {}: {}", meta_path.display(), e).into(),
+			)
+		})?;
+		return Ok(Some(SystemTime::UNIX_EPOCH + Duration::from_millis(millis)));
+	}
+	if !entry_path.exists() {
+		return Ok(None);
+	}
+
+	let metadata = std::fs::metadata(entry_path).map_err(CacheError::ReadCacheFile)?;
+	metadata.modified().map(Some).map_err(CacheError::ReadCacheFile)
+}
+
+/// Write a meta file atomically, recording the time as milliseconds since the Unix epoch
+pub fn write_written_at(meta_path: &Path, written_at: SystemTime) -> Result<(), CacheError> {
+	let millis = written_at
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_millis();
+	let temp_path = meta_path.with_extension(format!("tmp-{}", std::process::id()));
+	std::fs::write(&temp_path, format!("{}\n", millis)).map_err(CacheError::WriteCacheFile)?;
+	std::fs::rename(&temp_path, meta_path).map_err(CacheError::WriteCacheFile)
+}
+
+/// Check if an entry written at the given time is older than the time to live
+///
+/// An entry exactly as old as its time to live is still fresh. Entries written in the future,
+/// e.g. by a machine whose clock is ahead, are fresh as well.
+pub fn is_expired(written_at: SystemTime, ttl: Duration, now: SystemTime) -> bool {
+	now.duration_since(written_at).is_ok_and(|age| age > ttl)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	#[test]
+	fn test_read_and_write_written_at() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let entry_path = temp_dir.path().join("http_example");
+		let meta_path = temp_dir.path().join(format!("http_example.{}", ENTRY_META_EXTENSION));
+
+		assert_eq!(read_written_at(&entry_path, &meta_path)?, None);
+
+		// Without a meta file, the entry's modification time is used
+		std::fs::write(&entry_path, "cached")?;
+		let modified = std::fs::metadata(&entry_path)?.modified()?;
+		assert_eq!(read_written_at(&entry_path, &meta_path)?, Some(modified));
+
+		let written_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+		write_written_at(&meta_path, written_at)?;
+		assert_eq!(read_written_at(&entry_path, &meta_path)?, Some(written_at));
+		assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 2);
+
+		std::fs::write(&meta_path, "yesterday")?;
+		assert!(matches!(
+			read_written_at(&entry_path, &meta_path),
+			Err(CacheError::Deserialize(_))
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn test_is_expired() {
+		let written_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+		let ttl = Duration::from_secs(3600);
+
+		assert!(!is_expired(written_at, ttl, written_at + Duration::from_secs(60)));
+		assert!(!is_expired(written_at, ttl, written_at + ttl));
+		assert!(is_expired(written_at, ttl, written_at + ttl + Duration::from_millis(1)));
+		assert!(!is_expired(written_at, ttl, written_at - Duration::from_secs(60)));
+	}
+}
@@ -0,0 +1,204 @@
+use crate::CacheError;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// File name of the cache generation marker within the cite directory
+pub const CACHE_GENERATION_FILE: &str = "generation";
+
+/// Extension of the files recording the generation a cache entry was written in
+pub const ENTRY_GENERATION_EXTENSION: &str = "generation";
+
+/// Environment variable naming the build a process is part of, see [build_id]
+pub const BUILD_ID_ENV: &str = "CITE_BUILD_ID";
+
+/// How long the lock of the generation marker is waited for, and after which one is taken to be
+/// left behind by a crashed build
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Read a generation file; a missing file is generation 0
+///
+/// Caches that never saw a reset have no generation files at all, so their entries are all
+/// generation 0 and stay valid.
+pub fn read_generation(path: &Path) -> Result<u64, CacheError> {
+	read_marker(path).map(|(generation, _)| generation)
+}
+
+/// Read a generation file with the build that last bumped it, if it records one
+fn read_marker(path: &Path) -> Result<(u64, Option<String>), CacheError> {
+	if !path.exists() {
+		return Ok((0, None));
+	}
+
+	let content = std::fs::read_to_string(path).map_err(CacheError::ReadCacheFile)?;
+	let mut lines = content.lines();
+	let generation = lines.next().unwrap_or_default().trim().parse().map_err(|e| {
+		CacheError::Deserialize(
+			format!("Invalid cache generation in {}: {}", path.display(), e).into(),
+		)
+	})?;
+	let build = lines.next().map(str::trim).filter(|build| !build.is_empty());
+	Ok((generation, build.map(str::to_string)))
+}
+
+/// Write a generation file atomically, so concurrent readers never see a partial write
+pub fn write_generation(path: &Path, generation: u64) -> Result<(), CacheError> {
+	write_marker(path, generation, None)
+}
+
+fn write_marker(path: &Path, generation: u64, build: Option<&str>) -> Result<(), CacheError> {
+	let content = match build {
+		Some(build) => format!("{}\n{}\n", generation, build),
+		None => format!("{}\n", generation),
+	};
+	let temp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+	std::fs::write(&temp_path, content).map_err(CacheError::WriteCacheFile)?;
+	std::fs::rename(&temp_path, path).map_err(CacheError::WriteCacheFile)
+}
+
+/// Bump the generation of a marker, returning the new generation
+///
+/// The marker is read and written while holding a lock file next to it, so that concurrent
+/// bumps each count. Given a build, the generation is only bumped if that build did not already
+/// bump it, so that every compilation unit of a build may ask for the same reset.
+pub fn bump_generation(path: &Path, build: Option<&str>) -> Result<u64, CacheError> {
+	let _lock = MarkerLock::acquire(path)?;
+	let (generation, bumped_by) = read_marker(path)?;
+	if build.is_some() && bumped_by.as_deref() == build {
+		return Ok(generation);
+	}
+	write_marker(path, generation + 1, build)?;
+	Ok(generation + 1)
+}
+
+/// Identify the build this process is part of
+///
+/// Cargo compiles each unit of a build in a process of its own, so the units of one build share
+/// the id of their parent process. [BUILD_ID_ENV] overrides it, e.g. for builds through a
+/// compiler wrapper whose server outlives the build. Without either, each process is a build of
+/// its own.
+pub fn build_id() -> String {
+	std::env::var(BUILD_ID_ENV)
+		.ok()
+		.map(|build| build.trim().to_string())
+		.filter(|build| !build.is_empty())
+		.unwrap_or_else(process_build_id)
+}
+
+#[cfg(unix)]
+fn process_build_id() -> String {
+	format!("cargo-{}", std::os::unix::process::parent_id())
+}
+
+#[cfg(not(unix))]
+fn process_build_id() -> String {
+	format!("process-{}", std::process::id())
+}
+
+/// Lock held on a marker by creating a `.lock` file next to it, removed once dropped
+struct MarkerLock {
+	path: PathBuf,
+}
+
+impl MarkerLock {
+	fn acquire(path: &Path) -> Result<Self, CacheError> {
+		let mut lock_path = path.as_os_str().to_owned();
+		lock_path.push(".lock");
+		let lock_path = PathBuf::from(lock_path);
+
+		let started = Instant::now();
+		loop {
+			match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+				Ok(_) => return Ok(Self { path: lock_path }),
+				Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+				Err(e) => return Err(CacheError::WriteCacheFile(e)),
+			}
+
+			let stale = std::fs::metadata(&lock_path)
+				.and_then(|metadata| metadata.modified())
+				.is_ok_and(|modified| modified.elapsed().unwrap_or_default() > LOCK_TIMEOUT);
+			if stale {
+				let _ = std::fs::remove_file(&lock_path);
+			} else if started.elapsed() > LOCK_TIMEOUT {
+				return Err(CacheError::WriteCacheFile(std::io::Error::new(
+					ErrorKind::TimedOut,
+					format!("Timed out waiting for {}", lock_path.display()),
+				)));
+			} else {
+				std::thread::sleep(Duration::from_millis(10));
+			}
+		}
+	}
+}
+
+impl Drop for MarkerLock {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_file(&self.path);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	#[test]
+	fn test_read_and_write_generation() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let path = temp_dir.path().join(CACHE_GENERATION_FILE);
+
+		assert_eq!(read_generation(&path)?, 0);
+
+		write_generation(&path, 3)?;
+		assert_eq!(read_generation(&path)?, 3);
+		assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 1);
+
+		std::fs::write(&path, "three")?;
+		assert!(matches!(read_generation(&path), Err(CacheError::Deserialize(_))));
+		Ok(())
+	}
+
+	#[test]
+	fn test_concurrent_bumps_each_count() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let path = temp_dir.path().join(CACHE_GENERATION_FILE);
+
+		let threads = (0..8)
+			.map(|_| {
+				let path = path.clone();
+				std::thread::spawn(move || bump_generation(&path, None))
+			})
+			.collect::<Vec<_>>();
+		for thread in threads {
+			thread.join().expect("bumping thread panicked")?;
+		}
+
+		assert_eq!(read_generation(&path)?, 8);
+		assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn test_bump_once_per_build() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let path = temp_dir.path().join(CACHE_GENERATION_FILE);
+
+		// The units of a build all ask for the reset, in parallel
+		let threads = (0..8)
+			.map(|_| {
+				let path = path.clone();
+				std::thread::spawn(move || bump_generation(&path, Some("cargo-1")))
+			})
+			.collect::<Vec<_>>();
+		for thread in threads {
+			assert_eq!(thread.join().expect("bumping thread panicked")?, 1);
+		}
+
+		// A later build resets again
+		assert_eq!(bump_generation(&path, Some("cargo-2"))?, 2);
+		assert_eq!(bump_generation(&path, Some("cargo-2"))?, 2);
+		assert_eq!(bump_generation(&path, Some("cargo-1"))?, 3);
+		Ok(())
+	}
+}
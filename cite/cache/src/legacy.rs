@@ -0,0 +1,109 @@
+//! Cache errors of the legacy cite crates
+//!
+//! The legacy crates reported cache failures as string variants, e.g.
+//! `CacheError::ReadFailure(String)`. [LegacyCacheError] keeps those variants so that code
+//! matching on them still compiles, and converts to and from [CacheError] so that it can be
+//! migrated one call site at a time.
+#![allow(deprecated)]
+
+use crate::CacheError;
+use std::io;
+
+/// Cache error of the legacy cite crates
+#[deprecated(note = "Use CacheError instead; LegacyCacheError converts to and from it")]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LegacyCacheError {
+	#[error("Failed to read cache: {0}")]
+	ReadFailure(String),
+
+	#[error("Failed to write cache: {0}")]
+	WriteFailure(String),
+
+	#[error("Cache entry not found: {0}")]
+	NotFound(String),
+
+	#[error("Failed to delete cache entry: {0}")]
+	DeleteFailure(String),
+
+	#[error("Failed to serialize cache entry: {0}")]
+	SerializationFailure(String),
+
+	#[error("Failed to deserialize cache entry: {0}")]
+	DeserializationFailure(String),
+
+	#[error("Source error: {0}")]
+	SourceFailure(String),
+}
+
+impl From<LegacyCacheError> for CacheError {
+	fn from(error: LegacyCacheError) -> Self {
+		match error {
+			LegacyCacheError::ReadFailure(message) => {
+				CacheError::ReadCacheFile(io::Error::other(message))
+			}
+			LegacyCacheError::WriteFailure(message) => {
+				CacheError::WriteCacheFile(io::Error::other(message))
+			}
+			LegacyCacheError::NotFound(message) => {
+				CacheError::CacheFileNotFound(io::Error::new(io::ErrorKind::NotFound, message))
+			}
+			LegacyCacheError::DeleteFailure(message) => {
+				CacheError::DeleteCacheFile(io::Error::other(message))
+			}
+			LegacyCacheError::SerializationFailure(message) => {
+				CacheError::Serialize(message.into())
+			}
+			LegacyCacheError::DeserializationFailure(message) => {
+				CacheError::Deserialize(message.into())
+			}
+			LegacyCacheError::SourceFailure(message) => {
+				CacheError::SourceError(cite_core::SourceError::Cache(message))
+			}
+		}
+	}
+}
+
+impl From<CacheError> for LegacyCacheError {
+	/// Errors without a legacy counterpart, e.g. a failed round-trip, are read failures
+	fn from(error: CacheError) -> Self {
+		match error {
+			CacheError::ReadCacheFile(e) => LegacyCacheError::ReadFailure(e.to_string()),
+			CacheError::WriteCacheFile(e) => LegacyCacheError::WriteFailure(e.to_string()),
+			CacheError::CacheFileNotFound(e) => LegacyCacheError::NotFound(e.to_string()),
+			CacheError::DeleteCacheFile(e) => LegacyCacheError::DeleteFailure(e.to_string()),
+			CacheError::Serialize(e) => LegacyCacheError::SerializationFailure(e.to_string()),
+			CacheError::Deserialize(e) => LegacyCacheError::DeserializationFailure(e.to_string()),
+			CacheError::SourceError(e) => LegacyCacheError::SourceFailure(e.to_string()),
+			error => LegacyCacheError::ReadFailure(error.to_string()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_legacy_cache_error_round_trip() {
+		let legacy = LegacyCacheError::ReadFailure("permission denied".to_string());
+		let error = CacheError::from(legacy.clone());
+		assert!(
+			matches!(&error, CacheError::ReadCacheFile(e) if e.to_string() == "permission denied")
+		);
+		assert_eq!(LegacyCacheError::from(error), legacy);
+
+		let not_found = CacheError::from(LegacyCacheError::NotFound("abc".to_string()));
+		assert!(
+			matches!(&not_found, CacheError::CacheFileNotFound(e) if e.kind() == io::ErrorKind::NotFound)
+		);
+
+		let deserialize = LegacyCacheError::DeserializationFailure("invalid json".to_string());
+		assert_eq!(LegacyCacheError::from(CacheError::from(deserialize.clone())), deserialize);
+
+		// Errors the legacy crates did not have keep their message
+		let git_status = LegacyCacheError::from(CacheError::GitStatus("abc".to_string()));
+		assert!(
+			matches!(git_status, LegacyCacheError::ReadFailure(message) if message.contains("abc"))
+		);
+	}
+}
@@ -1,7 +1,24 @@
+pub mod citations;
+pub mod committed;
+pub mod expiry;
+pub mod generation;
+pub mod legacy;
+pub mod manifest;
+mod memory;
+
+pub use citations::{CitationRecord, CitationRegistry, CITATION_REGISTRY_DIR};
+pub use expiry::ENTRY_META_EXTENSION;
+pub use generation::{build_id, BUILD_ID_ENV, CACHE_GENERATION_FILE, ENTRY_GENERATION_EXTENSION};
+pub use manifest::{ChecksumManifest, ManifestCheck, CHECKSUM_MANIFEST_FILE};
+
 use cite_core::id::Id;
-use cite_core::{Comparison, Current, Diff, Referenced, Source, SourceError};
+use cite_core::{
+	Comparison, Content, Current, Diff, EmptyReferencePolicy, Referenced, Source, SourceError,
+};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 /// A cachable reference is serializable and deserializable
 ///
@@ -24,15 +41,40 @@ pub enum CacheBuilderError {
 	CreateCacheDir(#[source] std::io::Error),
 }
 
+/// Environment variable enabling (`1`) or disabling (`0`) the cache round-trip self-check
+///
+/// Defaults to disabled, see [CacheBuilder::with_self_check].
+pub const CACHE_SELF_CHECK_ENV: &str = "CITE_CACHE_SELF_CHECK";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheBuilder {
 	pub cite_dir: PathBuf,
 	pub cache_subdir: PathBuf,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub self_check: Option<bool>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub empty_reference: Option<EmptyReferencePolicy>,
 }
 
 impl CacheBuilder {
 	pub fn new(cite_dir: PathBuf, cache_subdir: PathBuf) -> Self {
-		Self { cite_dir, cache_subdir }
+		Self { cite_dir, cache_subdir, self_check: None, empty_reference: None }
+	}
+
+	/// Read each entry back right after writing it, warning if it does not round-trip
+	///
+	/// Overrides [CACHE_SELF_CHECK_ENV].
+	pub fn with_self_check(mut self, self_check: bool) -> Self {
+		self.self_check = Some(self_check);
+		self
+	}
+
+	/// Set how a reference that is first captured empty or whitespace-only is treated
+	///
+	/// Overrides `CITE_EMPTY_REFERENCE`.
+	pub fn with_empty_reference(mut self, policy: EmptyReferencePolicy) -> Self {
+		self.empty_reference = Some(policy);
+		self
 	}
 
 	pub fn build(&self) -> Result<Cache, CacheBuilderError> {
@@ -49,7 +91,7 @@ impl CacheBuilder {
 			Ok(metadata) => {
 				let cite_dir = metadata.workspace_root.join(".cite").into();
 				let cache_subdir = metadata.workspace_root.join("cache").into();
-				Ok(Self { cite_dir, cache_subdir })
+				Ok(Self::new(cite_dir, cache_subdir))
 			}
 			Err(_) => Ok(Self::default()),
 		}
@@ -85,6 +127,17 @@ pub enum CacheError {
 
 	#[error("Source error: {0}")]
 	SourceError(#[source] SourceError),
+
+	#[error("Failed to compare the cite directory against the committed state: {0}")]
+	GitStatus(String),
+
+	#[error(
+		"The reference of {0} was captured empty, so the citation would validate nothing. Check that its selector or pattern matches the cited content"
+	)]
+	EmptyReference(String),
+
+	#[error("{0}")]
+	EmptyReferencePolicy(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +149,9 @@ pub struct Cache {
 pub enum CacheBehavior {
 	Enabled,
 	Ignored,
+	/// Like [CacheBehavior::Enabled], but entries older than the time to live are misses, so that
+	/// the reference is fetched and cached again
+	Ttl(Duration),
 }
 
 impl Cache {
@@ -111,6 +167,191 @@ impl Cache {
 		self.builder.cite_dir.join(self.builder.cache_subdir.clone())
 	}
 
+	/// The registry of the citations expanded in the workspace
+	pub fn citations(&self) -> CitationRegistry {
+		CitationRegistry::new(self.cite_dir().join(CITATION_REGISTRY_DIR))
+	}
+
+	/// Path of the committed checksum manifest
+	pub fn manifest_path(&self) -> PathBuf {
+		self.builder.cite_dir.join(CHECKSUM_MANIFEST_FILE)
+	}
+
+	/// Read the committed checksum manifest, which is empty if the file does not exist
+	pub fn manifest(&self) -> Result<ChecksumManifest, CacheError> {
+		ChecksumManifest::read(&self.manifest_path())
+	}
+
+	/// Write the committed checksum manifest
+	pub fn write_manifest(&self, manifest: &ChecksumManifest) -> Result<(), CacheError> {
+		manifest.write(&self.manifest_path())?;
+		committed::forget(&self.builder.cite_dir);
+		Ok(())
+	}
+
+	/// List the files of the cite directory that differ from what is committed in git
+	///
+	/// See [committed::uncommitted_paths].
+	pub fn uncommitted_references(&self) -> Result<Vec<String>, CacheError> {
+		committed::uncommitted_paths(&self.builder.cite_dir)
+	}
+
+	/// Path of the workspace-wide cache generation marker
+	pub fn generation_path(&self) -> PathBuf {
+		self.builder.cite_dir.join(CACHE_GENERATION_FILE)
+	}
+
+	/// Get the current cache generation, which is 0 until the cache is first reset
+	pub fn generation(&self) -> Result<u64, CacheError> {
+		generation::read_generation(&self.generation_path())
+	}
+
+	/// Reset the cache for the whole workspace by bumping the generation
+	///
+	/// Entries written in older generations are treated as misses by
+	/// [Cache::get_source_with_cache], so every compilation unit sees the reset at once
+	/// rather than each unit overwriting its own entries. The generation is bumped under a lock,
+	/// so that concurrent resets each count.
+	pub fn bump_generation(&self) -> Result<u64, CacheError> {
+		generation::bump_generation(&self.generation_path(), None)
+	}
+
+	/// Reset the cache once for the given build, see [generation::build_id]
+	///
+	/// Every compilation unit of a build asks for the reset, but only the first one bumps the
+	/// generation, so that the entries the others write during the build stay valid.
+	pub fn reset_for_build(&self, build: &str) -> Result<u64, CacheError> {
+		generation::bump_generation(&self.generation_path(), Some(build))
+	}
+
+	/// Get the generation a cache entry was written in
+	pub fn entry_generation(&self, id: &Id) -> Result<u64, CacheError> {
+		generation::read_generation(&self.entry_generation_path(id))
+	}
+
+	/// Check if a cache entry was written before the latest reset
+	pub fn is_stale(&self, id: &Id) -> Result<bool, CacheError> {
+		Ok(self.entry_generation(id)? < self.generation()?)
+	}
+
+	/// Get when a cache entry was written, or `None` if there is no entry
+	///
+	/// Entries cached with [CacheBehavior::Ttl] record the time in a meta file; for others, the
+	/// modification time of the entry is used.
+	pub fn entry_written_at(&self, id: &Id) -> Result<Option<SystemTime>, CacheError> {
+		expiry::read_written_at(&self.cache_dir().join(id.as_str()), &self.entry_meta_path(id))
+	}
+
+	/// Check if a cache entry is older than the time to live
+	pub fn is_expired(&self, id: &Id, ttl: Duration) -> Result<bool, CacheError> {
+		self.is_expired_at(id, ttl, SystemTime::now())
+	}
+
+	/// Check if a cache entry is older than the time to live at the given time
+	///
+	/// An entry exactly as old as the time to live is still fresh, and a missing one is not
+	/// expired.
+	pub fn is_expired_at(
+		&self,
+		id: &Id,
+		ttl: Duration,
+		now: SystemTime,
+	) -> Result<bool, CacheError> {
+		Ok(self
+			.entry_written_at(id)?
+			.is_some_and(|written_at| expiry::is_expired(written_at, ttl, now)))
+	}
+
+	/// Check if the entry of a source is used with the given behavior, if there is one
+	///
+	/// Entries are not used when the cache is ignored, nor once written before the latest reset
+	/// or, with [CacheBehavior::Ttl], once older than the time to live.
+	pub fn is_usable(&self, id: &Id, behavior: &CacheBehavior) -> Result<bool, CacheError> {
+		let expired = match behavior {
+			CacheBehavior::Ignored => return Ok(false),
+			CacheBehavior::Enabled => false,
+			CacheBehavior::Ttl(ttl) => self.is_expired(id, *ttl)?,
+		};
+		Ok(!expired && !self.is_stale(id)?)
+	}
+
+	/// Check if new entries are read back and compared right after being written
+	pub fn self_check(&self) -> bool {
+		self.builder.self_check.unwrap_or_else(|| {
+			match std::env::var(CACHE_SELF_CHECK_ENV).as_deref().map(str::trim) {
+				Ok("1") | Ok("true") => true,
+				_ => false,
+			}
+		})
+	}
+
+	/// Get how a reference that is first captured empty or whitespace-only is treated
+	pub fn empty_reference(&self) -> Result<EmptyReferencePolicy, CacheError> {
+		match self.builder.empty_reference {
+			Some(policy) => Ok(policy),
+			None => EmptyReferencePolicy::from_env().map_err(CacheError::EmptyReferencePolicy),
+		}
+	}
+
+	/// Check the first capture of a reference before it is cached
+	///
+	/// An empty capture fails with [CacheError::EmptyReference] under
+	/// [EmptyReferencePolicy::Error], and returns the warning to report otherwise.
+	fn check_capture<C: Content>(
+		&self,
+		id: &Id,
+		current: &C,
+	) -> Result<Option<String>, CacheError> {
+		if !current.is_blank() {
+			return Ok(None);
+		}
+		cite_core::trace_event!("empty reference captured");
+		match self.empty_reference()? {
+			EmptyReferencePolicy::Error => Err(CacheError::EmptyReference(id.as_str().to_string())),
+			EmptyReferencePolicy::Warn => Ok(Some(format!(
+				"Warning: the reference of {} was captured empty, so the citation validates nothing. Check that its selector or pattern matches the cited content, then remove its cache entry: {}",
+				id.as_str(),
+				self.cache_dir().join(id.as_str()).display()
+			))),
+		}
+	}
+
+	/// Write a cache entry, reading it back if the self-check is enabled
+	///
+	/// The cache stores a [CacheableCurrent] and reads it back as a [CacheableReferenced], so a
+	/// source whose formats diverge would otherwise report drift on every comparison. Returns the
+	/// warning to report if the entry does not round-trip.
+	fn populate<R: CacheableReferenced, C: CacheableCurrent<R, D>, D: Diff>(
+		&self,
+		id: &Id,
+		value: &C,
+	) -> Result<Option<String>, CacheError> {
+		self.set(id, value)?;
+		if !self.self_check() {
+			return Ok(None);
+		}
+
+		let round_trips = match self.get::<R>(id)? {
+			Some(read_back) => value.diff(&read_back).map_err(CacheError::SourceError)?.is_empty(),
+			None => false,
+		};
+		cite_core::trace_event!(round_trips, "cache self-check");
+		Ok((!round_trips).then(|| {
+			format!(
+				"Warning: the cache entry of {} does not round-trip: the entry read back from the cache differs from the content written, so the citation will always report drift. Check that to_cached_buffer and from_cached_buffer use the same format",
+				id.as_str()
+			)
+		}))
+	}
+
+	fn entry_generation_path(&self, id: &Id) -> PathBuf {
+		self.cache_dir().join(format!("{}.{}", id.as_str(), ENTRY_GENERATION_EXTENSION))
+	}
+
+	fn entry_meta_path(&self, id: &Id) -> PathBuf {
+		self.cache_dir().join(format!("{}.{}", id.as_str(), ENTRY_META_EXTENSION))
+	}
+
 	pub fn get<R: CacheableReferenced>(&self, id: &Id) -> Result<Option<R>, CacheError> {
 		let cache_file = self.cache_dir().join(id.as_str());
 		if !cache_file.exists() {
@@ -122,6 +363,37 @@ impl Cache {
 		Ok(Some(cached_entry))
 	}
 
+	/// Get a cache entry, reading it from disk only the first time it is requested in this
+	/// process
+	///
+	/// Entries written in this process are memoized as well, see [Cache::set].
+	pub fn get_memoized<R: CacheableReferenced>(&self, id: &Id) -> Result<Option<R>, CacheError> {
+		let cache_file = self.cache_dir().join(id.as_str());
+		if let Some(cache_buffer) = memory::recall(&cache_file) {
+			return R::from_cached_buffer(cache_buffer).map(Some);
+		}
+		if !cache_file.exists() {
+			return Ok(None);
+		}
+		let cache_buffer = std::fs::read(&cache_file).map_err(CacheError::ReadCacheFile)?;
+		memory::remember(&cache_file, &cache_buffer);
+		R::from_cached_buffer(cache_buffer).map(Some)
+	}
+
+	/// Write a cache entry, recording when it was written if cached with [CacheBehavior::Ttl]
+	pub fn set_with_behavior<R: CacheableReferenced, C: CacheableCurrent<R, D>, D: Diff>(
+		&self,
+		id: &Id,
+		value: &C,
+		behavior: &CacheBehavior,
+	) -> Result<(), CacheError> {
+		self.set(id, value)?;
+		if let CacheBehavior::Ttl(_) = behavior {
+			expiry::write_written_at(&self.entry_meta_path(id), SystemTime::now())?;
+		}
+		Ok(())
+	}
+
 	pub fn set<R: CacheableReferenced, C: CacheableCurrent<R, D>, D: Diff>(
 		&self,
 		id: &Id,
@@ -129,7 +401,22 @@ impl Cache {
 	) -> Result<(), CacheError> {
 		let cache_file = self.cache_dir().join(id.as_str());
 		let cache_buffer = value.to_cached_buffer()?;
-		std::fs::write(&cache_file, cache_buffer).map_err(CacheError::WriteCacheFile)?;
+		std::fs::write(&cache_file, &cache_buffer).map_err(CacheError::WriteCacheFile)?;
+		memory::remember(&cache_file, &cache_buffer);
+		committed::forget(&self.builder.cite_dir);
+
+		// Record the generation only once the cache has been reset, keeping reset-free caches
+		// free of generation files
+		let generation = self.generation()?;
+		if generation > 0 {
+			generation::write_generation(&self.entry_generation_path(id), generation)?;
+		}
+
+		// An entry recording when it was written must not keep the time of the entry it replaces
+		let meta_file = self.entry_meta_path(id);
+		if meta_file.exists() {
+			expiry::write_written_at(&meta_file, SystemTime::now())?;
+		}
 		Ok(())
 	}
 
@@ -142,18 +429,131 @@ impl Cache {
 			)));
 		}
 		std::fs::remove_file(&cache_file).map_err(CacheError::DeleteCacheFile)?;
+		memory::forget(&cache_file);
+
+		for sidecar_file in [self.entry_generation_path(id), self.entry_meta_path(id)] {
+			if sidecar_file.exists() {
+				std::fs::remove_file(&sidecar_file).map_err(CacheError::DeleteCacheFile)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Delete every file under the cache directory, returning how many were deleted
+	///
+	/// The count includes the generation and meta files of entries. An empty or missing cache
+	/// directory is cleared without error.
+	pub fn clear_all(&self) -> Result<usize, CacheError> {
+		self.clear_prefix("")
+	}
+
+	/// Delete the entries whose id begins with the prefix, e.g. `http_` or `git_`, returning how
+	/// many files were deleted
+	///
+	/// The generation and meta files of the entries begin with the prefix as well, so they are
+	/// deleted and counted with them. Files removed while clearing, e.g. by a parallel build, are
+	/// skipped rather than failing the whole clear. Subdirectories of nested ids left empty are
+	/// removed as well.
+	pub fn clear_prefix(&self, prefix: &str) -> Result<usize, CacheError> {
+		let cache_dir = self.cache_dir();
+		let mut cleared = 0;
+		let mut cleared_dirs = BTreeSet::new();
+		for cache_file in self.cache_files()? {
+			let matches = Self::file_id(&cache_dir, &cache_file)
+				.is_some_and(|file_id| file_id.starts_with(prefix));
+			if matches && Self::remove_cache_file(&cache_file)? {
+				cleared += 1;
+				cleared_dirs.extend(cache_file.parent().map(Path::to_path_buf));
+			}
+		}
+		for dir in cleared_dirs {
+			Self::remove_empty_dirs(&cache_dir, &dir)?;
+		}
+		Ok(cleared)
+	}
+
+	/// Remove a subdirectory of the cache directory and its parents, as long as they are empty
+	fn remove_empty_dirs(cache_dir: &Path, dir: &Path) -> Result<(), CacheError> {
+		let dirs = dir
+			.ancestors()
+			.take_while(|dir| *dir != cache_dir && dir.starts_with(cache_dir));
+		for dir in dirs {
+			let empty = std::fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none());
+			if !empty {
+				break;
+			}
+			match std::fs::remove_dir(dir) {
+				Ok(()) => {}
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+				Err(e) => return Err(CacheError::DeleteCacheFile(e)),
+			}
+		}
 		Ok(())
 	}
 
+	/// Paths of the files under the cache directory, including those of ids cached in
+	/// subdirectories, e.g. ids containing the `/` of a URL
+	fn cache_files(&self) -> Result<Vec<PathBuf>, CacheError> {
+		let mut cache_files = Vec::new();
+		let mut dirs = vec![self.cache_dir()];
+		while let Some(dir) = dirs.pop() {
+			let entries = match std::fs::read_dir(&dir) {
+				Ok(entries) => entries,
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+				Err(e) => return Err(CacheError::ReadCacheFile(e)),
+			};
+			for entry in entries {
+				let path = entry.map_err(CacheError::ReadCacheFile)?.path();
+				if path.is_dir() {
+					dirs.push(path);
+				} else {
+					cache_files.push(path);
+				}
+			}
+		}
+		Ok(cache_files)
+	}
+
+	/// Get the id a file under the cache directory belongs to, with the suffix of sidecar files
+	///
+	/// Ids are joined with `/` whatever the path separator of the platform, so that an id
+	/// cached in a subdirectory matches its prefix on Windows as well.
+	fn file_id(cache_dir: &Path, cache_file: &Path) -> Option<String> {
+		let relative = cache_file.strip_prefix(cache_dir).ok()?;
+		let components = relative
+			.components()
+			.map(|component| component.as_os_str().to_string_lossy())
+			.collect::<Vec<_>>();
+		Some(components.join("/"))
+	}
+
+	/// Remove a cache file and its memoized buffer, returning whether there was a file to remove
+	fn remove_cache_file(cache_file: &Path) -> Result<bool, CacheError> {
+		memory::forget(cache_file);
+		match std::fs::remove_file(cache_file) {
+			Ok(()) => Ok(true),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+			Err(e) => Err(CacheError::DeleteCacheFile(e)),
+		}
+	}
+
 	/// Get a source with cache.
 	///
 	/// If the cache is ignored, the source is fetched via [Source::get] and the cache is filled with the current value.
 	///
-	/// If the cache is enabled, we first check if the source is in the cache.
-	/// If it is, we use the cached value.
+	/// If the cache is enabled, we first check if the source is in the cache, which is read from
+	/// disk once per process (see [Cache::get_memoized]).
+	/// If it is, we use the cached value, unless it was written before the latest generation
+	/// bump (see [Cache::bump_generation]), in which case it counts as a miss.
+	/// With [CacheBehavior::Ttl], an entry older than the time to live counts as a miss as well,
+	/// and the entry replacing it records when it was written (see [Cache::entry_written_at]).
 	/// If it is not, we fetch the source via [Source::get_referenced] and [Source::get_current] and fill the cache with the current value.
 	///
 	/// Note: this caching discprenacy between referenced and current means that a source that does not have a reference and current implementation that serialize to the same thing for the same content may always return a diff.
+	/// With the self-check enabled (see [Cache::self_check]), such a source is reported with a warning note on the comparison when the cache is populated.
+	///
+	/// A reference captured empty or whitespace-only is reported with a note on the comparison,
+	/// or fails with [CacheError::EmptyReference], following [Cache::empty_reference].
 	pub fn get_source_with_cache<
 		S: Source<R, C, D>,
 		R: CacheableReferenced,
@@ -164,31 +564,83 @@ impl Cache {
 		source: &S,
 		behavior: CacheBehavior,
 	) -> Result<Comparison<R, C, D>, CacheError> {
+		let _span = cite_core::trace_span!("citation", id = source.id().as_str());
 		match behavior {
 			CacheBehavior::Ignored => {
+				cite_core::trace_event!("cache ignored");
 				let comparison = source.get().map_err(CacheError::SourceError)?;
-				self.set(source.id(), comparison.current())?;
-				Ok(comparison)
+				let note = self.populate(source.id(), comparison.current())?;
+				cite_core::trace_event!(changed = !comparison.diff().is_empty(), "compared");
+				Ok(match note {
+					Some(note) => comparison.with_note(note),
+					None => comparison,
+				})
 			}
-			CacheBehavior::Enabled => {
-				let (referenced, current) = match self.get::<R>(source.id())? {
-					Some(referenced) => {
-						(referenced, source.get_current().map_err(CacheError::SourceError)?)
-					}
+			CacheBehavior::Enabled | CacheBehavior::Ttl(_) => {
+				let expired = match &behavior {
+					CacheBehavior::Ttl(ttl) => self.is_expired(source.id(), *ttl)?,
+					_ => false,
+				};
+				if expired {
+					cite_core::trace_event!("cache entry expired");
+				}
+				let cached = if expired || self.is_stale(source.id())? {
+					None
+				} else {
+					self.get_memoized::<R>(source.id())?
+				};
+				cite_core::trace_event!(hit = cached.is_some(), "cache lookup");
+				let (referenced, current, notes) = match cached {
+					Some(referenced) => (
+						referenced,
+						source.get_current().map_err(CacheError::SourceError)?,
+						Vec::new(),
+					),
 					None => {
 						let referenced =
 							source.get_referenced().map_err(CacheError::SourceError)?;
 						let current = source.get_current().map_err(CacheError::SourceError)?;
-						self.set(source.id(), &current)?;
-						(referenced, current)
+						let capture_note = self.check_capture(source.id(), &current)?;
+						let round_trip_note = self.populate(source.id(), &current)?;
+						if let CacheBehavior::Ttl(_) = behavior {
+							expiry::write_written_at(
+								&self.entry_meta_path(source.id()),
+								SystemTime::now(),
+							)?;
+						}
+						(
+							referenced,
+							current,
+							capture_note.into_iter().chain(round_trip_note).collect(),
+						)
 					}
 				};
 				let diff = current.diff(&referenced).map_err(CacheError::SourceError)?;
-				let comparison = Comparison::new(referenced, current, diff);
-				Ok(comparison)
+				cite_core::trace_event!(changed = !diff.is_empty(), "compared");
+				Ok(notes
+					.into_iter()
+					.fold(Comparison::new(referenced, current, diff), Comparison::with_note))
 			}
 		}
 	}
+
+	/// Accept the current content of a source as its new reference
+	///
+	/// Fetches the source via [Source::get_current] and overwrites its cache entry, so that the
+	/// next [Cache::get_source_with_cache] compares against the accepted content.
+	pub fn refresh<
+		S: Source<R, C, D>,
+		R: CacheableReferenced,
+		C: CacheableCurrent<R, D>,
+		D: Diff,
+	>(
+		&self,
+		source: &S,
+	) -> Result<C, CacheError> {
+		let current = source.get_current().map_err(CacheError::SourceError)?;
+		self.set(source.id(), &current)?;
+		Ok(current)
+	}
 }
 
 #[cfg(test)]
@@ -197,6 +649,7 @@ mod tests {
 	use cite_core::Content;
 	use serde::{Deserialize, Serialize};
 	use tempfile::TempDir;
+	use tracing_test::traced_test;
 
 	// Test implementations for CacheableReferenced and CacheableCurrent
 	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -219,7 +672,11 @@ mod tests {
 		content: String,
 	}
 
-	impl Content for TestCurrent {}
+	impl Content for TestCurrent {
+		fn is_blank(&self) -> bool {
+			self.content.trim().is_empty()
+		}
+	}
 
 	impl Current<TestReferenced, TestDiff> for TestCurrent {
 		fn diff(&self, referenced: &TestReferenced) -> Result<TestDiff, SourceError> {
@@ -335,6 +792,115 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_clear_prefix_and_clear_all() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache =
+			CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache")).build()?;
+		assert_eq!(cache.clear_all()?, 0);
+
+		for id in ["http_spec", "http_errors", "git_readme"] {
+			cache.set(&Id::new(id.to_string()), &TestCurrent { content: id.to_string() })?;
+		}
+		let http_spec = Id::new("http_spec".to_string());
+		std::fs::write(cache.cache_dir().join(format!("http_spec.{}", ENTRY_META_EXTENSION)), "0")?;
+		assert!(cache.get_memoized::<TestReferenced>(&http_spec)?.is_some());
+
+		// Entries are cleared with their sidecar files, and other sources are kept
+		assert_eq!(cache.clear_prefix("http_")?, 3);
+		assert!(cache.get_memoized::<TestReferenced>(&http_spec)?.is_none());
+		assert!(cache.get::<TestReferenced>(&Id::new("git_readme".to_string()))?.is_some());
+		assert_eq!(cache.clear_prefix("http_")?, 0);
+
+		// Ids containing a path separator are cached in subdirectories
+		std::fs::create_dir(cache.cache_dir().join("git_example.com"))?;
+		let nested = Id::new("git_example.com/repo".to_string());
+		cache.set(&nested, &TestCurrent { content: "nested".to_string() })?;
+		assert_eq!(cache.clear_prefix("git_example.com/")?, 1);
+		assert!(!cache.cache_dir().join("git_example.com").exists());
+		std::fs::create_dir(cache.cache_dir().join("git_example.com"))?;
+		cache.set(&nested, &TestCurrent { content: "nested".to_string() })?;
+
+		// Prefixes reach into nested directories, and are matched by id rather than by path
+		std::fs::create_dir_all(cache.cache_dir().join("git_example.com").join("group"))?;
+		let deeper = Id::new("git_example.com/group/repo".to_string());
+		cache.set(&deeper, &TestCurrent { content: "deeper".to_string() })?;
+		assert_eq!(
+			Cache::file_id(
+				&cache.cache_dir(),
+				&cache.cache_dir().join("git_example.com").join("group").join("repo")
+			),
+			Some(deeper.as_str().to_string())
+		);
+		assert_eq!(cache.clear_prefix("git_example.com/group/")?, 1);
+		assert!(cache.get::<TestReferenced>(&deeper)?.is_none());
+		assert!(cache.get::<TestReferenced>(&nested)?.is_some());
+
+		// Emptied subdirectories are removed, while those with entries left are kept
+		assert!(!cache.cache_dir().join("git_example.com").join("group").exists());
+		std::fs::create_dir(cache.cache_dir().join("git_example.com").join("group"))?;
+		cache.set(&deeper, &TestCurrent { content: "deeper".to_string() })?;
+		assert_eq!(cache.clear_prefix("git_example.com/gr")?, 1);
+		std::fs::create_dir(cache.cache_dir().join("git_example.com").join("group"))?;
+		cache.set(&deeper, &TestCurrent { content: "deeper".to_string() })?;
+		assert_eq!(cache.clear_all()?, 3);
+		assert_eq!(cache.clear_all()?, 0);
+		assert!(!cache.cache_dir().join("git_example.com").exists());
+		assert!(cache.cache_dir().exists());
+
+		// A missing cache directory has nothing to clear
+		std::fs::remove_dir_all(cache.cache_dir())?;
+		assert_eq!(cache.clear_all()?, 0);
+		assert_eq!(cache.clear_prefix("git_")?, 0);
+		Ok(())
+	}
+
+	#[test]
+	fn test_repeated_gets_read_disk_once() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache =
+			CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache")).build()?;
+		let id = Id::new("memoized".to_string());
+		let cache_file = cache.cache_dir().join(id.as_str());
+
+		// A reference committed by an earlier build
+		std::fs::write(&cache_file, "committed content")?;
+		let source = TestSource {
+			id: id.clone(),
+			referenced: TestReferenced { content: "ref content".to_string() },
+			current: TestCurrent { content: "committed content".to_string() },
+		};
+		assert!(cache.get_source_with_cache(&source, CacheBehavior::Enabled)?.is_same());
+
+		// Later citations of the same id do not read the file again
+		std::fs::write(&cache_file, "changed on disk")?;
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert_eq!(result.referenced().content, "committed content");
+		assert_eq!(cache.get::<TestReferenced>(&id)?.unwrap().content, "changed on disk");
+
+		// Writes update both layers, and deletes clear both
+		cache.set(&id, &TestCurrent { content: "written content".to_string() })?;
+		let memoized = cache.get_memoized::<TestReferenced>(&id)?.unwrap();
+		assert_eq!(memoized.content, "written content");
+		cache.delete(&id)?;
+		assert!(cache.get_memoized::<TestReferenced>(&id)?.is_none());
+		Ok(())
+	}
+
+	#[test]
+	fn test_cache_manifest() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache =
+			CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache")).build()?;
+		let id = Id::new("test_id".to_string());
+
+		assert_eq!(cache.manifest()?.digest(&id), None);
+
+		std::fs::write(cache.manifest_path(), "abcd  test_id\n")?;
+		assert_eq!(cache.manifest()?.check(&id, "abcd"), ManifestCheck::Matches);
+		Ok(())
+	}
+
 	#[test]
 	fn test_cacheable_referenced_serialization() -> Result<(), anyhow::Error> {
 		let referenced = TestReferenced { content: "test content".to_string() };
@@ -429,6 +995,318 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_generation_bump_invalidates_prior_entries() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let builder = CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache"));
+		let cache = builder.build()?;
+
+		let id = Id::new("test-source".to_string());
+		cache.set(&id, &TestCurrent { content: "cached content".to_string() })?;
+
+		let source = TestSource {
+			id: id.clone(),
+			referenced: TestReferenced { content: "ref content".to_string() },
+			current: TestCurrent { content: "current content".to_string() },
+		};
+
+		// Before any reset the entry is generation 0 and is used
+		assert_eq!(cache.generation()?, 0);
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert_eq!(result.referenced().content, "cached content");
+
+		// Another compilation unit bumps the generation, so the entry is now a miss
+		let other_unit = builder.build()?;
+		assert_eq!(other_unit.bump_generation()?, 1);
+		assert!(cache.is_stale(&id)?);
+
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert_eq!(result.referenced().content, "ref content");
+
+		// The miss repopulated the entry in the new generation, so it is used again
+		assert_eq!(cache.entry_generation(&id)?, 1);
+		let source = TestSource {
+			referenced: TestReferenced { content: "other ref content".to_string() },
+			..source
+		};
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert_eq!(result.referenced().content, "current content");
+
+		cache.delete(&id)?;
+		assert_eq!(cache.entry_generation(&id)?, 0);
+		Ok(())
+	}
+
+	#[test]
+	fn test_reset_for_build_keeps_entries_of_the_build() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let builder = CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache"));
+		let (first_unit, second_unit) = (builder.build()?, builder.build()?);
+
+		let id = Id::new("shared-source".to_string());
+		first_unit.set(&id, &TestCurrent { content: "previous build".to_string() })?;
+
+		// The first unit to reset invalidates the entries of earlier builds
+		assert_eq!(first_unit.reset_for_build("cargo-1")?, 1);
+		assert!(first_unit.is_stale(&id)?);
+		first_unit.set(&id, &TestCurrent { content: "this build".to_string() })?;
+
+		// Later units of the same build keep what the build wrote
+		assert_eq!(second_unit.reset_for_build("cargo-1")?, 1);
+		assert!(!second_unit.is_stale(&id)?);
+		assert_eq!(second_unit.get::<TestReferenced>(&id)?.unwrap().content, "this build");
+
+		assert_eq!(second_unit.reset_for_build("cargo-2")?, 2);
+		assert!(second_unit.is_stale(&id)?);
+		Ok(())
+	}
+
+	#[test]
+	fn test_ttl_expires_old_entries() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache =
+			CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache")).build()?;
+		let id = Id::new("ttl-source".to_string());
+		let meta_path = cache.cache_dir().join(format!("ttl-source.{}", ENTRY_META_EXTENSION));
+		let ttl = Duration::from_secs(3600);
+		let source = |content: &str| TestSource {
+			id: id.clone(),
+			referenced: TestReferenced { content: content.to_string() },
+			current: TestCurrent { content: content.to_string() },
+		};
+
+		// The first validation caches the reference, recording when it was written
+		assert!(cache.get_source_with_cache(&source("v1"), CacheBehavior::Ttl(ttl))?.is_same());
+		assert!(meta_path.exists());
+		let written_at = cache.entry_written_at(&id)?.expect("Should record the write time");
+
+		// A fresh entry is the reference
+		let comparison = cache.get_source_with_cache(&source("v2"), CacheBehavior::Ttl(ttl))?;
+		assert_eq!(comparison.referenced().content, "v1");
+		assert!(!comparison.is_same());
+
+		// An entry exactly as old as the time to live is still fresh
+		assert!(!cache.is_expired_at(&id, ttl, written_at + ttl)?);
+		assert!(cache.is_expired_at(&id, ttl, written_at + ttl + Duration::from_millis(1))?);
+
+		// An expired entry is a miss, so the reference is fetched and cached again
+		expiry::write_written_at(&meta_path, SystemTime::now() - ttl - Duration::from_secs(1))?;
+		assert!(cache.is_expired(&id, ttl)?);
+		let comparison = cache.get_source_with_cache(&source("v2"), CacheBehavior::Ttl(ttl))?;
+		assert_eq!(comparison.referenced().content, "v2");
+		assert!(comparison.is_same());
+		assert_eq!(cache.get::<TestReferenced>(&id)?.unwrap().content, "v2");
+		assert!(!cache.is_expired(&id, ttl)?);
+
+		// Without a time to live, the age of an entry does not matter
+		expiry::write_written_at(&meta_path, SystemTime::UNIX_EPOCH)?;
+		let comparison = cache.get_source_with_cache(&source("v3"), CacheBehavior::Enabled)?;
+		assert_eq!(comparison.referenced().content, "v2");
+		assert!(cache.is_usable(&id, &CacheBehavior::Enabled)?);
+		assert!(!cache.is_usable(&id, &CacheBehavior::Ttl(ttl))?);
+		assert!(!cache.is_usable(&id, &CacheBehavior::Ignored)?);
+
+		// Entries written outside of the cache lookup record their time as well
+		cache.set_with_behavior(
+			&id,
+			&TestCurrent { content: "v3".to_string() },
+			&CacheBehavior::Ttl(ttl),
+		)?;
+		assert!(cache.is_usable(&id, &CacheBehavior::Ttl(ttl))?);
+
+		cache.delete(&id)?;
+		assert!(!meta_path.exists());
+		assert_eq!(cache.entry_written_at(&id)?, None);
+		Ok(())
+	}
+
+	#[test]
+	#[traced_test]
+	fn test_validation_run_is_traced() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache =
+			CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache")).build()?;
+		let source = TestSource {
+			id: Id::new("traced-source".to_string()),
+			referenced: TestReferenced { content: "content".to_string() },
+			current: TestCurrent { content: "content".to_string() },
+		};
+
+		// The first validation misses the cache and fills it, the second hits it
+		assert!(cache.get_source_with_cache(&source, CacheBehavior::Enabled)?.is_same());
+		assert!(cache.get_source_with_cache(&source, CacheBehavior::Enabled)?.is_same());
+
+		// Each event is logged within the span of its citation
+		logs_assert(|lines: &[&str]| {
+			let events = lines
+				.iter()
+				.filter(|line| line.contains("citation{id=\"traced-source\"}"))
+				.filter_map(|line| line.rsplit(": ").next())
+				.collect::<Vec<_>>();
+			let expected = [
+				"cache lookup hit=false",
+				"compared changed=false",
+				"cache lookup hit=true",
+				"compared changed=false",
+			];
+			match events == expected {
+				true => Ok(()),
+				false => Err(format!("Unexpected events: {:?}", events)),
+			}
+		});
+		Ok(())
+	}
+
+	#[test]
+	fn test_refresh_accepts_current_content() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache =
+			CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache")).build()?;
+
+		let id = Id::new("test-source".to_string());
+		cache.set(&id, &TestCurrent { content: "cached content".to_string() })?;
+
+		let source = TestSource {
+			id: id.clone(),
+			referenced: TestReferenced { content: "ref content".to_string() },
+			current: TestCurrent { content: "current content".to_string() },
+		};
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert!(!result.is_same());
+
+		let refreshed = cache.refresh(&source)?;
+		assert_eq!(refreshed.content, "current content");
+
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert!(result.is_same());
+		Ok(())
+	}
+
+	/// A current that writes trailing whitespace its referenced counterpart does not strip
+	#[derive(Debug, Clone, PartialEq)]
+	struct InconsistentCurrent {
+		content: String,
+	}
+
+	impl Content for InconsistentCurrent {}
+
+	impl Current<TestReferenced, TestDiff> for InconsistentCurrent {
+		fn diff(&self, referenced: &TestReferenced) -> Result<TestDiff, SourceError> {
+			Ok(TestDiff { changed: self.content != referenced.content })
+		}
+	}
+
+	impl CacheableCurrent<TestReferenced, TestDiff> for InconsistentCurrent {
+		fn to_cached_buffer(&self) -> Result<Vec<u8>, CacheError> {
+			Ok(format!("{}\n", self.content).into_bytes())
+		}
+	}
+
+	struct InconsistentSource {
+		id: Id,
+	}
+
+	impl Source<TestReferenced, InconsistentCurrent, TestDiff> for InconsistentSource {
+		fn id(&self) -> &Id {
+			&self.id
+		}
+
+		fn get_referenced(&self) -> Result<TestReferenced, SourceError> {
+			Ok(TestReferenced { content: "content".to_string() })
+		}
+
+		fn get_current(&self) -> Result<InconsistentCurrent, SourceError> {
+			Ok(InconsistentCurrent { content: "content".to_string() })
+		}
+	}
+
+	#[test]
+	fn test_self_check_catches_inconsistent_serialization() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let builder = CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache"));
+		let source = InconsistentSource { id: Id::new("inconsistent".to_string()) };
+
+		// Without the self-check, the source silently reports drift once the cache is populated
+		let cache = builder.clone().with_self_check(false).build()?;
+		assert!(cache.get_source_with_cache(&source, CacheBehavior::Enabled)?.is_same());
+		assert!(!cache.get_source_with_cache(&source, CacheBehavior::Enabled)?.is_same());
+
+		// With it, populating the cache warns loudly
+		cache.delete(source.id())?;
+		let cache = builder.with_self_check(true).build()?;
+		for behavior in [CacheBehavior::Enabled, CacheBehavior::Ignored] {
+			let comparison = cache.get_source_with_cache(&source, behavior)?;
+			assert_eq!(comparison.notes().len(), 1);
+			assert!(comparison.notes()[0].contains("inconsistent does not round-trip"));
+			cache.delete(source.id())?;
+		}
+
+		// Consistent sources pass the self-check
+		let source = TestSource {
+			id: Id::new("consistent".to_string()),
+			referenced: TestReferenced { content: "content".to_string() },
+			current: TestCurrent { content: "content".to_string() },
+		};
+		let comparison = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert!(comparison.is_same());
+		assert!(comparison.notes().is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_empty_initial_capture_warns() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache = CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache"))
+			.with_empty_reference(EmptyReferencePolicy::Warn)
+			.build()?;
+		let source = TestSource {
+			id: Id::new("empty-selector".to_string()),
+			referenced: TestReferenced { content: " \n\t".to_string() },
+			current: TestCurrent { content: " \n\t".to_string() },
+		};
+
+		// The empty reference is still captured, with a warning to fix the selector
+		let comparison = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert!(comparison.is_same());
+		assert_eq!(comparison.notes().len(), 1);
+		assert!(comparison.notes()[0].contains("empty-selector was captured empty"));
+		assert!(cache.get::<TestReferenced>(source.id())?.is_some());
+
+		// Only the initial capture warns
+		let comparison = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert!(comparison.notes().is_empty());
+
+		// Content is captured without a warning
+		let source = TestSource {
+			id: Id::new("matching-selector".to_string()),
+			referenced: TestReferenced { content: "content".to_string() },
+			current: TestCurrent { content: "content".to_string() },
+		};
+		let comparison = cache.get_source_with_cache(&source, CacheBehavior::Enabled)?;
+		assert!(comparison.notes().is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_empty_initial_capture_errors() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let cache = CacheBuilder::new(temp_dir.path().to_path_buf(), PathBuf::from("cache"))
+			.with_empty_reference(EmptyReferencePolicy::Error)
+			.build()?;
+		let source = TestSource {
+			id: Id::new("empty-selector".to_string()),
+			referenced: TestReferenced { content: String::new() },
+			current: TestCurrent { content: String::new() },
+		};
+
+		let result = cache.get_source_with_cache(&source, CacheBehavior::Enabled);
+		assert!(matches!(result, Err(CacheError::EmptyReference(id)) if id == "empty-selector"));
+
+		// The empty reference is not captured, so fixing the selector needs no cache cleanup
+		assert!(cache.get::<TestReferenced>(source.id())?.is_none());
+		Ok(())
+	}
+
 	#[test]
 	fn test_cache_serialization_consistency() -> Result<(), anyhow::Error> {
 		let temp_dir = TempDir::new()?;
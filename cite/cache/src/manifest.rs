@@ -0,0 +1,166 @@
+use crate::CacheError;
+use cite_core::id::Id;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the checksum manifest within the cite directory
+pub const CHECKSUM_MANIFEST_FILE: &str = "checksums.txt";
+
+/// A committed manifest mapping citation ids to content digests
+///
+/// Lets many citations share one committed reference artifact rather than one cache file each.
+/// Each line holds a digest and an id separated by whitespace, in the style of `sha256sum`:
+///
+/// ```text
+/// # cite checksum manifest
+/// 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03  http_https___example_com_full
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumManifest {
+	entries: BTreeMap<String, String>,
+}
+
+/// Result of checking a digest against the [ChecksumManifest]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestCheck {
+	/// The manifest digest matches the current digest
+	Matches,
+	/// The manifest digest differs from the current digest
+	Stale { expected: String },
+	/// The manifest has no entry for the id
+	Missing,
+}
+
+impl ChecksumManifest {
+	/// Parse the contents of a manifest file
+	pub fn parse(content: &str) -> Result<Self, CacheError> {
+		let mut entries = BTreeMap::new();
+
+		for (index, line) in content.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+				[digest, id] => {
+					entries.insert((*id).to_string(), digest.to_lowercase());
+				}
+				_ => {
+					return Err(CacheError::Deserialize(
+						format!(
+							"Invalid checksum manifest line {}: expected '<digest> <id>'",
+							index + 1
+						)
+						.into(),
+					))
+				}
+			}
+		}
+
+		Ok(Self { entries })
+	}
+
+	/// Read a manifest file; a missing file is an empty manifest
+	pub fn read(path: &Path) -> Result<Self, CacheError> {
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+		let content = std::fs::read_to_string(path).map_err(CacheError::ReadCacheFile)?;
+		Self::parse(&content)
+	}
+
+	/// Write the manifest file, replacing any previous contents
+	pub fn write(&self, path: &Path) -> Result<(), CacheError> {
+		let content = format!("# cite checksum manifest\n{}", self.to_manifest_string());
+		std::fs::write(path, content).map_err(CacheError::WriteCacheFile)
+	}
+
+	/// Get the digest recorded for an id
+	pub fn digest(&self, id: &Id) -> Option<&str> {
+		self.entries.get(id.as_str()).map(String::as_str)
+	}
+
+	/// Record the digest for an id
+	pub fn insert(&mut self, id: &Id, digest: &str) {
+		self.entries.insert(id.as_str().to_string(), digest.to_lowercase());
+	}
+
+	/// Check the current digest of a citation against the manifest
+	pub fn check(&self, id: &Id, current_digest: &str) -> ManifestCheck {
+		match self.digest(id) {
+			Some(expected) if expected.eq_ignore_ascii_case(current_digest) => {
+				ManifestCheck::Matches
+			}
+			Some(expected) => ManifestCheck::Stale { expected: expected.to_string() },
+			None => ManifestCheck::Missing,
+		}
+	}
+
+	/// Render the manifest in its file format, sorted by id
+	pub fn to_manifest_string(&self) -> String {
+		self.entries
+			.iter()
+			.map(|(id, digest)| format!("{}  {}\n", digest, id))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	const MANIFEST: &str = "\
+# cite checksum manifest
+aaaa  first_citation
+BBBB  second_citation
+";
+
+	#[test]
+	fn test_manifest_matching_and_stale_digests() -> Result<(), anyhow::Error> {
+		let manifest = ChecksumManifest::parse(MANIFEST)?;
+
+		assert_eq!(
+			manifest.check(&Id::new("first_citation".to_string()), "aaaa"),
+			ManifestCheck::Matches
+		);
+		assert_eq!(
+			manifest.check(&Id::new("second_citation".to_string()), "cccc"),
+			ManifestCheck::Stale { expected: "bbbb".to_string() }
+		);
+		assert_eq!(
+			manifest.check(&Id::new("third_citation".to_string()), "aaaa"),
+			ManifestCheck::Missing
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_manifest_invalid_line() {
+		let result = ChecksumManifest::parse("aaaa first_citation extra\n");
+		assert!(matches!(result, Err(CacheError::Deserialize(_))));
+	}
+
+	#[test]
+	fn test_manifest_read_and_render() -> Result<(), anyhow::Error> {
+		let temp_dir = TempDir::new()?;
+		let path = temp_dir.path().join(CHECKSUM_MANIFEST_FILE);
+
+		// A missing manifest is empty
+		assert_eq!(ChecksumManifest::read(&path)?, ChecksumManifest::default());
+
+		std::fs::write(&path, MANIFEST)?;
+		let mut manifest = ChecksumManifest::read(&path)?;
+		manifest.insert(&Id::new("another_citation".to_string()), "dddd");
+		assert_eq!(
+			manifest.to_manifest_string(),
+			"dddd  another_citation\naaaa  first_citation\nbbbb  second_citation\n"
+		);
+		assert_eq!(ChecksumManifest::parse(&manifest.to_manifest_string())?, manifest);
+
+		manifest.write(&path)?;
+		assert_eq!(ChecksumManifest::read(&path)?, manifest);
+		Ok(())
+	}
+}
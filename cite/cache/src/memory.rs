@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Process-wide memo of the cache entries read or written in this build, by cache file path
+///
+/// A compilation unit expands all of its citations in one process, so an entry requested by
+/// several citations is only read from disk once. Entries are keyed by their path rather than
+/// their id, since caches in different directories may hold the same id.
+fn memory() -> &'static Mutex<HashMap<PathBuf, Vec<u8>>> {
+	static MEMORY: OnceLock<Mutex<HashMap<PathBuf, Vec<u8>>>> = OnceLock::new();
+	MEMORY.get_or_init(Default::default)
+}
+
+/// Get the memoized buffer of a cache file, if it was read or written in this process
+pub fn recall(path: &Path) -> Option<Vec<u8>> {
+	memory().lock().ok()?.get(path).cloned()
+}
+
+/// Memoize the buffer of a cache file
+pub fn remember(path: &Path, buffer: &[u8]) {
+	if let Ok(mut memory) = memory().lock() {
+		memory.insert(path.to_path_buf(), buffer.to_vec());
+	}
+}
+
+/// Drop the memoized buffer of a cache file
+pub fn forget(path: &Path) {
+	if let Ok(mut memory) = memory().lock() {
+		memory.remove(path);
+	}
+}
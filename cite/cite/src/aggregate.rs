@@ -0,0 +1,157 @@
+//! Aggregation of citation drift into one numbered report
+//!
+//! Each `#[cite]` expands on its own, so a build with many drifted citations fails with as many
+//! separate errors. With `CITE_AGGREGATE=1`, the drift that fails compilation is numbered across
+//! the crate in the order it expands: the first drifted citation heads the report, and every
+//! later one refers back to it, so that the errors read as one list.
+//!
+//! rustc gives no hook after the last item expands, so every drifted citation still fails at its
+//! own span. The numbering lives in process-wide state, which a proc-macro server such as
+//! rust-analyzer's keeps across expansions; a citation expanding again keeps its number, and the
+//! state only words the report, never whether a citation fails.
+
+use crate::outcome::MacroValidationOutcome;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable enabling drift aggregation
+const AGGREGATE_ENV: &str = "CITE_AGGREGATE";
+
+/// Check if drift aggregation is enabled
+pub fn is_enabled() -> bool {
+	std::env::var(AGGREGATE_ENV).is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+}
+
+/// Registry of the citations of a crate whose drift failed compilation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriftAggregator {
+	/// The cited items with the details of their drift, in the order they drifted
+	drifted: Vec<(String, String)>,
+}
+
+impl DriftAggregator {
+	/// Create a new, empty aggregator
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get the process-wide aggregators, by the crate they number the drift of
+	pub fn global() -> &'static Mutex<HashMap<String, DriftAggregator>> {
+		static AGGREGATORS: OnceLock<Mutex<HashMap<String, DriftAggregator>>> = OnceLock::new();
+		AGGREGATORS.get_or_init(|| Mutex::new(HashMap::new()))
+	}
+
+	/// Get the cited items recorded so far
+	pub fn drifted(&self) -> Vec<&str> {
+		self.drifted.iter().map(|(item, _)| item.as_str()).collect()
+	}
+
+	/// Record the outcome of the cited item and number it in the aggregated report
+	///
+	/// Outcomes that do not fail compilation are returned unchanged, and those that do keep
+	/// failing it. The first failing outcome is headed by a summary; later ones refer to it.
+	pub fn aggregate(
+		&mut self,
+		outcome: MacroValidationOutcome,
+		item: &str,
+	) -> MacroValidationOutcome {
+		if !outcome.should_fail_compilation() {
+			return outcome;
+		}
+
+		// The same drift expanding again, e.g. in an IDE, keeps its number
+		let details = outcome.message.clone().unwrap_or_default();
+		let drift = (item.to_string(), details.clone());
+		let position = match self.drifted.iter().position(|drifted| *drifted == drift) {
+			Some(index) => index + 1,
+			None => {
+				self.drifted.push(drift);
+				self.drifted.len()
+			}
+		};
+
+		let message = if position == 1 {
+			format!(
+				"Citations have drifted ({} is set), numbered in the order they expand:\n[1] {}\n{}",
+				AGGREGATE_ENV, item, details
+			)
+		} else {
+			format!(
+				"[{}] {} (drifted citations are numbered from [1] {})\n{}",
+				position, item, self.drifted[0].0, details
+			)
+		};
+		MacroValidationOutcome { message: Some(message), ..outcome }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::validation::execute_mock_source_validation;
+	use cite_core::{mock_source_changed, mock_source_same};
+	use cite_core::{CitationAnnotation, CitationBehavior, CitationGlobal, CitationLevel};
+
+	fn outcome(level: CitationLevel, referenced: &str, current: &str) -> MacroValidationOutcome {
+		let behavior =
+			CitationBehavior::new(level, CitationAnnotation::Any, CitationGlobal::Lenient);
+		execute_mock_source_validation(mock_source_changed(referenced, current), &behavior, None)
+	}
+
+	#[test]
+	fn test_aggregate_multiple_drifted_citations() {
+		let mut aggregator = DriftAggregator::new();
+
+		let first =
+			aggregator.aggregate(outcome(CitationLevel::Error, "old\n", "new\n"), "fn parse");
+		assert!(first.should_fail_compilation());
+		let message = first.message.unwrap_or_default();
+		assert!(message.starts_with("Citations have drifted (CITE_AGGREGATE is set)"));
+		assert!(message.contains("\n[1] fn parse\n"));
+		assert!(message.contains("Referenced: old"));
+
+		// Every drift fails at its own citation, referring to the first
+		let second =
+			aggregator.aggregate(outcome(CitationLevel::Error, "a\n", "b\n"), "impl Parser");
+		let third = aggregator.aggregate(outcome(CitationLevel::Error, "c\n", "d\n"), "fn lex");
+		for (outcome, header, referenced) in [
+			(&second, "[2] impl Parser (drifted citations are numbered from [1] fn parse)\n", "a"),
+			(&third, "[3] fn lex (drifted citations are numbered from [1] fn parse)\n", "c"),
+		] {
+			assert!(outcome.should_fail_compilation());
+			assert_eq!(outcome.result.level(), Some(CitationLevel::Error));
+			let message = outcome.message.clone().unwrap_or_default();
+			assert!(message.starts_with(header), "{}", message);
+			assert!(message.contains(&format!("Referenced: {}", referenced)));
+		}
+		assert_eq!(aggregator.drifted(), ["fn parse", "impl Parser", "fn lex"]);
+
+		// Expanding again, as a proc-macro server does, neither renumbers nor downgrades drift
+		let again =
+			aggregator.aggregate(outcome(CitationLevel::Error, "old\n", "new\n"), "fn parse");
+		assert!(again.should_fail_compilation());
+		assert!(again.message.unwrap_or_default().contains("\n[1] fn parse\n"));
+		let again = aggregator.aggregate(outcome(CitationLevel::Error, "c\n", "d\n"), "fn lex");
+		assert!(again.should_fail_compilation());
+		assert!(again.message.unwrap_or_default().starts_with("[3] fn lex"));
+		assert_eq!(aggregator.drifted().len(), 3);
+	}
+
+	#[test]
+	fn test_aggregate_keeps_passing_outcomes() {
+		let mut aggregator = DriftAggregator::new();
+
+		let warning = outcome(CitationLevel::Warn, "old\n", "new\n");
+		assert_eq!(aggregator.aggregate(warning.clone(), "fn parse"), warning);
+
+		let behavior = CitationBehavior::new(
+			CitationLevel::Error,
+			CitationAnnotation::Any,
+			CitationGlobal::Lenient,
+		);
+		let valid = execute_mock_source_validation(mock_source_same("content"), &behavior, None);
+		assert_eq!(aggregator.aggregate(valid.clone(), "fn parse"), valid);
+
+		assert!(aggregator.drifted().is_empty());
+	}
+}
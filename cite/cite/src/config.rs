@@ -0,0 +1,146 @@
+//! Crate configuration in `cite.toml`
+//!
+//! `cite.toml` sits next to the `Cargo.toml` of the crate being built. Its targets and normalizer
+//! profiles are resolved during expansion as described in [cite_sources::config].
+//!
+//! Its `behavior` table configures how citations report drift, over the feature flags of cite.
+//! A `cite.local.toml` beside it, left uncommitted, overrides it for one developer, and the
+//! `CITE_LEVEL`, `CITE_ANNOTATION` and `CITE_GLOBAL` environment variables override both.
+//!
+//! ```toml
+//! [behavior]
+//! level = "warn"
+//! annotation = "any"
+//! global = "lenient"
+//! ```
+
+use cite_core::{CitationBehavior, Layered, PartialBehavior};
+use cite_sources::config::{self, CONFIG_FILE};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the local, uncommitted configuration file, beside the configuration file
+const LOCAL_CONFIG_FILE: &str = "cite.local.toml";
+
+/// Get the directory of the manifest of the crate being built, where `cite.toml` is read from
+fn manifest_dir(purpose: &str) -> Result<PathBuf, String> {
+	std::env::var_os("CARGO_MANIFEST_DIR")
+		.map(PathBuf::from)
+		.ok_or_else(|| format!("{} need CARGO_MANIFEST_DIR to find {}", purpose, CONFIG_FILE))
+}
+
+/// Resolve the behavior of citations, layering the feature flags, `cite.toml`, `cite.local.toml`
+/// and the environment from lowest to highest precedence
+pub fn behavior() -> Result<CitationBehavior, String> {
+	let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from);
+	behavior_in(manifest_dir.as_deref(), PartialBehavior::from_env()?)
+}
+
+fn behavior_in(
+	manifest_dir: Option<&Path>,
+	env: PartialBehavior,
+) -> Result<CitationBehavior, String> {
+	let layer = |file| match manifest_dir {
+		Some(manifest_dir) => behavior_layer(&manifest_dir.join(file)),
+		None => Ok(PartialBehavior::new()),
+	};
+	Ok(Layered::new()
+		.with_features(PartialBehavior::from_features())
+		.with_config(layer(CONFIG_FILE)?)
+		.with_local(layer(LOCAL_CONFIG_FILE)?)
+		.with_env(env)
+		.resolve())
+}
+
+/// Read the `behavior` table of a configuration file, configuring nothing if there is none
+fn behavior_layer(path: &Path) -> Result<PartialBehavior, String> {
+	let config = match std::fs::read_to_string(path) {
+		Ok(config) => config,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(PartialBehavior::new()),
+		Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+	};
+	let config = config
+		.parse::<toml::Table>()
+		.map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+	let Some(behavior) = config.get("behavior") else {
+		return Ok(PartialBehavior::new());
+	};
+
+	let setting = |key: &str| match behavior.get(key) {
+		Some(value) => value
+			.as_str()
+			.map(Some)
+			.ok_or_else(|| format!("behavior.{} in {} must be a string", key, path.display())),
+		None => Ok(None),
+	};
+	PartialBehavior::from_values(setting("level")?, setting("annotation")?, setting("global")?)
+		.map_err(|e| format!("Invalid behavior in {}: {}", path.display(), e))
+}
+
+/// Resolve the `target` of a citation, if any, into the parameters of the active profile, see
+/// [cite_sources::config]
+pub fn resolve_target(kwargs: &mut HashMap<String, Value>) -> Result<(), String> {
+	if !kwargs.contains_key("target") {
+		return Ok(());
+	}
+	config::resolve_target(&manifest_dir("targets")?, config::active_profile().as_deref(), kwargs)
+}
+
+/// Resolve the `normalizer` profile of a citation, if any, into its `normalize` chain, see
+/// [cite_sources::config]
+pub fn resolve_normalizer(kwargs: &mut HashMap<String, Value>) -> Result<(), String> {
+	if !kwargs.contains_key("normalizer") {
+		return Ok(());
+	}
+	let src = kwargs.get("src").and_then(Value::as_str).unwrap_or_default().to_string();
+	config::resolve_normalizer(&src, &manifest_dir("normalizer profiles")?, kwargs)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_behavior_layers() -> Result<(), anyhow::Error> {
+		use cite_core::{CitationAnnotation, CitationGlobal, CitationLevel};
+
+		// Without configuration, the feature flags decide
+		let manifest_dir = tempfile::tempdir()?;
+		let behavior = |env| behavior_in(Some(manifest_dir.path()), env);
+		assert_eq!(behavior(PartialBehavior::new()), Ok(CitationBehavior::from_features()));
+		assert_eq!(
+			behavior_in(None, PartialBehavior::new()),
+			Ok(CitationBehavior::from_features())
+		);
+
+		// The local configuration overrides the project's, and the environment both
+		std::fs::write(
+			manifest_dir.path().join(CONFIG_FILE),
+			"[behavior]\nlevel = \"silent\"\nannotation = \"any\"\nglobal = \"lenient\"\n",
+		)?;
+		std::fs::write(
+			manifest_dir.path().join(LOCAL_CONFIG_FILE),
+			"[behavior]\nlevel = \"warn\"\n",
+		)?;
+		assert_eq!(
+			behavior(PartialBehavior::new()),
+			Ok(CitationBehavior::new(
+				CitationLevel::Warn,
+				CitationAnnotation::Any,
+				CitationGlobal::Lenient
+			))
+		);
+		let env = PartialBehavior::new().with_level(CitationLevel::Error);
+		assert_eq!(behavior(env).map(|behavior| behavior.level), Ok(CitationLevel::Error));
+
+		// Invalid settings are reported with the file configuring them
+		std::fs::write(
+			manifest_dir.path().join(LOCAL_CONFIG_FILE),
+			"[behavior]\nlevel = \"loud\"\n",
+		)?;
+		let error = behavior(PartialBehavior::new()).unwrap_err();
+		assert!(error.contains(LOCAL_CONFIG_FILE), "{}", error);
+		Ok(())
+	}
+}
@@ -0,0 +1,107 @@
+//! Patches of drifted citations archived in a directory
+//!
+//! With `CITE_DIFF_DIR=drift`, the diff of each citation that fails validation is written to the
+//! named directory as a patch file, named by the id of the citation and the time it was found to
+//! drift. The patches keep the evidence of what drifted for later review, after the cited source
+//! has moved on. Relative paths are relative to the manifest of the crate being built.
+
+use crate::outcome::MacroValidationOutcome;
+use cite_core::{Id, ReverifyInterval};
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the directory patches are archived in
+pub const DIFF_DIR_ENV: &str = "CITE_DIFF_DIR";
+
+/// The diff of a drifted citation, as a patch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+	/// The id of the cited source
+	pub id: Id,
+	/// The content of the patch file, see [cite_core::Diff::to_patch_file]
+	pub content: String,
+}
+
+/// Get the directory patches are archived in, if any
+pub fn path() -> Option<PathBuf> {
+	let path = PathBuf::from(std::env::var_os(DIFF_DIR_ENV).filter(|path| !path.is_empty())?);
+	match std::env::var_os("CARGO_MANIFEST_DIR") {
+		Some(manifest_dir) if path.is_relative() => Some(PathBuf::from(manifest_dir).join(path)),
+		_ => Some(path),
+	}
+}
+
+/// Name the patch file of a citation, e.g. `http_example.com_docs-20261015T093000Z.patch`
+///
+/// Ids name URLs and paths, so characters that cannot be part of a file name are replaced.
+pub fn file_name(id: &Id, timestamp: &str) -> String {
+	let id = id
+		.as_str()
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+		.collect::<String>();
+	format!("{}-{}.patch", id, timestamp)
+}
+
+/// Archive the patch of a citation that failed validation, returning the path written
+///
+/// Outcomes that are valid or have no patch write nothing.
+pub fn archive(dir: &Path, outcome: &MacroValidationOutcome) -> std::io::Result<Option<PathBuf>> {
+	let timestamp = ReverifyInterval::now().format("%Y%m%dT%H%M%SZ").to_string();
+	archive_at(dir, outcome, &timestamp)
+}
+
+fn archive_at(
+	dir: &Path,
+	outcome: &MacroValidationOutcome,
+	timestamp: &str,
+) -> std::io::Result<Option<PathBuf>> {
+	let Some(patch) = outcome.patch.as_ref().filter(|_| !outcome.result.is_valid()) else {
+		return Ok(None);
+	};
+	std::fs::create_dir_all(dir)?;
+	let path = dir.join(file_name(&patch.id, timestamp));
+	std::fs::write(&path, &patch.content)?;
+	Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cite_core::{CitationLevel, CitationValidationResult};
+
+	fn drifted(id: &str, patch: Option<&str>) -> MacroValidationOutcome {
+		let result = CitationValidationResult::Invalid {
+			level: CitationLevel::Error,
+			should_fail_compilation: true,
+			should_report: true,
+		};
+		MacroValidationOutcome::invalid(result, "changed".to_string())
+			.with_patch(&Id::new(id.to_string()), patch.map(str::to_string))
+	}
+
+	#[test]
+	fn test_file_name() {
+		let id = Id::new("git_https://github.com/ramate-io/cite_README.md_v1_main".to_string());
+		assert_eq!(
+			file_name(&id, "20261015T093000Z"),
+			"git_https___github.com_ramate-io_cite_README.md_v1_main-20261015T093000Z.patch"
+		);
+	}
+
+	#[test]
+	fn test_archive_failing_citations() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let patch = "--- a/http_docs\n+++ b/http_docs\n@@ -1,1 +1,1 @@\n-old\n+new\n";
+
+		let path = archive_at(dir.path(), &drifted("http_docs", Some(patch)), "20261015T093000Z")?;
+		assert_eq!(path, Some(dir.path().join("http_docs-20261015T093000Z.patch")));
+		assert_eq!(std::fs::read_to_string(path.unwrap())?, patch);
+
+		// Valid citations and diffs without a patch leave nothing behind
+		let valid = MacroValidationOutcome::valid();
+		assert_eq!(archive_at(dir.path(), &valid, "20261015T093001Z")?, None);
+		assert_eq!(archive_at(dir.path(), &drifted("mock", None), "20261015T093002Z")?, None);
+		assert_eq!(std::fs::read_dir(dir.path())?.count(), 1);
+		Ok(())
+	}
+}
@@ -1,6 +1,34 @@
-use crate::sources;
-use crate::Citation;
-use cite_core::Source;
+use crate::{gha, Citation};
+use cite_git::{CachedResolver, GithubApiResolver, RepoResolver};
+use cite_sources::AnySource;
+
+/// Document a cited item with its citation footnote
+///
+/// With `CITE_FOOTNOTE_FILE` set, the footnote is collected in that file instead, falling back
+/// to the doc comments if it cannot be written.
+pub fn document_item(
+	attrs: &mut Vec<syn::Attribute>,
+	citation: &Citation,
+	warning_text: String,
+	item: &str,
+) {
+	if let Some(path) = footnote_file::path() {
+		let package = std::env::var("CARGO_PKG_NAME").ok();
+		let source = citation_source(citation);
+		let footnote = citation_footnote(citation, source.as_ref(), warning_text.clone());
+		let entry = footnote_file::entry(
+			item,
+			package.as_deref(),
+			gha::locate().as_ref(),
+			source.as_ref().map(|source| source.id().as_str()).unwrap_or_default(),
+			&footnote,
+		);
+		if footnote_file::append(&path, &entry).is_ok() {
+			return;
+		}
+	}
+	add_citation_footnote_to_item(attrs, citation, warning_text);
+}
 
 /// Add citation footnote to doc comments
 pub fn add_citation_footnote_to_item(
@@ -19,53 +47,10 @@ pub fn add_citation_footnote_to_item(
 		complete_footnote.push_str(&formatting::generate_global_citation_formatting());
 	}
 
-	// Generate link text by constructing the source and calling its methods
-	let link_text = if let Some(kwargs) = &citation.kwargs {
-		if let Some(src) = citation.get_src().ok() {
-			match src.as_str() {
-				"git" => {
-					match sources::git::try_get_git_source_from_kwargs(kwargs) {
-						Ok(git_source) => {
-							let name = git_source.name();
-							let link = git_source.link();
-							Some(format!("[{}]({})", name, link))
-						}
-						Err(_) => None, // If construction fails, skip link generation
-					}
-				}
-				"http" => {
-					match sources::http::try_get_http_source_from_kwargs(kwargs) {
-						Ok(http_source) => {
-							let name = http_source.name();
-							let link = http_source.link();
-							Some(format!("[{}]({})", name, link))
-						}
-						Err(_) => None, // If construction fails, skip link generation
-					}
-				}
-				"mock" => {
-					match sources::mock::try_get_mock_source_from_kwargs(kwargs) {
-						Ok(mock_source) => {
-							let name = mock_source.name();
-							let link = mock_source.link();
-							Some(format!("[{}]({})", name, link))
-						}
-						Err(_) => None, // If construction fails, skip link generation
-					}
-				}
-				_ => None, // Unknown source type
-			}
-		} else {
-			None // No source found
-		}
-	} else {
-		None // No kwargs available
-	};
-
 	// Add the specific citation footnote
-	complete_footnote.push_str(&footnotes::generate_citation_footnote(
+	complete_footnote.push_str(&citation_footnote(
 		citation,
-		link_text,
+		citation_source(citation).as_ref(),
 		warning_text,
 	));
 
@@ -78,5 +63,129 @@ pub fn add_citation_footnote_to_item(
 	attrs.push(doc_attr);
 }
 
+/// Construct the source of a citation, for its link, embedded reference and id
+fn citation_source(citation: &Citation) -> Option<AnySource> {
+	match (&citation.kwargs, citation.get_src()) {
+		// If construction fails, skip link generation
+		(Some(kwargs), Ok(src)) => AnySource::from_kwargs(&src, kwargs).ok(),
+		_ => None, // No kwargs or source available
+	}
+}
+
+/// Generate the footnote of a citation, with its link and embedded reference
+fn citation_footnote(
+	citation: &Citation,
+	source: Option<&AnySource>,
+	warning_text: String,
+) -> String {
+	let link_text = source.map(|source| format!("[{}]({})", source.name(), source_link(source)));
+
+	// Embed the referenced content, as far as it is known without fetching
+	let embedded_reference = citation.embed.then(|| {
+		let snapshot = source.and_then(AnySource::referenced_snapshot);
+		footnotes::generate_embedded_reference(snapshot.as_deref())
+	});
+
+	footnotes::generate_citation_footnote(citation, link_text, embedded_reference, warning_text)
+}
+
+/// Get the link of a source, following renamed GitHub repositories if enabled
+///
+/// Resolved names are cached in the cite directory, so that the GitHub API is not called on
+/// every expansion.
+fn source_link(source: &AnySource) -> String {
+	if !GithubApiResolver::is_enabled() {
+		return source_link_with(source, None);
+	}
+	match cite_cache::CacheBuilder::default().build() {
+		Ok(cache) => source_link_with(source, Some(&CachedResolver::new(GithubApiResolver, cache))),
+		Err(_) => source_link_with(source, Some(&GithubApiResolver)),
+	}
+}
+
+fn source_link_with(source: &AnySource, resolver: Option<&dyn RepoResolver>) -> String {
+	match (source, resolver) {
+		(AnySource::Git(git), Some(resolver)) => git.resolved_link(resolver),
+		_ => source.link().to_string(),
+	}
+}
+
+pub mod footnote_file;
 pub mod footnotes;
 pub mod formatting;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cite_git::GitSource;
+
+	fn footnote(kwargs: serde_json::Value) -> String {
+		let kwargs = serde_json::from_value(kwargs).unwrap();
+		let citation = crate::prevalidation::validate_with_kwargs(&kwargs, &[]).unwrap();
+		let mut attrs = Vec::new();
+		add_citation_footnote_to_item(&mut attrs, &citation, String::new());
+
+		match &attrs[0].meta {
+			syn::Meta::NameValue(syn::MetaNameValue {
+				value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+				..
+			}) => doc.value(),
+			meta => panic!("expected a doc attribute, got {:?}", quote::quote!(#meta)),
+		}
+	}
+
+	#[test]
+	fn test_embed_referenced_content() {
+		let kwargs = serde_json::json!({
+			"src": "mock",
+			"same": "The parser rejects trailing commas",
+			"embed": "true",
+		});
+		assert!(footnote(kwargs.clone())
+			.contains("\t```text\n\tThe parser rejects trailing commas\n\t```"));
+
+		// Without embedding only the link is rendered
+		let mut kwargs = kwargs;
+		kwargs["embed"] = serde_json::json!(false);
+		assert!(!footnote(kwargs).contains("```"));
+	}
+
+	#[test]
+	fn test_embedded_reference_rendering() {
+		assert!(footnotes::generate_embedded_reference(None).contains("No referenced content"));
+
+		// Long content is truncated
+		let long = "word ".repeat(footnotes::EMBED_MAX_CHARS);
+		let embedded = footnotes::generate_embedded_reference(Some(&long));
+		assert!(embedded.len() < long.len());
+		assert!(embedded.contains("Truncated to"));
+
+		// Backticks in the content cannot close the fence
+		let embedded = footnotes::generate_embedded_reference(Some("use ```rust``` blocks"));
+		assert!(embedded.starts_with("\t````text\n"));
+	}
+
+	#[test]
+	fn test_source_link_follows_renamed_repo() {
+		let source = AnySource::Git(
+			GitSource::try_new(
+				"https://github.com/old-owner/old-name",
+				"README.md",
+				"abc123",
+				Some("main"),
+				None,
+			)
+			.unwrap(),
+		);
+		let renamed = |_: &str, _: &str| Some("new-owner/new-name".to_string());
+
+		assert_eq!(
+			source_link_with(&source, Some(&renamed)),
+			"https://github.com/new-owner/new-name/blob/abc123/README.md"
+		);
+		assert_eq!(
+			source_link_with(&source, None),
+			"https://github.com/old-owner/old-name/blob/abc123/README.md"
+		);
+	}
+}
@@ -0,0 +1,260 @@
+//! Citation footnotes collected in a Markdown file
+//!
+//! With `CITE_FOOTNOTE_FILE=CITATIONS.md`, the footnote of each citation is written to the named
+//! file, under a heading naming the cited item and where it is declared, instead of to the docs
+//! of the item. Relative paths are relative to the manifest of the crate being built.
+//!
+//! Crates are expanded in parallel, so the file is only written while holding a lock file next
+//! to it. Rebuilding a citation replaces its entry, while the entries of removed citations are
+//! left in place until the file is deleted. Entries are told apart by the identity of their
+//! citation, so that items sharing a name, e.g. several `fn new`, each keep their own.
+
+use crate::gha::Location;
+use std::fs::OpenOptions;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Environment variable naming the file footnotes are collected in
+pub const FOOTNOTE_FILE_ENV: &str = "CITE_FOOTNOTE_FILE";
+
+/// How long a lock is waited for, and after which one is taken to be left behind by a crashed
+/// build
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Get the file footnotes are collected in, if any
+pub fn path() -> Option<PathBuf> {
+	let path = PathBuf::from(std::env::var_os(FOOTNOTE_FILE_ENV).filter(|path| !path.is_empty())?);
+	match std::env::var_os("CARGO_MANIFEST_DIR") {
+		Some(manifest_dir) if path.is_relative() => Some(PathBuf::from(manifest_dir).join(path)),
+		_ => Some(path),
+	}
+}
+
+/// Render the entry of a citation's footnote, under a heading such as
+/// ``### `fn parse` (my-crate, src/lib.rs:12)``
+///
+/// The heading is followed by a comment with the id of the cited source, hidden when the file is
+/// rendered, so that the first two lines of an entry identify its citation.
+pub fn entry(
+	item: &str,
+	package: Option<&str>,
+	location: Option<&Location>,
+	source_id: &str,
+	footnote: &str,
+) -> String {
+	let mut context = package.map(str::to_string).into_iter().collect::<Vec<_>>();
+	if let Some(location) = location {
+		context.push(format!("{}:{}", location.file, location.line));
+	}
+	let heading = if context.is_empty() {
+		format!("### `{}`", item)
+	} else {
+		format!("### `{}` ({})", item, context.join(", "))
+	};
+	// Comments must not contain `--`, which ids of URLs may
+	let key = format!("<!-- cite: {} -->", source_id.replace("--", "- -"));
+	format!("{}\n{}\n{}\n\n", heading, key, footnote.trim_end())
+}
+
+/// Add an entry to the footnote file, replacing an earlier entry of the same citation
+pub fn append(path: &Path, entry: &str) -> std::io::Result<()> {
+	if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+		std::fs::create_dir_all(parent)?;
+	}
+	let _lock = FileLock::acquire(path)?;
+
+	let existing = match std::fs::read_to_string(path) {
+		Ok(existing) => existing,
+		Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+		Err(e) => return Err(e),
+	};
+	let mut entries = split_entries(&existing)
+		.into_iter()
+		.filter(|existing| identity(existing) != identity(entry))
+		.collect::<String>();
+	entries.push_str(entry);
+	std::fs::write(path, entries)
+}
+
+/// Get the identity of an entry's citation: its heading and the comment with its source id
+fn identity(entry: &str) -> Vec<&str> {
+	entry.lines().take(2).collect()
+}
+
+/// Split the content of a footnote file into its entries, each starting at its heading
+fn split_entries(content: &str) -> Vec<&str> {
+	let mut starts = content
+		.match_indices("### ")
+		.map(|(index, _)| index)
+		.filter(|index| *index == 0 || content[..*index].ends_with('\n'))
+		.collect::<Vec<_>>();
+	if starts.first() != Some(&0) {
+		starts.insert(0, 0);
+	}
+	starts
+		.iter()
+		.zip(starts.iter().skip(1).chain([&content.len()]))
+		.map(|(start, end)| &content[*start..*end])
+		.filter(|entry| !entry.is_empty())
+		.collect()
+}
+
+/// Lock held on a file by creating a `.lock` file next to it, removed once dropped
+struct FileLock {
+	path: PathBuf,
+}
+
+impl FileLock {
+	fn acquire(path: &Path) -> std::io::Result<Self> {
+		let mut lock_path = path.as_os_str().to_owned();
+		lock_path.push(".lock");
+		let lock_path = PathBuf::from(lock_path);
+
+		let started = Instant::now();
+		loop {
+			match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+				Ok(_) => return Ok(Self { path: lock_path }),
+				Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+				Err(e) => return Err(e),
+			}
+
+			let stale = std::fs::metadata(&lock_path)
+				.and_then(|metadata| metadata.modified())
+				.is_ok_and(|modified| modified.elapsed().unwrap_or_default() > LOCK_TIMEOUT);
+			if stale {
+				let _ = std::fs::remove_file(&lock_path);
+			} else if started.elapsed() > LOCK_TIMEOUT {
+				return Err(Error::new(
+					ErrorKind::TimedOut,
+					format!("Timed out waiting for {}", lock_path.display()),
+				));
+			} else {
+				std::thread::sleep(Duration::from_millis(10));
+			}
+		}
+	}
+}
+
+impl Drop for FileLock {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_file(&self.path);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn location(file: &str, line: usize) -> Location {
+		Location { file: file.to_string(), line }
+	}
+
+	#[test]
+	fn test_entry() {
+		let footnote = "\n1. **[Citation source](https://example.com)**\n\n\tWhy it matters";
+		assert_eq!(
+			entry(
+				"fn parse",
+				Some("my-crate"),
+				Some(&location("src/lib.rs", 12)),
+				"http_example.com",
+				footnote
+			),
+			"### `fn parse` (my-crate, src/lib.rs:12)\n<!-- cite: http_example.com -->\n\n1. **[Citation source](https://example.com)**\n\n\tWhy it matters\n\n"
+		);
+		let config = entry("struct Config", None, None, "http_a--b", footnote);
+		assert!(config.starts_with("### `struct Config`\n<!-- cite: http_a- -b -->\n"));
+	}
+
+	#[test]
+	fn test_append_replaces_entries() -> std::io::Result<()> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("docs").join("CITATIONS.md");
+
+		let parse =
+			|reason| entry("fn parse", Some("my-crate"), None, "mock", &format!("\n1. {}", reason));
+		append(&path, &parse("first"))?;
+		append(&path, &entry("fn render", Some("my-crate"), None, "mock", "\n1. render"))?;
+		append(&path, &parse("second"))?;
+
+		let content = std::fs::read_to_string(&path)?;
+		assert!(!content.contains("first"));
+		assert_eq!(content.matches("### `fn parse`").count(), 1);
+		assert!(content.starts_with("### `fn render`"));
+		assert!(content.ends_with("1. second\n\n"));
+		assert!(!dir.path().join("docs").join("CITATIONS.md.lock").exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_same_named_items_keep_their_entries() -> std::io::Result<()> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("CITATIONS.md");
+
+		// Two `fn new` of one file, citing different sources at different lines
+		let parser = |reason| {
+			let location = location("src/lib.rs", 16);
+			entry("fn new", Some("my-crate"), Some(&location), "mock_parser", reason)
+		};
+		let driver = |reason| {
+			let location = location("src/lib.rs", 54);
+			entry("fn new", Some("my-crate"), Some(&location), "mock_driver", reason)
+		};
+		append(&path, &parser("\n1. parser"))?;
+		append(&path, &driver("\n1. driver"))?;
+		append(&path, &parser("\n1. parser again"))?;
+
+		let content = std::fs::read_to_string(&path)?;
+		assert_eq!(split_entries(&content).len(), 2);
+		assert!(
+			content.contains("### `fn new` (my-crate, src/lib.rs:54)\n<!-- cite: mock_driver -->")
+		);
+		assert!(content.contains("1. driver"));
+		assert!(content.ends_with("1. parser again\n\n"));
+		assert!(!content.contains("1. parser\n"));
+
+		// Without locations, the ids of the sources still tell the items apart
+		let unlocated = |id| entry("fn new", Some("my-crate"), None, id, "\n1. unlocated");
+		append(&path, &unlocated("mock_parser"))?;
+		append(&path, &unlocated("mock_driver"))?;
+		assert_eq!(split_entries(&std::fs::read_to_string(&path)?).len(), 4);
+		Ok(())
+	}
+
+	#[test]
+	fn test_append_in_parallel() -> std::io::Result<()> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("CITATIONS.md");
+
+		let threads = (0..8)
+			.map(|index| {
+				let path = path.clone();
+				std::thread::spawn(move || {
+					let item = format!("fn item_{}", index);
+					append(&path, &entry(&item, None, None, "mock", "\n1. cited"))
+				})
+			})
+			.collect::<Vec<_>>();
+		for thread in threads {
+			thread.join().expect("appending thread panicked")?;
+		}
+
+		let content = std::fs::read_to_string(&path)?;
+		assert_eq!(split_entries(&content).len(), 8);
+		assert!((0..8).all(|index| content.contains(&format!("### `fn item_{}`", index))));
+		Ok(())
+	}
+
+	#[test]
+	fn test_stale_lock_is_taken_over() -> std::io::Result<()> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("CITATIONS.md");
+		let lock = std::fs::File::create(dir.path().join("CITATIONS.md.lock"))?;
+		lock.set_modified(std::time::SystemTime::now() - LOCK_TIMEOUT * 2)?;
+
+		append(&path, &entry("fn parse", None, None, "mock", "\n1. cited"))?;
+		assert!(std::fs::read_to_string(&path)?.contains("### `fn parse`"));
+		Ok(())
+	}
+}
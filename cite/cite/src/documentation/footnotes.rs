@@ -1,9 +1,42 @@
 use crate::Citation;
 
+/// Maximum number of characters of referenced content embedded in a footnote
+pub const EMBED_MAX_CHARS: usize = 2000;
+
+/// Generate the embedded referenced content of a footnote, as a fenced block
+///
+/// Content beyond [EMBED_MAX_CHARS] is truncated. The fence is longer than any run of backticks
+/// in the content, so the content cannot close it.
+pub fn generate_embedded_reference(snapshot: Option<&str>) -> String {
+	let Some(snapshot) = snapshot else {
+		return "\t*No referenced content is cached yet.*".to_string();
+	};
+
+	let mut content = snapshot.chars().take(EMBED_MAX_CHARS).collect::<String>();
+	let truncated = content.len() < snapshot.len();
+	if truncated {
+		content.push_str("\n...");
+	}
+
+	let longest_backticks = content.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+	let fence = "`".repeat(longest_backticks.max(2) + 1);
+
+	let mut block = format!("\t{}text\n", fence);
+	for line in content.lines() {
+		block.push_str(&format!("\t{}\n", line));
+	}
+	block.push_str(&format!("\t{}", fence));
+	if truncated {
+		block.push_str(&format!("\n\n\t*Truncated to {} characters.*", EMBED_MAX_CHARS));
+	}
+	block
+}
+
 /// Generate citation footnote text
 pub fn generate_citation_footnote(
 	citation: &Citation,
 	link_text: Option<String>,
+	embedded_reference: Option<String>,
 	warning_text: String,
 ) -> String {
 	let mut footnote = String::new();
@@ -37,6 +70,10 @@ pub fn generate_citation_footnote(
 		footnote.push_str(&format!("\n\n{}", formatted_reason));
 	}
 
+	if let Some(embedded_reference) = embedded_reference {
+		footnote.push_str(&format!("\n\n{}", embedded_reference));
+	}
+
 	if !warning_text.is_empty() {
 		// Handle multiline warning text by splitting and prefixing each line with tab
 		let formatted_warning = warning_text
@@ -33,7 +33,8 @@ pub fn generate_global_citation_formatting() -> String {
 	);
 
 	// Add behavior hint boxes
-	let behavior = cite_core::CitationBehavior::from_features();
+	let behavior =
+		crate::config::behavior().unwrap_or_else(|_| cite_core::CitationBehavior::from_features());
 
 	// Global behavior
 	match behavior.global {
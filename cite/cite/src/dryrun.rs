@@ -0,0 +1,144 @@
+//! Dry runs of citation validation
+//!
+//! With `CITE_DRYRUN=1`, citations are not validated. Instead each `#[cite]` prints to stderr
+//! what its source resolved to: the final URL, revision or path, the match expression, where
+//! the reference is kept and whether it exists yet. Nothing is fetched, and the build never
+//! fails, so it is a quick way to check how the macro read a citation.
+
+use crate::Citation;
+use cite_sources::AnySource;
+use std::io::Write;
+
+/// Environment variable enabling dry runs
+const DRYRUN_ENV: &str = "CITE_DRYRUN";
+
+/// Check if dry runs are enabled
+pub fn is_enabled() -> bool {
+	std::env::var(DRYRUN_ENV).is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+}
+
+/// Print what the citation resolved to
+pub fn emit(citation: &Citation) {
+	let _ = writeln!(std::io::stderr(), "{}", describe(citation));
+}
+
+/// Describe what the citation resolved to, one detail per line
+fn describe(citation: &Citation) -> String {
+	let src = match citation.get_src() {
+		Ok(src) => src,
+		Err(error) => return format!("cite dry run: {}", error),
+	};
+	let kwargs = citation.kwargs.clone().unwrap_or_default();
+	let source = match AnySource::from_kwargs(&src, &kwargs) {
+		Ok(source) => source,
+		Err(error) => return format!("cite dry run ({}): {}", src, error),
+	};
+
+	let mut details = vec![format!("cite dry run ({}): {}", source.src(), source.name())];
+	details.extend(source_details(&source));
+	details.join("\n  ")
+}
+
+/// Describe the resolved source, each detail as `Label: value`
+fn source_details(source: &AnySource) -> Vec<String> {
+	match source {
+		AnySource::Git(source) => {
+			let repository = match source.repository_path() {
+				Ok(path) => path.display().to_string(),
+				Err(error) => error.to_string(),
+			};
+			let reference = match source.has_referenced_revision() {
+				true => "cloned",
+				false => "not cloned yet",
+			};
+			vec![
+				format!("Remote: {}", source.remote),
+				format!("Path: {}", source.path_pattern.path),
+				format!("Referenced revision: {}", source.referenced_revision),
+				format!("Current revision: {}", source.describe_current_revision()),
+				format!("Repository: {}", repository),
+				format!("Reference: {}", reference),
+			]
+		}
+		AnySource::Http(source) => {
+			let reference = match source.cached_referenced() {
+				Some(_) => "cached",
+				None => "not cached yet",
+			};
+			vec![
+				format!("URL: {}", source.source_url.normalized()),
+				format!("Match: {}", source.matches),
+				format!(
+					"Cache path: {}",
+					source.cache().cache_dir().join(&source.cache_path).display()
+				),
+				format!("Reference: {}", reference),
+			]
+		}
+		AnySource::Bundle(source) => {
+			let reference = match source.expected() {
+				Some(expected) => format!("pinned to sha256 {}", expected),
+				None => "not pinned yet".to_string(),
+			};
+			vec![
+				format!("Root: {}", source.root().display()),
+				format!("Glob: {}", source.patterns().join(", ")),
+				format!("Recording path: {}", source.recording_path().display()),
+				format!("Reference: {}", reference),
+			]
+		}
+		AnySource::Mock(source) => {
+			vec![format!("Reference: {}", source.referenced_content)]
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn citation(kwargs: serde_json::Value) -> Citation {
+		let kwargs = serde_json::from_value(kwargs).unwrap();
+		crate::prevalidation::validate_with_kwargs(&kwargs, &[]).unwrap()
+	}
+
+	#[test]
+	fn test_describe_git_citation() {
+		let citation = citation(serde_json::json!({
+			"src": "git",
+			"remote": "https://github.com/ramate-io/cite",
+			"ref_rev": "94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			"cur_rev": "main",
+			"path": "README.md#L1-L3",
+			"reason": "Dry run of a git citation",
+		}));
+
+		let description = describe(&citation);
+		let lines = description.lines().collect::<Vec<_>>();
+		assert!(lines[0].starts_with("cite dry run (git): "), "{}", description);
+		assert_eq!(lines[1], "  Remote: https://github.com/ramate-io/cite");
+		assert_eq!(lines[2], "  Path: README.md");
+		assert_eq!(lines[3], "  Referenced revision: 94dab273cf6c2abe8742d6d459ad45c96ca9b694");
+		assert_eq!(lines[4], "  Current revision: main");
+		assert!(lines[5].starts_with("  Repository: ") && lines[5].ends_with("cite"));
+		assert!(lines[6].starts_with("  Reference: "));
+	}
+
+	#[test]
+	fn test_describe_http_citation() {
+		let citation = citation(serde_json::json!({
+			"src": "http",
+			"url": "https://example.com/docs#install",
+			"match": "css:h1",
+			"reason": "Dry run of an http citation",
+		}));
+
+		let description = describe(&citation);
+		let lines = description.lines().collect::<Vec<_>>();
+		assert!(lines[0].starts_with("cite dry run (http): "), "{}", description);
+		assert_eq!(lines[1], "  URL: https://example.com/docs");
+		assert_eq!(lines[2], "  Match: css:h1");
+		assert!(lines[3].starts_with("  Cache path: ") && lines[3].contains("http_"));
+		assert!(lines[4].starts_with("  Reference: "));
+	}
+}
@@ -0,0 +1,159 @@
+//! GitHub Actions annotations for citation drift
+//!
+//! With `CITE_GHA=1`, every citation that fails validation is also printed to stdout as a
+//! `::warning` or `::error` workflow command, so that the drift shows inline on the pull request.
+//! The file and line are those of the `#[cite]` attribute, which the compiler tells proc macros
+//! from Rust 1.88 on; annotations of older toolchains have no location.
+
+use crate::outcome::MacroValidationOutcome;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variable enabling GitHub Actions annotations
+const GHA_ENV: &str = "CITE_GHA";
+
+/// Check if GitHub Actions annotations are enabled
+pub fn is_enabled() -> bool {
+	std::env::var(GHA_ENV).is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+}
+
+/// Where a citation is declared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+	/// Path of the file, relative to the repository root
+	pub file: String,
+	/// 1-based line of the `#[cite]` attribute
+	pub line: usize,
+}
+
+/// Locate the `#[cite]` attribute being expanded, from the span of its invocation
+///
+/// Returns `None` where the compiler does not tell proc macros where they are invoked.
+pub fn locate() -> Option<Location> {
+	let root = std::env::var_os("GITHUB_WORKSPACE")
+		.map(PathBuf::from)
+		.or_else(|| std::env::current_dir().ok())?;
+	locate_in(&root)
+}
+
+/// Locate the `#[cite]` attribute being expanded, relative to the given root
+pub fn locate_in(root: &Path) -> Option<Location> {
+	let span = proc_macro2::Span::call_site();
+	location_in(root, &span.local_file()?, span.start().line)
+}
+
+/// Locate a line of a file relative to the root; line 0 is the compiler not knowing the line
+fn location_in(root: &Path, file: &Path, line: usize) -> Option<Location> {
+	if line == 0 {
+		return None;
+	}
+	// Files of the crates being built are relative to the directory the compiler runs in
+	let file = std::env::current_dir()
+		.map(|dir| dir.join(file))
+		.unwrap_or_else(|_| file.into());
+	let file = file.strip_prefix(root).unwrap_or(&file).to_string_lossy().replace('\\', "/");
+	Some(Location { file, line })
+}
+
+/// Print the workflow command for a citation that failed validation, if any
+pub fn emit(outcome: &MacroValidationOutcome) {
+	let _ = emit_to(&mut std::io::stdout(), outcome, locate().as_ref());
+}
+
+fn emit_to(
+	out: &mut impl Write,
+	outcome: &MacroValidationOutcome,
+	location: Option<&Location>,
+) -> std::io::Result<()> {
+	let Some(message) = outcome.message.as_deref().filter(|_| !outcome.result.is_valid()) else {
+		return Ok(());
+	};
+	let command = if outcome.should_fail_compilation() { "error" } else { "warning" };
+	writeln!(out, "{}", workflow_command(command, location, message))
+}
+
+/// Render a workflow command such as `::warning file=src/lib.rs,line=3,title=...::message`
+fn workflow_command(command: &str, location: Option<&Location>, message: &str) -> String {
+	let mut properties = Vec::new();
+	if let Some(location) = location {
+		properties.push(format!("file={}", escape_property(&location.file)));
+		properties.push(format!("line={}", location.line));
+	}
+	properties.push(format!("title={}", escape_property("Citation drift")));
+	format!("::{} {}::{}", command, properties.join(","), escape_data(message))
+}
+
+/// Escape the message of a workflow command, which must fit on one line
+fn escape_data(data: &str) -> String {
+	data.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escape a property of a workflow command, which additionally must not contain `:` or `,`
+fn escape_property(property: &str) -> String {
+	escape_data(property).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::validation::execute_mock_source_validation;
+	use cite_core::{mock_source_changed, mock_source_same};
+	use cite_core::{CitationAnnotation, CitationBehavior, CitationGlobal, CitationLevel};
+
+	fn outcome(source: cite_core::MockSource, level: CitationLevel) -> MacroValidationOutcome {
+		let behavior =
+			CitationBehavior::new(level, CitationAnnotation::Any, CitationGlobal::Lenient);
+		execute_mock_source_validation(source, &behavior, None)
+	}
+
+	#[test]
+	fn test_drift_is_emitted_as_workflow_command() {
+		let location = Location { file: "src/lib.rs".to_string(), line: 3 };
+		let drifted = outcome(mock_source_changed("old\n", "new\n"), CitationLevel::Warn);
+
+		let mut stdout = Vec::new();
+		emit_to(&mut stdout, &drifted, Some(&location)).unwrap();
+		let stdout = String::from_utf8(stdout).unwrap();
+		assert!(
+			stdout.starts_with("::warning file=src/lib.rs,line=3,title=Citation drift::"),
+			"{}",
+			stdout
+		);
+		// The whole message is on one line
+		assert!(stdout.contains("Citation content has changed!"));
+		assert!(stdout.contains("%0A"));
+		assert_eq!(stdout.lines().count(), 1);
+
+		let failed = outcome(mock_source_changed("old\n", "new\n"), CitationLevel::Error);
+		let mut stdout = Vec::new();
+		emit_to(&mut stdout, &failed, None).unwrap();
+		assert!(String::from_utf8(stdout).unwrap().starts_with("::error title=Citation drift::"));
+
+		// Valid citations print nothing
+		let mut stdout = Vec::new();
+		emit_to(&mut stdout, &outcome(mock_source_same("content"), CitationLevel::Error), None)
+			.unwrap();
+		assert!(stdout.is_empty());
+	}
+
+	#[test]
+	fn test_escaping() {
+		assert_eq!(escape_data("50% done\r\nnext"), "50%25 done%0D%0Anext");
+		assert_eq!(escape_property("a:b,c"), "a%3Ab%2Cc");
+	}
+
+	#[test]
+	fn test_location_relative_to_root() {
+		let root = std::env::current_dir().unwrap();
+		let location = location_in(&root, Path::new("crate/src/lib.rs"), 5);
+		assert_eq!(location, Some(Location { file: "crate/src/lib.rs".to_string(), line: 5 }));
+
+		let location = location_in(&root, &root.join("src").join("lib.rs"), 12);
+		assert_eq!(location, Some(Location { file: "src/lib.rs".to_string(), line: 12 }));
+
+		// Files outside of the root keep their path, and an unknown line is no location
+		let outside = location_in(Path::new("/workspace"), Path::new("/registry/lib.rs"), 3);
+		assert_eq!(outside.map(|location| location.file), Some("/registry/lib.rs".to_string()));
+		assert_eq!(location_in(&root, Path::new("src/lib.rs"), 0), None);
+	}
+}
@@ -44,9 +44,21 @@
 //! ## 4. Environment Integration
 //!
 //! Citations can be controlled globally via environment variables:
-//! - `CITE_LEVEL`: Set global error/warning behavior
+//! - `CITE_LEVEL`: Set global error/warning behavior, over `cite.toml` and `cite.local.toml`
 //! - `CITE_ANNOTATION`: Control annotation output format
 //! - `CITE_GLOBAL`: Set strict vs lenient mode
+//! - `CITE_GHA`: Also print drift as GitHub Actions annotations
+//! - `CITE_AGGREGATE`: Fail with one error for all drifted citations, listing the rest as warnings
+//! - `CITE_REQUIRE_COMMITTED`: Fail when validation leaves uncommitted references in `.cite`
+//! - `CITE_DRYRUN`: Print what each citation resolved to instead of validating it
+//! - `CITE_OFFLINE`: Validate without network access, following `CITE_OFFLINE_POLICY`
+//! - `CITE_EMPTY_REFERENCE`: Fail instead of warning when a reference is captured empty
+//! - `CITE_CHANGE_LEVELS`: Warn about minor changes and fail on substantive ones
+//! - `CITE_FOOTNOTE_FILE`: Collect footnotes in a Markdown file instead of the docs
+//! - `CITE_RENDER_COMMAND`: Render pages of `render = true` citations with a headless browser
+//! - `CITE_HOST_RPS`: Limit the requests per second sent to each host
+//! - `CITE_PROFILE`: Select the environment the targets of `cite.toml` resolve in
+//! - `CITE_DIFF_DIR`: Archive the diff of each drifted citation as a patch file
 //!
 //! This enables different behavior in development vs CI vs production builds.
 //!
@@ -108,10 +120,11 @@
 //!
 //! ## Error Propagation
 //!
-//! Validation results are converted to compile-time diagnostics:
-//! - `Ok(None)`: Validation passed, no output
-//! - `Ok(Some(msg))`: Validation failed, emit warning
-//! - `Err(msg)`: Validation failed, emit error and fail compilation
+//! Validation produces a `MacroValidationOutcome` (the validation result, the rendered
+//! message and the diff stats), which is converted to compile-time diagnostics:
+//! - No message: Validation passed (or is silent), no output
+//! - A message that does not fail compilation: emit warning
+//! - A message that fails compilation: emit error
 //!
 //! The specific behavior depends on the `level` parameter and environment variables.
 
@@ -136,12 +149,18 @@ fn next_source_id() -> usize {
 	SOURCE_COUNTER.fetch_add(1, Ordering::SeqCst)
 }
 
+mod aggregate;
 mod annotation;
+mod config;
+mod diff_dir;
 mod documentation;
+mod dryrun;
 mod extraction;
+mod gha;
 mod level;
+mod outcome;
 mod prevalidation;
-mod sources;
+mod record;
 mod validation;
 
 /// Mock source parsing and construction
@@ -176,8 +195,19 @@ pub fn cite(args: TokenStream, input: TokenStream) -> TokenStream {
 	// Parse into key-value map
 	let mut kwargs = std::collections::HashMap::new();
 
+	// Translate a legacy source construction, e.g. `MockSource::same("content")`, into kwargs
+	let legacy_kwargs = args_vec.first().and_then(extraction::legacy_source_kwargs);
+	let deprecation = legacy_kwargs.as_ref().map(|legacy_kwargs| {
+		format!(
+			"The legacy citation syntax is deprecated, use #[cite({})] instead",
+			extraction::keyword_syntax(legacy_kwargs)
+		)
+	});
+
 	// Parse the first argument as the source type
-	if let Some(source_type) = extraction::extract_source_type(&args_vec) {
+	if let Some(legacy_kwargs) = legacy_kwargs {
+		kwargs.extend(legacy_kwargs);
+	} else if let Some(source_type) = extraction::extract_source_type(&args_vec) {
 		kwargs.insert("src".to_string(), serde_json::Value::String(source_type));
 	}
 
@@ -215,9 +245,19 @@ pub fn cite(args: TokenStream, input: TokenStream) -> TokenStream {
 		}
 	}
 
+	// Resolve a target from cite.toml into the parameters of the active profile
+	if let Err(err) = config::resolve_target(&mut kwargs) {
+		return syn::Error::new(proc_macro2::Span::call_site(), err).to_compile_error().into();
+	}
+
+	// Resolve a normalizer profile from cite.toml into the chain it names
+	if let Err(err) = config::resolve_normalizer(&mut kwargs) {
+		return syn::Error::new(proc_macro2::Span::call_site(), err).to_compile_error().into();
+	}
+
 	// Validate and create citation
 	let citation = match prevalidation::validate_with_kwargs(&kwargs, &args_vec) {
-		Ok(citation) => citation,
+		Ok(citation) => Citation { deprecation, ..citation },
 		Err(err) => return err.to_compile_error().into(),
 	};
 
@@ -263,6 +303,12 @@ struct Citation {
 	reason: Option<String>,
 	level: Option<String>,
 	annotation: Option<String>,
+	deadline: Option<cite_core::CitationDeadline>,
+	reverify: Option<cite_core::ReverifyInterval>,
+	embed: bool,
+	requires_feature: Option<cite_core::RequiredFeature>,
+	/// Set when the citation uses the legacy syntax, warning to migrate it
+	deprecation: Option<String>,
 
 	// For kwargs syntax, store the parsed kwargs
 	kwargs: Option<std::collections::HashMap<String, serde_json::Value>>,
@@ -277,12 +323,39 @@ impl Citation {
 		}
 		Err("src not found".to_string())
 	}
+
+	/// Get the level the citation overrides the configured level with, if any
+	fn level_override(&self) -> Option<cite_core::CitationLevel> {
+		use cite_core::CitationLevel;
+
+		match self.level.as_deref()? {
+			"ERROR" | "error" => Some(CitationLevel::Error),
+			"WARN" | "warn" => Some(CitationLevel::Warn),
+			"SILENT" | "silent" => Some(CitationLevel::Silent),
+			_ => None,
+		}
+	}
+
+	/// Get the level drift of the citation is reported at, or `None` if it is not validated
+	///
+	/// Configuration that fails to load is reported by the validation, and leaves the features
+	/// to decide here.
+	fn reported_level(&self) -> Option<cite_core::CitationLevel> {
+		let behavior =
+			config::behavior().unwrap_or_else(|_| cite_core::CitationBehavior::from_features());
+		behavior.citation_level(
+			self.level_override(),
+			self.deadline.as_ref(),
+			self.requires_feature.as_ref(),
+		)
+	}
 }
 
 /// Handle citation on a function
 fn handle_function_citation(citation: Citation, mut item_fn: ItemFn) -> proc_macro2::TokenStream {
 	// Generate validation code that runs at compile time
-	let (warning_text, validation_code) = generate_validation_code(&citation);
+	let item = format!("fn {}", item_fn.sig.ident);
+	let (warning_text, validation_code) = generate_validation_code(&citation, &item);
 
 	// Insert the validation as a const block at the beginning of the function
 	let validation_stmt: syn::Stmt = parse_quote! {
@@ -292,7 +365,7 @@ fn handle_function_citation(citation: Citation, mut item_fn: ItemFn) -> proc_mac
 	item_fn.block.stmts.insert(0, validation_stmt);
 
 	// Add citation footnote to doc comments
-	documentation::add_citation_footnote_to_item(&mut item_fn.attrs, &citation, warning_text);
+	documentation::document_item(&mut item_fn.attrs, &citation, warning_text, &item);
 
 	quote! { #item_fn }
 }
@@ -302,14 +375,15 @@ fn handle_struct_citation(
 	citation: Citation,
 	mut item_struct: ItemStruct,
 ) -> proc_macro2::TokenStream {
-	let (warning_text, validation_code) = generate_validation_code(&citation);
+	let item = format!("struct {}", item_struct.ident);
+	let (warning_text, validation_code) = generate_validation_code(&citation, &item);
 	let validation_const_name = syn::Ident::new(
 		&format!("_CITE_VALIDATION_{}", next_validation_id()),
 		proc_macro2::Span::call_site(),
 	);
 
 	// Add citation footnote to doc comments
-	documentation::add_citation_footnote_to_item(&mut item_struct.attrs, &citation, warning_text);
+	documentation::document_item(&mut item_struct.attrs, &citation, warning_text, &item);
 
 	quote! {
 		#item_struct
@@ -323,14 +397,15 @@ fn handle_trait_citation(
 	citation: Citation,
 	mut item_trait: ItemTrait,
 ) -> proc_macro2::TokenStream {
-	let (warning_text, validation_code) = generate_validation_code(&citation);
+	let item = format!("trait {}", item_trait.ident);
+	let (warning_text, validation_code) = generate_validation_code(&citation, &item);
 	let validation_const_name = syn::Ident::new(
 		&format!("_CITE_VALIDATION_{}", next_validation_id()),
 		proc_macro2::Span::call_site(),
 	);
 
 	// Add citation footnote to doc comments
-	documentation::add_citation_footnote_to_item(&mut item_trait.attrs, &citation, warning_text);
+	documentation::document_item(&mut item_trait.attrs, &citation, warning_text, &item);
 
 	quote! {
 		#item_trait
@@ -341,7 +416,14 @@ fn handle_trait_citation(
 
 /// Handle citation on an impl block
 fn handle_impl_citation(citation: Citation, mut item_impl: ItemImpl) -> proc_macro2::TokenStream {
-	let (warning_text, validation_code) = generate_validation_code(&citation);
+	let item = match item_impl.self_ty.as_ref() {
+		syn::Type::Path(path) => match path.path.segments.last() {
+			Some(segment) => format!("impl {}", segment.ident),
+			None => "impl".to_string(),
+		},
+		_ => "impl".to_string(),
+	};
+	let (warning_text, validation_code) = generate_validation_code(&citation, &item);
 
 	// Use counter for unique const name
 	let validation_const_name = syn::Ident::new(
@@ -350,7 +432,7 @@ fn handle_impl_citation(citation: Citation, mut item_impl: ItemImpl) -> proc_mac
 	);
 
 	// Add citation footnote to doc comments
-	documentation::add_citation_footnote_to_item(&mut item_impl.attrs, &citation, warning_text);
+	documentation::document_item(&mut item_impl.attrs, &citation, warning_text, &item);
 
 	quote! {
 		#item_impl
@@ -361,14 +443,15 @@ fn handle_impl_citation(citation: Citation, mut item_impl: ItemImpl) -> proc_mac
 
 /// Handle citation on a module
 fn handle_mod_citation(citation: Citation, mut item_mod: ItemMod) -> proc_macro2::TokenStream {
-	let (warning_text, validation_code) = generate_validation_code(&citation);
+	let item = format!("mod {}", item_mod.ident);
+	let (warning_text, validation_code) = generate_validation_code(&citation, &item);
 	let validation_const_name = syn::Ident::new(
 		&format!("_CITE_VALIDATION_{}", next_validation_id()),
 		proc_macro2::Span::call_site(),
 	);
 
 	// Add citation footnote to doc comments
-	documentation::add_citation_footnote_to_item(&mut item_mod.attrs, &citation, warning_text);
+	documentation::document_item(&mut item_mod.attrs, &citation, warning_text, &item);
 
 	quote! {
 		#item_mod
@@ -379,7 +462,8 @@ fn handle_mod_citation(citation: Citation, mut item_mod: ItemMod) -> proc_macro2
 
 /// Handle citation on an enum
 fn handle_enum_citation(citation: Citation, mut item_enum: ItemEnum) -> proc_macro2::TokenStream {
-	let (warning_text, validation_code) = generate_validation_code(&citation);
+	let item = format!("enum {}", item_enum.ident);
+	let (warning_text, validation_code) = generate_validation_code(&citation, &item);
 
 	let validation_const_name = syn::Ident::new(
 		&format!("_CITE_VALIDATION_{}", next_validation_id()),
@@ -387,7 +471,7 @@ fn handle_enum_citation(citation: Citation, mut item_enum: ItemEnum) -> proc_mac
 	);
 
 	// Add citation footnote to doc comments
-	documentation::add_citation_footnote_to_item(&mut item_enum.attrs, &citation, warning_text);
+	documentation::document_item(&mut item_enum.attrs, &citation, warning_text, &item);
 
 	quote! {
 		#item_enum
@@ -398,7 +482,9 @@ fn handle_enum_citation(citation: Citation, mut item_enum: ItemEnum) -> proc_mac
 
 /// Generate validation code that executes the user's source expression with the real API
 /// Returns (warning_text, validation_code)
-fn generate_validation_code(citation: &Citation) -> (String, proc_macro2::TokenStream) {
+///
+/// The item, e.g. `fn parse`, names the citation in aggregated drift reports.
+fn generate_validation_code(citation: &Citation, item: &str) -> (String, proc_macro2::TokenStream) {
 	let source_expr = &citation.source_expr;
 
 	let reason_comment = if let Some(_reason) = &citation.reason {
@@ -423,15 +509,30 @@ fn generate_validation_code(citation: &Citation) -> (String, proc_macro2::TokenS
 	);
 
 	// Actually try to perform validation during macro expansion
-	let validation_result = attempt_macro_expansion_validation(citation);
-	let warning_text = match &validation_result {
-		Ok(Some(warning)) => warning.clone(),
-		_ => String::new(),
-	};
+	let mut outcome = attempt_macro_expansion_validation(citation);
+
+	// Record the citation for cite-review and cargo cite, with the severity of its drift
+	record::record(citation, outcome.change);
+	if gha::is_enabled() {
+		gha::emit(&outcome);
+	}
+	if let Some(dir) = diff_dir::path() {
+		// Archiving is best effort, so that it never fails a build the citation itself passes
+		if let Err(_error) = diff_dir::archive(&dir, &outcome) {
+			cite_core::trace_event!(error = %_error, dir = %dir.display(), "failed to archive drift");
+		}
+	}
+	if aggregate::is_enabled() {
+		if let Ok(mut aggregators) = aggregate::DriftAggregator::global().lock() {
+			let krate = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+			outcome = aggregators.entry(krate).or_default().aggregate(outcome, item);
+		}
+	}
+	let warning_text = outcome.warning().unwrap_or_default().to_string();
 
-	// Generate code based on the validation result from macro expansion
-	let validation_code = match validation_result {
-		Ok(None) => {
+	// Generate code based on the validation outcome from macro expansion
+	let validation_code = match (outcome.should_fail_compilation(), outcome.message) {
+		(false, None) => {
 			// Validation passed
 			if is_keyword_syntax {
 				quote! {
@@ -453,7 +554,7 @@ fn generate_validation_code(citation: &Citation) -> (String, proc_macro2::TokenS
 				}
 			}
 		}
-		Ok(Some(warning_msg)) => {
+		(false, Some(warning_msg)) => {
 			// Validation failed but should only warn
 			if is_keyword_syntax {
 				quote! {
@@ -479,8 +580,9 @@ fn generate_validation_code(citation: &Citation) -> (String, proc_macro2::TokenS
 				}
 			}
 		}
-		Err(error_msg) => {
+		(true, error_msg) => {
 			// Validation failed and should error
+			let error_msg = error_msg.unwrap_or_default();
 			let error_tokens =
 				syn::Error::new(proc_macro2::Span::call_site(), error_msg).to_compile_error();
 			if is_keyword_syntax {
@@ -501,53 +603,242 @@ fn generate_validation_code(citation: &Citation) -> (String, proc_macro2::TokenS
 		}
 	};
 
-	(warning_text, validation_code)
+	// Warn about the legacy syntax whatever the outcome, so that passing citations migrate too
+	let deprecation = match &citation.deprecation {
+		Some(note) => quote! {
+			#[deprecated(note = #note)]
+			const fn _cite_legacy_syntax() {}
+			const _: () = _cite_legacy_syntax();
+		},
+		None => quote! {},
+	};
+
+	(warning_text, quote! { #deprecation #validation_code })
 }
 
 /// Attempt to perform validation during macro expansion
 ///
 /// This is the key function that tries to execute the user's source expression
-/// during macro expansion and return the validation result.
-fn attempt_macro_expansion_validation(
-	citation: &Citation,
-) -> std::result::Result<Option<String>, String> {
-	use cite_core::{CitationBehavior, CitationLevel};
+/// during macro expansion and return the validation outcome.
+fn attempt_macro_expansion_validation(citation: &Citation) -> outcome::MacroValidationOutcome {
+	use cite_core::{CitationDeadline, ValidateWhen};
+	use outcome::MacroValidationOutcome;
+
+	// A dry run only reports what the citation resolved to, never failing the build
+	if dryrun::is_enabled() {
+		dryrun::emit(citation);
+		return MacroValidationOutcome::valid();
+	}
 
 	// Parse level override if provided
-	let level_override = if let Some(level_str) = &citation.level {
-		match level_str.as_str() {
-			"ERROR" | "error" => Some(CitationLevel::Error),
-			"WARN" | "warn" => Some(CitationLevel::Warn),
-			"SILENT" | "silent" => Some(CitationLevel::Silent),
-			_ => None,
+	let level_override = citation.level_override();
+
+	// Load behavior from the feature flags, the configuration and the environment
+	let behavior = match config::behavior() {
+		Ok(behavior) => behavior,
+		Err(error) => return MacroValidationOutcome::error(error),
+	};
+
+	// Check annotation requirements first
+	let annotation_result = match annotation::check_annotation_requirements(citation, &behavior) {
+		Ok(annotation_result) => annotation_result,
+		Err(error) => return MacroValidationOutcome::error(error),
+	};
+
+	// Lint copy-pasted reasons across citations, reported alongside annotation issues
+	let annotation_result = match (annotation_result, lint_duplicate_reason(citation)) {
+		(Some(annotation_result), Some(lint_result)) => {
+			Some(format!("{}\n{}", annotation_result, lint_result))
 		}
-	} else {
-		None
+		(annotation_result, lint_result) => annotation_result.or(lint_result),
 	};
 
-	// Load behavior from feature flags
-	let behavior = CitationBehavior::from_features();
+	// Skip fetching and comparing sources when validation is gated to CI or release builds;
+	// the macro's own debug assertions follow the profile being built
+	let validate_when = match ValidateWhen::from_env() {
+		Ok(validate_when) => validate_when,
+		Err(error) => return MacroValidationOutcome::error(error),
+	};
+	if !validate_when.should_validate(ValidateWhen::is_ci(), !cfg!(debug_assertions)) {
+		return MacroValidationOutcome::valid().with_note(annotation_result);
+	}
 
-	// Check annotation requirements first
-	let annotation_result = annotation::check_annotation_requirements(citation, &behavior)?;
+	// Content tied to a disabled cargo feature is not validated; its footnote is still rendered
+	if citation.requires_feature.as_ref().is_some_and(|feature| !feature.is_enabled()) {
+		return MacroValidationOutcome::valid().with_note(annotation_result);
+	}
+
+	// Drift past the citation's deadline is an error regardless of the configured level
+	let today = CitationDeadline::today();
+	let passed_deadline = citation.deadline.filter(|deadline| deadline.has_passed(today));
+	let source_behavior = match passed_deadline {
+		Some(deadline) => deadline.apply(behavior, today),
+		None => behavior,
+	};
 
 	// Try to handle common source patterns
-	if let Some(result) =
-		validation::try_execute_source_expression(citation, &behavior, level_override)
+	if let Some(outcome) =
+		validation::try_execute_source_expression(citation, &source_behavior, level_override)
 	{
-		return match (result, annotation_result) {
-			// if also an annotation result, join them together
-			(Ok(Some(result)), Some(annotation_result)) => {
-				Ok(Some(format!("{}\n{}", result, annotation_result)))
-			}
-			(Ok(Some(result)), None) => Ok(Some(result)),
-			(Ok(None), Some(annotation_result)) => Ok(Some(annotation_result)),
-			(Ok(None), None) => Ok(None),
-			(Err(error), _) => Err(error),
-		};
+		let deadline_note =
+			passed_deadline.filter(|_| !outcome.result.is_valid()).map(|deadline| {
+				format!("Citation deadline {} has passed; drift is now an error", deadline)
+			});
+		let outcome = outcome.with_note(deadline_note);
+
+		// Errors are reported on their own; otherwise join in the annotation result
+		if outcome.should_fail_compilation() {
+			return outcome;
+		}
+
+		// A reference written or refreshed by the validation means the committed one is stale
+		let cache_builder = cite_cache::CacheBuilder::default();
+		if let Some(error) = check_required_committed_references(&cache_builder.cite_dir) {
+			return MacroValidationOutcome::error(error);
+		}
+		return outcome.with_note(annotation_result);
 	}
 
 	// If we can't execute the source during macro expansion, assume it's valid
 	// The user can always add explicit validation later
-	Ok(None)
+	MacroValidationOutcome::valid()
+}
+
+/// Check the committed references where `CITE_REQUIRE_COMMITTED` requires it for this build
+fn check_required_committed_references(cite_dir: &std::path::Path) -> Option<String> {
+	use cite_core::{RequireCommitted, ValidateWhen};
+
+	match RequireCommitted::from_env() {
+		Ok(require_committed) if require_committed.should_check(ValidateWhen::is_ci()) => {
+			check_committed_references(cite_dir)
+		}
+		Ok(_) => None,
+		Err(error) => Some(error),
+	}
+}
+
+/// Check that the references in the cite directory are the committed ones, describing the
+/// references to commit otherwise
+fn check_committed_references(cite_dir: &std::path::Path) -> Option<String> {
+	match cite_cache::committed::uncommitted_paths(cite_dir) {
+		Ok(paths) if paths.is_empty() => None,
+		Ok(paths) => Some(format!(
+			"Citation references differ from the committed ones; you must commit the updated references:\n{}",
+			paths.iter().map(|path| format!("  {}", path)).collect::<Vec<_>>().join("\n")
+		)),
+		Err(error) => Some(error.to_string()),
+	}
+}
+
+/// Record the citation's reason in the process-wide registry, warning if it is reused by too
+/// many citations (only with the `pedantic` feature)
+fn lint_duplicate_reason(citation: &Citation) -> Option<String> {
+	use cite_core::ReasonRegistry;
+
+	if !ReasonRegistry::lint_enabled() {
+		return None;
+	}
+
+	let reason = citation.reason.as_ref()?;
+	let mut registry = ReasonRegistry::global().lock().ok()?;
+	registry.record_reason(reason)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn citation(kwargs: serde_json::Value) -> Citation {
+		let kwargs = serde_json::from_value(kwargs).unwrap();
+		prevalidation::validate_with_kwargs(&kwargs, &[]).unwrap()
+	}
+
+	#[test]
+	fn test_requires_feature_gates_validation() {
+		let citation = citation(serde_json::json!({
+			"src": "mock",
+			"changed": ["old", "new"],
+			"reason": "Only relevant with TLS enabled",
+			"requires_feature": "cite-gate-test",
+		}));
+		let env_var = "CARGO_FEATURE_CITE_GATE_TEST";
+
+		// Skipped while the feature is disabled
+		std::env::remove_var(env_var);
+		assert!(attempt_macro_expansion_validation(&citation).result.is_valid());
+
+		// Validated, reporting the drift, once it is enabled
+		std::env::set_var(env_var, "1");
+		let outcome = attempt_macro_expansion_validation(&citation);
+		std::env::remove_var(env_var);
+		assert!(!outcome.result.is_valid());
+		assert!(outcome.message.is_some());
+
+		// The footnote is rendered either way
+		let mut attrs = Vec::new();
+		documentation::add_citation_footnote_to_item(&mut attrs, &citation, String::new());
+		assert!(!attrs.is_empty());
+	}
+
+	#[test]
+	fn test_uncommitted_references_fail_validation() {
+		use cite_cache::committed::git;
+
+		let repo = tempfile::TempDir::new().unwrap();
+		let cite_dir = repo.path().join(".cite");
+		std::fs::create_dir_all(cite_dir.join("cache")).unwrap();
+		std::fs::write(cite_dir.join("cache").join("http_docs.json"), "{}").unwrap();
+		git(repo.path(), &["init", "-q"]).unwrap();
+		git(repo.path(), &["add", "-A"]).unwrap();
+		git(repo.path(), &["commit", "-qm", "Add references"]).unwrap();
+		assert_eq!(check_committed_references(&cite_dir), None);
+
+		// CI refreshed the reference after the content drifted
+		std::fs::write(cite_dir.join("cache").join("http_docs.json"), "{\"content\": 1}").unwrap();
+		cite_cache::committed::forget(&cite_dir);
+		let error = check_committed_references(&cite_dir).unwrap();
+		assert!(error.contains("you must commit the updated references"), "{}", error);
+		assert!(error.contains(".cite/cache/http_docs.json"));
+
+		// Only enforced where the environment requires it
+		let ci = std::env::var_os("CI");
+		std::env::set_var("CITE_REQUIRE_COMMITTED", "ci");
+		std::env::set_var("CI", "false");
+		assert_eq!(check_required_committed_references(&cite_dir), None);
+		std::env::set_var("CI", "true");
+		let enforced = check_required_committed_references(&cite_dir);
+		std::env::set_var("CITE_REQUIRE_COMMITTED", "sometimes");
+		let invalid = check_required_committed_references(&cite_dir);
+		std::env::remove_var("CITE_REQUIRE_COMMITTED");
+		match ci {
+			Some(ci) => std::env::set_var("CI", ci),
+			None => std::env::remove_var("CI"),
+		}
+		assert_eq!(enforced, Some(error));
+		assert!(invalid.is_some_and(|error| error.contains("CITE_REQUIRE_COMMITTED")));
+	}
+
+	#[test]
+	fn test_requires_feature_must_be_a_feature_name() {
+		let kwargs =
+			serde_json::json!({ "src": "mock", "same": "a", "requires_feature": "tls ssl" });
+		let kwargs = serde_json::from_value(kwargs).unwrap();
+		assert!(prevalidation::validate_with_kwargs(&kwargs, &[]).is_err());
+	}
+
+	#[test]
+	fn test_git_citation_without_current_revision() {
+		// Pinned to a commit, watching the default branch
+		let citation = citation(serde_json::json!({
+			"src": "git",
+			"remote": "https://github.com/ramate-io/cite",
+			"ref_rev": "94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			"path": "README.md",
+		}));
+		let kwargs = citation.kwargs.unwrap();
+		match cite_sources::AnySource::from_kwargs("git", &kwargs).unwrap() {
+			cite_sources::AnySource::Git(source) => assert_eq!(source.current_revision, None),
+			_ => panic!("expected a git source"),
+		}
+	}
 }
@@ -0,0 +1,189 @@
+use crate::diff_dir::Patch;
+use cite_core::{ChangeClass, CitationLevel, CitationValidationResult, DiffStats, Id};
+
+/// Structured result of validating a citation during macro expansion
+///
+/// Keeps the validation result, the rendered diagnostic and the size of the diff together, so
+/// that code generation and reporting do not have to recover them from strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroValidationOutcome {
+	/// The validation result of the citation
+	pub result: CitationValidationResult,
+	/// The rendered diagnostic, if the citation should be reported or fail compilation
+	pub message: Option<String>,
+	/// The size of the diff, if the source provides one
+	pub diff_stats: Option<DiffStats>,
+	/// The diff as a patch file, if the citation drifted and the source renders one
+	pub patch: Option<Patch>,
+	/// The severity of the drift, if the citation drifted and the source classifies it
+	pub change: Option<ChangeClass>,
+}
+
+impl MacroValidationOutcome {
+	/// A valid citation with nothing to report
+	pub fn valid() -> Self {
+		Self {
+			result: CitationValidationResult::Valid,
+			message: None,
+			diff_stats: None,
+			patch: None,
+			change: None,
+		}
+	}
+
+	/// An invalid citation; the message is only kept if the result is reported or fails
+	/// compilation
+	pub fn invalid(result: CitationValidationResult, message: String) -> Self {
+		let message =
+			(result.should_fail_compilation() || result.should_report()).then_some(message);
+		Self { result, message, diff_stats: None, patch: None, change: None }
+	}
+
+	/// An error that fails compilation regardless of behavior, e.g. an unreachable source
+	pub fn error(message: String) -> Self {
+		Self::from_level_output(Err(message))
+	}
+
+	/// Convert the output of [crate::level::level_output]
+	pub fn from_level_output(output: Result<Option<String>, String>) -> Self {
+		let (level, message) = match output {
+			Ok(None) => return Self::valid(),
+			Ok(Some(message)) => (CitationLevel::Warn, message),
+			Err(message) => (CitationLevel::Error, message),
+		};
+
+		Self {
+			result: CitationValidationResult::Invalid {
+				level,
+				should_fail_compilation: level == CitationLevel::Error,
+				should_report: true,
+			},
+			message: Some(message),
+			diff_stats: None,
+			patch: None,
+			change: None,
+		}
+	}
+
+	/// Set the size of the diff
+	pub fn with_diff_stats(mut self, diff_stats: DiffStats) -> Self {
+		self.diff_stats = Some(diff_stats);
+		self
+	}
+
+	/// Set the patch of the diff of the cited source, see [cite_core::Diff::to_patch_file]
+	pub fn with_patch(mut self, id: &Id, patch: Option<String>) -> Self {
+		self.patch = patch.map(|content| Patch { id: id.clone(), content });
+		self
+	}
+
+	/// Set the severity of the drift, see [cite_core::Diff::describe_change]
+	pub fn with_change(mut self, change: Option<ChangeClass>) -> Self {
+		self.change = change;
+		self
+	}
+
+	/// Append a note (e.g. an annotation or lint warning) to the message
+	///
+	/// Notes are only ever warnings, so they do not change the validation result.
+	pub fn with_note(mut self, note: Option<String>) -> Self {
+		if let Some(note) = note {
+			self.message = Some(match self.message.take() {
+				Some(message) => format!("{}\n{}", message, note),
+				None => note,
+			});
+		}
+		self
+	}
+
+	/// Check if compilation should fail
+	pub fn should_fail_compilation(&self) -> bool {
+		self.result.should_fail_compilation()
+	}
+
+	/// Get the warning to emit, if the outcome warns rather than fails compilation
+	pub fn warning(&self) -> Option<&str> {
+		if self.should_fail_compilation() {
+			None
+		} else {
+			self.message.as_deref()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::validation::execute_mock_source_validation;
+	use cite_core::{mock_source_changed, mock_source_same};
+	use cite_core::{CitationAnnotation, CitationBehavior, CitationGlobal};
+
+	fn behavior(level: CitationLevel) -> CitationBehavior {
+		CitationBehavior::new(level, CitationAnnotation::Any, CitationGlobal::Lenient)
+	}
+
+	#[test]
+	fn test_valid_outcome() {
+		let outcome = execute_mock_source_validation(
+			mock_source_same("content"),
+			&behavior(CitationLevel::Error),
+			None,
+		);
+
+		assert_eq!(outcome, MacroValidationOutcome::valid());
+		assert!(!outcome.should_fail_compilation());
+		assert_eq!(outcome.warning(), None);
+	}
+
+	#[test]
+	fn test_warn_outcome() {
+		let outcome = execute_mock_source_validation(
+			mock_source_changed("old\n", "new\n"),
+			&behavior(CitationLevel::Warn),
+			None,
+		);
+
+		assert_eq!(outcome.result.level(), Some(CitationLevel::Warn));
+		assert!(!outcome.should_fail_compilation());
+		assert!(outcome.warning().unwrap().contains("Citation content has changed!"));
+		assert_eq!(outcome.diff_stats, Some(DiffStats { additions: 1, removals: 1 }));
+	}
+
+	#[test]
+	fn test_error_outcome() {
+		let outcome = execute_mock_source_validation(
+			mock_source_changed("old\n", "new\n"),
+			&behavior(CitationLevel::Warn),
+			Some(CitationLevel::Error),
+		);
+
+		assert_eq!(outcome.result.level(), Some(CitationLevel::Error));
+		assert!(outcome.should_fail_compilation());
+		assert_eq!(outcome.warning(), None);
+		assert!(outcome.message.unwrap().contains("Referenced: old"));
+	}
+
+	#[test]
+	fn test_silent_outcome_has_no_message() {
+		let outcome = execute_mock_source_validation(
+			mock_source_changed("old\n", "new\n"),
+			&behavior(CitationLevel::Silent),
+			None,
+		);
+
+		assert!(!outcome.result.is_valid());
+		assert_eq!(outcome.message, None);
+	}
+
+	#[test]
+	fn test_notes_are_appended_as_warnings() {
+		let outcome = MacroValidationOutcome::valid().with_note(Some("reused reason".to_string()));
+		assert!(outcome.result.is_valid());
+		assert_eq!(outcome.warning(), Some("reused reason"));
+
+		let outcome = MacroValidationOutcome::from_level_output(Err("changed".to_string()))
+			.with_note(Some("reused reason".to_string()));
+		assert!(outcome.should_fail_compilation());
+		assert_eq!(outcome.message.as_deref(), Some("changed\nreused reason"));
+	}
+}
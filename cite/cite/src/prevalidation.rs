@@ -1,8 +1,5 @@
-use crate::sources::{git, http, mock};
-use cite_core::ui::SourceUi;
-use cite_core::MockSource;
-use cite_git::GitSource;
-use cite_http::HttpMatch;
+use cite_core::{CitationDeadline, RequiredFeature, ReverifyInterval};
+use cite_sources::{is_citation_level_field, AnySource};
 use syn::Result;
 
 /// Find the span of a specific parameter in the args
@@ -22,73 +19,19 @@ fn find_param_span(args: &[syn::Expr], param_name: &str) -> proc_macro2::Span {
 	proc_macro2::Span::call_site()
 }
 
-/// Check if a key is a top-level citation field (always valid)
-fn is_citation_level_field(key: &str) -> bool {
-	matches!(key, "src" | "reason" | "level" | "annotation")
-}
-
-/// Validate kwargs for git source and check for invalid attributes
-fn validate_git_kwargs(
-	kwargs: &std::collections::HashMap<String, serde_json::Value>,
-	args: &[syn::Expr],
-) -> Result<()> {
-	// First try to construct the source to validate required fields
-	git::try_get_git_source_from_kwargs(kwargs)
-		.map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
-
-	// Then check for invalid attributes
-	for key in kwargs.keys() {
-		if !is_citation_level_field(key)
-			&& !<GitSource as SourceUi<_, _, _>>::is_valid_attr_key(key)
-		{
-			return Err(syn::Error::new(
-				find_param_span(args, key),
-				format!("Unknown citation attribute: {}", key),
-			));
-		}
-	}
-
-	Ok(())
-}
-
-/// Validate kwargs for http source and check for invalid attributes
-fn validate_http_kwargs(
-	kwargs: &std::collections::HashMap<String, serde_json::Value>,
-	args: &[syn::Expr],
-) -> Result<()> {
-	// First try to construct the source to validate required fields
-	http::try_get_http_source_from_kwargs(kwargs)
-		.map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
-
-	// Then check for invalid attributes
-	for key in kwargs.keys() {
-		if !is_citation_level_field(key)
-			&& !<HttpMatch as SourceUi<_, _, _>>::is_valid_attr_key(key)
-		{
-			return Err(syn::Error::new(
-				find_param_span(args, key),
-				format!("Unknown citation attribute: {}", key),
-			));
-		}
-	}
-
-	Ok(())
-}
-
-/// Validate kwargs for mock source and check for invalid attributes
-fn validate_mock_kwargs(
+/// Validate kwargs for a source and check for invalid attributes
+fn validate_source_kwargs(
+	src: &str,
 	kwargs: &std::collections::HashMap<String, serde_json::Value>,
 	args: &[syn::Expr],
 ) -> Result<()> {
 	// First try to construct the source to validate required fields
-	mock::try_get_mock_source_from_kwargs(kwargs)
-		.map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
+	AnySource::from_kwargs(src, kwargs)
+		.map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e.to_string()))?;
 
 	// Then check for invalid attributes
 	for key in kwargs.keys() {
-		if !is_citation_level_field(key)
-			&& !<MockSource as SourceUi<_, _, _>>::is_valid_attr_key(key)
-		{
+		if !is_citation_level_field(key) && !AnySource::is_valid_attr_key(src, key) {
 			return Err(syn::Error::new(
 				find_param_span(args, key),
 				format!("Unknown citation attribute: {}", key),
@@ -116,22 +59,88 @@ pub fn validate_with_kwargs(
 	let reason = kwargs.get("reason").and_then(|v| v.as_str()).map(|s| s.to_string());
 	let level = kwargs.get("level").and_then(|v| v.as_str()).map(|s| s.to_string());
 	let annotation = kwargs.get("annotation").and_then(|v| v.as_str()).map(|s| s.to_string());
+	let deadline = match kwargs.get("deadline") {
+		Some(value) => {
+			let deadline = value
+				.as_str()
+				.ok_or_else(|| "deadline must be a string".to_string())
+				.and_then(CitationDeadline::from_str);
+			Some(deadline.map_err(|e| syn::Error::new(find_param_span(args, "deadline"), e))?)
+		}
+		None => None,
+	};
+	let reverify = match kwargs.get("reverify_days") {
+		Some(value) => {
+			let span = find_param_span(args, "reverify_days");
+			if src_str != "http" {
+				return Err(syn::Error::new(
+					span,
+					"reverify_days is only supported for http citations, whose references are snapshots",
+				));
+			}
+			let reverify = match value {
+				serde_json::Value::String(s) => ReverifyInterval::from_str(s),
+				value => ReverifyInterval::from_str(&value.to_string()),
+			};
+			Some(reverify.map_err(|e| syn::Error::new(span, e))?)
+		}
+		None => None,
+	};
+	let embed = match kwargs.get("embed") {
+		Some(value) => {
+			let span = find_param_span(args, "embed");
+			let embed = match value {
+				serde_json::Value::Bool(embed) => *embed,
+				serde_json::Value::String(s) if s == "true" => true,
+				serde_json::Value::String(s) if s == "false" => false,
+				value => {
+					return Err(syn::Error::new(
+						span,
+						format!("embed must be a boolean, got {}", value),
+					))
+				}
+			};
+			if embed && src_str == "git" {
+				return Err(syn::Error::new(
+					span,
+					"embed is only supported for http and mock citations, whose references are snapshots",
+				));
+			}
+			embed
+		}
+		None => false,
+	};
 
-	// Validate source-specific parameters using helper functions
-	match src_str.as_str() {
-		"git" => validate_git_kwargs(kwargs, args)?,
-		"http" => validate_http_kwargs(kwargs, args)?,
-		"mock" => validate_mock_kwargs(kwargs, args)?,
-		_ => {
-			return Err(syn::Error::new(
-				proc_macro2::Span::call_site(),
-				format!("unknown source type: {}", src_str),
-			));
+	let requires_feature = match kwargs.get("requires_feature") {
+		Some(value) => {
+			let requires_feature = value
+				.as_str()
+				.ok_or_else(|| "requires_feature must be a string".to_string())
+				.and_then(RequiredFeature::from_str);
+			Some(
+				requires_feature
+					.map_err(|e| syn::Error::new(find_param_span(args, "requires_feature"), e))?,
+			)
 		}
-	}
+		None => None,
+	};
+
+	// Validate source-specific parameters
+	validate_source_kwargs(src_str, kwargs, args)?;
 
 	// Create a simple source expression - just a unit tuple
 	let source_expr = syn::parse_quote! { () };
 
-	Ok(crate::Citation { source_expr, reason, level, annotation, kwargs: Some(kwargs.clone()) })
+	Ok(crate::Citation {
+		source_expr,
+		reason,
+		level,
+		annotation,
+		deadline,
+		reverify,
+		embed,
+		requires_feature,
+		deprecation: None,
+		kwargs: Some(kwargs.clone()),
+	})
 }
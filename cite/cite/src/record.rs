@@ -0,0 +1,41 @@
+//! Recording of expanded citations in the citation registry
+//!
+//! Every citation the macro expands is recorded with its resolved kwargs, so that `cite-review`
+//! and `cargo cite` refresh and check the citations of a workspace as the macro sees them,
+//! rather than parsing them back out of the sources. The severity of the drift the macro found
+//! is recorded along with it.
+
+use crate::Citation;
+use cite_cache::{CacheBuilder, CitationRecord, CitationRegistry, CITATION_REGISTRY_DIR};
+use cite_core::ChangeClass;
+
+/// Record an expanded citation in the registry of the cite directory, with the severity of its
+/// drift if it drifted
+///
+/// Recording is best effort, so that it never fails a build the citation itself passes.
+pub fn record(citation: &Citation, change: Option<ChangeClass>) {
+	let (Some(kwargs), Ok(src)) = (&citation.kwargs, citation.get_src()) else {
+		return;
+	};
+	// The compiler names the crate being compiled; without it there is no unit to record for
+	let Ok(krate) = std::env::var("CARGO_CRATE_NAME") else {
+		return;
+	};
+
+	let location = std::env::current_dir().ok().and_then(|dir| crate::gha::locate_in(&dir));
+	let record = CitationRecord {
+		krate,
+		file: location.as_ref().map(|location| location.file.clone()),
+		line: location.map(|location| location.line).unwrap_or_default(),
+		src,
+		kwargs: kwargs.clone().into_iter().collect(),
+		level: citation.reported_level(),
+		change,
+	};
+
+	let registry =
+		CitationRegistry::new(CacheBuilder::default().cite_dir.join(CITATION_REGISTRY_DIR));
+	if let Err(_error) = registry.record(&cite_cache::build_id(), record) {
+		cite_core::trace_event!(error = %_error, "failed to record citation");
+	}
+}
@@ -1,3 +0,0 @@
-pub mod git;
-pub mod http;
-pub mod mock;
@@ -1,55 +1,111 @@
-use crate::sources;
-use cite_core::{CitationBehavior, CitationLevel};
+use crate::outcome::MacroValidationOutcome;
+use cite_core::{
+	ChangeClass, ChangeLevels, CitationBehavior, CitationLevel, ContentType, DiffStats,
+	OfflinePolicy, ReverifyInterval,
+};
+use cite_sources::{AnySource, SourceKwargsError};
 
 /// Execute kwargs source validation and return the result
 pub fn execute_kwargs_source_validation(
 	citation: &crate::Citation,
 	behavior: &CitationBehavior,
 	level_override: Option<CitationLevel>,
-) -> Option<std::result::Result<Option<String>, String>> {
+) -> Option<MacroValidationOutcome> {
 	let kwargs = citation.kwargs.as_ref()?;
+	let src = citation.get_src().ok()?;
+	let _span = cite_core::trace_span!("validation", src = src.as_str());
 
-	match citation.get_src().ok()?.as_str() {
-		"git" => {
-			// Construct GitSource from kwargs using the utility function
-			match sources::git::try_get_git_source_from_kwargs(kwargs) {
-				Ok(git_source) => {
-					return execute_git_source_validation(git_source, behavior, level_override);
-				}
-				Err(e) => return Some(Err(e)),
-			}
+	let offline = match OfflinePolicy::is_offline().then(OfflinePolicy::from_env).transpose() {
+		Ok(offline) => offline,
+		Err(error) => return Some(MacroValidationOutcome::error(error)),
+	};
+
+	let outcome = match (AnySource::from_kwargs(&src, kwargs), offline) {
+		(Ok(source), Some(policy)) => execute_offline_source_validation(
+			source,
+			policy,
+			citation.reverify,
+			behavior,
+			level_override,
+		),
+		(Ok(source), None) => {
+			execute_source_validation(source, citation.reverify, behavior, level_override)
 		}
-		"http" => {
-			// Construct HttpMatch from kwargs using the utility function
-			match sources::http::try_get_http_source_from_kwargs(kwargs) {
-				Ok(http_source) => {
-					return execute_http_source_validation(http_source, behavior, level_override)
-				}
-				Err(e) => return Some(Err(e)),
-			}
+		// Unknown source type
+		(Err(SourceKwargsError::UnknownSourceType(_)), _) => return None,
+		(Err(e), _) => MacroValidationOutcome::error(e.to_string()),
+	};
+	cite_core::trace_event!(
+		valid = outcome.result.is_valid(),
+		fails_compilation = outcome.should_fail_compilation(),
+		"validated"
+	);
+	Some(outcome)
+}
+
+/// Validate a source of any type
+fn execute_source_validation(
+	source: AnySource,
+	reverify: Option<ReverifyInterval>,
+	behavior: &CitationBehavior,
+	level_override: Option<CitationLevel>,
+) -> MacroValidationOutcome {
+	match source {
+		AnySource::Git(git_source) => {
+			execute_git_source_validation(git_source, behavior, level_override)
 		}
-		"mock" => {
-			// Construct MockSource from kwargs using the utility function
-			match sources::mock::try_get_mock_source_from_kwargs(kwargs) {
-				Ok(mock_source) => {
-					return execute_mock_source_validation(mock_source, behavior, level_override)
-				}
-				Err(e) => return Some(Err(e)),
-			}
+		AnySource::Http(http_source) => {
+			execute_http_source_validation(http_source, reverify, behavior, level_override)
+		}
+		AnySource::Bundle(bundle_source) => {
+			execute_bundle_source_validation(bundle_source, behavior, level_override)
 		}
-		_ => {
-			// Unknown source type
-			return None;
+		AnySource::Mock(mock_source) => {
+			execute_mock_source_validation(mock_source, behavior, level_override)
 		}
 	}
 }
 
+/// Validate a source without network access, as with `CITE_OFFLINE=1`
+///
+/// Sources that cannot be validated offline follow the offline policy instead.
+fn execute_offline_source_validation(
+	source: AnySource,
+	policy: OfflinePolicy,
+	reverify: Option<ReverifyInterval>,
+	behavior: &CitationBehavior,
+	level_override: Option<CitationLevel>,
+) -> MacroValidationOutcome {
+	if !source.supports_offline() {
+		return MacroValidationOutcome::invalid(
+			policy.validate(),
+			format!(
+				"{}\n         Offline policy: {}",
+				message_header(
+					"Citation cannot be validated offline without a cached reference!",
+					&source.target_description()
+				),
+				policy
+			),
+		);
+	}
+
+	match source {
+		// The cached reference stands in for the content that cannot be fetched
+		AnySource::Http(_) => MacroValidationOutcome::valid(),
+		AnySource::Git(git_source) => {
+			execute_git_source_validation(git_source.with_offline(true), behavior, level_override)
+		}
+		source => execute_source_validation(source, reverify, behavior, level_override),
+	}
+}
+
 /// Try to execute source expressions that we can handle during macro expansion
 pub fn try_execute_source_expression(
 	citation: &crate::Citation,
 	behavior: &CitationBehavior,
 	level_override: Option<CitationLevel>,
-) -> Option<std::result::Result<Option<String>, String>> {
+) -> Option<MacroValidationOutcome> {
 	// All citations now use kwargs syntax
 	if citation.kwargs.is_some() {
 		return execute_kwargs_source_validation(citation, behavior, level_override);
@@ -60,11 +116,11 @@ pub fn try_execute_source_expression(
 }
 
 /// Execute mock source validation and return the result
-fn execute_mock_source_validation(
+pub(crate) fn execute_mock_source_validation(
 	mock_source: cite_core::mock::MockSource,
 	behavior: &CitationBehavior,
 	level_override: Option<CitationLevel>,
-) -> Option<std::result::Result<Option<String>, String>> {
+) -> MacroValidationOutcome {
 	use cite_core::Source;
 
 	// Execute the real API!
@@ -73,64 +129,220 @@ fn execute_mock_source_validation(
 			let result = comparison.validate(behavior, level_override);
 
 			if !result.is_valid() {
-				let diff_msg = format!(
-					"Citation content has changed!\n         Referenced: {}\n         Current: {}",
-					comparison.referenced().0,
-					comparison.current().0
-				);
+				let diff_stats =
+					DiffStats::from_texts(&comparison.referenced().0, &comparison.current().0);
+				let header = |title: &str| message_header(title, &mock_source.target_description());
+				let diff_msg = if mock_source.any_of.is_empty() {
+					format!(
+						"{}\n         Referenced: {}\n         Current: {}",
+						header("Citation content has changed!"),
+						comparison.referenced().0,
+						comparison.current().0
+					)
+				} else {
+					format!(
+						"{}\n         Accepted: {:?}\n         Current: {}",
+						header("Citation content matches none of the accepted values!"),
+						mock_source.any_of,
+						comparison.current().0
+					)
+				};
 
-				if result.should_fail_compilation() {
-					return Some(Err(diff_msg));
-				} else if result.should_report() {
-					return Some(Ok(Some(diff_msg)));
-				}
+				return MacroValidationOutcome::invalid(result, diff_msg)
+					.with_diff_stats(diff_stats);
 			}
 
-			Some(Ok(None))
+			MacroValidationOutcome::valid()
 		}
-		Err(e) => Some(Err(format!("Citation source error: {:?}", e))),
+		Err(e) => MacroValidationOutcome::error(format!("Citation source error: {:?}", e)),
 	}
 }
 
 /// Execute HTTP source validation and return the result
 fn execute_http_source_validation(
 	http_source: cite_http::HttpMatch,
+	reverify: Option<ReverifyInterval>,
 	behavior: &CitationBehavior,
 	level_override: Option<CitationLevel>,
-) -> Option<std::result::Result<Option<String>, String>> {
-	use cite_core::Source;
+) -> MacroValidationOutcome {
+	use cite_core::{Diff, Source};
+
+	let header = |title: &str| message_header(title, &http_source.target_description());
 
 	// HTTP sources now handle caching internally
 	match http_source.get() {
 		Ok(comparison) => {
+			let change = comparison.diff().describe_change();
+			let level_override = match change_level(change, level_override) {
+				Ok(level_override) => level_override,
+				Err(error) => return MacroValidationOutcome::error(error),
+			};
 			let result = comparison.validate(behavior, level_override);
 
 			if !result.is_valid() {
-				let diff_msg = if let Some(unified_diff) = comparison.diff().unified_diff() {
+				let target_description = describe_target(http_source.target_description(), change);
+				let header = |title: &str| message_header(title, &target_description);
+				let diff_stats = match comparison.diff().unified_diff() {
+					Some(unified_diff) => DiffStats::from_unified_diff(unified_diff),
+					None => DiffStats::from_texts(
+						&comparison.referenced().content,
+						&comparison.current().content,
+					),
+				};
+				// Drift is shown in the granularity of the citation, e.g. word by word
+				let rendered_diff = comparison.render_diff(http_source.diff_granularity());
+				let diff_msg = if let Some(pinned) = &comparison.diff().pinned_digest {
+					if pinned.is_unpinned() && http_source.uses_manifest() {
+						format!(
+							"{}\n         Current sha256: {}\n         Add it to .cite/checksums.txt: {}  {}",
+							header("HTTP citation is missing from the checksum manifest!"),
+							pinned.current,
+							pinned.current,
+							http_source.id().as_str()
+						)
+					} else if pinned.is_unpinned() {
+						format!(
+							"{}\n         Current sha256: {}\n         Pin it with: sha256 = \"{}\"",
+							header("HTTP citation has no pinned digest!"),
+							pinned.current,
+							pinned.current
+						)
+					} else {
+						format!(
+							"{}\n         Expected sha256: {}\n         Current sha256: {}",
+							header("HTTP citation content digest has changed!"),
+							pinned.expected,
+							pinned.current
+						)
+					}
+				} else if let Some(check) = &comparison.diff().number_check {
+					let (low, high) = check.expected.range();
+					format!(
+						"{}\n         Expected: {} (allowed range {} to {})\n         Current: {} (off by {:+})",
+						header("HTTP citation value is outside its tolerance!"),
+						check.expected,
+						low,
+						high,
+						check.current,
+						check.delta()
+					)
+				} else if let Some(check) = &comparison.diff().length_check {
+					format!(
+						"{}\n         Expected: {}\n         Current: {} {}",
+						header("HTTP citation content length is outside its bounds!"),
+						check.expected,
+						check.current,
+						check.expected.unit
+					)
+				} else if let Some(check) = &comparison.diff().constraint_check {
+					format!(
+						"{}\n         Constraint: {}\n         Violations:\n           - {}",
+						header("HTTP citation content no longer satisfies its constraint!"),
+						check.constraint,
+						check.violations.join("\n           - ")
+					)
+				} else if let Some(check) = &comparison.diff().anchor_check {
 					format!(
-						"HTTP citation content has changed!\n         URL: {}\n{}",
-						comparison.current().source_url.as_str(),
-						unified_diff
+						"{}\n         Anchor: #{}",
+						header("HTTP citation anchor is missing!"),
+						check.anchor
+					)
+				} else if let Some(expected) = http_source.expect_status() {
+					format!(
+						"{}\n         Expected status: {}\n         Current status: {}",
+						header("HTTP citation status has changed!"),
+						expected,
+						comparison.current().content
+					)
+				} else if !http_source.any_of().is_empty() {
+					format!(
+						"{}\n         Accepted: {:?}\n         Current: {}",
+						header("HTTP citation content matches none of the accepted values!"),
+						http_source.any_of(),
+						comparison.current().content
+					)
+				} else if let Some(change) = &comparison.diff().redirect_change {
+					format!(
+						"{}\n         Referenced target: {}\n         Current target: {}\n{}",
+						header("HTTP citation redirects to a different URL!"),
+						change.referenced,
+						change.current,
+						rendered_diff.unwrap_or_default()
+					)
+				} else if !comparison.diff().schema_changes.is_empty() {
+					let changes = comparison
+						.diff()
+						.schema_changes
+						.iter()
+						.map(ToString::to_string)
+						.collect::<Vec<_>>();
+					format!(
+						"{}\n         Changes:\n           - {}",
+						header("GraphQL schema has changed!"),
+						changes.join("\n           - ")
+					)
+				} else if !comparison.diff().changed_fields.is_empty() {
+					format!(
+						"{}\n         Fields: {}\n{}",
+						header("HTTP citation fields have changed!"),
+						comparison.diff().changed_fields.join(", "),
+						rendered_diff.unwrap_or_default()
+					)
+				} else if let Some(rendered_diff) = rendered_diff {
+					format!("{}\n{}", header("HTTP citation content has changed!"), rendered_diff)
+				} else if comparison.content_type() == ContentType::Binary {
+					format!(
+						"{}\n         Current: {} bytes\n         Referenced: {} bytes",
+						header("HTTP citation content has changed!"),
+						comparison.current().content.len(),
+						comparison.referenced().content.len()
 					)
 				} else {
 					format!(
-                        "HTTP citation content has changed!\n         URL: {}\n         Current: {}\n         Referenced: {}",
-                        comparison.current().source_url.as_str(),
-                        comparison.current().content,
-                        comparison.referenced().content
-                    )
+						"{}\n         Current: {}\n         Referenced: {}",
+						header("HTTP citation content has changed!"),
+						comparison.current().content,
+						comparison.referenced().content
+					)
 				};
 
-				if result.should_fail_compilation() {
-					return Some(Err(diff_msg));
-				} else if result.should_report() {
-					return Some(Ok(Some(diff_msg)));
+				let patch = comparison.diff().to_patch_file(http_source.id());
+				return MacroValidationOutcome::invalid(result, diff_msg)
+					.with_diff_stats(diff_stats)
+					.with_patch(http_source.id(), patch)
+					.with_change(change);
+			}
+
+			// An unchanged reference still has to be re-confirmed once its snapshot is too old
+			if let Some(reverify) = reverify {
+				let fetched_at = comparison.referenced().fetched_at();
+				let result = reverify.validate(
+					fetched_at,
+					ReverifyInterval::now(),
+					behavior,
+					level_override,
+				);
+				if !result.is_valid() {
+					return MacroValidationOutcome::invalid(
+						result,
+						format!(
+							"{}\n         Snapshot taken: {}\n         Interval: {}\n         Re-confirm the cited content, then remove its cache entry: {}",
+							header("HTTP citation reference is due for reverification!"),
+							fetched_at
+								.map(|fetched_at| fetched_at.to_rfc3339())
+								.unwrap_or_else(|| "unknown".to_string()),
+							reverify,
+							http_source.cache().cache_dir().join(http_source.id().as_str()).display()
+						),
+					);
 				}
 			}
 
-			Some(Ok(None))
+			// A reference captured empty or just now passes, but validates nothing
+			let notes = comparison.notes();
+			MacroValidationOutcome::valid().with_note((!notes.is_empty()).then(|| notes.join("\n")))
 		}
-		Err(e) => Some(Err(format!("HTTP citation source error: {:?}", e))),
+		Err(e) => MacroValidationOutcome::error(format!("HTTP citation source error: {:?}", e)),
 	}
 }
 
@@ -139,41 +351,367 @@ fn execute_git_source_validation(
 	git_source: cite_git::GitSource,
 	behavior: &CitationBehavior,
 	level_override: Option<CitationLevel>,
-) -> Option<std::result::Result<Option<String>, String>> {
-	use cite_core::Source;
+) -> MacroValidationOutcome {
+	use cite_core::{Diff, Source};
 
 	// Git sources handle git operations internally
 	match git_source.get() {
+		Ok(comparison) => {
+			let change = comparison.diff().describe_change();
+			let level_override = match change_level(change, level_override) {
+				Ok(level_override) => level_override,
+				Err(error) => return MacroValidationOutcome::error(error),
+			};
+			let result = comparison.validate(behavior, level_override);
+
+			if !result.is_valid() {
+				// Name the commits that revisions like `main~3` resolved to
+				let target_description = format!(
+					"{}\nReferenced revision: {}\nCurrent revision: {}",
+					git_source.target_description(),
+					comparison.referenced().describe_revision(),
+					comparison.current().describe_revision()
+				);
+				let target_description = describe_target(target_description, change);
+				let header = |title: &str| message_header(title, &target_description);
+				let diff_stats = comparison
+					.diff()
+					.unified_diff()
+					.map(DiffStats::from_unified_diff)
+					.unwrap_or_default();
+				// Drift is shown in the granularity of the citation, e.g. word by word
+				let rendered_diff = comparison.render_diff(git_source.diff_granularity);
+				// An expected diff fails by the change being absent or different, which is shown
+				// as the expected lines that are missing and the changed lines that are not
+				let expected = git_source.expect_diff.as_deref().map(|expect_diff| {
+					cite_git::ExpectedDiffCheck::new(expect_diff, comparison.diff().diff())
+				});
+				let title = expected
+					.as_ref()
+					.and_then(cite_git::ExpectedDiffCheck::describe_failure)
+					.unwrap_or("Git citation content has changed!");
+				let mut diff_msg = header(title);
+				if let Some(expected) = &expected {
+					diff_msg = format!("{}\n{}", diff_msg, expected.render().trim_end());
+				}
+				if let Some(rendered_diff) = rendered_diff {
+					diff_msg = format!("{}\n{}", diff_msg, rendered_diff);
+				}
+
+				let patch = comparison.diff().to_patch_file(git_source.id());
+				return MacroValidationOutcome::invalid(result, diff_msg)
+					.with_diff_stats(diff_stats)
+					.with_patch(git_source.id(), patch)
+					.with_change(change);
+			}
+
+			MacroValidationOutcome::valid()
+		}
+		Err(e) => MacroValidationOutcome::error(format!("Git citation source error: {:?}", e)),
+	}
+}
+
+/// Execute bundle source validation and return the result
+fn execute_bundle_source_validation(
+	bundle_source: cite_bundle::BundleSource,
+	behavior: &CitationBehavior,
+	level_override: Option<CitationLevel>,
+) -> MacroValidationOutcome {
+	use cite_core::Source;
+
+	let header = |title: &str| message_header(title, &bundle_source.target_description());
+
+	match bundle_source.get() {
 		Ok(comparison) => {
 			let result = comparison.validate(behavior, level_override);
 
 			if !result.is_valid() {
-				let diff_msg = if let Some(unified_diff) = comparison.diff().unified_diff() {
+				let diff = comparison.diff();
+				let diff_msg = if diff.is_unpinned() {
 					format!(
-						"Git citation content has changed!\n         Remote: {}\n         Path: {}\n         Revision: {}\n{}",
-						comparison.current().remote,
-						comparison.current().path_pattern.path,
-						comparison.current().revision,
-						unified_diff
+						"{}\n         Current sha256: {}\n         Pin it with: sha256 = \"{}\"",
+						header("Bundle citation has no pinned root hash!"),
+						diff.current,
+						diff.current
 					)
 				} else {
+					let changes = match &diff.changes {
+						Some(changes) => changes
+							.iter()
+							.map(|change| format!("\n           - {}", change))
+							.collect::<Vec<_>>()
+							.concat(),
+						None => format!(
+							"\n           (unknown, no digests are recorded for the expected root hash in {})",
+							bundle_source.recording_path().display()
+						),
+					};
 					format!(
-						"Git citation content has changed!\n         Remote: {}\n         Path: {}\n         Revision: {}",
-						comparison.current().remote,
-						comparison.current().path_pattern.path,
-						comparison.current().revision
+						"{}\n         Expected sha256: {}\n         Current sha256: {}\n         Changed files:{}",
+						header("Bundle citation files have changed!"),
+						diff.expected,
+						diff.current,
+						changes
 					)
 				};
 
-				if result.should_fail_compilation() {
-					return Some(Err(diff_msg));
-				} else if result.should_report() {
-					return Some(Ok(Some(diff_msg)));
-				}
+				return MacroValidationOutcome::invalid(result, diff_msg);
 			}
 
-			Some(Ok(None))
+			MacroValidationOutcome::valid()
+		}
+		Err(e) => MacroValidationOutcome::error(format!("Bundle citation source error: {:?}", e)),
+	}
+}
+
+/// Resolve the level of drift: the citation's own level, or else the level of the change's
+/// severity per `CITE_CHANGE_LEVELS`
+fn change_level(
+	change: Option<ChangeClass>,
+	level_override: Option<CitationLevel>,
+) -> Result<Option<CitationLevel>, String> {
+	match level_override {
+		Some(level) => Ok(Some(level)),
+		None => Ok(ChangeLevels::from_env()?.level(change)),
+	}
+}
+
+/// Add the severity of the change, if classified, to the description of the cited target
+fn describe_target(target_description: String, change: Option<ChangeClass>) -> String {
+	match change {
+		Some(change) => format!("{}\nChange: {}", target_description, change),
+		None => target_description,
+	}
+}
+
+/// Render the title of a validation message, followed by the description of the cited target
+fn message_header(title: &str, target_description: &str) -> String {
+	let mut header = title.to_string();
+	for line in target_description.lines() {
+		header.push_str("\n         ");
+		header.push_str(line);
+	}
+	header
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cite_cache::committed::git;
+	use cite_core::{CitationAnnotation, CitationGlobal, DiffGranularity};
+
+	fn behavior() -> CitationBehavior {
+		CitationBehavior::new(
+			CitationLevel::Error,
+			CitationAnnotation::Any,
+			CitationGlobal::Lenient,
+		)
+	}
+
+	fn offline(source: AnySource, policy: OfflinePolicy) -> MacroValidationOutcome {
+		execute_offline_source_validation(source, policy, None, &behavior(), None)
+	}
+
+	/// An http citation whose cache lives in the given directory, with a cached reference if warm
+	fn http_source(cite_dir: &std::path::Path, warm: bool) -> AnySource {
+		let cache = cite_cache::CacheBuilder::new(cite_dir.to_path_buf(), "cache".into())
+			.build()
+			.unwrap();
+		let http_match = cite_http::HttpMatch::with_match_expression(
+			"https://example.com/docs",
+			cite_http::MatchExpression::FullDocument,
+		)
+		.unwrap()
+		.with_cache(cache.clone());
+		if warm {
+			let current = cite_http::CurrentHttp {
+				content: "cited content".to_string(),
+				metadata: std::collections::HashMap::new(),
+				source_url: http_match.source_url.clone(),
+				match_expression: http_match.matches.clone(),
+				raw_content: None,
+			};
+			cache.set(cite_core::Source::id(&http_match), &current).unwrap();
 		}
-		Err(e) => Some(Err(format!("Git citation source error: {:?}", e))),
+		AnySource::Http(http_match)
+	}
+
+	#[test]
+	fn test_offline_mock_is_validated() {
+		let same = AnySource::Mock(cite_core::mock_source_same("content"));
+		assert!(offline(same, OfflinePolicy::Error).result.is_valid());
+
+		// Drift is still reported, as it is online
+		let changed = AnySource::Mock(cite_core::mock_source_changed("old", "new"));
+		let outcome = offline(changed, OfflinePolicy::Skip);
+		assert!(outcome.should_fail_compilation());
+		assert!(outcome.message.unwrap().contains("Citation content has changed!"));
+	}
+
+	#[test]
+	fn test_mock_drift_describes_target() {
+		let changed = cite_core::mock_source_changed("old", "new");
+		let outcome = execute_mock_source_validation(changed, &behavior(), None);
+		assert_eq!(
+			outcome.message.unwrap(),
+			"Citation content has changed!\n         Mock: old\n         Referenced: old\n         Current: new"
+		);
+
+		// Content beyond the first line is elided from the target
+		let changed = cite_core::mock_source_changed("ab\nc", "new");
+		let outcome = execute_mock_source_validation(changed, &behavior(), None);
+		assert!(outcome.message.unwrap().contains("\n         Mock: ab...\n"));
+	}
+
+	#[test]
+	fn test_offline_http_follows_policy_without_cached_reference() {
+		let cite_dir = tempfile::TempDir::new().unwrap();
+
+		// A cold cache cannot stand in for the page
+		let cold = || http_source(cite_dir.path(), false);
+		assert!(offline(cold(), OfflinePolicy::Skip).result.is_valid());
+		let warned = offline(cold(), OfflinePolicy::Warn);
+		assert!(!warned.result.is_valid());
+		assert!(!warned.should_fail_compilation());
+		let message = warned.message.unwrap();
+		assert!(message.contains("cannot be validated offline"), "{}", message);
+		assert!(message.contains("URL: https://example.com/docs"));
+		assert!(offline(cold(), OfflinePolicy::Error).should_fail_compilation());
+
+		// A warm one can, whatever the policy
+		let outcome = offline(http_source(cite_dir.path(), true), OfflinePolicy::Error);
+		assert!(outcome.result.is_valid());
+		assert_eq!(outcome.message, None);
+	}
+
+	#[test]
+	fn test_offline_git_uses_existing_clone() {
+		let target_dir = tempfile::TempDir::new().unwrap();
+		let source = |revision: &str| {
+			let source = cite_git::GitSource::try_new(
+				"https://example.com/fixture.git",
+				"notes.txt",
+				revision,
+				Some(revision),
+				None,
+			)
+			.unwrap()
+			.with_target_dir(target_dir.path().to_path_buf());
+			AnySource::Git(source)
+		};
+
+		// Nothing is cloned yet
+		assert!(offline(source("main"), OfflinePolicy::Skip).result.is_valid());
+		assert!(offline(source("main"), OfflinePolicy::Error).should_fail_compilation());
+
+		// The existing clone is compared without fetching, as it has no remote to fetch from
+		let clone = target_dir.path().join("fixture");
+		std::fs::create_dir_all(&clone).unwrap();
+		std::fs::write(clone.join("notes.txt"), "one\n").unwrap();
+		git(&clone, &["init", "-q"]).unwrap();
+		git(&clone, &["add", "-A"]).unwrap();
+		git(&clone, &["commit", "-qm", "Add notes"]).unwrap();
+		let head = git(&clone, &["rev-parse", "HEAD"]).unwrap();
+		let outcome = offline(source(&head), OfflinePolicy::Error);
+		assert!(outcome.result.is_valid(), "{:?}", outcome.message);
+	}
+
+	#[test]
+	fn test_git_drift_describes_change() {
+		let target_dir = tempfile::TempDir::new().unwrap();
+		let clone = target_dir.path().join("fixture");
+		std::fs::create_dir_all(&clone).unwrap();
+		git(&clone, &["init", "-q"]).unwrap();
+		let commit = |content: &str| {
+			std::fs::write(clone.join("retry.rs"), content).unwrap();
+			git(&clone, &["add", "-A"]).unwrap();
+			git(&clone, &["commit", "-qm", "Update retries"]).unwrap();
+			git(&clone, &["rev-parse", "HEAD"]).unwrap()
+		};
+		let referenced = commit("fn retries() -> u32 {\n\t3\n}\n");
+		let padded = commit("fn retries() -> u32 {  \n\t3\n}\n\n");
+		let reformatted = commit("fn retries() -> u32 { 3 }\n");
+		let changed = commit("fn retries() -> u32 { 5 }\n");
+
+		let validate = |current: &str| {
+			let source = cite_git::GitSource::try_new(
+				"https://example.com/fixture.git",
+				"retry.rs",
+				&referenced,
+				Some(current),
+				None,
+			)
+			.unwrap()
+			.with_target_dir(target_dir.path().to_path_buf());
+			offline(AnySource::Git(source), OfflinePolicy::Error)
+		};
+		let message = |current: &str| validate(current).message.unwrap();
+		assert!(message(&padded).contains("Change: whitespace-only"));
+		assert_eq!(validate(&padded).change, Some(ChangeClass::Whitespace));
+		assert!(message(&changed).contains("Change: code"));
+
+		// The drift is kept as a patch of the cited source, to be archived
+		let patch = validate(&changed).patch.unwrap();
+		assert!(patch.id.as_str().starts_with("git_https://example.com/fixture.git_retry.rs"));
+		assert!(patch.content.starts_with(&format!("--- a/{}\n", patch.id.as_str())));
+		assert!(patch.content.contains("+fn retries() -> u32 { 5 }\n"));
+		assert_eq!(validate(&referenced).patch, None);
+
+		// Word diffs mark only the changed words, while the patch keeps whole lines
+		let source = cite_git::GitSource::try_new(
+			"https://example.com/fixture.git",
+			"retry.rs",
+			&reformatted,
+			Some(&changed),
+			None,
+		)
+		.unwrap()
+		.with_target_dir(target_dir.path().to_path_buf())
+		.with_diff_granularity(DiffGranularity::Words);
+		let outcome = offline(AnySource::Git(source), OfflinePolicy::Error);
+		let message = outcome.message.unwrap();
+		assert!(message.contains("\nfn retries() -> u32 { [-3-]{+5+} }\n"), "{}", message);
+		assert!(outcome.patch.unwrap().content.contains("+fn retries() -> u32 { 5 }\n"));
+
+		// An expected diff failing is worded by how it failed, with the lines that differ
+		let expecting = |current: &str| {
+			let source = cite_git::GitSource::try_new(
+				"https://example.com/fixture.git",
+				"retry.rs",
+				&reformatted,
+				Some(current),
+				None,
+			)
+			.unwrap()
+			.with_target_dir(target_dir.path().to_path_buf())
+			.with_expect_diff("-fn retries() -> u32 { 3 }\n+fn retries() -> u32 { 4 }\n");
+			offline(AnySource::Git(source), OfflinePolicy::Error).message.unwrap()
+		};
+		let message = expecting(&changed);
+		assert!(message.starts_with("Git citation content has changed differently than expected!"));
+		assert!(
+			message.contains(
+				"Missing expected changes:\n+fn retries() -> u32 { 4 }\nUnexpected changes:\n+fn retries() -> u32 { 5 }\n"
+			),
+			"{}",
+			message
+		);
+		let message = expecting(&reformatted);
+		assert!(message.starts_with("Git citation content has not changed by the expected diff!"));
+		assert!(message.contains("Missing expected changes:\n-fn retries() -> u32 { 3 }\n"));
+	}
+
+	#[test]
+	fn test_change_level() {
+		let change = Some(ChangeClass::Whitespace);
+
+		// The citation's own level takes precedence over the severity of the change
+		let level = change_level(change, Some(CitationLevel::Error)).unwrap();
+		assert_eq!(level, Some(CitationLevel::Error));
+		assert_eq!(
+			describe_target("Remote: fixture".to_string(), change),
+			"Remote: fixture\nChange: whitespace-only"
+		);
+		assert_eq!(describe_target("Remote: fixture".to_string(), None), "Remote: fixture");
 	}
 }
@@ -1,10 +1,28 @@
 pub mod annotation;
+pub mod change_levels;
+pub mod deadline;
+pub mod empty_reference;
 pub mod global;
+pub mod layered;
 pub mod level;
+pub mod offline;
+pub mod require_committed;
+pub mod required_feature;
+pub mod reverify;
+pub mod validate_when;
 
 pub use annotation::CitationAnnotation;
+pub use change_levels::ChangeLevels;
+pub use deadline::CitationDeadline;
+pub use empty_reference::EmptyReferencePolicy;
 pub use global::CitationGlobal;
+pub use layered::{Layered, PartialBehavior};
 pub use level::CitationLevel;
+pub use offline::OfflinePolicy;
+pub use require_committed::RequireCommitted;
+pub use required_feature::RequiredFeature;
+pub use reverify::ReverifyInterval;
+pub use validate_when::ValidateWhen;
 
 /// Complete citation behavior configuration
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -29,34 +47,17 @@ impl CitationBehavior {
 
 	/// Load configuration from feature flags
 	pub fn from_features() -> Self {
-		// Check feature flags for citation level
-		// Default is Error (no feature flag needed)
-		// Silent takes precedence over warn
-		let level = if cfg!(feature = "silent") {
-			CitationLevel::Silent
-		} else if cfg!(feature = "warn") {
-			CitationLevel::Warn
-		} else {
-			CitationLevel::Error // Default to error
-		};
-
-		// Check feature flags for annotation requirement
-		// Default is Footnote (no feature flag needed)
-		let annotation = if cfg!(feature = "annotationless") {
-			CitationAnnotation::Any
-		} else {
-			CitationAnnotation::Footnote // Default to footnote
-		};
-
-		// Check feature flags for global behavior
-		// Default is Strict (no feature flag needed)
-		let global = if cfg!(feature = "lenient") {
-			CitationGlobal::Lenient
-		} else {
-			CitationGlobal::Strict // Default to strict
-		};
+		Layered::new().with_features(PartialBehavior::from_features()).resolve()
+	}
 
-		Self { level, annotation, global }
+	/// Layer a partial behavior over a base behavior, the settings the overlay configures taking
+	/// precedence
+	pub fn merge(base: Self, overlay: PartialBehavior) -> Self {
+		Self {
+			level: overlay.level.unwrap_or(base.level),
+			annotation: overlay.annotation.unwrap_or(base.annotation),
+			global: overlay.global.unwrap_or(base.global),
+		}
 	}
 
 	/// Resolve the effective citation level, considering local overrides
@@ -90,6 +91,26 @@ impl CitationBehavior {
 		}
 	}
 
+	/// Resolve the level a citation reports drift at, or `None` if its drift is not validated
+	///
+	/// Drift past a passed deadline is an error whatever the local level, and citations
+	/// requiring a disabled feature are not validated.
+	pub fn citation_level(
+		&self,
+		local_level: Option<CitationLevel>,
+		deadline: Option<&CitationDeadline>,
+		requires_feature: Option<&RequiredFeature>,
+	) -> Option<CitationLevel> {
+		if requires_feature.is_some_and(|feature| !feature.is_enabled()) {
+			return None;
+		}
+		let behavior = match deadline {
+			Some(deadline) => deadline.apply(self.clone(), CitationDeadline::today()),
+			None => self.clone(),
+		};
+		Some(behavior.effective_level(local_level))
+	}
+
 	/// Check if a citation validation issue should be reported
 	pub fn should_report(&self, local_level: Option<CitationLevel>) -> bool {
 		self.effective_level(local_level).should_emit()
@@ -184,4 +205,25 @@ mod tests {
 		assert!(behavior.requires_effective_annotation(Some(CitationAnnotation::Footnote)));
 		assert!(behavior.requires_effective_annotation(None));
 	}
+	#[test]
+	fn test_citation_level() {
+		let behavior = CitationBehavior::new(
+			CitationLevel::Warn,
+			CitationAnnotation::Any,
+			CitationGlobal::Lenient,
+		);
+		let passed = CitationDeadline::from_str("2000-01-01").unwrap();
+		let upcoming = CitationDeadline::from_str("9999-12-31").unwrap();
+		let disabled = RequiredFeature::from_str("cite-citation-level-test").unwrap();
+
+		let level = Some(CitationLevel::Silent);
+		assert_eq!(behavior.citation_level(level, None, None), level);
+		assert_eq!(behavior.citation_level(level, Some(&upcoming), None), level);
+
+		// A passed deadline overrides the local level
+		assert_eq!(behavior.citation_level(level, Some(&passed), None), Some(CitationLevel::Error));
+
+		// Citations of disabled features are not validated
+		assert_eq!(behavior.citation_level(level, Some(&passed), Some(&disabled)), None);
+	}
 }
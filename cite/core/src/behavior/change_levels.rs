@@ -0,0 +1,87 @@
+use crate::{ChangeClass, CitationLevel};
+
+/// Environment variable selecting how the severity of a change sets its level
+pub const CHANGE_LEVELS_ENV: &str = "CITE_CHANGE_LEVELS";
+
+/// How the level of drift follows the severity classified by [crate::Diff::describe_change]
+///
+/// A citation's own `level` takes precedence, and changes a source does not classify are
+/// reported at the configured level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChangeLevels {
+	/// Every change is reported at the configured level (default)
+	#[default]
+	Uniform,
+	/// Minor changes, like whitespace or comments, warn and substantive changes error
+	Severity,
+}
+
+impl ChangeLevels {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"uniform" => Ok(ChangeLevels::Uniform),
+			"severity" => Ok(ChangeLevels::Severity),
+			_ => Err(format!(
+				"Invalid {} value: '{}'. Valid values: uniform, severity",
+				CHANGE_LEVELS_ENV, s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ChangeLevels::Uniform => "uniform",
+			ChangeLevels::Severity => "severity",
+		}
+	}
+
+	/// Load from the `CITE_CHANGE_LEVELS` environment variable, defaulting to uniform
+	pub fn from_env() -> Result<Self, String> {
+		match std::env::var(CHANGE_LEVELS_ENV) {
+			Ok(value) => Self::from_str(&value),
+			Err(_) => Ok(Self::default()),
+		}
+	}
+
+	/// Get the level of a change of the given class, or `None` for the configured level
+	pub fn level(&self, change: Option<ChangeClass>) -> Option<CitationLevel> {
+		match (self, change) {
+			(ChangeLevels::Severity, Some(change)) if change.is_minor() => {
+				Some(CitationLevel::Warn)
+			}
+			(ChangeLevels::Severity, Some(_)) => Some(CitationLevel::Error),
+			_ => None,
+		}
+	}
+}
+
+impl std::fmt::Display for ChangeLevels {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		assert_eq!(ChangeLevels::from_str("uniform")?, ChangeLevels::Uniform);
+		assert_eq!(ChangeLevels::from_str("Severity")?, ChangeLevels::Severity);
+		assert!(ChangeLevels::from_str("strict").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_level() {
+		let levels = ChangeLevels::Severity;
+		assert_eq!(levels.level(Some(ChangeClass::Whitespace)), Some(CitationLevel::Warn));
+		assert_eq!(levels.level(Some(ChangeClass::Comment)), Some(CitationLevel::Warn));
+		assert_eq!(levels.level(Some(ChangeClass::Code)), Some(CitationLevel::Error));
+		assert_eq!(levels.level(None), None);
+		assert_eq!(ChangeLevels::Uniform.level(Some(ChangeClass::Code)), None);
+	}
+}
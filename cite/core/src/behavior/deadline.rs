@@ -0,0 +1,113 @@
+use super::{CitationBehavior, CitationGlobal, CitationLevel};
+use chrono::NaiveDate;
+
+/// Date after which drift in a citation fails compilation
+///
+/// Encodes a planned migration in the citation itself: before the deadline, drift is reported
+/// at the configured level; on or after it, drift is an error regardless of the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CitationDeadline {
+	date: NaiveDate,
+}
+
+impl CitationDeadline {
+	/// Create a deadline for the given date
+	pub fn new(date: NaiveDate) -> Self {
+		Self { date }
+	}
+
+	/// Parse from an ISO 8601 date, e.g. `2025-06-01`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map(Self::new).map_err(|_| {
+			format!("Invalid citation deadline: '{}'. Expected a date like 2025-06-01", s)
+		})
+	}
+
+	/// Get the date of the deadline
+	pub fn date(&self) -> NaiveDate {
+		self.date
+	}
+
+	/// Get the current local date, as of macro expansion
+	pub fn today() -> NaiveDate {
+		chrono::Local::now().date_naive()
+	}
+
+	/// Check if the deadline has passed; the deadline day itself counts as passed
+	pub fn has_passed(&self, today: NaiveDate) -> bool {
+		today >= self.date
+	}
+
+	/// Escalate a behavior once the deadline has passed
+	///
+	/// The escalated behavior reports drift as an error and ignores local level overrides, so
+	/// neither `level = "SILENT"` nor the `warn` feature can defer the deadline.
+	pub fn apply(&self, behavior: CitationBehavior, today: NaiveDate) -> CitationBehavior {
+		if !self.has_passed(today) {
+			return behavior;
+		}
+
+		CitationBehavior { level: CitationLevel::Error, global: CitationGlobal::Strict, ..behavior }
+	}
+}
+
+impl std::fmt::Display for CitationDeadline {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.date.format("%Y-%m-%d"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{mock_source_changed, CitationAnnotation, Source, SourceError};
+
+	fn warn_behavior() -> CitationBehavior {
+		CitationBehavior::new(CitationLevel::Warn, CitationAnnotation::Any, CitationGlobal::Lenient)
+	}
+
+	fn date(s: &str) -> NaiveDate {
+		NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+	}
+
+	#[test]
+	fn test_from_str() {
+		let deadline = CitationDeadline::from_str("2025-06-01").unwrap();
+		assert_eq!(deadline.date(), date("2025-06-01"));
+		assert_eq!(deadline.to_string(), "2025-06-01");
+		assert!(CitationDeadline::from_str("June 1st").is_err());
+		assert!(CitationDeadline::from_str("2025-13-01").is_err());
+	}
+
+	#[test]
+	fn test_future_deadline_keeps_configured_level() -> Result<(), SourceError> {
+		let deadline = CitationDeadline::from_str("2025-06-01").unwrap();
+		let behavior = deadline.apply(warn_behavior(), date("2025-05-31"));
+		let comparison = mock_source_changed("old", "new").get()?;
+
+		let result = comparison.validate(&behavior, None);
+		assert_eq!(result.level(), Some(CitationLevel::Warn));
+		assert!(!result.should_fail_compilation());
+
+		let result = comparison.validate(&behavior, Some(CitationLevel::Silent));
+		assert!(!result.should_report());
+		Ok(())
+	}
+
+	#[test]
+	fn test_passed_deadline_escalates_to_error() -> Result<(), SourceError> {
+		let deadline = CitationDeadline::from_str("2025-06-01").unwrap();
+		let comparison = mock_source_changed("old", "new").get()?;
+
+		for today in [date("2025-06-01"), date("2026-01-01")] {
+			let behavior = deadline.apply(warn_behavior(), today);
+
+			for local_level in [None, Some(CitationLevel::Warn), Some(CitationLevel::Silent)] {
+				let result = comparison.validate(&behavior, local_level);
+				assert_eq!(result.level(), Some(CitationLevel::Error));
+				assert!(result.should_fail_compilation());
+			}
+		}
+		Ok(())
+	}
+}
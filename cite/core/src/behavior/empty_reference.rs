@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Environment variable selecting how an empty reference capture is treated
+pub const EMPTY_REFERENCE_ENV: &str = "CITE_EMPTY_REFERENCE";
+
+/// How a citation is treated when its reference is first captured empty or whitespace-only
+///
+/// A selector or pattern that matches nothing pins the citation to empty content, which then
+/// passes no matter what the cited page does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyReferencePolicy {
+	/// Capture the reference, but warn that the citation validates nothing (default)
+	#[default]
+	Warn,
+	/// Fail without capturing the reference
+	Error,
+}
+
+impl EmptyReferencePolicy {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"warn" => Ok(EmptyReferencePolicy::Warn),
+			"error" => Ok(EmptyReferencePolicy::Error),
+			_ => Err(format!(
+				"Invalid {} value: '{}'. Valid values: warn, error",
+				EMPTY_REFERENCE_ENV, s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			EmptyReferencePolicy::Warn => "warn",
+			EmptyReferencePolicy::Error => "error",
+		}
+	}
+
+	/// Load from the `CITE_EMPTY_REFERENCE` environment variable, defaulting to warn
+	pub fn from_env() -> Result<Self, String> {
+		match std::env::var(EMPTY_REFERENCE_ENV) {
+			Ok(value) => Self::from_str(&value),
+			Err(_) => Ok(Self::default()),
+		}
+	}
+}
+
+impl std::fmt::Display for EmptyReferencePolicy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		assert_eq!(EmptyReferencePolicy::from_str("warn")?, EmptyReferencePolicy::Warn);
+		assert_eq!(EmptyReferencePolicy::from_str("ERROR")?, EmptyReferencePolicy::Error);
+		assert!(EmptyReferencePolicy::from_str("skip").is_err());
+		assert_eq!(EmptyReferencePolicy::default().to_string(), "warn");
+		Ok(())
+	}
+}
@@ -0,0 +1,274 @@
+use super::{CitationAnnotation, CitationBehavior, CitationGlobal, CitationLevel};
+
+/// Environment variable overriding the citation level
+pub const LEVEL_ENV: &str = "CITE_LEVEL";
+
+/// Environment variable overriding the annotation requirement
+pub const ANNOTATION_ENV: &str = "CITE_ANNOTATION";
+
+/// Environment variable overriding the global enforcement mode
+pub const GLOBAL_ENV: &str = "CITE_GLOBAL";
+
+/// Behavior configuration of a single source, setting only what that source configures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PartialBehavior {
+	/// How to report citation validation issues, if configured
+	pub level: Option<CitationLevel>,
+	/// When citations are required/allowed, if configured
+	pub annotation: Option<CitationAnnotation>,
+	/// Whether local overrides are allowed, if configured
+	pub global: Option<CitationGlobal>,
+}
+
+impl PartialBehavior {
+	/// Create a partial behavior that configures nothing
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the citation level
+	pub fn with_level(mut self, level: CitationLevel) -> Self {
+		self.level = Some(level);
+		self
+	}
+
+	/// Set the annotation requirement
+	pub fn with_annotation(mut self, annotation: CitationAnnotation) -> Self {
+		self.annotation = Some(annotation);
+		self
+	}
+
+	/// Set the global enforcement mode
+	pub fn with_global(mut self, global: CitationGlobal) -> Self {
+		self.global = Some(global);
+		self
+	}
+
+	/// Load from feature flags, which configure every setting as they always have a default
+	pub fn from_features() -> Self {
+		// Check feature flags for citation level
+		// Default is Error (no feature flag needed)
+		// Silent takes precedence over warn
+		let level = if cfg!(feature = "silent") {
+			CitationLevel::Silent
+		} else if cfg!(feature = "warn") {
+			CitationLevel::Warn
+		} else {
+			CitationLevel::Error // Default to error
+		};
+
+		// Check feature flags for annotation requirement
+		// Default is Footnote (no feature flag needed)
+		let annotation = if cfg!(feature = "annotationless") {
+			CitationAnnotation::Any
+		} else {
+			CitationAnnotation::Footnote // Default to footnote
+		};
+
+		// Check feature flags for global behavior
+		// Default is Strict (no feature flag needed)
+		let global = if cfg!(feature = "lenient") {
+			CitationGlobal::Lenient
+		} else {
+			CitationGlobal::Strict // Default to strict
+		};
+
+		Self { level: Some(level), annotation: Some(annotation), global: Some(global) }
+	}
+
+	/// Load from the `CITE_LEVEL`, `CITE_ANNOTATION` and `CITE_GLOBAL` environment variables,
+	/// configuring only those that are set
+	pub fn from_env() -> Result<Self, String> {
+		let var = |name| std::env::var(name).ok();
+		Self::from_values(
+			var(LEVEL_ENV).as_deref(),
+			var(ANNOTATION_ENV).as_deref(),
+			var(GLOBAL_ENV).as_deref(),
+		)
+	}
+
+	/// Parse from the values of the settings a source configures, e.g. `warn` for the level
+	pub fn from_values(
+		level: Option<&str>,
+		annotation: Option<&str>,
+		global: Option<&str>,
+	) -> Result<Self, String> {
+		Ok(Self {
+			level: level.map(CitationLevel::from_str).transpose()?,
+			annotation: annotation.map(CitationAnnotation::from_str).transpose()?,
+			global: global.map(CitationGlobal::from_str).transpose()?,
+		})
+	}
+
+	/// Layer another partial behavior over this one, its configured settings taking precedence
+	pub fn merge(self, overlay: PartialBehavior) -> Self {
+		Self {
+			level: overlay.level.or(self.level),
+			annotation: overlay.annotation.or(self.annotation),
+			global: overlay.global.or(self.global),
+		}
+	}
+
+	/// Check if no setting is configured
+	pub fn is_empty(&self) -> bool {
+		self.level.is_none() && self.annotation.is_none() && self.global.is_none()
+	}
+}
+
+impl From<CitationBehavior> for PartialBehavior {
+	fn from(behavior: CitationBehavior) -> Self {
+		Self {
+			level: Some(behavior.level),
+			annotation: Some(behavior.annotation),
+			global: Some(behavior.global),
+		}
+	}
+}
+
+/// Behavior composed from the sources that configure it, in a fixed order of precedence
+///
+/// From lowest to highest precedence: feature flags, the project configuration, the local
+/// (uncommitted, per-developer) configuration and environment variables. Layers may be given in
+/// any order; settings no layer configures fall back to [CitationBehavior::default].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Layered {
+	features: PartialBehavior,
+	config: PartialBehavior,
+	local: PartialBehavior,
+	env: PartialBehavior,
+}
+
+impl Layered {
+	/// Create a layered behavior without any layers
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the layer configured by feature flags
+	pub fn with_features(mut self, features: PartialBehavior) -> Self {
+		self.features = features;
+		self
+	}
+
+	/// Set the layer configured by the project configuration
+	pub fn with_config(mut self, config: PartialBehavior) -> Self {
+		self.config = config;
+		self
+	}
+
+	/// Set the layer configured by the local configuration
+	pub fn with_local(mut self, local: PartialBehavior) -> Self {
+		self.local = local;
+		self
+	}
+
+	/// Set the layer configured by environment variables
+	pub fn with_env(mut self, env: PartialBehavior) -> Self {
+		self.env = env;
+		self
+	}
+
+	/// Get the layer configured by feature flags
+	pub fn features(&self) -> &PartialBehavior {
+		&self.features
+	}
+
+	/// Get the layer configured by the project configuration
+	pub fn config(&self) -> &PartialBehavior {
+		&self.config
+	}
+
+	/// Get the layer configured by the local configuration
+	pub fn local(&self) -> &PartialBehavior {
+		&self.local
+	}
+
+	/// Get the layer configured by environment variables
+	pub fn env(&self) -> &PartialBehavior {
+		&self.env
+	}
+
+	/// Resolve the behavior, merging the layers from lowest to highest precedence
+	pub fn resolve(&self) -> CitationBehavior {
+		[self.features, self.config, self.local, self.env]
+			.into_iter()
+			.fold(CitationBehavior::default(), CitationBehavior::merge)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_merge_keeps_unset_settings() {
+		let base = CitationBehavior::new(
+			CitationLevel::Error,
+			CitationAnnotation::Footnote,
+			CitationGlobal::Strict,
+		);
+		let overlay = PartialBehavior::new().with_level(CitationLevel::Warn);
+
+		let merged = CitationBehavior::merge(base.clone(), overlay);
+		assert_eq!(merged.level, CitationLevel::Warn);
+		assert_eq!(merged.annotation, CitationAnnotation::Footnote);
+		assert_eq!(merged.global, CitationGlobal::Strict);
+
+		// An empty overlay changes nothing
+		assert!(PartialBehavior::new().is_empty());
+		assert_eq!(CitationBehavior::merge(base.clone(), PartialBehavior::new()), base);
+	}
+
+	#[test]
+	fn test_layered_precedence() {
+		let features = PartialBehavior::from(CitationBehavior::new(
+			CitationLevel::Error,
+			CitationAnnotation::Footnote,
+			CitationGlobal::Strict,
+		));
+		let config = PartialBehavior::new()
+			.with_level(CitationLevel::Warn)
+			.with_annotation(CitationAnnotation::Any)
+			.with_global(CitationGlobal::Lenient);
+		let local = PartialBehavior::new()
+			.with_level(CitationLevel::Silent)
+			.with_annotation(CitationAnnotation::Footnote);
+		let env = PartialBehavior::new().with_level(CitationLevel::Error);
+
+		// Each setting comes from the highest layer configuring it, whatever the call order
+		let layered = Layered::new()
+			.with_env(env)
+			.with_local(local)
+			.with_config(config)
+			.with_features(features);
+		assert_eq!(
+			layered.resolve(),
+			CitationBehavior::new(
+				CitationLevel::Error,         // env over local
+				CitationAnnotation::Footnote, // local over config
+				CitationGlobal::Lenient,      // config over features
+			)
+		);
+
+		// Without higher layers, the features decide
+		assert_eq!(
+			Layered::new().with_features(features).resolve(),
+			CitationBehavior::merge(CitationBehavior::default(), features)
+		);
+		assert_eq!(Layered::new().resolve(), CitationBehavior::default());
+	}
+
+	#[test]
+	fn test_from_env() -> Result<(), String> {
+		std::env::set_var(LEVEL_ENV, "warn");
+		std::env::set_var(GLOBAL_ENV, "strict");
+		let env = PartialBehavior::from_env()?;
+		std::env::remove_var(LEVEL_ENV);
+		std::env::remove_var(GLOBAL_ENV);
+
+		assert_eq!(env.level, Some(CitationLevel::Warn));
+		assert_eq!(env.annotation, None);
+		assert_eq!(env.global, Some(CitationGlobal::Strict));
+		Ok(())
+	}
+}
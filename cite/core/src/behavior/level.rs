@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 /// Citation reporting level - determines how citation validation issues are reported
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CitationLevel {
 	/// Emit compilation errors for citation issues
 	Error,
@@ -0,0 +1,105 @@
+use crate::CitationValidationResult;
+
+/// Environment variable enabling offline validation
+pub const OFFLINE_ENV: &str = "CITE_OFFLINE";
+
+/// Environment variable selecting how offline validation treats citations it cannot validate
+pub const OFFLINE_POLICY_ENV: &str = "CITE_OFFLINE_POLICY";
+
+/// How offline validation treats a citation whose source cannot be validated without network
+/// access, e.g. an http citation that has no cached reference yet
+///
+/// Sources that can validate offline, such as mock sources or git sources whose revisions are
+/// already cloned, are validated as usual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OfflinePolicy {
+	/// Skip the citation as valid (default)
+	#[default]
+	Skip,
+	/// Warn that the citation was not validated
+	Warn,
+	/// Fail compilation, as the citation cannot be validated
+	Error,
+}
+
+impl OfflinePolicy {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"skip" => Ok(OfflinePolicy::Skip),
+			"warn" => Ok(OfflinePolicy::Warn),
+			"error" => Ok(OfflinePolicy::Error),
+			_ => Err(format!(
+				"Invalid {} value: '{}'. Valid values: skip, warn, error",
+				OFFLINE_POLICY_ENV, s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			OfflinePolicy::Skip => "skip",
+			OfflinePolicy::Warn => "warn",
+			OfflinePolicy::Error => "error",
+		}
+	}
+
+	/// Load from the `CITE_OFFLINE_POLICY` environment variable, defaulting to skip
+	pub fn from_env() -> Result<Self, String> {
+		match std::env::var(OFFLINE_POLICY_ENV) {
+			Ok(value) => Self::from_str(&value),
+			Err(_) => Ok(Self::default()),
+		}
+	}
+
+	/// Check if validation is offline, as set by `CITE_OFFLINE`
+	pub fn is_offline() -> bool {
+		std::env::var(OFFLINE_ENV).is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+	}
+
+	/// Get the result of a citation that cannot be validated offline
+	pub fn validate(&self) -> CitationValidationResult {
+		let (level, should_fail_compilation) = match self {
+			OfflinePolicy::Skip => return CitationValidationResult::Valid,
+			OfflinePolicy::Warn => (crate::CitationLevel::Warn, false),
+			OfflinePolicy::Error => (crate::CitationLevel::Error, true),
+		};
+		CitationValidationResult::Invalid { level, should_fail_compilation, should_report: true }
+	}
+}
+
+impl std::fmt::Display for OfflinePolicy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::CitationLevel;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		assert_eq!(OfflinePolicy::from_str("skip")?, OfflinePolicy::Skip);
+		assert_eq!(OfflinePolicy::from_str("WARN")?, OfflinePolicy::Warn);
+		assert_eq!(OfflinePolicy::from_str("Error")?, OfflinePolicy::Error);
+		assert!(OfflinePolicy::from_str("ignore").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate() {
+		assert!(OfflinePolicy::Skip.validate().is_valid());
+
+		let warn = OfflinePolicy::Warn.validate();
+		assert_eq!(warn.level(), Some(CitationLevel::Warn));
+		assert!(!warn.should_fail_compilation());
+		assert!(warn.should_report());
+
+		let error = OfflinePolicy::Error.validate();
+		assert_eq!(error.level(), Some(CitationLevel::Error));
+		assert!(error.should_fail_compilation());
+	}
+}
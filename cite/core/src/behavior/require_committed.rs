@@ -0,0 +1,86 @@
+/// Environment variable selecting when the committed references must be up to date
+pub const REQUIRE_COMMITTED_ENV: &str = "CITE_REQUIRE_COMMITTED";
+
+/// When validation fails if it leaves the `.cite` directory differing from what is committed
+///
+/// With the references committed alongside the source, a validation that writes a new or
+/// refreshed reference means the committed one is stale. Requiring committed references in CI
+/// turns that into a build failure, so the updated reference is committed rather than lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequireCommitted {
+	/// Uncommitted references are allowed (default)
+	#[default]
+	Never,
+	/// Only require committed references when running in CI
+	Ci,
+	/// Always require committed references
+	Always,
+}
+
+impl RequireCommitted {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"never" => Ok(RequireCommitted::Never),
+			"ci" => Ok(RequireCommitted::Ci),
+			"always" => Ok(RequireCommitted::Always),
+			_ => Err(format!(
+				"Invalid {} value: '{}'. Valid values: never, ci, always",
+				REQUIRE_COMMITTED_ENV, s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RequireCommitted::Never => "never",
+			RequireCommitted::Ci => "ci",
+			RequireCommitted::Always => "always",
+		}
+	}
+
+	/// Load from the `CITE_REQUIRE_COMMITTED` environment variable, defaulting to never
+	pub fn from_env() -> Result<Self, String> {
+		match std::env::var(REQUIRE_COMMITTED_ENV) {
+			Ok(value) => Self::from_str(&value),
+			Err(_) => Ok(Self::default()),
+		}
+	}
+
+	/// Check if the committed references must be up to date under the given build conditions
+	pub fn should_check(&self, is_ci: bool) -> bool {
+		match self {
+			RequireCommitted::Never => false,
+			RequireCommitted::Ci => is_ci,
+			RequireCommitted::Always => true,
+		}
+	}
+}
+
+impl std::fmt::Display for RequireCommitted {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(RequireCommitted::from_str("never").unwrap(), RequireCommitted::Never);
+		assert_eq!(RequireCommitted::from_str("CI").unwrap(), RequireCommitted::Ci);
+		assert_eq!(RequireCommitted::from_str("Always").unwrap(), RequireCommitted::Always);
+		assert!(RequireCommitted::from_str("sometimes").is_err());
+	}
+
+	#[test]
+	fn test_should_check() {
+		assert!(!RequireCommitted::Never.should_check(true));
+		assert!(RequireCommitted::Ci.should_check(true));
+		assert!(!RequireCommitted::Ci.should_check(false));
+		assert!(RequireCommitted::Always.should_check(false));
+	}
+}
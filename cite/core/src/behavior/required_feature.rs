@@ -0,0 +1,77 @@
+/// Cargo feature a citation depends on, validated only while the feature is enabled
+///
+/// Content only relevant to a disabled feature should not fail the build. The feature is enabled
+/// when its `CARGO_FEATURE_<NAME>` variable is set as the macro expands. Cargo sets these
+/// variables for build scripts, so a crate forwards the ones its citations need, e.g. with
+/// `cargo:rustc-env=CARGO_FEATURE_TLS=1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredFeature {
+	name: String,
+}
+
+impl RequiredFeature {
+	/// Parse from a cargo feature name, e.g. `tls`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		let name = s.trim();
+		let is_valid = !name.is_empty()
+			&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+		if !is_valid {
+			return Err(format!(
+				"Invalid requires_feature: '{}'. Expected a cargo feature name like \"tls\"",
+				s
+			));
+		}
+		Ok(Self { name: name.to_string() })
+	}
+
+	/// Get the name of the feature
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Get the environment variable that is set while the feature is enabled
+	pub fn env_var(&self) -> String {
+		format!("CARGO_FEATURE_{}", self.name.to_uppercase().replace('-', "_"))
+	}
+
+	/// Check if the feature is enabled
+	pub fn is_enabled(&self) -> bool {
+		std::env::var_os(self.env_var()).is_some()
+	}
+}
+
+impl std::fmt::Display for RequiredFeature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.name)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() {
+		let feature = RequiredFeature::from_str(" tls ").unwrap();
+		assert_eq!(feature.name(), "tls");
+		assert_eq!(feature.env_var(), "CARGO_FEATURE_TLS");
+		assert_eq!(
+			RequiredFeature::from_str("native-tls").unwrap().env_var(),
+			"CARGO_FEATURE_NATIVE_TLS"
+		);
+		for invalid in ["", "tls ssl", "dep:tls"] {
+			assert!(RequiredFeature::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+	}
+
+	#[test]
+	fn test_is_enabled() {
+		let feature = RequiredFeature::from_str("cite-required-feature-test").unwrap();
+		assert!(!feature.is_enabled());
+
+		std::env::set_var(feature.env_var(), "1");
+		assert!(feature.is_enabled());
+		std::env::remove_var(feature.env_var());
+		assert!(!feature.is_enabled());
+	}
+}
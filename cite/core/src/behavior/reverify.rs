@@ -0,0 +1,129 @@
+use super::{CitationBehavior, CitationLevel};
+use crate::CitationValidationResult;
+use chrono::{DateTime, Duration, Utc};
+
+/// Interval after which a reference snapshot must be re-confirmed by a human
+///
+/// Independent of content drift: a reference that has not changed still becomes due once its
+/// snapshot is older than the interval, and is reported at the configured level until it is
+/// refreshed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReverifyInterval {
+	days: u32,
+}
+
+impl ReverifyInterval {
+	/// Create an interval of the given number of days
+	pub fn new(days: u32) -> Self {
+		Self { days }
+	}
+
+	/// Parse from a positive number of days, e.g. `180`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.trim().parse() {
+			Ok(days) if days > 0 => Ok(Self::new(days)),
+			_ => Err(format!("Invalid reverify_days: '{}'. Expected a positive number of days", s)),
+		}
+	}
+
+	/// Get the number of days of the interval
+	pub fn days(&self) -> u32 {
+		self.days
+	}
+
+	/// Get the current time, as of macro expansion
+	pub fn now() -> DateTime<Utc> {
+		Utc::now()
+	}
+
+	/// Check if a snapshot taken at the given time must be re-confirmed
+	///
+	/// A reference without a snapshot time is always due, since its age is unknown.
+	pub fn is_due(&self, snapshot: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+		match snapshot {
+			Some(snapshot) => now - snapshot >= Duration::days(i64::from(self.days)),
+			None => true,
+		}
+	}
+
+	/// Validate the age of a snapshot, reporting a due reference like drift
+	pub fn validate(
+		&self,
+		snapshot: Option<DateTime<Utc>>,
+		now: DateTime<Utc>,
+		behavior: &CitationBehavior,
+		local_level: Option<CitationLevel>,
+	) -> CitationValidationResult {
+		if !self.is_due(snapshot, now) {
+			return CitationValidationResult::Valid;
+		}
+
+		CitationValidationResult::Invalid {
+			level: behavior.effective_level(local_level),
+			should_fail_compilation: behavior.should_fail_compilation(local_level),
+			should_report: behavior.should_report(local_level),
+		}
+	}
+}
+
+impl std::fmt::Display for ReverifyInterval {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} days", self.days)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{CitationAnnotation, CitationGlobal};
+
+	fn behavior(level: CitationLevel) -> CitationBehavior {
+		CitationBehavior::new(level, CitationAnnotation::Any, CitationGlobal::Lenient)
+	}
+
+	fn time(s: &str) -> DateTime<Utc> {
+		DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+	}
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(ReverifyInterval::from_str("180").unwrap().days(), 180);
+		assert_eq!(ReverifyInterval::from_str(" 30 ").unwrap().to_string(), "30 days");
+		for invalid in ["0", "-5", "six months", ""] {
+			assert!(ReverifyInterval::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+	}
+
+	#[test]
+	fn test_due_after_interval() {
+		let interval = ReverifyInterval::new(180);
+		let snapshot = time("2025-01-01T00:00:00Z");
+
+		assert!(!interval.is_due(Some(snapshot), time("2025-06-29T23:59:59Z")));
+		assert!(interval.is_due(Some(snapshot), time("2025-06-30T00:00:00Z")));
+		assert!(interval.is_due(None, snapshot));
+	}
+
+	#[test]
+	fn test_validate_reports_at_configured_level() {
+		let interval = ReverifyInterval::new(30);
+		let snapshot = Some(time("2025-01-01T00:00:00Z"));
+		let now = time("2025-03-01T00:00:00Z");
+
+		let result = interval.validate(snapshot, now, &behavior(CitationLevel::Warn), None);
+		assert_eq!(result.level(), Some(CitationLevel::Warn));
+		assert!(!result.should_fail_compilation());
+
+		let result = interval.validate(
+			snapshot,
+			now,
+			&behavior(CitationLevel::Warn),
+			Some(CitationLevel::Error),
+		);
+		assert!(result.should_fail_compilation());
+
+		let result =
+			interval.validate(snapshot, snapshot.unwrap(), &behavior(CitationLevel::Error), None);
+		assert!(result.is_valid());
+	}
+}
@@ -0,0 +1,93 @@
+/// Environment variable selecting when citation sources are validated
+pub const VALIDATE_WHEN_ENV: &str = "CITE_VALIDATE_WHEN";
+
+/// When citation sources are fetched and compared during macro expansion
+///
+/// Network-dependent citations slow down local builds. Gating validation to CI or release
+/// builds keeps `cargo check` fast; footnotes are rendered regardless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidateWhen {
+	/// Always validate (default)
+	#[default]
+	Always,
+	/// Only validate when running in CI (the `CI` environment variable is set)
+	Ci,
+	/// Only validate in release builds
+	Release,
+}
+
+impl ValidateWhen {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"always" => Ok(ValidateWhen::Always),
+			"ci" => Ok(ValidateWhen::Ci),
+			"release" => Ok(ValidateWhen::Release),
+			_ => Err(format!(
+				"Invalid {} value: '{}'. Valid values: always, ci, release",
+				VALIDATE_WHEN_ENV, s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ValidateWhen::Always => "always",
+			ValidateWhen::Ci => "ci",
+			ValidateWhen::Release => "release",
+		}
+	}
+
+	/// Load from the `CITE_VALIDATE_WHEN` environment variable, defaulting to always
+	pub fn from_env() -> Result<Self, String> {
+		match std::env::var(VALIDATE_WHEN_ENV) {
+			Ok(value) => Self::from_str(&value),
+			Err(_) => Ok(Self::default()),
+		}
+	}
+
+	/// Check if the build is running in CI, per the conventional `CI` environment variable
+	pub fn is_ci() -> bool {
+		std::env::var("CI").is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false"))
+	}
+
+	/// Check if sources should be validated under the given build conditions
+	pub fn should_validate(&self, is_ci: bool, is_release: bool) -> bool {
+		match self {
+			ValidateWhen::Always => true,
+			ValidateWhen::Ci => is_ci,
+			ValidateWhen::Release => is_release,
+		}
+	}
+}
+
+impl std::fmt::Display for ValidateWhen {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(ValidateWhen::from_str("always").unwrap(), ValidateWhen::Always);
+		assert_eq!(ValidateWhen::from_str("CI").unwrap(), ValidateWhen::Ci);
+		assert_eq!(ValidateWhen::from_str("Release").unwrap(), ValidateWhen::Release);
+		assert!(ValidateWhen::from_str("nightly").is_err());
+	}
+
+	#[test]
+	fn test_should_validate() {
+		assert!(ValidateWhen::Always.should_validate(false, false));
+
+		assert!(!ValidateWhen::Ci.should_validate(false, true));
+		assert!(ValidateWhen::Ci.should_validate(true, false));
+
+		assert!(!ValidateWhen::Release.should_validate(true, false));
+		assert!(ValidateWhen::Release.should_validate(false, true));
+	}
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// Drift severity - how substantive a detected change is
+///
+/// Classified by [crate::Diff::describe_change] for reporting, so that low-severity changes,
+/// like reformatting, can be told apart from changes to what was cited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeClass {
+	/// Only whitespace changed: trailing whitespace, line endings or blank lines
+	Whitespace,
+	/// Only comments changed, besides whitespace
+	Comment,
+	/// Only the text changed, not the structure of the content
+	Text,
+	/// The structure of the content changed, e.g. the markup of a page
+	Structure,
+	/// The code changed
+	Code,
+}
+
+impl ChangeClass {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"whitespace" => Ok(ChangeClass::Whitespace),
+			"comment" => Ok(ChangeClass::Comment),
+			"text" => Ok(ChangeClass::Text),
+			"structure" => Ok(ChangeClass::Structure),
+			"code" => Ok(ChangeClass::Code),
+			_ => Err(format!(
+				"Invalid change class: '{}'. Valid values: whitespace, comment, text, structure, code",
+				s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ChangeClass::Whitespace => "whitespace",
+			ChangeClass::Comment => "comment",
+			ChangeClass::Text => "text",
+			ChangeClass::Structure => "structure",
+			ChangeClass::Code => "code",
+		}
+	}
+
+	/// Describe the change for validation messages, e.g. `whitespace-only`
+	pub fn description(&self) -> &'static str {
+		match self {
+			ChangeClass::Whitespace => "whitespace-only",
+			ChangeClass::Comment => "comment-only",
+			ChangeClass::Text => "minor text",
+			ChangeClass::Structure => "structural",
+			ChangeClass::Code => "code",
+		}
+	}
+
+	/// Check if the change is of low severity, leaving what was cited intact
+	pub fn is_minor(&self) -> bool {
+		matches!(self, ChangeClass::Whitespace | ChangeClass::Comment | ChangeClass::Text)
+	}
+}
+
+impl std::fmt::Display for ChangeClass {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.description())
+	}
+}
+
+/// Get the lines that are not blank, without their trailing whitespace and line endings
+///
+/// Lines differing only in those compare equal. Indentation and the whitespace within a line
+/// are kept, as they can be significant, e.g. in Python or in string literals.
+pub fn significant_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+	lines.into_iter().map(str::trim_end).filter(|line| !line.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		assert_eq!(ChangeClass::from_str("whitespace")?, ChangeClass::Whitespace);
+		assert_eq!(ChangeClass::from_str("Code")?, ChangeClass::Code);
+		assert!(ChangeClass::from_str("typo").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_significant_lines() {
+		assert_eq!(significant_lines("a  \r\n\n\tb\n".lines()), ["a", "\tb"]);
+		assert_ne!(significant_lines(["  a"]), significant_lines(["a"]));
+		assert_ne!(significant_lines(["a b"]), significant_lines(["ab"]));
+	}
+
+	#[test]
+	fn test_severity_order() {
+		assert!(ChangeClass::Whitespace < ChangeClass::Comment);
+		assert!(ChangeClass::Comment < ChangeClass::Code);
+		assert!(ChangeClass::Text.is_minor());
+		assert!(!ChangeClass::Structure.is_minor());
+		assert!(!ChangeClass::Code.is_minor());
+	}
+}
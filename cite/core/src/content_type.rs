@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Format of cited content, declared by its [crate::Content] so that drift can be compared and
+/// rendered in a way that suits the format rather than per source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentType {
+	/// Plain text, compared line by line
+	#[default]
+	Text,
+	/// A JSON document
+	Json,
+	/// An HTML document or fragment
+	Html,
+	/// Content that is not text, which has no meaningful line diff
+	Binary,
+}
+
+/// File extensions of content that is not text
+const BINARY_EXTENSIONS: [&str; 18] = [
+	"png", "jpg", "jpeg", "gif", "webp", "ico", "pdf", "zip", "gz", "tgz", "xz", "wasm", "woff",
+	"woff2", "so", "dylib", "dll", "exe",
+];
+
+impl ContentType {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"text" => Ok(ContentType::Text),
+			"json" => Ok(ContentType::Json),
+			"html" => Ok(ContentType::Html),
+			"binary" => Ok(ContentType::Binary),
+			_ => Err(format!(
+				"Invalid content type: '{}'. Valid values: text, json, html, binary",
+				s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ContentType::Text => "text",
+			ContentType::Json => "json",
+			ContentType::Html => "html",
+			ContentType::Binary => "binary",
+		}
+	}
+
+	/// Detect the content type from a MIME type, e.g. the `Content-Type` header of a response
+	///
+	/// Parameters such as the charset are ignored. `+json` types are JSON, other textual types
+	/// (`text/*`, XML and JavaScript) are text and anything else is binary.
+	pub fn from_mime(mime: &str) -> Self {
+		let essence = mime.split(';').next().unwrap_or_default().trim().to_lowercase();
+		match essence.as_str() {
+			"application/json" => ContentType::Json,
+			"text/html" | "application/xhtml+xml" => ContentType::Html,
+			essence if essence.ends_with("+json") => ContentType::Json,
+			essence
+				if essence.starts_with("text/")
+					|| essence.ends_with("+xml")
+					|| matches!(essence, "application/xml" | "application/javascript") =>
+			{
+				ContentType::Text
+			}
+			"" => ContentType::Text,
+			_ => ContentType::Binary,
+		}
+	}
+
+	/// Detect the content type from the extension of a file path, defaulting to text
+	pub fn from_path(path: &Path) -> Self {
+		let extension = match path.extension().and_then(|extension| extension.to_str()) {
+			Some(extension) => extension.to_lowercase(),
+			None => return ContentType::Text,
+		};
+		match extension.as_str() {
+			"json" => ContentType::Json,
+			"html" | "htm" => ContentType::Html,
+			extension if BINARY_EXTENSIONS.contains(&extension) => ContentType::Binary,
+			_ => ContentType::Text,
+		}
+	}
+}
+
+impl std::fmt::Display for ContentType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_mime() {
+		assert_eq!(ContentType::from_mime("application/json"), ContentType::Json);
+		assert_eq!(ContentType::from_mime("application/vnd.github+json"), ContentType::Json);
+		assert_eq!(ContentType::from_mime("text/html; charset=utf-8"), ContentType::Html);
+		assert_eq!(ContentType::from_mime("text/plain"), ContentType::Text);
+		assert_eq!(ContentType::from_mime("image/svg+xml"), ContentType::Text);
+		assert_eq!(ContentType::from_mime("image/png"), ContentType::Binary);
+		assert_eq!(ContentType::from_mime(""), ContentType::Text);
+	}
+
+	#[test]
+	fn test_from_path() {
+		assert_eq!(ContentType::from_path(Path::new("package.json")), ContentType::Json);
+		assert_eq!(ContentType::from_path(Path::new("docs/index.HTML")), ContentType::Html);
+		assert_eq!(ContentType::from_path(Path::new("assets/logo.png")), ContentType::Binary);
+		assert_eq!(ContentType::from_path(Path::new("src/lib.rs")), ContentType::Text);
+		assert_eq!(ContentType::from_path(Path::new("LICENSE")), ContentType::Text);
+	}
+}
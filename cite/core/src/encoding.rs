@@ -0,0 +1,217 @@
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+
+/// Character encoding of fetched or read content, which is normalized to UTF-8 without a byte
+/// order mark before it is compared or cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum TextEncoding {
+	/// Detect the encoding: by the byte order mark, UTF-8 if the content is valid UTF-8, and
+	/// as sniffed by `chardetng` otherwise
+	#[default]
+	Auto,
+	/// UTF-8, replacing invalid sequences
+	Utf8,
+	/// Latin-1, decoded as its superset windows-1252 like browsers do
+	Latin1,
+	/// Any other encoding of the WHATWG Encoding Standard, e.g. Shift_JIS
+	Charset(&'static Encoding),
+}
+
+impl TextEncoding {
+	/// Parse from string, which is `auto` or a WHATWG encoding label
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().replace('_', "-").as_str() {
+			"auto" => Ok(TextEncoding::Auto),
+			"utf8" => Ok(TextEncoding::Utf8),
+			"latin-1" => Ok(TextEncoding::Latin1),
+			_ => Encoding::for_label(s.as_bytes()).map(Self::from_encoding).ok_or_else(|| {
+				format!(
+					"Invalid encoding: '{}'. Valid values: auto, or an encoding label such as \
+					 utf-8, latin-1 or shift_jis",
+					s
+				)
+			}),
+		}
+	}
+
+	/// The variant for an encoding of `encoding_rs`
+	fn from_encoding(encoding: &'static Encoding) -> Self {
+		match encoding {
+			encoding if encoding == UTF_8 => TextEncoding::Utf8,
+			encoding if encoding == WINDOWS_1252 => TextEncoding::Latin1,
+			encoding => TextEncoding::Charset(encoding),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			TextEncoding::Auto => "auto",
+			TextEncoding::Utf8 => "utf-8",
+			TextEncoding::Latin1 => "latin-1",
+			TextEncoding::Charset(encoding) => encoding.name(),
+		}
+	}
+
+	/// Check if this is the default encoding (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == TextEncoding::default()
+	}
+
+	/// Get the encoding declared by the charset of a `Content-Type`, e.g.
+	/// `text/html; charset=ISO-8859-1`, if it is a known encoding
+	pub fn from_content_type(content_type: &str) -> Option<Self> {
+		let charset = content_type.split(';').skip(1).find_map(|parameter| {
+			let (name, value) = parameter.split_once('=')?;
+			name.trim()
+				.eq_ignore_ascii_case("charset")
+				.then(|| value.trim().trim_matches('"'))
+		})?;
+		Encoding::for_label(charset.as_bytes()).map(Self::from_encoding)
+	}
+
+	/// Decode content to text, without a byte order mark
+	pub fn decode(&self, content: &[u8]) -> String {
+		let encoding = match self {
+			TextEncoding::Auto => match Encoding::for_bom(content) {
+				Some((encoding, _)) => encoding,
+				None if std::str::from_utf8(content).is_ok() => UTF_8,
+				None => {
+					let mut detector = chardetng::EncodingDetector::new();
+					detector.feed(content, true);
+					detector.guess(None, true)
+				}
+			},
+			TextEncoding::Utf8 => UTF_8,
+			TextEncoding::Latin1 => WINDOWS_1252,
+			TextEncoding::Charset(encoding) => encoding,
+		};
+		encoding.decode_with_bom_removal(content).0.into_owned()
+	}
+
+	/// Normalize content to UTF-8 without a byte order mark, leaving binary content, which has
+	/// NUL bytes that are not UTF-16 text, as it is
+	pub fn normalize(&self, content: &[u8]) -> Vec<u8> {
+		let is_utf16 = Encoding::for_bom(content).is_some_and(|(encoding, _)| encoding != UTF_8);
+		if content.contains(&0) && !is_utf16 {
+			return content.to_vec();
+		}
+		self.decode(content).into_bytes()
+	}
+}
+
+impl std::fmt::Display for TextEncoding {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+impl From<TextEncoding> for String {
+	fn from(encoding: TextEncoding) -> Self {
+		encoding.as_str().to_string()
+	}
+}
+
+impl TryFrom<String> for TextEncoding {
+	type Error = String;
+
+	fn try_from(s: String) -> Result<Self, Self::Error> {
+		TextEncoding::from_str(&s)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_text_encoding_from_str() -> Result<(), String> {
+		assert_eq!(TextEncoding::from_str("auto")?, TextEncoding::Auto);
+		assert_eq!(TextEncoding::from_str("UTF-8")?, TextEncoding::Utf8);
+		assert_eq!(TextEncoding::from_str("iso_8859_1")?, TextEncoding::Latin1);
+		assert_eq!(TextEncoding::from_str("latin-1")?.to_string(), "latin-1");
+		assert_eq!(
+			TextEncoding::from_str("shift_jis")?,
+			TextEncoding::Charset(encoding_rs::SHIFT_JIS)
+		);
+		assert_eq!(TextEncoding::from_str("windows-1252")?, TextEncoding::Latin1);
+		assert!(TextEncoding::from_str("klingon").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_decode_strips_bom() {
+		let auto = TextEncoding::Auto;
+		assert_eq!(auto.decode(b"\xef\xbb\xbfcaf\xc3\xa9"), "café");
+		assert_eq!(auto.decode(b"\xef\xbb\xbfcaf\xc3\xa9"), auto.decode("café".as_bytes()));
+		assert_eq!(TextEncoding::Utf8.decode(b"\xef\xbb\xbfcaf\xc3\xa9"), "café");
+
+		// UTF-16 is only recognized by its byte order mark
+		assert_eq!(auto.decode(b"\xff\xfec\x00a\x00f\x00\xe9\x00"), "café");
+		assert_eq!(auto.decode(b"\xfe\xff\x00c\x00a\x00f\x00\xe9"), "café");
+	}
+
+	#[test]
+	fn test_decode_latin1() {
+		// "café" in Latin-1 is not valid UTF-8
+		let latin1 = b"caf\xe9";
+		assert_eq!(TextEncoding::Auto.decode(latin1), "café");
+		assert_eq!(TextEncoding::Latin1.decode(latin1), "café");
+		assert_eq!(TextEncoding::Utf8.decode(latin1), "caf\u{fffd}");
+
+		// Valid UTF-8 is taken as such, unless Latin-1 is asked for
+		assert_eq!(TextEncoding::Latin1.decode("café".as_bytes()), "cafÃ©");
+
+		// Latin-1 is decoded as windows-1252, whose extra characters are not control codes
+		assert_eq!(TextEncoding::Latin1.decode(b"\x80 5"), "€ 5");
+	}
+
+	#[test]
+	fn test_decode_charset() -> Result<(), String> {
+		// "日本" in Shift_JIS
+		let shift_jis = b"\x93\xfa\x96\x7b";
+		assert_eq!(TextEncoding::from_str("shift_jis")?.decode(shift_jis), "日本");
+		Ok(())
+	}
+
+	#[test]
+	fn test_serde() -> Result<(), serde_json::Error> {
+		for encoding in
+			[TextEncoding::Auto, TextEncoding::Latin1, TextEncoding::Charset(encoding_rs::EUC_KR)]
+		{
+			let json = serde_json::to_string(&encoding)?;
+			assert_eq!(serde_json::from_str::<TextEncoding>(&json)?, encoding);
+		}
+		assert_eq!(serde_json::to_string(&TextEncoding::Latin1)?, "\"latin-1\"");
+		Ok(())
+	}
+
+	#[test]
+	fn test_normalize() {
+		assert_eq!(TextEncoding::Auto.normalize(b"\xef\xbb\xbfcaf\xe9"), "café".as_bytes());
+		assert_eq!(TextEncoding::Auto.normalize(b"caf\xe9"), "café".as_bytes());
+		assert_eq!(TextEncoding::Auto.normalize(b"\xff\xfeA\x00"), b"A");
+
+		// Binary content is left alone
+		assert_eq!(TextEncoding::Auto.normalize(b"\x89PNG\0\xe9"), b"\x89PNG\0\xe9");
+	}
+
+	#[test]
+	fn test_from_content_type() {
+		assert_eq!(
+			TextEncoding::from_content_type("text/html; charset=ISO-8859-1"),
+			Some(TextEncoding::Latin1)
+		);
+		assert_eq!(
+			TextEncoding::from_content_type("application/json; charset=\"utf-8\""),
+			Some(TextEncoding::Utf8)
+		);
+		assert_eq!(TextEncoding::from_content_type("text/html"), None);
+		assert_eq!(
+			TextEncoding::from_content_type("text/html; charset=shift_jis"),
+			Some(TextEncoding::Charset(encoding_rs::SHIFT_JIS))
+		);
+		assert_eq!(TextEncoding::from_content_type("text/html; charset=unknown"), None);
+	}
+}
@@ -81,6 +81,9 @@
 //!
 //! // Override locally
 //! let result = comparison.validate(&behavior, Some(CitationLevel::Warn));
+//!
+//! // Or supply a custom significance test for the diff
+//! let result = comparison.validate_with(|diff| diff.has_changes, &behavior, None);
 //! ```
 //!
 //! # Mock Implementation
@@ -113,12 +116,27 @@
 //! New source types integrate seamlessly with the existing validation and behavior system.
 
 pub mod behavior;
+pub mod change;
+pub mod content_type;
+pub mod encoding;
 pub mod id;
 pub mod macro_ergonomics;
 pub mod mock;
+pub mod moves;
+pub mod registry;
+pub mod sensitivity;
+pub mod text_diff;
+pub mod trace;
 pub mod ui;
 
-pub use behavior::{CitationAnnotation, CitationBehavior, CitationGlobal, CitationLevel};
+pub use behavior::{
+	ChangeLevels, CitationAnnotation, CitationBehavior, CitationDeadline, CitationGlobal,
+	CitationLevel, EmptyReferencePolicy, Layered, OfflinePolicy, PartialBehavior, RequireCommitted,
+	RequiredFeature, ReverifyInterval, ValidateWhen,
+};
+pub use change::ChangeClass;
+pub use content_type::ContentType;
+pub use encoding::TextEncoding;
 pub use id::Id;
 pub use macro_ergonomics::{
 	create_kwargs_from_params, create_standard_json_from_kwargs, generate_doc_attr_string,
@@ -126,6 +144,15 @@ pub use macro_ergonomics::{
 	MacroResult,
 };
 pub use mock::{mock_source_changed, mock_source_same, MockSource};
+pub use moves::{label_moves, only_moves, LineChange};
+pub use registry::ReasonRegistry;
+pub use sensitivity::{
+	file_header_flags, text_similarity, DiffSensitivity, DiffStats, MinSimilarity,
+};
+pub use text_diff::{
+	label_lines, render_text_diff, render_unified_diff_inline, text_diff_stats,
+	unified_diff_to_patch, DiffGranularity,
+};
 pub use ui::{AboveDocAttr, SourceUi, SourceUiError};
 
 /// Errors thrown by the [Source].
@@ -145,6 +172,10 @@ pub enum SourceError {
 
 	#[error("External dependency error: {0}")]
 	ExternalDependency(String),
+
+	/// The reference is part of the citation itself, so it must be updated by editing the citation
+	#[error("{0}")]
+	ManualUpdate(String),
 }
 
 /// [Diff] is a trait that contains information as to the diff between two [Content] types.
@@ -152,12 +183,81 @@ pub enum SourceError {
 /// TODO: we need to standardize a diff output format, s.t., we can add a method to the [Source] trait.
 pub trait Diff {
 	fn is_empty(&self) -> bool;
+
+	/// Get how similar the diffed contents are, from 0.0 (nothing in common) to 1.0 (identical)
+	///
+	/// Diffs that do not compare texts report 1.0 when empty and 0.0 otherwise.
+	fn similarity(&self) -> f64 {
+		if self.is_empty() {
+			1.0
+		} else {
+			0.0
+		}
+	}
+
+	/// Classify the severity of the change, e.g. whitespace-only, for richer reporting
+	///
+	/// Returns `None` for empty diffs and for diffs the source does not classify.
+	fn describe_change(&self) -> Option<ChangeClass> {
+		None
+	}
+
+	/// Render the changes as a unified diff, for the sources that compare texts
+	///
+	/// Returns `None` for diffs without changed text and for diffs that do not compare texts.
+	fn unified_diff(&self) -> Option<&str> {
+		None
+	}
+
+	/// Render the changes as a unified diff whose changed lines are diffed word by word, marking
+	/// the changed words inline as `[-removed-]{+added+}`
+	///
+	/// Returns `None` for diffs without a [Diff::unified_diff].
+	fn unified_diff_words(&self) -> Option<String> {
+		self.unified_diff()
+			.map(|unified_diff| render_unified_diff_inline(unified_diff, DiffGranularity::Words))
+	}
+
+	/// Render the changes in the given granularity, e.g. for the message of a drifted citation
+	///
+	/// Returns `None` for diffs without a [Diff::unified_diff].
+	fn render_diff(&self, granularity: DiffGranularity) -> Option<String> {
+		match granularity {
+			DiffGranularity::Lines => self.unified_diff().map(str::to_string),
+			DiffGranularity::Words => self.unified_diff_words(),
+			DiffGranularity::Chars => self
+				.unified_diff()
+				.map(|unified_diff| render_unified_diff_inline(unified_diff, granularity)),
+		}
+	}
+
+	/// Serialize the diff as a standalone patch file of the cited source, e.g. to archive the
+	/// drift a citation failed on
+	///
+	/// Diffs with file headers of their own keep them, such as the repository paths of git
+	/// sources; others are headed with the id, see [unified_diff_to_patch]. Diffs narrowed to
+	/// some lines or a region of a file may not apply to the whole file.
+	///
+	/// Returns `None` for diffs without a [Diff::unified_diff].
+	fn to_patch_file(&self, id: &Id) -> Option<String> {
+		Some(unified_diff_to_patch(self.unified_diff()?, id.as_str()))
+	}
 }
 
-/// [Content] is a marker trait.
+/// [Content] is what a [Source] provides, referenced or current.
 ///
 /// TODO: we should constrain this to have some kind of formatter.
-pub trait Content {}
+pub trait Content {
+	/// Get the format of the content, which drift comparison and rendering can follow
+	fn content_type(&self) -> ContentType {
+		ContentType::Text
+	}
+
+	/// Check if the content is empty or whitespace-only, i.e. there is nothing to compare
+	fn is_blank(&self) -> bool {
+		false
+	}
+}
 
 /// [Referenced] marks the [Content] type that was originally referenced by the [Source].
 pub trait Referenced: Content {}
@@ -181,6 +281,29 @@ pub trait Source<R: Referenced, C: Current<R, D>, D: Diff> {
 		self.id().as_str()
 	}
 
+	/// Describe the cited target for the header of validation messages, as one
+	/// `Label: value` line per property
+	fn target_description(&self) -> String {
+		format!("Source: {}", self.name())
+	}
+
+	/// Check if the source can be validated without network access, e.g. because its content
+	/// is local or its reference is already cached
+	fn supports_offline(&self) -> bool {
+		false
+	}
+
+	/// Accept the current content as the new reference
+	///
+	/// Sources whose reference is part of the citation itself cannot be refreshed and return
+	/// [SourceError::ManualUpdate], describing what to edit instead.
+	fn refresh(&self) -> Result<(), SourceError> {
+		Err(SourceError::ManualUpdate(format!(
+			"{} cannot be refreshed; update the citation",
+			self.name()
+		)))
+	}
+
 	fn get_referenced(&self) -> Result<R, SourceError>;
 
 	fn get_current(&self) -> Result<C, SourceError>;
@@ -198,6 +321,8 @@ pub struct Comparison<R: Referenced, C: Current<R, D>, D: Diff> {
 	pub referenced: R,
 	pub current: C,
 	pub diff: D,
+	/// Warnings about how the comparison was made, e.g. that its reference was captured empty
+	pub notes: Vec<String>,
 }
 
 impl<R, C, D> Comparison<R, C, D>
@@ -207,7 +332,17 @@ where
 	D: Diff,
 {
 	pub fn new(referenced: R, current: C, diff: D) -> Self {
-		Self { referenced, current, diff }
+		Self { referenced, current, diff, notes: Vec::new() }
+	}
+
+	/// Add a warning to report alongside the validation of the comparison
+	pub fn with_note(mut self, note: String) -> Self {
+		self.notes.push(note);
+		self
+	}
+
+	pub fn notes(&self) -> &[String] {
+		&self.notes
 	}
 
 	pub fn referenced(&self) -> &R {
@@ -226,6 +361,23 @@ where
 		self.diff.is_empty()
 	}
 
+	/// Get the format drift is rendered in, that of the current content
+	pub fn content_type(&self) -> ContentType {
+		self.current.content_type()
+	}
+
+	/// Render the drift in the given granularity, following the format of the content
+	///
+	/// Binary content has no meaningful text diff, so none is rendered for it.
+	pub fn render_diff(&self, granularity: DiffGranularity) -> Option<String> {
+		match self.content_type() {
+			ContentType::Binary => None,
+			ContentType::Text | ContentType::Json | ContentType::Html => {
+				self.diff.render_diff(granularity)
+			}
+		}
+	}
+
 	/// Validate this comparison against behavior configuration
 	pub fn validate(
 		&self,
@@ -243,6 +395,26 @@ where
 			}
 		}
 	}
+
+	/// Validate this comparison, treating diffs the predicate deems insignificant as unchanged
+	///
+	/// Useful for runtime users who need a custom significance test, e.g. ignoring changes
+	/// under a threshold. The predicate is only consulted for non-empty diffs.
+	pub fn validate_with<F>(
+		&self,
+		is_significant: F,
+		behavior: &CitationBehavior,
+		local_level: Option<CitationLevel>,
+	) -> CitationValidationResult
+	where
+		F: Fn(&D) -> bool,
+	{
+		if !self.is_same() && !is_significant(&self.diff) {
+			return CitationValidationResult::Valid;
+		}
+
+		self.validate(behavior, local_level)
+	}
 }
 
 /// Result of citation validation
@@ -293,3 +465,100 @@ impl CitationValidationResult {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Deems a diff significant only if the content length changed by more than two bytes
+	fn changed_by_more_than_two_bytes(diff: &mock::StringDiff) -> bool {
+		diff.current.len().abs_diff(diff.referenced.len()) > 2
+	}
+
+	#[test]
+	fn test_validate_with_insignificant_diff() -> Result<(), SourceError> {
+		let behavior = CitationBehavior::default();
+		let comparison = mock_source_changed("version 1.0", "version 1.1").get()?;
+
+		assert!(!comparison.validate(&behavior, None).is_valid());
+
+		let result = comparison.validate_with(changed_by_more_than_two_bytes, &behavior, None);
+		assert!(result.is_valid());
+		Ok(())
+	}
+
+	#[test]
+	fn test_mock_content_is_text() -> Result<(), SourceError> {
+		let comparison = mock_source_changed("{\"version\": 1}", "{\"version\": 2}").get()?;
+		assert_eq!(comparison.referenced().content_type(), ContentType::Text);
+		assert_eq!(comparison.content_type(), ContentType::Text);
+		assert_eq!(
+			comparison.render_diff(DiffGranularity::Lines),
+			comparison.diff().render_diff(DiffGranularity::Lines)
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_validate_with_significant_diff() -> Result<(), SourceError> {
+		let behavior = CitationBehavior::default();
+		let comparison = mock_source_changed("version 1.0", "version 1.0 (deprecated)").get()?;
+
+		let result = comparison.validate_with(changed_by_more_than_two_bytes, &behavior, None);
+		assert_eq!(result, comparison.validate(&behavior, None));
+		assert!(!result.is_valid());
+		Ok(())
+	}
+
+	#[test]
+	fn test_mock_target_description() {
+		assert_eq!(mock_source_same("stable").target_description(), "Mock: stable");
+
+		let long = mock_source_changed(
+			"A referenced paragraph that runs well past forty characters",
+			"changed",
+		);
+		assert_eq!(long.target_description(), "Mock: A referenced paragraph that runs well pa...");
+		assert_eq!(mock_source_same("first\nsecond").target_description(), "Mock: first...");
+	}
+
+	#[test]
+	fn test_to_patch_file() {
+		struct TextDiff(Option<&'static str>);
+		impl Diff for TextDiff {
+			fn is_empty(&self) -> bool {
+				self.0.is_none()
+			}
+
+			fn unified_diff(&self) -> Option<&str> {
+				self.0
+			}
+		}
+
+		let id = Id::new("http_example.com_docs".to_string());
+		assert_eq!(
+			TextDiff(Some("-old\n+new")).to_patch_file(&id).as_deref(),
+			Some(
+				"--- a/http_example.com_docs\n+++ b/http_example.com_docs\n@@ -1,1 +1,1 @@\n-old\n+new\n"
+			)
+		);
+		assert_eq!(TextDiff(None).to_patch_file(&id), None);
+
+		// Diffs that do not compare texts have no patch
+		let diff = mock::StringDiff {
+			has_changes: true,
+			referenced: "old".to_string(),
+			current: "new".to_string(),
+		};
+		assert_eq!(diff.to_patch_file(&id), None);
+	}
+
+	#[test]
+	fn test_validate_with_skips_predicate_for_empty_diff() -> Result<(), SourceError> {
+		let behavior = CitationBehavior::default();
+		let comparison = mock_source_same("stable").get()?;
+
+		assert!(comparison.validate_with(|_| true, &behavior, None).is_valid());
+		Ok(())
+	}
+}
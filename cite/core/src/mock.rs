@@ -1,6 +1,9 @@
 pub mod ui;
 
-use crate::{Comparison, Content, Current, Diff, Id, Referenced, Source, SourceError};
+use crate::{
+	text_similarity, Comparison, Content, Current, Diff, DiffSensitivity, Id, MinSimilarity,
+	Referenced, Source, SourceError,
+};
 use serde::{Deserialize, Serialize};
 
 // ==============================================================================
@@ -38,6 +41,27 @@ impl Diff for StringDiff {
 	fn is_empty(&self) -> bool {
 		!self.has_changes
 	}
+
+	fn similarity(&self) -> f64 {
+		text_similarity(&self.referenced, &self.current)
+	}
+}
+
+impl StringDiff {
+	/// Filter the change set by sensitivity, so only the relevant kinds of changes count
+	pub fn with_sensitivity(mut self, sensitivity: DiffSensitivity) -> Self {
+		self.has_changes =
+			self.has_changes && sensitivity.is_text_drift(&self.referenced, &self.current);
+		self
+	}
+
+	/// Accept changes that leave the content at least as similar as the bound, if any
+	pub fn with_min_similarity(mut self, min_similarity: Option<MinSimilarity>) -> Self {
+		if let Some(min_similarity) = min_similarity {
+			self.has_changes = self.has_changes && !min_similarity.accepts(self.similarity());
+		}
+		self
+	}
 }
 
 impl Current<ReferencedString, StringDiff> for CurrentString {
@@ -56,6 +80,14 @@ pub struct MockSource {
 	pub id: Id,
 	pub referenced_content: String,
 	pub current_content: String,
+	#[serde(default, skip_serializing_if = "DiffSensitivity::is_default")]
+	pub sensitivity: DiffSensitivity,
+	/// Accepted values for the current content; when set, these replace the referenced content
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub any_of: Vec<String>,
+	/// Lower bound on the similarity of changed content for the change to still be accepted
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub min_similarity: Option<MinSimilarity>,
 }
 
 impl MockSource {
@@ -65,9 +97,32 @@ impl MockSource {
 			id: Id::new(format!("mock_source_{}", referenced)),
 			referenced_content: referenced,
 			current_content: current,
+			sensitivity: DiffSensitivity::default(),
+			any_of: Vec::new(),
+			min_similarity: None,
 		}
 	}
 
+	/// Set which kinds of changes count as drift
+	pub fn with_sensitivity(mut self, sensitivity: DiffSensitivity) -> Self {
+		self.sensitivity = sensitivity;
+		self
+	}
+
+	/// Accept any of the given values as the current content
+	///
+	/// The citation only reports drift when the current content matches none of them.
+	pub fn with_any_of(mut self, any_of: Vec<String>) -> Self {
+		self.any_of = any_of;
+		self
+	}
+
+	/// Accept changes to content that stays at least as similar as the bound
+	pub fn with_min_similarity(mut self, min_similarity: MinSimilarity) -> Self {
+		self.min_similarity = Some(min_similarity);
+		self
+	}
+
 	/// Helper for when referenced and current are the same (no diff)
 	pub fn same(content: String) -> Self {
 		Self::new(content.clone(), content)
@@ -96,6 +151,20 @@ impl Source<ReferencedString, CurrentString, StringDiff> for MockSource {
 		&self.id
 	}
 
+	fn target_description(&self) -> String {
+		format!("Mock: {}", summarize(&self.referenced_content))
+	}
+
+	fn supports_offline(&self) -> bool {
+		true
+	}
+
+	fn refresh(&self) -> Result<(), SourceError> {
+		Err(SourceError::ManualUpdate(
+			"mock citations are references themselves; update the citation".to_string(),
+		))
+	}
+
 	fn get_referenced(&self) -> Result<ReferencedString, SourceError> {
 		Ok(ReferencedString(self.referenced_content.clone()))
 	}
@@ -105,14 +174,45 @@ impl Source<ReferencedString, CurrentString, StringDiff> for MockSource {
 	}
 
 	fn get(&self) -> Result<Comparison<ReferencedString, CurrentString, StringDiff>, SourceError> {
-		let referenced = ReferencedString(self.referenced_content.clone());
 		let current = CurrentString(self.current_content.clone());
-		let diff = current.diff(&referenced)?;
+		let diff_against = |referenced: &ReferencedString| {
+			current.diff(referenced).map(|diff| {
+				diff.with_sensitivity(self.sensitivity).with_min_similarity(self.min_similarity)
+			})
+		};
+
+		// Compare against the first accepted value that matches, or the first one if none do
+		if let Some(first) = self.any_of.first() {
+			for value in &self.any_of {
+				let referenced = ReferencedString(value.clone());
+				let diff = diff_against(&referenced)?;
+				if diff.is_empty() {
+					return Ok(Comparison::new(referenced, current, diff));
+				}
+			}
+
+			let referenced = ReferencedString(first.clone());
+			let diff = diff_against(&referenced)?;
+			return Ok(Comparison::new(referenced, current, diff));
+		}
+
+		let referenced = ReferencedString(self.referenced_content.clone());
+		let diff = diff_against(&referenced)?;
 
 		Ok(Comparison::new(referenced, current, diff))
 	}
 }
 
+/// Summarize content to its first line, shortened to at most 40 characters
+fn summarize(content: &str) -> String {
+	let first_line = content.lines().next().unwrap_or_default();
+	let mut summary = first_line.chars().take(40).collect::<String>();
+	if summary != content {
+		summary.push_str("...");
+	}
+	summary
+}
+
 // ==============================================================================
 // Macro Pattern Matching Support
 // ==============================================================================
@@ -1,5 +1,6 @@
 use crate::mock::{CurrentString, MockSource, ReferencedString, StringDiff};
-use crate::ui::{AboveDocAttr, SourceUi, SourceUiError};
+use crate::ui::{parse_any_of, parse_min_similarity, AboveDocAttr, SourceUi, SourceUiError};
+use crate::DiffSensitivity;
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
@@ -54,7 +55,7 @@ impl SourceUi<ReferencedString, CurrentString, StringDiff> for MockSource {
 			// Direct serde fields
 			"id" | "referenced_content" | "current_content" |
 			// Legacy ergonomic fields
-			"same" | "changed" | "referenced" | "current" |
+			"same" | "changed" | "referenced" | "current" | "sensitivity" | "any_of" | "min_similarity" |
 			// Citation-level fields
 			"src" | "reason" | "level" | "annotation" => true,
 			_ => false,
@@ -80,6 +81,12 @@ impl MockSource {
 
 	/// Try legacy syntax patterns for backward compatibility
 	fn try_legacy_syntax(kwargs: &HashMap<String, Value>) -> Result<Self, SourceUiError> {
+		// Extract optional accepted values
+		let any_of = match kwargs.get("any_of") {
+			Some(value) => parse_any_of(value)?,
+			None => Vec::new(),
+		};
+
 		// Handle legacy "changed" syntax first
 		let (referenced_content, current_content) = if let Some(changed_val) = kwargs.get("changed")
 		{
@@ -106,12 +113,13 @@ impl MockSource {
 				.or_else(|| kwargs.get("referenced_content"))
 				.or_else(|| kwargs.get("same")) // Legacy support
 				.and_then(|v| v.as_str())
+				.map(|s| s.to_string())
+				.or_else(|| any_of.first().cloned())
 				.ok_or_else(|| {
 					SourceUiError::MissingParameter(
-						"referenced, referenced_content, same, or changed".to_string(),
+						"referenced, referenced_content, same, changed, or any_of".to_string(),
 					)
-				})?
-				.to_string();
+				})?;
 
 			let current = kwargs
 				.get("current")
@@ -126,8 +134,21 @@ impl MockSource {
 		// Extract optional name parameter
 		let name = kwargs.get("name").and_then(|v| v.as_str()).map(|s| s.to_string());
 
+		// Extract optional diff sensitivity
+		let sensitivity = match kwargs.get("sensitivity").and_then(|v| v.as_str()) {
+			Some(s) => DiffSensitivity::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => DiffSensitivity::default(),
+		};
+
 		// Create the MockSource
-		let mut source = MockSource::new(referenced_content, current_content);
+		let mut source = MockSource::new(referenced_content, current_content)
+			.with_sensitivity(sensitivity)
+			.with_any_of(any_of);
+
+		// Extract optional similarity bound for accepting small changes
+		if let Some(value) = kwargs.get("min_similarity") {
+			source = source.with_min_similarity(parse_min_similarity(value)?);
+		}
 
 		// Override the ID if a name was provided
 		if let Some(name) = name {
@@ -141,6 +162,7 @@ impl MockSource {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::{Diff, Source};
 	use serde_json::json;
 
 	#[test]
@@ -345,6 +367,100 @@ mod tests {
 		assert_eq!(mock_source.current_content, "new content");
 	}
 
+	#[test]
+	fn test_from_kwarg_json_sensitivity_appended() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("changed".to_string(), json!(["guarantee\n", "guarantee\naddition\n"]));
+		kwargs.insert("sensitivity".to_string(), json!("removals"));
+
+		let mock_source = MockSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(mock_source.sensitivity, DiffSensitivity::Removals);
+		assert!(mock_source.get().unwrap().is_same());
+
+		kwargs.insert("sensitivity".to_string(), json!("both"));
+		let mock_source = MockSource::from_kwarg_json(&kwargs).unwrap();
+		assert!(!mock_source.get().unwrap().is_same());
+	}
+
+	#[test]
+	fn test_from_kwarg_json_sensitivity_removed() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("changed".to_string(), json!(["guarantee\nremoved\n", "guarantee\n"]));
+		kwargs.insert("sensitivity".to_string(), json!("removals"));
+
+		let mock_source = MockSource::from_kwarg_json(&kwargs).unwrap();
+		assert!(!mock_source.get().unwrap().is_same());
+
+		kwargs.insert("sensitivity".to_string(), json!("additions"));
+		let mock_source = MockSource::from_kwarg_json(&kwargs).unwrap();
+		assert!(mock_source.get().unwrap().is_same());
+	}
+
+	#[test]
+	fn test_from_kwarg_json_any_of_matches_one() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("any_of".to_string(), json!(["Hello", "Hi", "Hey"]));
+		kwargs.insert("current".to_string(), json!("Hi"));
+
+		let mock_source = MockSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(mock_source.any_of, vec!["Hello", "Hi", "Hey"]);
+
+		let comparison = mock_source.get().unwrap();
+		assert!(comparison.is_same());
+		assert_eq!(comparison.referenced().0, "Hi");
+	}
+
+	#[test]
+	fn test_from_kwarg_json_any_of_matches_none() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("any_of".to_string(), json!(["Hello", "Hi", "Hey"]));
+		kwargs.insert("current".to_string(), json!("Goodbye"));
+
+		let comparison = MockSource::from_kwarg_json(&kwargs).unwrap().get().unwrap();
+		assert!(!comparison.is_same());
+		assert_eq!(comparison.referenced().0, "Hello");
+
+		kwargs.insert("any_of".to_string(), json!([]));
+		let result = MockSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_min_similarity() {
+		let referenced =
+			"Requests are retried three times with exponential backoff before failing.";
+		let mut kwargs = HashMap::new();
+		kwargs.insert("referenced".to_string(), json!(referenced));
+		kwargs.insert("current".to_string(), json!(referenced.replace("failing", "giving up")));
+		kwargs.insert("min_similarity".to_string(), json!("0.8"));
+
+		// A minor wording change stays above the bound
+		let comparison = MockSource::from_kwarg_json(&kwargs).unwrap().get().unwrap();
+		assert!(comparison.is_same());
+		assert!(comparison.diff().similarity() < 1.0);
+
+		// A rewrite falls below it
+		kwargs.insert("current".to_string(), json!("Requests fail immediately."));
+		let comparison = MockSource::from_kwarg_json(&kwargs).unwrap().get().unwrap();
+		assert!(!comparison.is_same());
+
+		kwargs.insert("min_similarity".to_string(), json!(0.5));
+		assert!(MockSource::from_kwarg_json(&kwargs).is_ok());
+		kwargs.insert("min_similarity".to_string(), json!("most"));
+		let result = MockSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_invalid_sensitivity() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("same".to_string(), json!("content"));
+		kwargs.insert("sensitivity".to_string(), json!("sideways"));
+
+		let result = MockSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
 	#[test]
 	fn test_is_valid_attr_key() {
 		// Test valid direct serde fields
@@ -0,0 +1,175 @@
+use similar::ChangeTag;
+
+/// How a line of a diff changed, telling relocated lines apart from changed ones
+///
+/// A block being moved shows up in a line diff as a removal plus an insertion of the same
+/// lines. Labelling both sides as moves keeps such a diff from exaggerating the change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+	/// The line is unchanged
+	Equal,
+	/// The line was removed
+	Removed,
+	/// The line was added
+	Added,
+	/// The line was moved away from here, unchanged
+	MovedFrom,
+	/// The line was moved here, unchanged
+	MovedTo,
+}
+
+impl LineChange {
+	/// Get the prefix of the line in a unified diff
+	///
+	/// Moved lines use `<` (moved away) and `>` (moved here) instead of `-` and `+`.
+	pub fn sign(&self) -> char {
+		match self {
+			LineChange::Equal => ' ',
+			LineChange::Removed => '-',
+			LineChange::Added => '+',
+			LineChange::MovedFrom => '<',
+			LineChange::MovedTo => '>',
+		}
+	}
+
+	/// Check if the line was moved
+	pub fn is_move(&self) -> bool {
+		matches!(self, LineChange::MovedFrom | LineChange::MovedTo)
+	}
+
+	/// Check if the line was removed or added, rather than moved or left as is
+	pub fn is_change(&self) -> bool {
+		matches!(self, LineChange::Removed | LineChange::Added)
+	}
+}
+
+/// Label the lines of a diff, marking removed blocks that reappear elsewhere as moves
+///
+/// A block is a run of consecutive removed or added lines. A removed block is moved when an
+/// added block consists of the same lines; each added block matches at most one removed block.
+/// Blocks of blank lines are never moves, since they carry no content.
+pub fn label_moves(changes: &[(ChangeTag, &str)]) -> Vec<LineChange> {
+	let mut labels = changes
+		.iter()
+		.map(|(tag, _)| match tag {
+			ChangeTag::Equal => LineChange::Equal,
+			ChangeTag::Delete => LineChange::Removed,
+			ChangeTag::Insert => LineChange::Added,
+		})
+		.collect::<Vec<_>>();
+
+	let removed = blocks(changes, ChangeTag::Delete);
+	let mut added = blocks(changes, ChangeTag::Insert);
+
+	for removed_block in removed {
+		let lines = block_lines(changes, &removed_block);
+		if lines.iter().all(|line| line.trim().is_empty()) {
+			continue;
+		}
+
+		let matched =
+			added.iter().position(|added_block| block_lines(changes, added_block) == lines);
+		if let Some(index) = matched {
+			let added_block = added.remove(index);
+			for label in &mut labels[removed_block] {
+				*label = LineChange::MovedFrom;
+			}
+			for label in &mut labels[added_block] {
+				*label = LineChange::MovedTo;
+			}
+		}
+	}
+
+	labels
+}
+
+/// Check if the changes of a labelled diff are all moves
+///
+/// A diff without any moves is not considered to consist of moves only.
+pub fn only_moves(labels: &[LineChange]) -> bool {
+	labels.iter().any(LineChange::is_move) && !labels.iter().any(LineChange::is_change)
+}
+
+/// Find the runs of consecutive lines with the given tag
+fn blocks(changes: &[(ChangeTag, &str)], tag: ChangeTag) -> Vec<std::ops::Range<usize>> {
+	let mut blocks = Vec::new();
+	let mut start = None;
+
+	for (index, (line_tag, _)) in changes.iter().enumerate() {
+		match (*line_tag == tag, start) {
+			(true, None) => start = Some(index),
+			(false, Some(block_start)) => {
+				blocks.push(block_start..index);
+				start = None;
+			}
+			_ => {}
+		}
+	}
+	if let Some(block_start) = start {
+		blocks.push(block_start..changes.len());
+	}
+
+	blocks
+}
+
+/// Get the lines of a block, ignoring line endings so a moved last line still matches
+fn block_lines<'a>(
+	changes: &[(ChangeTag, &'a str)],
+	block: &std::ops::Range<usize>,
+) -> Vec<&'a str> {
+	changes[block.clone()]
+		.iter()
+		.map(|(_, line)| line.trim_end_matches(['\r', '\n']))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use similar::TextDiff;
+
+	fn labels(referenced: &str, current: &str) -> Vec<LineChange> {
+		let diff = TextDiff::from_lines(referenced, current);
+		let changes = diff
+			.iter_all_changes()
+			.map(|change| (change.tag(), change.value()))
+			.collect::<Vec<_>>();
+		label_moves(&changes)
+	}
+
+	fn signs(referenced: &str, current: &str) -> String {
+		labels(referenced, current).iter().map(LineChange::sign).collect()
+	}
+
+	#[test]
+	fn test_relocated_block_is_moved() {
+		let referenced = "intro\nstep one\nstep two\na\nb\nc\noutro\n";
+		let current = "intro\na\nb\nc\nstep one\nstep two\noutro\n";
+
+		let signs = signs(referenced, current);
+		assert_eq!(signs.matches('<').count(), 2);
+		assert_eq!(signs.matches('>').count(), 2);
+		assert!(!signs.contains('-') && !signs.contains('+'));
+		assert!(only_moves(&labels(referenced, current)));
+	}
+
+	#[test]
+	fn test_changed_block_is_not_moved() {
+		let signs = signs("a\nb\nc\n", "c\na\nB\n");
+		assert!(signs.contains('-') && signs.contains('+'));
+
+		// Unchanged content has no moves either
+		assert!(!only_moves(&labels("a\n", "a\n")));
+	}
+
+	#[test]
+	fn test_moved_and_changed_lines() {
+		let labels = labels("a\nmoved\nb\nold\n", "moved\na\nb\nnew\n");
+		assert!(labels.iter().any(LineChange::is_move));
+		assert!(labels.iter().any(LineChange::is_change));
+		assert!(!only_moves(&labels));
+
+		// Blank lines are never moves
+		assert!(!signs("a\n\nb\n", "\na\nb\n").contains('<'));
+	}
+}
@@ -0,0 +1,115 @@
+//! Process-wide registry of citations seen during macro expansion
+//!
+//! The procedural macro is loaded once per compiled crate, so state recorded here spans all
+//! `#[cite]` invocations in that crate. This enables lints that need to look across
+//! citations, such as detecting copy-pasted boilerplate reasons.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable for the duplicate reason threshold
+pub const DUPLICATE_REASON_THRESHOLD_ENV: &str = "CITE_DUPLICATE_REASON_THRESHOLD";
+
+/// Default number of citations that may share a reason before the lint fires
+pub const DEFAULT_DUPLICATE_REASON_THRESHOLD: usize = 3;
+
+/// Registry of citation reasons and how many citations use each
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReasonRegistry {
+	/// Number of citations using each (trimmed) reason
+	counts: HashMap<String, usize>,
+	/// Number of citations that may share a reason before warning
+	threshold: usize,
+}
+
+impl ReasonRegistry {
+	/// Create a new registry with the given threshold
+	pub fn new(threshold: usize) -> Self {
+		Self { counts: HashMap::new(), threshold }
+	}
+
+	/// Create a new registry with the threshold from the environment, or the default
+	pub fn from_env() -> Self {
+		let threshold = std::env::var(DUPLICATE_REASON_THRESHOLD_ENV)
+			.ok()
+			.and_then(|value| value.parse().ok())
+			.unwrap_or(DEFAULT_DUPLICATE_REASON_THRESHOLD);
+		Self::new(threshold)
+	}
+
+	/// Get the process-wide registry
+	pub fn global() -> &'static Mutex<ReasonRegistry> {
+		static REGISTRY: OnceLock<Mutex<ReasonRegistry>> = OnceLock::new();
+		REGISTRY.get_or_init(|| Mutex::new(ReasonRegistry::from_env()))
+	}
+
+	/// Check if the duplicate reason lint is enabled via feature flags
+	pub fn lint_enabled() -> bool {
+		cfg!(feature = "pedantic")
+	}
+
+	/// Get the threshold of this registry
+	pub fn threshold(&self) -> usize {
+		self.threshold
+	}
+
+	/// Get the number of citations recorded with the given reason
+	pub fn count(&self, reason: &str) -> usize {
+		self.counts.get(reason.trim()).copied().unwrap_or(0)
+	}
+
+	/// Record a citation's reason, returning a warning if the reason is reused by more
+	/// citations than the threshold allows
+	pub fn record_reason(&mut self, reason: &str) -> Option<String> {
+		let reason = reason.trim();
+		if reason.is_empty() {
+			return None;
+		}
+
+		let count = self.counts.entry(reason.to_string()).or_insert(0);
+		*count += 1;
+
+		if *count > self.threshold {
+			Some(format!(
+				"Citation reason \"{}\" is shared by {} citations (threshold {}); consider a more specific justification",
+				reason, count, self.threshold
+			))
+		} else {
+			None
+		}
+	}
+}
+
+impl Default for ReasonRegistry {
+	fn default() -> Self {
+		Self::new(DEFAULT_DUPLICATE_REASON_THRESHOLD)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_duplicate_reason_warns_past_threshold() {
+		let mut registry = ReasonRegistry::new(2);
+
+		assert!(registry.record_reason("test reason").is_none());
+		assert!(registry.record_reason("test reason").is_none());
+
+		let warning = registry.record_reason("test reason").unwrap();
+		assert!(warning.contains("\"test reason\" is shared by 3 citations"));
+		assert!(registry.record_reason(" test reason ").is_some());
+		assert_eq!(registry.count("test reason"), 4);
+	}
+
+	#[test]
+	fn test_distinct_reasons_do_not_warn() {
+		let mut registry = ReasonRegistry::new(1);
+
+		assert!(registry.record_reason("tracks the API version").is_none());
+		assert!(registry.record_reason("documents the wire format").is_none());
+		assert!(registry.record_reason("").is_none());
+		assert!(registry.record_reason("").is_none());
+	}
+}
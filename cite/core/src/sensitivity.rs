@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+/// Diff sensitivity - determines which kinds of changes count as drift
+///
+/// Some citations only care that referenced content does not disappear (e.g. a documented
+/// guarantee) and tolerate additions, or vice versa. The sensitivity is consulted by the
+/// [crate::Diff] implementations before deciding whether a diff is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffSensitivity {
+	/// Only removed content counts as drift
+	Removals,
+	/// Only added content counts as drift
+	Additions,
+	/// Any change counts as drift
+	#[default]
+	Both,
+}
+
+impl DiffSensitivity {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"removals" => Ok(DiffSensitivity::Removals),
+			"additions" => Ok(DiffSensitivity::Additions),
+			"both" => Ok(DiffSensitivity::Both),
+			_ => Err(format!(
+				"Invalid diff sensitivity: '{}'. Valid values: removals, additions, both",
+				s
+			)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			DiffSensitivity::Removals => "removals",
+			DiffSensitivity::Additions => "additions",
+			DiffSensitivity::Both => "both",
+		}
+	}
+
+	/// Check if this is the default sensitivity (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == DiffSensitivity::default()
+	}
+
+	/// Check if a change set with the given kinds of changes counts as drift
+	pub fn is_drift(&self, has_additions: bool, has_removals: bool) -> bool {
+		match self {
+			DiffSensitivity::Removals => has_removals,
+			DiffSensitivity::Additions => has_additions,
+			DiffSensitivity::Both => has_additions || has_removals,
+		}
+	}
+
+	/// Check if the line-level changes between two texts count as drift
+	pub fn is_text_drift(&self, referenced: &str, current: &str) -> bool {
+		let diff = TextDiff::from_lines(referenced, current);
+		let mut has_additions = false;
+		let mut has_removals = false;
+
+		for change in diff.iter_all_changes() {
+			match change.tag() {
+				ChangeTag::Insert => has_additions = true,
+				ChangeTag::Delete => has_removals = true,
+				ChangeTag::Equal => {}
+			}
+		}
+
+		self.is_drift(has_additions, has_removals)
+	}
+}
+
+impl std::fmt::Display for DiffSensitivity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+/// Get the similarity of two texts, from 0.0 (nothing in common) to 1.0 (identical)
+///
+/// Computed from the word-level ratio of their diff, so rewording a sentence weighs by the words
+/// changed rather than by the lines they are on.
+pub fn text_similarity(referenced: &str, current: &str) -> f64 {
+	f64::from(TextDiff::from_words(referenced, current).ratio())
+}
+
+/// Lower bound on the similarity of changed content for the change to still be accepted
+///
+/// A more forgiving alternative to exact matching for prose, where small wording changes do not
+/// affect what a citation relies on. See [text_similarity].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MinSimilarity(f64);
+
+impl MinSimilarity {
+	/// Create a bound, which must be between 0.0 and 1.0
+	pub fn new(bound: f64) -> Result<Self, String> {
+		if (0.0..=1.0).contains(&bound) {
+			Ok(Self(bound))
+		} else {
+			Err(format!(
+				"Invalid min_similarity: '{}'. Expected a number between 0.0 and 1.0",
+				bound
+			))
+		}
+	}
+
+	/// Parse from a number between 0.0 and 1.0, e.g. `0.95`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.trim().parse::<f64>() {
+			Ok(bound) if (0.0..=1.0).contains(&bound) => Ok(Self(bound)),
+			_ => Err(format!(
+				"Invalid min_similarity: '{}'. Expected a number between 0.0 and 1.0",
+				s
+			)),
+		}
+	}
+
+	/// Get the lower bound
+	pub fn bound(&self) -> f64 {
+		self.0
+	}
+
+	/// Check if content with the given similarity to its reference is accepted
+	pub fn accepts(&self, similarity: f64) -> bool {
+		similarity >= self.0
+	}
+}
+
+impl std::fmt::Display for MinSimilarity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+/// Flag the file header lines of a unified diff, e.g. `--- a/path` and `+++ b/path`, which are
+/// not changes in themselves
+///
+/// Headers are told apart by their position rather than their prefix, since a removed line such
+/// as `-- comment` reads like a header too: only the `---` and `+++` lines before the first hunk
+/// of a file are headers, a file starting at the top of the diff or at its `diff --git` line.
+pub fn file_header_flags<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<bool> {
+	let mut before_hunk = true;
+	lines
+		.map(|line| {
+			if line.starts_with("diff --git ") {
+				before_hunk = true;
+				return true;
+			}
+			if line.starts_with("@@") {
+				before_hunk = false;
+			}
+			before_hunk && (line.starts_with("--- ") || line.starts_with("+++ "))
+		})
+		.collect()
+}
+
+/// Line-level size of a diff, for reporting how much a citation drifted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiffStats {
+	/// Number of added lines
+	pub additions: usize,
+	/// Number of removed lines
+	pub removals: usize,
+}
+
+impl DiffStats {
+	/// Count the `+`/`-` lines of a unified diff, skipping the file headers
+	pub fn from_unified_diff(diff: &str) -> Self {
+		let mut stats = Self::default();
+
+		for (line, is_header) in diff.lines().zip(file_header_flags(diff.lines())) {
+			if is_header {
+				continue;
+			}
+			if line.starts_with('+') {
+				stats.additions += 1;
+			} else if line.starts_with('-') {
+				stats.removals += 1;
+			}
+		}
+
+		stats
+	}
+
+	/// Count the lines added and removed between two texts
+	pub fn from_texts(referenced: &str, current: &str) -> Self {
+		let mut stats = Self::default();
+
+		for change in TextDiff::from_lines(referenced, current).iter_all_changes() {
+			match change.tag() {
+				ChangeTag::Insert => stats.additions += 1,
+				ChangeTag::Delete => stats.removals += 1,
+				ChangeTag::Equal => {}
+			}
+		}
+
+		stats
+	}
+
+	/// Check if no lines changed
+	pub fn is_empty(&self) -> bool {
+		self.additions == 0 && self.removals == 0
+	}
+}
+
+impl std::fmt::Display for DiffStats {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "+{} -{}", self.additions, self.removals)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const REFERENCED: &str =
+		"The retry loop backs off exponentially between attempts, starting at half \
+		a second and doubling after every failed attempt until the configured number of retries is \
+		exhausted, at which point the last error is returned to the caller unchanged.";
+
+	#[test]
+	fn test_text_similarity() {
+		assert_eq!(text_similarity(REFERENCED, REFERENCED), 1.0);
+
+		// Rewording a single phrase keeps the text above a strict bound
+		let reworded = REFERENCED.replace("unchanged", "as is");
+		let min_similarity = MinSimilarity::from_str("0.95").unwrap();
+		assert!(min_similarity.accepts(text_similarity(REFERENCED, &reworded)));
+
+		// A rewrite does not
+		let rewritten = "Failed attempts are retried immediately, up to three times.";
+		assert!(!min_similarity.accepts(text_similarity(REFERENCED, rewritten)));
+		assert!(text_similarity(REFERENCED, rewritten) < 0.5);
+	}
+
+	#[test]
+	fn test_min_similarity_from_str() {
+		assert_eq!(MinSimilarity::from_str(" 0.9 ").unwrap().bound(), 0.9);
+		assert_eq!(MinSimilarity::from_str("1").unwrap().to_string(), "1");
+		for invalid in ["1.5", "-0.1", "high", ""] {
+			assert!(MinSimilarity::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+		assert!(MinSimilarity::new(f64::NAN).is_err());
+	}
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(DiffSensitivity::from_str("removals").unwrap(), DiffSensitivity::Removals);
+		assert_eq!(DiffSensitivity::from_str("ADDITIONS").unwrap(), DiffSensitivity::Additions);
+		assert_eq!(DiffSensitivity::from_str("Both").unwrap(), DiffSensitivity::Both);
+		assert!(DiffSensitivity::from_str("invalid").is_err());
+	}
+
+	#[test]
+	fn test_appended_content() {
+		let referenced = "guarantee one\n";
+		let current = "guarantee one\nguarantee two\n";
+
+		assert!(!DiffSensitivity::Removals.is_text_drift(referenced, current));
+		assert!(DiffSensitivity::Additions.is_text_drift(referenced, current));
+		assert!(DiffSensitivity::Both.is_text_drift(referenced, current));
+	}
+
+	#[test]
+	fn test_removed_content() {
+		let referenced = "guarantee one\nguarantee two\n";
+		let current = "guarantee one\n";
+
+		assert!(DiffSensitivity::Removals.is_text_drift(referenced, current));
+		assert!(!DiffSensitivity::Additions.is_text_drift(referenced, current));
+		assert!(DiffSensitivity::Both.is_text_drift(referenced, current));
+	}
+
+	#[test]
+	fn test_unchanged_content() {
+		assert!(!DiffSensitivity::Both.is_text_drift("same\n", "same\n"));
+	}
+
+	#[test]
+	fn test_diff_stats() {
+		let stats = DiffStats::from_texts("one\ntwo\n", "one\nthree\nfour\n");
+		assert_eq!(stats, DiffStats { additions: 2, removals: 1 });
+		assert_eq!(stats.to_string(), "+2 -1");
+
+		let unified = "--- referenced\n+++ current\n@@ -1,2 +1,3 @@\n one\n-two\n+three\n+four\n";
+		assert_eq!(DiffStats::from_unified_diff(unified), stats);
+
+		// A removed `-- comment` line is a change, not a header
+		let unified = "diff --git a/schema.sql b/schema.sql\n--- a/schema.sql\n+++ b/schema.sql\n@@ -1,2 +1,1 @@\n select 1;\n--- deprecated\n";
+		assert_eq!(DiffStats::from_unified_diff(unified), DiffStats { additions: 0, removals: 1 });
+		assert!(DiffStats::from_texts("same\n", "same\n").is_empty());
+	}
+}
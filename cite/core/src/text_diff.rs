@@ -0,0 +1,345 @@
+use crate::moves::{label_moves, LineChange};
+use crate::sensitivity::{file_header_flags, DiffStats};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+/// Unit of text a diff is computed and rendered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffGranularity {
+	/// Whole lines, rendered as a unified diff with moved blocks labelled
+	#[default]
+	Lines,
+	/// Words, rendered inline as `[-removed-]{+added+}`
+	Words,
+	/// Characters, rendered inline like words
+	Chars,
+}
+
+impl DiffGranularity {
+	/// Parse from `lines`, `words` or `chars`, or their singular, e.g. `word`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.trim().to_lowercase().as_str() {
+			"lines" | "line" => Ok(DiffGranularity::Lines),
+			"words" | "word" => Ok(DiffGranularity::Words),
+			"chars" | "char" => Ok(DiffGranularity::Chars),
+			_ => {
+				Err(format!("Invalid diff granularity: '{}'. Valid values: lines, words, chars", s))
+			}
+		}
+	}
+
+	/// Check if this is the default granularity (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == DiffGranularity::default()
+	}
+
+	fn diff<'a>(&self, referenced: &'a str, current: &'a str) -> TextDiff<'a, 'a, 'a, str> {
+		match self {
+			DiffGranularity::Lines => TextDiff::from_lines(referenced, current),
+			DiffGranularity::Words => TextDiff::from_words(referenced, current),
+			DiffGranularity::Chars => TextDiff::from_chars(referenced, current),
+		}
+	}
+}
+
+/// Diff two texts by line, labelling the blocks that moved without changing
+pub fn label_lines<'a>(referenced: &'a str, current: &'a str) -> Vec<(LineChange, &'a str)> {
+	let diff = TextDiff::from_lines(referenced, current);
+	let changes = diff
+		.iter_all_changes()
+		.map(|change| (change.tag(), change.value()))
+		.collect::<Vec<_>>();
+	label_moves(&changes)
+		.into_iter()
+		.zip(changes.into_iter().map(|(_, line)| line))
+		.collect()
+}
+
+/// Render the diff between two texts the way citation messages show drift
+///
+/// Line diffs prefix each line with its [LineChange::sign], so moved blocks show as `<` and `>`
+/// rather than as removals and additions. Word and character diffs mark changes inline. Texts
+/// without changes have no diff.
+pub fn render_text_diff(
+	referenced: &str,
+	current: &str,
+	granularity: DiffGranularity,
+) -> Option<String> {
+	if referenced == current {
+		return None;
+	}
+
+	let rendered = match granularity {
+		// Every line ends with a line break, including a last line that lacks one
+		DiffGranularity::Lines => label_lines(referenced, current)
+			.into_iter()
+			.map(|(label, line)| format!("{}{}\n", label.sign(), line.trim_end_matches('\n')))
+			.collect::<String>(),
+		DiffGranularity::Words | DiffGranularity::Chars => {
+			let diff = granularity.diff(referenced, current);
+			let mut rendered = String::new();
+			let mut open = ChangeTag::Equal;
+
+			for change in diff.iter_all_changes() {
+				if change.tag() != open {
+					rendered.push_str(close_marker(open));
+					rendered.push_str(open_marker(change.tag()));
+					open = change.tag();
+				}
+				rendered.push_str(change.value());
+			}
+			rendered.push_str(close_marker(open));
+			rendered
+		}
+	};
+
+	Some(rendered)
+}
+
+/// Render a unified diff with its changes marked inline, word by word or character by character
+///
+/// Each run of removed lines and the added lines following it are diffed in the unit of the
+/// granularity, so that a few words changing in a long line do not show as the whole line
+/// removed and added. Context lines lose their prefix, as with `git diff --word-diff`, while
+/// file and hunk headers and lines labelled as moved are kept. Line diffs are returned as is.
+pub fn render_unified_diff_inline(unified_diff: &str, granularity: DiffGranularity) -> String {
+	if granularity == DiffGranularity::Lines {
+		return unified_diff.to_string();
+	}
+
+	let mut rendered = String::new();
+	let (mut removed, mut added) = (String::new(), String::new());
+	let mut in_header = false;
+	for line in unified_diff.split_inclusive('\n') {
+		// The headers of a file run from its `diff --git` line to its first hunk
+		in_header = (in_header || line.starts_with("diff --git ")) && !line.starts_with("@@");
+		let removal = line.strip_prefix('-').filter(|_| !in_header);
+		let addition = line.strip_prefix('+').filter(|_| !in_header);
+		match (removal, addition) {
+			(Some(rest), _) if added.is_empty() => removed.push_str(rest),
+			(_, Some(rest)) => added.push_str(rest),
+			_ => {
+				flush_inline(&mut rendered, &mut removed, &mut added, granularity);
+				// A removal right after additions starts a new run
+				if let Some(rest) = removal {
+					removed.push_str(rest);
+				} else if let Some(rest) = line.strip_prefix(' ').filter(|_| !in_header) {
+					rendered.push_str(rest);
+				} else {
+					rendered.push_str(line);
+				}
+			}
+		}
+	}
+	flush_inline(&mut rendered, &mut removed, &mut added, granularity);
+	rendered
+}
+
+/// Render a run of removed and added lines inline, leaving both empty
+fn flush_inline(
+	rendered: &mut String,
+	removed: &mut String,
+	added: &mut String,
+	granularity: DiffGranularity,
+) {
+	if removed.is_empty() && added.is_empty() {
+		return;
+	}
+	// The line break ending the run is kept out of the markers
+	let removed_text = removed.strip_suffix('\n').unwrap_or(removed.as_str());
+	let added_text = added.strip_suffix('\n').unwrap_or(added.as_str());
+	rendered.push_str(&render_text_diff(removed_text, added_text, granularity).unwrap_or_default());
+	if removed_text.len() < removed.len() || added_text.len() < added.len() {
+		rendered.push('\n');
+	}
+	removed.clear();
+	added.clear();
+}
+
+/// Turn a unified diff into a patch of the file at the path, e.g. for `git apply`
+///
+/// Moved lines become removals and additions again. Diffs with `diff --git` headers of their own,
+/// such as those of git sources, keep them; others get headers for the path and, if they have no hunk
+/// header, a single hunk spanning the whole diff, as rendered by [render_text_diff].
+pub fn unified_diff_to_patch(unified_diff: &str, path: &str) -> String {
+	let lines = unified_diff.split_inclusive('\n').collect::<Vec<_>>();
+	// Without a `diff --git` line, a leading `--- ` line is a removal rather than a header
+	let has_headers = lines.iter().any(|line| line.starts_with("diff --git "));
+	let is_header = if has_headers {
+		file_header_flags(lines.iter().copied())
+	} else {
+		vec![false; lines.len()]
+	};
+	let has_hunks = lines.iter().any(|line| line.starts_with("@@"));
+
+	let mut body = String::with_capacity(unified_diff.len());
+	let (mut old, mut new) = (0, 0);
+	for (line, is_header) in lines.iter().zip(is_header) {
+		match line.chars().next() {
+			_ if is_header || line.starts_with("@@") => body.push_str(line),
+			Some('<') | Some('-') => {
+				old += 1;
+				body.push('-');
+				body.push_str(&line[1..]);
+			}
+			Some('>') | Some('+') => {
+				new += 1;
+				body.push('+');
+				body.push_str(&line[1..]);
+			}
+			Some(' ') => {
+				old += 1;
+				new += 1;
+				body.push_str(line);
+			}
+			_ => body.push_str(line),
+		}
+	}
+	if !body.ends_with('\n') {
+		body.push('\n');
+	}
+
+	let mut patch = String::new();
+	if !has_headers {
+		patch.push_str(&format!("--- a/{}\n+++ b/{}\n", path, path));
+	}
+	if !has_headers && !has_hunks {
+		// Sides without lines start before the first line
+		let start = |count: usize| usize::from(count > 0);
+		patch.push_str(&format!("@@ -{},{} +{},{} @@\n", start(old), old, start(new), new));
+	}
+	patch.push_str(&body);
+	patch
+}
+
+/// Count the units added and removed between two texts, in the unit of the granularity
+///
+/// With [DiffGranularity::Lines] this is [DiffStats::from_texts].
+pub fn text_diff_stats(referenced: &str, current: &str, granularity: DiffGranularity) -> DiffStats {
+	let mut stats = DiffStats::default();
+
+	for change in granularity.diff(referenced, current).iter_all_changes() {
+		// Whitespace between words is not a change in its own right
+		if granularity == DiffGranularity::Words && change.value().trim().is_empty() {
+			continue;
+		}
+		match change.tag() {
+			ChangeTag::Insert => stats.additions += 1,
+			ChangeTag::Delete => stats.removals += 1,
+			ChangeTag::Equal => {}
+		}
+	}
+
+	stats
+}
+
+fn open_marker(tag: ChangeTag) -> &'static str {
+	match tag {
+		ChangeTag::Equal => "",
+		ChangeTag::Delete => "[-",
+		ChangeTag::Insert => "{+",
+	}
+}
+
+fn close_marker(tag: ChangeTag) -> &'static str {
+	match tag {
+		ChangeTag::Equal => "",
+		ChangeTag::Delete => "-]",
+		ChangeTag::Insert => "+}",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_render_line_diff() {
+		let rendered = render_text_diff("one\ntwo\n", "one\nthree\n", DiffGranularity::Lines);
+		assert_eq!(rendered.as_deref(), Some(" one\n-two\n+three\n"));
+		assert_eq!(render_text_diff("same\n", "same\n", DiffGranularity::Lines), None);
+
+		// Lines without a trailing line break are still rendered one per line
+		let rendered = render_text_diff("title", "title, revised", DiffGranularity::Lines);
+		assert_eq!(rendered.as_deref(), Some("-title\n+title, revised\n"));
+
+		// Moved blocks are labelled as such
+		let rendered =
+			render_text_diff("a\nb\nc\n", "c\na\nb\n", DiffGranularity::Lines).unwrap_or_default();
+		assert!(rendered.contains('<') && rendered.contains('>'), "{}", rendered);
+	}
+
+	#[test]
+	fn test_render_inline_diff() {
+		let rendered =
+			render_text_diff("retries three times", "retries five times", DiffGranularity::Words);
+		assert_eq!(rendered.as_deref(), Some("retries [-three-]{+five+} times"));
+
+		let rendered = render_text_diff("v1.2", "v1.3", DiffGranularity::Chars);
+		assert_eq!(rendered.as_deref(), Some("v1.[-2-]{+3+}"));
+	}
+
+	#[test]
+	fn test_render_unified_diff_inline() {
+		let unified_diff = "diff --git a/README.md b/README.md\nindex 1a2b3c4..5d6e7f8 100644\n--- a/README.md\n+++ b/README.md\n@@ -1,2 +1,2 @@\n # Cite\n-Citations are validated at compile time, with retries on transient failures.\n+Citations are validated at build time, with retries on transient failures.\n";
+		let rendered = render_unified_diff_inline(unified_diff, DiffGranularity::Words);
+		assert_eq!(
+			rendered,
+			"diff --git a/README.md b/README.md\nindex 1a2b3c4..5d6e7f8 100644\n--- a/README.md\n+++ b/README.md\n@@ -1,2 +1,2 @@\n# Cite\nCitations are validated at [-compile-]{+build+} time, with retries on transient failures.\n"
+		);
+
+		// Runs are told apart by the lines between them, and lines only added or removed are
+		// marked whole
+		let rendered =
+			render_unified_diff_inline(" a\n-b c\n+b d\n e\n+f\n", DiffGranularity::Words);
+		assert_eq!(rendered, "a\nb [-c-]{+d+}\ne\n{+f+}\n");
+		assert_eq!(render_unified_diff_inline("-b\n+c\n", DiffGranularity::Lines), "-b\n+c\n");
+
+		// Lines starting with a multibyte character are kept as they are
+		let rendered = render_unified_diff_inline("é\n-b c\n+b d\n", DiffGranularity::Words);
+		assert_eq!(rendered, "é\nb [-c-]{+d+}\n");
+	}
+
+	#[test]
+	fn test_text_diff_stats() {
+		let stats = text_diff_stats("one\ntwo\n", "one\nthree\nfour\n", DiffGranularity::Lines);
+		assert_eq!(stats, DiffStats::from_texts("one\ntwo\n", "one\nthree\nfour\n"));
+
+		let stats = text_diff_stats("a b c", "a x c d", DiffGranularity::Words);
+		assert_eq!(stats, DiffStats { additions: 2, removals: 1 });
+	}
+
+	#[test]
+	fn test_unified_diff_to_patch() {
+		// Rendered diffs get headers and a hunk spanning them, with moves undone
+		let diff = render_text_diff("a\nb\nc\n", "b\nc\na\nd\n", DiffGranularity::Lines).unwrap();
+		assert_eq!(
+			unified_diff_to_patch(&diff, "docs"),
+			"--- a/docs\n+++ b/docs\n@@ -1,3 +1,4 @@\n-a\n b\n c\n+a\n+d\n"
+		);
+		assert_eq!(
+			unified_diff_to_patch("--- deprecated\n+new", "docs"),
+			"--- a/docs\n+++ b/docs\n@@ -1,1 +1,1 @@\n--- deprecated\n+new\n"
+		);
+		assert_eq!(
+			unified_diff_to_patch("+new\n", "docs"),
+			"--- a/docs\n+++ b/docs\n@@ -0,0 +1,1 @@\n+new\n"
+		);
+
+		// Diffs with headers of their own are kept as they are
+		let diff = "diff --git a/src/lib.rs b/src/lib.rs\nindex 1a2b3c4..5d6e7f8 100644\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n<a\n>a\n--- b\n+b\n";
+		assert_eq!(
+			unified_diff_to_patch(diff, "ignored"),
+			"diff --git a/src/lib.rs b/src/lib.rs\nindex 1a2b3c4..5d6e7f8 100644\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n-a\n+a\n--- b\n+b\n"
+		);
+	}
+
+	#[test]
+	fn test_granularity_from_str() {
+		assert_eq!(DiffGranularity::from_str("Words"), Ok(DiffGranularity::Words));
+		assert_eq!(DiffGranularity::from_str("word"), Ok(DiffGranularity::Words));
+		assert_eq!(DiffGranularity::default(), DiffGranularity::Lines);
+		assert!(DiffGranularity::from_str("paragraphs").is_err());
+	}
+}
@@ -0,0 +1,76 @@
+//! Instrumentation of the citation pipeline, enabled by the `tracing` feature
+//!
+//! Sources and the macro trace fetching, extraction, caching and comparison through the
+//! macros of this module. With the feature enabled they emit [tracing] spans and events at the
+//! debug level to whichever subscriber is installed; without it they expand to nothing, so the
+//! instrumentation costs nothing.
+
+#[cfg(feature = "tracing")]
+pub use tracing;
+
+/// Stand-in for an entered span when the `tracing` feature is disabled
+#[cfg(not(feature = "tracing"))]
+#[derive(Debug, Clone, Copy)]
+pub struct DisabledSpan;
+
+/// Enter a debug span, e.g. `let _span = trace_span!("citation", id = %id);`, which lasts
+/// until the returned guard is dropped
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_span {
+	($($span:tt)+) => {
+		$crate::trace::tracing::debug_span!($($span)+).entered()
+	};
+}
+
+/// Enter a debug span, e.g. `let _span = trace_span!("citation", id = %id);`, which lasts
+/// until the returned guard is dropped
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_span {
+	($($span:tt)+) => {
+		$crate::trace::DisabledSpan
+	};
+}
+
+/// Emit a debug event, e.g. `trace_event!(hit = true, "cache lookup");`
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_event {
+	($($event:tt)+) => {
+		$crate::trace::tracing::debug!($($event)+)
+	};
+}
+
+/// Emit a debug event, e.g. `trace_event!(hit = true, "cache lookup");`
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_event {
+	($($event:tt)+) => {};
+}
+
+/// Evaluate an expression, emitting a debug event with how long it took in `elapsed_ms`, e.g.
+/// `trace_timed!(fetch(url), url, "fetched")`
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_timed {
+	($body:expr, $($event:tt)+) => {{
+		let started = ::std::time::Instant::now();
+		let result = $body;
+		$crate::trace::tracing::debug!(
+			elapsed_ms = started.elapsed().as_millis() as u64,
+			$($event)+
+		);
+		result
+	}};
+}
+
+/// Evaluate an expression, emitting a debug event with how long it took in `elapsed_ms`, e.g.
+/// `trace_timed!(fetch(url), url, "fetched")`
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_timed {
+	($body:expr, $($event:tt)+) => {
+		$body
+	};
+}
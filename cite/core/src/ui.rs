@@ -1,4 +1,4 @@
-use crate::{Current, Diff, Referenced, Source};
+use crate::{Current, Diff, MinSimilarity, Referenced, Source};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
@@ -71,3 +71,34 @@ where
 	/// This is used for validation and error reporting in procedural macros
 	fn is_valid_attr_key(attr_key: &str) -> bool;
 }
+
+/// Parse an `any_of` kwarg: a non-empty tuple of accepted string values
+pub fn parse_any_of(value: &Value) -> Result<Vec<String>, SourceUiError> {
+	let invalid = || {
+		SourceUiError::InvalidParameter(
+			"any_of parameter must be a non-empty tuple of strings".to_string(),
+		)
+	};
+
+	let values = value
+		.as_array()
+		.ok_or_else(invalid)?
+		.iter()
+		.map(|v| v.as_str().map(|s| s.to_string()).ok_or_else(invalid))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	if values.is_empty() {
+		return Err(invalid());
+	}
+
+	Ok(values)
+}
+
+/// Parse a `min_similarity` kwarg: a number between 0.0 and 1.0, given as a string or a number
+pub fn parse_min_similarity(value: &Value) -> Result<MinSimilarity, SourceUiError> {
+	match value {
+		Value::String(s) => MinSimilarity::from_str(s),
+		value => MinSimilarity::from_str(&value.to_string()),
+	}
+	.map_err(SourceUiError::InvalidParameter)
+}
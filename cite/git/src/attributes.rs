@@ -0,0 +1,195 @@
+use git2::{Repository, Tree};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Filter applied to blob content before diffing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFilter {
+	/// Compare blob content as committed, only normalizing its character encoding (fastest)
+	#[default]
+	Raw,
+	/// Apply the text normalization configured in the current revision's `.gitattributes`,
+	/// matching what a user sees in the working tree
+	GitAttributes,
+}
+
+impl ContentFilter {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"raw" => Ok(ContentFilter::Raw),
+			"gitattributes" => Ok(ContentFilter::GitAttributes),
+			_ => Err(format!("Invalid content filter: '{}'. Valid values: raw, gitattributes", s)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ContentFilter::Raw => "raw",
+			ContentFilter::GitAttributes => "gitattributes",
+		}
+	}
+
+	/// Check if this is the default filter (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == ContentFilter::default()
+	}
+}
+
+impl std::fmt::Display for ContentFilter {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+/// State of the `text` attribute for a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAttribute {
+	/// `text` or `eol=...`: always normalize line endings
+	Set,
+	/// `-text` or `binary`: never normalize
+	Unset,
+	/// `text=auto`: normalize unless the content looks binary
+	Auto,
+}
+
+/// The text normalization rules of a `.gitattributes` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitAttributes {
+	rules: Vec<(String, TextAttribute)>,
+}
+
+impl GitAttributes {
+	/// Parse the contents of a `.gitattributes` file
+	///
+	/// Only the attributes that affect text normalization (`text`, `eol`, `binary`) are kept.
+	pub fn parse(content: &str) -> Self {
+		let mut rules = Vec::new();
+
+		for line in content.lines() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let mut parts = line.split_whitespace();
+			let pattern = match parts.next() {
+				Some(pattern) => pattern,
+				None => continue,
+			};
+
+			for attribute in parts {
+				let text = match attribute {
+					"text=auto" => Some(TextAttribute::Auto),
+					"-text" | "binary" => Some(TextAttribute::Unset),
+					_ if attribute == "text" || attribute.starts_with("eol=") => {
+						Some(TextAttribute::Set)
+					}
+					_ => None,
+				};
+
+				if let Some(text) = text {
+					rules.push((pattern.to_string(), text));
+				}
+			}
+		}
+
+		Self { rules }
+	}
+
+	/// Read the `.gitattributes` file at the root of a tree, if any
+	pub fn from_tree(repo: &Repository, tree: &Tree) -> Result<Self, git2::Error> {
+		let entry = match tree.get_path(Path::new(".gitattributes")) {
+			Ok(entry) => entry,
+			Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(Self::default()),
+			Err(e) => return Err(e),
+		};
+		let blob = repo.find_blob(entry.id())?;
+
+		Ok(Self::parse(&String::from_utf8_lossy(blob.content())))
+	}
+
+	/// Get the `text` attribute for a path; the last matching rule wins
+	pub fn text_attribute(&self, path: &Path) -> Option<TextAttribute> {
+		self.rules
+			.iter()
+			.rev()
+			.find(|(pattern, _)| Self::pattern_matches(pattern, path))
+			.map(|(_, text)| *text)
+	}
+
+	/// Normalize blob content the way git's text clean filter would
+	pub fn normalize(&self, path: &Path, content: &[u8]) -> Vec<u8> {
+		let normalize = match self.text_attribute(path) {
+			Some(TextAttribute::Set) => true,
+			Some(TextAttribute::Auto) => !content.contains(&0),
+			Some(TextAttribute::Unset) | None => false,
+		};
+
+		if !normalize {
+			return content.to_vec();
+		}
+
+		let mut normalized = Vec::with_capacity(content.len());
+		let mut bytes = content.iter().peekable();
+		while let Some(&byte) = bytes.next() {
+			if byte == b'\r' && bytes.peek() == Some(&&b'\n') {
+				continue;
+			}
+			normalized.push(byte);
+		}
+		normalized
+	}
+
+	/// Match a `.gitattributes` pattern: patterns without a slash match the file name at any
+	/// depth, others match the path relative to the repository root
+	fn pattern_matches(pattern: &str, path: &Path) -> bool {
+		let glob = match glob::Pattern::new(pattern.trim_start_matches('/')) {
+			Ok(glob) => glob,
+			Err(_) => return false,
+		};
+
+		if pattern.contains('/') {
+			glob.matches_path(path)
+		} else {
+			path.file_name().is_some_and(|name| glob.matches(&name.to_string_lossy()))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_content_filter_from_str() {
+		assert_eq!(ContentFilter::from_str("raw").unwrap(), ContentFilter::Raw);
+		assert_eq!(ContentFilter::from_str("GitAttributes").unwrap(), ContentFilter::GitAttributes);
+		assert!(ContentFilter::from_str("smudge").is_err());
+	}
+
+	#[test]
+	fn test_parse_and_match() {
+		let attributes = GitAttributes::parse(
+			"# normalize text\n* text=auto\n*.txt text\n*.png binary\ndocs/*.md eol=crlf\n",
+		);
+
+		assert_eq!(attributes.text_attribute(Path::new("a/b.txt")), Some(TextAttribute::Set));
+		assert_eq!(attributes.text_attribute(Path::new("logo.png")), Some(TextAttribute::Unset));
+		assert_eq!(attributes.text_attribute(Path::new("src/lib.rs")), Some(TextAttribute::Auto));
+		assert_eq!(attributes.text_attribute(Path::new("docs/a.md")), Some(TextAttribute::Set));
+		assert_eq!(GitAttributes::default().text_attribute(Path::new("a.txt")), None);
+	}
+
+	#[test]
+	fn test_normalize() {
+		let attributes = GitAttributes::parse("* text=auto\n*.txt text\n*.bin -text\n");
+
+		assert_eq!(attributes.normalize(Path::new("a.txt"), b"one\r\ntwo\r\n"), b"one\ntwo\n");
+		assert_eq!(attributes.normalize(Path::new("a.bin"), b"one\r\n"), b"one\r\n");
+		assert_eq!(attributes.normalize(Path::new("a.rs"), b"one\r\n"), b"one\n");
+		assert_eq!(attributes.normalize(Path::new("a.rs"), b"\0one\r\n"), b"\0one\r\n");
+	}
+}
@@ -0,0 +1,164 @@
+//! Classification of the changes of cited files
+//!
+//! A changed file is whitespace-only when its removed and added lines are the same once their
+//! trailing whitespace, line endings and blank lines are dropped, so that such noise is told
+//! apart from editing lines. Indentation counts as a change, as it is significant in some
+//! languages. A file is comment-only when that holds for its lines other than comments, in the
+//! comment syntax of the file's extension. Files of unknown extensions have no comments.
+
+use cite_core::change::significant_lines;
+use cite_core::{label_moves, ChangeClass, LineChange};
+use similar::ChangeTag;
+use std::path::Path;
+
+/// Prefixes of comment lines in C-like languages, including the lines of block comments
+const SLASH_COMMENTS: [&str; 4] = ["//", "/*", "*/", "* "];
+
+/// Prefixes of comment lines in shell-like languages and configuration files
+const HASH_COMMENTS: [&str; 1] = ["#"];
+
+/// Prefixes of comment lines in SQL-like languages
+const DASH_COMMENTS: [&str; 1] = ["--"];
+
+/// Prefixes of comment lines in markup
+const MARKUP_COMMENTS: [&str; 2] = ["<!--", "-->"];
+
+/// Get the prefixes of comment lines in a file, by its extension
+pub fn comment_prefixes(path: &Path) -> &'static [&'static str] {
+	let extension = match path.extension().and_then(|extension| extension.to_str()) {
+		Some(extension) => extension.to_lowercase(),
+		None => return &[],
+	};
+	match extension.as_str() {
+		"rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "go" | "java" | "js" | "jsx" | "ts"
+		| "tsx" | "kt" | "swift" | "scala" | "dart" | "proto" | "zig" | "sol" => &SLASH_COMMENTS,
+		"py" | "sh" | "bash" | "zsh" | "rb" | "pl" | "toml" | "yaml" | "yml" | "nix" | "r" => {
+			&HASH_COMMENTS
+		}
+		"sql" | "lua" | "hs" | "elm" => &DASH_COMMENTS,
+		"html" | "htm" | "xml" | "svg" | "md" => &MARKUP_COMMENTS,
+		_ => &[],
+	}
+}
+
+/// Check if a line is a comment, given the comment prefixes of its file
+fn is_comment(line: &str, prefixes: &[&str]) -> bool {
+	let line = line.trim();
+	prefixes
+		.iter()
+		.any(|prefix| line.starts_with(prefix) || line == prefix.trim_end())
+}
+
+/// Classify the changes of a file, given as the lines of its diff
+///
+/// Blocks that moved count as changed, since a line diff does not show them moving otherwise.
+/// Returns `None` if no line changed.
+pub fn classify(path: &Path, changes: &[(ChangeTag, &str)]) -> Option<ChangeClass> {
+	let prefixes = comment_prefixes(path);
+	let (mut removed, mut added) = (Vec::new(), Vec::new());
+	let (mut moved_code, mut moved_comment) = (false, false);
+	let mut changed = false;
+
+	for (label, (_, line)) in label_moves(changes).iter().zip(changes) {
+		let comment = is_comment(line, prefixes);
+		match label {
+			LineChange::Equal => continue,
+			LineChange::MovedFrom | LineChange::MovedTo => {
+				moved_code |= !comment;
+				moved_comment |= comment;
+			}
+			LineChange::Removed => removed.push((*line, comment)),
+			LineChange::Added => added.push((*line, comment)),
+		}
+		changed = true;
+	}
+
+	if !changed {
+		None
+	} else if moved_code || significant(&removed, true) != significant(&added, true) {
+		Some(ChangeClass::Code)
+	} else if moved_comment || significant(&removed, false) != significant(&added, false) {
+		Some(ChangeClass::Comment)
+	} else {
+		Some(ChangeClass::Whitespace)
+	}
+}
+
+/// Get the significant lines of the removed or added lines, flagged as comments, leaving out
+/// the comments for the code only
+fn significant<'a>(lines: &[(&'a str, bool)], code_only: bool) -> Vec<&'a str> {
+	significant_lines(
+		lines
+			.iter()
+			.filter(|(_, comment)| !(code_only && *comment))
+			.map(|(line, _)| *line),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lines<'a>(changes: &[(char, &'a str)]) -> Vec<(ChangeTag, &'a str)> {
+		changes
+			.iter()
+			.map(|&(origin, line)| match origin {
+				'-' => (ChangeTag::Delete, line),
+				'+' => (ChangeTag::Insert, line),
+				_ => (ChangeTag::Equal, line),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_classify_whitespace() {
+		let path = Path::new("src/lib.rs");
+
+		// Trailing whitespace and line endings
+		let trailing = lines(&[
+			('-', "fn parse(input: &str) {\n"),
+			('-', "}\n"),
+			('+', "fn parse(input: &str) {  \r\n"),
+			('+', "}\r\n"),
+		]);
+		assert_eq!(classify(path, &trailing), Some(ChangeClass::Whitespace));
+
+		// Indentation and whitespace within lines are significant
+		let reindented = lines(&[(' ', "if ready:\n"), ('-', "    run()\n"), ('+', "run()\n")]);
+		assert_eq!(classify(Path::new("run.py"), &reindented), Some(ChangeClass::Code));
+		let literal = lines(&[('-', "let s = \"a b\";\n"), ('+', "let s = \"ab\";\n")]);
+		assert_eq!(classify(path, &literal), Some(ChangeClass::Code));
+
+		let blank = lines(&[(' ', "fn a() {}\n"), ('+', "\n"), (' ', "fn b() {}\n")]);
+		assert_eq!(classify(path, &blank), Some(ChangeClass::Whitespace));
+		assert_eq!(classify(path, &lines(&[(' ', "fn a() {}\n")])), None);
+	}
+
+	#[test]
+	fn test_classify_comments() {
+		let path = Path::new("src/lib.rs");
+		let comment = lines(&[
+			('-', "// Parses the input\n"),
+			('+', "/// Parse the input, failing on unknown keys\n"),
+			(' ', "fn parse() {}\n"),
+		]);
+		assert_eq!(classify(path, &comment), Some(ChangeClass::Comment));
+
+		// `#` is not a comment in Rust, but is in Python
+		let attribute = lines(&[('+', "#[inline]\n"), (' ', "fn parse() {}\n")]);
+		assert_eq!(classify(path, &attribute), Some(ChangeClass::Code));
+		let python = lines(&[('+', "# Parse the input\n"), (' ', "def parse(): pass\n")]);
+		assert_eq!(classify(Path::new("parse.py"), &python), Some(ChangeClass::Comment));
+	}
+
+	#[test]
+	fn test_classify_code() {
+		let path = Path::new("src/lib.rs");
+		let code = lines(&[('-', "const RETRIES: u32 = 3;\n"), ('+', "const RETRIES: u32 = 5;\n")]);
+		assert_eq!(classify(path, &code), Some(ChangeClass::Code));
+
+		// Reordering code is a change, though no line changed
+		let moved = lines(&[('-', "first();\n"), (' ', "second();\n"), ('+', "first();\n")]);
+		assert_eq!(classify(path, &moved), Some(ChangeClass::Code));
+	}
+}
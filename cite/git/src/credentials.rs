@@ -0,0 +1,179 @@
+use git2::{Cred, CredentialType, ErrorClass, ErrorCode, RemoteCallbacks};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Username sent with a token when the remote URL names none, which GitHub and GitLab accept
+pub const DEFAULT_TOKEN_USERNAME: &str = "x-access-token";
+
+/// Number of times a remote may ask for credentials before they count as rejected
+///
+/// libgit2 asks again for as long as the remote rejects what it was given, so without a limit a
+/// wrong token would be offered forever.
+const MAX_CREDENTIAL_ATTEMPTS: usize = 3;
+
+/// Credentials for cloning and fetching private repositories
+///
+/// Secrets are never part of a citation: a token is read from its environment variable when the
+/// remote asks for it, and the passphrase of an SSH key is neither serialized nor shown by
+/// `Debug`, so that neither ends up in logs or in the cache.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitCredentials {
+	/// The default credentials of libgit2, e.g. for public repositories
+	#[default]
+	Default,
+	/// An SSH private key, for `git@` and `ssh://` remotes
+	SshKey {
+		/// The path of the private key
+		path: PathBuf,
+		/// The passphrase of the private key, if it is encrypted
+		#[serde(skip)]
+		passphrase: Option<String>,
+	},
+	/// A token read from the given environment variable, for HTTPS remotes
+	TokenEnv(String),
+}
+
+impl GitCredentials {
+	/// Authenticate with an SSH private key, decrypted with the passphrase if given
+	pub fn ssh_key(path: impl Into<PathBuf>, passphrase: Option<&str>) -> Self {
+		GitCredentials::SshKey { path: path.into(), passphrase: passphrase.map(str::to_string) }
+	}
+
+	/// Authenticate with a token read from the given environment variable, e.g. `GIT_TOKEN`
+	pub fn token_env(env: &str) -> Self {
+		GitCredentials::TokenEnv(env.to_string())
+	}
+
+	/// Check if these are the default credentials
+	pub fn is_default(&self) -> bool {
+		*self == GitCredentials::Default
+	}
+
+	/// Get the credential to answer the remote with, given the username in its URL and the
+	/// kinds of credentials it allows
+	///
+	/// Remotes not allowing the configured kind are answered with the default credentials.
+	pub fn credential(
+		&self,
+		username_from_url: Option<&str>,
+		allowed_types: CredentialType,
+	) -> Result<Cred, git2::Error> {
+		match self {
+			// SSH remotes ask for the username before the key
+			GitCredentials::SshKey { .. } if allowed_types.contains(CredentialType::USERNAME) => {
+				Cred::username(username_from_url.unwrap_or("git"))
+			}
+			GitCredentials::SshKey { path, passphrase }
+				if allowed_types.contains(CredentialType::SSH_KEY) =>
+			{
+				Cred::ssh_key(username_from_url.unwrap_or("git"), None, path, passphrase.as_deref())
+			}
+			GitCredentials::TokenEnv(env)
+				if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) =>
+			{
+				let token = std::env::var(env).map_err(|_| {
+					git2::Error::new(
+						ErrorCode::Auth,
+						ErrorClass::Http,
+						format!("{} is not set, so the token to authenticate with is missing", env),
+					)
+				})?;
+				Cred::userpass_plaintext(
+					username_from_url.unwrap_or(DEFAULT_TOKEN_USERNAME),
+					&token,
+				)
+			}
+			_ => Cred::default(),
+		}
+	}
+
+	/// Get the remote callbacks answering credential requests with these credentials
+	pub fn callbacks(&self) -> RemoteCallbacks<'_> {
+		let mut attempts = 0;
+		let mut callbacks = RemoteCallbacks::new();
+		callbacks.credentials(move |_url, username_from_url, allowed_types| {
+			attempts += 1;
+			if attempts > MAX_CREDENTIAL_ATTEMPTS {
+				return Err(git2::Error::new(
+					ErrorCode::Auth,
+					ErrorClass::Net,
+					"the remote rejected the credentials",
+				));
+			}
+			self.credential(username_from_url, allowed_types)
+		});
+		callbacks
+	}
+}
+
+impl std::fmt::Debug for GitCredentials {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GitCredentials::Default => write!(f, "Default"),
+			GitCredentials::SshKey { path, passphrase } => f
+				.debug_struct("SshKey")
+				.field("path", path)
+				.field("passphrase", &passphrase.as_ref().map(|_| "<redacted>"))
+				.finish(),
+			GitCredentials::TokenEnv(env) => f.debug_tuple("TokenEnv").field(env).finish(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_token_from_env() -> Result<(), anyhow::Error> {
+		let credentials = GitCredentials::token_env("CITE_TEST_GIT_TOKEN");
+
+		std::env::remove_var("CITE_TEST_GIT_TOKEN");
+		let error = credentials
+			.credential(None, CredentialType::USER_PASS_PLAINTEXT)
+			.err()
+			.map(|e| (e.code(), e.message().to_string()));
+		assert_eq!(
+			error,
+			Some((
+				ErrorCode::Auth,
+				"CITE_TEST_GIT_TOKEN is not set, so the token to authenticate with is missing"
+					.to_string()
+			))
+		);
+
+		std::env::set_var("CITE_TEST_GIT_TOKEN", "ghp_secret");
+		let credential = credentials.credential(None, CredentialType::USER_PASS_PLAINTEXT)?;
+		assert!(credential.has_username());
+
+		// Only the name of the variable is kept, never the token
+		let json = serde_json::to_string(&credentials)?;
+		assert_eq!(json, r#"{"token_env":"CITE_TEST_GIT_TOKEN"}"#);
+		assert!(!format!("{:?}", credentials).contains("ghp_secret"));
+		std::env::remove_var("CITE_TEST_GIT_TOKEN");
+		Ok(())
+	}
+
+	#[test]
+	fn test_ssh_key_passphrase_is_not_kept() -> Result<(), serde_json::Error> {
+		let credentials = GitCredentials::ssh_key("/home/ci/.ssh/id_ed25519", Some("hunter2"));
+		assert!(!format!("{:?}", credentials).contains("hunter2"));
+
+		let json = serde_json::to_string(&credentials)?;
+		assert!(!json.contains("hunter2"));
+		assert_eq!(
+			serde_json::from_str::<GitCredentials>(&json)?,
+			GitCredentials::ssh_key("/home/ci/.ssh/id_ed25519", None)
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_ssh_username_is_sent_first() {
+		let credentials = GitCredentials::ssh_key("/home/ci/.ssh/id_ed25519", None);
+		let credential = credentials.credential(Some("git"), CredentialType::USERNAME);
+		assert!(credential
+			.is_ok_and(|credential| credential.credtype() == CredentialType::USERNAME.bits()));
+	}
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+/// What the current revision is diffed from
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DiffBase {
+	/// Diff from the referenced revision
+	#[default]
+	Referenced,
+	/// Diff from the merge-base of the given revision and the current revision, showing only
+	/// what changed on the current branch since it diverged, as code review tools do
+	MergeBase(String),
+}
+
+impl DiffBase {
+	/// Parse from `referenced` or `merge-base:<revision>`, e.g. `merge-base:main`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		let s = s.trim();
+		match s.split_once(':') {
+			Some(("merge-base", revision)) if !revision.trim().is_empty() => {
+				Ok(DiffBase::MergeBase(revision.trim().to_string()))
+			}
+			None if s == "referenced" => Ok(DiffBase::Referenced),
+			_ => Err(format!(
+				"Invalid diff base: '{}'. Valid values: referenced, merge-base:<revision>",
+				s
+			)),
+		}
+	}
+
+	/// Check if this is the default base (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == DiffBase::default()
+	}
+}
+
+impl std::fmt::Display for DiffBase {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			DiffBase::Referenced => write!(f, "referenced"),
+			DiffBase::MergeBase(revision) => write!(f, "merge-base:{}", revision),
+		}
+	}
+}
+
+impl TryFrom<String> for DiffBase {
+	type Error = String;
+
+	fn try_from(s: String) -> Result<Self, Self::Error> {
+		Self::from_str(&s)
+	}
+}
+
+impl From<DiffBase> for String {
+	fn from(base: DiffBase) -> Self {
+		base.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(DiffBase::from_str("referenced"), Ok(DiffBase::Referenced));
+		assert_eq!(
+			DiffBase::from_str("merge-base:main"),
+			Ok(DiffBase::MergeBase("main".to_string()))
+		);
+		for invalid in ["", "merge-base", "merge-base:", "fork-point:main"] {
+			assert!(DiffBase::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+
+		// The string form round-trips through serde
+		let base = DiffBase::MergeBase("origin/main".to_string());
+		let json = serde_json::to_string(&base).unwrap();
+		assert_eq!(json, "\"merge-base:origin/main\"");
+		assert_eq!(serde_json::from_str::<DiffBase>(&json).unwrap(), base);
+	}
+}
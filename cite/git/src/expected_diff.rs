@@ -0,0 +1,166 @@
+use cite_core::file_header_flags;
+use serde::{Deserialize, Serialize};
+
+/// Normalize a unified diff into its changed lines, for comparing diffs stated by hand against
+/// computed ones
+///
+/// File and hunk headers and context lines are dropped, as they depend on how the diff was
+/// produced rather than on the change. Whitespace within each line is collapsed, and moved
+/// lines (`<` and `>`) count as removed and added.
+pub fn normalize_diff(diff: &str) -> Vec<String> {
+	diff.lines()
+		.zip(file_header_flags(diff.lines()))
+		.filter(|(_, is_header)| !is_header)
+		.filter_map(|(line, _)| {
+			let sign = match line.chars().next()? {
+				'+' | '>' => '+',
+				'-' | '<' => '-',
+				_ => return None,
+			};
+			let content = line[1..].split_whitespace().collect::<Vec<_>>().join(" ");
+			Some(format!("{}{}", sign, content))
+		})
+		.collect()
+}
+
+/// Result of comparing the diff between two revisions against the diff the citation expects
+///
+/// Intentional transformations, e.g. of generated code, are locked in by citing the change
+/// itself: the citation drifts if the file changed differently, or not at all.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedDiffCheck {
+	/// Expected changed lines missing from the actual diff
+	pub missing: Vec<String>,
+	/// Changed lines of the actual diff that are not expected
+	pub unexpected: Vec<String>,
+	/// Whether the file changed at all
+	pub changed: bool,
+}
+
+impl ExpectedDiffCheck {
+	/// Compare the actual diff against the expected one, after normalizing both
+	pub fn new(expected: &str, actual: &str) -> Self {
+		let expected = normalize_diff(expected);
+		let actual = normalize_diff(actual);
+		let changed = !actual.is_empty();
+
+		// Compare as multisets, so that the order lines are printed in does not matter
+		let mut unexpected = actual;
+		let mut missing = Vec::new();
+		for line in expected {
+			match unexpected.iter().position(|actual| *actual == line) {
+				Some(index) => {
+					unexpected.remove(index);
+				}
+				None => missing.push(line),
+			}
+		}
+		Self { missing, unexpected, changed }
+	}
+
+	/// Check if the file changed exactly as expected
+	pub fn is_match(&self) -> bool {
+		self.missing.is_empty() && self.unexpected.is_empty()
+	}
+
+	/// Describe how the file failed to change as expected, or `None` if it did
+	pub fn describe_failure(&self) -> Option<&'static str> {
+		match (self.is_match(), self.changed) {
+			(true, _) => None,
+			(false, false) => Some("Git citation content has not changed by the expected diff!"),
+			(false, true) => Some("Git citation content has changed differently than expected!"),
+		}
+	}
+
+	/// Render the expected changed lines that are missing and the changed lines that are not
+	/// expected, each under a heading
+	pub fn render(&self) -> String {
+		let mut rendered = String::new();
+		for (heading, lines) in [
+			("Missing expected changes:", &self.missing),
+			("Unexpected changes:", &self.unexpected),
+		] {
+			if lines.is_empty() {
+				continue;
+			}
+			rendered.push_str(heading);
+			rendered.push('\n');
+			for line in lines {
+				rendered.push_str(line);
+				rendered.push('\n');
+			}
+		}
+		rendered
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EXPECTED: &str = "\
+--- a/generated.rs
++++ b/generated.rs
+@@ -1,3 +1,3 @@
+ pub mod api;
+-pub const VERSION: u32 = 1;
++pub const VERSION: u32 = 2;
+";
+
+	#[test]
+	fn test_normalize_diff() {
+		assert_eq!(
+			normalize_diff(EXPECTED),
+			["-pub const VERSION: u32 = 1;", "+pub const VERSION: u32 = 2;"]
+		);
+
+		// Whitespace and context do not matter, and moves are changes
+		assert_eq!(
+			normalize_diff("@@ -4 +4 @@\n fn a()\n-\tlet  x = 1;  \n>moved\n"),
+			["-let x = 1;", "+moved"]
+		);
+
+		// A removed `-- comment` line is a change, not a file header
+		assert_eq!(
+			normalize_diff(
+				"--- a/schema.sql\n+++ b/schema.sql\n@@ -1,2 +1 @@\n select 1;\n--- legacy\n"
+			),
+			["--- legacy"]
+		);
+	}
+
+	#[test]
+	fn test_expected_diff_check() {
+		let actual = "-pub const VERSION: u32 = 1;\n+pub const VERSION:  u32 = 2;\n";
+		assert!(ExpectedDiffCheck::new(EXPECTED, actual).is_match());
+
+		let differs = ExpectedDiffCheck::new(
+			EXPECTED,
+			"-pub const VERSION: u32 = 1;\n+pub const VERSION: u32 = 3;\n",
+		);
+		assert!(!differs.is_match());
+		assert_eq!(differs.missing, ["+pub const VERSION: u32 = 2;"]);
+		assert_eq!(differs.unexpected, ["+pub const VERSION: u32 = 3;"]);
+		assert_eq!(
+			differs.describe_failure(),
+			Some("Git citation content has changed differently than expected!")
+		);
+		assert_eq!(
+			differs.render(),
+			"Missing expected changes:\n+pub const VERSION: u32 = 2;\nUnexpected changes:\n+pub const VERSION: u32 = 3;\n"
+		);
+
+		let absent = ExpectedDiffCheck::new(EXPECTED, "");
+		assert!(!absent.is_match());
+		assert!(!absent.changed);
+		assert_eq!(
+			absent.describe_failure(),
+			Some("Git citation content has not changed by the expected diff!")
+		);
+		assert_eq!(
+			absent.render(),
+			"Missing expected changes:\n-pub const VERSION: u32 = 1;\n+pub const VERSION: u32 = 2;\n"
+		);
+		assert_eq!(ExpectedDiffCheck::new(EXPECTED, actual).describe_failure(), None);
+	}
+}
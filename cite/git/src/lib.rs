@@ -1,17 +1,36 @@
-pub mod ui;
+pub mod attributes;
+pub mod change;
+pub mod credentials;
+pub mod diff_base;
+pub mod expected_diff;
 pub mod line_range;
+pub mod region;
+pub mod rename;
 pub mod repository_manager;
+pub mod retry;
+pub mod ui;
+pub mod whitespace;
 
-use git2::{DiffFormat, DiffOptions};
+pub use attributes::{ContentFilter, GitAttributes};
+pub use credentials::GitCredentials;
+pub use diff_base::DiffBase;
+pub use expected_diff::ExpectedDiffCheck;
+use git2::DiffOptions;
 pub use line_range::LineRange;
+pub use rename::{CachedResolver, GithubApiResolver, RepoResolver};
 use repository_manager::{RepositoryBuilder, RepositoryManager};
-
-use cite_core::{Content, Current, Diff, Id, Referenced, Source, SourceError};
+pub use retry::RetryPolicy;
+pub use whitespace::WhitespaceMode;
+
+use cite_core::{
+	file_header_flags, label_moves, only_moves, ChangeClass, Comparison, Content, ContentType,
+	Current, Diff, DiffGranularity, DiffSensitivity, Id, LineChange, Referenced, Source,
+	SourceError, TextEncoding,
+};
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use std::path::Path;
 use thiserror::Error;
-use std::fmt::Display;
-
 
 /// Error types for git operations
 #[derive(Error, Debug)]
@@ -41,14 +60,16 @@ impl From<GitSourceError> for SourceError {
 	}
 }
 
-
-
 /// Path pattern for git source files
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PathPattern {
 	pub path: String,
 	pub line_range: Option<LineRange>,
 	pub glob: Option<String>,
+	/// Named region of the file whose content is cited, marked by `cite:begin` and `cite:end`
+	/// comments
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub region: Option<String>,
 }
 
 impl Display for PathPattern {
@@ -76,7 +97,20 @@ impl PathPattern {
 			None
 		};
 
-		Ok(Self { path: file_path, line_range, glob })
+		Ok(Self { path: file_path, line_range, glob, region: None })
+	}
+
+	/// Cite only the named region of the file, instead of a line range
+	pub fn with_region(mut self, region: &str) -> Result<Self, GitSourceError> {
+		region::validate_name(region)?;
+		if self.line_range.is_some() || self.glob.is_some() {
+			return Err(GitSourceError::InvalidPathPattern(format!(
+				"Region {} cannot be combined with a line range or glob: {}",
+				region, self.path
+			)));
+		}
+		self.region = Some(region.to_string());
+		Ok(self)
 	}
 
 	/// Check if this pattern matches a given path
@@ -114,34 +148,75 @@ pub struct GitSource {
 	pub path_pattern: PathPattern,
 	/// The revision being referenced (commit hash, branch, tag)
 	pub referenced_revision: String,
-	/// The current revision to compare against (commit hash, branch, tag)
-	pub current_revision: String,
+	/// The current revision to compare against (commit hash, branch, tag), or the default branch
+	/// of the remote if omitted
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub current_revision: Option<String>,
 	/// The optional name of the source
 	pub name: String,
 	/// The formatted URL for documentation links
 	pub formatted_url: String,
 	/// Repository builder for handling remote repository operations
 	repository_builder: RepositoryBuilder,
+	/// Which kinds of changes count as drift
+	#[serde(default, skip_serializing_if = "DiffSensitivity::is_default")]
+	pub sensitivity: DiffSensitivity,
+	/// Filter applied to blob content before diffing
+	#[serde(default, skip_serializing_if = "ContentFilter::is_default")]
+	pub filter: ContentFilter,
+	/// Character encoding of blob content, which is normalized to UTF-8 before diffing
+	#[serde(default, skip_serializing_if = "TextEncoding::is_default")]
+	pub encoding: TextEncoding,
+	/// Whether changes of whitespace alone count as drift
+	#[serde(default, skip_serializing_if = "WhitespaceMode::is_default")]
+	pub whitespace: WhitespaceMode,
+	/// Whether blocks moving without changing count as drift
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub ignore_moves: bool,
+	/// Unit of text drift is rendered in
+	#[serde(default, skip_serializing_if = "DiffGranularity::is_default")]
+	pub diff_granularity: DiffGranularity,
+	/// What the current revision is diffed from
+	#[serde(default, skip_serializing_if = "DiffBase::is_default")]
+	pub diff_base: DiffBase,
+	/// Further revisions whose content must be identical to the referenced revision's, as the
+	/// current revision's must
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub revisions: Vec<String>,
+	/// Unified diff the file must have changed by between the revisions, instead of not changing
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub expect_diff: Option<String>,
 }
 
 impl GitSource {
-	pub fn try_new(remote: &str, path: &str, referenced_revision: &str, current_revision: &str, name: Option<String>) -> Result<Self, GitSourceError> {
+	pub fn try_new(
+		remote: &str,
+		path: &str,
+		referenced_revision: &str,
+		current_revision: Option<&str>,
+		name: Option<String>,
+	) -> Result<Self, GitSourceError> {
 		// Basic validation
 		if remote.is_empty() {
 			return Err(GitSourceError::InvalidRemote("Remote URL cannot be empty".into()));
 		}
 		if referenced_revision.is_empty() {
-			return Err(GitSourceError::InvalidRevision("Referenced revision cannot be empty".into()));
+			return Err(GitSourceError::InvalidRevision(
+				"Referenced revision cannot be empty".into(),
+			));
 		}
-		if current_revision.is_empty() {
+		if current_revision.is_some_and(str::is_empty) {
 			return Err(GitSourceError::InvalidRevision("Current revision cannot be empty".into()));
 		}
-		
+
 		// Parse the path into a PathPattern
 		let path_pattern = PathPattern::try_new(path)?;
 
-		let name = name.as_deref().unwrap_or(&format!("{}/{}@{}", remote, path_pattern, referenced_revision)).to_string();
-		
+		let name = name
+			.as_deref()
+			.unwrap_or(&format!("{}/{}@{}", remote, path_pattern, referenced_revision))
+			.to_string();
+
 		// Compute the formatted URL for GitHub links
 		let formatted_url = if remote.contains("github.com") {
 			// Determine if this is a file or directory
@@ -150,12 +225,8 @@ impl GitSource {
 				// Check for common file extensions
 				path.contains('.') && !path.ends_with('/')
 			};
-			let github_path = if is_file {
-				"blob"
-			} else {
-				"tree"
-			};
-			
+			let github_path = if is_file { "blob" } else { "tree" };
+
 			format!(
 				"{}/{}/{}/{}",
 				remote.trim_end_matches(".git"),
@@ -166,19 +237,165 @@ impl GitSource {
 		} else {
 			remote.to_string()
 		};
-		
-		let id = Id::new(format!("git_{}_{}_{}_{}", remote, path, referenced_revision, current_revision));
+
+		let id = Id::new(format!(
+			"git_{}_{}_{}_{}",
+			remote,
+			path,
+			referenced_revision,
+			current_revision.unwrap_or("HEAD")
+		));
 		Ok(Self {
 			id,
 			remote: remote.to_string(),
 			path_pattern,
 			referenced_revision: referenced_revision.to_string(),
-			current_revision: current_revision.to_string(),
+			current_revision: current_revision.map(str::to_string),
 			name,
 			formatted_url,
 			repository_builder: RepositoryBuilder::new(remote.to_string()),
+			sensitivity: DiffSensitivity::default(),
+			filter: ContentFilter::default(),
+			encoding: TextEncoding::default(),
+			whitespace: WhitespaceMode::default(),
+			ignore_moves: false,
+			diff_granularity: DiffGranularity::default(),
+			diff_base: DiffBase::default(),
+			revisions: Vec::new(),
+			expect_diff: None,
 		})
 	}
+
+	/// Set which kinds of changes count as drift
+	pub fn with_sensitivity(mut self, sensitivity: DiffSensitivity) -> Self {
+		self.sensitivity = sensitivity;
+		self
+	}
+
+	/// Set whether changes of whitespace alone are ignored, e.g. reindentation
+	pub fn with_whitespace(mut self, whitespace: WhitespaceMode) -> Self {
+		self.whitespace = whitespace;
+		self
+	}
+
+	/// Set whether blocks moving without changing are ignored
+	pub fn with_ignore_moves(mut self, ignore_moves: bool) -> Self {
+		self.ignore_moves = ignore_moves;
+		self
+	}
+
+	/// Render drift word by word or character by character instead of line by line
+	pub fn with_diff_granularity(mut self, diff_granularity: DiffGranularity) -> Self {
+		self.diff_granularity = diff_granularity;
+		self
+	}
+
+	/// Set the filter applied to blob content before diffing
+	pub fn with_filter(mut self, filter: ContentFilter) -> Self {
+		self.filter = filter;
+		self
+	}
+
+	/// Set the character encoding of blob content, instead of detecting it
+	pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+		self.encoding = encoding;
+		self
+	}
+
+	/// Set what the current revision is diffed from, e.g. the merge-base with `main`
+	pub fn with_diff_base(mut self, diff_base: DiffBase) -> Self {
+		self.diff_base = diff_base;
+		self
+	}
+
+	/// Require the file to have changed exactly by the unified diff between the revisions, e.g.
+	/// to lock in a transformation of generated code
+	///
+	/// Headers, context lines and whitespace are not compared, see [expected_diff::normalize_diff].
+	pub fn with_expect_diff(mut self, expect_diff: &str) -> Self {
+		self.id = Id::new(format!("{}_expected_diff", self.id.as_str()));
+		self.expect_diff = Some(expect_diff.to_string());
+		self
+	}
+
+	/// Also require the content of each of the revisions to be identical to the referenced
+	/// revision's, e.g. to keep a file consistent across release branches
+	pub fn with_revisions(mut self, revisions: Vec<String>) -> Self {
+		if !revisions.is_empty() {
+			self.id = Id::new(format!("{}_{}", self.id.as_str(), revisions.join("_")));
+		}
+		self.revisions = revisions;
+		self
+	}
+
+	/// Cite only the named region of the file, so that lines added or removed around it are not
+	/// drift
+	pub fn with_region(mut self, region: &str) -> Result<Self, GitSourceError> {
+		self.path_pattern = self.path_pattern.with_region(region)?;
+		self.id = Id::new(format!("{}_{}", self.id.as_str(), region));
+		Ok(self)
+	}
+
+	/// Clone the repository into the given directory instead of the default
+	pub fn with_target_dir(mut self, target_dir: std::path::PathBuf) -> Self {
+		self.repository_builder = self.repository_builder.with_target_dir(target_dir);
+		self
+	}
+
+	/// Get the path the repository is cloned to, or the path of a local repository
+	pub fn repository_path(&self) -> Result<std::path::PathBuf, GitSourceError> {
+		if let Some(path) = RepositoryBuilder::local_path(&self.remote) {
+			return Ok(path);
+		}
+		let target_dir = self.repository_builder.get_target_dir()?;
+		Ok(target_dir.join(RepositoryBuilder::generate_repo_dir_name(&self.remote)))
+	}
+
+	/// Check if the referenced revision is in the local clone of the repository, without fetching
+	/// anything
+	pub fn has_referenced_revision(&self) -> bool {
+		self.repository_path().is_ok_and(|path| {
+			RepositoryManager::new(path).revision_exists(&self.referenced_revision)
+		})
+	}
+
+	/// Describe the current revision, which is the default branch of the remote if omitted
+	pub fn describe_current_revision(&self) -> &str {
+		self.current_revision.as_deref().unwrap_or("default branch")
+	}
+
+	/// Get the documentation link, following a rename of the repository known to the resolver
+	///
+	/// Falls back to the link of the remote as given when the repository cannot be resolved.
+	pub fn resolved_link(&self, resolver: &dyn RepoResolver) -> String {
+		rename::resolve_link(&self.formatted_url, &self.remote, resolver)
+	}
+
+	/// Set the number of retries for transient clone and fetch failures
+	pub fn with_retries(mut self, retries: u32) -> Self {
+		let retry = self.repository_builder.retry_policy().with_retries(retries);
+		self.repository_builder = self.repository_builder.with_retry_policy(retry);
+		self
+	}
+
+	/// Validate against the existing clone of the repository, without network access
+	pub fn with_offline(mut self, offline: bool) -> Self {
+		self.repository_builder = self.repository_builder.with_offline(offline);
+		self
+	}
+
+	/// Authenticate to the remote with the given credentials, e.g. an SSH key or a token for a
+	/// private repository
+	pub fn with_credentials(mut self, credentials: GitCredentials) -> Self {
+		self.repository_builder = self.repository_builder.with_credentials(credentials);
+		self
+	}
+
+	/// Clone and fetch only the given number of commits of history
+	pub fn with_depth(mut self, depth: Option<u32>) -> Self {
+		self.repository_builder = self.repository_builder.with_depth(depth);
+		self
+	}
 }
 
 impl Source<ReferencedGitContent, CurrentGitContent, GitDiff> for GitSource {
@@ -196,37 +413,159 @@ impl Source<ReferencedGitContent, CurrentGitContent, GitDiff> for GitSource {
 		&self.formatted_url
 	}
 
+	/// Offline, every compared revision must already be in the local clone
+	fn supports_offline(&self) -> bool {
+		let Ok(path) = self.repository_path() else {
+			return false;
+		};
+		let manager = match RepositoryBuilder::local_path(&self.remote) {
+			Some(_) => RepositoryManager::local(path),
+			None => RepositoryManager::new(path).with_offline(true),
+		};
+		let base = match &self.diff_base {
+			DiffBase::MergeBase(base) => Some(base),
+			DiffBase::Referenced => None,
+		};
+		let current_revision =
+			self.current_revision.clone().or_else(|| manager.default_branch().ok());
+		if !current_revision.is_some_and(|revision| manager.revision_exists(&revision)) {
+			return false;
+		}
+		[&self.referenced_revision]
+			.into_iter()
+			.chain(&self.revisions)
+			.chain(base)
+			.all(|revision| manager.revision_exists(revision))
+	}
+
+	fn refresh(&self) -> Result<(), SourceError> {
+		Err(SourceError::ManualUpdate(
+			"git citations reference a revision; update `ref_rev` in the citation".to_string(),
+		))
+	}
+
+	fn target_description(&self) -> String {
+		let description = format!(
+			"Remote: {}\nPath: {}\nRevision: {}",
+			self.remote,
+			self.path_pattern.path,
+			self.describe_current_revision()
+		);
+		let description = match &self.path_pattern.region {
+			Some(region) => format!("{}\nRegion: {}", description, region),
+			None => description,
+		};
+		let description = match &self.diff_base {
+			DiffBase::Referenced => description,
+			DiffBase::MergeBase(_) => format!("{}\nDiff base: {}", description, self.diff_base),
+		};
+		if self.revisions.is_empty() {
+			description
+		} else {
+			format!("{}\nAlso compared: {}", description, self.revisions.join(", "))
+		}
+	}
+
+	fn get(
+		&self,
+	) -> Result<Comparison<ReferencedGitContent, CurrentGitContent, GitDiff>, SourceError> {
+		let _span = cite_core::trace_span!(
+			"git",
+			remote = self.remote.as_str(),
+			path = self.path_pattern.path.as_str()
+		);
+		let referenced = self.get_referenced()?;
+		let current = self.get_current()?;
+		let diff = current.diff(&referenced)?;
+		let diff = match &self.expect_diff {
+			Some(expect_diff) => diff.with_expected_diff(expect_diff),
+			None => diff.with_sensitivity(self.sensitivity).with_ignore_moves(self.ignore_moves),
+		};
+		cite_core::trace_event!(changed = !diff.is_empty(), "compared");
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
 	fn get_referenced(&self) -> Result<ReferencedGitContent, SourceError> {
 		// Use the embedded repository builder to fetch the repository
-		let repository_manager = self.repository_builder.clone().fetch()
-			.map_err(|e| SourceError::Internal(e.into()))?;
-		
+		let repository_manager = cite_core::trace_timed!(
+			self.repository_builder.clone().fetch(),
+			revision = self.referenced_revision.as_str(),
+			"fetched"
+		)
+		.map_err(|e| SourceError::Internal(e.into()))?;
+
 		// Fetch the specific referenced revision if it doesn't exist
-		repository_manager.fetch_specific_revisions(&[&self.referenced_revision])
+		repository_manager
+			.fetch_specific_revisions(&[&self.referenced_revision])
 			.map_err(|e| SourceError::Internal(e.into()))?;
-		
-		Ok(ReferencedGitContent { 
-			remote: self.remote.clone(), 
-			path_pattern: self.path_pattern.clone(), 
+
+		// Resolve revisions like `main~3` to the commit they name
+		let commit = repository_manager
+			.resolve_revision(&self.referenced_revision)
+			.map_err(|e| SourceError::Internal(e.into()))?;
+
+		Ok(ReferencedGitContent {
+			remote: self.remote.clone(),
+			path_pattern: self.path_pattern.clone(),
 			revision: self.referenced_revision.clone(),
+			commit: Some(commit),
 			repository_manager,
 		})
 	}
 
 	fn get_current(&self) -> Result<CurrentGitContent, SourceError> {
 		// Use the embedded repository builder to fetch the repository
-		let repository_manager = self.repository_builder.clone().fetch()
-			.map_err(|e| SourceError::Internal(e.into()))?;
-		
+		let repository_manager = cite_core::trace_timed!(
+			self.repository_builder.clone().fetch(),
+			revision = self.describe_current_revision(),
+			"fetched"
+		)
+		.map_err(|e| SourceError::Internal(e.into()))?;
+
+		// Without a current revision, the tip of the remote's default branch is compared
+		let current_revision = match &self.current_revision {
+			Some(current_revision) => current_revision.clone(),
+			None => repository_manager
+				.default_branch()
+				.map_err(|e| SourceError::Internal(e.into()))?,
+		};
+
 		// Fetch the specific current revision if it doesn't exist
-		repository_manager.fetch_specific_revisions(&[&self.current_revision])
+		repository_manager
+			.fetch_specific_revisions(&[&current_revision])
+			.map_err(|e| SourceError::Internal(e.into()))?;
+
+		// So must the further revisions compared against the referenced one
+		if !self.revisions.is_empty() {
+			let revisions = self.revisions.iter().map(String::as_str).collect::<Vec<_>>();
+			repository_manager
+				.fetch_specific_revisions(&revisions)
+				.map_err(|e| SourceError::Internal(e.into()))?;
+		}
+
+		// The merge-base can only be found with the base revision present
+		if let DiffBase::MergeBase(base) = &self.diff_base {
+			repository_manager
+				.fetch_specific_revisions(&[base])
+				.map_err(|e| SourceError::Internal(e.into()))?;
+		}
+
+		// Resolve revisions like `main~3` to the commit they name
+		let commit = repository_manager
+			.resolve_revision(&current_revision)
 			.map_err(|e| SourceError::Internal(e.into()))?;
-		
-		Ok(CurrentGitContent { 
-			remote: self.remote.clone(), 
-			path_pattern: self.path_pattern.clone(), 
-			revision: self.current_revision.clone(),
+
+		Ok(CurrentGitContent {
+			remote: self.remote.clone(),
+			path_pattern: self.path_pattern.clone(),
+			revision: current_revision,
+			commit: Some(commit),
 			repository_manager,
+			filter: self.filter,
+			encoding: self.encoding,
+			whitespace: self.whitespace,
+			diff_base: self.diff_base.clone(),
+			revisions: self.revisions.clone(),
 		})
 	}
 }
@@ -237,6 +576,9 @@ pub struct ReferencedGitContent {
 	pub remote: String,
 	pub path_pattern: PathPattern,
 	pub revision: String,
+	/// SHA of the commit the revision resolved to, e.g. of `main~3`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub commit: Option<String>,
 	#[serde(skip)]
 	pub repository_manager: RepositoryManager,
 }
@@ -247,27 +589,146 @@ pub struct CurrentGitContent {
 	pub remote: String,
 	pub path_pattern: PathPattern,
 	pub revision: String,
+	/// SHA of the commit the revision resolved to, e.g. of `main~3`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub commit: Option<String>,
 	#[serde(skip)]
 	pub repository_manager: RepositoryManager,
+	/// Filter applied to blob content before diffing
+	#[serde(default)]
+	pub filter: ContentFilter,
+	/// Character encoding of blob content, which is normalized to UTF-8 before diffing
+	#[serde(default)]
+	pub encoding: TextEncoding,
+	/// Whether changes of whitespace alone count as drift
+	#[serde(default)]
+	pub whitespace: WhitespaceMode,
+	/// What the current revision is diffed from
+	#[serde(default)]
+	pub diff_base: DiffBase,
+	/// Further revisions diffed against the referenced revision, as the current revision is
+	#[serde(default)]
+	pub revisions: Vec<String>,
+}
+
+/// Describe a revision with the commit it resolved to, e.g. `main~3 (1a2b3c4...)`
+fn describe_revision(revision: &str, commit: Option<&str>) -> String {
+	match commit {
+		Some(commit) if commit != revision => format!("{} ({})", revision, commit),
+		_ => revision.to_string(),
+	}
+}
+
+impl ReferencedGitContent {
+	/// Describe the revision with the commit it resolved to
+	pub fn describe_revision(&self) -> String {
+		describe_revision(&self.revision, self.commit.as_deref())
+	}
 }
 
+impl CurrentGitContent {
+	/// Describe the revision with the commit it resolved to
+	pub fn describe_revision(&self) -> String {
+		describe_revision(&self.revision, self.commit.as_deref())
+	}
+
+	/// Diff the current and each further revision against the referenced revision, reporting
+	/// each revision that diverges with its diff
+	fn diff_each_revision(
+		&self,
+		referenced: &ReferencedGitContent,
+	) -> Result<GitDiff, SourceError> {
+		let current = CurrentGitContent { revisions: Vec::new(), ..self.clone() };
+		let mut compared = vec![current.clone()];
+		for revision in &self.revisions {
+			let commit = self
+				.repository_manager
+				.resolve_revision(revision)
+				.map_err(|e| SourceError::Internal(e.into()))?;
+			compared.push(CurrentGitContent {
+				revision: revision.clone(),
+				commit: Some(commit),
+				..current.clone()
+			});
+		}
+
+		let mut diff = String::new();
+		let mut change = None;
+		let mut files = Vec::new();
+		for current in compared {
+			let revision_diff = current.diff(referenced)?;
+			change = change.max(revision_diff.describe_change());
+			if revision_diff.has_changes() {
+				files.extend(revision_diff.files().iter().cloned());
+				diff.push_str(&format!(
+					"Revision {} diverges from {}:\n{}",
+					current.describe_revision(),
+					referenced.describe_revision(),
+					revision_diff.diff()
+				));
+			}
+		}
+		Ok(GitDiff { has_changes: !diff.is_empty(), diff, change, files })
+	}
+}
 
+/// Files are sniffed by the extension of the cited path
+impl Content for ReferencedGitContent {
+	fn content_type(&self) -> ContentType {
+		ContentType::from_path(Path::new(&self.path_pattern.path))
+	}
+}
+
+impl Content for CurrentGitContent {
+	fn content_type(&self) -> ContentType {
+		ContentType::from_path(Path::new(&self.path_pattern.path))
+	}
+}
 
-impl Content for ReferencedGitContent {}
-impl Content for CurrentGitContent {}
 impl Referenced for ReferencedGitContent {}
 
+/// Summary of the changes of one file of a diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+	/// The path of the file in the repository
+	pub path: String,
+	/// The number of lines added to the file
+	pub added: usize,
+	/// The number of lines removed from the file
+	pub removed: usize,
+}
+
 /// Git diff representation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitDiff {
 	diff: String,
 	has_changes: bool,
+	/// Severity of the change, the most severe of those of each changed file
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	change: Option<ChangeClass>,
+	/// The changed files, in the order their hunks appear in the diff
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	files: Vec<FileChange>,
 }
 
 impl Diff for GitDiff {
 	fn is_empty(&self) -> bool {
 		!self.has_changes
 	}
+
+	fn describe_change(&self) -> Option<ChangeClass> {
+		self.change.filter(|_| self.has_changes)
+	}
+
+	/// Get the unified diff output, similar to HTTP sources
+	/// Returns Some(diff_string) if there are changes, None if no changes
+	fn unified_diff(&self) -> Option<&str> {
+		if self.has_changes && !self.diff.is_empty() {
+			Some(&self.diff)
+		} else {
+			None
+		}
+	}
 }
 
 impl GitDiff {
@@ -279,35 +740,127 @@ impl GitDiff {
 		&self.diff
 	}
 
-	/// Get the unified diff output, similar to HTTP sources
-	/// Returns Some(diff_string) if there are changes, None if no changes
-	pub fn unified_diff(&self) -> Option<&str> {
-		if self.has_changes && !self.diff.is_empty() {
-			Some(&self.diff)
-		} else {
-			None
+	/// Get the changed files with the number of lines added to and removed from each, e.g. for
+	/// a glob matching many files
+	pub fn files(&self) -> &[FileChange] {
+		&self.files
+	}
+
+	/// Filter the change set by sensitivity, inspecting the changed lines of the diff
+	///
+	/// Moved lines count as both removed (where they were) and added (where they are now).
+	pub fn with_sensitivity(mut self, sensitivity: DiffSensitivity) -> Self {
+		let changes = self.line_changes();
+		let has_additions = changes
+			.iter()
+			.any(|change| matches!(change, LineChange::Added | LineChange::MovedTo));
+		let has_removals = changes
+			.iter()
+			.any(|change| matches!(change, LineChange::Removed | LineChange::MovedFrom));
+
+		self.has_changes = self.has_changes && sensitivity.is_drift(has_additions, has_removals);
+		self
+	}
+
+	/// Count the diff as drift unless it is the expected one, which an unchanged file is not
+	pub fn with_expected_diff(mut self, expected: &str) -> Self {
+		self.has_changes = !ExpectedDiffCheck::new(expected, &self.diff).is_match();
+		self
+	}
+
+	/// Check if the diff only consists of blocks moving, without any line being changed
+	pub fn has_only_moves(&self) -> bool {
+		only_moves(&self.line_changes())
+	}
+
+	/// Drop the change set if it only consists of moved blocks
+	pub fn with_ignore_moves(mut self, ignore_moves: bool) -> Self {
+		self.has_changes = self.has_changes && !(ignore_moves && self.has_only_moves());
+		self
+	}
+
+	/// Relabel the removed and added blocks of the diff that are relocations of one another,
+	/// prefixing them with `<` where they were and `>` where they are now
+	fn with_moves_labelled(mut self) -> Self {
+		let lines = self.diff.split_inclusive('\n').collect::<Vec<_>>();
+		let changes = lines
+			.iter()
+			.zip(file_header_flags(lines.iter().copied()))
+			.map(|(line, is_header)| match line.chars().next() {
+				_ if is_header => (similar::ChangeTag::Equal, *line),
+				Some('-') => (similar::ChangeTag::Delete, &line[1..]),
+				Some('+') => (similar::ChangeTag::Insert, &line[1..]),
+				_ => (similar::ChangeTag::Equal, *line),
+			})
+			.collect::<Vec<_>>();
+
+		let mut diff = String::with_capacity(self.diff.len());
+		for (label, (_, line)) in label_moves(&changes).iter().zip(&changes) {
+			// Unchanged lines and headers keep their prefix
+			if *label != LineChange::Equal {
+				diff.push(label.sign());
+			}
+			diff.push_str(line);
 		}
+
+		self.diff = diff;
+		self
+	}
+
+	/// Get the kind of change of each changed line of the diff
+	fn line_changes(&self) -> Vec<LineChange> {
+		self.diff
+			.lines()
+			.zip(file_header_flags(self.diff.lines()))
+			.filter(|(_, is_header)| !is_header)
+			.filter_map(|(line, _)| match line.chars().next() {
+				Some('-') => Some(LineChange::Removed),
+				Some('+') => Some(LineChange::Added),
+				Some('<') => Some(LineChange::MovedFrom),
+				Some('>') => Some(LineChange::MovedTo),
+				_ => None,
+			})
+			.collect()
 	}
 }
 
 impl Current<ReferencedGitContent, GitDiff> for CurrentGitContent {
 	fn diff(&self, other: &ReferencedGitContent) -> Result<GitDiff, SourceError> {
+		if !self.revisions.is_empty() {
+			return self.diff_each_revision(other);
+		}
+
 		// Use the repository manager
 		let repo_manager = &self.repository_manager;
-		
-		let repo = repo_manager.get_repository()
-			.map_err(|e| SourceError::Internal(e.into()))?;
+
+		let repo = repo_manager.get_repository().map_err(|e| SourceError::Internal(e.into()))?;
 		let _repo_path = repo_manager.path().clone();
-		
+
+		// Compare the resolved commits, so that `main~3` means the same commit on both sides
+		let referenced_revision = other.commit.as_deref().unwrap_or(&other.revision);
+		let current_revision = self.commit.as_deref().unwrap_or(&self.revision);
+
+		// Diff from where the current revision diverged from the base instead, if asked to
+		let merge_base = match &self.diff_base {
+			DiffBase::Referenced => None,
+			DiffBase::MergeBase(base) => Some(
+				repo_manager
+					.merge_base(base, current_revision)
+					.map_err(|e| SourceError::Internal(e.into()))?,
+			),
+		};
+		let referenced_revision = merge_base.as_deref().unwrap_or(referenced_revision);
+
 		// Check if the revision exists in the repository
-		if !repo_manager.revision_exists(&other.revision) {
+		if !repo_manager.revision_exists(referenced_revision) {
 			return Err(SourceError::Internal(
-				format!("Revision {} not found in repository {}", other.revision, self.remote).into(),
+				format!("Revision {} not found in repository {}", other.revision, self.remote)
+					.into(),
 			));
 		}
-		
+
 		let obj = repo
-			.revparse_single(&other.revision)
+			.revparse_single(referenced_revision)
 			.map_err(|e| SourceError::Internal(e.into()))?;
 
 		let comparison_tree = match obj.kind() {
@@ -334,12 +887,13 @@ impl Current<ReferencedGitContent, GitDiff> for CurrentGitContent {
 
 		// Get the current revision's tree for comparison
 		let current_obj = repo
-			.revparse_single(&self.revision)
+			.revparse_single(current_revision)
 			.map_err(|e| SourceError::Internal(e.into()))?;
 
 		let current_tree = match current_obj.kind() {
 			Some(git2::ObjectType::Commit) => {
-				let commit = current_obj.peel_to_commit().map_err(|e| SourceError::Internal(e.into()))?;
+				let commit =
+					current_obj.peel_to_commit().map_err(|e| SourceError::Internal(e.into()))?;
 				commit.tree().map_err(|e| SourceError::Internal(e.into()))?
 			}
 			Some(git2::ObjectType::Tag) => {
@@ -359,61 +913,197 @@ impl Current<ReferencedGitContent, GitDiff> for CurrentGitContent {
 			}
 		};
 
+		// The cited region must exist at the referenced revision, even if the file is unchanged
+		if let Some(region) = &self.path_pattern.region {
+			let path = Path::new(&self.path_pattern.path);
+			let entry = comparison_tree.get_path(path).map_err(|_| {
+				SourceError::ContentParsing(format!(
+					"Path {} not found at revision {}",
+					self.path_pattern.path, other.revision
+				))
+			})?;
+			let content = self.encoding.normalize(&read_blob(&repo, entry.id())?);
+			if extract_region(&content, region)?.is_none() {
+				return Err(SourceError::ContentParsing(format!(
+					"Region {} not found in {} at revision {}",
+					region, self.path_pattern.path, other.revision
+				)));
+			}
+		}
+
 		// Compare the two trees: referenced_revision vs current_revision
 		let mut opts = DiffOptions::new();
 		opts.pathspec(&self.path_pattern.path);
+		self.whitespace.apply(&mut opts);
 
-		let diff = repo.diff_tree_to_tree(Some(&comparison_tree), Some(&current_tree), Some(&mut opts))
+		let diff = repo
+			.diff_tree_to_tree(Some(&comparison_tree), Some(&current_tree), Some(&mut opts))
 			.map_err(|e| SourceError::Internal(e.into()))?;
 
 		// Capture the diff output and check for intersections
 		let mut buffer = String::new();
 		let mut has_changes = false;
+		let mut changed_lines = Vec::new();
+		let mut files: Vec<FileChange> = Vec::new();
+		let mut file_header = None;
+
+		let mut print_line =
+			|delta: git2::DiffDelta, _hunk: Option<git2::DiffHunk>, line: git2::DiffLine| {
+				// Check if this delta affects a file that matches our pattern
+				let file_path = delta.new_file().path().or_else(|| delta.old_file().path());
+
+				if let Some(path) = file_path {
+					if self.path_pattern.matches(path) {
+						// The `diff --git a/... b/...` header of the file precedes its first
+						// included line, so that the hunks of each file are told apart
+						if line.origin() == 'F' {
+							let header = String::from_utf8_lossy(line.content());
+							file_header = Some(header.into_owned());
+							return true;
+						}
 
-		diff.print(DiffFormat::Patch, |delta, _hunk, line| {
-			// Check if this delta affects a file that matches our pattern
-			let file_path = delta.new_file().path().or_else(|| delta.old_file().path());
-
-			if let Some(path) = file_path {
-				if self.path_pattern.matches(path) {
-					// Check if this line is within our line range
-					let should_include = if let Some(ref line_range) = self.path_pattern.line_range
-					{
-						// Get line numbers from the diff line
-						let new_line = line.new_lineno();
-						let old_line = line.old_lineno();
-
-						// Check if any of the line numbers fall within our range
-						(new_line.map_or(false, |line_num| {
-							line_range.start <= line_num as usize
-								&& line_num as usize <= line_range.end
-						})) || (old_line.map_or(false, |line_num| {
-							line_range.start <= line_num as usize
-								&& line_num as usize <= line_range.end
-						}))
-					} else {
-						// No line range specified, include all lines
-						true
-					};
-
-					if should_include {
-						has_changes = true;
-
-						// Add the diff line
-						buffer.push(line.origin());
-						if let Ok(content) = std::str::from_utf8(line.content()) {
-							buffer.push_str(content);
+						// Check if this line is within our line range
+						let should_include =
+							if let Some(ref line_range) = self.path_pattern.line_range {
+								// Get line numbers from the diff line
+								let new_line = line.new_lineno();
+								let old_line = line.old_lineno();
+
+								// Check if any of the line numbers fall within our range
+								(new_line.map_or(false, |line_num| {
+									line_range.start <= line_num as usize
+										&& line_num as usize <= line_range.end
+								})) || (old_line.map_or(false, |line_num| {
+									line_range.start <= line_num as usize
+										&& line_num as usize <= line_range.end
+								}))
+							} else {
+								// No line range specified, include all lines
+								true
+							};
+
+						if should_include {
+							has_changes = true;
+
+							if let Some(header) = file_header.take() {
+								buffer.push_str(&header);
+								files.push(FileChange {
+									path: path.to_string_lossy().into_owned(),
+									added: 0,
+									removed: 0,
+								});
+							}
+
+							// Add the diff line; hunk headers carry their own `@@` prefix, and
+							// the `\ No newline at end of file` marks their own line breaks
+							if !matches!(line.origin(), 'H' | '=' | '<' | '>') {
+								buffer.push(line.origin());
+							}
+							if let Ok(content) = std::str::from_utf8(line.content()) {
+								buffer.push_str(content);
+							}
+							if let Some(file) = files.last_mut() {
+								match line.origin() {
+									'+' => file.added += 1,
+									'-' => file.removed += 1,
+									_ => {}
+								}
+							}
+
+							// Keep the lines of each file to classify its change
+							let tag = match line.origin() {
+								'+' => Some(similar::ChangeTag::Insert),
+								'-' => Some(similar::ChangeTag::Delete),
+								' ' => Some(similar::ChangeTag::Equal),
+								_ => None,
+							};
+							if let Some(tag) = tag {
+								let content = String::from_utf8_lossy(line.content()).into_owned();
+								changed_lines.push((path.to_path_buf(), tag, content));
+							}
 						}
 					}
 				}
+
+				true
+			};
+
+		// Re-diff each changed file from its normalized content: decoded to UTF-8 without a byte
+		// order mark, and as seen in a working tree checked out at the current revision if asked to
+		let attributes = match self.filter {
+			ContentFilter::Raw => GitAttributes::default(),
+			ContentFilter::GitAttributes => GitAttributes::from_tree(&repo, &current_tree)
+				.map_err(|e| SourceError::Internal(e.into()))?,
+		};
+
+		for delta in diff.deltas() {
+			let (old_file, new_file) = (delta.old_file(), delta.new_file());
+			let path = match new_file.path().or_else(|| old_file.path()) {
+				Some(path) if self.path_pattern.matches(path) => path,
+				_ => continue,
+			};
+
+			let old_content = self.encoding.normalize(&read_blob(&repo, old_file.id())?);
+			let new_content = self.encoding.normalize(&read_blob(&repo, new_file.id())?);
+			let old_content = attributes.normalize(path, &old_content);
+			let new_content = attributes.normalize(path, &new_content);
+
+			// Only the cited region is compared; its removal leaves nothing of it
+			let (old_content, new_content) = match &self.path_pattern.region {
+				Some(region) => (
+					extract_region(&old_content, region)?.unwrap_or_default(),
+					extract_region(&new_content, region)?.unwrap_or_default(),
+				),
+				None => (old_content, new_content),
+			};
+			if old_content == new_content {
+				continue;
 			}
 
-			true
-		})
-		.map_err(|e| SourceError::Internal(e.into()))?;
+			let mut opts = DiffOptions::new();
+			self.whitespace.apply(&mut opts);
+			let mut patch = git2::Patch::from_buffers(
+				&old_content,
+				Some(path),
+				&new_content,
+				Some(path),
+				Some(&mut opts),
+			)
+			.map_err(|e| SourceError::Internal(e.into()))?;
+			patch.print(&mut print_line).map_err(|e| SourceError::Internal(e.into()))?;
+		}
+
+		// Classify the change of each file, the most severe being that of the diff
+		let change = changed_lines
+			.chunk_by(|(a, _, _), (b, _, _)| a == b)
+			.filter_map(|lines| {
+				let changes = lines
+					.iter()
+					.map(|(_, tag, content)| (*tag, content.as_str()))
+					.collect::<Vec<_>>();
+				change::classify(&lines[0].0, &changes)
+			})
+			.max();
+
+		Ok(GitDiff { diff: buffer, has_changes, change, files }.with_moves_labelled())
+	}
+}
+
+/// Extract the named region of normalized blob content
+fn extract_region(content: &[u8], region: &str) -> Result<Option<Vec<u8>>, SourceError> {
+	let content = String::from_utf8_lossy(content);
+	region::extract(&content, region)
+		.map(|region| region.map(String::into_bytes))
+		.map_err(|e| SourceError::ContentParsing(e.to_string()))
+}
 
-		Ok(GitDiff { diff: buffer, has_changes })
+/// Read a blob's content, treating a missing (zero) id as an empty file
+fn read_blob(repo: &git2::Repository, id: git2::Oid) -> Result<Vec<u8>, SourceError> {
+	if id.is_zero() {
+		return Ok(Vec::new());
 	}
+	let blob = repo.find_blob(id).map_err(|e| SourceError::Internal(e.into()))?;
+	Ok(blob.content().to_vec())
 }
 
 #[cfg(test)]
@@ -492,15 +1182,19 @@ mod tests {
 			"https://github.com/ramate-io/cite",
 			"README.md",
 			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
-			"main",
-			None
+			Some("main"),
+			None,
 		)?;
 
 		assert_eq!(source.remote, "https://github.com/ramate-io/cite");
 		assert_eq!(source.path_pattern.path, "README.md");
 		assert_eq!(source.referenced_revision, "94dab273cf6c2abe8742d6d459ad45c96ca9b694");
-		assert_eq!(source.current_revision, "main");
+		assert_eq!(source.current_revision.as_deref(), Some("main"));
 		assert!(format!("{:?}", source.id).contains("94dab273cf6c2abe8742d6d459ad45c96ca9b694"));
+		assert_eq!(
+			source.target_description(),
+			"Remote: https://github.com/ramate-io/cite\nPath: README.md\nRevision: main"
+		);
 
 		Ok(())
 	}
@@ -511,8 +1205,8 @@ mod tests {
 			"https://github.com/ramate-io/cite",
 			"README.md#L1-L5",
 			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
-			"main",
-			None
+			Some("main"),
+			None,
 		)?;
 
 		let referenced_content = source.get_referenced()?;
@@ -528,15 +1222,41 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_git_content_type_by_extension() -> Result<(), anyhow::Error> {
+		let content_type = |path: &str| -> Result<_, anyhow::Error> {
+			let referenced_content = ReferencedGitContent {
+				remote: "fixture".to_string(),
+				path_pattern: PathPattern::try_new(path)?,
+				revision: "main".to_string(),
+				commit: None,
+				repository_manager: RepositoryManager::new(std::env::temp_dir()),
+			};
+			Ok(referenced_content.content_type())
+		};
+
+		assert_eq!(content_type("src/lib.rs#L1-L5")?, ContentType::Text);
+		assert_eq!(content_type("package.json")?, ContentType::Json);
+		assert_eq!(content_type("docs/index.html")?, ContentType::Html);
+		assert_eq!(content_type("assets/logo.png")?, ContentType::Binary);
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_git_diff_creation() {
-		let diff =
-			GitDiff { diff: "--- a/README.md\n+++ b/README.md\n".to_string(), has_changes: true };
+		let diff = GitDiff {
+			diff: "--- a/README.md\n+++ b/README.md\n".to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
+		};
 
 		assert!(!diff.is_empty());
 		assert!(diff.has_changes);
 
-		let empty_diff = GitDiff { diff: String::new(), has_changes: false };
+		let empty_diff =
+			GitDiff { diff: String::new(), has_changes: false, change: None, files: Vec::new() };
 
 		assert!(empty_diff.is_empty());
 		assert!(!empty_diff.has_changes);
@@ -544,32 +1264,776 @@ mod tests {
 
 	#[test]
 	fn test_git_diff_unified_diff() {
-		let diff_with_changes = GitDiff { 
-			diff: "--- a/README.md\n+++ b/README.md\n@@ -1,3 +1,3 @@\n-old content\n+new content\n unchanged\n".to_string(), 
-			has_changes: true 
+		let diff_with_changes = GitDiff {
+			diff: "--- a/README.md\n+++ b/README.md\n@@ -1,3 +1,3 @@\n-old content\n+new content\n unchanged\n".to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
 		};
 
 		// Should return Some when there are changes
 		assert!(diff_with_changes.unified_diff().is_some());
 		assert_eq!(diff_with_changes.unified_diff().unwrap(), diff_with_changes.diff());
 
-		let diff_no_changes = GitDiff { 
-			diff: "".to_string(), 
-			has_changes: false 
-		};
+		let diff_no_changes =
+			GitDiff { diff: "".to_string(), has_changes: false, change: None, files: Vec::new() };
 
 		// Should return None when there are no changes
 		assert!(diff_no_changes.unified_diff().is_none());
 
-		let diff_empty_string = GitDiff { 
-			diff: "".to_string(), 
-			has_changes: true 
-		};
+		let diff_empty_string =
+			GitDiff { diff: "".to_string(), has_changes: true, change: None, files: Vec::new() };
 
 		// Should return None when diff string is empty even if has_changes is true
 		assert!(diff_empty_string.unified_diff().is_none());
 	}
 
+	#[test]
+	fn test_git_diff_moved_block() {
+		let moved = GitDiff {
+			diff: "--- a/README.md\n+++ b/README.md\n@@ -1,5 +1,5 @@\n intro\n-step one\n-step two\n usage\n+step one\n+step two\n outro\n".to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
+		}
+		.with_moves_labelled();
+
+		assert_eq!(
+			moved.diff(),
+			"--- a/README.md\n+++ b/README.md\n@@ -1,5 +1,5 @@\n intro\n<step one\n<step two\n usage\n>step one\n>step two\n outro\n"
+		);
+		assert!(moved.has_only_moves());
+		assert!(!moved.clone().with_ignore_moves(false).is_empty());
+		assert!(moved.clone().with_ignore_moves(true).is_empty());
+
+		// Moves are both removals and additions
+		assert!(!moved.clone().with_sensitivity(DiffSensitivity::Removals).is_empty());
+		assert!(!moved.with_sensitivity(DiffSensitivity::Additions).is_empty());
+
+		let changed = GitDiff {
+			diff: "--- a/README.md\n+++ b/README.md\n@@ -1,5 +1,5 @@\n intro\n-step one\n-step two\n usage\n+step one\n+step 2\n outro\n".to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
+		}
+		.with_moves_labelled();
+
+		// A block that changed while moving is not a move
+		assert!(!changed.diff().contains('<'));
+		assert!(!changed.has_only_moves());
+		assert!(!changed.with_ignore_moves(true).is_empty());
+	}
+
+	#[test]
+	fn test_git_diff_sensitivity() {
+		let appended = GitDiff {
+			diff: "--- a/README.md\n+++ b/README.md\n@@ -1,1 +1,2 @@\n guarantee\n+addition\n"
+				.to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
+		};
+
+		// Appending content is not drift when only removals matter
+		assert!(appended.clone().with_sensitivity(DiffSensitivity::Removals).is_empty());
+		assert!(!appended.clone().with_sensitivity(DiffSensitivity::Additions).is_empty());
+		assert!(!appended.with_sensitivity(DiffSensitivity::Both).is_empty());
+
+		let removed = GitDiff {
+			diff: "--- a/README.md\n+++ b/README.md\n@@ -1,2 +1,1 @@\n guarantee\n-removed\n"
+				.to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
+		};
+
+		// Removing content is drift when only removals matter
+		assert!(!removed.clone().with_sensitivity(DiffSensitivity::Removals).is_empty());
+		assert!(removed.clone().with_sensitivity(DiffSensitivity::Additions).is_empty());
+		assert!(!removed.with_sensitivity(DiffSensitivity::Both).is_empty());
+
+		// A removed `-- comment` line reads like a file header, but follows the hunk header
+		let removed_comment = GitDiff {
+			diff: "diff --git a/schema.sql b/schema.sql\n--- a/schema.sql\n+++ b/schema.sql\n@@ -1,2 +1,1 @@\n select 1;\n--- deprecated\n".to_string(),
+			has_changes: true,
+			change: None,
+			files: Vec::new(),
+		};
+		assert!(!removed_comment.clone().with_sensitivity(DiffSensitivity::Removals).is_empty());
+		assert!(removed_comment.with_sensitivity(DiffSensitivity::Additions).is_empty());
+	}
+
+	/// Commit the given files on top of the parent, returning the new commit id
+	fn commit_files(
+		repo: &git2::Repository,
+		parent: Option<git2::Oid>,
+		files: &[(&str, &[u8])],
+	) -> Result<git2::Oid, anyhow::Error> {
+		let mut builder = repo.treebuilder(None)?;
+		for (path, content) in files {
+			let blob = repo.blob(content)?;
+			builder.insert(path, blob, 0o100644)?;
+		}
+		let tree = repo.find_tree(builder.write()?)?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		let parents = match parent {
+			Some(parent) => vec![repo.find_commit(parent)?],
+			None => vec![],
+		};
+		let parents = parents.iter().collect::<Vec<_>>();
+		Ok(repo.commit(None, &signature, &signature, "fixture", &tree, &parents)?)
+	}
+
+	/// The content of the fixture repository at a revision, as referenced
+	fn referenced_at(
+		manager: &RepositoryManager,
+		path_pattern: &PathPattern,
+		revision: impl ToString,
+	) -> ReferencedGitContent {
+		ReferencedGitContent {
+			remote: "fixture".to_string(),
+			path_pattern: path_pattern.clone(),
+			revision: revision.to_string(),
+			commit: None,
+			repository_manager: manager.clone(),
+		}
+	}
+
+	/// The content of the fixture repository at a revision, as current with the default options
+	fn current_at(
+		manager: &RepositoryManager,
+		path_pattern: &PathPattern,
+		revision: impl ToString,
+	) -> CurrentGitContent {
+		CurrentGitContent {
+			remote: "fixture".to_string(),
+			path_pattern: path_pattern.clone(),
+			revision: revision.to_string(),
+			commit: None,
+			repository_manager: manager.clone(),
+			filter: ContentFilter::Raw,
+			encoding: TextEncoding::default(),
+			whitespace: WhitespaceMode::default(),
+			diff_base: DiffBase::Referenced,
+			revisions: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn test_gitattributes_filter_normalizes_line_endings() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// The file was committed with CRLF line endings before a normalization rule was added
+		let referenced = commit_files(&repo, None, &[("notes.txt", b"one\r\ntwo\r\n")])?;
+		let normalized = commit_files(
+			&repo,
+			Some(referenced),
+			&[(".gitattributes", b"*.txt text\n"), ("notes.txt", b"one\ntwo\n")],
+		)?;
+		let changed = commit_files(
+			&repo,
+			Some(normalized),
+			&[(".gitattributes", b"*.txt text\n"), ("notes.txt", b"one\nthree\n")],
+		)?;
+
+		let path_pattern = PathPattern::try_new("notes.txt")?;
+		let referenced_content = referenced_at(&manager, &path_pattern, referenced);
+		let current_content = |revision: git2::Oid, filter: ContentFilter| CurrentGitContent {
+			filter,
+			..current_at(&manager, &path_pattern, revision)
+		};
+
+		// Raw blob content differs only in line endings
+		let diff = current_content(normalized, ContentFilter::Raw).diff(&referenced_content)?;
+		assert!(!diff.is_empty());
+
+		// Normalized content is what a user sees in the working tree
+		let diff =
+			current_content(normalized, ContentFilter::GitAttributes).diff(&referenced_content)?;
+		assert!(diff.is_empty());
+
+		// Real changes are still detected after normalization
+		let diff =
+			current_content(changed, ContentFilter::GitAttributes).diff(&referenced_content)?;
+		assert!(!diff.is_empty());
+		assert!(diff.diff().contains("-two"));
+		assert!(diff.diff().contains("+three"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_encoding_is_normalized() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// The file was re-saved with a byte order mark, then converted to Latin-1
+		let referenced = commit_files(&repo, None, &[("menu.txt", "café\nthé\n".as_bytes())])?;
+		let bom = commit_files(
+			&repo,
+			Some(referenced),
+			&[("menu.txt", b"\xef\xbb\xbfcaf\xc3\xa9\nth\xc3\xa9\n")],
+		)?;
+		let latin1 = commit_files(&repo, Some(bom), &[("menu.txt", b"caf\xe9\nth\xe9\n")])?;
+		let changed = commit_files(&repo, Some(latin1), &[("menu.txt", b"caf\xe9\nchocolat\n")])?;
+
+		let path_pattern = PathPattern::try_new("menu.txt")?;
+		let referenced_content = referenced_at(&manager, &path_pattern, referenced);
+		let current_content = |revision: git2::Oid, encoding: TextEncoding| CurrentGitContent {
+			encoding,
+			..current_at(&manager, &path_pattern, revision)
+		};
+
+		// Neither the byte order mark nor the encoding is drift
+		assert!(current_content(bom, TextEncoding::Auto).diff(&referenced_content)?.is_empty());
+		assert!(current_content(latin1, TextEncoding::Auto)
+			.diff(&referenced_content)?
+			.is_empty());
+
+		// Real changes are still detected, and shown decoded
+		let diff = current_content(changed, TextEncoding::Auto).diff(&referenced_content)?;
+		assert!(diff.diff().contains("-thé"));
+		assert!(diff.diff().contains("+chocolat"));
+
+		// Content read in another encoding than it was written in is drift
+		let diff = current_content(latin1, TextEncoding::Utf8).diff(&referenced_content)?;
+		assert!(!diff.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_whitespace_changes_are_ignored() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// The file was reindented and gained trailing whitespace, then really changed
+		let referenced = commit_files(&repo, None, &[("lib.rs", b"fn main() {\n  run();\n}\n")])?;
+		let reindented =
+			commit_files(&repo, Some(referenced), &[("lib.rs", b"fn main()  {\n\trun(); \n}\n")])?;
+		let changed =
+			commit_files(&repo, Some(reindented), &[("lib.rs", b"fn main()  {\n\tstop();\n}\n")])?;
+
+		let path_pattern = PathPattern::try_new("lib.rs")?;
+		let referenced_content = referenced_at(&manager, &path_pattern, referenced);
+		let current_content = |revision: git2::Oid, whitespace: WhitespaceMode| CurrentGitContent {
+			whitespace,
+			..current_at(&manager, &path_pattern, revision)
+		};
+
+		// Whitespace alone is drift unless ignored
+		let diff = current_content(reindented, WhitespaceMode::Exact).diff(&referenced_content)?;
+		assert!(diff.has_changes());
+		let diff = current_content(reindented, WhitespaceMode::Ignore).diff(&referenced_content)?;
+		assert!(!diff.has_changes());
+		assert!(diff.diff().is_empty());
+
+		// Other changes are still detected
+		let diff = current_content(changed, WhitespaceMode::Ignore).diff(&referenced_content)?;
+		assert!(diff.has_changes());
+		assert!(diff.diff().contains("+\tstop();"));
+		assert!(!diff.diff().contains("-fn main() {"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_patch_file_applies_to_referenced_revision() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// Blocks swap places, a removed comment looks like a file header, and the last line
+		// gains its line break
+		let referenced_schema = concat!(
+			"-- users\ncreate table users;\n",
+			"-- orders\ncreate table orders;\n",
+			"-- deprecated\nselect 1;"
+		);
+		let current_schema =
+			"-- orders\ncreate table orders;\n-- users\ncreate table users;\nselect 2;\n";
+		let referenced =
+			commit_files(&repo, None, &[("schema.sql", referenced_schema.as_bytes())])?;
+		let current =
+			commit_files(&repo, Some(referenced), &[("schema.sql", current_schema.as_bytes())])?;
+
+		let path_pattern = PathPattern::try_new("schema.sql")?;
+		let referenced_content = referenced_at(&manager, &path_pattern, referenced);
+		let diff = current_at(&manager, &path_pattern, current).diff(&referenced_content)?;
+		assert!(diff.diff().contains('<') && diff.diff().contains('>'), "{}", diff.diff());
+
+		// The patch is headed once, with the path in the repository rather than the id
+		let patch = diff.to_patch_file(&Id::new("git_fixture".to_string())).unwrap();
+		assert_eq!(patch.matches("--- a/").count(), 1, "{}", patch);
+		assert!(patch.contains("--- a/schema.sql\n+++ b/schema.sql\n"), "{}", patch);
+		assert!(!patch.contains("git_fixture"));
+
+		// It applies to the file as referenced, turning it into the file as it is now
+		std::fs::write(temp_dir.path().join("schema.sql"), referenced_schema)?;
+		let patch_path = temp_dir.path().join("schema.patch");
+		std::fs::write(&patch_path, &patch)?;
+		for args in [vec!["apply", "--check"], vec!["apply"]] {
+			let output = std::process::Command::new("git")
+				.args(args)
+				.arg(&patch_path)
+				.current_dir(temp_dir.path())
+				.output()?;
+			assert!(
+				output.status.success(),
+				"{}\n{}",
+				String::from_utf8_lossy(&output.stderr),
+				patch
+			);
+		}
+		assert_eq!(std::fs::read_to_string(temp_dir.path().join("schema.sql"))?, current_schema);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_region_follows_moved_content() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// Code is added above the region, then the file changes outside and inside of it
+		let fixture = include_str!("../tests/region/config.rs");
+		let moved_fixture = format!("use std::time::Duration;\n\n{}", fixture);
+		let outside_fixture = moved_fixture.replace("client/1.0", "client/2.0");
+		let inside_fixture = outside_fixture.replace("= 30;", "= 60;");
+		let referenced = commit_files(&repo, None, &[("config.rs", fixture.as_bytes())])?;
+		let commit =
+			|parent, content: &[u8]| commit_files(&repo, Some(parent), &[("config.rs", content)]);
+		let moved = commit(referenced, moved_fixture.as_bytes())?;
+		let outside = commit(moved, outside_fixture.as_bytes())?;
+		let inside = commit(outside, inside_fixture.as_bytes())?;
+		let removed = commit(inside, b"pub const A: u8 = 1;\n")?;
+
+		let path_pattern = PathPattern::try_new("config.rs")?.with_region("api-default")?;
+		let diff = |revision: git2::Oid| {
+			let referenced_content = referenced_at(&manager, &path_pattern, referenced);
+			current_at(&manager, &path_pattern, revision).diff(&referenced_content)
+		};
+
+		// Neither moving the region nor changing the file around it is drift
+		assert!(diff(moved)?.is_empty());
+		assert!(diff(outside)?.is_empty());
+
+		// Changing the region is, as is removing it
+		let changed = diff(inside)?;
+		assert!(!changed.is_empty());
+		assert!(changed.diff().contains("+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;"));
+		assert!(!changed.diff().contains("client/2.0"));
+		assert!(!diff(removed)?.is_empty());
+
+		// A region missing from the referenced revision cannot be cited
+		let missing = PathPattern::try_new("config.rs")?.with_region("missing")?;
+		let result = current_at(&manager, &missing, moved)
+			.diff(&referenced_at(&manager, &missing, referenced));
+		assert!(matches!(result, Err(SourceError::ContentParsing(_))));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_describe_change() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		let source = "pub fn retries() -> u32 {\n\t3\n}\n";
+		let referenced = commit_files(&repo, None, &[("retry.rs", source.as_bytes())])?;
+		let commit = |content: &str| {
+			commit_files(&repo, Some(referenced), &[("retry.rs", content.as_bytes())])
+		};
+		let padded = commit("pub fn retries() -> u32 {  \r\n\t3\r\n}\r\n\r\n")?;
+		let reformatted = commit("pub fn retries() -> u32 { 3 }\n")?;
+		let commented = commit("/// Retries of each request\npub fn retries() -> u32 {\n\t3\n}\n")?;
+		let changed = commit("pub fn retries() -> u32 {\n\t5\n}\n")?;
+
+		let path_pattern = PathPattern::try_new("retry.rs")?;
+		let referenced_content = referenced_at(&manager, &path_pattern, referenced);
+		let change = |revision: git2::Oid| -> Result<Option<ChangeClass>, anyhow::Error> {
+			let current_content = current_at(&manager, &path_pattern, revision);
+			Ok(current_content.diff(&referenced_content)?.describe_change())
+		};
+
+		assert_eq!(change(padded)?, Some(ChangeClass::Whitespace));
+		assert_eq!(change(reformatted)?, Some(ChangeClass::Code));
+		assert_eq!(change(commented)?, Some(ChangeClass::Comment));
+		assert_eq!(change(changed)?, Some(ChangeClass::Code));
+		assert_eq!(change(referenced)?, None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_offline_validation_uses_existing_clone() -> Result<(), anyhow::Error> {
+		let target_dir = tempfile::TempDir::new()?;
+		let remote = "https://example.com/fixture.git";
+		let source = |referenced: &str, current: &str| -> Result<GitSource, anyhow::Error> {
+			let source = GitSource::try_new(remote, "notes.txt", referenced, Some(current), None)?;
+			Ok(source.with_target_dir(target_dir.path().to_path_buf()).with_offline(true))
+		};
+
+		// Nothing is cloned yet, and offline nothing can be
+		let cold = source("main", "main")?;
+		assert!(!cold.supports_offline());
+		assert!(cold.get().is_err());
+
+		// Once cloned, the revisions in the clone are compared without fetching anything; the
+		// clone has no `origin` to fetch from
+		let repo = git2::Repository::init(target_dir.path().join("fixture"))?;
+		let referenced = commit_files(&repo, None, &[("notes.txt", b"one\n")])?;
+		let current = commit_files(&repo, Some(referenced), &[("notes.txt", b"one\ntwo\n")])?;
+		let warm = source(&referenced.to_string(), &current.to_string())?;
+		assert!(warm.supports_offline());
+		assert!(warm.get()?.diff().diff().contains("+two"));
+
+		// A revision missing from the clone cannot be validated offline
+		let missing = source(&referenced.to_string(), "0123456789abcdef0123456789abcdef01234567")?;
+		assert!(!missing.supports_offline());
+		Ok(())
+	}
+
+	#[test]
+	fn test_omitted_current_revision_is_default_branch() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+
+		// A local fixture remote whose default branch is trunk, with main left behind
+		let remote_path = temp_dir.path().join("fixture");
+		let remote = git2::Repository::init(&remote_path)?;
+		let referenced = commit_files(&remote, None, &[("notes.txt", b"one\n")])?;
+		let tip = commit_files(&remote, Some(referenced), &[("notes.txt", b"one\ntwo\n")])?;
+		remote.reference("refs/heads/main", referenced, true, "fixture")?;
+		remote.reference("refs/heads/trunk", tip, true, "fixture")?;
+		remote.set_head("refs/heads/trunk")?;
+
+		let source = GitSource::try_new(
+			&remote_path.to_string_lossy(),
+			"notes.txt",
+			&referenced.to_string(),
+			None,
+			None,
+		)?
+		.with_target_dir(temp_dir.path().join("clones"));
+		assert!(source.id.as_str().ends_with("_HEAD"));
+
+		let comparison = source.get()?;
+		assert_eq!(comparison.current().revision, "origin/trunk");
+		assert_eq!(comparison.current().commit, Some(tip.to_string()));
+		assert!(comparison.diff().diff().contains("+two"));
+
+		// The tip is followed as the branch moves on, by the next build
+		let moved = commit_files(&remote, Some(tip), &[("notes.txt", b"one\ntwo\nthree\n")])?;
+		remote.reference("refs/heads/trunk", moved, true, "fixture")?;
+		assert_eq!(source.get_current()?.commit, Some(tip.to_string()));
+		source.repository_builder.forget_fetched()?;
+		assert_eq!(source.get_current()?.commit, Some(moved.to_string()));
+
+		// Offline, the default branch recorded by the clone is used
+		let offline = source.clone().with_offline(true);
+		assert!(offline.supports_offline());
+		assert_eq!(offline.get_current()?.revision, "origin/trunk");
+
+		// Online, a change of the default branch is picked up from the remote
+		remote.set_head("refs/heads/main")?;
+		assert_eq!(source.get_current()?.revision, "origin/main");
+		assert!(source.get()?.is_same());
+		Ok(())
+	}
+
+	#[test]
+	fn test_tag_revisions() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+
+		// A local fixture remote with an annotated release tag and a lightweight one
+		let remote_path = temp_dir.path().join("fixture");
+		let remote = git2::Repository::init(&remote_path)?;
+		let released = commit_files(&remote, None, &[("notes.txt", b"one\n")])?;
+		let patched = commit_files(&remote, Some(released), &[("notes.txt", b"one\ntwo\n")])?;
+		remote.reference("refs/heads/main", patched, true, "fixture")?;
+		remote.set_head("refs/heads/main")?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		remote.tag("v1.2.0", &remote.find_object(released, None)?, &signature, "v1.2.0", false)?;
+		remote.tag_lightweight("v1.2.1", &remote.find_object(patched, None)?, false)?;
+
+		let source = |referenced: &str, current: &str| -> Result<GitSource, anyhow::Error> {
+			let source = GitSource::try_new(
+				&remote_path.to_string_lossy(),
+				"notes.txt",
+				referenced,
+				Some(current),
+				None,
+			)?;
+			Ok(source.with_target_dir(temp_dir.path().join("clones")))
+		};
+
+		let tags = source("v1.2.0", "v1.2.1")?;
+		assert!(tags.id.as_str().ends_with("_notes.txt_v1.2.0_v1.2.1"));
+		let comparison = tags.get()?;
+		assert_eq!(comparison.referenced().commit, Some(released.to_string()));
+		assert_eq!(comparison.current().commit, Some(patched.to_string()));
+		assert!(comparison.diff().diff().contains("+two"));
+
+		// A tag names the same content as its commit
+		assert!(source("v1.2.1", &patched.to_string())?.get()?.is_same());
+		Ok(())
+	}
+
+	#[test]
+	fn test_local_repository_is_used_in_place() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+
+		// A sibling working copy with main checked out
+		let local_path = temp_dir.path().join("monorepo");
+		let local = git2::Repository::init(&local_path)?;
+		let referenced = commit_files(&local, None, &[("notes.txt", b"one\n")])?;
+		let tip = commit_files(&local, Some(referenced), &[("notes.txt", b"one\ntwo\n")])?;
+		local.reference("refs/heads/main", tip, true, "fixture")?;
+		local.set_head("refs/heads/main")?;
+
+		let clones = temp_dir.path().join("clones");
+		let remote = format!("file://{}", local_path.display());
+		let source = GitSource::try_new(&remote, "notes.txt", &referenced.to_string(), None, None)?
+			.with_target_dir(clones.clone());
+		assert_eq!(source.repository_path()?, local_path);
+		assert!(source.supports_offline());
+
+		// Revisions resolve against the objects of the local repository, and nothing is cloned
+		let comparison = source.get()?;
+		assert_eq!(comparison.current().revision, "main");
+		assert_eq!(comparison.current().commit, Some(tip.to_string()));
+		assert!(comparison.diff().diff().contains("+two"));
+		assert!(!clones.exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_expected_diff() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+
+		// A local fixture remote where generated code was bumped to a new version
+		let remote_path = temp_dir.path().join("fixture");
+		let remote = git2::Repository::init(&remote_path)?;
+		let before = commit_files(
+			&remote,
+			None,
+			&[("generated.rs", b"pub mod api;\npub const VERSION: u32 = 1;\n")],
+		)?;
+		let after = commit_files(
+			&remote,
+			Some(before),
+			&[("generated.rs", b"pub mod api;\npub const VERSION: u32 = 2;\n")],
+		)?;
+		let source = |current: git2::Oid, expect_diff: &str| -> Result<GitSource, anyhow::Error> {
+			let source = GitSource::try_new(
+				&remote_path.to_string_lossy(),
+				"generated.rs",
+				&before.to_string(),
+				Some(&current.to_string()),
+				None,
+			)?;
+			Ok(source
+				.with_target_dir(temp_dir.path().join("clones"))
+				.with_expect_diff(expect_diff))
+		};
+
+		// The change matches regardless of headers, context and whitespace
+		let expected = "@@ -1,2 +1,2 @@\n pub mod api;\n-pub const VERSION: u32 = 1;\n+pub const  VERSION: u32 = 2;\n";
+		assert!(source(after, expected)?.get()?.is_same());
+
+		// A different change drifts
+		let mismatching = "-pub const VERSION: u32 = 1;\n+pub const VERSION: u32 = 3;\n";
+		assert!(!source(after, mismatching)?.get()?.is_same());
+
+		// As does the change being absent
+		assert!(!source(before, expected)?.get()?.is_same());
+		Ok(())
+	}
+
+	#[test]
+	fn test_ancestry_revision_diff() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		let first = commit_files(&repo, None, &[("notes.txt", b"one\n")])?;
+		let second = commit_files(&repo, Some(first), &[("notes.txt", b"one\ntwo\n")])?;
+		let third = commit_files(&repo, Some(second), &[("notes.txt", b"one\ntwo\n")])?;
+		repo.reference("refs/heads/main", third, true, "fixture")?;
+
+		let path_pattern = PathPattern::try_new("notes.txt")?;
+		let referenced_content = ReferencedGitContent {
+			commit: Some(manager.resolve_revision("main~2")?),
+			..referenced_at(&manager, &path_pattern, "main~2")
+		};
+		let current_content = CurrentGitContent {
+			commit: Some(manager.resolve_revision("main")?),
+			..current_at(&manager, &path_pattern, "main")
+		};
+
+		// The revision is reported with the commit it resolved to
+		assert_eq!(referenced_content.describe_revision(), format!("main~2 ({})", first));
+		let diff = current_content.diff(&referenced_content)?;
+		assert!(diff.diff().contains("+two"));
+
+		// A concrete SHA is reported as is
+		let pinned = ReferencedGitContent {
+			revision: second.to_string(),
+			commit: Some(second.to_string()),
+			..referenced_content
+		};
+		assert_eq!(pinned.describe_revision(), second.to_string());
+		assert!(current_content.diff(&pinned)?.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_glob_diff_summarizes_files() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		let referenced = commit_files(
+			&repo,
+			None,
+			&[
+				("api.rs", b"fn get() {}\n"),
+				("cli.rs", b"fn main() {}\nfn run() {}\n"),
+				("notes.txt", b"one\n"),
+			],
+		)?;
+		let current = commit_files(
+			&repo,
+			Some(referenced),
+			&[
+				("api.rs", b"fn get() {}\nfn put() {}\n"),
+				("cli.rs", b"fn main() {}\n"),
+				("notes.txt", b"two\n"),
+			],
+		)?;
+
+		let path_pattern = PathPattern::try_new("*.rs")?;
+		let referenced_content = ReferencedGitContent {
+			commit: Some(referenced.to_string()),
+			..referenced_at(&manager, &path_pattern, referenced)
+		};
+		let current_content = CurrentGitContent {
+			commit: Some(current.to_string()),
+			..current_at(&manager, &path_pattern, current)
+		};
+
+		let diff = current_content.diff(&referenced_content)?;
+		assert_eq!(
+			diff.files(),
+			[
+				FileChange { path: "api.rs".to_string(), added: 1, removed: 0 },
+				FileChange { path: "cli.rs".to_string(), added: 0, removed: 1 },
+			]
+		);
+
+		// The hunks of each file follow its header
+		let unified = diff.unified_diff().unwrap_or_default();
+		let api = unified.find("diff --git a/api.rs b/api.rs\n");
+		let cli = unified.find("diff --git a/cli.rs b/cli.rs\n");
+		assert!(api.is_some_and(|api| cli.is_some_and(|cli| api < cli)), "{}", unified);
+		assert!(unified[cli.unwrap_or_default()..].contains("\n fn main() {}\n-fn run() {}\n"));
+		assert!(!unified.contains("notes.txt"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_merge_base_diff() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// A feature branch diverges from main, which then moves on
+		let fork = commit_files(&repo, None, &[("notes.txt", b"one\ntwo\n")])?;
+		let main = commit_files(&repo, Some(fork), &[("notes.txt", b"one\ntwo\nfrom main\n")])?;
+		let feature =
+			commit_files(&repo, Some(fork), &[("notes.txt", b"one\ntwo\nfrom feature\n")])?;
+		repo.reference("refs/heads/main", main, true, "fixture")?;
+		repo.reference("refs/heads/feature", feature, true, "fixture")?;
+
+		let path_pattern = PathPattern::try_new("notes.txt")?;
+		let referenced_content = ReferencedGitContent {
+			commit: Some(main.to_string()),
+			..referenced_at(&manager, &path_pattern, "main")
+		};
+		let current_content = |diff_base: DiffBase| CurrentGitContent {
+			commit: Some(feature.to_string()),
+			diff_base,
+			..current_at(&manager, &path_pattern, "feature")
+		};
+		assert_eq!(manager.merge_base("main", "feature")?, fork.to_string());
+
+		// The direct diff also undoes what main added since the fork
+		let direct = current_content(DiffBase::Referenced).diff(&referenced_content)?;
+		assert!(direct.diff().contains("-from main"));
+		assert!(direct.diff().contains("+from feature"));
+
+		// The merge-base diff only shows what the feature branch changed
+		let merge_base = current_content(DiffBase::from_str("merge-base:main").unwrap())
+			.diff(&referenced_content)?;
+		assert_ne!(merge_base.diff(), direct.diff());
+		assert!(!merge_base.diff().contains("from main"));
+		assert!(merge_base.diff().contains("+from feature"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_n_way_revision_diff() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let repo = git2::Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// A fix is backported to one release branch but not to the other
+		let base = commit_files(&repo, None, &[("compat.rs", b"fn compat() {}\n")])?;
+		let fixed = b"fn compat() { fixed() }\n";
+		let main = commit_files(&repo, Some(base), &[("compat.rs", fixed)])?;
+		let backported = commit_files(
+			&repo,
+			Some(base),
+			&[("compat.rs", fixed), ("CHANGELOG.md", b"backported\n")],
+		)?;
+		repo.reference("refs/heads/main", main, true, "fixture")?;
+		repo.reference("refs/heads/release/2.x", backported, true, "fixture")?;
+		repo.reference("refs/heads/release/1.x", base, true, "fixture")?;
+
+		let path_pattern = PathPattern::try_new("compat.rs")?;
+		let referenced_content = ReferencedGitContent {
+			commit: Some(main.to_string()),
+			..referenced_at(&manager, &path_pattern, "main")
+		};
+		let current_content = |revisions: &[&str]| CurrentGitContent {
+			commit: Some(backported.to_string()),
+			revisions: revisions.iter().map(|revision| (*revision).to_string()).collect(),
+			..current_at(&manager, &path_pattern, "release/2.x")
+		};
+
+		// The cited path matches across revisions, even though other files differ
+		assert!(current_content(&["main"]).diff(&referenced_content)?.is_empty());
+
+		// Only the revision that diverges is reported
+		let diff = current_content(&["release/1.x", "main"]).diff(&referenced_content)?;
+		assert!(!diff.is_empty());
+		let header = format!("Revision release/1.x ({}) diverges from main", base);
+		assert!(diff.diff().starts_with(&header));
+		assert!(!diff.diff().contains("Revision release/2.x"));
+		assert!(diff.diff().contains("-fn compat() { fixed() }"));
+		assert!(diff.diff().contains("+fn compat() {}"));
+
+		Ok(())
+	}
+
 	#[test]
 	fn test_invalid_path_patterns() {
 		// Test invalid line ranges
@@ -646,10 +2110,34 @@ mod tests {
 
 	#[test]
 	fn test_git_source_id_generation() -> Result<(), anyhow::Error> {
-		let source1 = GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
-		let source2 = GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
-		let source3 = GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "b156c85e9734b8628a7d1b8d03cbd99205b99ff9", None)?;
-		let source4 = GitSource::try_new("https://github.com/ramate-io/cite", "src/lib.rs", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
+		let source1 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
+		let source2 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
+		let source3 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("b156c85e9734b8628a7d1b8d03cbd99205b99ff9"),
+			None,
+		)?;
+		let source4 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"src/lib.rs",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
 
 		// Same remote, path, and revision should generate same ID
 		assert_eq!(source1.id, source2.id);
@@ -661,16 +2149,84 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_git_source_cites_release_tag() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+
+		// A local fixture remote released as v0.1.0, with main moved on since
+		let remote_path = temp_dir.path().join("fixture");
+		let remote = git2::Repository::init(&remote_path)?;
+		let released = commit_files(&remote, None, &[("README.md", b"# cite\n")])?;
+		let tip = commit_files(&remote, Some(released), &[("README.md", b"# cite\n\nMore.\n")])?;
+		remote.reference("refs/heads/main", tip, true, "fixture")?;
+		remote.set_head("refs/heads/main")?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		remote.tag("v0.1.0", &remote.find_object(released, None)?, &signature, "v0.1.0", false)?;
+
+		let source = GitSource::try_new(
+			&remote_path.to_string_lossy(),
+			"README.md",
+			"v0.1.0",
+			Some("v0.1.0"),
+			None,
+		)?
+		.with_target_dir(temp_dir.path().join("clones"));
+		assert!(source.id.as_str().ends_with("_README.md_v0.1.0_v0.1.0"));
+
+		// The tag resolves to the commit it was released at, on both sides
+		let comparison = source.get()?;
+		assert_eq!(comparison.referenced().commit, Some(released.to_string()));
+		assert_eq!(comparison.current().commit, Some(released.to_string()));
+		assert!(comparison.is_same());
+		Ok(())
+	}
+
+	#[test]
+	fn test_resolved_link_follows_rename() -> Result<(), anyhow::Error> {
+		let source = GitSource::try_new(
+			"https://github.com/old-owner/old-name.git",
+			"src/lib.rs",
+			"abc123",
+			Some("main"),
+			None,
+		)?;
+		let renamed = |owner: &str, repo: &str| {
+			(owner == "old-owner" && repo == "old-name").then(|| "new-owner/new-name".to_string())
+		};
+
+		assert_eq!(
+			source.resolved_link(&renamed),
+			"https://github.com/new-owner/new-name/blob/abc123/src/lib.rs"
+		);
+
+		// Without a resolution the link of the remote is kept
+		let offline = |_: &str, _: &str| None;
+		assert_eq!(source.resolved_link(&offline), source.link());
+		Ok(())
+	}
+
 	#[test]
 	fn test_real_git_diff_with_line_ranges() -> Result<(), anyhow::Error> {
 		// This test requires a git repository with the specified commit
 		// We'll use the commit mentioned in the user's requirements
 		// Try to create a git source for README.md with line range
-		let source = GitSource::try_new("https://github.com/ramate-io/cite", "README.md#L1-L5", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
+		let source = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md#L1-L5",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
 		let content = source.get_referenced()?;
 
 		// Create another content with a different line range
-		let source2 = GitSource::try_new("https://github.com/ramate-io/cite", "README.md#L10-L15", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
+		let source2 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md#L10-L15",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
 		let _content2 = source2.get_referenced()?;
 
 		// The diff should work (even if there are no changes, it should not panic)
@@ -687,11 +2243,23 @@ mod tests {
 		// Test that line range filtering works correctly
 
 		// Create content with full file (no line range)
-		let source_full = GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
+		let source_full = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
 		let content_full = source_full.get_referenced()?;
 
 		// Create content with limited line range
-		let source_limited = GitSource::try_new("https://github.com/ramate-io/cite", "README.md#L1-L3", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "main", None)?;
+		let source_limited = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"README.md#L1-L3",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("main"),
+			None,
+		)?;
 		let content_limited = source_limited.get_referenced()?;
 
 		// Both should work without panicking
@@ -709,48 +2277,93 @@ mod tests {
 		// Test various line range intersection scenarios with the test commit
 
 		// Test 1: Lines 1-3 (covering the beginning)
-		let source_1_3 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L1-L3", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_1_3 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L1-L3",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_1_3 = source_1_3.get_referenced()?;
 
 		// Test 2: Lines 5-10 (covering the middle to end)
-		let source_5_10 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-5-10.md#L5-L10", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_5_10 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-5-10.md#L5-L10",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_5_10 = source_5_10.get_referenced()?;
 
 		// Test 3: Lines 4-6 (intersecting with both ranges)
-		let source_4_6 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L4-L6", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_4_6 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L4-L6",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_4_6 = source_4_6.get_referenced()?;
 
 		// Test 4: Lines 8-12 (partially intersecting, extending beyond file)
-		let source_8_12 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L8-L12", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_8_12 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L8-L12",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_8_12 = source_8_12.get_referenced()?;
 
 		// Test 5: Lines 11-15 (not intersecting with file content)
-		let source_11_15 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L11-L15", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_11_15 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L11-L15",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_11_15 = source_11_15.get_referenced()?;
 
 		// Test 6: Single line (line 5)
-		let source_line_5 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L5", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_line_5 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L5",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_line_5 = source_line_5.get_referenced()?;
 
 		// Test 7: Full file (no line range)
-		let source_full =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_full = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let _content_full = source_full.get_referenced()?;
 
 		// Test 8: File with no changes
-		let source_no_diff =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/no-diffed.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_no_diff = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/no-diffed.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_no_diff = source_no_diff.get_referenced()?;
 
 		// Test 9: File that will be deleted
-		let source_to_delete =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/to-delete.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_to_delete = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/to-delete.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_to_delete = source_to_delete.get_referenced()?;
 
 		// Run diffs to test line intersection logic
@@ -773,28 +2386,53 @@ mod tests {
 	fn test_line_range_edge_cases_with_real_files() -> Result<(), anyhow::Error> {
 		// Test edge cases for line range filtering with real files
 		// Test 1: Line range exactly matching file boundaries
-		let source_exact =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L1-L10", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_exact = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L1-L10",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_exact = source_exact.get_referenced()?;
 
 		// Test 2: Line range starting at 1, ending before file end
-		let source_start_1 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L1-L5", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_start_1 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L1-L5",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_start_1 = source_start_1.get_referenced()?;
 
 		// Test 3: Line range starting after file start, ending at file end
-		let source_end_file =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L5-L10", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_end_file = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L5-L10",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_end_file = source_end_file.get_referenced()?;
 
 		// Test 4: Line range completely outside file (after)
-		let source_after =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L15-L20", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_after = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L15-L20",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_after = source_after.get_referenced()?;
 
 		// Test 5: Single line at file boundary
-		let source_boundary =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L10", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_boundary = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L10",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let content_boundary = source_boundary.get_referenced()?;
 
 		// Run diffs to test edge case handling
@@ -814,22 +2452,37 @@ mod tests {
 		// Test that diff content is actually filtered by line ranges
 
 		// Create different line range sources
-		let source_1_3 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L1-L3", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_1_3 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L1-L3",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let comparison_1_3 = source_1_3.get()?;
-        assert!(comparison_1_3.diff().has_changes());
-        assert_eq!(comparison_1_3.diff().diff(), "-Alpha\n-Bravo\n-Charlie\n+Aaron\n+Bear\n+Cat\n");
+		assert!(comparison_1_3.diff().has_changes());
+		assert_eq!(comparison_1_3.diff().diff(), "-Alpha\n-Bravo\n-Charlie\n+Aaron\n+Bear\n+Cat\n");
 
-        let source_5_10 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-5-10.md#L5-L10", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
-        let comparison_5_10 = source_5_10.get()?;
-        assert!(comparison_5_10.diff().has_changes());
-        assert_eq!(comparison_5_10.diff().diff(), "-Echo\n-Foxtrot\n-Gamma\n-Halifax\n-Istanbul\n-Juniper>\n\\ No newline at end of file\n+Epsom\n+Fox\n+Golf\n+Hotel\n+India\n+Juliet<\n\\ No newline at end of file\n");
+		let source_5_10 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-5-10.md#L5-L10",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
+		let comparison_5_10 = source_5_10.get()?;
+		assert!(comparison_5_10.diff().has_changes());
+		assert_eq!(comparison_5_10.diff().diff(), "-Echo\n-Foxtrot\n-Gamma\n-Halifax\n-Istanbul\n-Juniper>\n\\ No newline at end of file\n+Epsom\n+Fox\n+Golf\n+Hotel\n+India\n+Juliet<\n\\ No newline at end of file\n");
 
-		let source_full =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/no-diffed.md", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_full = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/no-diffed.md",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let comparison_full = source_full.get()?;
-        assert!(!comparison_full.diff().has_changes());
+		assert!(!comparison_full.diff().has_changes());
 
 		Ok(())
 	}
@@ -838,10 +2491,15 @@ mod tests {
 	fn test_diff_content_verification_edge_cases() -> Result<(), anyhow::Error> {
 		// Test that diff content is actually filtered by line ranges
 
-		let source_intersects_1_3 =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L3-L5", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_intersects_1_3 = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L3-L5",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let comparison_intersects_1_3 = source_intersects_1_3.get()?;
-        assert!(comparison_intersects_1_3.diff().has_changes());
+		assert!(comparison_intersects_1_3.diff().has_changes());
 		assert_eq!(comparison_intersects_1_3.diff().diff(), "-Charlie\n+Cat\n Delta\n Echo\n");
 
 		Ok(())
@@ -849,13 +2507,16 @@ mod tests {
 
 	#[test]
 	fn test_diff_does_not_intersect() -> Result<(), anyhow::Error> {
-		let source_does_not_intersect =
-			GitSource::try_new("https://github.com/ramate-io/cite", "cite/http/tests/content/diffed-lines-1-3.md#L7-L10", "94dab273cf6c2abe8742d6d459ad45c96ca9b694", "2bcceb14934dbe0803ddb70bc8952a0c33f931e2", None)?;
+		let source_does_not_intersect = GitSource::try_new(
+			"https://github.com/ramate-io/cite",
+			"cite/http/tests/content/diffed-lines-1-3.md#L7-L10",
+			"94dab273cf6c2abe8742d6d459ad45c96ca9b694",
+			Some("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"),
+			None,
+		)?;
 		let comparison_does_not_intersect = source_does_not_intersect.get()?;
 		assert!(!comparison_does_not_intersect.diff().has_changes());
 
 		Ok(())
 	}
-
-	
 }
@@ -0,0 +1,120 @@
+//! Named regions of cited files
+//!
+//! A region is marked with sentinel comments, in whatever comment syntax the file uses:
+//!
+//! ```text
+//! // cite:begin(api-default)
+//! pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+//! // cite:end(api-default)
+//! ```
+//!
+//! Citing the region by name rather than by line range keeps the citation on the same content
+//! when lines are added or removed elsewhere in the file. Only the lines between the sentinels
+//! are compared.
+
+use crate::GitSourceError;
+
+/// Check that a region name can be used in sentinel comments
+pub fn validate_name(name: &str) -> Result<(), GitSourceError> {
+	let valid = !name.is_empty()
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+	if valid {
+		Ok(())
+	} else {
+		Err(GitSourceError::InvalidPathPattern(format!(
+			"Invalid region name: '{}'. Names use letters, digits, '-', '_' and '.'",
+			name
+		)))
+	}
+}
+
+/// Extract the lines of the named region, between its `cite:begin` and `cite:end` sentinels
+///
+/// Returns `None` if the content has no such region. A region that is not closed, or is marked
+/// more than once, is an error.
+pub fn extract(content: &str, name: &str) -> Result<Option<String>, GitSourceError> {
+	let begin = format!("cite:begin({})", name);
+	let end = format!("cite:end({})", name);
+
+	let mut region: Option<String> = None;
+	let mut closed = false;
+	for line in content.split_inclusive('\n') {
+		if line.contains(&begin) {
+			if region.is_some() {
+				return Err(GitSourceError::InvalidPathPattern(format!(
+					"Region {} is marked more than once",
+					name
+				)));
+			}
+			region = Some(String::new());
+		} else if line.contains(&end) {
+			if region.is_none() || closed {
+				return Err(GitSourceError::InvalidPathPattern(format!(
+					"Region {} ends before it begins",
+					name
+				)));
+			}
+			closed = true;
+		} else if let Some(region) = region.as_mut().filter(|_| !closed) {
+			region.push_str(line);
+		}
+	}
+
+	match (region, closed) {
+		(Some(_), false) => Err(GitSourceError::InvalidPathPattern(format!(
+			"Region {} is never closed with {}",
+			name, end
+		))),
+		(region, _) => Ok(region),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FIXTURE: &str = include_str!("../tests/region/config.rs");
+
+	#[test]
+	fn test_extract() -> Result<(), GitSourceError> {
+		assert_eq!(
+			extract(FIXTURE, "api-default")?.as_deref(),
+			Some(
+				"pub const DEFAULT_TIMEOUT_SECS: u64 = 30;\npub const DEFAULT_RETRIES: u32 = 3;\n"
+			)
+		);
+		assert!(extract(FIXTURE, "user-agent")?.is_some_and(|region| region.contains("client/1.0")));
+		assert_eq!(extract(FIXTURE, "missing")?, None);
+
+		// Any comment syntax marks a region
+		let shell = "# cite:begin(install)\ncargo install cite\n# cite:end(install)\n";
+		assert_eq!(extract(shell, "install")?.as_deref(), Some("cargo install cite\n"));
+		let html = "<!-- cite:begin(intro) -->\n<p>Hello</p>\n<!-- cite:end(intro) -->";
+		assert_eq!(extract(html, "intro")?.as_deref(), Some("<p>Hello</p>\n"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_extract_malformed_regions() {
+		let unclosed = "// cite:begin(api)\nconst A: u8 = 1;\n";
+		assert!(extract(unclosed, "api").is_err());
+
+		let twice = "// cite:begin(api)\n// cite:end(api)\n// cite:begin(api)\n// cite:end(api)\n";
+		assert!(extract(twice, "api").is_err());
+
+		let reversed = "// cite:end(api)\n// cite:begin(api)\n";
+		assert!(extract(reversed, "api").is_err());
+
+		// A region name that prefixes another is a different region
+		let other = "// cite:begin(api-v2)\nconst A: u8 = 1;\n// cite:end(api-v2)\n";
+		assert!(matches!(extract(other, "api"), Ok(None)));
+	}
+
+	#[test]
+	fn test_validate_name() {
+		assert!(validate_name("api-default").is_ok());
+		assert!(validate_name("v1.2_config").is_ok());
+		assert!(validate_name("").is_err());
+		assert!(validate_name("api)").is_err());
+	}
+}
@@ -0,0 +1,269 @@
+use cite_cache::{Cache, CacheBehavior, CacheError, CacheableCurrent, CacheableReferenced};
+use cite_core::id::Id;
+use cite_core::{Content, Current, Diff, Referenced, SourceError};
+use std::time::Duration;
+
+/// Environment variable enabling the resolution of renamed GitHub repositories in links
+pub const RESOLVE_RENAMES_ENV: &str = "CITE_RESOLVE_RENAMES";
+
+/// How long a resolved repository name is cached before the repository is looked up again
+pub const RESOLVED_NAME_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Base URL of GitHub repository links
+const GITHUB_URL: &str = "https://github.com";
+
+/// Resolves the current name of a repository that may have been renamed or transferred
+///
+/// Resolution is best-effort: a resolver that cannot tell, e.g. because it is offline, returns
+/// `None` and the link keeps the name of the remote.
+pub trait RepoResolver {
+	/// Get the current `owner/repo` of a repository, if known
+	fn resolve(&self, owner: &str, repo: &str) -> Option<String>;
+}
+
+impl<F> RepoResolver for F
+where
+	F: Fn(&str, &str) -> Option<String>,
+{
+	fn resolve(&self, owner: &str, repo: &str) -> Option<String> {
+		self(owner, repo)
+	}
+}
+
+/// Resolves repository names with the GitHub API, which follows renames and transfers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GithubApiResolver;
+
+impl GithubApiResolver {
+	/// Check if resolution is enabled by the environment
+	pub fn is_enabled() -> bool {
+		std::env::var(RESOLVE_RENAMES_ENV)
+			.is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+	}
+}
+
+impl RepoResolver for GithubApiResolver {
+	fn resolve(&self, owner: &str, repo: &str) -> Option<String> {
+		let client = reqwest::blocking::Client::builder()
+			.timeout(Duration::from_secs(5))
+			.user_agent("cite-git/1.0")
+			.build()
+			.ok()?;
+
+		let response = client
+			.get(format!("https://api.github.com/repos/{}/{}", owner, repo))
+			.header("Accept", "application/vnd.github+json")
+			.send()
+			.ok()?;
+		if !response.status().is_success() {
+			return None;
+		}
+
+		let body: serde_json::Value = response.json().ok()?;
+		body.get("full_name").and_then(|name| name.as_str()).map(str::to_string)
+	}
+}
+
+/// Caches the names another resolver resolves, like the references of http citations
+///
+/// Each repository is then looked up once per [RESOLVED_NAME_TTL] rather than on every expansion
+/// of every citation of it. Repositories the resolver cannot tell about are not cached, so they
+/// are looked up again, e.g. once back online.
+#[derive(Debug, Clone)]
+pub struct CachedResolver<R> {
+	resolver: R,
+	cache: Cache,
+	ttl: Duration,
+}
+
+impl<R: RepoResolver> CachedResolver<R> {
+	/// Cache the names the resolver resolves for [RESOLVED_NAME_TTL]
+	pub fn new(resolver: R, cache: Cache) -> Self {
+		Self { resolver, cache, ttl: RESOLVED_NAME_TTL }
+	}
+
+	/// Set how long resolved names are cached
+	pub fn with_ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = ttl;
+		self
+	}
+}
+
+impl<R: RepoResolver> RepoResolver for CachedResolver<R> {
+	fn resolve(&self, owner: &str, repo: &str) -> Option<String> {
+		let id = Id::new(format!("github_repo_{}_{}", owner, repo));
+		let behavior = CacheBehavior::Ttl(self.ttl);
+		if self.cache.is_usable(&id, &behavior).unwrap_or(false) {
+			if let Ok(Some(ResolvedRepo(name))) = self.cache.get_memoized::<ResolvedRepo>(&id) {
+				return Some(name);
+			}
+		}
+
+		let name = self.resolver.resolve(owner, repo)?;
+		// Caching is best effort; the name is resolved either way
+		let _ = self.cache.set_with_behavior(&id, &ResolvedRepo(name.clone()), &behavior);
+		Some(name)
+	}
+}
+
+/// The current `owner/repo` of a repository, as cached by [CachedResolver]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRepo(pub String);
+
+/// Whether a repository was renamed since its name was cached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoRename {
+	pub renamed: bool,
+}
+
+impl Diff for RepoRename {
+	fn is_empty(&self) -> bool {
+		!self.renamed
+	}
+}
+
+impl Content for ResolvedRepo {}
+
+impl Referenced for ResolvedRepo {}
+
+impl Current<ResolvedRepo, RepoRename> for ResolvedRepo {
+	fn diff(&self, other: &ResolvedRepo) -> Result<RepoRename, SourceError> {
+		Ok(RepoRename { renamed: self != other })
+	}
+}
+
+impl CacheableReferenced for ResolvedRepo {
+	fn from_cached_buffer(buffer: Vec<u8>) -> Result<Self, CacheError> {
+		String::from_utf8(buffer)
+			.map(Self)
+			.map_err(|e| CacheError::Deserialize(e.into()))
+	}
+}
+
+impl CacheableCurrent<ResolvedRepo, RepoRename> for ResolvedRepo {
+	fn to_cached_buffer(&self) -> Result<Vec<u8>, CacheError> {
+		Ok(self.0.clone().into_bytes())
+	}
+}
+
+/// Get the owner and name of a GitHub repository from its remote URL
+///
+/// Supports `https://github.com/owner/repo(.git)` and `git@github.com:owner/repo(.git)`.
+pub fn github_repo(remote: &str) -> Option<(&str, &str)> {
+	let path = remote
+		.strip_prefix("https://github.com/")
+		.or_else(|| remote.strip_prefix("http://github.com/"))
+		.or_else(|| remote.strip_prefix("git@github.com:"))?;
+	let path = path.trim_end_matches('/');
+	let path = path.strip_suffix(".git").unwrap_or(path);
+
+	match path.split_once('/') {
+		Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+			Some((owner, repo))
+		}
+		_ => None,
+	}
+}
+
+/// Rewrite a GitHub link of a remote to the current name of its repository
+///
+/// Links that are not GitHub links, and repositories the resolver does not know or that kept
+/// their name, are returned as is.
+pub fn resolve_link(link: &str, remote: &str, resolver: &dyn RepoResolver) -> String {
+	let Some((owner, repo)) = github_repo(remote) else {
+		return link.to_string();
+	};
+	let Some(resolved) = resolver.resolve(owner, repo) else {
+		return link.to_string();
+	};
+
+	let old_prefix = format!("{}/{}/{}", GITHUB_URL, owner, repo);
+	match link.strip_prefix(&old_prefix) {
+		Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+			format!("{}/{}{}", GITHUB_URL, resolved, rest)
+		}
+		_ => link.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn renamed(owner: &str, repo: &str) -> Option<String> {
+		match (owner, repo) {
+			("old-owner", "old-name") => Some("new-owner/new-name".to_string()),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn test_github_repo() {
+		assert_eq!(
+			github_repo("https://github.com/ramate-io/cite.git"),
+			Some(("ramate-io", "cite"))
+		);
+		assert_eq!(github_repo("git@github.com:ramate-io/cite.git"), Some(("ramate-io", "cite")));
+		assert_eq!(github_repo("https://github.com/ramate-io/cite/"), Some(("ramate-io", "cite")));
+		assert_eq!(github_repo("https://gitlab.com/ramate-io/cite.git"), None);
+		assert_eq!(github_repo("https://github.com/ramate-io"), None);
+	}
+
+	#[test]
+	fn test_resolve_link_to_renamed_repo() {
+		let remote = "https://github.com/old-owner/old-name.git";
+		let link = "https://github.com/old-owner/old-name/blob/abc123/src/lib.rs";
+
+		assert_eq!(
+			resolve_link(link, remote, &renamed),
+			"https://github.com/new-owner/new-name/blob/abc123/src/lib.rs"
+		);
+
+		// Unknown repositories and links elsewhere keep their name
+		let other = "https://github.com/old-owner/old-name-fork/blob/abc123/src/lib.rs";
+		assert_eq!(resolve_link(other, remote, &renamed), other);
+		let unknown = "https://github.com/someone/else/tree/main/docs";
+		assert_eq!(resolve_link(unknown, "https://github.com/someone/else", &renamed), unknown);
+	}
+
+	#[test]
+	fn test_cached_resolver_looks_up_once() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(temp_dir.path().to_path_buf(), "cache".into()).build()?;
+		let lookups = std::cell::Cell::new(0);
+		let counting = |owner: &str, repo: &str| {
+			lookups.set(lookups.get() + 1);
+			renamed(owner, repo)
+		};
+
+		let resolver = CachedResolver::new(&counting, cache.clone());
+		for _ in 0..3 {
+			assert_eq!(
+				resolver.resolve("old-owner", "old-name").as_deref(),
+				Some("new-owner/new-name")
+			);
+		}
+		assert_eq!(lookups.get(), 1);
+
+		// Unresolved repositories are looked up again
+		assert_eq!(resolver.resolve("someone", "else"), None);
+		assert_eq!(resolver.resolve("someone", "else"), None);
+		assert_eq!(lookups.get(), 3);
+
+		// Once expired, the name is looked up again
+		std::thread::sleep(Duration::from_millis(2));
+		let expired = CachedResolver::new(&counting, cache).with_ttl(Duration::ZERO);
+		assert_eq!(expired.resolve("old-owner", "old-name").as_deref(), Some("new-owner/new-name"));
+		assert_eq!(lookups.get(), 4);
+		Ok(())
+	}
+
+	#[test]
+	fn test_resolve_link_offline() {
+		// A resolver that cannot reach the network leaves the link as is
+		let offline = |_: &str, _: &str| None;
+		let link = "https://github.com/old-owner/old-name/tree/main/docs";
+		assert_eq!(resolve_link(link, "git@github.com:old-owner/old-name.git", &offline), link);
+	}
+}
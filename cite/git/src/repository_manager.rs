@@ -1,87 +1,328 @@
+use crate::credentials::GitCredentials;
+use crate::retry::RetryPolicy;
 use crate::GitSourceError;
-use git2::{FetchOptions, RemoteCallbacks, Repository};
-use std::path::{Path, PathBuf};
+use git2::{FetchOptions, Repository};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Environment variable overriding the directory git repositories are cloned into
+pub const GIT_DIR_ENV: &str = "CITE_GIT_DIR";
+
+/// How long a fetched repository is reused before it is fetched again
+///
+/// Covers a build, while processes outliving builds, such as the proc macro server of an editor,
+/// still see the remote move on.
+const FETCHED_TTL: Duration = Duration::from_secs(300);
+
+/// A repository fetched by this process with when it was fetched, empty until its first fetch
+/// completes
+type FetchedRepository = Arc<Mutex<Option<(RepositoryManager, Instant)>>>;
+
+/// Get the repository fetched by this process for a normalized remote URL and clone path
+///
+/// Citations of the same remote share one clone and one fetch per build, see [FETCHED_TTL].
+/// Each repository has its own lock, held while it is cloned or fetched, so that parallel
+/// expansions of citations wait for the fetch of their remote without holding up those of other
+/// remotes.
+fn fetched_repository(remote_url: String, repo_path: PathBuf) -> FetchedRepository {
+	static FETCHED: OnceLock<Mutex<HashMap<(String, PathBuf), FetchedRepository>>> =
+		OnceLock::new();
+	let mut fetched = FETCHED
+		.get_or_init(Default::default)
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner);
+	fetched.entry((remote_url, repo_path)).or_default().clone()
+}
 
 /// Builder for fetching and preparing git repositories
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RepositoryBuilder {
 	remote_url: String,
 	parent_dir: Option<PathBuf>,
+	#[serde(default = "RetryPolicy::from_env")]
+	retry: RetryPolicy,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	offline: bool,
+	#[serde(default, skip_serializing_if = "GitCredentials::is_default")]
+	credentials: GitCredentials,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	depth: Option<u32>,
 }
 
 impl Default for RepositoryBuilder {
 	fn default() -> Self {
-		Self { remote_url: String::new(), parent_dir: None }
+		Self::new(String::new())
 	}
 }
 
 impl RepositoryBuilder {
 	/// Create a new repository builder for the given remote URL
 	pub fn new(remote_url: String) -> Self {
-		Self { remote_url, parent_dir: None }
+		Self {
+			remote_url,
+			parent_dir: None,
+			retry: RetryPolicy::from_env(),
+			offline: false,
+			credentials: GitCredentials::default(),
+			depth: None,
+		}
 	}
 
 	/// Create a new repository builder with a custom parent directory
 	pub fn with_parent_dir(remote_url: String, parent_dir: PathBuf) -> Self {
-		Self { remote_url, parent_dir: Some(parent_dir) }
+		Self::new(remote_url).with_target_dir(parent_dir)
+	}
+
+	/// Clone into the given directory instead of the default
+	///
+	/// Takes precedence over `CITE_GIT_DIR`, so clones can be shared across a workspace.
+	pub fn with_target_dir(mut self, target_dir: PathBuf) -> Self {
+		self.parent_dir = Some(target_dir);
+		self
+	}
+
+	/// Set the retry policy for clone and fetch operations
+	pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+		self.retry = retry;
+		self
+	}
+
+	/// Get the retry policy for clone and fetch operations
+	pub fn retry_policy(&self) -> &RetryPolicy {
+		&self.retry
 	}
 
-	/// Get the target directory for git repositories
+	/// Use the existing clone as it is, without cloning, updating or fetching anything
+	pub fn with_offline(mut self, offline: bool) -> Self {
+		self.offline = offline;
+		self
+	}
+
+	/// Check if the existing clone is used without network access
+	pub fn is_offline(&self) -> bool {
+		self.offline
+	}
+
+	/// Authenticate to the remote with the given credentials, e.g. for private repositories
+	pub fn with_credentials(mut self, credentials: GitCredentials) -> Self {
+		self.credentials = credentials;
+		self
+	}
+
+	/// Get the credentials the remote is authenticated to with
+	pub fn credentials(&self) -> &GitCredentials {
+		&self.credentials
+	}
+
+	/// Clone and fetch only the given number of commits of history, e.g. `1` for large
+	/// monorepos on CI
+	///
+	/// Shallow clones are then never deepened: a revision beyond the depth fails to resolve,
+	/// suggesting a larger depth.
+	pub fn with_depth(mut self, depth: Option<u32>) -> Self {
+		self.depth = depth;
+		self
+	}
+
+	/// Get the number of commits of history fetched, if the clone is shallow
+	pub fn depth(&self) -> Option<u32> {
+		self.depth
+	}
+
+	/// Get the target directory for git repositories, creating it if needed
 	pub fn get_target_dir(&self) -> Result<PathBuf, GitSourceError> {
-		let base_dir = if let Some(ref parent_dir) = self.parent_dir {
-			parent_dir.clone()
-		} else {
-			// Use CARGO_TARGET_DIR if set, otherwise default to target/cite-git
-			std::env::var("CARGO_TARGET_DIR")
-				.map(PathBuf::from)
-				.unwrap_or_else(|_| PathBuf::from("target"))
-				.join("cite-git")
-		};
+		let base_dir = Self::resolve_target_dir(
+			self.parent_dir.clone(),
+			std::env::var_os(GIT_DIR_ENV).map(PathBuf::from),
+			std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from),
+		);
 
 		std::fs::create_dir_all(&base_dir).map_err(|e| {
-			GitSourceError::InvalidRemote(format!("Failed to create target directory: {}", e))
+			GitSourceError::InvalidRemote(format!(
+				"Failed to create target directory {}: {}",
+				base_dir.display(),
+				e
+			))
 		})?;
 
 		Ok(base_dir)
 	}
 
-	/// Generate a simple directory name for a repository
+	/// Resolve the target directory: an explicit directory, then `CITE_GIT_DIR`, then
+	/// `cite-git` in `CARGO_TARGET_DIR`, and finally `target/cite-git`
+	fn resolve_target_dir(
+		parent_dir: Option<PathBuf>,
+		git_dir: Option<PathBuf>,
+		cargo_target_dir: Option<PathBuf>,
+	) -> PathBuf {
+		parent_dir
+			.or_else(|| git_dir.filter(|git_dir| !git_dir.as_os_str().is_empty()))
+			.unwrap_or_else(|| {
+				cargo_target_dir.unwrap_or_else(|| PathBuf::from("target")).join("cite-git")
+			})
+	}
+
+	/// Get the path of a local repository given as a `file://` remote, e.g. a sibling clone
+	///
+	/// Local repositories are used in place, without cloning or fetching anything. Other remotes,
+	/// including bare local paths, are cloned as usual.
+	pub fn local_path(remote_url: &str) -> Option<PathBuf> {
+		remote_url
+			.strip_prefix("file://")
+			.filter(|path| !path.is_empty())
+			.map(PathBuf::from)
+	}
+
+	/// Generate the directory name of a repository from its host and path
+	///
+	/// The normalized URL without its scheme and user is sanitized, so that repositories of the
+	/// same name on different hosts or owners get clones of their own, e.g.
+	/// `https://github.com/ramate-io/cite.git` is cloned into `github.com_ramate-io_cite`.
 	pub fn generate_repo_dir_name(remote_url: &str) -> String {
-		// Extract the repo name from the URL
-		// e.g., "https://github.com/ramate-io/cite.git" -> "cite"
-		// e.g., "https://github.com/user/repo-name.git" -> "repo-name"
-		if let Some(last_part) = remote_url.split('/').last() {
-			if last_part.ends_with(".git") {
-				return last_part[..last_part.len() - 4].to_string();
-			}
-			return last_part.to_string();
-		}
+		let remote_url = Self::normalize_remote_url(remote_url);
+		let location = remote_url.split_once("://").map_or(remote_url.as_str(), |(_, rest)| rest);
+		// A user such as the `git` of `git@github.com:ramate-io/cite` precedes the host
+		let location = match location.split_once('@') {
+			Some((user, rest)) if !user.contains('/') => rest,
+			_ => location,
+		};
 
-		// Fallback: use a sanitized version of the URL
-		remote_url.replace([':', '/', '.'], "_")
+		let name = location
+			.chars()
+			.map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '.') { c } else { '_' })
+			.collect::<String>();
+		let name = name.trim_matches(|c| c == '_' || c == '.');
+		if name.is_empty() {
+			"repository".to_string()
+		} else {
+			name.to_string()
+		}
 	}
 
 	/// Fetch the repository and return a RepositoryManager
 	pub fn fetch(self) -> Result<RepositoryManager, GitSourceError> {
+		// A local repository is opened in place, resolving revisions against its objects
+		if let Some(path) = Self::local_path(&self.remote_url) {
+			Repository::open(&path).map_err(|e| {
+				GitSourceError::InvalidRemote(format!(
+					"{} is not a git repository: {}",
+					path.display(),
+					e.message()
+				))
+			})?;
+			return Ok(RepositoryManager::local(path));
+		}
+
 		let target_dir = self.get_target_dir()?;
 		let repo_dir_name = Self::generate_repo_dir_name(&self.remote_url);
 		let repo_path = target_dir.join(repo_dir_name);
 
+		// Offline, only an existing clone can be used
+		if self.offline {
+			if !repo_path.exists() {
+				return Err(GitSourceError::InvalidRemote(format!(
+					"{} is not cloned in {} and cannot be cloned offline",
+					self.remote_url,
+					target_dir.display()
+				)));
+			}
+			return Ok(RepositoryManager::new(repo_path)
+				.with_offline(true)
+				.with_credentials(self.credentials)
+				.with_depth(self.depth));
+		}
+
+		// Once fetched, a repository is reused by the other citations of its remote, unless its
+		// clone has since been damaged or the fetch is too old. Each citation still fetches
+		// missing revisions with its own credentials and depth.
+		let fetched =
+			fetched_repository(Self::normalize_remote_url(&self.remote_url), repo_path.clone());
+		let mut fetched = fetched.lock().unwrap_or_else(PoisonError::into_inner);
+		let reusable = fetched.as_ref().filter(|(manager, fetched_at)| {
+			fetched_at.elapsed() < FETCHED_TTL && Self::is_complete_clone(manager.path())
+		});
+		if let Some((manager, _)) = reusable {
+			return Ok(manager.clone().with_credentials(self.credentials).with_depth(self.depth));
+		}
+		let manager = self.clone_or_update(repo_path)?;
+		*fetched = Some((manager.clone(), Instant::now()));
+		Ok(manager)
+	}
+
+	/// Forget the repository fetched for this remote, so that the next fetch updates it again
+	///
+	/// Fetched repositories are otherwise reused for a few minutes, e.g. throughout a build.
+	pub fn forget_fetched(&self) -> Result<(), GitSourceError> {
+		*self.fetched()?.lock().unwrap_or_else(PoisonError::into_inner) = None;
+		Ok(())
+	}
+
+	/// Normalize a remote URL, so that spellings of the same remote share a fetched repository
+	///
+	/// Trailing slashes and the `.git` suffix are dropped, and the case-insensitive scheme and
+	/// host are lowercased, e.g. `HTTPS://GitHub.com/ramate-io/cite.git/` becomes
+	/// `https://github.com/ramate-io/cite`.
+	pub fn normalize_remote_url(remote_url: &str) -> String {
+		let url = remote_url.trim().trim_end_matches('/');
+		let url = url.strip_suffix(".git").unwrap_or(url);
+		match url.split_once("://") {
+			Some((scheme, rest)) => {
+				let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+				format!("{}://{}{}", scheme.to_lowercase(), host.to_lowercase(), path)
+			}
+			None => url.to_string(),
+		}
+	}
+
+	/// Get the repository fetched by this process for this remote and its clone path
+	fn fetched(&self) -> Result<FetchedRepository, GitSourceError> {
+		let repo_path = self.get_target_dir()?.join(Self::generate_repo_dir_name(&self.remote_url));
+		Ok(fetched_repository(Self::normalize_remote_url(&self.remote_url), repo_path))
+	}
+
+	/// Clone the repository, or update an existing clone, into the given path
+	fn clone_or_update(&self, repo_path: PathBuf) -> Result<RepositoryManager, GitSourceError> {
+		// An interrupted clone leaves a directory that fails on every build, so it is removed
+		// and cloned again, once
+		if repo_path.exists() && !Self::is_complete_clone(&repo_path) {
+			cite_core::trace_event!(path = %repo_path.display(), "re-cloning incomplete clone");
+			std::fs::remove_dir_all(&repo_path).map_err(|e| {
+				GitSourceError::InvalidRemote(format!(
+					"Failed to remove incomplete clone {}: {}",
+					repo_path.display(),
+					e
+				))
+			})?;
+		}
+
 		// If the repository already exists, check if we need to update it
 		if repo_path.exists() {
 			// Try to update the repository to get latest changes
 			// This is a best-effort operation - if it fails, we'll still use the existing repo
-			let _ = Self::update_existing_repository(&repo_path, &self.remote_url);
+			let _ = Self::update_existing_repository(
+				&repo_path,
+				&self.remote_url,
+				&self.retry,
+				&self.credentials,
+				self.depth,
+			);
 		} else {
 			// Clone the repository
-			let mut callbacks = RemoteCallbacks::new();
-			callbacks.credentials(|_url, _username_from_url, _allowed_types| git2::Cred::default());
-
-			let mut fetch_options = FetchOptions::new();
-			fetch_options.remote_callbacks(callbacks);
+			let clone = || {
+				let mut fetch_options = FetchOptions::new();
+				fetch_options.remote_callbacks(self.credentials.callbacks());
+				if let Some(depth) = self.depth {
+					fetch_options.depth(fetch_depth(depth));
+				}
+				git2::build::RepoBuilder::new()
+					.fetch_options(fetch_options)
+					.clone(&self.remote_url, &repo_path)
+			};
 
-			match Repository::clone(&self.remote_url, &repo_path) {
+			match self.retry.run(clone) {
 				Ok(_repo) => {}
 				Err(e) => {
 					// Check if this is the "exists and is not an empty directory" error
@@ -96,38 +337,61 @@ impl RepositoryBuilder {
 			};
 		}
 
-		Ok(RepositoryManager::new(repo_path))
+		Ok(RepositoryManager::new(repo_path)
+			.with_credentials(self.credentials.clone())
+			.with_depth(self.depth))
+	}
+
+	/// Check if a clone can be opened and has a checked out commit
+	///
+	/// A clone that was interrupted before it fetched anything has no `HEAD` commit, and one
+	/// interrupted earlier is not a repository at all.
+	fn is_complete_clone(repo_path: &Path) -> bool {
+		Repository::open(repo_path)
+			.and_then(|repo| repo.head()?.peel_to_commit().map(|_| ()))
+			.is_ok()
 	}
 
 	/// Update an existing repository
 	fn update_existing_repository(
 		repo_path: &Path,
 		remote_url: &str,
+		retry: &RetryPolicy,
+		credentials: &GitCredentials,
+		depth: Option<u32>,
 	) -> Result<(), GitSourceError> {
 		let repo = Repository::open(repo_path).map_err(|e| GitSourceError::Git(e))?;
-		Self::fetch_latest_changes(&repo, remote_url)
+		Self::fetch_latest_changes(&repo, remote_url, retry, credentials, depth)
 	}
 
 	/// Fetch latest changes for an existing repository
-	fn fetch_latest_changes(repo: &Repository, remote_url: &str) -> Result<(), GitSourceError> {
+	fn fetch_latest_changes(
+		repo: &Repository,
+		remote_url: &str,
+		retry: &RetryPolicy,
+		credentials: &GitCredentials,
+		depth: Option<u32>,
+	) -> Result<(), GitSourceError> {
 		let mut remote = repo
 			.find_remote("origin")
 			.or_else(|_| repo.remote("origin", remote_url))
 			.map_err(|e| GitSourceError::Git(e))?;
 
-		let mut callbacks = RemoteCallbacks::new();
-		callbacks.credentials(|_url, _username_from_url, _allowed_types| git2::Cred::default());
-
-		let mut fetch_options = FetchOptions::new();
-		fetch_options.remote_callbacks(callbacks);
-
 		// Fetch all branches and tags to ensure we have the latest symbols
-		remote
-			.fetch(
-				&["refs/heads/*:refs/remotes/origin/*", "refs/tags/*:refs/tags/*"],
-				Some(&mut fetch_options),
-				None,
-			)
+		retry
+			.run(|| {
+				let mut fetch_options = FetchOptions::new();
+				fetch_options.remote_callbacks(credentials.callbacks());
+				if let Some(depth) = depth {
+					fetch_options.depth(fetch_depth(depth));
+				}
+
+				remote.fetch(
+					&["refs/heads/*:refs/remotes/origin/*", "refs/tags/*:refs/tags/*"],
+					Some(&mut fetch_options),
+					None,
+				)
+			})
 			.map_err(|e| GitSourceError::Git(e))?;
 
 		Ok(())
@@ -138,18 +402,52 @@ impl RepositoryBuilder {
 #[derive(Debug, Clone, PartialEq)]
 pub struct RepositoryManager {
 	repo_path: PathBuf,
+	offline: bool,
+	credentials: GitCredentials,
+	depth: Option<u32>,
+	local: bool,
 }
 
 impl Default for RepositoryManager {
 	fn default() -> Self {
-		Self { repo_path: PathBuf::new() }
+		Self::new(PathBuf::new())
 	}
 }
 
 impl RepositoryManager {
 	/// Create a new repository manager for the given repository path
 	pub fn new(repo_path: PathBuf) -> Self {
-		Self { repo_path }
+		Self {
+			repo_path,
+			offline: false,
+			credentials: GitCredentials::default(),
+			depth: None,
+			local: false,
+		}
+	}
+
+	/// Create a repository manager for a local repository, which is used in place: nothing is
+	/// fetched, and its default branch is the branch its `HEAD` points to
+	pub fn local(repo_path: PathBuf) -> Self {
+		Self { local: true, ..Self::new(repo_path).with_offline(true) }
+	}
+
+	/// Never fetch missing revisions or history, as without network access
+	pub fn with_offline(mut self, offline: bool) -> Self {
+		self.offline = offline;
+		self
+	}
+
+	/// Authenticate to the remote with the given credentials when fetching
+	pub fn with_credentials(mut self, credentials: GitCredentials) -> Self {
+		self.credentials = credentials;
+		self
+	}
+
+	/// Fetch revisions only to the given depth, never deepening the clone
+	pub fn with_depth(mut self, depth: Option<u32>) -> Self {
+		self.depth = depth;
+		self
 	}
 
 	/// Get the repository path
@@ -175,27 +473,229 @@ impl RepositoryManager {
 
 	/// Fetch specific revisions that are needed
 	pub fn fetch_specific_revisions(&self, revisions: &[&str]) -> Result<(), GitSourceError> {
+		// Missing revisions then fail to resolve
+		if self.offline {
+			return Ok(());
+		}
+
+		let missing = revisions
+			.iter()
+			.filter(|revision| !self.revision_exists(revision))
+			.collect::<Vec<_>>();
+		if missing.is_empty() {
+			return Ok(());
+		}
+
 		let repo = Repository::open(&self.repo_path).map_err(|e| GitSourceError::Git(e))?;
 		let mut remote = repo.find_remote("origin").map_err(|e| GitSourceError::Git(e))?;
 
-		let mut callbacks = RemoteCallbacks::new();
-		callbacks.credentials(|_url, _username_from_url, _allowed_types| git2::Cred::default());
+		// Tags are fetched into refs/tags first, so that a tag like `v1.2.0` resolves like a
+		// branch; best-effort, since a tag moved on the remote fails to update
+		let _ = remote.fetch(&["refs/tags/*:refs/tags/*"], Some(&mut self.fetch_options()), None);
 
-		let mut fetch_options = FetchOptions::new();
-		fetch_options.remote_callbacks(callbacks);
-
-		// For each revision, try to fetch it if it doesn't exist locally
-		for revision in revisions {
+		// For each revision still missing, try to fetch it
+		for revision in missing {
 			if !self.revision_exists(revision) {
 				// Try to fetch this specific commit
 				// Note: This is a best-effort approach - some commits might not be fetchable
 				// if they're not reachable from any ref
-				let _ = remote.fetch(&[revision], Some(&mut fetch_options), None);
+				let _ = remote.fetch(&[revision], Some(&mut self.fetch_options()), None);
 			}
 		}
 
 		Ok(())
 	}
+
+	/// Get the options of a fetch, authenticating with the credentials to the chosen depth
+	fn fetch_options(&self) -> FetchOptions<'_> {
+		let mut fetch_options = FetchOptions::new();
+		fetch_options.remote_callbacks(self.credentials.callbacks());
+		if let Some(depth) = self.depth {
+			fetch_options.depth(fetch_depth(depth));
+		}
+		fetch_options
+	}
+
+	/// Get the remote-tracking branch of the remote's default branch, e.g. `origin/main`
+	///
+	/// The default branch is the one the remote's `HEAD` points to. It is asked from the remote,
+	/// and read from the `origin/HEAD` recorded by the clone when offline or when the remote
+	/// cannot be reached. A local repository has no remote, and its own `HEAD` branch is used,
+	/// e.g. `main`.
+	pub fn default_branch(&self) -> Result<String, GitSourceError> {
+		let repo = self.get_repository()?;
+		if self.local {
+			let head = repo.head()?;
+			return match head.shorthand() {
+				Some(branch) if head.is_branch() => Ok(branch.to_string()),
+				_ => Err(GitSourceError::InvalidRevision(format!(
+					"The HEAD of {} is not a branch, give a current revision instead",
+					self.repo_path.display()
+				))),
+			};
+		}
+		let remote_head = (!self.offline).then(|| self.remote_default_branch(&repo)).flatten();
+		let branch = match remote_head {
+			Some(branch) => branch,
+			None => repo
+				.find_reference("refs/remotes/origin/HEAD")
+				.ok()
+				.and_then(|head| head.symbolic_target().map(str::to_string))
+				.and_then(|target| target.strip_prefix("refs/remotes/origin/").map(str::to_string))
+				.ok_or_else(|| {
+					GitSourceError::InvalidRevision(format!(
+						"Cannot resolve the default branch of {}, give a current revision instead",
+						self.repo_path.display()
+					))
+				})?,
+		};
+		Ok(format!("origin/{}", branch))
+	}
+
+	/// Ask the remote for the branch its `HEAD` points to, e.g. `main`
+	fn remote_default_branch(&self, repo: &Repository) -> Option<String> {
+		let mut remote = repo.find_remote("origin").ok()?;
+		let callbacks = self.credentials.callbacks();
+		remote.connect_auth(git2::Direction::Fetch, Some(callbacks), None).ok()?;
+
+		let default_branch = remote.default_branch().ok()?;
+		let branch = default_branch.as_str()?.strip_prefix("refs/heads/")?.to_string();
+		let _ = remote.disconnect();
+		Some(branch)
+	}
+
+	/// Resolve a revision to the SHA of the commit it names
+	///
+	/// Supports ancestry suffixes like `main~3` or `HEAD^`. A shallow clone lacking the
+	/// ancestors is deepened first, unless its depth was chosen; a revision reaching past the
+	/// start of the history is an [GitSourceError::InvalidRevision] naming how many ancestors
+	/// are available.
+	pub fn resolve_revision(&self, revision: &str) -> Result<String, GitSourceError> {
+		let repo = self.get_repository()?;
+		let ancestry = ancestry(revision);
+
+		if !self.offline
+			&& self.depth.is_none()
+			&& ancestry.is_some()
+			&& repo.is_shallow()
+			&& repo.revparse_single(revision).is_err()
+		{
+			// Best-effort: a clone that cannot be deepened fails to resolve below
+			let _ = self.unshallow(&repo);
+		}
+
+		match repo.revparse_single(revision) {
+			Ok(object) => {
+				// Trees have no commit and resolve to themselves
+				let id = object.peel_to_commit().map(|commit| commit.id()).unwrap_or(object.id());
+				Ok(id.to_string())
+			}
+			Err(e) => match ancestry {
+				Some((base, depth)) => {
+					let mut commit = repo.revparse_single(base)?.peel_to_commit()?;
+					let mut available = 0;
+					while let Ok(parent) = commit.parent(0) {
+						available += 1;
+						commit = parent;
+					}
+					Err(GitSourceError::InvalidRevision(format!(
+						"{} is {} commits back from {}, but only {} are available{}",
+						revision,
+						depth,
+						base,
+						available,
+						self.depth_hint()
+					)))
+				}
+				None if self.depth.is_some() => Err(GitSourceError::InvalidRevision(format!(
+					"{} was not found{}",
+					revision,
+					self.depth_hint()
+				))),
+				None => Err(GitSourceError::Git(e)),
+			},
+		}
+	}
+
+	/// Find the commit where the current revision diverged from the base revision
+	///
+	/// Shallow clones are deepened first, since the merge-base is usually outside their history,
+	/// unless their depth was chosen.
+	pub fn merge_base(&self, base: &str, current: &str) -> Result<String, GitSourceError> {
+		let repo = self.get_repository()?;
+		if !self.offline && self.depth.is_none() && repo.is_shallow() {
+			// Best-effort: a clone that cannot be deepened fails to find the merge-base below
+			let _ = self.unshallow(&repo);
+		}
+
+		let base_commit = repo.revparse_single(base)?.peel_to_commit()?;
+		let current_commit = repo.revparse_single(current)?.peel_to_commit()?;
+		let merge_base = repo.merge_base(base_commit.id(), current_commit.id()).map_err(|e| {
+			GitSourceError::InvalidRevision(format!(
+				"{} and {} have no common history: {}{}",
+				base,
+				current,
+				e,
+				self.depth_hint()
+			))
+		})?;
+		Ok(merge_base.to_string())
+	}
+
+	/// Suggest a larger depth for a revision missing from a clone of chosen depth
+	fn depth_hint(&self) -> String {
+		match self.depth {
+			Some(depth) => format!(
+				" within the fetched depth of {}, increase depth to fetch more history",
+				depth
+			),
+			None => String::new(),
+		}
+	}
+
+	/// Fetch the full history of a shallow clone
+	fn unshallow(&self, repo: &Repository) -> Result<(), GitSourceError> {
+		let mut remote = repo.find_remote("origin").map_err(GitSourceError::Git)?;
+
+		// libgit2 reads the largest depth as a request to unshallow
+		let mut fetch_options = self.fetch_options();
+		fetch_options.depth(i32::MAX);
+
+		remote
+			.fetch(&["refs/heads/*:refs/remotes/origin/*"], Some(&mut fetch_options), None)
+			.map_err(GitSourceError::Git)
+	}
+}
+
+/// Get the depth libgit2 fetches for a depth of history, which it takes as an `i32`
+fn fetch_depth(depth: u32) -> i32 {
+	i32::try_from(depth).unwrap_or(i32::MAX)
+}
+
+/// Split a revision with ancestry suffixes into its base and the number of generations back
+///
+/// `main~3` is three commits back from `main`, and `HEAD^^` two from `HEAD`. Revisions without
+/// suffixes are `None`.
+fn ancestry(revision: &str) -> Option<(&str, usize)> {
+	let start = revision.find(['~', '^'])?;
+	let (base, mut suffixes) = revision.split_at(start);
+	let mut depth = 0;
+
+	while !suffixes.is_empty() {
+		let count = suffixes[1..].find(['~', '^']).map_or(suffixes.len(), |end| end + 1);
+		let (suffix, rest) = suffixes.split_at(count);
+		let number = &suffix[1..];
+		depth += match (suffix.starts_with('~'), number.parse::<usize>()) {
+			(_, Err(_)) if number.is_empty() => 1,
+			(true, Ok(generations)) => generations,
+			// `^N` is the Nth parent, one generation back
+			(false, Ok(_)) => 1,
+			(_, Err(_)) => return None,
+		};
+		suffixes = rest;
+	}
+
+	Some((base, depth))
 }
 
 // Legacy functions for backward compatibility
@@ -222,16 +722,33 @@ mod tests {
 	fn test_generate_repo_dir_name() {
 		assert_eq!(
 			RepositoryBuilder::generate_repo_dir_name("https://github.com/ramate-io/cite.git"),
-			"cite"
+			"github.com_ramate-io_cite"
 		);
 		assert_eq!(
-			RepositoryBuilder::generate_repo_dir_name("https://github.com/user/repo-name.git"),
-			"repo-name"
+			RepositoryBuilder::generate_repo_dir_name("https://gitlab.com/group/project/"),
+			"gitlab.com_group_project"
 		);
+
+		// Spellings of a remote share a clone
 		assert_eq!(
-			RepositoryBuilder::generate_repo_dir_name("https://gitlab.com/group/project"),
-			"project"
+			RepositoryBuilder::generate_repo_dir_name("git@github.com:ramate-io/cite.git"),
+			"github.com_ramate-io_cite"
+		);
+		assert_eq!(
+			RepositoryBuilder::generate_repo_dir_name("HTTPS://GitHub.com/ramate-io/cite/"),
+			"github.com_ramate-io_cite"
+		);
+
+		// Repositories of the same name on other owners or hosts do not
+		assert_ne!(
+			RepositoryBuilder::generate_repo_dir_name("https://github.com/a/cite"),
+			RepositoryBuilder::generate_repo_dir_name("https://github.com/b/cite")
+		);
+		assert_ne!(
+			RepositoryBuilder::generate_repo_dir_name("https://github.com/a/cite"),
+			RepositoryBuilder::generate_repo_dir_name("https://gitlab.com/a/cite")
 		);
+		assert_eq!(RepositoryBuilder::generate_repo_dir_name("/srv/git/remote"), "srv_git_remote");
 	}
 
 	#[test]
@@ -242,6 +759,55 @@ mod tests {
 		assert!(target_dir.is_dir());
 	}
 
+	#[test]
+	fn test_resolve_target_dir() {
+		let explicit = Some(PathBuf::from("/shared/explicit"));
+		let git_dir = Some(PathBuf::from("/shared/cite-git"));
+		let cargo_target_dir = Some(PathBuf::from("/build/target"));
+
+		// The default is preserved without overrides
+		assert_eq!(
+			RepositoryBuilder::resolve_target_dir(None, None, None),
+			PathBuf::from("target/cite-git")
+		);
+		assert_eq!(
+			RepositoryBuilder::resolve_target_dir(None, None, cargo_target_dir.clone()),
+			PathBuf::from("/build/target/cite-git")
+		);
+		assert_eq!(
+			RepositoryBuilder::resolve_target_dir(None, Some(PathBuf::new()), None),
+			PathBuf::from("target/cite-git")
+		);
+
+		// CITE_GIT_DIR is used as is, and an explicit directory wins over it
+		assert_eq!(
+			RepositoryBuilder::resolve_target_dir(None, git_dir.clone(), cargo_target_dir),
+			PathBuf::from("/shared/cite-git")
+		);
+		assert_eq!(
+			RepositoryBuilder::resolve_target_dir(explicit, git_dir, None),
+			PathBuf::from("/shared/explicit")
+		);
+	}
+
+	#[test]
+	fn test_with_target_dir() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let shared = temp_dir.path().join("shared").join("clones");
+
+		let builder = RepositoryBuilder::new("https://github.com/ramate-io/cite.git".to_string())
+			.with_target_dir(shared.clone());
+		assert_eq!(builder.get_target_dir().unwrap(), shared);
+		assert!(shared.is_dir());
+
+		// A file in the way of the directory is reported
+		let file = temp_dir.path().join("file");
+		std::fs::write(&file, "").unwrap();
+		let builder = RepositoryBuilder::new("https://github.com/ramate-io/cite.git".to_string())
+			.with_target_dir(file);
+		assert!(matches!(builder.get_target_dir(), Err(GitSourceError::InvalidRemote(_))));
+	}
+
 	#[test]
 	fn test_repository_builder_new() {
 		let builder = RepositoryBuilder::new("https://github.com/ramate-io/cite".to_string());
@@ -357,4 +923,233 @@ mod tests {
 		assert!(manager.revision_exists("main"));
 		assert!(manager.revision_exists("2bcceb14934dbe0803ddb70bc8952a0c33f931e2"));
 	}
+
+	#[test]
+	fn test_fetch_recovers_incomplete_clone() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+
+		// A local fixture remote with a single commit on main
+		let remote_path = temp_dir.path().join("remote");
+		let remote = Repository::init(&remote_path)?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		let tree = remote.find_tree(remote.treebuilder(None)?.write()?)?;
+		let commit = remote.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])?;
+
+		let clones = temp_dir.path().join("clones");
+		let builder = RepositoryBuilder::with_parent_dir(
+			remote_path.to_string_lossy().to_string(),
+			clones.clone(),
+		);
+		let repo_path = builder.clone().fetch()?.path().clone();
+		assert_eq!(
+			repo_path,
+			clones.join(RepositoryBuilder::generate_repo_dir_name(&remote_path.to_string_lossy()))
+		);
+
+		// A clone interrupted before fetching has no HEAD commit
+		std::fs::remove_dir_all(repo_path.join(".git").join("objects"))?;
+		assert!(!RepositoryBuilder::is_complete_clone(&repo_path));
+		let manager = builder.clone().fetch()?;
+		assert_eq!(manager.resolve_revision("HEAD")?, commit.to_string());
+
+		// A clone interrupted earlier is not a repository at all
+		std::fs::remove_dir_all(&repo_path)?;
+		std::fs::create_dir_all(repo_path.join(".git"))?;
+		std::fs::write(repo_path.join(".git").join("config"), "partial")?;
+		let manager = builder.fetch()?;
+		assert_eq!(manager.resolve_revision("HEAD")?, commit.to_string());
+		Ok(())
+	}
+
+	#[test]
+	fn test_fetched_repository_is_shared() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+
+		// A local fixture remote with a single commit on main
+		let remote_path = temp_dir.path().join("remote");
+		let remote = Repository::init(&remote_path)?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		let tree = remote.find_tree(remote.treebuilder(None)?.write()?)?;
+		let first = remote.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])?;
+		let builder = RepositoryBuilder::with_parent_dir(
+			remote_path.to_string_lossy().to_string(),
+			temp_dir.path().join("clones"),
+		);
+		let manager = builder.clone().fetch()?;
+
+		// Further citations of the remote reuse the fetched repository without fetching again
+		let parent = remote.find_commit(first)?;
+		let second =
+			remote.commit(Some("HEAD"), &signature, &signature, "second", &tree, &[&parent])?;
+		remote.tag_lightweight("v2", remote.find_commit(second)?.as_object(), false)?;
+		let shared = builder.clone().fetch()?;
+		assert_eq!(shared, manager);
+		assert!(!shared.revision_exists("v2"));
+
+		// With the depth of the citation reusing it
+		let shallow = builder.clone().with_depth(Some(1)).fetch()?;
+		assert_eq!(shallow, manager.clone().with_depth(Some(1)));
+
+		// Once forgotten, the repository is fetched again
+		builder.forget_fetched()?;
+		let updated = builder.clone().fetch()?;
+		assert_eq!(updated.path(), manager.path());
+		assert_eq!(updated.resolve_revision("v2")?, second.to_string());
+
+		// As it is once its fetch is too old, e.g. in a process outliving the build
+		let parent = remote.find_commit(second)?;
+		let third =
+			remote.commit(Some("HEAD"), &signature, &signature, "third", &tree, &[&parent])?;
+		remote.tag_lightweight("v3", remote.find_commit(third)?.as_object(), false)?;
+		assert!(!builder.clone().fetch()?.revision_exists("v3"));
+		if let Some((_, fetched_at)) =
+			builder.fetched()?.lock().unwrap_or_else(PoisonError::into_inner).as_mut()
+		{
+			*fetched_at -= FETCHED_TTL;
+		}
+		assert_eq!(builder.fetch()?.resolve_revision("v3")?, third.to_string());
+		Ok(())
+	}
+
+	#[test]
+	fn test_normalize_remote_url() {
+		for remote_url in [
+			"https://github.com/ramate-io/cite",
+			"https://github.com/ramate-io/cite.git",
+			"https://github.com/ramate-io/cite/",
+			"HTTPS://GitHub.com/ramate-io/cite.git/",
+		] {
+			assert_eq!(
+				RepositoryBuilder::normalize_remote_url(remote_url),
+				"https://github.com/ramate-io/cite"
+			);
+		}
+
+		// Paths are case-sensitive
+		assert_eq!(
+			RepositoryBuilder::normalize_remote_url("https://github.com/Ramate-IO/Cite.git"),
+			"https://github.com/Ramate-IO/Cite"
+		);
+		assert_eq!(
+			RepositoryBuilder::normalize_remote_url("git@github.com:ramate-io/cite.git"),
+			"git@github.com:ramate-io/cite"
+		);
+	}
+
+	#[test]
+	fn test_fetch_specific_revisions_fetches_tags() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+
+		// A local fixture remote with a single commit on main
+		let remote_path = temp_dir.path().join("remote");
+		let remote = Repository::init(&remote_path)?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		let tree = remote.find_tree(remote.treebuilder(None)?.write()?)?;
+		let first = remote.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])?;
+		let manager = RepositoryBuilder::with_parent_dir(
+			remote_path.to_string_lossy().to_string(),
+			temp_dir.path().join("clones"),
+		)
+		.fetch()?;
+
+		// Tags created after the clone, one annotated and one lightweight
+		let parent = remote.find_commit(first)?;
+		let second =
+			remote.commit(Some("HEAD"), &signature, &signature, "second", &tree, &[&parent])?;
+		remote.tag("v1.2.0", parent.as_object(), &signature, "v1.2.0", false)?;
+		remote.tag_lightweight("v1.3.0", remote.find_commit(second)?.as_object(), false)?;
+		assert!(!manager.revision_exists("v1.2.0"));
+
+		manager.fetch_specific_revisions(&["v1.2.0", "v1.3.0"])?;
+		assert_eq!(manager.resolve_revision("v1.2.0")?, first.to_string());
+		assert_eq!(manager.resolve_revision("v1.3.0")?, second.to_string());
+		assert_eq!(manager.resolve_revision("refs/tags/v1.3.0")?, second.to_string());
+		Ok(())
+	}
+
+	#[test]
+	fn test_local_path() {
+		assert_eq!(
+			RepositoryBuilder::local_path("file:///work/monorepo"),
+			Some(PathBuf::from("/work/monorepo"))
+		);
+		assert_eq!(RepositoryBuilder::local_path("file://"), None);
+		assert_eq!(RepositoryBuilder::local_path("https://github.com/ramate-io/cite"), None);
+		assert_eq!(RepositoryBuilder::local_path("/work/monorepo"), None);
+
+		// A local path that is not a repository is reported
+		let temp_dir = tempfile::tempdir().unwrap();
+		let builder =
+			RepositoryBuilder::new(format!("file://{}", temp_dir.path().join("missing").display()));
+		assert!(matches!(builder.fetch(), Err(GitSourceError::InvalidRemote(_))));
+	}
+
+	#[test]
+	fn test_resolve_ancestry_revision() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let repo = Repository::init(temp_dir.path())?;
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+
+		// A fixture history of four commits on main
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		let tree = repo.find_tree(repo.treebuilder(None)?.write()?)?;
+		let mut commits = Vec::new();
+		for message in ["first", "second", "third", "fourth"] {
+			let parent = commits.last().map(|id| repo.find_commit(*id)).transpose()?;
+			let parents = parent.iter().collect::<Vec<_>>();
+			commits.push(repo.commit(None, &signature, &signature, message, &tree, &parents)?);
+		}
+		repo.reference("refs/heads/main", commits[3], true, "fixture")?;
+
+		assert_eq!(manager.resolve_revision("main~3")?, commits[0].to_string());
+		assert_eq!(manager.resolve_revision("main~1")?, commits[2].to_string());
+		assert_eq!(manager.resolve_revision("main^^")?, commits[1].to_string());
+		assert_eq!(manager.resolve_revision("main")?, commits[3].to_string());
+
+		// Reaching past the first commit names the available history
+		let error = manager.resolve_revision("main~4").unwrap_err();
+		assert!(matches!(error, GitSourceError::InvalidRevision(_)));
+		assert!(error.to_string().contains("only 3 are available"));
+		assert!(manager.resolve_revision("missing").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_revision_beyond_depth_suggests_increasing_it() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let repo = Repository::init(temp_dir.path())?;
+		let signature = git2::Signature::now("cite", "cite@example.com")?;
+		let tree = repo.find_tree(repo.treebuilder(None)?.write()?)?;
+		let first = repo.commit(None, &signature, &signature, "first", &tree, &[])?;
+		let second = repo.find_commit(first)?;
+		let second = repo.commit(None, &signature, &signature, "second", &tree, &[&second])?;
+		repo.reference("refs/heads/main", second, true, "fixture")?;
+
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf()).with_depth(Some(1));
+		assert_eq!(manager.resolve_revision("main")?, second.to_string());
+
+		let error = manager.resolve_revision("v1.0").unwrap_err();
+		assert!(matches!(error, GitSourceError::InvalidRevision(_)));
+		assert!(error
+			.to_string()
+			.contains("v1.0 was not found within the fetched depth of 1, increase depth"));
+
+		let error = manager.resolve_revision("main~2").unwrap_err();
+		assert!(error.to_string().contains("only 1 are available within the fetched depth of 1"));
+
+		// Without a chosen depth, the error is not about depth
+		let manager = RepositoryManager::new(temp_dir.path().to_path_buf());
+		assert!(!manager.resolve_revision("v1.0").unwrap_err().to_string().contains("depth"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_ancestry() {
+		assert_eq!(ancestry("main~3"), Some(("main", 3)));
+		assert_eq!(ancestry("HEAD~"), Some(("HEAD", 1)));
+		assert_eq!(ancestry("HEAD^^"), Some(("HEAD", 2)));
+		assert_eq!(ancestry("v1.0~2^2~1"), Some(("v1.0", 4)));
+		assert_eq!(ancestry("main"), None);
+		assert_eq!(ancestry("main~x"), None);
+	}
 }
@@ -0,0 +1,169 @@
+use git2::{ErrorClass, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Environment variable overriding the number of git network retries
+pub const GIT_RETRIES_ENV: &str = "CITE_GIT_RETRIES";
+
+/// Default number of retries for git network operations
+pub const DEFAULT_GIT_RETRIES: u32 = 2;
+
+/// Default backoff before the first retry, in milliseconds
+pub const DEFAULT_INITIAL_BACKOFF_MS: u64 = 500;
+
+/// Bounded retries with exponential backoff for git clone and fetch operations
+///
+/// Only transient transport failures are retried; errors that will not resolve themselves
+/// (authentication, missing repositories, certificate problems) fail immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+	/// Number of retries after the first attempt
+	pub retries: u32,
+	/// Backoff before the first retry; doubled for each further retry
+	pub initial_backoff_ms: u64,
+}
+
+impl RetryPolicy {
+	/// Create a new retry policy
+	pub fn new(retries: u32, initial_backoff_ms: u64) -> Self {
+		Self { retries, initial_backoff_ms }
+	}
+
+	/// Create a retry policy with the number of retries from the environment, or the default
+	pub fn from_env() -> Self {
+		let retries = std::env::var(GIT_RETRIES_ENV)
+			.ok()
+			.and_then(|value| value.parse().ok())
+			.unwrap_or(DEFAULT_GIT_RETRIES);
+		Self { retries, ..Self::default() }
+	}
+
+	/// Set the number of retries
+	pub fn with_retries(mut self, retries: u32) -> Self {
+		self.retries = retries;
+		self
+	}
+
+	/// Get the backoff before the given retry (zero-based)
+	pub fn backoff(&self, retry: u32) -> Duration {
+		Duration::from_millis(self.initial_backoff_ms.saturating_mul(1 << retry.min(16)))
+	}
+
+	/// Check if a git error is a transient transport failure worth retrying
+	pub fn is_retryable(error: &git2::Error) -> bool {
+		match error.code() {
+			ErrorCode::Auth
+			| ErrorCode::NotFound
+			| ErrorCode::Certificate
+			| ErrorCode::Exists
+			| ErrorCode::Invalid => return false,
+			_ => {}
+		}
+
+		// Remotes that do not exist surface as plain http errors
+		let message = error.message().to_lowercase();
+		if message.contains("not found") || message.contains("404") || message.contains("401") {
+			return false;
+		}
+
+		matches!(
+			error.class(),
+			ErrorClass::Net | ErrorClass::Os | ErrorClass::Http | ErrorClass::Ssl | ErrorClass::Ssh
+		)
+	}
+
+	/// Run a git operation, retrying transient failures with backoff
+	pub fn run<T, F>(&self, mut operation: F) -> Result<T, git2::Error>
+	where
+		F: FnMut() -> Result<T, git2::Error>,
+	{
+		let mut retry = 0;
+		loop {
+			match operation() {
+				Err(e) if retry < self.retries && Self::is_retryable(&e) => {
+					std::thread::sleep(self.backoff(retry));
+					retry += 1;
+				}
+				result => return result,
+			}
+		}
+	}
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self::new(DEFAULT_GIT_RETRIES, DEFAULT_INITIAL_BACKOFF_MS)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn transport_error() -> git2::Error {
+		git2::Error::new(ErrorCode::GenericError, ErrorClass::Net, "connection reset by peer")
+	}
+
+	#[test]
+	fn test_failing_then_succeeding_fetch() {
+		let policy = RetryPolicy::new(2, 0);
+		let mut attempts = 0;
+
+		let result = policy.run(|| {
+			attempts += 1;
+			if attempts < 3 {
+				Err(transport_error())
+			} else {
+				Ok("fetched")
+			}
+		});
+
+		assert_eq!(result.unwrap(), "fetched");
+		assert_eq!(attempts, 3);
+	}
+
+	#[test]
+	fn test_retries_are_bounded() {
+		let policy = RetryPolicy::new(1, 0);
+		let mut attempts = 0;
+
+		let result: Result<(), _> = policy.run(|| {
+			attempts += 1;
+			Err(transport_error())
+		});
+
+		assert!(result.is_err());
+		assert_eq!(attempts, 2);
+	}
+
+	#[test]
+	fn test_non_retryable_errors_fail_immediately() {
+		let policy = RetryPolicy::new(3, 0);
+
+		for error in [
+			git2::Error::new(ErrorCode::Auth, ErrorClass::Http, "authentication required"),
+			git2::Error::new(ErrorCode::NotFound, ErrorClass::Reference, "reference not found"),
+			git2::Error::new(
+				ErrorCode::GenericError,
+				ErrorClass::Http,
+				"unexpected http status code: 404",
+			),
+		] {
+			let mut attempts = 0;
+			let result: Result<(), _> = policy.run(|| {
+				attempts += 1;
+				Err(git2::Error::new(error.code(), error.class(), error.message()))
+			});
+
+			assert!(result.is_err());
+			assert_eq!(attempts, 1, "{} should not be retried", error.message());
+		}
+	}
+
+	#[test]
+	fn test_backoff_doubles() {
+		let policy = RetryPolicy::new(3, 100);
+		assert_eq!(policy.backoff(0), Duration::from_millis(100));
+		assert_eq!(policy.backoff(2), Duration::from_millis(400));
+	}
+}
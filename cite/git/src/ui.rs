@@ -1,5 +1,9 @@
-use crate::{CurrentGitContent, GitDiff, GitSource, ReferencedGitContent};
+use crate::{
+	ContentFilter, CurrentGitContent, DiffBase, GitCredentials, GitDiff, GitSource,
+	ReferencedGitContent, WhitespaceMode,
+};
 use cite_core::ui::{AboveDocAttr, SourceUi, SourceUiError};
+use cite_core::{DiffGranularity, DiffSensitivity, TextEncoding};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
@@ -49,7 +53,8 @@ impl SourceUi<ReferencedGitContent, CurrentGitContent, GitDiff> for GitSource {
 			// Direct serde fields
 			"id" | "remote" | "path_pattern" | "referenced_revision" | "current_revision" | "name" | "formatted_url" | "repository_builder" |
 			// Legacy ergonomic fields
-			"ref_rev" | "cur_rev" | "path" |
+			"ref_rev" | "cur_rev" | "path" | "sensitivity" | "filter" | "encoding" | "retries" | "ignore_moves" | "diff_base" | "revisions" | "region" | "expect_diff" | "expect_diff_file" |
+			"cred_env" | "depth" | "whitespace" | "diff_granularity" |
 			// Citation-level fields
 			"src" | "reason" | "level" | "annotation" => true,
 			_ => false,
@@ -81,21 +86,43 @@ impl GitSource {
 			.and_then(|v| v.as_str())
 			.ok_or_else(|| SourceUiError::MissingParameter("remote".to_string()))?;
 
-		let ref_rev = kwargs
-			.get("ref_rev")
-			.or_else(|| kwargs.get("referenced_revision"))
-			.and_then(|v| v.as_str())
-			.ok_or_else(|| {
-				SourceUiError::MissingParameter("ref_rev or referenced_revision".to_string())
-			})?;
+		// A list of revisions generalizes the referenced and current revision: the first is
+		// referenced, and every other one must match it
+		let revisions = kwargs.get("revisions").map(parse_revisions).transpose()?;
+		if revisions.is_some()
+			&& ["ref_rev", "referenced_revision", "cur_rev", "current_revision"]
+				.iter()
+				.any(|key| kwargs.contains_key(*key))
+		{
+			return Err(SourceUiError::InvalidParameter(
+				"revisions cannot be combined with ref_rev or cur_rev".to_string(),
+			));
+		}
 
-		let cur_rev = kwargs
-			.get("cur_rev")
-			.or_else(|| kwargs.get("current_revision"))
-			.and_then(|v| v.as_str())
-			.ok_or_else(|| {
-				SourceUiError::MissingParameter("cur_rev or current_revision".to_string())
-			})?;
+		let ref_rev = match &revisions {
+			Some(revisions) => revisions[0].as_str(),
+			None => kwargs
+				.get("ref_rev")
+				.or_else(|| kwargs.get("referenced_revision"))
+				.and_then(|v| v.as_str())
+				.ok_or_else(|| {
+					SourceUiError::MissingParameter("ref_rev or referenced_revision".to_string())
+				})?,
+		};
+
+		// Without a current revision, the default branch of the remote is compared
+		let cur_rev = match &revisions {
+			Some(revisions) => Some(revisions[1].as_str()),
+			None => match kwargs.get("cur_rev").or_else(|| kwargs.get("current_revision")) {
+				Some(value) => Some(value.as_str().ok_or_else(|| {
+					SourceUiError::InvalidParameter(format!(
+						"cur_rev must be a string, got {}",
+						value
+					))
+				})?),
+				None => None,
+			},
+		};
 
 		let path = kwargs
 			.get("path")
@@ -113,20 +140,220 @@ impl GitSource {
 			)));
 		}
 
+		// Extract optional diff sensitivity
+		let sensitivity = match kwargs.get("sensitivity").and_then(|v| v.as_str()) {
+			Some(s) => DiffSensitivity::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => DiffSensitivity::default(),
+		};
+
+		// Extract optional content filter
+		let filter = match kwargs.get("filter").and_then(|v| v.as_str()) {
+			Some(s) => ContentFilter::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => ContentFilter::default(),
+		};
+
+		// Extract optional character encoding of blob content
+		let encoding = match kwargs.get("encoding").and_then(|v| v.as_str()) {
+			Some(s) => TextEncoding::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => TextEncoding::default(),
+		};
+
+		// Extract optional tolerance of whitespace changes
+		let whitespace = match kwargs.get("whitespace").and_then(|v| v.as_str()) {
+			Some(s) => WhitespaceMode::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => WhitespaceMode::default(),
+		};
+
+		// Extract optional tolerance of moved blocks
+		let ignore_moves = match kwargs.get("ignore_moves") {
+			Some(Value::Bool(ignore_moves)) => *ignore_moves,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"ignore_moves must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional granularity drift is rendered in
+		let diff_granularity = match kwargs.get("diff_granularity").and_then(|v| v.as_str()) {
+			Some(s) => DiffGranularity::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => DiffGranularity::default(),
+		};
+
+		// Extract optional base the current revision is diffed from
+		let diff_base = match kwargs.get("diff_base").and_then(|v| v.as_str()) {
+			Some(s) => DiffBase::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => DiffBase::default(),
+		};
+
+		// Extract optional retry count for clone and fetch operations
+		let retries = match kwargs.get("retries") {
+			Some(value) => {
+				Some(value.as_u64().and_then(|v| u32::try_from(v).ok()).ok_or_else(|| {
+					SourceUiError::InvalidParameter(format!(
+						"retries must be a non-negative integer, got {}",
+						value
+					))
+				})?)
+			}
+			None => None,
+		};
+
+		// Extract the optional environment variable holding the token to authenticate with
+		let cred_env = match kwargs.get("cred_env") {
+			Some(value) => Some(value.as_str().filter(|env| !env.is_empty()).ok_or_else(|| {
+				SourceUiError::InvalidParameter(format!(
+					"cred_env must be the name of an environment variable, got {}",
+					value
+				))
+			})?),
+			None => None,
+		};
+
+		// Extract the optional depth of history cloned and fetched
+		let depth = match kwargs.get("depth") {
+			Some(value) => Some(parse_depth(value)?),
+			None => None,
+		};
+
+		// Extract optional named region of the cited file
+		let region = match kwargs.get("region") {
+			Some(value) => Some(value.as_str().ok_or_else(|| {
+				SourceUiError::InvalidParameter(format!("region must be a string, got {}", value))
+			})?),
+			None => None,
+		};
+
+		// Extract the optional diff the file is expected to have changed by, inline or from a file
+		let expect_diff = match (kwargs.get("expect_diff"), kwargs.get("expect_diff_file")) {
+			(Some(_), Some(_)) => {
+				return Err(SourceUiError::InvalidParameter(
+					"expect_diff cannot be combined with expect_diff_file".to_string(),
+				))
+			}
+			(Some(value), None) => Some(
+				value
+					.as_str()
+					.ok_or_else(|| {
+						SourceUiError::InvalidParameter(format!(
+							"expect_diff must be a string, got {}",
+							value
+						))
+					})?
+					.to_string(),
+			),
+			(None, Some(value)) => {
+				Some(read_expect_diff_file(value.as_str().ok_or_else(|| {
+					SourceUiError::InvalidParameter(format!(
+						"expect_diff_file must be a string, got {}",
+						value
+					))
+				})?)?)
+			}
+			(None, None) => None,
+		};
+
 		// Create the GitSource
-		GitSource::try_new(remote, path, ref_rev, cur_rev, name)
-			.map_err(|e| SourceUiError::Internal(e.into()))
+		let source = GitSource::try_new(remote, path, ref_rev, cur_rev, name)
+			.map(|source| {
+				let source = source
+					.with_sensitivity(sensitivity)
+					.with_filter(filter)
+					.with_encoding(encoding)
+					.with_whitespace(whitespace)
+					.with_ignore_moves(ignore_moves)
+					.with_diff_granularity(diff_granularity)
+					.with_diff_base(diff_base)
+					.with_revisions(
+						revisions.map(|revisions| revisions[2..].to_vec()).unwrap_or_default(),
+					);
+				let source = match expect_diff {
+					Some(expect_diff) => source.with_expect_diff(&expect_diff),
+					None => source,
+				};
+				let source = match cred_env {
+					Some(cred_env) => source.with_credentials(GitCredentials::token_env(cred_env)),
+					None => source,
+				};
+				let source = source.with_depth(depth);
+				match retries {
+					Some(retries) => source.with_retries(retries),
+					None => source,
+				}
+			})
+			.map_err(|e| SourceUiError::Internal(e.into()))?;
+		match region {
+			Some(region) => source
+				.with_region(region)
+				.map_err(|e| SourceUiError::InvalidParameter(e.to_string())),
+			None => Ok(source),
+		}
 	}
 }
 
+/// Parse a list of at least two revisions, e.g. `revisions = ("release/1.x", "release/2.x", "main")`
+fn parse_revisions(value: &Value) -> Result<Vec<String>, SourceUiError> {
+	let revisions = value
+		.as_array()
+		.and_then(|revisions| {
+			revisions
+				.iter()
+				.map(|revision| revision.as_str().filter(|revision| !revision.is_empty()))
+				.map(|revision| revision.map(str::to_string))
+				.collect::<Option<Vec<_>>>()
+		})
+		.filter(|revisions| revisions.len() >= 2);
+	revisions.ok_or_else(|| {
+		SourceUiError::InvalidParameter(format!(
+			"revisions must be a tuple of at least two revisions, got {}",
+			value
+		))
+	})
+}
+
+/// Parse a positive depth of history, given as a number or a string like `"1"`
+fn parse_depth(value: &Value) -> Result<u32, SourceUiError> {
+	let depth = match value {
+		Value::String(s) => s.trim().parse::<u32>().ok(),
+		value => value.as_u64().and_then(|depth| u32::try_from(depth).ok()),
+	};
+	depth.filter(|depth| *depth > 0).ok_or_else(|| {
+		SourceUiError::InvalidParameter(format!(
+			"depth must be a positive number of commits, got {}",
+			value
+		))
+	})
+}
+
+/// Read an expected diff from a file, relative to the manifest of the crate being built
+fn read_expect_diff_file(path: &str) -> Result<String, SourceUiError> {
+	let path = match std::env::var_os("CARGO_MANIFEST_DIR") {
+		Some(manifest_dir) => std::path::PathBuf::from(manifest_dir).join(path),
+		None => std::path::PathBuf::from(path),
+	};
+	std::fs::read_to_string(&path).map_err(|e| {
+		SourceUiError::InvalidParameter(format!(
+			"Failed to read expect_diff_file {}: {}",
+			path.display(),
+			e
+		))
+	})
+}
+
 /// Basic Git URL validation for parse-time checking
 fn is_valid_git_url(url: &str) -> bool {
-	url.starts_with("https://") || url.starts_with("http://") || url.starts_with("git@")
+	url.starts_with("https://")
+		|| url.starts_with("http://")
+		|| url.starts_with("git@")
+		|| url.starts_with("file://")
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use cite_core::Source;
 	use serde_json::json;
 
 	#[test]
@@ -141,11 +368,30 @@ mod tests {
 		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
 		assert_eq!(git_source.remote, "https://github.com/user/repo.git");
 		assert_eq!(git_source.referenced_revision, "abc123");
-		assert_eq!(git_source.current_revision, "def456");
+		assert_eq!(git_source.current_revision.as_deref(), Some("def456"));
 		assert_eq!(git_source.path_pattern.to_string(), "src/main.rs");
 		assert_eq!(git_source.name, "test-name");
 	}
 
+	#[test]
+	fn test_from_kwarg_json_with_local_remote() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("file:///work/monorepo"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.remote, "file:///work/monorepo");
+		assert_eq!(
+			git_source.repository_path().unwrap(),
+			std::path::PathBuf::from("/work/monorepo")
+		);
+
+		kwargs.insert("remote".to_string(), json!("/work/monorepo"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
 	#[test]
 	fn test_from_kwarg_json_legacy_field_names() {
 		let mut kwargs = HashMap::new();
@@ -156,7 +402,7 @@ mod tests {
 
 		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
 		assert_eq!(git_source.referenced_revision, "abc123");
-		assert_eq!(git_source.current_revision, "def456");
+		assert_eq!(git_source.current_revision.as_deref(), Some("def456"));
 	}
 
 	#[test]
@@ -172,6 +418,276 @@ mod tests {
 		assert!(!git_source.name.is_empty());
 	}
 
+	#[test]
+	fn test_from_kwarg_json_with_sensitivity() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+		kwargs.insert("sensitivity".to_string(), json!("additions"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.sensitivity, DiffSensitivity::Additions);
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_ignore_moves() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+		kwargs.insert("ignore_moves".to_string(), json!(true));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert!(git_source.ignore_moves);
+
+		kwargs.insert("ignore_moves".to_string(), json!(1));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_retries() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+		kwargs.insert("retries".to_string(), json!(5));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.repository_builder.retry_policy().retries, 5);
+
+		kwargs.insert("retries".to_string(), json!("many"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_cred_env() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/private.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert!(git_source.repository_builder.credentials().is_default());
+
+		kwargs.insert("cred_env".to_string(), json!("GIT_TOKEN"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			git_source.repository_builder.credentials(),
+			&GitCredentials::token_env("GIT_TOKEN")
+		);
+		assert!(GitSource::is_valid_attr_key("cred_env"));
+
+		for invalid in [json!(""), json!(1)] {
+			kwargs.insert("cred_env".to_string(), invalid);
+			let result = GitSource::from_kwarg_json(&kwargs);
+			assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_depth() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/monorepo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("main"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.repository_builder.depth(), None);
+
+		for depth in [json!("1"), json!(1)] {
+			kwargs.insert("depth".to_string(), depth);
+			let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+			assert_eq!(git_source.repository_builder.depth(), Some(1));
+		}
+		assert!(GitSource::is_valid_attr_key("depth"));
+
+		for invalid in [json!("0"), json!("shallow"), json!(-1), json!(true)] {
+			kwargs.insert("depth".to_string(), invalid);
+			let result = GitSource::from_kwarg_json(&kwargs);
+			assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_region() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/config.rs"));
+		kwargs.insert("region".to_string(), json!("api-default"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.path_pattern.region.as_deref(), Some("api-default"));
+
+		// A region is cited instead of a line range
+		kwargs.insert("path".to_string(), json!("src/config.rs#L1-L10"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_filter() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.filter, ContentFilter::Raw);
+
+		kwargs.insert("filter".to_string(), json!("gitattributes"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.filter, ContentFilter::GitAttributes);
+
+		kwargs.insert("filter".to_string(), json!("smudge"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_encoding() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("docs/legacy.txt"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.encoding, TextEncoding::Auto);
+
+		kwargs.insert("encoding".to_string(), json!("latin-1"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.encoding, TextEncoding::Latin1);
+		assert!(GitSource::is_valid_attr_key("encoding"));
+
+		kwargs.insert("encoding".to_string(), json!("ebcdic"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_whitespace() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.whitespace, WhitespaceMode::Exact);
+
+		kwargs.insert("whitespace".to_string(), json!("ignore"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.whitespace, WhitespaceMode::Ignore);
+		assert!(GitSource::is_valid_attr_key("whitespace"));
+
+		kwargs.insert("whitespace".to_string(), json!("trailing"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_diff_granularity() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("README.md"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.diff_granularity, DiffGranularity::Lines);
+
+		kwargs.insert("diff_granularity".to_string(), json!("word"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.diff_granularity, DiffGranularity::Words);
+		assert!(GitSource::is_valid_attr_key("diff_granularity"));
+
+		kwargs.insert("diff_granularity".to_string(), json!("sentence"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_diff_base() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("feature"));
+		kwargs.insert("path".to_string(), json!("src/main.rs"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.diff_base, DiffBase::Referenced);
+
+		kwargs.insert("diff_base".to_string(), json!("merge-base:main"));
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.diff_base, DiffBase::MergeBase("main".to_string()));
+		assert!(git_source.target_description().ends_with("Diff base: merge-base:main"));
+
+		kwargs.insert("diff_base".to_string(), json!("merge-base"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_revisions() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("path".to_string(), json!("src/compat.rs"));
+		kwargs.insert("revisions".to_string(), json!(["release/1.x", "release/2.x", "main"]));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.referenced_revision, "release/1.x");
+		assert_eq!(git_source.current_revision.as_deref(), Some("release/2.x"));
+		assert_eq!(git_source.revisions, vec!["main".to_string()]);
+		assert!(git_source.target_description().ends_with("Also compared: main"));
+		assert!(GitSource::is_valid_attr_key("revisions"));
+
+		kwargs.insert("revisions".to_string(), json!(["main"]));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+
+		kwargs.insert("revisions".to_string(), json!(["release/1.x", "main"]));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_expect_diff() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("def456"));
+		kwargs.insert("path".to_string(), json!("src/generated.rs"));
+		kwargs.insert("expect_diff".to_string(), json!("-const V: u32 = 1;\n+const V: u32 = 2;\n"));
+
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			git_source.expect_diff.as_deref(),
+			Some("-const V: u32 = 1;\n+const V: u32 = 2;\n")
+		);
+		assert!(git_source.id.as_str().ends_with("_expected_diff"));
+		assert!(GitSource::is_valid_attr_key("expect_diff"));
+
+		// The diff can be read from a file instead, but not both
+		let temp_dir = tempfile::TempDir::new().unwrap();
+		let diff_path = temp_dir.path().join("bump.diff");
+		std::fs::write(&diff_path, "+const V: u32 = 2;\n").unwrap();
+		kwargs.insert("expect_diff_file".to_string(), json!(diff_path.to_string_lossy()));
+		let result = GitSource::from_kwarg_json(&kwargs);
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
+
+		kwargs.remove("expect_diff");
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.expect_diff.as_deref(), Some("+const V: u32 = 2;\n"));
+	}
+
 	#[test]
 	fn test_from_kwarg_json_missing_remote() {
 		let mut kwargs = HashMap::new();
@@ -197,15 +713,20 @@ mod tests {
 	}
 
 	#[test]
-	fn test_from_kwarg_json_missing_cur_rev() {
+	fn test_from_kwarg_json_without_cur_rev() {
 		let mut kwargs = HashMap::new();
 		kwargs.insert("remote".to_string(), json!("https://github.com/user/repo.git"));
 		kwargs.insert("ref_rev".to_string(), json!("abc123"));
 		kwargs.insert("path".to_string(), json!("src/main.rs"));
 
+		// The default branch of the remote is compared
+		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(git_source.current_revision, None);
+		assert!(git_source.target_description().ends_with("Revision: default branch"));
+
+		kwargs.insert("cur_rev".to_string(), json!(3));
 		let result = GitSource::from_kwarg_json(&kwargs);
-		assert!(result.is_err());
-		assert!(matches!(result.unwrap_err(), SourceUiError::MissingParameter(_)));
+		assert!(matches!(result.unwrap_err(), SourceUiError::InvalidParameter(_)));
 	}
 
 	#[test]
@@ -259,7 +780,7 @@ mod tests {
 			"https://github.com/user/repo.git",
 			"src/main.rs",
 			"abc123",
-			"def456",
+			Some("def456"),
 			Some("test-name".to_string()),
 		)
 		.unwrap();
@@ -282,7 +803,7 @@ mod tests {
 			"https://github.com/user/repo.git",
 			"src/main.rs",
 			"abc123",
-			"def456",
+			Some("def456"),
 			None,
 		)
 		.unwrap();
@@ -298,7 +819,7 @@ mod tests {
 			"https://github.com/user/repo.git",
 			"src/main.rs",
 			"abc123",
-			"def456",
+			Some("def456"),
 			Some("test-name".to_string()),
 		)
 		.unwrap();
@@ -350,7 +871,7 @@ mod tests {
 			"https://github.com/user/repo.git",
 			"src/main.rs",
 			"abc123",
-			"def456",
+			Some("def456"),
 			Some("test-name".to_string()),
 		)
 		.unwrap();
@@ -374,7 +895,7 @@ mod tests {
 			"https://github.com/user/repo.git",
 			"src/main.rs",
 			"abc123",
-			"def456",
+			Some("def456"),
 			Some("test-name".to_string()),
 		)
 		.unwrap();
@@ -415,7 +936,7 @@ mod tests {
 		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
 		assert_eq!(git_source.remote, "https://github.com/user/repo.git");
 		assert_eq!(git_source.referenced_revision, "abc123");
-		assert_eq!(git_source.current_revision, "def456");
+		assert_eq!(git_source.current_revision.as_deref(), Some("def456"));
 		assert_eq!(git_source.path_pattern.to_string(), "src/main.rs");
 	}
 
@@ -426,7 +947,7 @@ mod tests {
 			"https://github.com/user/repo.git",
 			"src/main.rs",
 			"abc123",
-			"def456",
+			Some("def456"),
 			Some("test-name".to_string()),
 		)
 		.unwrap();
@@ -446,7 +967,7 @@ mod tests {
 		let git_source = GitSource::from_kwarg_json(&kwargs).unwrap();
 		assert_eq!(git_source.remote, "https://github.com/user/repo.git");
 		assert_eq!(git_source.referenced_revision, "abc123");
-		assert_eq!(git_source.current_revision, "def456");
+		assert_eq!(git_source.current_revision.as_deref(), Some("def456"));
 		assert_eq!(git_source.path_pattern.to_string(), "src/main.rs");
 		assert_eq!(git_source.name, "test-name");
 	}
@@ -0,0 +1,59 @@
+use git2::DiffOptions;
+use serde::{Deserialize, Serialize};
+
+/// Whether changes of whitespace alone count as drift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WhitespaceMode {
+	/// Any change of whitespace is drift
+	#[default]
+	Exact,
+	/// Changes of whitespace are ignored, e.g. reindentation and trailing whitespace, so that
+	/// only changes of the other characters of a line are drift
+	Ignore,
+}
+
+impl WhitespaceMode {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"exact" => Ok(WhitespaceMode::Exact),
+			"ignore" => Ok(WhitespaceMode::Ignore),
+			_ => Err(format!("Invalid whitespace mode: '{}'. Valid values: exact, ignore", s)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			WhitespaceMode::Exact => "exact",
+			WhitespaceMode::Ignore => "ignore",
+		}
+	}
+
+	/// Check if this is the default mode (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == WhitespaceMode::default()
+	}
+
+	/// Apply the mode to the options of a diff
+	pub fn apply(&self, opts: &mut DiffOptions) {
+		let ignore = *self == WhitespaceMode::Ignore;
+		opts.ignore_whitespace(ignore)
+			.ignore_whitespace_change(ignore)
+			.ignore_whitespace_eol(ignore);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_whitespace_mode_from_str() {
+		assert_eq!(WhitespaceMode::from_str("ignore").unwrap(), WhitespaceMode::Ignore);
+		assert_eq!(WhitespaceMode::from_str("Exact").unwrap(), WhitespaceMode::Exact);
+		assert!(WhitespaceMode::default().is_default());
+		assert!(WhitespaceMode::from_str("trailing").is_err());
+	}
+}
@@ -0,0 +1,12 @@
+//! Client configuration, cited by named region
+
+// cite:begin(api-default)
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_RETRIES: u32 = 3;
+// cite:end(api-default)
+
+// cite:begin(user-agent)
+pub fn user_agent() -> &'static str {
+	"client/1.0"
+}
+// cite:end(user-agent)
@@ -0,0 +1,75 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// Selectors of the element a fragment id targets, in order of precedence: an element with a
+/// matching id, then one with a matching name (as in older HTML)
+pub fn fragment_selectors(fragment_id: &str) -> [String; 4] {
+	[
+		format!("#{}", fragment_id),          // #fragment-id
+		format!("[id='{}']", fragment_id),    // [id='fragment-id']
+		format!("[name='{}']", fragment_id),  // [name='fragment-id']
+		format!("a[name='{}']", fragment_id), // a[name='fragment-id']
+	]
+}
+
+/// Check if a document has the element a fragment id targets, e.g. `installation` of a
+/// `#installation` deep link
+pub fn has_anchor(content: &str, anchor: &str) -> bool {
+	let document = Html::parse_document(content);
+	fragment_selectors(anchor)
+		.iter()
+		.filter_map(|selector| Selector::parse(selector).ok())
+		.any(|selector| document.select(&selector).next().is_some())
+}
+
+/// Result of checking that a document still has the anchor a citation requires
+///
+/// Link rot often leaves the page in place while removing the section a deep link points to,
+/// so the anchor is checked instead of comparing the content against a reference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorCheck {
+	/// The required fragment id, without the leading `#`
+	pub anchor: String,
+	/// Whether the current document has the anchor
+	pub found: bool,
+}
+
+impl AnchorCheck {
+	/// Check the current document for the anchor
+	pub fn new(anchor: &str, current_content: &str) -> Self {
+		let anchor = anchor.trim_start_matches('#').to_string();
+		let found = has_anchor(current_content, &anchor);
+		Self { anchor, found }
+	}
+
+	/// Check if the anchor is still in the document
+	pub fn is_found(&self) -> bool {
+		self.found
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_has_anchor() {
+		let document = r#"<h2 id="installation">Installation</h2><a name="legacy">Legacy</a>"#;
+		assert!(has_anchor(document, "installation"));
+		assert!(has_anchor(document, "legacy"));
+		assert!(!has_anchor(document, "usage"));
+
+		// The text of a heading is not its anchor
+		assert!(!has_anchor(document, "Installation"));
+	}
+
+	#[test]
+	fn test_anchor_check() {
+		let check = AnchorCheck::new("#installation", r#"<h2 id="installation">Install</h2>"#);
+		assert_eq!(check.anchor, "installation");
+		assert!(check.is_found());
+
+		let removed = AnchorCheck::new("installation", "<h2>Getting started</h2>");
+		assert!(!removed.is_found());
+	}
+}
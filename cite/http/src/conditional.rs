@@ -0,0 +1,127 @@
+//! Conditional requests for cited URLs whose reference is cached
+//!
+//! The `ETag` and `Last-Modified` validators a page is served with are recorded in the metadata
+//! of its cache entry. Later fetches send them back as `If-None-Match` and `If-Modified-Since`,
+//! so that a server answering `304 Not Modified` spares the download, the cached reference
+//! standing in for the body it did not send.
+
+use std::collections::HashMap;
+
+/// Metadata key of the `ETag` a page was served with
+pub const ETAG_KEY: &str = "etag";
+
+/// Metadata key of the `Last-Modified` date a page was served with
+pub const LAST_MODIFIED_KEY: &str = "last_modified";
+
+/// Validators of a fetched page, which a conditional request sends back
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+	/// The `ETag` of the page, if the server sent one
+	pub etag: Option<String>,
+	/// The `Last-Modified` date of the page, if the server sent one
+	pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+	/// Read the validators from the headers of a response
+	pub fn from_response(headers: &reqwest::header::HeaderMap) -> Self {
+		let header = |name: reqwest::header::HeaderName| {
+			headers
+				.get(name)
+				.and_then(|value| value.to_str().ok())
+				.map(|value| value.to_string())
+		};
+		Self {
+			etag: header(reqwest::header::ETAG),
+			last_modified: header(reqwest::header::LAST_MODIFIED),
+		}
+	}
+
+	/// Read the validators recorded in the metadata of a cache entry
+	pub fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+		Self {
+			etag: metadata.get(ETAG_KEY).cloned(),
+			last_modified: metadata.get(LAST_MODIFIED_KEY).cloned(),
+		}
+	}
+
+	/// Record the validators in the metadata of a cache entry
+	pub fn record(&self, metadata: &mut HashMap<String, String>) {
+		if let Some(etag) = &self.etag {
+			metadata.insert(ETAG_KEY.to_string(), etag.clone());
+		}
+		if let Some(last_modified) = &self.last_modified {
+			metadata.insert(LAST_MODIFIED_KEY.to_string(), last_modified.clone());
+		}
+	}
+
+	/// Check if the server sent no validators, in which case requests cannot be conditional
+	pub fn is_empty(&self) -> bool {
+		self.etag.is_none() && self.last_modified.is_none()
+	}
+
+	/// Get the headers making a request conditional on the page having changed
+	pub fn conditional_headers(&self) -> Vec<(String, String)> {
+		let mut headers = Vec::new();
+		if let Some(etag) = &self.etag {
+			headers.push((reqwest::header::IF_NONE_MATCH.to_string(), etag.clone()));
+		}
+		if let Some(last_modified) = &self.last_modified {
+			headers.push((reqwest::header::IF_MODIFIED_SINCE.to_string(), last_modified.clone()));
+		}
+		headers
+	}
+
+	/// Check if a request with the given headers is conditional, i.e. may be answered with
+	/// `304 Not Modified`
+	pub fn is_conditional(headers: &[(String, String)]) -> bool {
+		headers.iter().any(|(name, _)| {
+			name.eq_ignore_ascii_case(reqwest::header::IF_NONE_MATCH.as_str())
+				|| name.eq_ignore_ascii_case(reqwest::header::IF_MODIFIED_SINCE.as_str())
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+
+	#[test]
+	fn test_validators_round_trip() {
+		let mut headers = HeaderMap::new();
+		headers.insert(ETAG, HeaderValue::from_static("\"33a64df5\""));
+		headers.insert(LAST_MODIFIED, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+		let validators = CacheValidators::from_response(&headers);
+		assert_eq!(validators.etag.as_deref(), Some("\"33a64df5\""));
+
+		let mut metadata = HashMap::new();
+		validators.record(&mut metadata);
+		assert_eq!(CacheValidators::from_metadata(&metadata), validators);
+
+		let conditional = validators.conditional_headers();
+		assert_eq!(
+			conditional,
+			vec![
+				("if-none-match".to_string(), "\"33a64df5\"".to_string()),
+				("if-modified-since".to_string(), "Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+			]
+		);
+		assert!(CacheValidators::is_conditional(&conditional));
+	}
+
+	#[test]
+	fn test_no_validators() {
+		let validators = CacheValidators::from_response(&HeaderMap::new());
+		assert!(validators.is_empty());
+		assert!(validators.conditional_headers().is_empty());
+
+		let mut metadata = HashMap::new();
+		validators.record(&mut metadata);
+		assert!(metadata.is_empty());
+		assert!(!CacheValidators::is_conditional(&[(
+			"Accept".to_string(),
+			"text/html".to_string()
+		)]));
+	}
+}
@@ -0,0 +1,203 @@
+use cite_core::SourceError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Shape the cited content must keep conforming to, checked instead of comparing the content
+/// against a reference
+///
+/// A constrained citation has no snapshot, so it is not cached and only drifts once the
+/// current content stops satisfying the constraint, whatever else about it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentConstraint {
+	/// The content matches a regular expression somewhere
+	MustMatch(String),
+	/// The content contains each of the substrings
+	MustContain(Vec<String>),
+	/// The content is JSON that is valid against a JSON Schema
+	///
+	/// Schemas are checked with `jsonschema`, so every keyword of the draft the schema declares
+	/// applies. References (`$ref`) resolve within the schema only; remote ones are not fetched.
+	JsonSchema(Value),
+}
+
+impl ContentConstraint {
+	/// Parse a JSON Schema from its JSON text
+	pub fn json_schema(schema: &str) -> Result<Self, String> {
+		match serde_json::from_str::<Value>(schema) {
+			Ok(schema @ (Value::Object(_) | Value::Bool(_))) => {
+				validator(&schema)?;
+				Ok(ContentConstraint::JsonSchema(schema))
+			}
+			Ok(schema) => Err(format!("Invalid JSON schema: expected an object, got {}", schema)),
+			Err(e) => Err(format!("Invalid JSON schema: {}", e)),
+		}
+	}
+
+	/// Check the content, returning how it violates the constraint
+	///
+	/// Content that does not satisfy the constraint is reported as violations rather than an
+	/// error, e.g. content that is no longer JSON. Only a constraint that cannot be checked at
+	/// all, such as an invalid regular expression, is an error.
+	pub fn violations(&self, content: &str) -> Result<Vec<String>, SourceError> {
+		match self {
+			ContentConstraint::MustMatch(pattern) => {
+				let regex = Regex::new(pattern).map_err(|e| {
+					SourceError::ContentParsing(format!("Invalid must_match pattern: {}", e))
+				})?;
+				Ok(if regex.is_match(content) {
+					Vec::new()
+				} else {
+					vec![format!("content does not match /{}/", pattern)]
+				})
+			}
+			ContentConstraint::MustContain(substrings) => Ok(substrings
+				.iter()
+				.filter(|substring| !content.contains(substring.as_str()))
+				.map(|substring| format!("content does not contain {:?}", substring))
+				.collect()),
+			ContentConstraint::JsonSchema(schema) => {
+				let value = match serde_json::from_str::<Value>(content) {
+					Ok(value) => value,
+					Err(e) => return Ok(vec![format!("content is not valid JSON: {}", e)]),
+				};
+				let validator = validator(schema).map_err(SourceError::ContentParsing)?;
+				Ok(validator
+					.iter_errors(&value)
+					.map(|error| {
+						let pointer = error.instance_path.to_string();
+						let at = if pointer.is_empty() { "/" } else { pointer.as_str() };
+						format!("{}: {}", at, error)
+					})
+					.collect())
+			}
+		}
+	}
+}
+
+impl std::fmt::Display for ContentConstraint {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ContentConstraint::MustMatch(pattern) => write!(f, "must match /{}/", pattern),
+			ContentConstraint::MustContain(substrings) => {
+				write!(f, "must contain {:?}", substrings)
+			}
+			ContentConstraint::JsonSchema(schema) => {
+				write!(f, "must be valid against the JSON schema {}", schema)
+			}
+		}
+	}
+}
+
+/// Constraint checked against the content currently extracted
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintCheck {
+	/// The constraint of the citation
+	pub constraint: ContentConstraint,
+	/// How the current content violates the constraint, empty if it satisfies it
+	pub violations: Vec<String>,
+}
+
+impl ConstraintCheck {
+	/// Check the current content against the constraint
+	pub fn new(constraint: ContentConstraint, current_content: &str) -> Result<Self, SourceError> {
+		let violations = constraint.violations(current_content)?;
+		Ok(Self { constraint, violations })
+	}
+
+	/// Check if the current content satisfies the constraint
+	pub fn is_satisfied(&self) -> bool {
+		self.violations.is_empty()
+	}
+}
+
+/// Compile a schema, failing on keywords with invalid values such as a malformed `pattern`
+fn validator(schema: &Value) -> Result<jsonschema::Validator, String> {
+	jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON schema: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_must_match() -> Result<(), SourceError> {
+		let constraint = ContentConstraint::MustMatch(r"^v\d+\.\d+\.\d+$".to_string());
+		assert!(constraint.violations("v1.4.2")?.is_empty());
+		assert_eq!(constraint.violations("1.4")?, [r"content does not match /^v\d+\.\d+\.\d+$/"]);
+
+		assert!(ContentConstraint::MustMatch("(unclosed".to_string()).violations("").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_must_contain() -> Result<(), SourceError> {
+		let constraint =
+			ContentConstraint::MustContain(vec!["MUST".to_string(), "SHOULD".to_string()]);
+		assert!(constraint.violations("Clients MUST retry and SHOULD back off.")?.is_empty());
+		assert_eq!(
+			constraint.violations("Clients MUST retry.")?,
+			["content does not contain \"SHOULD\""]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_json_schema() -> Result<(), anyhow::Error> {
+		let constraint = ContentConstraint::json_schema(
+			r#"{
+				"type": "object",
+				"required": ["name", "version"],
+				"properties": {
+					"name": { "type": "string", "minLength": 1 },
+					"version": { "type": "string", "pattern": "^\\d+\\.\\d+\\.\\d+$" },
+					"downloads": { "type": "integer", "minimum": 0 },
+					"tags": { "type": "array", "items": { "enum": ["stable", "beta"] } }
+				}
+			}"#,
+		)
+		.map_err(anyhow::Error::msg)?;
+
+		// Benign changes, e.g. a new property or another version, still satisfy the schema
+		let satisfying =
+			r#"{"name": "cite", "version": "0.2.0", "downloads": 42, "license": "MIT"}"#;
+		assert!(constraint.violations(satisfying)?.is_empty());
+
+		let violating = r#"{"name": "", "downloads": -1.5, "tags": ["stable", "nightly"]}"#;
+		let mut violations = constraint.violations(violating)?;
+		violations.sort();
+		assert_eq!(violations.len(), 4);
+		assert!(violations[0].starts_with("/: ") && violations[0].contains("\"version\""));
+		assert!(violations[1].starts_with("/downloads: "));
+		assert!(violations[2].starts_with("/name: "));
+		assert!(violations[3].starts_with("/tags/1: ") && violations[3].contains("\"nightly\""));
+
+		// Keywords beyond the basic ones are checked too
+		let one_of = ContentConstraint::json_schema(
+			r#"{"oneOf": [{"type": "integer"}, {"minimum": 2}], "not": {"const": 5}}"#,
+		)
+		.map_err(anyhow::Error::msg)?;
+		assert!(one_of.violations("1")?.is_empty());
+		assert_eq!(one_of.violations("3")?.len(), 1);
+		assert_eq!(one_of.violations("5")?.len(), 2);
+
+		// Content that is no longer JSON violates the schema rather than failing to compare
+		let violations = constraint.violations("<html>moved</html>")?;
+		assert!(violations[0].starts_with("content is not valid JSON"));
+
+		assert!(ContentConstraint::json_schema("[1, 2]").is_err());
+		assert!(ContentConstraint::json_schema("{").is_err());
+		assert!(ContentConstraint::json_schema(r#"{"pattern": "(unclosed"}"#).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_constraint_check() -> Result<(), SourceError> {
+		let constraint = ContentConstraint::MustContain(vec!["stable".to_string()]);
+		assert!(ConstraintCheck::new(constraint.clone(), "stable release")?.is_satisfied());
+		assert!(!ConstraintCheck::new(constraint.clone(), "beta release")?.is_satisfied());
+		assert_eq!(constraint.to_string(), "must contain [\"stable\"]");
+		Ok(())
+	}
+}
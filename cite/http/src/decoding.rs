@@ -0,0 +1,98 @@
+use base64::Engine;
+use cite_core::SourceError;
+use serde::{Deserialize, Serialize};
+
+/// How extracted content is encoded, e.g. file bodies in the GitHub contents API are base64
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Decoding {
+	/// The content is compared as extracted
+	#[default]
+	None,
+	/// Standard base64, possibly broken over lines
+	Base64,
+	/// Hexadecimal, in either case
+	Hex,
+}
+
+impl Decoding {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"none" => Ok(Decoding::None),
+			"base64" => Ok(Decoding::Base64),
+			"hex" => Ok(Decoding::Hex),
+			_ => Err(format!("Invalid decoding: '{}'. Valid values: none, base64, hex", s)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Decoding::None => "none",
+			Decoding::Base64 => "base64",
+			Decoding::Hex => "hex",
+		}
+	}
+
+	/// Check if this is the default decoding (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == Decoding::default()
+	}
+
+	/// Decode extracted content, which must decode to UTF-8 text
+	///
+	/// Whitespace is ignored, since encoded content is commonly wrapped or indented.
+	pub fn decode(&self, content: &str) -> Result<String, SourceError> {
+		let encoded = content.split_whitespace().collect::<String>();
+		let bytes = match self {
+			Decoding::None => return Ok(content.to_string()),
+			Decoding::Base64 => base64::engine::general_purpose::STANDARD
+				.decode(&encoded)
+				.map_err(|e| e.to_string()),
+			Decoding::Hex => hex::decode(&encoded).map_err(|e| e.to_string()),
+		}
+		.map_err(|e| {
+			SourceError::ContentParsing(format!(
+				"Failed to decode {} content: {}",
+				self.as_str(),
+				e
+			))
+		})?;
+
+		String::from_utf8(bytes).map_err(|_| {
+			SourceError::ContentParsing(format!(
+				"Decoded {} content is not valid UTF-8",
+				self.as_str()
+			))
+		})
+	}
+}
+
+impl std::fmt::Display for Decoding {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_decode() {
+		assert_eq!(Decoding::Base64.decode("aGVsbG8g\nd29ybGQ=\n").unwrap(), "hello world");
+		assert_eq!(Decoding::Hex.decode("68656C6C6f").unwrap(), "hello");
+		assert_eq!(Decoding::None.decode("aGVsbG8=").unwrap(), "aGVsbG8=");
+
+		assert!(Decoding::Base64.decode("not base64!").is_err());
+		assert!(Decoding::Hex.decode("ff").is_err());
+	}
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(Decoding::from_str("BASE64"), Ok(Decoding::Base64));
+		assert_eq!(Decoding::from_str("hex"), Ok(Decoding::Hex));
+		assert!(Decoding::from_str("rot13").unwrap_err().contains("Valid values"));
+	}
+}
@@ -0,0 +1,263 @@
+use cite_core::SourceError;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Standard introspection query, limited to what describes the fields of the schema's types
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery { __schema { types { kind name \
+	fields(includeDeprecated: true) { name args { name type { ...TypeRef } } type { ...TypeRef } } \
+	inputFields { name type { ...TypeRef } } enumValues(includeDeprecated: true) { name } } } } \
+	fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name ofType { kind name \
+	ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } } } } } }";
+
+/// Body of the POST request sending the introspection query
+pub fn introspection_body() -> String {
+	serde_json::json!({ "query": INTROSPECTION_QUERY }).to_string()
+}
+
+/// Render the fields of a type of an introspection response, one `name(args): Type` line per
+/// field sorted by name, or only the line of the given field
+///
+/// Input fields and enum values are rendered like fields, the latter without a type. A type or
+/// field missing from the schema renders as empty content, so that its removal is drift rather
+/// than an error.
+pub fn extract_schema(
+	response: &str,
+	type_name: &str,
+	field: Option<&str>,
+) -> Result<String, SourceError> {
+	let response: Value = serde_json::from_str(response).map_err(|e| {
+		SourceError::ContentParsing(format!("Invalid GraphQL introspection response: {}", e))
+	})?;
+	if let Some(errors) = response.get("errors").filter(|errors| !errors.is_null()) {
+		return Err(SourceError::ContentParsing(format!(
+			"GraphQL introspection failed: {}",
+			errors
+		)));
+	}
+	let types = response.pointer("/data/__schema/types").and_then(Value::as_array);
+	let types = types.ok_or_else(|| {
+		SourceError::ContentParsing(
+			"GraphQL introspection response has no data.__schema.types".to_string(),
+		)
+	})?;
+
+	let Some(schema_type) = types.iter().find(|t| t["name"].as_str() == Some(type_name)) else {
+		return Ok(String::new());
+	};
+	let mut lines = ["fields", "inputFields", "enumValues"]
+		.iter()
+		.filter_map(|kind| schema_type[kind].as_array())
+		.flatten()
+		.filter(|f| field.is_none_or(|field| f["name"].as_str() == Some(field)))
+		.map(render_field)
+		.collect::<Vec<_>>();
+	lines.sort();
+	Ok(lines.join("\n"))
+}
+
+/// Render a field as `name(arg: Type, ...): Type`
+fn render_field(field: &Value) -> String {
+	let name = field["name"].as_str().unwrap_or_default();
+	let args = field["args"]
+		.as_array()
+		.filter(|args| !args.is_empty())
+		.map(|args| {
+			let args = args
+				.iter()
+				.map(|arg| {
+					format!(
+						"{}: {}",
+						arg["name"].as_str().unwrap_or_default(),
+						render_type(&arg["type"])
+					)
+				})
+				.collect::<Vec<_>>();
+			format!("({})", args.join(", "))
+		})
+		.unwrap_or_default();
+	match field.get("type") {
+		Some(field_type) => format!("{}{}: {}", name, args, render_type(field_type)),
+		None => name.to_string(),
+	}
+}
+
+/// Render a type reference in SDL notation, e.g. `[String!]!`
+fn render_type(type_ref: &Value) -> String {
+	match type_ref["kind"].as_str() {
+		Some("NON_NULL") => format!("{}!", render_type(&type_ref["ofType"])),
+		Some("LIST") => format!("[{}]", render_type(&type_ref["ofType"])),
+		_ => type_ref["name"].as_str().unwrap_or_default().to_string(),
+	}
+}
+
+/// Change to a field of a cited GraphQL type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+	/// A field was added, which existing clients are unaffected by
+	Added { field: String, signature: String },
+	/// A field was removed
+	Removed { field: String, signature: String },
+	/// The arguments or type of a field changed
+	Changed { field: String, from: String, to: String },
+}
+
+impl SchemaChange {
+	/// Check if the change can break existing clients, i.e. anything but an added field
+	pub fn is_breaking(&self) -> bool {
+		!matches!(self, SchemaChange::Added { .. })
+	}
+}
+
+impl std::fmt::Display for SchemaChange {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SchemaChange::Added { signature, .. } => write!(f, "field added: {}", signature),
+			SchemaChange::Removed { signature, .. } => {
+				write!(f, "field removed (breaking): {}", signature)
+			}
+			SchemaChange::Changed { field, from, to } => {
+				write!(f, "field changed (breaking): {} from {} to {}", field, from, to)
+			}
+		}
+	}
+}
+
+/// Compare the rendered fields of the referenced and current schema
+pub fn schema_changes(referenced: &str, current: &str) -> Vec<SchemaChange> {
+	let fields = |schema: &str| {
+		schema
+			.lines()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| (field_name(line).to_string(), line.to_string()))
+			.collect::<BTreeMap<_, _>>()
+	};
+	let referenced = fields(referenced);
+	let current = fields(current);
+
+	let mut changes = Vec::new();
+	for (field, signature) in &referenced {
+		match current.get(field) {
+			None => changes
+				.push(SchemaChange::Removed { field: field.clone(), signature: signature.clone() }),
+			Some(current) if current != signature => changes.push(SchemaChange::Changed {
+				field: field.clone(),
+				from: signature[field.len()..].trim_start_matches(": ").to_string(),
+				to: current[field.len()..].trim_start_matches(": ").to_string(),
+			}),
+			Some(_) => {}
+		}
+	}
+	for (field, signature) in &current {
+		if !referenced.contains_key(field) {
+			changes
+				.push(SchemaChange::Added { field: field.clone(), signature: signature.clone() });
+		}
+	}
+	changes
+}
+
+/// Get the name of a rendered field, i.e. up to its arguments or type
+fn field_name(line: &str) -> &str {
+	let end = line.find(['(', ':']).unwrap_or(line.len());
+	&line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn response(email_type: &str, extra_field: bool) -> String {
+		let mut fields = vec![
+			serde_json::json!({
+				"name": "id",
+				"args": [],
+				"type": { "kind": "NON_NULL", "name": null, "ofType": { "kind": "SCALAR", "name": "ID" } }
+			}),
+			serde_json::json!({
+				"name": "email",
+				"args": [{ "name": "masked", "type": { "kind": "SCALAR", "name": "Boolean" } }],
+				"type": { "kind": "SCALAR", "name": email_type }
+			}),
+		];
+		if extra_field {
+			fields.push(serde_json::json!({
+				"name": "avatars",
+				"args": [],
+				"type": { "kind": "LIST", "name": null, "ofType": { "kind": "SCALAR", "name": "String" } }
+			}));
+		}
+		serde_json::json!({
+			"data": { "__schema": { "types": [
+				{ "kind": "OBJECT", "name": "Query", "fields": [] },
+				{ "kind": "OBJECT", "name": "User", "fields": fields }
+			] } }
+		})
+		.to_string()
+	}
+
+	#[test]
+	fn test_introspection_body() -> Result<(), serde_json::Error> {
+		let body: Value = serde_json::from_str(&introspection_body())?;
+		assert!(body["query"].as_str().is_some_and(|query| query.contains("__schema")));
+		Ok(())
+	}
+
+	#[test]
+	fn test_extract_schema() -> Result<(), SourceError> {
+		let response = response("String", true);
+		assert_eq!(
+			extract_schema(&response, "User", None)?,
+			"avatars: [String]\nemail(masked: Boolean): String\nid: ID!"
+		);
+		assert_eq!(
+			extract_schema(&response, "User", Some("email"))?,
+			"email(masked: Boolean): String"
+		);
+
+		// Missing types and fields render as empty content
+		assert_eq!(extract_schema(&response, "Order", None)?, "");
+		assert_eq!(extract_schema(&response, "User", Some("phone"))?, "");
+
+		// Responses that are not introspection results cannot be compared
+		assert!(extract_schema(r#"{"errors": [{"message": "denied"}]}"#, "User", None).is_err());
+		assert!(extract_schema("<html></html>", "User", None).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_additive_schema_changes() -> Result<(), SourceError> {
+		let referenced = extract_schema(&response("String", false), "User", None)?;
+		let current = extract_schema(&response("String", true), "User", None)?;
+
+		let changes = schema_changes(&referenced, &current);
+		assert_eq!(
+			changes,
+			[SchemaChange::Added {
+				field: "avatars".to_string(),
+				signature: "avatars: [String]".to_string()
+			}]
+		);
+		assert!(!changes[0].is_breaking());
+		assert_eq!(changes[0].to_string(), "field added: avatars: [String]");
+		Ok(())
+	}
+
+	#[test]
+	fn test_breaking_schema_changes() -> Result<(), SourceError> {
+		let referenced = extract_schema(&response("String", true), "User", None)?;
+		let current = extract_schema(&response("EmailAddress", false), "User", None)?;
+
+		let changes = schema_changes(&referenced, &current);
+		assert_eq!(changes.len(), 2);
+		assert!(changes.iter().all(SchemaChange::is_breaking));
+		assert_eq!(changes[0].to_string(), "field removed (breaking): avatars: [String]");
+		assert_eq!(
+			changes[1].to_string(),
+			"field changed (breaking): email from (masked: Boolean): String to (masked: Boolean): EmailAddress"
+		);
+
+		// Unchanged schemas have no changes
+		assert!(schema_changes(&referenced, &referenced).is_empty());
+		Ok(())
+	}
+}
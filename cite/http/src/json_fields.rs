@@ -0,0 +1,210 @@
+//! Comparison of selected fields of JSON content
+//!
+//! JSON API responses often carry volatile fields (timestamps, request ids) next to the values
+//! a citation cares about. Rather than transforming the response, a citation can list the
+//! [JSON pointers](https://www.rfc-editor.org/rfc/rfc6901) to compare (`include_fields`) or to
+//! ignore (`exclude_fields`). Both the referenced and the current content are projected to one
+//! `pointer: value` line per leaf field before diffing, so drift is reported per field.
+
+use cite_core::SourceError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The JSON fields that participate in the comparison of a citation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonFields {
+	/// Pointers of the compared fields; empty compares the whole document
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub include: Vec<String>,
+	/// Pointers of the ignored fields, including everything beneath them
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub exclude: Vec<String>,
+}
+
+impl JsonFields {
+	/// Create a field selection, validating the pointers
+	pub fn new(include: Vec<String>, exclude: Vec<String>) -> Result<Self, String> {
+		for pointer in include.iter().chain(&exclude) {
+			if !pointer.is_empty() && !pointer.starts_with('/') {
+				return Err(format!(
+					"Invalid JSON pointer: '{}'. Pointers must be empty or start with '/'",
+					pointer
+				));
+			}
+		}
+		Ok(Self { include, exclude })
+	}
+
+	/// Check if no fields are selected, i.e. the content is compared as is
+	pub fn is_empty(&self) -> bool {
+		self.include.is_empty() && self.exclude.is_empty()
+	}
+
+	/// Get the selected leaf fields of a JSON document by pointer
+	///
+	/// Included pointers missing from the document map to `None`, so that a field disappearing
+	/// counts as drift.
+	pub fn fields(&self, content: &str) -> Result<BTreeMap<String, Option<Value>>, SourceError> {
+		let document: Value = serde_json::from_str(content).map_err(|e| {
+			SourceError::ContentParsing(format!(
+				"Content is not valid JSON for field selection: {}",
+				e
+			))
+		})?;
+
+		let mut fields = BTreeMap::new();
+		if self.include.is_empty() {
+			flatten(&document, String::new(), &mut fields);
+		} else {
+			for pointer in &self.include {
+				match document.pointer(pointer) {
+					Some(value) => flatten(value, pointer.clone(), &mut fields),
+					None => {
+						fields.insert(pointer.clone(), None);
+					}
+				}
+			}
+		}
+
+		fields.retain(|pointer, _| !self.is_excluded(pointer));
+		Ok(fields)
+	}
+
+	/// Project a JSON document to one `pointer: value` line per selected field
+	pub fn project(&self, content: &str) -> Result<String, SourceError> {
+		Ok(self
+			.fields(content)?
+			.iter()
+			.map(|(pointer, value)| match value {
+				Some(value) => format!("{}: {}\n", pointer, value),
+				None => format!("{}: (missing)\n", pointer),
+			})
+			.collect())
+	}
+
+	/// Get the pointers of the selected fields that differ between two documents
+	pub fn changed_fields(
+		&self,
+		referenced: &str,
+		current: &str,
+	) -> Result<Vec<String>, SourceError> {
+		let referenced = self.fields(referenced)?;
+		let current = self.fields(current)?;
+
+		let mut changed = referenced
+			.iter()
+			.filter(|(pointer, value)| current.get(*pointer) != Some(value))
+			.map(|(pointer, _)| pointer.clone())
+			.chain(current.keys().filter(|pointer| !referenced.contains_key(*pointer)).cloned())
+			.collect::<Vec<_>>();
+		changed.sort();
+		Ok(changed)
+	}
+
+	fn is_excluded(&self, pointer: &str) -> bool {
+		self.exclude.iter().any(|excluded| {
+			pointer == excluded
+				|| pointer
+					.strip_prefix(excluded.as_str())
+					.is_some_and(|rest| rest.starts_with('/'))
+		})
+	}
+}
+
+/// Collect the leaf values beneath a value, keyed by pointer
+///
+/// Empty objects and arrays are leaves, so that they still take part in the comparison.
+fn flatten(value: &Value, pointer: String, fields: &mut BTreeMap<String, Option<Value>>) {
+	match value {
+		Value::Object(members) if !members.is_empty() => {
+			for (key, member) in members {
+				let key = key.replace('~', "~0").replace('/', "~1");
+				flatten(member, format!("{}/{}", pointer, key), fields);
+			}
+		}
+		Value::Array(elements) if !elements.is_empty() => {
+			for (index, element) in elements.iter().enumerate() {
+				flatten(element, format!("{}/{}", pointer, index), fields);
+			}
+		}
+		leaf => {
+			fields.insert(pointer, Some(leaf.clone()));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const REFERENCED: &str = r#"{
+		"timestamp": "2025-01-01T00:00:00Z",
+		"release": { "version": "1.78.0", "channel": "stable" },
+		"mirrors": ["a", "b"]
+	}"#;
+
+	const CURRENT: &str = r#"{
+		"timestamp": "2025-02-01T12:30:00Z",
+		"release": { "version": "1.79.0", "channel": "stable" },
+		"mirrors": ["a", "b"]
+	}"#;
+
+	fn strings(values: &[&str]) -> Vec<String> {
+		values.iter().map(|value| String::from(*value)).collect()
+	}
+
+	#[test]
+	fn test_exclude_volatile_field() -> Result<(), SourceError> {
+		let fields = JsonFields::new(Vec::new(), strings(&["/timestamp"])).unwrap();
+
+		assert_eq!(fields.changed_fields(REFERENCED, CURRENT)?, ["/release/version"]);
+
+		// Only the timestamp changed, so nothing drifted
+		let current = CURRENT.replace("1.79.0", "1.78.0");
+		assert!(fields.changed_fields(REFERENCED, &current)?.is_empty());
+		assert_eq!(fields.project(REFERENCED)?, fields.project(&current)?);
+		Ok(())
+	}
+
+	#[test]
+	fn test_include_fields() -> Result<(), SourceError> {
+		let fields =
+			JsonFields::new(strings(&["/release/channel", "/mirrors"]), Vec::new()).unwrap();
+
+		assert!(fields.changed_fields(REFERENCED, CURRENT)?.is_empty());
+		assert_eq!(
+			fields.project(CURRENT)?,
+			"/mirrors/0: \"a\"\n/mirrors/1: \"b\"\n/release/channel: \"stable\"\n"
+		);
+
+		// A removed field is drift
+		let current = CURRENT.replace(r#", "channel": "stable""#, "");
+		assert_eq!(fields.changed_fields(REFERENCED, &current)?, ["/release/channel"]);
+		assert!(fields.project(&current)?.contains("/release/channel: (missing)"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_exclude_within_included_field() -> Result<(), SourceError> {
+		let fields =
+			JsonFields::new(strings(&["/release"]), strings(&["/release/version"])).unwrap();
+		assert!(fields.changed_fields(REFERENCED, CURRENT)?.is_empty());
+
+		// Excluding a pointer does not exclude its siblings sharing a prefix
+		let fields = JsonFields::new(Vec::new(), strings(&["/release/ver"])).unwrap();
+		assert!(fields
+			.changed_fields(REFERENCED, CURRENT)?
+			.contains(&"/release/version".to_string()));
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_pointers_and_content() {
+		assert!(JsonFields::new(strings(&["timestamp"]), Vec::new()).is_err());
+		assert!(JsonFields::new(Vec::new(), strings(&[""])).is_ok());
+
+		let fields = JsonFields::new(Vec::new(), strings(&["/timestamp"])).unwrap();
+		assert!(matches!(fields.project("<html></html>"), Err(SourceError::ContentParsing(_))));
+	}
+}
@@ -0,0 +1,363 @@
+//! A small JSONPath evaluator for citing values of JSON API responses
+//!
+//! Supports the subset of JSONPath needed to select a stable value from a document:
+//! - `$` the root, `.name` and `['name']` members, `[0]` (or `[-1]`) array indices, `*` wildcards
+//! - `[?(@.field == 'value')]` filters over array elements (and object members), with `==`
+//!   or `!=` against a string, number, boolean or `null`, or `[?(@.field)]` to test existence
+//!
+//! Filters let a citation select an element by a field value, e.g.
+//! `$.releases[?(@.channel=='stable')].version`, which keeps working when the element moves
+//! within the array.
+
+use cite_core::SourceError;
+use serde_json::Value;
+
+/// A step of a parsed JSONPath
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+	/// Member of an object
+	Member(String),
+	/// Element of an array; negative indices count from the end
+	Index(i64),
+	/// All members of an object or elements of an array
+	Wildcard,
+	/// Members or elements matching a filter
+	Filter(Filter),
+}
+
+/// A `?(...)` filter expression
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+	/// Member path from the current element (`@`)
+	path: Vec<String>,
+	/// Comparison; a filter without one tests that the path exists
+	comparison: Option<(Comparator, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparator {
+	Equal,
+	NotEqual,
+}
+
+impl Filter {
+	fn matches(&self, value: &Value) -> bool {
+		let selected = self.path.iter().try_fold(value, |value, member| value.get(member));
+		match (&self.comparison, selected) {
+			(None, selected) => selected.is_some(),
+			(Some((Comparator::Equal, expected)), Some(selected)) => selected == expected,
+			(Some((Comparator::NotEqual, expected)), Some(selected)) => selected != expected,
+			(Some(_), None) => false,
+		}
+	}
+}
+
+/// Evaluate a JSONPath against a JSON document, returning the content of the single match
+///
+/// Strings are returned as-is and other values as compact JSON with sorted keys, so that the
+/// reference does not change when a server reorders members. No match yields an empty
+/// string, like a regex without matches; several matches are an error, since a citation must
+/// refer to one value.
+pub fn extract_json_path(content: &str, path: &str) -> Result<String, SourceError> {
+	let segments = parse(path)?;
+	let document: Value = serde_json::from_str(content).map_err(|e| {
+		SourceError::ContentParsing(format!("Content is not valid JSON for '{}': {}", path, e))
+	})?;
+
+	let mut selected = vec![&document];
+	for segment in &segments {
+		selected = selected.into_iter().flat_map(|value| select(value, segment)).collect();
+	}
+
+	match selected.as_slice() {
+		[] => Ok(String::new()),
+		[Value::String(s)] => Ok(s.clone()),
+		[value] => Ok(canonical_json(value)),
+		values => Err(SourceError::ContentParsing(format!(
+			"JSONPath '{}' matched {} values; refine it to select exactly one",
+			path,
+			values.len()
+		))),
+	}
+}
+
+/// Serialize a value as compact JSON with the members of every object sorted by name
+///
+/// serde_json keeps members sorted unless a crate of the build enables its `preserve_order`
+/// feature, which Cargo unifies across the dependency graph and which keeps members in the
+/// order of the document. Members are sorted here so that the selection is canonical either way.
+fn canonical_json(value: &Value) -> String {
+	match value {
+		Value::Object(members) => {
+			let mut members: Vec<_> = members.iter().collect();
+			members.sort_by_key(|(name, _)| *name);
+			let members = members
+				.into_iter()
+				.map(|(name, value)| {
+					format!("{}:{}", Value::from(name.as_str()), canonical_json(value))
+				})
+				.collect::<Vec<_>>();
+			format!("{{{}}}", members.join(","))
+		}
+		Value::Array(elements) => {
+			format!("[{}]", elements.iter().map(canonical_json).collect::<Vec<_>>().join(","))
+		}
+		value => value.to_string(),
+	}
+}
+
+/// Apply a segment to a value
+fn select<'a>(value: &'a Value, segment: &Segment) -> Vec<&'a Value> {
+	let children = || -> Vec<&'a Value> {
+		match value {
+			Value::Array(elements) => elements.iter().collect(),
+			Value::Object(members) => members.values().collect(),
+			_ => Vec::new(),
+		}
+	};
+
+	match segment {
+		Segment::Member(name) => value.get(name).into_iter().collect(),
+		Segment::Index(index) => {
+			let elements = match value.as_array() {
+				Some(elements) => elements,
+				None => return Vec::new(),
+			};
+			let index = if *index < 0 { elements.len() as i64 + index } else { *index };
+			usize::try_from(index)
+				.ok()
+				.and_then(|index| elements.get(index))
+				.into_iter()
+				.collect()
+		}
+		Segment::Wildcard => children(),
+		Segment::Filter(filter) => {
+			children().into_iter().filter(|value| filter.matches(value)).collect()
+		}
+	}
+}
+
+/// Parse a JSONPath into segments
+fn parse(path: &str) -> Result<Vec<Segment>, SourceError> {
+	let invalid = |reason: &str| {
+		SourceError::ContentParsing(format!("Invalid JSONPath '{}': {}", path, reason))
+	};
+
+	let mut rest = path.trim().strip_prefix('$').ok_or_else(|| invalid("must start with '$'"))?;
+	let mut segments = Vec::new();
+
+	while !rest.is_empty() {
+		if rest.starts_with("..") {
+			return Err(invalid("recursive descent ('..') is not supported"));
+		} else if let Some(after_dot) = rest.strip_prefix('.') {
+			let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+			let name = &after_dot[..end];
+			segments.push(match name {
+				"" => return Err(invalid("expected a member name after '.'")),
+				"*" => Segment::Wildcard,
+				name => Segment::Member(name.to_string()),
+			});
+			rest = &after_dot[end..];
+		} else if let Some(after_bracket) = rest.strip_prefix('[') {
+			let (segment, after_segment) = parse_bracket(after_bracket).map_err(|e| invalid(&e))?;
+			segments.push(segment);
+			rest = after_segment;
+		} else {
+			return Err(invalid(&format!("unexpected '{}'", rest)));
+		}
+	}
+
+	Ok(segments)
+}
+
+/// Parse the contents of a `[...]` segment, returning the segment and the rest of the path
+fn parse_bracket(input: &str) -> Result<(Segment, &str), String> {
+	if let Some(filter) = input.strip_prefix("?(") {
+		let end = find_unquoted(filter, ")]").ok_or("unterminated filter")?;
+		let segment = Segment::Filter(parse_filter(filter[..end].trim())?);
+		return Ok((segment, &filter[end + 2..]));
+	}
+
+	let end = find_unquoted(input, "]").ok_or("unterminated '['")?;
+	let inner = input[..end].trim();
+	let segment = if inner == "*" {
+		Segment::Wildcard
+	} else if let Some(name) = parse_quoted(inner) {
+		Segment::Member(name)
+	} else {
+		Segment::Index(inner.parse().map_err(|_| format!("invalid index '{}'", inner))?)
+	};
+
+	Ok((segment, &input[end + 1..]))
+}
+
+/// Parse a filter expression such as `@.name == 'stable'`
+fn parse_filter(input: &str) -> Result<Filter, String> {
+	let operator = [("==", Comparator::Equal), ("!=", Comparator::NotEqual)].into_iter().find_map(
+		|(operator, comparator)| find_unquoted(input, operator).map(|index| (index, comparator)),
+	);
+	let (operand, comparison) = match operator {
+		Some((index, comparator)) => {
+			let literal = parse_literal(input[index + 2..].trim())?;
+			(input[..index].trim(), Some((comparator, literal)))
+		}
+		None => (input, None),
+	};
+
+	let mut rest = operand.strip_prefix('@').ok_or("filters must start with '@'")?;
+	let mut path = Vec::new();
+	while !rest.is_empty() {
+		if let Some(after_dot) = rest.strip_prefix('.') {
+			let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+			if end == 0 {
+				return Err("expected a member name after '.' in filter".to_string());
+			}
+			path.push(after_dot[..end].to_string());
+			rest = &after_dot[end..];
+		} else if let Some(after_bracket) = rest.strip_prefix('[') {
+			let end = find_unquoted(after_bracket, "]").ok_or("unterminated '[' in filter")?;
+			let name = parse_quoted(after_bracket[..end].trim())
+				.ok_or("filters only support quoted member names in brackets")?;
+			path.push(name);
+			rest = &after_bracket[end + 1..];
+		} else {
+			return Err(format!("unexpected '{}' in filter", rest));
+		}
+	}
+
+	Ok(Filter { path, comparison })
+}
+
+/// Parse a filter literal: a quoted string, number, boolean or `null`
+fn parse_literal(input: &str) -> Result<Value, String> {
+	if let Some(s) = parse_quoted(input) {
+		return Ok(Value::String(s));
+	}
+	serde_json::from_str(input).map_err(|_| format!("invalid filter value '{}'", input))
+}
+
+/// Parse a single- or double-quoted string
+fn parse_quoted(input: &str) -> Option<String> {
+	let quote = input.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+	let inner = input.strip_prefix(quote)?.strip_suffix(quote)?;
+	Some(inner.to_string())
+}
+
+/// Find a pattern outside of quoted strings
+fn find_unquoted(input: &str, pattern: &str) -> Option<usize> {
+	let mut quote = None;
+	for (index, c) in input.char_indices() {
+		match quote {
+			Some(q) => {
+				if c == q {
+					quote = None;
+				}
+			}
+			None if c == '\'' || c == '"' => quote = Some(c),
+			None if input[index..].starts_with(pattern) => return Some(index),
+			None => {}
+		}
+	}
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RELEASES: &str = r#"{
+		"releases": [
+			{ "channel": "nightly", "version": "1.80.0-nightly" },
+			{ "channel": "stable", "version": "1.78.0", "lts": true },
+			{ "channel": "beta", "version": "1.79.0-beta.1" }
+		]
+	}"#;
+
+	const RELEASES_REORDERED: &str = r#"{
+		"releases": [
+			{ "channel": "stable", "version": "1.78.0", "lts": true },
+			{ "channel": "beta", "version": "1.79.0-beta.1" },
+			{ "channel": "nightly", "version": "1.80.0-nightly" }
+		]
+	}"#;
+
+	#[test]
+	fn test_filter_is_stable_across_positions() -> Result<(), SourceError> {
+		for path in [
+			"$.releases[?(@.channel=='stable')].version",
+			"$['releases'][?(@['channel'] == \"stable\")].version",
+		] {
+			assert_eq!(extract_json_path(RELEASES, path)?, "1.78.0");
+			assert_eq!(extract_json_path(RELEASES_REORDERED, path)?, "1.78.0");
+		}
+
+		// The positional equivalent depends on the order
+		assert_eq!(extract_json_path(RELEASES, "$.releases[1].version")?, "1.78.0");
+		assert_ne!(extract_json_path(RELEASES_REORDERED, "$.releases[1].version")?, "1.78.0");
+		Ok(())
+	}
+
+	#[test]
+	fn test_filter_selects_whole_element() -> Result<(), SourceError> {
+		assert_eq!(
+			extract_json_path(RELEASES, "$.releases[?(@.lts == true)]")?,
+			r#"{"channel":"stable","lts":true,"version":"1.78.0"}"#
+		);
+		assert_eq!(extract_json_path(RELEASES, "$.releases[?(@.lts)].channel")?, "stable");
+		Ok(())
+	}
+
+	#[test]
+	fn test_selected_value_has_sorted_keys() -> Result<(), SourceError> {
+		let reordered =
+			r#"{"data": {"version": "2.0", "build": {"z": [{"b": 1, "a": "\"x\""}], "y": null}}}"#;
+		let canonical = r#"{"build":{"y":null,"z":[{"a":"\"x\"","b":1}]},"version":"2.0"}"#;
+		assert_eq!(extract_json_path(reordered, "$.data")?, canonical);
+		assert_eq!(extract_json_path(reordered, "$.data.version")?, "2.0");
+		Ok(())
+	}
+
+	#[test]
+	fn test_canonical_json_sorts_insertion_ordered_members() {
+		// With preserve_order, members stay in the order they are inserted in
+		let mut inner = serde_json::Map::new();
+		inner.insert("z".to_string(), Value::from(1));
+		inner.insert("a".to_string(), Value::from(2));
+		let mut outer = serde_json::Map::new();
+		outer.insert("list".to_string(), Value::Array(vec![Value::Object(inner)]));
+		outer.insert("id".to_string(), Value::from("x"));
+		assert_eq!(canonical_json(&Value::Object(outer)), r#"{"id":"x","list":[{"a":2,"z":1}]}"#);
+	}
+
+	#[test]
+	fn test_zero_and_multiple_matches() {
+		// No match is a no-match
+		let result = extract_json_path(RELEASES, "$.releases[?(@.channel=='lts')].version");
+		assert_eq!(result.unwrap(), "");
+
+		// Several matches are ambiguous
+		let result = extract_json_path(RELEASES, "$.releases[?(@.channel!='stable')].version");
+		match result {
+			Err(SourceError::ContentParsing(message)) => {
+				assert!(message.contains("matched 2 values"));
+			}
+			other => panic!("expected an ambiguous match error, got {:?}", other),
+		}
+		assert!(extract_json_path(RELEASES, "$.releases[*].version").is_err());
+	}
+
+	#[test]
+	fn test_indices_and_invalid_paths() {
+		assert_eq!(extract_json_path(RELEASES, "$.releases[-1].channel").unwrap(), "beta");
+		assert_eq!(extract_json_path(RELEASES, "$.releases[9].channel").unwrap(), "");
+
+		for path in ["releases", "$..version", "$.releases[", "$.releases[?(@.channel=='stable')"] {
+			assert!(
+				matches!(extract_json_path(RELEASES, path), Err(SourceError::ContentParsing(_))),
+				"{} should be invalid",
+				path
+			);
+		}
+		assert!(extract_json_path("<html></html>", "$.releases").is_err());
+	}
+}
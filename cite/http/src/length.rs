@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+/// Unit in which the length of extracted content is counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthUnit {
+	/// Whitespace-separated words
+	#[default]
+	Words,
+	/// Unicode characters
+	Chars,
+}
+
+impl LengthUnit {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.trim().to_lowercase().as_str() {
+			"word" | "words" => Ok(LengthUnit::Words),
+			"char" | "chars" | "character" | "characters" => Ok(LengthUnit::Chars),
+			_ => Err(format!("Invalid length unit: '{}'. Expected 'words' or 'chars'", s.trim())),
+		}
+	}
+
+	/// Count the length of the given content in this unit
+	pub fn count(&self, content: &str) -> usize {
+		match self {
+			LengthUnit::Words => content.split_whitespace().count(),
+			LengthUnit::Chars => content.chars().count(),
+		}
+	}
+}
+
+impl std::fmt::Display for LengthUnit {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LengthUnit::Words => write!(f, "words"),
+			LengthUnit::Chars => write!(f, "chars"),
+		}
+	}
+}
+
+/// Range a citation expects the length of the extracted content to stay within
+///
+/// Written as `200..800`, optionally followed by the unit, e.g. `200..800 words` or
+/// `1000.. chars`. Either bound may be left out and the unit defaults to words, so that a cited
+/// section only drifts when it is gutted or bloated, whatever its exact text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedLength {
+	/// The inclusive lower bound, unbounded if none
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub min: Option<usize>,
+	/// The inclusive upper bound, unbounded if none
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub max: Option<usize>,
+	/// The unit the bounds are given in
+	#[serde(default)]
+	pub unit: LengthUnit,
+}
+
+impl ExpectedLength {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		let invalid = || {
+			format!(
+				"Invalid expected length: '{}'. Expected a range with an optional unit, e.g. 200..800, 200..800 words or 1000.. chars",
+				s
+			)
+		};
+
+		let s = s.trim();
+		let (range, unit) = match s.split_once(char::is_whitespace) {
+			Some((range, unit)) => (range, LengthUnit::from_str(unit)?),
+			None => (s, LengthUnit::default()),
+		};
+		let (min, max) = range.split_once("..").ok_or_else(invalid)?;
+		let bound = |bound: &str| match bound.trim() {
+			"" => Ok(None),
+			bound => bound.parse::<usize>().map(Some).map_err(|_| invalid()),
+		};
+		let (min, max) = (bound(min)?, bound(max.strip_prefix('=').unwrap_or(max))?);
+
+		match (min, max) {
+			(None, None) => Err(invalid()),
+			(Some(min), Some(max)) if min > max => Err(format!(
+				"Invalid expected length: '{}'. The lower bound must not exceed the upper bound",
+				s
+			)),
+			_ => Ok(Self { min, max, unit }),
+		}
+	}
+
+	/// Check if the given length is within the bounds
+	pub fn contains(&self, length: usize) -> bool {
+		self.min.map_or(true, |min| length >= min) && self.max.map_or(true, |max| length <= max)
+	}
+}
+
+impl std::fmt::Display for ExpectedLength {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if let Some(min) = self.min {
+			write!(f, "{}", min)?;
+		}
+		write!(f, "..")?;
+		if let Some(max) = self.max {
+			write!(f, "{}", max)?;
+		}
+		write!(f, " {}", self.unit)
+	}
+}
+
+/// Expected length compared against the length of the content currently extracted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthCheck {
+	/// The range the citation expects the length to stay within
+	pub expected: ExpectedLength,
+	/// The length of the current extracted content, in the unit of the expected range
+	pub current: usize,
+}
+
+impl LengthCheck {
+	/// Count the length of the current extracted content and compare it to the expected range
+	pub fn new(expected: ExpectedLength, current_content: &str) -> Self {
+		Self { expected, current: expected.unit.count(current_content) }
+	}
+
+	/// Check if the current length is within the expected range
+	pub fn matches(&self) -> bool {
+		self.expected.contains(self.current)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		let words = ExpectedLength::from_str("200..800")?;
+		assert_eq!(
+			words,
+			ExpectedLength { min: Some(200), max: Some(800), unit: LengthUnit::Words }
+		);
+		assert_eq!(words.to_string(), "200..800 words");
+		assert_eq!(ExpectedLength::from_str(&words.to_string())?, words);
+		assert_eq!(ExpectedLength::from_str(" 200..=800 words ")?, words);
+
+		let chars = ExpectedLength::from_str("1000.. chars")?;
+		assert_eq!(chars, ExpectedLength { min: Some(1000), max: None, unit: LengthUnit::Chars });
+		assert_eq!(chars.to_string(), "1000.. chars");
+
+		assert_eq!(ExpectedLength::from_str("..800")?.min, None);
+
+		for invalid in ["", "800", "..", "800..200", "200..800 lines", "a..b", "-1..5"] {
+			assert!(ExpectedLength::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_length_check() -> Result<(), String> {
+		let words = ExpectedLength::from_str("3..5")?;
+		assert!(LengthCheck::new(words, "one two three").matches());
+		assert!(LengthCheck::new(words, " one\ntwo  three four\tfive ").matches());
+		assert!(!LengthCheck::new(words, "one two").matches());
+		assert_eq!(LengthCheck::new(words, "a b c d e f").current, 6);
+
+		let chars = ExpectedLength::from_str("..4 chars")?;
+		assert!(LengthCheck::new(chars, "café").matches());
+		assert!(!LengthCheck::new(chars, "cafés").matches());
+		Ok(())
+	}
+}
@@ -1,18 +1,62 @@
+pub mod anchor;
+pub mod conditional;
+pub mod constraint;
+pub mod decoding;
+pub mod graphql;
+pub mod json_fields;
+pub mod json_path;
+pub mod length;
+pub mod normalize;
+pub mod numeric;
+pub mod pagination;
+pub mod pdf;
+pub mod politeness;
+pub mod proxy;
+pub mod redirect;
+pub mod renderer;
+pub mod retry;
+pub mod signed;
+pub mod table;
 pub mod ui;
 
+pub use anchor::AnchorCheck;
+pub use conditional::CacheValidators;
+pub use constraint::{ConstraintCheck, ContentConstraint};
+pub use decoding::Decoding;
+pub use graphql::SchemaChange;
+pub use json_fields::JsonFields;
+pub use length::{ExpectedLength, LengthCheck, LengthUnit};
+pub use normalize::{NormalizeMode, Normalizer};
+pub use numeric::{ExpectedNumber, NumberCheck, Tolerance};
+pub use pagination::{Pagination, DEFAULT_MAX_PAGES};
+pub use pdf::PdfPages;
+pub use redirect::{RedirectChange, RedirectPolicy, DEFAULT_MAX_REDIRECTS};
+pub use renderer::{CommandRenderer, Renderer};
+pub use retry::DEFAULT_TIMEOUT_SECS;
+pub use table::TableKey;
+
 use cite_cache::{CacheError, CacheableCurrent, CacheableReferenced};
-use cite_core::{Comparison, Content, Current, Diff, Id, Referenced, Source, SourceError};
+use cite_core::{
+	change::significant_lines, label_lines, only_moves, render_text_diff, text_similarity,
+	ChangeClass, Comparison, Content, ContentType, Current, Diff, DiffGranularity, DiffSensitivity,
+	Id, MinSimilarity, Referenced, Source, SourceError, TextEncoding,
+};
 use regex::Regex;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
-use similar::{ChangeTag, TextDiff};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Read;
+use std::sync::Arc;
 
 /// Match expression for extracting content from http
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MatchExpression {
 	/// Regex pattern matching
 	Regex(String),
+	/// Regex pattern matching, the compared content being a template expanded with the groups
+	/// of the match, e.g. `$1.$2.$3` from a version written as `v1_2_3`
+	RegexReplace { pattern: String, template: String },
 	/// CSS selector matching
 	CssSelector(String),
 	/// XPath expression matching
@@ -21,6 +65,22 @@ pub enum MatchExpression {
 	FullDocument,
 	/// Fragment-based matching (automatically targets element with matching id/name)
 	Fragment(String),
+	/// Content between a start marker and the next end marker after it
+	Between { start: String, end: String },
+	/// Text of a single cell of the first table matching a CSS selector
+	TableCell { table: String, row: TableKey, column: TableKey },
+	/// JSONPath expression over a JSON document, selecting exactly one value
+	JsonPath(String),
+	/// `content` of the `<meta>` tag with a matching `name` or `property` (e.g. `og:title`)
+	Meta(String),
+	/// Results of several expressions over the same document, rendered into a template whose
+	/// `{0}`, `{1}`, ... placeholders stand for the result of each part
+	Composite { parts: Vec<MatchExpression>, template: String },
+	/// The first of several alternative expressions that matches, so that the citation survives
+	/// minor markup changes, e.g. a stable id, then a CSS class, then a regex
+	FirstOf(Vec<MatchExpression>),
+	/// Fields of a type of a GraphQL introspection response, or only the given field
+	GraphqlSchema { type_name: String, field: Option<String> },
 }
 
 impl MatchExpression {
@@ -29,6 +89,11 @@ impl MatchExpression {
 		Self::Regex(pattern.to_string())
 	}
 
+	/// Create a regex match expression whose groups are expanded into a replacement template
+	pub fn regex_replace(pattern: &str, template: &str) -> Self {
+		Self::RegexReplace { pattern: pattern.to_string(), template: template.to_string() }
+	}
+
 	/// Create a CSS selector match expression
 	pub fn css_selector(selector: &str) -> Self {
 		Self::CssSelector(selector.to_string())
@@ -49,6 +114,55 @@ impl MatchExpression {
 		Self::Fragment(fragment_id.to_string())
 	}
 
+	/// Create a match expression for the content between two text markers
+	pub fn between(start: &str, end: &str) -> Self {
+		Self::Between { start: start.to_string(), end: end.to_string() }
+	}
+
+	/// Create a match expression for a table cell, by row and column name or index
+	pub fn table_cell(table_selector: &str, row: TableKey, column: TableKey) -> Self {
+		Self::TableCell { table: table_selector.to_string(), row, column }
+	}
+
+	/// Create a JSONPath match expression
+	pub fn json_path(path: &str) -> Self {
+		Self::JsonPath(path.to_string())
+	}
+
+	/// Create a meta tag match expression
+	pub fn meta(key: &str) -> Self {
+		Self::Meta(key.to_string())
+	}
+
+	/// Create a composite match expression, rendering the results of the parts into a template
+	///
+	/// Without a template, the results are joined by newlines.
+	pub fn composite(parts: Vec<MatchExpression>, template: Option<&str>) -> Self {
+		let template = match template {
+			Some(template) => template.to_string(),
+			None => (0..parts.len())
+				.map(|index| format!("{{{}}}", index))
+				.collect::<Vec<_>>()
+				.join("\n"),
+		};
+		Self::Composite { parts, template }
+	}
+
+	/// Create a match expression trying each alternative in order
+	pub fn first_of(alternatives: Vec<MatchExpression>) -> Self {
+		Self::FirstOf(alternatives)
+	}
+
+	/// Create a match expression for the fields of a GraphQL type, or one of its fields
+	pub fn graphql_schema(type_name: &str, field: Option<&str>) -> Self {
+		Self::GraphqlSchema { type_name: type_name.to_string(), field: field.map(str::to_string) }
+	}
+
+	/// Check that the placeholders of a composite template refer to existing parts
+	pub fn validate_template(template: &str, parts: usize) -> Result<(), String> {
+		render_template(template, &vec![String::new(); parts]).map(|_| ())
+	}
+
 	/// Extract matching content from http
 	pub fn extract_from(&self, content: &str) -> Result<String, SourceError> {
 		match self {
@@ -62,15 +176,31 @@ impl MatchExpression {
 
 				if let Some(captures) = regex.captures(content) {
 					// If there are capture groups, return the first one; otherwise return the full match
-					if captures.len() > 1 {
-						Ok(captures.get(1).map(|m| m.as_str()).unwrap_or("").to_string())
+					let matched = if captures.len() > 1 {
+						captures.get(1).map(|m| m.as_str()).unwrap_or("")
 					} else {
-						Ok(captures.get(0).map(|m| m.as_str()).unwrap_or("").to_string())
-					}
+						captures.get(0).map(|m| m.as_str()).unwrap_or("")
+					};
+					Ok(decode_entities(matched))
 				} else {
 					Ok(String::new())
 				}
 			}
+			MatchExpression::RegexReplace { pattern, template } => {
+				let regex = Regex::new(pattern).map_err(|e| {
+					SourceError::ContentParsing(format!(
+						"Invalid regex pattern '{}': {}",
+						pattern, e
+					))
+				})?;
+
+				// Without a match there are no groups to expand, like a regex without matches
+				let mut expanded = String::new();
+				if let Some(captures) = regex.captures(content) {
+					captures.expand(template, &mut expanded);
+				}
+				Ok(decode_entities(&expanded))
+			}
 			MatchExpression::CssSelector(selector_str) => {
 				let document = Html::parse_document(content);
 				let selector = Selector::parse(selector_str).map_err(|e| {
@@ -101,16 +231,8 @@ impl MatchExpression {
 			MatchExpression::Fragment(fragment_id) => {
 				let document = Html::parse_document(content);
 
-				// Try multiple selectors to find the fragment:
-				// 1. Element with matching id
-				// 2. Element with matching name (for older HTML)
-				// 3. Anchor with matching name
-				let selectors = [
-					format!("#{}", fragment_id),          // #fragment-id
-					format!("[id='{}']", fragment_id),    // [id='fragment-id']
-					format!("[name='{}']", fragment_id),  // [name='fragment-id']
-					format!("a[name='{}']", fragment_id), // a[name='fragment-id']
-				];
+				// Try multiple selectors to find the fragment
+				let selectors = anchor::fragment_selectors(fragment_id);
 
 				for selector_str in &selectors {
 					if let Ok(selector) = Selector::parse(selector_str) {
@@ -131,6 +253,265 @@ impl MatchExpression {
 				// If no fragment found, return empty string (not an error - fragment might not exist)
 				Ok(String::new())
 			}
+			MatchExpression::Between { start, end } => {
+				// Find the first start marker, then the next end marker after it
+				let enclosed = content.find(start.as_str()).and_then(|start_index| {
+					let rest = &content[start_index + start.len()..];
+					rest.find(end.as_str()).map(|end_index| &rest[..end_index])
+				});
+
+				// Missing markers are a no-match, like a regex without matches
+				Ok(decode_entities(enclosed.unwrap_or_default()))
+			}
+			MatchExpression::TableCell { table, row, column } => {
+				table::extract_table_cell(content, table, row, column)
+			}
+			MatchExpression::JsonPath(path) => json_path::extract_json_path(content, path),
+			MatchExpression::GraphqlSchema { type_name, field } => {
+				graphql::extract_schema(content, type_name, field.as_deref())
+			}
+			MatchExpression::Meta(key) => {
+				let document = Html::parse_document(content);
+				let selector_str = format!("meta[name=\"{0}\"], meta[property=\"{0}\"]", key);
+				let selector = Selector::parse(&selector_str).map_err(|e| {
+					SourceError::ContentParsing(format!("Invalid meta key '{}': {:?}", key, e))
+				})?;
+
+				// A missing meta tag is a no-match, like a missing fragment
+				Ok(document
+					.select(&selector)
+					.find_map(|element| element.value().attr("content"))
+					.unwrap_or_default()
+					.trim()
+					.to_string())
+			}
+			MatchExpression::Composite { parts, template } => {
+				let results = parts
+					.iter()
+					.map(|part| part.extract_from(content))
+					.collect::<Result<Vec<_>, _>>()?;
+				render_template(template, &results).map_err(SourceError::ContentParsing)
+			}
+			MatchExpression::FirstOf(_) => {
+				let extracted = self.extract_alternative(content)?;
+				Ok(extracted.map(|(_, extracted)| extracted).unwrap_or_default())
+			}
+		}
+	}
+
+	/// Extract content with the first alternative of a [MatchExpression::FirstOf] that matches,
+	/// returning its index along with the content
+	///
+	/// An alternative that fails, e.g. a JSONPath over an HTML page, is passed over like one that
+	/// does not match. If none matches, the error of the first failing alternative is returned,
+	/// or else no match. Other expressions are their own single alternative.
+	pub fn extract_alternative(
+		&self,
+		content: &str,
+	) -> Result<Option<(usize, String)>, SourceError> {
+		let MatchExpression::FirstOf(alternatives) = self else {
+			let extracted = self.extract_from(content)?;
+			return Ok((!extracted.trim().is_empty()).then_some((0, extracted)));
+		};
+
+		let mut first_error = None;
+		for (index, alternative) in alternatives.iter().enumerate() {
+			match alternative.extract_from(content) {
+				Ok(extracted) if !extracted.trim().is_empty() => {
+					return Ok(Some((index, extracted)))
+				}
+				Ok(_) => {}
+				Err(error) => {
+					first_error.get_or_insert(error);
+				}
+			}
+		}
+
+		match first_error {
+			Some(error) => Err(error),
+			None => Ok(None),
+		}
+	}
+
+	/// Check that the expression selects a single region of the content
+	///
+	/// [MatchExpression::extract_from] quietly resolves ambiguity: a regex with several capture
+	/// groups yields the first group, a regex is applied at its first match only, and all
+	/// elements matching a CSS selector are joined. Strict citations reject such expressions so
+	/// that the author narrows them down.
+	pub fn check_unambiguous(&self, content: &str) -> Result<(), SourceError> {
+		let ambiguous = |expression: &str, problem: String, hint: &str| {
+			Err(SourceError::ContentParsing(format!(
+				"Ambiguous match expression '{}': {}. {}",
+				expression, problem, hint
+			)))
+		};
+
+		match self {
+			MatchExpression::Regex(pattern) => {
+				let regex = Regex::new(pattern).map_err(|e| {
+					SourceError::ContentParsing(format!(
+						"Invalid regex pattern '{}': {}",
+						pattern, e
+					))
+				})?;
+
+				let groups = regex.captures_len() - 1;
+				if groups > 1 {
+					return ambiguous(
+						pattern,
+						format!("it has {} capture groups and only the first is extracted", groups),
+						"Keep one capture group and make the others non-capturing with (?:...)",
+					);
+				}
+
+				let matches = regex.find_iter(content).count();
+				if matches > 1 {
+					return ambiguous(
+						pattern,
+						format!("it matches {} times and only the first is extracted", matches),
+						"Anchor the pattern to the surrounding text so that it matches once",
+					);
+				}
+				Ok(())
+			}
+			// All groups are expanded, so only several matches are ambiguous
+			MatchExpression::RegexReplace { pattern, .. } => {
+				let regex = Regex::new(pattern).map_err(|e| {
+					SourceError::ContentParsing(format!(
+						"Invalid regex pattern '{}': {}",
+						pattern, e
+					))
+				})?;
+
+				let matches = regex.find_iter(content).count();
+				if matches > 1 {
+					return ambiguous(
+						pattern,
+						format!("it matches {} times and only the first is expanded", matches),
+						"Anchor the pattern to the surrounding text so that it matches once",
+					);
+				}
+				Ok(())
+			}
+			MatchExpression::CssSelector(selector_str) => {
+				let document = Html::parse_document(content);
+				let selector = Selector::parse(selector_str).map_err(|e| {
+					SourceError::ContentParsing(format!(
+						"Invalid CSS selector '{}': {:?}",
+						selector_str, e
+					))
+				})?;
+
+				let elements = document.select(&selector).count();
+				if elements > 1 {
+					return ambiguous(
+						selector_str,
+						format!("it matches {} elements, whose texts are joined", elements),
+						"Select a single element, e.g. with an id or :nth-of-type(n)",
+					);
+				}
+				Ok(())
+			}
+			MatchExpression::Meta(key) => {
+				let document = Html::parse_document(content);
+				let selector_str = format!("meta[name=\"{0}\"], meta[property=\"{0}\"]", key);
+				let selector = Selector::parse(&selector_str).map_err(|e| {
+					SourceError::ContentParsing(format!("Invalid meta key '{}': {:?}", key, e))
+				})?;
+
+				let tags = document.select(&selector).count();
+				if tags > 1 {
+					return ambiguous(
+						key,
+						format!("{} meta tags have this key and only the first is extracted", tags),
+						"Cite the value with a CSS selector that tells the tags apart",
+					);
+				}
+				Ok(())
+			}
+			MatchExpression::Composite { parts, .. } => {
+				parts.iter().try_for_each(|part| part.check_unambiguous(content))
+			}
+			// Only the alternative that is extracted needs to be unambiguous
+			MatchExpression::FirstOf(alternatives) => match self.extract_alternative(content)? {
+				Some((index, _)) => alternatives[index].check_unambiguous(content),
+				None => Ok(()),
+			},
+			// The other expressions select a single region by construction or fail when they
+			// cannot
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Render a composite template, replacing each `{N}` placeholder with the Nth result
+///
+/// Braces that do not enclose an index are kept as is.
+fn render_template(template: &str, results: &[String]) -> Result<String, String> {
+	let mut rendered = String::with_capacity(template.len());
+	let mut rest = template;
+
+	while let Some(open) = rest.find('{') {
+		rendered.push_str(&rest[..open]);
+		let placeholder = rest[open..].find('}').map(|close| &rest[open..=open + close]);
+
+		match placeholder.and_then(|p| Some((p, p[1..p.len() - 1].parse::<usize>().ok()?))) {
+			Some((placeholder, index)) => {
+				let result = results.get(index).ok_or_else(|| {
+					format!(
+						"Invalid template: '{}'. Placeholder {} refers to a missing expression; {} given",
+						template,
+						placeholder,
+						results.len()
+					)
+				})?;
+				rendered.push_str(result);
+				rest = &rest[open + placeholder.len()..];
+			}
+			None => {
+				rendered.push('{');
+				rest = &rest[open + 1..];
+			}
+		}
+	}
+
+	rendered.push_str(rest);
+	Ok(rendered)
+}
+
+/// Renders like the `match` kwarg, e.g. `css:h1`
+impl std::fmt::Display for MatchExpression {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MatchExpression::Regex(pattern) => write!(f, "regex:{}", pattern),
+			MatchExpression::RegexReplace { pattern, template } => {
+				write!(f, "regex:{} as {:?}", pattern, template)
+			}
+			MatchExpression::CssSelector(selector) => write!(f, "css:{}", selector),
+			MatchExpression::XPath(xpath) => write!(f, "xpath:{}", xpath),
+			MatchExpression::FullDocument => write!(f, "full"),
+			MatchExpression::Fragment(fragment) => write!(f, "fragment:{}", fragment),
+			MatchExpression::Between { start, end } => write!(f, "between:{}..{}", start, end),
+			MatchExpression::TableCell { table, row, column } => {
+				write!(f, "table:{}[{}][{}]", table, row, column)
+			}
+			MatchExpression::JsonPath(path) => write!(f, "jsonpath:{}", path),
+			MatchExpression::GraphqlSchema { type_name, field: Some(field) } => {
+				write!(f, "graphql:{}.{}", type_name, field)
+			}
+			MatchExpression::GraphqlSchema { type_name, field: None } => {
+				write!(f, "graphql:{}", type_name)
+			}
+			MatchExpression::Meta(key) => write!(f, "meta:{}", key),
+			MatchExpression::Composite { parts, template } => {
+				let parts = parts.iter().map(ToString::to_string).collect::<Vec<_>>();
+				write!(f, "combine:({}) as {:?}", parts.join(", "), template)
+			}
+			MatchExpression::FirstOf(alternatives) => {
+				let alternatives = alternatives.iter().map(ToString::to_string).collect::<Vec<_>>();
+				write!(f, "first_of:({})", alternatives.join(", "))
+			}
 		}
 	}
 }
@@ -212,7 +593,28 @@ pub struct ReferencedHttp {
 	pub match_expression: MatchExpression,
 }
 
-impl Content for ReferencedHttp {}
+impl ReferencedHttp {
+	/// Get the time the referenced content was fetched, if recorded in its metadata
+	pub fn fetched_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+		let fetched_at = self.metadata.get("fetched_at")?;
+		chrono::DateTime::parse_from_rfc3339(fetched_at)
+			.ok()
+			.map(|fetched_at| fetched_at.with_timezone(&chrono::Utc))
+	}
+
+	/// Get the `ETag` and `Last-Modified` validators the referenced page was served with, as
+	/// recorded in its metadata
+	pub fn validators(&self) -> CacheValidators {
+		CacheValidators::from_metadata(&self.metadata)
+	}
+}
+
+impl Content for ReferencedHttp {
+	fn content_type(&self) -> ContentType {
+		content_type_of(&self.match_expression, &self.metadata)
+	}
+}
+
 impl Referenced for ReferencedHttp {}
 
 impl CacheableReferenced for ReferencedHttp {
@@ -236,22 +638,65 @@ pub struct CurrentHttp {
 	pub raw_content: Option<String>,
 }
 
-impl Content for CurrentHttp {}
+impl Content for CurrentHttp {
+	fn content_type(&self) -> ContentType {
+		content_type_of(&self.match_expression, &self.metadata)
+	}
+
+	fn is_blank(&self) -> bool {
+		self.content.trim().is_empty()
+	}
+}
+
+/// Detect the type of extracted content from the `Content-Type` the document was served with
+///
+/// Only the full document keeps the format of the response; content extracted by any other
+/// expression is text.
+fn content_type_of(
+	match_expression: &MatchExpression,
+	metadata: &HashMap<String, String>,
+) -> ContentType {
+	match (match_expression, metadata.get("content_type")) {
+		(MatchExpression::FullDocument, Some(mime)) => ContentType::from_mime(mime),
+		_ => ContentType::Text,
+	}
+}
 
 impl Current<ReferencedHttp, HttpDiff> for CurrentHttp {
 	fn diff(&self, referenced: &ReferencedHttp) -> Result<HttpDiff, SourceError> {
-		let content_changed = self.content != referenced.content;
+		// Both sides are compared decoded, so that e.g. `&amp;` becoming `&` is not drift, also
+		// against references cached with their entities
+		let referenced_content = html_escape::decode_html_entities(&referenced.content);
+		let current_content = html_escape::decode_html_entities(&self.content);
+		let content_changed = current_content != referenced_content;
 		let url_changed = self.source_url != referenced.source_url;
 		let match_expression_changed = self.match_expression != referenced.match_expression;
+		let redirect_change = RedirectChange::between(
+			referenced.metadata.get("final_url"),
+			self.metadata.get("final_url"),
+		);
 
 		let mut diff = HttpDiff {
 			content_changed,
 			url_changed,
 			match_expression_changed,
-			referenced_content: referenced.content.clone(),
-			current_content: self.content.clone(),
+			redirect_change,
+			referenced_content: referenced_content.into_owned(),
+			current_content: current_content.into_owned(),
 			unified_diff: None,
+			pinned_digest: None,
+			changed_fields: Vec::new(),
+			number_check: None,
+			length_check: None,
+			constraint_check: None,
+			anchor_check: None,
+			schema_changes: Vec::new(),
 		};
+		let is_graphql = matches!(self.match_expression, MatchExpression::GraphqlSchema { .. });
+		if content_changed && is_graphql {
+			diff.schema_changes =
+				graphql::schema_changes(&diff.referenced_content, &diff.current_content);
+		}
 
 		// Generate unified diff if content changed
 		diff.generate_unified_diff();
@@ -273,51 +718,201 @@ impl CacheableCurrent<ReferencedHttp, HttpDiff> for CurrentHttp {
 	}
 }
 
+/// Compute the sha256 digest of extracted content, as a lowercase hex string
+pub fn content_digest(content: &str) -> String {
+	hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// Digest pinned in the citation, compared against the digest of the current content
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinnedDigest {
+	/// The digest stored in the citation (empty if the author has not filled it in yet)
+	pub expected: String,
+	/// The digest of the current extracted content
+	pub current: String,
+}
+
+impl PinnedDigest {
+	/// Create a pinned digest comparison for the current content
+	pub fn new(expected: &str, current_content: &str) -> Self {
+		Self { expected: expected.trim().to_lowercase(), current: content_digest(current_content) }
+	}
+
+	/// Check if the current content matches the pinned digest
+	pub fn matches(&self) -> bool {
+		self.expected == self.current
+	}
+
+	/// Check if no digest has been pinned yet, i.e., the author wants the current digest printed
+	pub fn is_unpinned(&self) -> bool {
+		self.expected.is_empty()
+	}
+}
+
 /// Diff between referenced and current http
 #[derive(Debug, Clone, PartialEq)]
 pub struct HttpDiff {
 	pub content_changed: bool,
 	pub url_changed: bool,
 	pub match_expression_changed: bool,
+	/// Set when the redirects of the cited URL end at a different URL than for the reference
+	pub redirect_change: Option<RedirectChange>,
 	pub referenced_content: String,
 	pub current_content: String,
 	pub unified_diff: Option<String>,
+	/// Set when the citation pins a content digest instead of using the cache
+	pub pinned_digest: Option<PinnedDigest>,
+	/// JSON pointers of the changed fields, when the citation compares selected JSON fields
+	pub changed_fields: Vec<String>,
+	/// Changes to the fields of the cited type, when the citation compares a GraphQL schema
+	pub schema_changes: Vec<SchemaChange>,
+	/// Set when the citation expects a number instead of using the cache
+	pub number_check: Option<NumberCheck>,
+	/// Set when the citation expects the length of its content instead of using the cache
+	pub length_check: Option<LengthCheck>,
+	/// Set when the citation constrains the shape of its content instead of using the cache
+	pub constraint_check: Option<ConstraintCheck>,
+	/// Set when the citation requires an anchor of the page instead of using the cache
+	pub anchor_check: Option<AnchorCheck>,
 }
 
 impl HttpDiff {
+	/// Create the diff of content that did not change
+	pub fn unchanged(content: &str) -> Self {
+		let content = html_escape::decode_html_entities(content).into_owned();
+		Self {
+			content_changed: false,
+			url_changed: false,
+			match_expression_changed: false,
+			redirect_change: None,
+			referenced_content: content.clone(),
+			current_content: content,
+			unified_diff: None,
+			pinned_digest: None,
+			changed_fields: Vec::new(),
+			number_check: None,
+			length_check: None,
+			constraint_check: None,
+			anchor_check: None,
+			schema_changes: Vec::new(),
+		}
+	}
+
 	/// Generate a git-style unified diff
+	///
+	/// Blocks that moved without changing are prefixed with `<` where they were and `>` where
+	/// they are now, instead of being shown as removed and added.
 	pub fn generate_unified_diff(&mut self) {
 		if self.content_changed {
-			let diff = TextDiff::from_lines(&self.referenced_content, &self.current_content);
-			let mut result = Vec::new();
-
-			for change in diff.iter_all_changes() {
-				let sign = match change.tag() {
-					ChangeTag::Delete => "-",
-					ChangeTag::Insert => "+",
-					ChangeTag::Equal => " ",
-				};
-				result.push(format!("{}{}", sign, change));
-			}
-
-			if !result.is_empty() {
-				self.unified_diff = Some(result.join(""));
-			}
+			self.unified_diff = render_text_diff(
+				&self.referenced_content,
+				&self.current_content,
+				DiffGranularity::Lines,
+			);
 		}
 	}
 
-	/// Get the unified diff as a string
-	pub fn unified_diff(&self) -> Option<&str> {
-		self.unified_diff.as_deref()
+	/// Check if the content only changed by blocks moving, without any line being changed
+	pub fn has_only_moves(&self) -> bool {
+		let labels = label_lines(&self.referenced_content, &self.current_content)
+			.into_iter()
+			.map(|(label, _)| label)
+			.collect::<Vec<_>>();
+		only_moves(&labels)
+	}
+
+	/// Drop the content change if it only consists of moved blocks
+	pub fn with_ignore_moves(mut self, ignore_moves: bool) -> Self {
+		self.content_changed = self.content_changed && !(ignore_moves && self.has_only_moves());
+		self
+	}
+
+	/// Filter the content change set by sensitivity, so only the relevant kinds of changes count
+	pub fn with_sensitivity(mut self, sensitivity: DiffSensitivity) -> Self {
+		self.content_changed = self.content_changed
+			&& sensitivity.is_text_drift(&self.referenced_content, &self.current_content);
+		self
+	}
+
+	/// Drop the content change if the content stays at least as similar as the bound, if any
+	pub fn with_min_similarity(mut self, min_similarity: Option<MinSimilarity>) -> Self {
+		if let Some(min_similarity) = min_similarity {
+			self.content_changed =
+				self.content_changed && !min_similarity.accepts(self.similarity());
+		}
+		self
 	}
 }
 
 impl Diff for HttpDiff {
 	fn is_empty(&self) -> bool {
-		!self.content_changed && !self.url_changed && !self.match_expression_changed
+		!self.content_changed
+			&& !self.url_changed
+			&& !self.match_expression_changed
+			&& self.redirect_change.is_none()
+			&& self.pinned_digest.as_ref().map_or(true, PinnedDigest::matches)
+			&& self.number_check.as_ref().map_or(true, NumberCheck::matches)
+			&& self.length_check.as_ref().map_or(true, LengthCheck::matches)
+			&& self.constraint_check.as_ref().map_or(true, ConstraintCheck::is_satisfied)
+			&& self.anchor_check.as_ref().map_or(true, AnchorCheck::is_found)
+	}
+
+	fn similarity(&self) -> f64 {
+		text_similarity(&self.referenced_content, &self.current_content)
+	}
+
+	/// Content whose markup changed is structural, and otherwise a minor text change unless
+	/// only trailing whitespace, line endings or blank lines changed. A removed anchor or a moved
+	/// redirect target is structural too. Digest, number and constraint checks are not classified.
+	fn describe_change(&self) -> Option<ChangeClass> {
+		let anchor_removed = self.anchor_check.as_ref().is_some_and(|check| !check.is_found());
+		if self.url_changed
+			|| self.match_expression_changed
+			|| self.redirect_change.is_some()
+			|| !self.schema_changes.is_empty()
+			|| anchor_removed
+		{
+			return Some(ChangeClass::Structure);
+		}
+		if !self.content_changed {
+			return None;
+		}
+
+		let (referenced, current) = (&self.referenced_content, &self.current_content);
+		if markup_tags(referenced) != markup_tags(current) {
+			Some(ChangeClass::Structure)
+		} else if significant_lines(referenced.lines()) == significant_lines(current.lines()) {
+			Some(ChangeClass::Whitespace)
+		} else {
+			Some(ChangeClass::Text)
+		}
+	}
+
+	/// Get the unified diff as a string
+	fn unified_diff(&self) -> Option<&str> {
+		self.unified_diff.as_deref()
 	}
 }
 
+/// Get the names of the tags of markup content in order, e.g. `p` and `/p` of `<p>text</p>`
+fn markup_tags(content: &str) -> Vec<&str> {
+	content
+		.split('<')
+		.skip(1)
+		.filter_map(|tag| {
+			let end = tag
+				.char_indices()
+				.skip(1)
+				.find(|(_, c)| !c.is_ascii_alphanumeric())
+				.map_or(tag.len(), |(index, _)| index);
+			let name = &tag[..end];
+			let is_tag =
+				name.trim_start_matches('/').starts_with(|c: char| c.is_ascii_alphabetic());
+			is_tag.then_some(name)
+		})
+		.collect()
+}
+
 /// Http match source for checking committed http references
 #[derive(Clone, Serialize, Deserialize)]
 pub struct HttpMatch {
@@ -327,6 +922,113 @@ pub struct HttpMatch {
 	id: Id,
 	cache: cite_cache::Cache,
 	cache_behavior: cite_cache::CacheBehavior,
+	#[serde(default, skip_serializing_if = "DiffSensitivity::is_default")]
+	sensitivity: DiffSensitivity,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	digest: Option<String>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	manifest: bool,
+	#[serde(default = "default_max_bytes", skip_serializing_if = "is_default_max_bytes")]
+	max_bytes: usize,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	any_of: Vec<String>,
+	#[serde(default, skip_serializing_if = "Pagination::is_default")]
+	pagination: Pagination,
+	#[serde(default = "default_max_pages", skip_serializing_if = "is_default_max_pages")]
+	max_pages: usize,
+	#[serde(default, skip_serializing_if = "JsonFields::is_empty")]
+	json_fields: JsonFields,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	cookie_env: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	accept_language: Option<String>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	headers: BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	ref_url: Option<SourceUrl>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	response_header: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	redirects: Option<RedirectPolicy>,
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	accept_status: BTreeSet<u16>,
+	#[serde(default = "default_timeout_secs", skip_serializing_if = "is_default_timeout_secs")]
+	timeout_secs: u64,
+	#[serde(default, skip_serializing_if = "is_zero")]
+	retries: u32,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	expect_status: Option<u16>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	expect_num: Option<ExpectedNumber>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	expect_len: Option<ExpectedLength>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	constraint: Option<ContentConstraint>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	require_anchor: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	user_agent: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	proxy: Option<String>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	respect_robots: bool,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	render: bool,
+	#[serde(skip)]
+	renderer: Option<Arc<dyn Renderer>>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	ignore_moves: bool,
+	#[serde(default, skip_serializing_if = "DiffGranularity::is_default")]
+	diff_granularity: DiffGranularity,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	min_similarity: Option<MinSimilarity>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	strict_match: bool,
+	#[serde(default, skip_serializing_if = "Decoding::is_default")]
+	decoding: Decoding,
+	#[serde(default, skip_serializing_if = "TextEncoding::is_default")]
+	encoding: TextEncoding,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	normalizers: Vec<Normalizer>,
+	#[serde(default, skip_serializing_if = "NormalizeMode::is_default")]
+	normalize_mode: NormalizeMode,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	request_body: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pdf: Option<PdfPages>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	signed: bool,
+}
+
+/// Default maximum size of a fetched response body (5 MiB)
+pub const DEFAULT_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+fn default_max_bytes() -> usize {
+	DEFAULT_MAX_BYTES
+}
+
+fn is_default_max_bytes(max_bytes: &usize) -> bool {
+	*max_bytes == DEFAULT_MAX_BYTES
+}
+
+fn default_max_pages() -> usize {
+	DEFAULT_MAX_PAGES
+}
+
+fn is_default_max_pages(max_pages: &usize) -> bool {
+	*max_pages == DEFAULT_MAX_PAGES
+}
+
+fn default_timeout_secs() -> u64 {
+	DEFAULT_TIMEOUT_SECS
+}
+
+fn is_default_timeout_secs(timeout_secs: &u64) -> bool {
+	*timeout_secs == DEFAULT_TIMEOUT_SECS
+}
+
+fn is_zero(retries: &u32) -> bool {
+	*retries == 0
 }
 
 impl HttpMatch {
@@ -347,6 +1049,16 @@ impl HttpMatch {
 		)
 	}
 
+	/// Create with custom match expression and headers sent with every request
+	pub fn with_headers(
+		url: &str,
+		expression: MatchExpression,
+		headers: HashMap<String, String>,
+	) -> Result<Self, SourceError> {
+		Self::with_match_expression(url, expression)
+			.map(|source| source.with_request_headers(headers))
+	}
+
 	/// Create with custom match expression and cache behavior
 	pub fn with_match_expression_and_cache_behavior(
 		url: &str,
@@ -369,293 +1081,3193 @@ impl HttpMatch {
 			.build()
 			.map_err(|e| SourceError::Network(format!("Failed to create cache: {}", e)))?;
 
-		Ok(Self { matches: expression, source_url, cache_path, id, cache, cache_behavior })
+		Ok(Self {
+			matches: expression,
+			source_url,
+			cache_path,
+			id,
+			cache,
+			cache_behavior,
+			sensitivity: DiffSensitivity::default(),
+			digest: None,
+			manifest: false,
+			max_bytes: DEFAULT_MAX_BYTES,
+			any_of: Vec::new(),
+			pagination: Pagination::default(),
+			max_pages: DEFAULT_MAX_PAGES,
+			json_fields: JsonFields::default(),
+			cookie_env: None,
+			accept_language: None,
+			headers: BTreeMap::new(),
+			ref_url: None,
+			response_header: None,
+			redirects: None,
+			accept_status: BTreeSet::new(),
+			timeout_secs: DEFAULT_TIMEOUT_SECS,
+			retries: 0,
+			expect_status: None,
+			expect_num: None,
+			expect_len: None,
+			constraint: None,
+			require_anchor: None,
+			user_agent: None,
+			proxy: None,
+			respect_robots: false,
+			render: false,
+			renderer: None,
+			ignore_moves: false,
+			diff_granularity: DiffGranularity::default(),
+			min_similarity: None,
+			strict_match: false,
+			decoding: Decoding::default(),
+			encoding: TextEncoding::default(),
+			normalizers: Vec::new(),
+			normalize_mode: NormalizeMode::default(),
+			request_body: None,
+			pdf: None,
+			signed: false,
+		})
 	}
 
-	/// Create HTTP match with automatic fragment detection
-	/// If the URL contains a fragment, it will automatically use fragment-based matching
-	/// If no fragment is present, defaults to full document matching
-	pub fn with_auto_fragment(url: &str) -> Result<Self, SourceError> {
-		let source_url = SourceUrl::new(url)?;
-		let match_expression = if let Some(fragment) = source_url.fragment() {
-			MatchExpression::fragment(fragment)
-		} else {
-			MatchExpression::full_document()
-		};
+	/// Create a match citing a type of a GraphQL schema, or only one of its fields
+	///
+	/// The schema is fetched by POSTing the standard introspection query to the endpoint, and
+	/// drift is reported as the fields added, removed or changed.
+	pub fn graphql(url: &str, type_name: &str, field: Option<&str>) -> Result<Self, SourceError> {
+		Self::with_match_expression(url, MatchExpression::graphql_schema(type_name, field))
+			.map(|source| source.with_request_body(&graphql::introspection_body()))
+	}
 
-		Self::with_match_expression_and_cache_behavior(
-			url,
-			match_expression,
-			cite_cache::CacheBehavior::Enabled,
-		)
+	/// Set which kinds of content changes count as drift
+	pub fn with_sensitivity(mut self, sensitivity: DiffSensitivity) -> Self {
+		self.sensitivity = sensitivity;
+		self
 	}
 
-	/// Create HTTP match for macro usage with cache behavior determination
-	///
-	/// This is the main constructor for procedural macros. It:
-	/// 1. Determines cache behavior from environment variables and kwargs
-	/// 2. Handles auto-fragment detection if URL contains #fragment
-	/// 3. Creates appropriate match expression based on parameters
-	///
-	/// Parameters:
-	/// - url: The target URL (may contain fragment)
-	/// - match_expression: Optional explicit match expression
-	/// - cache_override: Optional cache behavior from macro kwargs
-	pub fn try_new_for_macro(
-		url: &str,
-		match_expression: Option<MatchExpression>,
-		cache_override: Option<cite_cache::CacheBehavior>,
-	) -> Result<Self, SourceError> {
-		// Determine final cache behavior (env var overrides kwargs)
-		let cache_behavior = determine_cache_behavior_for_macro(cache_override);
+	/// Get the diff sensitivity of this source
+	pub fn sensitivity(&self) -> DiffSensitivity {
+		self.sensitivity
+	}
 
-		// Determine match expression
-		let final_match_expression = if let Some(expr) = match_expression {
-			expr
-		} else {
-			// Auto-detect based on URL fragment
-			let source_url = SourceUrl::new(url)?;
-			if let Some(fragment) = source_url.fragment() {
-				MatchExpression::fragment(fragment)
-			} else {
-				// No explicit match expression and no fragment - this should be an error
-				return Err(SourceError::ContentParsing(
-                    "HTTP citation requires either an explicit match expression (pattern/selector/match_type) or a URL with fragment".to_string()
-                ));
-			}
-		};
+	/// Don't count blocks moving without changing as drift
+	pub fn with_ignore_moves(mut self, ignore_moves: bool) -> Self {
+		self.ignore_moves = ignore_moves;
+		self
+	}
 
-		Self::with_match_expression_and_cache_behavior(url, final_match_expression, cache_behavior)
+	/// Check if blocks moving without changing are ignored
+	pub fn ignores_moves(&self) -> bool {
+		self.ignore_moves
 	}
 
-	/// Convert URL to a safe cache key
-	fn url_to_cache_key(url: &str) -> String {
+	/// Render drift word by word or character by character instead of line by line, for long
+	/// lines of which only a few words change
+	pub fn with_diff_granularity(mut self, diff_granularity: DiffGranularity) -> Self {
+		self.diff_granularity = diff_granularity;
+		self
+	}
+
+	/// Get the granularity drift is rendered in
+	pub fn diff_granularity(&self) -> DiffGranularity {
+		self.diff_granularity
+	}
+
+	/// Accept content changes that leave the content at least as similar as the bound
+	///
+	/// Meant for prose, where small wording changes do not affect what the citation relies on.
+	pub fn with_min_similarity(mut self, min_similarity: MinSimilarity) -> Self {
+		self.min_similarity = Some(min_similarity);
+		self
+	}
+
+	/// Get the similarity bound for accepting content changes, if any
+	pub fn min_similarity(&self) -> Option<MinSimilarity> {
+		self.min_similarity
+	}
+
+	/// Fail instead of quietly picking a region when the match expression is ambiguous
+	///
+	/// See [MatchExpression::check_unambiguous] for what counts as ambiguous.
+	pub fn with_strict_match(mut self, strict_match: bool) -> Self {
+		self.strict_match = strict_match;
+		self
+	}
+
+	/// Check if ambiguous match expressions are rejected
+	pub fn strict_match(&self) -> bool {
+		self.strict_match
+	}
+
+	/// Decode the extracted content before comparing it, e.g. the base64 `content` field of the
+	/// GitHub contents API selected with a JSONPath
+	///
+	/// Decoded sources get their own cache entry, since their content differs from the encoded
+	/// content.
+	pub fn with_decoding(mut self, decoding: Decoding) -> Self {
+		self.decoding = decoding;
+		if !decoding.is_default() {
+			self.cache_path = format!("{}_{}", self.cache_path, decoding.as_str());
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self
+	}
+
+	/// Get how the extracted content is decoded
+	pub fn decoding(&self) -> Decoding {
+		self.decoding
+	}
+
+	/// Set the character encoding of fetched bodies, instead of detecting it
+	///
+	/// Bodies are normalized to UTF-8 without a byte order mark before matching. By default the
+	/// charset of the `Content-Type` is followed, falling back to UTF-8 or Latin-1 detection.
+	pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+		self.encoding = encoding;
+		if !encoding.is_default() {
+			self.cache_path = format!("{}_{}", self.cache_path, encoding.as_str());
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self
+	}
+
+	/// Get the character encoding of fetched bodies
+	pub fn encoding(&self) -> TextEncoding {
+		self.encoding
+	}
+
+	/// Normalize the extracted content with a chain of normalizers, applied in order
+	///
+	/// Normalized sources get their own cache entry, named by a digest of the chain, since their
+	/// content differs from the content as extracted.
+	pub fn with_normalizers(mut self, normalizers: Vec<Normalizer>) -> Self {
+		if !normalizers.is_empty() {
+			let chain = normalizers.iter().map(Normalizer::to_string).collect::<Vec<_>>();
+			let digest = content_digest(&chain.join("\n"));
+			self.cache_path = format!("{}_normalized_{}", self.cache_path, &digest[..12]);
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self.normalizers = normalizers;
+		self
+	}
+
+	/// Get the normalizers of the extracted content
+	pub fn normalizers(&self) -> &[Normalizer] {
+		&self.normalizers
+	}
+
+	/// Normalize the whitespace of the extracted content, after any other normalizers
+	///
+	/// Like other normalizers, a mode gives the source its own cache entry, so that changing
+	/// the mode fetches a new reference.
+	pub fn with_normalize_mode(mut self, mode: NormalizeMode) -> Self {
+		if !mode.is_default() {
+			self.cache_path = format!("{}_{}", self.cache_path, mode);
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self.normalize_mode = mode;
+		self
+	}
+
+	/// Get the whitespace normalization of the extracted content
+	pub fn normalize_mode(&self) -> NormalizeMode {
+		self.normalize_mode
+	}
+
+	/// Pin the expected sha256 digest of the extracted content
+	///
+	/// Pinned sources compare the digest of the current content against the literal digest
+	/// instead of using the cache. An empty digest reports the current digest, so authors can
+	/// fill it in.
+	pub fn with_digest(mut self, digest: &str) -> Self {
+		self.digest = Some(digest.to_string());
+		self
+	}
+
+	/// Get the pinned digest of this source, if any
+	pub fn digest(&self) -> Option<&str> {
+		self.digest.as_deref()
+	}
+
+	/// Compare against the digest recorded for this source in the committed checksum manifest
+	/// (`.cite/checksums.txt`) instead of using the cache
+	///
+	/// A source missing from the manifest reports its current digest, so authors can add it.
+	pub fn with_manifest(mut self) -> Self {
+		self.manifest = true;
+		self
+	}
+
+	/// Check if this source compares against the checksum manifest
+	pub fn uses_manifest(&self) -> bool {
+		self.manifest
+	}
+
+	/// Set the maximum size of the fetched response body
+	///
+	/// Fetching aborts with a network error once the body exceeds this many bytes, so a
+	/// misbehaving endpoint cannot exhaust the memory of the compiler process.
+	pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+		self.max_bytes = max_bytes;
+		self
+	}
+
+	/// Get the maximum size of the fetched response body
+	pub fn max_bytes(&self) -> usize {
+		self.max_bytes
+	}
+
+	/// Accept any of the given values as the extracted content
+	///
+	/// Sources with accepted values compare the current content against each of them instead
+	/// of using the cache, and only report drift when it matches none.
+	pub fn with_any_of(mut self, any_of: Vec<String>) -> Self {
+		self.any_of = any_of;
+		self
+	}
+
+	/// Get the accepted values of this source
+	pub fn any_of(&self) -> &[String] {
+		&self.any_of
+	}
+
+	/// Follow paginated documentation, concatenating the content extracted from up to
+	/// `max_pages` pages
+	///
+	/// Paginated sources get their own cache entry, since their content differs from that of the
+	/// first page alone.
+	pub fn with_pagination(mut self, pagination: Pagination, max_pages: usize) -> Self {
+		self.pagination = pagination;
+		self.max_pages = max_pages;
+		if !pagination.is_default() {
+			let suffix = pagination.as_str().replace('-', "_");
+			self.cache_path = format!("{}_{}_{}", self.cache_path, suffix, max_pages);
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self
+	}
+
+	/// Get the pagination of this source
+	pub fn pagination(&self) -> Pagination {
+		self.pagination
+	}
+
+	/// Get the maximum number of pages followed by this source
+	pub fn max_pages(&self) -> usize {
+		self.max_pages
+	}
+
+	/// Compare only the selected fields of JSON content
+	///
+	/// Both the referenced and the current content are projected to the selected fields before
+	/// diffing, so changes to other fields, e.g. a volatile timestamp, do not count as drift.
+	pub fn with_json_fields(mut self, json_fields: JsonFields) -> Self {
+		self.json_fields = json_fields;
+		self
+	}
+
+	/// Get the compared JSON fields of this source
+	pub fn json_fields(&self) -> &JsonFields {
+		&self.json_fields
+	}
+
+	/// Send the session cookie held by the given environment variable with every request
+	///
+	/// Only the name of the variable is part of the citation. The cookie itself is read when
+	/// fetching, so it is never committed, cached, or part of the cache key.
+	pub fn with_cookie_env(mut self, cookie_env: &str) -> Self {
+		self.cookie_env = Some(cookie_env.to_string());
+		self
+	}
+
+	/// Get the name of the environment variable holding the session cookie, if any
+	pub fn cookie_env(&self) -> Option<&str> {
+		self.cookie_env.as_deref()
+	}
+
+	/// Request the content in the given language, sent as the `Accept-Language` header
+	///
+	/// Each language gets its own cache entry, so the citation always compares the same
+	/// language variant of the page.
+	pub fn with_accept_language(mut self, accept_language: &str) -> Self {
+		self.accept_language = Some(accept_language.to_string());
+		self.cache_path =
+			format!("{}_lang_{}", self.cache_path, Self::url_to_cache_key(accept_language));
+		self.id = Id::new(self.cache_path.clone());
+		self
+	}
+
+	/// Get the language requested by this source, if any
+	pub fn accept_language(&self) -> Option<&str> {
+		self.accept_language.as_deref()
+	}
+
+	/// Send the given headers with every request, e.g. an `Accept` header selecting the
+	/// canonical representation of the page
+	///
+	/// Headers replace the defaults of the same name. The cache key includes a digest of the
+	/// headers rather than their values, which may be API keys, so that citations of the same
+	/// URL with different headers are cached separately.
+	pub fn with_request_headers(mut self, headers: HashMap<String, String>) -> Self {
+		if headers.is_empty() {
+			return self;
+		}
+		self.headers.extend(headers);
+		let headers = self
+			.headers
+			.iter()
+			.map(|(name, value)| format!("{}: {}\n", name.to_lowercase(), value))
+			.collect::<String>();
+		self.cache_path =
+			format!("{}_headers_{}", self.cache_path, &content_digest(&headers)[..16]);
+		self.id = Id::new(self.cache_path.clone());
+		self
+	}
+
+	/// Get the headers sent with every request of this source
+	pub fn headers(&self) -> &BTreeMap<String, String> {
+		&self.headers
+	}
+
+	/// Cite the status code the URL responds with instead of its content
+	///
+	/// The citation drifts when the status changes, e.g. when a page that returned 200 starts
+	/// returning 404. No body is extracted or cached. Redirects are followed, unless a redirect
+	/// status (3xx) is expected, so that a moved page can be cited as such.
+	pub fn with_expect_status(mut self, status: u16) -> Self {
+		self.expect_status = Some(status);
+		self
+	}
+
+	/// Get the status code this source expects, if it cites a status instead of content
+	pub fn expect_status(&self) -> Option<u16> {
+		self.expect_status
+	}
+
+	/// Expect the extracted content to be a number within a tolerance of the given one
+	///
+	/// The last number in the current content is compared against the expected number instead
+	/// of the cache, so that a fluctuating value only drifts once it leaves the allowed range.
+	pub fn with_expect_num(mut self, expected: ExpectedNumber) -> Self {
+		self.expect_num = Some(expected);
+		self
+	}
+
+	/// Get the number this source expects, if any
+	pub fn expect_num(&self) -> Option<&ExpectedNumber> {
+		self.expect_num.as_ref()
+	}
+
+	/// Expect the length of the extracted content to stay within a range of words or characters
+	///
+	/// The current content is counted instead of compared against the cache, so that only a
+	/// section being gutted or bloated drifts, not every edit to its text.
+	pub fn with_expect_len(mut self, expected: ExpectedLength) -> Self {
+		self.expect_len = Some(expected);
+		self
+	}
+
+	/// Get the length this source expects, if any
+	pub fn expect_len(&self) -> Option<&ExpectedLength> {
+		self.expect_len.as_ref()
+	}
+
+	/// Require the extracted content to satisfy a constraint instead of matching a reference
+	///
+	/// The current content is checked against the constraint without using the cache, so that
+	/// content which keeps its shape, e.g. a JSON document gaining fields, is not drift.
+	pub fn with_constraint(mut self, constraint: ContentConstraint) -> Self {
+		self.constraint = Some(constraint);
+		self
+	}
+
+	/// Get the constraint the content of this source must satisfy, if any
+	pub fn constraint(&self) -> Option<&ContentConstraint> {
+		self.constraint.as_ref()
+	}
+
+	/// Require the page to still have the element a fragment id targets, e.g. `installation`
+	/// for a deep link to `#installation`, instead of matching a reference
+	///
+	/// The fetched document is checked for the anchor without using the cache, so the citation
+	/// only drifts once the anchor disappears, even if the page otherwise still loads.
+	pub fn with_require_anchor(mut self, anchor: &str) -> Self {
+		self.require_anchor = Some(anchor.trim_start_matches('#').to_string());
+		self
+	}
+
+	/// Get the anchor the page of this source must have, if any
+	pub fn require_anchor(&self) -> Option<&str> {
+		self.require_anchor.as_deref()
+	}
+
+	/// Fetch the URL by POSTing the given JSON body instead of with a GET
+	///
+	/// Only the cited URL is POSTed to; robots.txt and following pages are still fetched with a
+	/// GET.
+	pub fn with_request_body(mut self, body: &str) -> Self {
+		self.request_body = Some(body.to_string());
+		self
+	}
+
+	/// Get the JSON body this source POSTs, if any
+	pub fn request_body(&self) -> Option<&str> {
+		self.request_body.as_deref()
+	}
+
+	/// Check if this source cites a GraphQL schema
+	pub fn is_graphql(&self) -> bool {
+		matches!(self.matches, MatchExpression::GraphqlSchema { .. })
+	}
+
+	/// Cite the text extracted from the given pages of the PDF document at the URL
+	///
+	/// The match expression then selects from the extracted text, one line per line of text,
+	/// rather than from the raw document. Extraction requires the `pdf` feature. Each page
+	/// selection gets its own cache entry.
+	pub fn with_pdf(mut self, pages: PdfPages) -> Self {
+		self.pdf = Some(pages);
+		self.cache_path =
+			format!("{}_pdf_{}", self.cache_path, pages.to_string().replace('-', "_"));
+		self.id = Id::new(self.cache_path.clone());
+		self
+	}
+
+	/// Get the cited pages, if this source cites a PDF document
+	pub fn pdf(&self) -> Option<PdfPages> {
+		self.pdf
+	}
+
+	/// Send the given user agent instead of the default, or that of `CITE_USER_AGENT`
+	pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+		self.user_agent = Some(user_agent.to_string());
+		self
+	}
+
+	/// Get the user agent sent with the requests of this source
+	pub fn user_agent(&self) -> String {
+		politeness::user_agent(self.user_agent.as_deref())
+	}
+
+	/// Send requests through the given proxy instead of that of `HTTPS_PROXY` or `HTTP_PROXY`
+	///
+	/// Hosts listed in `NO_PROXY` are still fetched directly.
+	pub fn with_proxy(mut self, proxy: &str) -> Self {
+		self.proxy = Some(proxy.to_string());
+		self
+	}
+
+	/// Get the proxy given for this source, if any
+	pub fn proxy(&self) -> Option<&str> {
+		self.proxy.as_deref()
+	}
+
+	/// Check the robots.txt of the host before fetching, and wait out its crawl delay
+	///
+	/// See [politeness] for how the rules apply. `CITE_RESPECT_ROBOTS` enables this for every
+	/// source.
+	pub fn with_respect_robots(mut self, respect_robots: bool) -> Self {
+		self.respect_robots = respect_robots;
+		self
+	}
+
+	/// Check if this source respects the robots.txt of its host
+	pub fn respects_robots(&self) -> bool {
+		self.respect_robots || politeness::respects_robots_from_env()
+	}
+
+	/// Fetch pages as rendered by a browser, for pages that build their content with JavaScript
+	///
+	/// See [renderer] for how pages are rendered. Rendered sources get their own cache entry,
+	/// since the rendered page differs from the page as served.
+	pub fn with_render(mut self, render: bool) -> Self {
+		if render && !self.render {
+			self.cache_path = format!("{}_rendered", self.cache_path);
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self.render = render;
+		self
+	}
+
+	/// Treat the URL as a presigned object-store URL, whose signature changes on every build
+	///
+	/// See [signed] for the parameters left out. The cache entry and the compared URL are those
+	/// of the URL without its signature, so that citations of differently signed URLs of the
+	/// same object share a reference.
+	pub fn with_signed(mut self, signed: bool) -> Self {
+		if signed && !self.signed {
+			let url = self.source_url.as_str();
+			self.cache_path = self.cache_path.replacen(
+				&Self::url_to_cache_key(url),
+				&Self::url_to_cache_key(&signed::strip_signature(url)),
+				1,
+			);
+			self.id = Id::new(self.cache_path.clone());
+		}
+		self.signed = signed;
+		self
+	}
+
+	/// Check if the URL is a presigned object-store URL
+	pub fn is_signed(&self) -> bool {
+		self.signed
+	}
+
+	/// Take the reference from a pinned URL, the cited URL only supplying the current content
+	///
+	/// The pinned URL, e.g. a file of the GitHub contents API at a fixed ref, is fetched once
+	/// and its content cached as the reference. The cited URL tracks the live content, e.g. the
+	/// same file at the default branch, so that drift means it diverged from the pinned one.
+	pub fn with_ref_url(mut self, ref_url: &str) -> Result<Self, SourceError> {
+		let ref_url = SourceUrl::new(ref_url)?;
+		self.cache_path =
+			format!("{}_ref_{}", self.cache_path, Self::url_to_cache_key(ref_url.as_str()));
+		self.id = Id::new(self.cache_path.clone());
+		self.ref_url = Some(ref_url);
+		Ok(self)
+	}
+
+	/// Get the pinned URL the reference is taken from, if any
+	pub fn ref_url(&self) -> Option<&SourceUrl> {
+		self.ref_url.as_ref()
+	}
+
+	/// Set whether redirects of the cited URL are followed, and how many
+	///
+	/// Redirects are followed by default. Set explicitly, followed redirects record the URL they
+	/// end at, so that the citation drifts when a canonical page moves even if its content did
+	/// not. Without following, a redirect fails the citation, naming where it leads.
+	pub fn with_redirects(mut self, redirects: RedirectPolicy) -> Self {
+		self.redirects = Some(redirects);
+		self
+	}
+
+	/// Get whether redirects of the cited URL are followed
+	pub fn redirects(&self) -> RedirectPolicy {
+		self.redirects.unwrap_or_default()
+	}
+
+	/// Get the URL to record as the one the redirects of a request ended at, if the redirect
+	/// policy was set explicitly to follow them
+	///
+	/// Signatures differ between fetches, so they are left out like from the cited URL.
+	fn recorded_final_url(&self, url: &reqwest::Url) -> Option<String> {
+		match self.redirects {
+			Some(RedirectPolicy::Follow(_)) if self.signed => {
+				Some(signed::strip_signature(url.as_str()))
+			}
+			Some(RedirectPolicy::Follow(_)) => Some(url.to_string()),
+			Some(RedirectPolicy::None) | None => None,
+		}
+	}
+
+	/// Accept exactly the given statuses instead of any success status (2xx), e.g. a 404 of a
+	/// page that is cited as gone
+	///
+	/// Other statuses fail the citation before anything is extracted from the response. An
+	/// accepted redirect (3xx) is only seen by citations that do not follow redirects. Only the
+	/// response of the cited URL is checked against them; robots.txt and following pages must
+	/// succeed.
+	pub fn with_accept_status(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+		self.accept_status = statuses.into_iter().collect();
+		self
+	}
+
+	/// Get the statuses this source accepts, all success statuses if empty
+	pub fn accept_status(&self) -> &BTreeSet<u16> {
+		&self.accept_status
+	}
+
+	/// Set how long a request may take, in seconds, before it fails
+	pub fn with_timeout_secs(mut self, timeout_secs: u64) -> Self {
+		self.timeout_secs = timeout_secs;
+		self
+	}
+
+	/// Get how long a request may take, in seconds
+	pub fn timeout_secs(&self) -> u64 {
+		self.timeout_secs
+	}
+
+	/// Retry requests failing transiently up to the given number of times, with exponential
+	/// backoff and jitter between attempts
+	///
+	/// Connection failures, timeouts and gateway errors (502, 503 and 504) are transient. A
+	/// request failing on its last attempt fails the citation with the error of that attempt.
+	pub fn with_retries(mut self, retries: u32) -> Self {
+		self.retries = retries;
+		self
+	}
+
+	/// Get how many times a request failing transiently is retried
+	pub fn retries(&self) -> u32 {
+		self.retries
+	}
+
+	/// Cite the value of a response header, e.g. `X-Api-Version` or `Sunset`, instead of the body
+	///
+	/// The value is compared like extracted content, so the citation drifts when the header
+	/// changes; a missing header is empty content, like a match expression without matches. The
+	/// header name is part of the cache key, in lowercase since header names are
+	/// case-insensitive.
+	pub fn with_response_header(mut self, name: &str) -> Result<Self, SourceError> {
+		let name =
+			reqwest::header::HeaderName::from_bytes(name.trim().as_bytes()).map_err(|e| {
+				SourceError::ContentParsing(format!("Invalid response header '{}': {}", name, e))
+			})?;
+		self.cache_path =
+			format!("{}_header_{}", self.cache_path, Self::url_to_cache_key(name.as_str()));
+		self.id = Id::new(self.cache_path.clone());
+		self.response_header = Some(name.as_str().to_string());
+		Ok(self)
+	}
+
+	/// Get the name of the response header this source cites, if it cites one
+	pub fn response_header(&self) -> Option<&str> {
+		self.response_header.as_deref()
+	}
+
+	/// Get the URL the content is cited by, which leaves out the signature of a signed URL
+	///
+	/// The content is still fetched from [HttpMatch::source_url].
+	pub fn cited_url(&self) -> SourceUrl {
+		if !self.signed {
+			return self.source_url.clone();
+		}
+		SourceUrl::new(&signed::strip_signature(self.source_url.as_str()))
+			.unwrap_or_else(|_| self.source_url.clone())
+	}
+
+	/// Render pages with the given renderer instead of `CITE_RENDER_COMMAND`
+	pub fn with_renderer(self, renderer: Arc<dyn Renderer>) -> Self {
+		let mut source = self.with_render(true);
+		source.renderer = Some(renderer);
+		source
+	}
+
+	/// Check if pages are fetched as rendered by a browser
+	pub fn renders(&self) -> bool {
+		self.render
+	}
+
+	/// Get the renderer pages are fetched with, if they are rendered
+	fn active_renderer(&self) -> Result<Option<Arc<dyn Renderer>>, SourceError> {
+		if !self.render {
+			return Ok(None);
+		}
+		match &self.renderer {
+			Some(renderer) => Ok(Some(renderer.clone())),
+			None => match CommandRenderer::from_env() {
+				Some(renderer) => Ok(Some(Arc::new(renderer))),
+				None => Err(SourceError::Network(format!(
+					"{} must be rendered, but {} names no render command",
+					self.source_url.as_str(),
+					renderer::RENDER_COMMAND_ENV
+				))),
+			},
+		}
+	}
+
+	/// Resolve the headers sent with every request of this source
+	fn request_headers(&self) -> Result<Vec<(String, String)>, SourceError> {
+		let mut headers = vec![("User-Agent".to_string(), self.user_agent())];
+
+		if let Some(cookie_env) = &self.cookie_env {
+			// Name the variable, never its value
+			let cookie = std::env::var(cookie_env)
+				.ok()
+				.filter(|cookie| !cookie.is_empty())
+				.ok_or_else(|| {
+					SourceError::Network(format!(
+						"Cookie environment variable {} is not set",
+						cookie_env
+					))
+				})?;
+			headers.push(("Cookie".to_string(), cookie));
+		}
+
+		if let Some(accept_language) = &self.accept_language {
+			headers.push(("Accept-Language".to_string(), accept_language.clone()));
+		}
+
+		for (name, value) in &self.headers {
+			headers.retain(|(default, _)| !default.eq_ignore_ascii_case(name));
+			headers.push((name.clone(), value.clone()));
+		}
+
+		Ok(headers)
+	}
+
+	/// Use the given cache instead of the default `.cite/cache` of the working directory
+	pub fn with_cache(mut self, cache: cite_cache::Cache) -> Self {
+		self.cache = cache;
+		self
+	}
+
+	/// Get the cache of this source
+	pub fn cache(&self) -> &cite_cache::Cache {
+		&self.cache
+	}
+
+	/// Get the referenced content cached for this source, without fetching anything
+	///
+	/// Returns `None` until the first validation has cached a reference.
+	pub fn cached_referenced(&self) -> Option<ReferencedHttp> {
+		self.cache.get::<ReferencedHttp>(&self.id).ok().flatten()
+	}
+
+	/// Get the cached reference, if it is used and records the digest of its raw content
+	///
+	/// References cached before the digest was recorded, before the latest cache reset or longer
+	/// ago than their time to live go through the cache as usual.
+	fn cached_with_raw_digest(&self) -> Result<Option<ReferencedHttp>, SourceError> {
+		let cache_error = |e: CacheError| SourceError::Network(format!("Cache error: {}", e));
+		if !self.cache.is_usable(&self.id, &self.cache_behavior).map_err(cache_error)? {
+			return Ok(None);
+		}
+		let referenced =
+			self.cache.get_memoized::<ReferencedHttp>(&self.id).map_err(cache_error)?;
+		Ok(referenced.filter(|referenced| referenced.metadata.contains_key("raw_digest")))
+	}
+
+	/// Compare the current content against the content of the pinned reference URL
+	///
+	/// The reference is taken from the cache, or else fetched from the pinned URL with the given
+	/// function and cached, which also fetches the current content of a source.
+	fn compare_ref_url_with<F>(
+		&self,
+		ref_url: &SourceUrl,
+		get_current: F,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError>
+	where
+		F: Fn(&HttpMatch) -> Result<CurrentHttp, SourceError>,
+	{
+		let cache_error = |e: CacheError| SourceError::Network(format!("Cache error: {}", e));
+		let cached = if self.cache.is_usable(&self.id, &self.cache_behavior).map_err(cache_error)? {
+			self.cache.get_memoized::<ReferencedHttp>(&self.id).map_err(cache_error)?
+		} else {
+			None
+		};
+
+		let referenced = match cached {
+			Some(referenced) => referenced,
+			None => {
+				let pinned = HttpMatch { source_url: ref_url.clone(), ..self.clone() };
+				let mut fetched = get_current(&pinned)?;
+				// The reference stands for the cited URL, so that only its content is compared
+				fetched.source_url = self.cited_url();
+				if self.cache_behavior != cite_cache::CacheBehavior::Ignored {
+					self.cache
+						.set_with_behavior(&self.id, &fetched, &self.cache_behavior)
+						.map_err(cache_error)?;
+				}
+				ReferencedHttp {
+					content: fetched.content,
+					metadata: fetched.metadata,
+					source_url: fetched.source_url,
+					match_expression: fetched.match_expression,
+				}
+			}
+		};
+
+		let current = get_current(self)?;
+		let diff = current.diff(&referenced)?;
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Get the note to report when the cache holds no reference for this source yet
+	///
+	/// Without one, the cache compares the current content against itself and captures it as
+	/// the reference, so the first validation passes without having checked anything. The note
+	/// tells the author that the citation only detects drift once that reference is committed.
+	fn unreferenced_note(&self) -> Result<Option<String>, SourceError> {
+		if self.cache_behavior == cite_cache::CacheBehavior::Ignored {
+			return Ok(None);
+		}
+		let cache_error = |e: CacheError| SourceError::Network(format!("Cache error: {}", e));
+		if self.cache.is_usable(&self.id, &self.cache_behavior).map_err(cache_error)?
+			&& self
+				.cache
+				.get_memoized::<ReferencedHttp>(&self.id)
+				.map_err(cache_error)?
+				.is_some()
+		{
+			return Ok(None);
+		}
+		Ok(Some(format!(
+			"Note: the citation of {} ({}) has no reference yet, so it was compared against its own current content and cannot detect drift until the captured reference is committed: {}",
+			self.cited_url().as_str(),
+			self.matches,
+			self.cache.cache_dir().join(self.id.as_str()).display()
+		)))
+	}
+
+	/// Compare fetched pages against the cached reference, extracting them with the given
+	/// function only if they differ from the raw content the reference was extracted from
+	///
+	/// Extraction settings are part of the id of the source, so identical raw content always
+	/// extracts to the cached reference and neither extraction nor diffing is needed, unless the
+	/// redirects of the cited URL now end elsewhere. Neither is a page the server reported as
+	/// not modified since the reference was fetched.
+	fn compare_raw_with<E>(
+		&self,
+		referenced: ReferencedHttp,
+		raw: RawHttp,
+		extract: E,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError>
+	where
+		E: FnOnce(RawHttp) -> Result<CurrentHttp, SourceError>,
+	{
+		let raw_content = raw.pages.join("\n");
+		let redirect_change =
+			RedirectChange::between(referenced.metadata.get("final_url"), raw.final_url.as_ref());
+		let changed = referenced.metadata.get("raw_digest") != Some(&content_digest(&raw_content))
+			|| redirect_change.is_some();
+		if changed && !raw.not_modified {
+			let current = extract(raw)?;
+			let diff = current.diff(&referenced)?;
+			return Ok(Comparison::new(referenced, current, diff));
+		}
+
+		cite_core::trace_event!("raw content unchanged");
+		let current = CurrentHttp {
+			content: referenced.content.clone(),
+			metadata: referenced.metadata.clone(),
+			source_url: referenced.source_url.clone(),
+			match_expression: referenced.match_expression.clone(),
+			raw_content: (!raw.not_modified).then_some(raw_content),
+		};
+		let diff = HttpDiff::unchanged(&referenced.content);
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Create HTTP match with automatic fragment detection
+	/// If the URL contains a fragment, it will automatically use fragment-based matching
+	/// If no fragment is present, defaults to full document matching
+	pub fn with_auto_fragment(url: &str) -> Result<Self, SourceError> {
+		let source_url = SourceUrl::new(url)?;
+		let match_expression = if let Some(fragment) = source_url.fragment() {
+			MatchExpression::fragment(fragment)
+		} else {
+			MatchExpression::full_document()
+		};
+
+		Self::with_match_expression_and_cache_behavior(
+			url,
+			match_expression,
+			cite_cache::CacheBehavior::Enabled,
+		)
+	}
+
+	/// Create HTTP match for macro usage with cache behavior determination
+	///
+	/// This is the main constructor for procedural macros. It:
+	/// 1. Determines cache behavior from environment variables and kwargs
+	/// 2. Handles auto-fragment detection if URL contains #fragment
+	/// 3. Creates appropriate match expression based on parameters
+	///
+	/// Parameters:
+	/// - url: The target URL (may contain fragment)
+	/// - match_expression: Optional explicit match expression
+	/// - cache_override: Optional cache behavior from macro kwargs
+	pub fn try_new_for_macro(
+		url: &str,
+		match_expression: Option<MatchExpression>,
+		cache_override: Option<cite_cache::CacheBehavior>,
+	) -> Result<Self, SourceError> {
+		// Determine final cache behavior (env var overrides kwargs)
+		let cache_behavior = determine_cache_behavior_for_macro(cache_override);
+
+		// Determine match expression
+		let final_match_expression = if let Some(expr) = match_expression {
+			expr
+		} else {
+			// Auto-detect based on URL fragment
+			let source_url = SourceUrl::new(url)?;
+			if let Some(fragment) = source_url.fragment() {
+				MatchExpression::fragment(fragment)
+			} else {
+				// No explicit match expression and no fragment - this should be an error
+				return Err(SourceError::ContentParsing(
+                    "HTTP citation requires either an explicit match expression (pattern/selector/match_type) or a URL with fragment".to_string()
+                ));
+			}
+		};
+
+		Self::with_match_expression_and_cache_behavior(url, final_match_expression, cache_behavior)
+	}
+
+	/// Convert URL to a safe cache key
+	fn url_to_cache_key(url: &str) -> String {
 		// Replace unsafe characters for filesystem
 		url.chars()
 			.map(|c| match c {
 				'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
 				_ => '_',
 			})
-			.collect()
+			.collect()
+	}
+
+	/// Convert match expression to a safe cache key component
+	fn match_expression_to_cache_key(expression: &MatchExpression) -> String {
+		let key = match expression {
+			MatchExpression::Regex(pattern) => format!("regex_{}", pattern),
+			MatchExpression::RegexReplace { pattern, template } => {
+				format!("regex_replace_{}_{}", pattern, template)
+			}
+			MatchExpression::CssSelector(selector) => format!("css_{}", selector),
+			MatchExpression::Fragment(fragment) => format!("frag_{}", fragment),
+			MatchExpression::XPath(xpath) => format!("xpath_{}", xpath),
+			MatchExpression::FullDocument => "full".to_string(),
+			MatchExpression::Between { start, end } => format!("between_{}_{}", start, end),
+			MatchExpression::TableCell { table, row, column } => {
+				format!("table_{}_{}_{}", table, row, column)
+			}
+			MatchExpression::JsonPath(path) => format!("jsonpath_{}", path),
+			MatchExpression::GraphqlSchema { type_name, field } => {
+				format!("graphql_{}_{}", type_name, field.as_deref().unwrap_or_default())
+			}
+			MatchExpression::Meta(key) => format!("meta_{}", key),
+			MatchExpression::Composite { parts, template } => {
+				let parts =
+					parts.iter().map(Self::match_expression_to_cache_key).collect::<Vec<_>>();
+				format!("combine_{}_{}", parts.join("_"), template)
+			}
+			MatchExpression::FirstOf(alternatives) => {
+				let alternatives = alternatives
+					.iter()
+					.map(Self::match_expression_to_cache_key)
+					.collect::<Vec<_>>();
+				format!("first_of_{}", alternatives.join("_"))
+			}
+		};
+
+		// Make it filesystem-safe
+		key.chars()
+			.map(|c| match c {
+				'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+				_ => '_',
+			})
+			.collect()
+	}
+
+	/// Fetch http content from the URL using synchronous HTTP client
+	fn fetch_http(&self, url: &str, headers: &[(String, String)]) -> Result<String, SourceError> {
+		self.fetch_page(url, headers).map(|page| page.body)
+	}
+
+	/// Fetch http content from the URL along with the `Content-Type` it was served with and the
+	/// URL its redirects ended at
+	fn fetch_page(
+		&self,
+		url: &str,
+		headers: &[(String, String)],
+	) -> Result<FetchedPage, SourceError> {
+		// Use blocking reqwest client for synchronous HTTP requests
+		// This is appropriate for compile-time macro execution
+		let builder = reqwest::blocking::Client::builder()
+			.timeout(std::time::Duration::from_secs(self.timeout_secs))
+			.redirect(self.redirects().to_reqwest());
+		let client = proxy::apply(builder, self.proxy())?
+			.build()
+			.map_err(|e| SourceError::Network(format!("Failed to create HTTP client: {}", e)))?;
+
+		let mut request = match &self.request_body {
+			Some(body) if url == self.source_url.as_str() => client
+				.post(url)
+				.header(reqwest::header::CONTENT_TYPE, "application/json")
+				.body(body.clone()),
+			_ => client.get(url),
+		};
+		for (name, value) in headers {
+			request = request.header(name.as_str(), value.as_str());
+		}
+
+		let (body, content_type, final_url, validators) = self.send_with(request, |response| {
+			let final_url = self.recorded_final_url(response.url());
+			let validators = CacheValidators::from_response(response.headers());
+
+			// A conditional request may be answered without a body, which the caller has cached
+			if response.status() == reqwest::StatusCode::NOT_MODIFIED
+				&& CacheValidators::is_conditional(headers)
+			{
+				return Ok((None, None, final_url, validators));
+			}
+			self.check_success(&response, url).map_err(retry::Failure::Fatal)?;
+
+			// Reject bodies announced as too large before reading them
+			if response.content_length().is_some_and(|length| length > self.max_bytes as u64) {
+				return Err(retry::Failure::Fatal(body_too_large(self.max_bytes, url)));
+			}
+
+			let content_type = response
+				.headers()
+				.get(reqwest::header::CONTENT_TYPE)
+				.and_then(|content_type| content_type.to_str().ok())
+				.map(|content_type| content_type.to_string());
+			let body = read_capped_bytes(response, self.max_bytes, url)?;
+			Ok((Some(body), content_type, final_url, validators))
+		})?;
+
+		let Some(body) = body else {
+			cite_core::trace_event!("not modified");
+			return Ok(FetchedPage {
+				body: String::new(),
+				content_type: None,
+				final_url,
+				validators,
+				not_modified: true,
+			});
+		};
+		let (body, content_type) = if self.pdf.is_some() && url == self.source_url.as_str() {
+			// The extracted text is compared rather than the PDF it was served as
+			(self.body_text(url, &body, None)?, None)
+		} else {
+			(self.body_text(url, &body, content_type.as_deref())?, content_type)
+		};
+		Ok(FetchedPage { body, content_type, final_url, validators, not_modified: false })
+	}
+
+	/// Send a request, retrying transient failures as configured with [HttpMatch::with_retries]
+	///
+	/// A gateway error on the last attempt is returned as a response, so that its status is
+	/// checked like any other.
+	fn send(
+		&self,
+		request: reqwest::blocking::RequestBuilder,
+	) -> Result<reqwest::blocking::Response, SourceError> {
+		self.send_with(request, Ok)
+	}
+
+	/// Send a request and read its response with the given function, see [HttpMatch::send]
+	///
+	/// Reading the response is retried along with sending the request, so that a body cut off
+	/// mid-transfer is fetched again.
+	fn send_with<T, F>(
+		&self,
+		request: reqwest::blocking::RequestBuilder,
+		mut read: F,
+	) -> Result<T, SourceError>
+	where
+		F: FnMut(reqwest::blocking::Response) -> Result<T, retry::Failure>,
+	{
+		retry::retry_with_backoff(
+			self.retries,
+			|retries_left| {
+				let request = request.try_clone().ok_or_else(|| {
+					retry::Failure::Fatal(SourceError::Network(
+						"HTTP request cannot be sent".to_string(),
+					))
+				})?;
+				let response = request.send().map_err(retry::Failure::from_send_error)?;
+				let status = response.status();
+				if retries_left > 0 && retry::is_transient_status(status.as_u16()) {
+					return Err(retry::Failure::Transient(SourceError::Network(format!(
+						"HTTP request failed with status {}: {}",
+						status,
+						response.url()
+					))));
+				}
+				read(response)
+			},
+			std::thread::sleep,
+		)
+	}
+
+	/// Check that a response succeeded, see [HttpMatch::check_status]
+	fn check_success(
+		&self,
+		response: &reqwest::blocking::Response,
+		url: &str,
+	) -> Result<(), SourceError> {
+		let location = response
+			.headers()
+			.get(reqwest::header::LOCATION)
+			.and_then(|location| location.to_str().ok());
+		self.check_status(response.status(), location, url)
+	}
+
+	/// Check that a status is accepted before its body is extracted, so that e.g. the body of
+	/// an error page is never cached as the reference
+	///
+	/// Success statuses (2xx) are accepted unless the citation accepts a set of statuses, and a
+	/// redirect that was not followed fails naming where it leads. Accepted statuses only apply
+	/// to the cited URL, not to e.g. robots.txt or the following pages of a paginated source.
+	fn check_status(
+		&self,
+		status: reqwest::StatusCode,
+		location: Option<&str>,
+		url: &str,
+	) -> Result<(), SourceError> {
+		let accept_status = Some(&self.accept_status)
+			.filter(|accept_status| !accept_status.is_empty() && url == self.source_url.as_str());
+		let accepted = match accept_status {
+			None => status.is_success(),
+			Some(accept_status) => accept_status.contains(&status.as_u16()),
+		};
+		if accepted {
+			return Ok(());
+		}
+		if status.is_redirection() {
+			return Err(redirect::unfollowed_redirect(url, status.as_u16(), location));
+		}
+		let Some(accept_status) = accept_status else {
+			return Err(SourceError::Network(format!(
+				"HTTP request failed with status {}: {}",
+				status, url
+			)));
+		};
+		let accepted = accept_status.iter().map(u16::to_string).collect::<Vec<_>>();
+		Err(SourceError::Network(format!(
+			"HTTP request failed with status {}, which is not one of the accepted statuses {}: {}",
+			status,
+			accepted.join(", "),
+			url
+		)))
+	}
+
+	/// Get the text of a fetched body, which is the extracted text for a cited PDF document
+	///
+	/// Other bodies are decoded in the encoding of the citation, or in the one declared by their
+	/// `Content-Type` when it is detected.
+	fn body_text(
+		&self,
+		url: &str,
+		body: &[u8],
+		content_type: Option<&str>,
+	) -> Result<String, SourceError> {
+		let declared = content_type.and_then(TextEncoding::from_content_type);
+		match (self.pdf, self.encoding, declared) {
+			(Some(pages), _, _) if url == self.source_url.as_str() => {
+				pdf::extract_text(body, pages)
+			}
+			(_, TextEncoding::Auto, Some(declared)) => Ok(declared.decode(body)),
+			(_, encoding, _) => Ok(encoding.decode(body)),
+		}
+	}
+
+	/// Fetch the status code the URL responds with, without reading the body
+	fn fetch_status(&self, url: &str, headers: &[(String, String)]) -> Result<u16, SourceError> {
+		let follows_redirects =
+			!self.expect_status.is_some_and(|status| (300..400).contains(&status));
+		let redirect = if follows_redirects {
+			self.redirects().to_reqwest()
+		} else {
+			reqwest::redirect::Policy::none()
+		};
+
+		let builder = reqwest::blocking::Client::builder()
+			.timeout(std::time::Duration::from_secs(self.timeout_secs))
+			.redirect(redirect);
+		let client = proxy::apply(builder, self.proxy())?
+			.build()
+			.map_err(|e| SourceError::Network(format!("Failed to create HTTP client: {}", e)))?;
+
+		let mut request = client.get(url);
+		for (name, value) in headers {
+			request = request.header(name.as_str(), value.as_str());
+		}
+
+		let response = self.send(request)?;
+		Ok(response.status().as_u16())
+	}
+
+	/// Fetch the value of a response header of the URL, without reading the body
+	///
+	/// A header sent several times is joined with `, `, as it would be folded into one line.
+	fn fetch_response_header(
+		&self,
+		url: &str,
+		headers: &[(String, String)],
+		name: &str,
+	) -> Result<Option<String>, SourceError> {
+		let builder = reqwest::blocking::Client::builder()
+			.timeout(std::time::Duration::from_secs(self.timeout_secs))
+			.redirect(self.redirects().to_reqwest());
+		let client = proxy::apply(builder, self.proxy())?
+			.build()
+			.map_err(|e| SourceError::Network(format!("Failed to create HTTP client: {}", e)))?;
+
+		let mut request = client.get(url);
+		for (name, value) in headers {
+			request = request.header(name.as_str(), value.as_str());
+		}
+
+		let response = self.send(request)?;
+		self.check_success(&response, url)?;
+
+		let values = response
+			.headers()
+			.get_all(name)
+			.iter()
+			.map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+			.collect::<Vec<_>>();
+		Ok((!values.is_empty()).then(|| values.join(", ")))
+	}
+
+	/// Get the value of the cited response header as the current content, fetching it with the
+	/// given function
+	///
+	/// The function is given the URL, the headers to send with the request and the name of the
+	/// response header.
+	fn get_response_header_with<F>(
+		&self,
+		name: &str,
+		mut fetch_header: F,
+	) -> Result<CurrentHttp, SourceError>
+	where
+		F: FnMut(&str, &[(String, String)], &str) -> Result<Option<String>, SourceError>,
+	{
+		let headers = self.request_headers()?;
+		if self.respects_robots() {
+			politeness::before_request(self.source_url.as_str(), &self.user_agent(), |robots| {
+				self.fetch_http(robots, &headers)
+			})?;
+		}
+		politeness::throttle(self.source_url.as_str());
+		let value = fetch_header(self.source_url.as_str(), &headers, name)?;
+
+		let mut metadata = HashMap::new();
+		metadata.insert("fetched_at".to_string(), chrono::Utc::now().to_rfc3339());
+		metadata.insert("response_header".to_string(), name.to_string());
+
+		Ok(CurrentHttp {
+			content: value.unwrap_or_default(),
+			metadata,
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+			raw_content: None,
+		})
+	}
+
+	/// Compare the status code the URL responds with against the expected one
+	fn compare_status_with<F>(
+		&self,
+		expected: u16,
+		mut fetch_status: F,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError>
+	where
+		F: FnMut(&str, &[(String, String)]) -> Result<u16, SourceError>,
+	{
+		let headers = self.request_headers()?;
+		if self.respects_robots() {
+			politeness::before_request(self.source_url.as_str(), &self.user_agent(), |robots| {
+				self.fetch_http(robots, &headers)
+			})?;
+		}
+		politeness::throttle(self.source_url.as_str());
+		let status = fetch_status(self.source_url.as_str(), &headers)?;
+
+		let mut metadata = HashMap::new();
+		metadata.insert("fetched_at".to_string(), chrono::Utc::now().to_rfc3339());
+
+		let referenced = ReferencedHttp {
+			content: expected.to_string(),
+			metadata: HashMap::new(),
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		};
+		let current = CurrentHttp {
+			content: status.to_string(),
+			metadata,
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+			raw_content: None,
+		};
+		let diff = current.diff(&referenced)?;
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Compare the digest of the current content against an expected digest
+	fn compare_pinned_digest(
+		&self,
+		digest: &str,
+		current: CurrentHttp,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let referenced = ReferencedHttp {
+			content: current.content.clone(),
+			metadata: current.metadata.clone(),
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		};
+		let mut diff = current.diff(&referenced)?;
+		diff.pinned_digest = Some(PinnedDigest::new(digest, &current.content));
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Compare current content against the accepted values
+	///
+	/// Uses the first accepted value that matches as the reference, or the first one if none do.
+	fn compare_any_of(
+		&self,
+		current: CurrentHttp,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let mut comparison = None;
+
+		for value in &self.any_of {
+			let referenced = ReferencedHttp {
+				content: value.clone(),
+				metadata: HashMap::new(),
+				source_url: self.cited_url(),
+				match_expression: self.matches.clone(),
+			};
+			let diff = current
+				.diff(&referenced)?
+				.with_sensitivity(self.sensitivity)
+				.with_ignore_moves(self.ignore_moves)
+				.with_min_similarity(self.min_similarity);
+			let matched = diff.is_empty();
+
+			if matched || comparison.is_none() {
+				comparison = Some((referenced, diff));
+			}
+			if matched {
+				break;
+			}
+		}
+
+		let (referenced, diff) = comparison.ok_or_else(|| {
+			SourceError::ContentParsing("any_of requires at least one accepted value".to_string())
+		})?;
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Compare the number in the current content against the expected number
+	fn compare_expected_number(
+		&self,
+		expected: ExpectedNumber,
+		current: CurrentHttp,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let referenced = ReferencedHttp {
+			content: current.content.clone(),
+			metadata: HashMap::new(),
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		};
+		let mut diff = current.diff(&referenced)?;
+		diff.referenced_content = expected.to_string();
+		diff.number_check = Some(NumberCheck::new(expected, &current.content)?);
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Compare the length of the current content against the expected range
+	fn compare_expected_length(
+		&self,
+		expected: ExpectedLength,
+		current: CurrentHttp,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let referenced = ReferencedHttp {
+			content: current.content.clone(),
+			metadata: HashMap::new(),
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		};
+		let mut diff = current.diff(&referenced)?;
+		diff.referenced_content = expected.to_string();
+		diff.length_check = Some(LengthCheck::new(expected, &current.content));
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Check the current content against the constraint of the citation
+	fn compare_constraint(
+		&self,
+		constraint: &ContentConstraint,
+		current: CurrentHttp,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let referenced = ReferencedHttp {
+			content: current.content.clone(),
+			metadata: HashMap::new(),
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		};
+		let mut diff = current.diff(&referenced)?;
+		diff.referenced_content = constraint.to_string();
+		diff.constraint_check = Some(ConstraintCheck::new(constraint.clone(), &current.content)?);
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Check the current document for the anchor the citation requires
+	fn compare_anchor(
+		&self,
+		anchor: &str,
+		current: CurrentHttp,
+	) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let referenced = ReferencedHttp {
+			content: current.content.clone(),
+			metadata: HashMap::new(),
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		};
+		let mut diff = current.diff(&referenced)?;
+		diff.referenced_content = format!("#{}", anchor);
+		let document = current.raw_content.as_deref().unwrap_or(&current.content);
+		diff.anchor_check = Some(AnchorCheck::new(anchor, document));
+		Ok(Comparison::new(referenced, current, diff))
+	}
+
+	/// Diff the selected JSON fields of the referenced and current content
+	fn diff_json_fields(
+		&self,
+		referenced: &ReferencedHttp,
+		current: &CurrentHttp,
+	) -> Result<HttpDiff, SourceError> {
+		let projected_referenced = ReferencedHttp {
+			content: self.json_fields.project(&referenced.content)?,
+			..referenced.clone()
+		};
+		let projected_current =
+			CurrentHttp { content: self.json_fields.project(&current.content)?, ..current.clone() };
+
+		let mut diff = projected_current.diff(&projected_referenced)?;
+		diff.changed_fields =
+			self.json_fields.changed_fields(&referenced.content, &current.content)?;
+		Ok(diff)
+	}
+
+	/// Extract content using the match expression, then decode and normalize it
+	fn extract_content(
+		&self,
+		raw_content: &str,
+		metadata: &mut HashMap<String, String>,
+	) -> Result<String, SourceError> {
+		let decoded = self.extract_decoded(raw_content, metadata)?;
+		let normalized = normalize::normalize(&self.normalizers, &decoded)?;
+		match self.normalize_mode.normalizer() {
+			Some(normalizer) => normalizer.apply(&normalized),
+			None => Ok(normalized),
+		}
+	}
+
+	/// Extract content using the match expression, then decode it
+	fn extract_decoded(
+		&self,
+		raw_content: &str,
+		metadata: &mut HashMap<String, String>,
+	) -> Result<String, SourceError> {
+		if self.strict_match {
+			self.matches.check_unambiguous(raw_content)?;
+		}
+
+		let MatchExpression::FirstOf(alternatives) = &self.matches else {
+			return self.decoding.decode(&self.matches.extract_from(raw_content)?);
+		};
+
+		// Record which alternative matched, so that a fallback taking over is visible; for
+		// paginated sources, the first page decides
+		let extracted = self.matches.extract_alternative(raw_content)?;
+		if let Some((index, _)) = &extracted {
+			metadata
+				.entry("matched_alternative".to_string())
+				.or_insert_with(|| alternatives[*index].to_string());
+		}
+		self.decoding
+			.decode(&extracted.map(|(_, extracted)| extracted).unwrap_or_default())
+	}
+
+	/// Flushes the cache for this source
+	pub fn flush_cache(&self) -> Result<(), SourceError> {
+		match self.cache.delete(self.id()) {
+			Ok(_) => Ok(()),
+			Err(CacheError::CacheFileNotFound(_)) => Ok(()),
+			Err(e) => Err(SourceError::Cache(format!("Failed to flush cache: {}", e))),
+		}
+	}
+}
+
+/// Decode the HTML entities of content matched from the raw document, e.g. `&amp;` and `&#38;`
+/// both decode to `&`, as text extracted from elements is
+fn decode_entities(content: &str) -> String {
+	html_escape::decode_html_entities(content).into_owned()
+}
+
+/// Read a response body, aborting once it exceeds `max_bytes`
+/// Read a body up to the cap; failing to read it, e.g. as the connection was reset, is
+/// transient
+fn read_capped_bytes(
+	body: impl std::io::Read,
+	max_bytes: usize,
+	url: &str,
+) -> Result<Vec<u8>, retry::Failure> {
+	let mut buffer = Vec::new();
+	body.take(max_bytes as u64 + 1).read_to_end(&mut buffer).map_err(|e| {
+		retry::Failure::Transient(SourceError::Network(format!(
+			"Failed to read response body: {}",
+			e
+		)))
+	})?;
+
+	if buffer.len() > max_bytes {
+		return Err(retry::Failure::Fatal(body_too_large(max_bytes, url)));
+	}
+
+	Ok(buffer)
+}
+
+fn body_too_large(max_bytes: usize, url: &str) -> SourceError {
+	SourceError::Network(format!(
+		"HTTP response body exceeds the maximum size of {} bytes: {}",
+		max_bytes, url
+	))
+}
+
+/// Determine cache behavior for macro usage based on environment variables and keyword arguments
+///
+/// Environment variable CACHE_RESET takes precedence:
+/// - CACHE_RESET=OVERWRITE -> Resets the cache once for the whole build (see
+///   [cite_cache::Cache::reset_for_build]), so that entries of earlier builds are misses while
+///   those written by other units of this build are shared. If the reset cannot be recorded,
+///   CacheBehavior::Ignored forces a fresh fetch instead
+/// - CACHE_RESET=NONE -> Uses default behavior
+///
+/// If no environment override, uses the provided cache_override or defaults to Enabled
+fn determine_cache_behavior_for_macro(
+	cache_override: Option<cite_cache::CacheBehavior>,
+) -> cite_cache::CacheBehavior {
+	// Check environment variable first (takes precedence)
+	if let Ok(cache_reset) = std::env::var("CACHE_RESET") {
+		match cache_reset.to_uppercase().as_str() {
+			"OVERWRITE" => {
+				let reset = cite_cache::CacheBuilder::default()
+					.build()
+					.ok()
+					.map(|cache| cache.reset_for_build(&cite_cache::build_id()));
+				if !matches!(reset, Some(Ok(_))) {
+					return cite_cache::CacheBehavior::Ignored;
+				}
+			}
+			"NONE" => {
+				// Fall through to use provided behavior or default
+			}
+			_ => {
+				// Invalid value, fall through to default behavior
+			}
+		}
+	}
+
+	// Use provided cache behavior or default to Enabled
+	cache_override.unwrap_or(cite_cache::CacheBehavior::Enabled)
+}
+
+impl Source<ReferencedHttp, CurrentHttp, HttpDiff> for HttpMatch {
+	fn id(&self) -> &Id {
+		&self.id
+	}
+
+	fn target_description(&self) -> String {
+		if let Some(name) = &self.response_header {
+			return format!("URL: {}\nHeader: {}", self.cited_url().as_str(), name);
+		}
+		match &self.ref_url {
+			Some(ref_url) => format!(
+				"URL: {}\nReference URL: {}\nMatch: {}",
+				self.cited_url().as_str(),
+				ref_url.as_str(),
+				self.matches
+			),
+			None => format!("URL: {}\nMatch: {}", self.cited_url().as_str(), self.matches),
+		}
+	}
+
+	/// Offline, a cached reference stands in for the content that cannot be fetched
+	fn supports_offline(&self) -> bool {
+		self.cached_referenced().is_some()
+	}
+
+	/// Accept the current content as the new reference
+	///
+	/// Cached sources overwrite their cache entry and manifest sources record the current digest
+	/// in the checksum manifest. Sources whose reference is part of the citation itself, i.e. a
+	/// pinned digest, accepted values, an expected number or length, a constraint, a required
+	/// anchor or a reference URL, cannot be refreshed and must be edited instead.
+	fn refresh(&self) -> Result<(), SourceError> {
+		if self.digest.is_some()
+			|| !self.any_of.is_empty()
+			|| self.expect_num.is_some()
+			|| self.expect_len.is_some()
+			|| self.constraint.is_some()
+			|| self.require_anchor.is_some()
+			|| self.ref_url.is_some()
+		{
+			return Err(SourceError::ManualUpdate(
+				"Citations with a pinned digest, accepted values, an expected number or length, a constraint, a required anchor or a reference URL must be updated in the source"
+					.to_string(),
+			));
+		}
+
+		if self.manifest {
+			let current = self.get_current()?;
+			let mut manifest = self.cache.manifest().map_err(|e| {
+				SourceError::Cache(format!("Failed to read checksum manifest: {}", e))
+			})?;
+			manifest.insert(&self.id, &content_digest(&current.content));
+			return self.cache.write_manifest(&manifest).map_err(|e| {
+				SourceError::Cache(format!("Failed to write checksum manifest: {}", e))
+			});
+		}
+
+		self.cache
+			.refresh(self)
+			.map(|_| ())
+			.map_err(|e| SourceError::Cache(format!("Failed to refresh cache: {}", e)))
+	}
+
+	fn get(&self) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
+		let _span = cite_core::trace_span!("http", url = self.source_url.as_str());
+
+		// Status citations compare the status code alone
+		if let Some(status) = self.expect_status {
+			return self
+				.compare_status_with(status, |url, headers| self.fetch_status(url, headers));
+		}
+
+		// A pinned digest stands in for the cached reference
+		if let Some(digest) = &self.digest {
+			return self.compare_pinned_digest(digest, self.get_current()?);
+		}
+
+		// So does the digest recorded for this source in the committed checksum manifest
+		if self.manifest {
+			let manifest = self.cache.manifest().map_err(|e| {
+				SourceError::Cache(format!("Failed to read checksum manifest: {}", e))
+			})?;
+			let digest = manifest.digest(&self.id).unwrap_or_default();
+			return self.compare_pinned_digest(digest, self.get_current()?);
+		}
+
+		// Accepted values stand in for the cached reference
+		if !self.any_of.is_empty() {
+			return self.compare_any_of(self.get_current()?);
+		}
+
+		// So does an expected number
+		if let Some(expected) = self.expect_num {
+			return self.compare_expected_number(expected, self.get_current()?);
+		}
+
+		// Or an expected length
+		if let Some(expected) = self.expect_len {
+			return self.compare_expected_length(expected, self.get_current()?);
+		}
+
+		// A constraint replaces the reference altogether
+		if let Some(constraint) = &self.constraint {
+			return self.compare_constraint(constraint, self.get_current()?);
+		}
+
+		// As does a required anchor
+		if let Some(anchor) = &self.require_anchor {
+			return self.compare_anchor(anchor, self.get_current()?);
+		}
+
+		// Use the internal cache with the configured behavior, without extracting raw content
+		// identical to what the cached reference was extracted from. A pinned reference URL
+		// supplies the reference instead of the cited URL.
+		let cached_with_raw_digest = match self.ref_url {
+			Some(_) => None,
+			None => self.cached_with_raw_digest()?,
+		};
+		let mut comparison = match (&self.ref_url, cached_with_raw_digest) {
+			(Some(ref_url), _) => {
+				self.compare_ref_url_with(ref_url, |source| source.get_current())?
+			}
+			(None, Some(referenced)) => {
+				let raw = self.fetch_raw_since(&referenced.validators())?;
+				self.compare_raw_with(referenced, raw, |raw| self.extract_raw(raw))?
+			}
+			(None, None) => {
+				let note = self.unreferenced_note()?;
+				let comparison = self
+					.cache
+					.get_source_with_cache(self, self.cache_behavior.clone())
+					.map_err(|e| SourceError::Network(format!("Cache error: {}", e)))?;
+				match note {
+					Some(note) => comparison.with_note(note),
+					None => comparison,
+				}
+			}
+		};
+		if !self.json_fields.is_empty() {
+			comparison.diff =
+				self.diff_json_fields(comparison.referenced(), comparison.current())?;
+		}
+		comparison.diff = comparison
+			.diff
+			.with_sensitivity(self.sensitivity)
+			.with_ignore_moves(self.ignore_moves)
+			.with_min_similarity(self.min_similarity);
+		Ok(comparison)
+	}
+
+	fn get_referenced(&self) -> Result<ReferencedHttp, SourceError> {
+		// This method provides a fallback when no cache is available
+		// In practice, the cache system should be used via Cache::get_source_with_cache()
+		// which will provide the actual referenced content from the cache
+		//
+		// For sources without cache, we return the current content as the reference
+		// This makes the source "self-referencing" on first use
+		let current = self.get_current()?;
+
+		Ok(ReferencedHttp {
+			content: current.content,
+			metadata: current.metadata,
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+		})
+	}
+
+	fn get_current(&self) -> Result<CurrentHttp, SourceError> {
+		// Header citations compare the value of the header instead of the extracted body
+		if let Some(name) = &self.response_header {
+			return self.get_response_header_with(name, |url, headers, name| {
+				self.fetch_response_header(url, headers, name)
+			});
+		}
+		self.extract_raw(self.fetch_raw()?)
+	}
+}
+
+/// Pages fetched for a citation, before their content is extracted
+#[derive(Debug, Clone, PartialEq)]
+struct RawHttp {
+	/// The fetched pages, of which there is one unless the source is paginated
+	pages: Vec<String>,
+	/// The `Content-Type` the cited URL was served with, if known
+	content_type: Option<String>,
+	/// The URL the redirects of the cited URL ended at, if they were followed
+	final_url: Option<String>,
+	/// The validators the cited URL was served with
+	validators: CacheValidators,
+	/// Whether the server answered a conditional request for the cited URL with
+	/// `304 Not Modified`, leaving the pages empty
+	not_modified: bool,
+}
+
+/// Body of a fetched page, with what the response told about it
+#[derive(Debug, Clone, PartialEq)]
+struct FetchedPage {
+	/// The body, as text
+	body: String,
+	/// The `Content-Type` the page was served with, if any
+	content_type: Option<String>,
+	/// The URL the redirects of the request ended at, if they were followed
+	final_url: Option<String>,
+	/// The validators the page was served with
+	validators: CacheValidators,
+	/// Whether the server answered a conditional request with `304 Not Modified`, without a
+	/// body
+	not_modified: bool,
+}
+
+impl HttpMatch {
+	/// Fetch the pages of the citation, rendering them if the source is rendered
+	fn fetch_raw(&self) -> Result<RawHttp, SourceError> {
+		self.fetch_raw_since(&CacheValidators::default())
+	}
+
+	/// Fetch the pages of the citation, the cited URL only if it changed since it was served
+	/// with the given validators
+	///
+	/// Requests are only conditional for sources of one page that is not rendered, since the
+	/// following pages of a paginated source are found in the body of the first.
+	fn fetch_raw_since(&self, validators: &CacheValidators) -> Result<RawHttp, SourceError> {
+		// Rendered pages hold rendered HTML, whatever they were served as
+		if let Some(renderer) = self.active_renderer()? {
+			return self.fetch_raw_with(|url, headers| {
+				if politeness::is_robots_txt(url) {
+					return self.fetch_http(url, headers);
+				}
+				Ok(cite_core::trace_timed!(renderer.render(url, headers)?, url, "rendered"))
+			});
+		}
+
+		let conditional = match self.pagination {
+			Pagination::None => validators.conditional_headers(),
+			Pagination::RelNext => Vec::new(),
+		};
+
+		// The content type, final URL and validators are those of the cited URL, not of
+		// robots.txt or of following pages
+		let mut cited_page = None;
+		let mut raw = self.fetch_raw_with(|url, headers| {
+			let is_cited = url == self.source_url.as_str();
+			let headers = match is_cited {
+				true => [headers, conditional.as_slice()].concat(),
+				false => headers.to_vec(),
+			};
+			let page = cite_core::trace_timed!(self.fetch_page(url, &headers)?, url, "fetched");
+			if is_cited {
+				cited_page =
+					Some((page.content_type, page.final_url, page.validators, page.not_modified));
+			}
+			Ok(page.body)
+		})?;
+		(raw.content_type, raw.final_url, raw.validators, raw.not_modified) =
+			cited_page.unwrap_or_default();
+		Ok(raw)
+	}
+
+	/// Get the current content, fetching each page with the given function
+	///
+	/// The function is given the URL of the page and the headers to send with the request.
+	fn get_current_with<F>(&self, fetch: F) -> Result<CurrentHttp, SourceError>
+	where
+		F: FnMut(&str, &[(String, String)]) -> Result<String, SourceError>,
+	{
+		self.extract_raw(self.fetch_raw_with(fetch)?)
+	}
+
+	/// Fetch the pages of the citation with the given function, see [HttpMatch::get_current_with]
+	fn fetch_raw_with<F>(&self, mut fetch: F) -> Result<RawHttp, SourceError>
+	where
+		F: FnMut(&str, &[(String, String)]) -> Result<String, SourceError>,
+	{
+		let headers = self.request_headers()?;
+		let user_agent = self.user_agent();
+		let respects_robots = self.respects_robots();
+		let mut fetch = |url: &str| {
+			if respects_robots {
+				politeness::before_request(url, &user_agent, |robots| fetch(robots, &headers))?;
+			}
+			politeness::throttle(url);
+			fetch(url, &headers)
+		};
+		let pages = match self.pagination {
+			Pagination::None => vec![fetch(self.source_url.as_str())?],
+			Pagination::RelNext => {
+				pagination::fetch_pages(self.source_url.as_str(), self.max_pages, fetch)?
+			}
+		};
+		Ok(RawHttp {
+			pages,
+			content_type: None,
+			final_url: None,
+			validators: CacheValidators::default(),
+			not_modified: false,
+		})
+	}
+
+	/// Extract the current content from the fetched pages
+	///
+	/// The digest of the raw content is recorded in the metadata, so that a later fetch of the
+	/// same bytes can skip extraction, see [HttpMatch::compare_raw_with].
+	fn extract_raw(&self, raw: RawHttp) -> Result<CurrentHttp, SourceError> {
+		let mut metadata = HashMap::new();
+		let extracted_content = raw
+			.pages
+			.iter()
+			.map(|page| self.extract_content(page, &mut metadata))
+			.collect::<Result<Vec<_>, _>>()?
+			.join("\n");
+		if self.pagination == Pagination::RelNext {
+			metadata.insert("pages".to_string(), raw.pages.len().to_string());
+		}
+		if let Some(content_type) = raw.content_type {
+			metadata.insert("content_type".to_string(), content_type);
+		}
+		if let Some(final_url) = raw.final_url {
+			metadata.insert("final_url".to_string(), final_url);
+		}
+		raw.validators.record(&mut metadata);
+
+		let raw_content = raw.pages.join("\n");
+		metadata.insert("fetched_at".to_string(), chrono::Utc::now().to_rfc3339());
+		metadata.insert("content_length".to_string(), raw_content.len().to_string());
+		metadata.insert("raw_digest".to_string(), content_digest(&raw_content));
+		cite_core::trace_event!(
+			raw_length = raw_content.len(),
+			extracted_length = extracted_content.len(),
+			"extracted"
+		);
+
+		Ok(CurrentHttp {
+			content: extracted_content,
+			metadata,
+			source_url: self.cited_url(),
+			match_expression: self.matches.clone(),
+			raw_content: Some(raw_content),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn test_match_expression_regex() -> Result<(), anyhow::Error> {
+		let expr = MatchExpression::regex(".*");
+		let content = "Hello, world!";
+		let extracted = expr.extract_from(content)?;
+		assert_eq!(extracted, content);
+		Ok(())
+	}
+
+	#[test]
+	fn test_source_url_validation() -> Result<(), anyhow::Error> {
+		let url = SourceUrl::new("https://example.com/page")?;
+		assert_eq!(url.as_str(), "https://example.com/page");
+		assert_eq!(url.normalized(), "https://example.com/page");
+
+		let url_with_slash = SourceUrl::new("https://example.com/")?;
+		assert_eq!(url_with_slash.normalized(), "https://example.com");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_source_url_invalid() {
+		let result = SourceUrl::new("ftp://example.com");
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn test_http_match_creation() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::cached("https://example.com", ".*")?;
+		assert_eq!(http_match.source_url.as_str(), "https://example.com");
+		assert_eq!(http_match.target_description(), "URL: https://example.com\nMatch: regex:.*");
+
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/docs",
+			MatchExpression::between("<!-- start -->", "<!-- end -->"),
+		)?;
+		assert_eq!(
+			http_match.target_description(),
+			"URL: https://example.com/docs\nMatch: between:<!-- start -->..<!-- end -->"
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_source_fetch() -> Result<(), anyhow::Error> {
+		// Use FullDocument to get the entire page content
+		let http_match = HttpMatch::with_match_expression_and_cache_behavior(
+			"https://example.com",
+			MatchExpression::full_document(),
+			cite_cache::CacheBehavior::Enabled,
+		)?;
+		let current = http_match.get_current()?;
+
+		// Real example.com should contain "Example Domain"
+		assert!(current.content.contains("Example Domain"));
+		assert!(current.metadata.contains_key("fetched_at"));
+		assert_eq!(current.source_url.as_str(), "https://example.com");
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_diff() -> Result<(), anyhow::Error> {
+		let referenced = ReferencedHttp {
+			content: "old content".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+		};
+
+		let current = CurrentHttp {
+			content: "new content".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+			raw_content: None,
+		};
+
+		let diff = current.diff(&referenced)?;
+		assert!(diff.content_changed);
+		assert!(!diff.url_changed);
+		assert!(!diff.match_expression_changed);
+		assert!(!diff.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_diff_decodes_entities() -> Result<(), anyhow::Error> {
+		let source_url = SourceUrl::new("https://example.com")?;
+		let referenced = |content: &str| ReferencedHttp {
+			content: content.to_string(),
+			metadata: HashMap::new(),
+			source_url: source_url.clone(),
+			match_expression: MatchExpression::regex("<code>.*</code>"),
+		};
+		let current = |content: &str| CurrentHttp {
+			content: content.to_string(),
+			metadata: HashMap::new(),
+			source_url: source_url.clone(),
+			match_expression: MatchExpression::regex("<code>.*</code>"),
+			raw_content: None,
+		};
+
+		// The page switched from named to numeric entities, or to none at all
+		let encoded = referenced("<code>Vec&lt;u8&gt; &amp;&amp; &quot;ok&quot;</code>");
+		let numeric = current("<code>Vec&#60;u8&#x3E; &#38;&#38; &#34;ok&#34;</code>");
+		let decoded = current("<code>Vec<u8> && \"ok\"</code>");
+		assert!(numeric.diff(&encoded)?.is_empty());
+		assert!(decoded.diff(&encoded)?.is_empty());
+
+		// Real changes are still drift, and are shown decoded
+		let changed = current("<code>Vec&lt;u16&gt; &amp;&amp; &quot;ok&quot;</code>");
+		let diff = changed.diff(&encoded)?;
+		assert!(!diff.is_empty());
+		assert!(diff.unified_diff.as_deref().is_some_and(|diff| diff.contains("Vec<u16>")));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_extracted_content_decodes_entities() -> Result<(), anyhow::Error> {
+		let page = "<p>Tom &amp; Jerry &mdash; &#x3C;T&#62; &unknown; R&D</p>";
+		let text = "Tom & Jerry — <T> &unknown; R&D";
+
+		// Content matched from the raw document decodes like text extracted from elements
+		assert_eq!(MatchExpression::regex("<p>(.*)</p>").extract_from(page)?, text);
+		assert_eq!(MatchExpression::between("<p>", "</p>").extract_from(page)?, text);
+		assert_eq!(MatchExpression::css_selector("p").extract_from(page)?, text);
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_content_type_from_header() -> Result<(), anyhow::Error> {
+		let source_url = SourceUrl::new("https://example.com/api")?;
+		let current = |match_expression: MatchExpression, content_type: Option<&str>| {
+			let metadata = content_type
+				.map(|content_type| ("content_type".to_string(), content_type.to_string()))
+				.into_iter()
+				.collect();
+			CurrentHttp {
+				content: "{}".to_string(),
+				metadata,
+				source_url: source_url.clone(),
+				match_expression,
+				raw_content: None,
+			}
+		};
+
+		let json = current(MatchExpression::FullDocument, Some("application/json; charset=utf-8"));
+		assert_eq!(json.content_type(), ContentType::Json);
+		let html = current(MatchExpression::FullDocument, Some("text/html"));
+		assert_eq!(html.content_type(), ContentType::Html);
+		let image = current(MatchExpression::FullDocument, Some("image/png"));
+		assert_eq!(image.content_type(), ContentType::Binary);
+
+		// Extracted content is text whatever the document was, as is content of an unknown type
+		let extracted = current(MatchExpression::json_path("$.name"), Some("application/json"));
+		assert_eq!(extracted.content_type(), ContentType::Text);
+		let untyped = current(MatchExpression::FullDocument, None);
+		assert_eq!(untyped.content_type(), ContentType::Text);
+
+		// The referenced content keeps the type it was fetched with
+		let referenced = ReferencedHttp {
+			content: json.content.clone(),
+			metadata: json.metadata.clone(),
+			source_url: source_url.clone(),
+			match_expression: MatchExpression::FullDocument,
+		};
+		assert_eq!(referenced.content_type(), ContentType::Json);
+
+		// Drift of binary content renders no text diff
+		let referenced_image = ReferencedHttp {
+			content: image.content.clone(),
+			metadata: image.metadata.clone(),
+			source_url: source_url.clone(),
+			match_expression: MatchExpression::FullDocument,
+		};
+		let changed_image = CurrentHttp { content: "[]".to_string(), ..image };
+		let diff = changed_image.diff(&referenced_image)?;
+		let comparison = Comparison::new(referenced_image, changed_image, diff);
+		assert_eq!(comparison.render_diff(DiffGranularity::Lines), None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_diff_sensitivity() -> Result<(), anyhow::Error> {
+		let referenced = ReferencedHttp {
+			content: "guarantee\n".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+		};
+
+		let appended = CurrentHttp {
+			content: "guarantee\naddition\n".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+			raw_content: None,
+		};
+
+		// Appending content is not drift when only removals matter
+		let diff = appended.diff(&referenced)?;
+		assert!(diff.clone().with_sensitivity(DiffSensitivity::Removals).is_empty());
+		assert!(!diff.with_sensitivity(DiffSensitivity::Both).is_empty());
+
+		let removed = CurrentHttp { content: String::new(), ..appended };
+
+		// Removing content is drift when only removals matter
+		let diff = removed.diff(&referenced)?;
+		assert!(!diff.clone().with_sensitivity(DiffSensitivity::Removals).is_empty());
+		assert!(diff.with_sensitivity(DiffSensitivity::Additions).is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_diff_min_similarity() -> Result<(), anyhow::Error> {
+		let paragraph = "The client retries failed requests three times, waiting a little longer \
+			before each attempt, and reports the last error once every attempt has failed.";
+		let referenced = ReferencedHttp {
+			content: paragraph.to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::css_selector("p"),
+		};
+		let current = |content: &str| -> Result<CurrentHttp, anyhow::Error> {
+			Ok(CurrentHttp {
+				content: content.to_string(),
+				metadata: HashMap::new(),
+				source_url: SourceUrl::new("https://example.com")?,
+				match_expression: MatchExpression::css_selector("p"),
+				raw_content: None,
+			})
+		};
+		let bound = Some(MinSimilarity::from_str("0.95").map_err(anyhow::Error::msg)?);
+
+		// A minor wording change stays above the bound
+		let reworded = current(&paragraph.replace("a little longer", "a bit longer"))?;
+		let diff = reworded.diff(&referenced)?;
+		assert!(diff.similarity() > 0.95 && diff.similarity() < 1.0);
+		assert!(!diff.clone().with_min_similarity(None).is_empty());
+		assert!(diff.with_min_similarity(bound).is_empty());
+
+		// A rewrite falls below it
+		let rewritten = current("Failed requests are not retried; the error is returned at once.")?;
+		let diff = rewritten.diff(&referenced)?;
+		assert!(diff.similarity() < 0.95);
+		assert!(!diff.with_min_similarity(bound).is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_diff_moved_block() -> Result<(), anyhow::Error> {
+		let referenced = ReferencedHttp {
+			content: "Install\nstep one\nstep two\nConfigure\nUse\nTroubleshoot\n".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+		};
+
+		// The steps moved to the end without changing
+		let moved = CurrentHttp {
+			content: "Install\nConfigure\nUse\nTroubleshoot\nstep one\nstep two\n".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+			raw_content: None,
+		};
+
+		let diff = moved.diff(&referenced)?;
+		assert!(diff.has_only_moves());
+		assert_eq!(
+			diff.unified_diff(),
+			Some(" Install\n<step one\n<step two\n Configure\n Use\n Troubleshoot\n>step one\n>step two\n")
+		);
+
+		// The diff renders like any other text diff of the crate
+		let rendered =
+			render_text_diff(&referenced.content, &moved.content, DiffGranularity::Lines);
+		assert_eq!(diff.unified_diff(), rendered.as_deref());
+		assert!(!diff.clone().with_ignore_moves(false).is_empty());
+		assert!(diff.with_ignore_moves(true).is_empty());
+
+		// A moved step that also changed is drift regardless
+		let changed = CurrentHttp { content: moved.content.replace("step two", "step 2"), ..moved };
+		let diff = changed.diff(&referenced)?;
+		assert!(!diff.has_only_moves());
+		assert!(!diff.with_ignore_moves(true).is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_http_describe_change() -> Result<(), anyhow::Error> {
+		let referenced = ReferencedHttp {
+			content: "<p>Install with cargo</p>\n<p>Then run it</p>\n".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::regex(".*"),
+		};
+		let current = |content: &str| CurrentHttp {
+			content: content.to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com").unwrap(),
+			match_expression: MatchExpression::regex(".*"),
+			raw_content: None,
+		};
+		let change =
+			|content: &str| current(content).diff(&referenced).map(|diff| diff.describe_change());
+
+		assert_eq!(change("<p>Install with cargo</p>\n<p>Then run it</p>\n")?, None);
+		assert_eq!(
+			change("<p>Install with cargo</p>  \r\n\n<p>Then run it</p>\n")?,
+			Some(ChangeClass::Whitespace)
+		);
+		assert_eq!(
+			change("<p>Install with cargo</p>\n<p>Then  run it</p>\n")?,
+			Some(ChangeClass::Text)
+		);
+		assert_eq!(
+			change("<p>Install with cargo</p>\n<p>Then run the binary</p>\n")?,
+			Some(ChangeClass::Text)
+		);
+		assert_eq!(
+			change("<p>Install with cargo</p>\n<ul><li>Then run it</li></ul>\n")?,
+			Some(ChangeClass::Structure)
+		);
+
+		Ok(())
 	}
 
-	/// Convert match expression to a safe cache key component
-	fn match_expression_to_cache_key(expression: &MatchExpression) -> String {
-		let key = match expression {
-			MatchExpression::Regex(pattern) => format!("regex_{}", pattern),
-			MatchExpression::CssSelector(selector) => format!("css_{}", selector),
-			MatchExpression::Fragment(fragment) => format!("frag_{}", fragment),
-			MatchExpression::XPath(xpath) => format!("xpath_{}", xpath),
-			MatchExpression::FullDocument => "full".to_string(),
+	#[test]
+	fn test_content_digest() {
+		assert_eq!(
+			content_digest("abc"),
+			"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+		);
+	}
+
+	#[test]
+	fn test_pinned_digest() -> Result<(), anyhow::Error> {
+		let referenced = ReferencedHttp {
+			content: "abc".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::css_selector("h1"),
+		};
+		let current = CurrentHttp {
+			content: "abc".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.com")?,
+			match_expression: MatchExpression::css_selector("h1"),
+			raw_content: None,
 		};
 
-		// Make it filesystem-safe
-		key.chars()
-			.map(|c| match c {
-				'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
-				_ => '_',
+		// Matching digest (case-insensitive)
+		let mut diff = current.diff(&referenced)?;
+		diff.pinned_digest = Some(PinnedDigest::new(
+			"BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD",
+			&current.content,
+		));
+		assert!(diff.is_empty());
+
+		// Mismatching digest
+		diff.pinned_digest = Some(PinnedDigest::new("abc123", &current.content));
+		assert!(!diff.is_empty());
+		assert_eq!(diff.pinned_digest.map(|d| d.current), Some(content_digest("abc")));
+
+		// Unpinned digest reports the current digest
+		let pinned = PinnedDigest::new("", &current.content);
+		assert!(pinned.is_unpinned());
+		assert!(!pinned.matches());
+		assert_eq!(pinned.current, content_digest("abc"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_capped_body() -> Result<(), anyhow::Error> {
+		// An endless body is aborted once it exceeds the cap
+		let result = read_capped_bytes(std::io::repeat(b'a'), 1024, "https://example.com");
+		match result {
+			Err(retry::Failure::Fatal(SourceError::Network(message))) => {
+				assert!(message.contains("exceeds the maximum size of 1024 bytes"));
+			}
+			_ => panic!("expected the body to exceed the cap"),
+		}
+
+		// A body cut off mid-transfer is read again on retry
+		struct Reset;
+		impl std::io::Read for Reset {
+			fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+				Err(std::io::ErrorKind::ConnectionReset.into())
+			}
+		}
+		let result = read_capped_bytes(Reset, 1024, "https://example.com");
+		assert!(matches!(result, Err(retry::Failure::Transient(_))));
+
+		// A body within the cap is read in full
+		let body = read_capped_bytes(&b"<h1>small</h1>"[..], 1024, "https://example.com")
+			.map_err(retry::Failure::into_error)?;
+		assert_eq!(body, b"<h1>small</h1>");
+
+		let http_match = HttpMatch::cached("https://example.com", ".*")?;
+		assert_eq!(http_match.max_bytes(), DEFAULT_MAX_BYTES);
+		assert_eq!(http_match.with_max_bytes(1024).max_bytes(), 1024);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_any_of() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::cached("https://example.com", ".*")?.with_any_of(vec![
+			"Hello".to_string(),
+			"Hi".to_string(),
+			"Hey".to_string(),
+		]);
+		let current = |content: &str| -> Result<CurrentHttp, anyhow::Error> {
+			Ok(CurrentHttp {
+				content: content.to_string(),
+				metadata: HashMap::new(),
+				source_url: SourceUrl::new("https://example.com")?,
+				match_expression: MatchExpression::regex(".*"),
+				raw_content: None,
 			})
-			.collect()
+		};
+
+		// Matches one of the accepted values
+		let comparison = http_match.compare_any_of(current("Hi")?)?;
+		assert!(comparison.is_same());
+		assert_eq!(comparison.referenced().content, "Hi");
+
+		// Matches none of the accepted values
+		let comparison = http_match.compare_any_of(current("Goodbye")?)?;
+		assert!(!comparison.is_same());
+		assert_eq!(comparison.referenced().content, "Hello");
+
+		Ok(())
 	}
 
-	/// Fetch http content from the URL using synchronous HTTP client
-	fn fetch_http(&self) -> Result<String, SourceError> {
-		// Use blocking reqwest client for synchronous HTTP requests
-		// This is appropriate for compile-time macro execution
-		let client = reqwest::blocking::Client::builder()
-			.timeout(std::time::Duration::from_secs(30))
-			.user_agent("cite-http/1.0")
-			.build()
-			.map_err(|e| SourceError::Network(format!("Failed to create HTTP client: {}", e)))?;
+	#[test]
+	fn test_graphql_schema_drift() -> Result<(), anyhow::Error> {
+		let http_match =
+			HttpMatch::graphql("https://api.example.com/graphql", "User", Some("email"))?;
+		let introspection = |fields: serde_json::Value| {
+			serde_json::json!({
+				"data": { "__schema": { "types": [{ "kind": "OBJECT", "name": "User", "fields": fields }] } }
+			})
+			.to_string()
+		};
+		let field = |name: &str, type_name: &str| {
+			serde_json::json!({
+				"name": name,
+				"args": [],
+				"type": { "kind": "NON_NULL", "ofType": { "kind": "SCALAR", "name": type_name } }
+			})
+		};
+		let cited = http_match.get_current_with(|_url, _headers| {
+			Ok(introspection(serde_json::json!([field("id", "ID"), field("email", "String")])))
+		})?;
+		assert_eq!(cited.content, "email: String!");
+		let referenced = ReferencedHttp {
+			content: cited.content,
+			metadata: HashMap::new(),
+			source_url: http_match.source_url.clone(),
+			match_expression: http_match.matches.clone(),
+		};
 
-		let response = client
-			.get(self.source_url.as_str())
-			.send()
-			.map_err(|e| SourceError::Network(format!("HTTP request failed: {}", e)))?;
+		// Other fields of the type being added is not drift of the cited field
+		let additive = http_match.get_current_with(|_url, _headers| {
+			Ok(introspection(serde_json::json!([
+				field("id", "ID"),
+				field("email", "String"),
+				field("phone", "String")
+			])))
+		})?;
+		assert!(additive.diff(&referenced)?.is_empty());
+
+		// Its type changing is, and is reported as a breaking change
+		let changed = http_match.get_current_with(|_url, _headers| {
+			Ok(introspection(serde_json::json!([field("id", "ID"), field("email", "Email")])))
+		})?;
+		let diff = changed.diff(&referenced)?;
+		assert!(!diff.is_empty());
+		assert_eq!(
+			diff.schema_changes,
+			[SchemaChange::Changed {
+				field: "email".to_string(),
+				from: "String!".to_string(),
+				to: "Email!".to_string()
+			}]
+		);
 
-		if !response.status().is_success() {
-			return Err(SourceError::Network(format!(
-				"HTTP request failed with status {}: {}",
-				response.status(),
-				self.source_url.as_str()
-			)));
-		}
+		// As is its removal
+		let removed = http_match.get_current_with(|_url, _headers| {
+			Ok(introspection(serde_json::json!([field("id", "ID")])))
+		})?;
+		let diff = removed.diff(&referenced)?;
+		assert!(diff.schema_changes.iter().all(SchemaChange::is_breaking));
+		assert_eq!(diff.schema_changes.len(), 1);
+
+		// Citing the whole type reports added fields, which are not breaking
+		let whole_type = HttpMatch::graphql("https://api.example.com/graphql", "User", None)?;
+		let referenced = ReferencedHttp {
+			content: "email: String!\nid: ID!".to_string(),
+			match_expression: whole_type.matches.clone(),
+			..referenced
+		};
+		let additive = whole_type.get_current_with(|_url, _headers| {
+			Ok(introspection(serde_json::json!([
+				field("id", "ID"),
+				field("email", "String"),
+				field("phone", "String")
+			])))
+		})?;
+		let diff = additive.diff(&referenced)?;
+		assert!(!diff.is_empty());
+		assert_eq!(diff.schema_changes.len(), 1);
+		assert!(!diff.schema_changes[0].is_breaking());
+		Ok(())
+	}
 
-		let content = response
-			.text()
-			.map_err(|e| SourceError::Network(format!("Failed to read response body: {}", e)))?;
+	#[cfg(feature = "pdf")]
+	#[test]
+	fn test_pdf_drift() -> Result<(), anyhow::Error> {
+		let spec = include_bytes!("../tests/content/spec.pdf");
+		let revised = include_bytes!("../tests/content/spec-revised.pdf");
+		let url = "https://example.com/spec.pdf";
+		let http_match =
+			HttpMatch::with_match_expression(url, MatchExpression::regex(r"Each message.*"))?
+				.with_pdf(PdfPages::from_str("2").map_err(|e| anyhow::anyhow!(e))?);
+		assert!(http_match.cache_path.ends_with("_pdf_2"));
+
+		let cited =
+			http_match.get_current_with(|url, _headers| http_match.body_text(url, spec, None))?;
+		assert_eq!(cited.content, "Each message starts with a 4-byte length (big endian).");
+		let referenced = ReferencedHttp {
+			content: cited.content,
+			metadata: HashMap::new(),
+			source_url: http_match.source_url.clone(),
+			match_expression: http_match.matches.clone(),
+		};
 
-		Ok(content)
+		// The same text extracted again is not drift
+		let same =
+			http_match.get_current_with(|url, _headers| http_match.body_text(url, spec, None))?;
+		assert!(same.diff(&referenced)?.is_empty());
+
+		// A revision of the cited text is
+		let drifted = http_match
+			.get_current_with(|url, _headers| http_match.body_text(url, revised, None))?;
+		assert_eq!(drifted.content, "Each message starts with an 8-byte length (big endian).");
+		assert!(!drifted.diff(&referenced)?.is_empty());
+
+		// Documents that are not PDFs fail to extract
+		let html = http_match
+			.get_current_with(|url, _headers| http_match.body_text(url, b"<html></html>", None));
+		assert!(html.is_err());
+		Ok(())
 	}
 
-	/// Extract content using the match expression
-	fn extract_content(&self, raw_content: &str) -> Result<String, SourceError> {
-		self.matches.extract_from(raw_content)
+	#[test]
+	fn test_body_encoding_is_normalized() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/menu",
+			MatchExpression::regex(r"Caf.*"),
+		)?;
+		let fetch = |body: &'static [u8], content_type: Option<&'static str>| {
+			http_match
+				.get_current_with(|url, _headers| http_match.body_text(url, body, content_type))
+		};
+
+		let cited = fetch("<p>Café au lait</p>".as_bytes(), None)?;
+		assert_eq!(cited.content, "Café au lait</p>");
+		let referenced = ReferencedHttp {
+			content: cited.content,
+			metadata: HashMap::new(),
+			source_url: http_match.source_url.clone(),
+			match_expression: http_match.matches.clone(),
+		};
+
+		// A byte order mark or a Latin-1 encoding of the same text is not drift
+		let bom = fetch(b"\xef\xbb\xbf<p>Caf\xc3\xa9 au lait</p>", None)?;
+		assert!(bom.diff(&referenced)?.is_empty());
+		let latin1 = fetch(b"<p>Caf\xe9 au lait</p>", None)?;
+		assert!(latin1.diff(&referenced)?.is_empty());
+		let declared = fetch(b"<p>Caf\xe9 au lait</p>", Some("text/html; charset=iso-8859-1"))?;
+		assert!(declared.diff(&referenced)?.is_empty());
+
+		// An encoding named by the citation takes precedence over detection
+		let utf8 = http_match.clone().with_encoding(TextEncoding::Utf8);
+		assert!(utf8.cache_path.ends_with("_utf-8"));
+		let lossy = utf8.get_current_with(|url, _headers| {
+			utf8.body_text(url, b"<p>Caf\xe9 au lait</p>", None)
+		})?;
+		assert!(!lossy.diff(&referenced)?.is_empty());
+		Ok(())
 	}
 
-	/// Flushes the cache for this source
-	pub fn flush_cache(&self) -> Result<(), SourceError> {
-		match self.cache.delete(self.id()) {
-			Ok(_) => Ok(()),
-			Err(CacheError::CacheFileNotFound(_)) => Ok(()),
-			Err(e) => Err(SourceError::Cache(format!("Failed to flush cache: {}", e))),
-		}
+	#[test]
+	fn test_expect_num() -> Result<(), anyhow::Error> {
+		let compare = |expected: &str, content: &str| -> Result<_, anyhow::Error> {
+			let expected = ExpectedNumber::from_str(expected).map_err(anyhow::Error::msg)?;
+			let http_match = HttpMatch::cached("https://example.com", "latency: [^<]*")?
+				.with_expect_num(expected);
+			let current = CurrentHttp {
+				content: content.to_string(),
+				metadata: HashMap::new(),
+				source_url: SourceUrl::new("https://example.com")?,
+				match_expression: MatchExpression::regex("latency: [^<]*"),
+				raw_content: None,
+			};
+			Ok(http_match.compare_expected_number(expected, current)?)
+		};
+
+		// Values inside an absolute or percentage tolerance are not drift
+		assert!(compare("100±10", "latency: 108ms")?.is_same());
+		assert!(compare("100 ~ 10%", "latency: 91ms")?.is_same());
+
+		// Values outside it are, reporting the expected and current number
+		let comparison = compare("100±10", "latency: 130ms")?;
+		assert!(!comparison.is_same());
+		let check = comparison
+			.diff()
+			.number_check
+			.clone()
+			.ok_or(anyhow::anyhow!("No number check"))?;
+		assert_eq!((check.current, check.expected.range()), (130.0, (90.0, 110.0)));
+		assert_eq!(comparison.diff().referenced_content, "100±10");
+		assert!(!compare("100 ~ 10%", "latency: 89ms")?.is_same());
+
+		// Content without a number cannot be compared
+		assert!(compare("100", "latency: unavailable").is_err());
+
+		Ok(())
 	}
-}
 
-/// Determine cache behavior for macro usage based on environment variables and keyword arguments
-///
-/// Environment variable CACHE_RESET takes precedence:
-/// - CACHE_RESET=OVERWRITE -> CacheBehavior::Ignored (forces fresh fetch)
-/// - CACHE_RESET=NONE -> Uses default behavior
-///
-/// If no environment override, uses the provided cache_override or defaults to Enabled
-fn determine_cache_behavior_for_macro(
-	cache_override: Option<cite_cache::CacheBehavior>,
-) -> cite_cache::CacheBehavior {
-	// Check environment variable first (takes precedence)
-	if let Ok(cache_reset) = std::env::var("CACHE_RESET") {
-		match cache_reset.to_uppercase().as_str() {
-			"OVERWRITE" => return cite_cache::CacheBehavior::Ignored,
-			"NONE" => {
-				// Fall through to use provided behavior or default
-			}
-			_ => {
-				// Invalid value, fall through to default behavior
+	#[test]
+	fn test_expect_len() -> Result<(), anyhow::Error> {
+		let compare = |expected: &str, content: &str| -> Result<_, anyhow::Error> {
+			let expected = ExpectedLength::from_str(expected).map_err(anyhow::Error::msg)?;
+			let http_match = HttpMatch::with_match_expression(
+				"https://example.com",
+				MatchExpression::css_selector("#docs"),
+			)?
+			.with_expect_len(expected);
+			let current = CurrentHttp {
+				content: content.to_string(),
+				metadata: HashMap::new(),
+				source_url: SourceUrl::new("https://example.com")?,
+				match_expression: MatchExpression::css_selector("#docs"),
+				raw_content: None,
+			};
+			Ok(http_match.compare_expected_length(expected, current)?)
+		};
+
+		// Content within the bounds is not drift, whatever its text
+		assert!(compare("3..5", "Install the crate first")?.is_same());
+		assert!(compare("10..30 chars", "Install the crate")?.is_same());
+
+		// Gutted or bloated content is, reporting the expected range and current length
+		let comparison = compare("3..5", "Removed")?;
+		assert!(!comparison.is_same());
+		let check = comparison
+			.diff()
+			.length_check
+			.clone()
+			.ok_or(anyhow::anyhow!("No length check"))?;
+		assert_eq!(check.current, 1);
+		assert_eq!(comparison.diff().referenced_content, "3..5 words");
+		assert!(!compare("..10 chars", "Install the crate first")?.is_same());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_constraint() -> Result<(), anyhow::Error> {
+		let constraint = ContentConstraint::json_schema(
+			r#"{"type": "object", "required": ["version"], "properties": {"version": {"type": "string"}}}"#,
+		)
+		.map_err(anyhow::Error::msg)?;
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/release.json",
+			MatchExpression::full_document(),
+		)?
+		.with_constraint(constraint.clone());
+		let compare = |content: &str| -> Result<_, anyhow::Error> {
+			let current = CurrentHttp {
+				content: content.to_string(),
+				metadata: HashMap::new(),
+				source_url: SourceUrl::new("https://example.com/release.json")?,
+				match_expression: MatchExpression::full_document(),
+				raw_content: None,
+			};
+			Ok(http_match.compare_constraint(&constraint, current)?)
+		};
+
+		// Content that keeps conforming is not drift, however it changes
+		assert!(compare(r#"{"version": "1.0.0"}"#)?.is_same());
+		assert!(compare(r#"{"version": "2.0.0", "notes": "faster"}"#)?.is_same());
+
+		// Content that stops conforming is, reporting how
+		let comparison = compare(r#"{"version": 2}"#)?;
+		assert!(!comparison.is_same());
+		let check = comparison
+			.diff()
+			.constraint_check
+			.clone()
+			.ok_or(anyhow::anyhow!("No constraint check"))?;
+		assert_eq!(check.violations, ["/version: 2 is not of type \"string\""]);
+		assert_eq!(comparison.diff().referenced_content, constraint.to_string());
+
+		// The constraint is part of the citation, so there is no reference to refresh
+		assert!(http_match.refresh().is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_require_anchor() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/docs#installation",
+			MatchExpression::fragment("installation"),
+		)?
+		.with_require_anchor("installation");
+		let compare = |document: &str| -> Result<_, anyhow::Error> {
+			let current = CurrentHttp {
+				content: MatchExpression::fragment("installation").extract_from(document)?,
+				metadata: HashMap::new(),
+				source_url: SourceUrl::new("https://example.com/docs#installation")?,
+				match_expression: MatchExpression::fragment("installation"),
+				raw_content: Some(document.to_string()),
+			};
+			Ok(http_match.compare_anchor("installation", current)?)
+		};
+
+		// The page may change as long as the anchor stays
+		assert!(
+			compare(r#"<h2 id="installation">Installation</h2><p>cargo add cite</p>"#)?.is_same()
+		);
+		assert!(
+			compare(r#"<h2 id="installation">Install</h2><p>cargo install cite</p>"#)?.is_same()
+		);
+
+		// A page that still loads without the anchor is drift
+		let comparison = compare(r#"<h2 id="getting-started">Getting started</h2>"#)?;
+		assert!(!comparison.is_same());
+		let check = comparison
+			.diff()
+			.anchor_check
+			.clone()
+			.ok_or(anyhow::anyhow!("No anchor check"))?;
+		assert_eq!(check.anchor, "installation");
+		assert!(!check.is_found());
+		assert_eq!(comparison.diff().describe_change(), Some(ChangeClass::Structure));
+
+		// The anchor is part of the citation, so there is no reference to refresh
+		assert!(http_match.refresh().is_err());
+		Ok(())
+	}
+
+	/// Stands in for a headless browser, returning the page as its scripts render it
+	struct StubRenderer;
+
+	impl Renderer for StubRenderer {
+		fn render(&self, url: &str, _headers: &[(String, String)]) -> Result<String, SourceError> {
+			match url {
+				"https://example.com/app" => {
+					Ok(r#"<div id="root"><h1>Dashboard</h1></div>"#.to_string())
+				}
+				_ => Err(SourceError::Network(format!("unexpected render: {}", url))),
 			}
 		}
 	}
 
-	// Use provided cache behavior or default to Enabled
-	cache_override.unwrap_or(cite_cache::CacheBehavior::Enabled)
-}
+	#[test]
+	fn test_rendered_content_is_extracted() -> Result<(), anyhow::Error> {
+		let plain = HttpMatch::with_match_expression(
+			"https://example.com/app",
+			MatchExpression::css_selector("#root h1"),
+		)?;
+		let rendered = plain.clone().with_renderer(Arc::new(StubRenderer));
+		assert!(rendered.renders());
+		assert_ne!(rendered.id(), plain.id());
 
-impl Source<ReferencedHttp, CurrentHttp, HttpDiff> for HttpMatch {
-	fn id(&self) -> &Id {
-		&self.id
+		let current = rendered.get_current()?;
+		assert_eq!(current.content, "Dashboard");
+		assert_eq!(
+			current.raw_content.as_deref(),
+			Some(r#"<div id="root"><h1>Dashboard</h1></div>"#)
+		);
+
+		// Rendering without a renderer names the missing command
+		if std::env::var_os(renderer::RENDER_COMMAND_ENV).is_none() {
+			let error = plain.with_render(true).get_current().err().map(|e| e.to_string());
+			assert!(error.is_some_and(|error| error.contains("CITE_RENDER_COMMAND")));
+		}
+		Ok(())
 	}
 
-	fn get(&self) -> Result<Comparison<ReferencedHttp, CurrentHttp, HttpDiff>, SourceError> {
-		// Use the internal cache with the configured behavior
-		self.cache
-			.get_source_with_cache(self, self.cache_behavior.clone())
-			.map_err(|e| SourceError::Network(format!("Cache error: {}", e)))
+	#[test]
+	fn test_paginated_content_is_concatenated() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/docs/1",
+			MatchExpression::css_selector("main"),
+		)?
+		.with_pagination(Pagination::RelNext, 5);
+		assert_ne!(http_match.id(), HttpMatch::cached("https://example.com/docs/1", ".*")?.id());
+
+		let mut responses = HashMap::new();
+		responses.insert(
+			"https://example.com/docs/1",
+			r#"<link rel="next" href="/docs/2"><main>Part one</main>"#,
+		);
+		responses.insert(
+			"https://example.com/docs/2",
+			r#"<a rel="next" href="3">Next</a><main>Part two</main>"#,
+		);
+		// The last page links back to the first
+		responses.insert(
+			"https://example.com/docs/3",
+			r#"<a rel="next" href="/docs/1">Next</a><main>Part three</main>"#,
+		);
+
+		let current = http_match.get_current_with(|url, _headers| {
+			responses
+				.get(url)
+				.map(|page| (*page).to_string())
+				.ok_or_else(|| SourceError::Network(format!("unexpected request: {}", url)))
+		})?;
+		assert_eq!(current.content, "Part one\nPart two\nPart three");
+		assert_eq!(current.metadata.get("pages").map(String::as_str), Some("3"));
+
+		Ok(())
 	}
 
-	fn get_referenced(&self) -> Result<ReferencedHttp, SourceError> {
-		// This method provides a fallback when no cache is available
-		// In practice, the cache system should be used via Cache::get_source_with_cache()
-		// which will provide the actual referenced content from the cache
-		//
-		// For sources without cache, we return the current content as the reference
-		// This makes the source "self-referencing" on first use
-		let current = self.get_current()?;
+	#[test]
+	fn test_normalized_content_is_compared() -> Result<(), anyhow::Error> {
+		let extracted = HttpMatch::with_match_expression(
+			"https://example.com/terms",
+			MatchExpression::css_selector("main"),
+		)?;
+		let http_match = extracted.clone().with_normalizers(vec![
+			Normalizer::Entities,
+			Normalizer::Redact(r"\d{4}-\d{2}-\d{2}".to_string()),
+			Normalizer::Case,
+			Normalizer::Whitespace,
+		]);
+		assert_ne!(http_match.id(), extracted.id());
+
+		let current = |page: &'static str| {
+			http_match
+				.get_current_with(|_url, _headers| Ok(page.to_string()))
+				.map(|c| c.content)
+		};
+		let referenced = current("<main>Terms &amp; Conditions,\n  updated 2024-05-01</main>")?;
+		assert_eq!(referenced, "terms & conditions, updated [redacted]");
+		assert_eq!(current("<main>TERMS &amp; CONDITIONS, updated 2025-01-15</main>")?, referenced);
+		Ok(())
+	}
 
-		Ok(ReferencedHttp {
-			content: current.content,
-			metadata: current.metadata,
-			source_url: self.source_url.clone(),
-			match_expression: self.matches.clone(),
-		})
+	#[test]
+	fn test_normalize_mode_ignores_reflow() -> Result<(), anyhow::Error> {
+		let extracted = HttpMatch::with_match_expression(
+			"https://example.com/guide",
+			MatchExpression::css_selector("main"),
+		)?;
+		let collapsed = extracted.clone().with_normalize_mode(NormalizeMode::CollapseWhitespace);
+		let trimmed = extracted.clone().with_normalize_mode(NormalizeMode::TrimLines);
+		assert_eq!(extracted.clone().with_normalize_mode(NormalizeMode::None).id(), extracted.id());
+		assert_ne!(collapsed.id(), extracted.id());
+		assert_ne!(trimmed.id(), extracted.id());
+		assert_ne!(trimmed.id(), collapsed.id());
+
+		let current = |http_match: &HttpMatch, page: &'static str| {
+			http_match
+				.get_current_with(|_url, _headers| Ok(page.to_string()))
+				.map(|c| c.content)
+		};
+		let page = "<main>\n  Install the crate.\n\n  Then   run it.\n</main>";
+		let reflowed = "<main>Install the crate.\n    Then   run it.</main>";
+		assert_ne!(current(&extracted, page)?, current(&extracted, reflowed)?);
+
+		assert_eq!(current(&collapsed, page)?, "Install the crate. Then run it.");
+		assert_eq!(current(&collapsed, reflowed)?, current(&collapsed, page)?);
+		assert_eq!(current(&trimmed, page)?, "Install the crate.\nThen   run it.");
+		assert_eq!(current(&trimmed, reflowed)?, current(&trimmed, page)?);
+		Ok(())
 	}
 
-	fn get_current(&self) -> Result<CurrentHttp, SourceError> {
-		let raw_content = self.fetch_http()?;
-		let extracted_content = self.extract_content(&raw_content)?;
+	#[test]
+	fn test_decoded_content_is_compared() -> Result<(), anyhow::Error> {
+		use base64::Engine;
+
+		let http_match = HttpMatch::with_match_expression(
+			"https://api.github.com/repos/owner/repo/contents/Cargo.toml",
+			MatchExpression::json_path("$.content"),
+		)?
+		.with_decoding(Decoding::Base64);
+
+		// The contents API wraps the base64 body every 60 characters
+		let response = |content: &str| {
+			let encoded = base64::engine::general_purpose::STANDARD.encode(content);
+			let wrapped = encoded
+				.as_bytes()
+				.chunks(60)
+				.map(|line| String::from_utf8_lossy(line).into_owned());
+			serde_json::json!({
+				"type": "file",
+				"encoding": "base64",
+				"content": wrapped.collect::<Vec<_>>().join("\n"),
+			})
+			.to_string()
+		};
+		let manifest = "[package]\nname = \"cited\"\nversion = \"1.0.0\"\nedition = \"2021\"\n";
+		let current = http_match.get_current_with(|_url, _headers| Ok(response(manifest)))?;
+		assert_eq!(current.content, manifest);
 
-		let mut metadata = HashMap::new();
-		metadata.insert("fetched_at".to_string(), chrono::Utc::now().to_rfc3339());
-		metadata.insert("content_length".to_string(), raw_content.len().to_string());
+		let bumped = manifest.replace("1.0.0", "2.0.0");
+		let drifted = http_match.get_current_with(|_url, _headers| Ok(response(&bumped)))?;
+		assert_ne!(drifted.content, current.content);
+		assert!(drifted.content.contains("version = \"2.0.0\""));
 
-		Ok(CurrentHttp {
-			content: extracted_content,
-			metadata,
-			source_url: self.source_url.clone(),
-			match_expression: self.matches.clone(),
-			raw_content: Some(raw_content),
-		})
+		let result = http_match
+			.get_current_with(|_url, _headers| Ok(r#"{"content": "not base64!"}"#.to_string()));
+		assert!(matches!(result, Err(SourceError::ContentParsing(_))));
+
+		Ok(())
 	}
-}
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-	use anyhow::Result;
+	#[test]
+	fn test_cookie_is_sent_but_never_stored() -> Result<(), anyhow::Error> {
+		let cookie_env = "CITE_HTTP_TEST_SESSION_COOKIE";
+		std::env::set_var(cookie_env, "session=s3cr3t");
+
+		let public = HttpMatch::with_match_expression(
+			"https://docs.example.com/internal",
+			MatchExpression::css_selector("main"),
+		)?;
+		let gated = public.clone().with_cookie_env(cookie_env);
+		assert_eq!(gated.cookie_env(), Some(cookie_env));
+
+		// The cookie is not part of the cache key
+		assert_eq!(gated.id(), public.id());
+
+		// The gated page is only served with the session cookie
+		let fetch = |_url: &str, headers: &[(String, String)]| {
+			let cookie = headers.iter().find(|(name, _)| name == "Cookie");
+			match cookie.map(|(_, cookie)| cookie.as_str()) {
+				Some("session=s3cr3t") => Ok("<main>Internal docs</main>".to_string()),
+				_ => Err(SourceError::Network("HTTP request failed with status 401".to_string())),
+			}
+		};
+		assert!(public.get_current_with(fetch).is_err());
+		let current = gated.get_current_with(fetch)?;
+		assert_eq!(current.content, "Internal docs");
+
+		// Neither the cached entry nor the serialized source hold the cookie
+		let cached = String::from_utf8(current.to_cached_buffer()?)?;
+		assert!(!cached.contains("s3cr3t"));
+		assert!(!serde_json::to_string(&gated)?.contains("s3cr3t"));
+
+		// A missing cookie names the variable
+		let missing = public.with_cookie_env("CITE_HTTP_TEST_MISSING_COOKIE");
+		let error = missing.get_current_with(fetch).unwrap_err();
+		assert!(error.to_string().contains("CITE_HTTP_TEST_MISSING_COOKIE"));
+		Ok(())
+	}
 
 	#[test]
-	fn test_match_expression_regex() -> Result<(), anyhow::Error> {
-		let expr = MatchExpression::regex(".*");
-		let content = "Hello, world!";
-		let extracted = expr.extract_from(content)?;
-		assert_eq!(extracted, content);
+	fn test_accept_language_selects_variant() -> Result<(), anyhow::Error> {
+		let url = "https://docs.example.com/guide";
+		let default = HttpMatch::with_match_expression(url, MatchExpression::css_selector("h1"))?;
+		let english = default.clone().with_accept_language("en-US");
+		let german = default.clone().with_accept_language("de-DE");
+		assert_eq!(english.accept_language(), Some("en-US"));
+
+		// The page is served in the requested language
+		let fetch = |_url: &str, headers: &[(String, String)]| {
+			let language = headers.iter().find(|(name, _)| name == "Accept-Language");
+			Ok(match language.map(|(_, language)| language.as_str()) {
+				Some("de-DE") => "<h1>Anleitung</h1>".to_string(),
+				_ => "<h1>Guide</h1>".to_string(),
+			})
+		};
+		assert_eq!(english.get_current_with(fetch)?.content, "Guide");
+		assert_eq!(german.get_current_with(fetch)?.content, "Anleitung");
+
+		// Each language variant is cached separately
+		assert_ne!(english.id(), german.id());
+		assert_ne!(english.id(), default.id());
+		assert!(english.id().as_str().ends_with("_lang_en-US"));
 		Ok(())
 	}
 
 	#[test]
-	fn test_source_url_validation() -> Result<(), anyhow::Error> {
-		let url = SourceUrl::new("https://example.com/page")?;
-		assert_eq!(url.as_str(), "https://example.com/page");
-		assert_eq!(url.normalized(), "https://example.com/page");
+	fn test_ref_url_supplies_reference() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let tracking = "https://api.github.com/repos/owner/repo/contents/API.md?ref=main";
+		let pinned = "https://api.github.com/repos/owner/repo/contents/API.md?ref=v1.0";
+		let http_match =
+			HttpMatch::with_match_expression(tracking, MatchExpression::json_path("$.content"))?
+				.with_cache(cache)
+				.with_ref_url(pinned)?;
+		let ref_url = http_match.ref_url().cloned().ok_or(anyhow::anyhow!("No reference URL"))?;
+		assert_eq!(ref_url.as_str(), pinned);
+
+		let fetches = std::cell::RefCell::new(Vec::new());
+		let serve = |main: &'static str| {
+			let fetches = &fetches;
+			move |source: &HttpMatch| {
+				source.get_current_with(|url, _headers| {
+					fetches.borrow_mut().push(url.to_string());
+					let content = if url == pinned { "v1 API" } else { main };
+					Ok(format!(r#"{{"content": "{}"}}"#, content))
+				})
+			}
+		};
 
-		let url_with_slash = SourceUrl::new("https://example.com/")?;
-		assert_eq!(url_with_slash.normalized(), "https://example.com");
+		// The pinned URL supplies the reference and the cited URL the current content
+		let comparison = http_match.compare_ref_url_with(&ref_url, serve("v2 API"))?;
+		assert!(!comparison.is_same());
+		assert_eq!(comparison.referenced().content, "v1 API");
+		assert_eq!(comparison.current().content, "v2 API");
+		assert!(!comparison.diff().url_changed);
+		assert_eq!(*fetches.borrow(), vec![pinned.to_string(), tracking.to_string()]);
+
+		// The reference is fetched once and cached, so only the cited URL is fetched again
+		fetches.borrow_mut().clear();
+		let comparison = http_match.compare_ref_url_with(&ref_url, serve("v1 API"))?;
+		assert!(comparison.is_same());
+		assert_eq!(*fetches.borrow(), vec![tracking.to_string()]);
+
+		// Citations pinned to different URLs are cached separately
+		let plain =
+			HttpMatch::with_match_expression(tracking, MatchExpression::json_path("$.content"))?;
+		assert_ne!(plain.id(), http_match.id());
+		assert!(matches!(http_match.refresh(), Err(SourceError::ManualUpdate(_))));
+		Ok(())
+	}
+
+	#[test]
+	fn test_ref_url_reference_expires_with_ttl() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let tracking = "https://api.github.com/repos/owner/repo/contents/API.md?ref=main";
+		let pinned = "https://api.github.com/repos/owner/repo/contents/API.md?ref=v1.0";
+		let ttl = std::time::Duration::from_secs(3600);
+		let mut http_match =
+			HttpMatch::with_match_expression(tracking, MatchExpression::json_path("$.content"))?
+				.with_cache(cache.clone())
+				.with_ref_url(pinned)?;
+		http_match.cache_behavior = cite_cache::CacheBehavior::Ttl(ttl);
+		let ref_url = http_match.ref_url().cloned().ok_or(anyhow::anyhow!("No reference URL"))?;
+		let meta_path = cache.cache_dir().join(format!(
+			"{}.{}",
+			http_match.id().as_str(),
+			cite_cache::ENTRY_META_EXTENSION
+		));
+
+		let fetches = std::cell::RefCell::new(Vec::new());
+		let serve = |pinned_content: &'static str| {
+			let fetches = &fetches;
+			move |source: &HttpMatch| {
+				source.get_current_with(|url, _headers| {
+					fetches.borrow_mut().push(url.to_string());
+					let content = if url == pinned { pinned_content } else { "v2 API" };
+					Ok(format!(r#"{{"content": "{}"}}"#, content))
+				})
+			}
+		};
 
+		// The reference is cached with its write time, and used while fresh
+		let comparison = http_match.compare_ref_url_with(&ref_url, serve("v1 API"))?;
+		assert_eq!(comparison.referenced().content, "v1 API");
+		assert!(meta_path.exists());
+		fetches.borrow_mut().clear();
+		let comparison = http_match.compare_ref_url_with(&ref_url, serve("v1.1 API"))?;
+		assert_eq!(comparison.referenced().content, "v1 API");
+		assert_eq!(*fetches.borrow(), vec![tracking.to_string()]);
+
+		// Once expired, the pinned URL is fetched again
+		cite_cache::expiry::write_written_at(&meta_path, std::time::SystemTime::UNIX_EPOCH)?;
+		fetches.borrow_mut().clear();
+		let comparison = http_match.compare_ref_url_with(&ref_url, serve("v1.1 API"))?;
+		assert_eq!(comparison.referenced().content, "v1.1 API");
+		assert_eq!(*fetches.borrow(), vec![pinned.to_string(), tracking.to_string()]);
+		assert!(!cache.is_expired(http_match.id(), ttl)?);
 		Ok(())
 	}
 
 	#[test]
-	fn test_source_url_invalid() {
-		let result = SourceUrl::new("ftp://example.com");
-		assert!(result.is_err());
-	}
+	fn test_response_header() -> Result<(), anyhow::Error> {
+		let url = "https://api.example.com/v2/releases";
+		let http_match = HttpMatch::with_match_expression(url, MatchExpression::full_document())?
+			.with_response_header("X-Api-Version")?;
+		assert_eq!(http_match.response_header(), Some("x-api-version"));
+		assert_eq!(http_match.target_description(), format!("URL: {}\nHeader: x-api-version", url));
+
+		// The value of the named header is the content, fetched from the cited URL
+		let serve = |version: Option<&'static str>| {
+			move |fetched: &str, _headers: &[(String, String)], name: &str| {
+				assert_eq!((fetched, name), (url, "x-api-version"));
+				Ok(version.map(str::to_string))
+			}
+		};
+		let current =
+			http_match.get_response_header_with("x-api-version", serve(Some("2024-06-01")))?;
+		assert_eq!(current.content, "2024-06-01");
+		let referenced = ReferencedHttp {
+			content: current.content.clone(),
+			metadata: current.metadata.clone(),
+			source_url: current.source_url.clone(),
+			match_expression: current.match_expression.clone(),
+		};
 
-	#[test]
-	fn test_http_match_creation() -> Result<(), anyhow::Error> {
-		let http_match = HttpMatch::cached("https://example.com", ".*")?;
-		assert_eq!(http_match.source_url.as_str(), "https://example.com");
+		// A constant value is no drift, whatever the body
+		let constant =
+			http_match.get_response_header_with("x-api-version", serve(Some("2024-06-01")))?;
+		assert!(constant.diff(&referenced)?.is_empty());
+
+		// A changed or removed header is drift
+		let changed =
+			http_match.get_response_header_with("x-api-version", serve(Some("2025-01-15")))?;
+		assert!(!changed.diff(&referenced)?.is_empty());
+		let removed = http_match.get_response_header_with("x-api-version", serve(None))?;
+		assert_eq!(removed.content, "");
+		assert!(!removed.diff(&referenced)?.is_empty());
+
+		// Each header is cached separately from the body and other headers, whatever its case
+		let body = HttpMatch::with_match_expression(url, MatchExpression::full_document())?;
+		let sunset = HttpMatch::with_match_expression(url, MatchExpression::full_document())?
+			.with_response_header("Sunset")?;
+		let lowercase = HttpMatch::with_match_expression(url, MatchExpression::full_document())?
+			.with_response_header("x-api-version")?;
+		assert_ne!(http_match.id(), body.id());
+		assert_ne!(http_match.id(), sunset.id());
+		assert_eq!(http_match.id(), lowercase.id());
+
+		assert!(body.with_response_header("X Api Version").is_err());
 		Ok(())
 	}
 
 	#[test]
-	fn test_http_source_fetch() -> Result<(), anyhow::Error> {
-		// Use FullDocument to get the entire page content
-		let http_match = HttpMatch::with_match_expression_and_cache_behavior(
-			"https://example.com",
-			MatchExpression::full_document(),
-			cite_cache::CacheBehavior::Enabled,
+	fn test_custom_headers() -> Result<(), anyhow::Error> {
+		let url = "https://api.example.com/releases";
+		let headers = |pairs: &[(&str, &str)]| {
+			pairs
+				.iter()
+				.map(|(name, value)| (name.to_string(), value.to_string()))
+				.collect()
+		};
+		let json = HttpMatch::with_headers(
+			url,
+			MatchExpression::json_path("$.version"),
+			headers(&[("Accept", "application/json"), ("X-Api-Key", "secret")]),
 		)?;
-		let current = http_match.get_current()?;
+		let other_key = HttpMatch::with_headers(
+			url,
+			MatchExpression::json_path("$.version"),
+			headers(&[("Accept", "application/json"), ("X-Api-Key", "rotated")]),
+		)?;
+		let plain = HttpMatch::with_match_expression(url, MatchExpression::json_path("$.version"))?;
+
+		// The headers are sent with the request, replacing defaults of the same name
+		let fetch = |_url: &str, headers: &[(String, String)]| {
+			let accept = headers.iter().find(|(name, _)| name.eq_ignore_ascii_case("Accept"));
+			Ok(match accept.map(|(_, accept)| accept.as_str()) {
+				Some("application/json") => r#"{"version": "1.2.0"}"#.to_string(),
+				_ => "<html>1.2.0</html>".to_string(),
+			})
+		};
+		assert_eq!(json.get_current_with(fetch)?.content, "1.2.0");
+		let replaced = plain.clone().with_request_headers(headers(&[("user-agent", "docs-bot")]));
+		let sent = replaced.request_headers()?;
+		assert_eq!(
+			sent.iter().filter(|(name, _)| name.eq_ignore_ascii_case("User-Agent")).count(),
+			1
+		);
+		assert!(sent.contains(&("user-agent".to_string(), "docs-bot".to_string())));
 
-		// Real example.com should contain "Example Domain"
-		assert!(current.content.contains("Example Domain"));
-		assert!(current.metadata.contains_key("fetched_at"));
-		assert_eq!(current.source_url.as_str(), "https://example.com");
+		// Citations with different headers are cached separately, without exposing the values
+		assert_ne!(json.id(), plain.id());
+		assert_ne!(json.id(), other_key.id());
+		assert!(!json.id().as_str().contains("secret"));
+		assert_eq!(plain.clone().with_request_headers(HashMap::new()).id(), plain.id());
 		Ok(())
 	}
 
 	#[test]
-	fn test_http_diff() -> Result<(), anyhow::Error> {
-		let referenced = ReferencedHttp {
-			content: "old content".to_string(),
-			metadata: HashMap::new(),
-			source_url: SourceUrl::new("https://example.com")?,
-			match_expression: MatchExpression::regex(".*"),
+	fn test_user_agent_and_robots() -> Result<(), anyhow::Error> {
+		let url = "https://polite.example.com/docs/guide";
+		let http_match =
+			HttpMatch::with_match_expression(url, MatchExpression::css_selector("h1"))?;
+
+		// Requests identify the crate, unless overridden
+		let mut user_agents = Vec::new();
+		let mut fetch = |_url: &str, headers: &[(String, String)]| {
+			let user_agent = headers.iter().find(|(name, _)| name == "User-Agent");
+			user_agents.push(user_agent.map(|(_, user_agent)| user_agent.clone()));
+			Ok("<h1>Guide</h1>".to_string())
 		};
+		let custom = http_match.clone().with_user_agent("docs-bot/2.0");
+		http_match.get_current_with(&mut fetch)?;
+		custom.get_current_with(&mut fetch)?;
+		assert_eq!(
+			user_agents,
+			[Some(politeness::user_agent(None)), Some("docs-bot/2.0".to_string())]
+		);
 
-		let current = CurrentHttp {
-			content: "new content".to_string(),
-			metadata: HashMap::new(),
-			source_url: SourceUrl::new("https://example.com")?,
-			match_expression: MatchExpression::regex(".*"),
-			raw_content: None,
+		// The robots.txt of the host is fetched once, and disallowed paths are not fetched
+		let polite = http_match.with_respect_robots(true);
+		let private = HttpMatch::with_match_expression(
+			"https://polite.example.com/private/notes",
+			MatchExpression::css_selector("h1"),
+		)?
+		.with_respect_robots(true);
+		let mut fetched = Vec::new();
+		let mut fetch = |url: &str, _headers: &[(String, String)]| {
+			fetched.push(url.to_string());
+			Ok(if url.ends_with("/robots.txt") {
+				"User-agent: *\nDisallow: /private\n".to_string()
+			} else {
+				"<h1>Guide</h1>".to_string()
+			})
 		};
+		assert_eq!(polite.get_current_with(&mut fetch)?.content, "Guide");
+		assert_eq!(polite.get_current_with(&mut fetch)?.content, "Guide");
+		assert!(private.get_current_with(&mut fetch).is_err());
+		assert_eq!(
+			fetched,
+			[
+				"https://polite.example.com/robots.txt",
+				"https://polite.example.com/docs/guide",
+				"https://polite.example.com/docs/guide",
+			]
+		);
+		Ok(())
+	}
 
-		let diff = current.diff(&referenced)?;
-		assert!(diff.content_changed);
-		assert!(!diff.url_changed);
-		assert!(!diff.match_expression_changed);
-		assert!(!diff.is_empty());
-
+	#[test]
+	fn test_expect_status() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/old-page",
+			MatchExpression::full_document(),
+		)?
+		.with_expect_status(200);
+		assert_eq!(http_match.expect_status(), Some(200));
+
+		// The expected status passes
+		let comparison = http_match.compare_status_with(200, |_, _| Ok(200))?;
+		assert!(comparison.is_same());
+
+		// A page that went missing fails, reporting both codes
+		let comparison = http_match.compare_status_with(200, |_, _| Ok(404))?;
+		assert!(!comparison.is_same());
+		assert_eq!(comparison.referenced().content, "200");
+		assert_eq!(comparison.current().content, "404");
+
+		// A redirect can be expected as well
+		let moved = http_match.with_expect_status(301);
+		assert!(moved.compare_status_with(301, |_, _| Ok(301))?.is_same());
+		assert!(!moved.compare_status_with(301, |_, _| Ok(200))?.is_same());
+
+		// Network failures are errors, not drift
+		let unreachable = moved.compare_status_with(301, |_, _| {
+			Err(SourceError::Network("HTTP request failed: connection refused".to_string()))
+		});
+		assert!(matches!(unreachable, Err(SourceError::Network(_))));
 		Ok(())
 	}
 
@@ -686,6 +4298,102 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_between_match_expression() -> Result<(), anyhow::Error> {
+		let expr = MatchExpression::between("<!-- BEGIN -->", "<!-- END -->");
+
+		// Present markers
+		let content = "intro<!-- BEGIN -->cited\nregion<!-- END -->outro";
+		assert_eq!(expr.extract_from(content)?, "cited\nregion");
+
+		// Missing markers
+		assert_eq!(expr.extract_from("no markers here")?, "");
+		assert_eq!(expr.extract_from("<!-- BEGIN -->unterminated")?, "");
+		assert_eq!(expr.extract_from("<!-- END --> before <!-- BEGIN -->")?, "");
+
+		// Nested markers: the first start and the next end after it
+		let content = "<!-- BEGIN -->a<!-- BEGIN -->b<!-- END -->c<!-- END -->";
+		assert_eq!(expr.extract_from(content)?, "a<!-- BEGIN -->b");
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_cache_key_includes_between_markers() -> Result<(), anyhow::Error> {
+		let first = HttpMatch::with_match_expression(
+			"https://example.com",
+			MatchExpression::between("BEGIN", "END"),
+		)?;
+		let second = HttpMatch::with_match_expression(
+			"https://example.com",
+			MatchExpression::between("START", "END"),
+		)?;
+		assert_ne!(first.cache_path, second.cache_path);
+		assert!(first.cache_path.contains("between_BEGIN_END"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_json_path_match_expression() -> Result<(), anyhow::Error> {
+		let expr = MatchExpression::json_path("$[?(@.name=='stable')].version");
+		let json_content = r#"[{"name":"beta","version":"2.0"},{"name":"stable","version":"1.9"}]"#;
+
+		assert_eq!(expr.extract_from(json_content)?, "1.9");
+
+		let http_match = HttpMatch::with_match_expression("https://example.com", expr)?;
+		assert!(http_match.cache_path.contains("jsonpath___"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_meta_match_expression() -> Result<(), anyhow::Error> {
+		let html_content = r#"<html><head>
+			<meta charset="utf-8">
+			<meta name="description" content="Configuration reference">
+			<meta property="og:title" content=" Cite Docs ">
+			<meta name="version" content="2.3.0">
+		</head><body><h1>Docs</h1></body></html>"#;
+
+		assert_eq!(MatchExpression::meta("og:title").extract_from(html_content)?, "Cite Docs");
+		assert_eq!(
+			MatchExpression::meta("description").extract_from(html_content)?,
+			"Configuration reference"
+		);
+		assert_eq!(MatchExpression::meta("version").extract_from(html_content)?, "2.3.0");
+
+		// Missing meta tags are a no-match
+		assert_eq!(MatchExpression::meta("og:image").extract_from(html_content)?, "");
+
+		let first = HttpMatch::with_match_expression(
+			"https://example.com",
+			MatchExpression::meta("og:title"),
+		)?;
+		let second = HttpMatch::with_match_expression(
+			"https://example.com",
+			MatchExpression::meta("version"),
+		)?;
+		assert_ne!(first.cache_path, second.cache_path);
+		assert!(first.cache_path.contains("meta_og_title"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_table_cell_match_expression() -> Result<(), anyhow::Error> {
+		let expr = MatchExpression::table_cell(
+			"table.defaults",
+			TableKey::from_str("max_connections"),
+			TableKey::from_str("Default"),
+		);
+		let html_content = "<table class=\"defaults\"><tr><th>Name</th><th>Default</th></tr>\
+			<tr><td>max_connections</td><td>100</td></tr></table>";
+
+		assert_eq!(expr.extract_from(html_content)?, "100");
+
+		let http_match = HttpMatch::with_match_expression("https://example.com", expr)?;
+		assert!(http_match.cache_path.contains("table_table_defaults_max_connections_Default"));
+		Ok(())
+	}
+
 	#[test]
 	fn test_css_selector_match_expression() -> Result<(), anyhow::Error> {
 		let expr = MatchExpression::css_selector("h1");
@@ -898,6 +4606,437 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_json_fields_ignore_volatile_timestamp() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.com/api/status",
+			MatchExpression::FullDocument,
+		)?
+		.with_json_fields(
+			JsonFields::new(Vec::new(), vec!["/timestamp".to_string()])
+				.map_err(anyhow::Error::msg)?,
+		);
+
+		let referenced = ReferencedHttp {
+			content: r#"{"timestamp": 1700000000, "status": {"api": "v2", "region": "eu"}}"#
+				.to_string(),
+			metadata: HashMap::new(),
+			source_url: http_match.source_url.clone(),
+			match_expression: http_match.matches.clone(),
+		};
+		let current = |content: &str| CurrentHttp {
+			content: content.to_string(),
+			metadata: HashMap::new(),
+			source_url: http_match.source_url.clone(),
+			match_expression: http_match.matches.clone(),
+			raw_content: None,
+		};
+
+		// Only the timestamp changed
+		let diff = http_match.diff_json_fields(
+			&referenced,
+			&current(r#"{"timestamp": 1700086400, "status": {"api": "v2", "region": "eu"}}"#),
+		)?;
+		assert!(diff.is_empty());
+		assert!(diff.changed_fields.is_empty());
+
+		// An included field changed as well
+		let diff = http_match.diff_json_fields(
+			&referenced,
+			&current(r#"{"timestamp": 1700086400, "status": {"api": "v3", "region": "eu"}}"#),
+		)?;
+		assert!(!diff.is_empty());
+		assert_eq!(diff.changed_fields, ["/status/api"]);
+		assert_eq!(
+			diff.unified_diff(),
+			Some("-/status/api: \"v2\"\n+/status/api: \"v3\"\n /status/region: \"eu\"\n")
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_cached_referenced_does_not_fetch() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.invalid",
+			MatchExpression::FullDocument,
+		)?
+		.with_cache(cache.clone());
+
+		// Nothing is cached before the first validation
+		assert_eq!(http_match.cached_referenced(), None);
+
+		let current = CurrentHttp {
+			content: "cited content".to_string(),
+			metadata: HashMap::new(),
+			source_url: http_match.source_url.clone(),
+			match_expression: http_match.matches.clone(),
+			raw_content: None,
+		};
+		cache.set(http_match.id(), &current)?;
+		assert_eq!(
+			http_match.cached_referenced().map(|referenced| referenced.content),
+			Some("cited content".to_string())
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_unchanged_raw_content_is_not_extracted() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.invalid/guide",
+			MatchExpression::css_selector("main"),
+		)?
+		.with_cache(cache.clone());
+		let raw = |page: &str| RawHttp {
+			pages: vec![page.to_string()],
+			content_type: None,
+			final_url: None,
+			validators: CacheValidators::default(),
+			not_modified: false,
+		};
+
+		// The first validation caches the reference with the digest of its raw content
+		assert_eq!(http_match.cached_with_raw_digest()?, None);
+		let page = "<html><main>Guide</main><footer>v1</footer></html>";
+		cache.set(http_match.id(), &http_match.extract_raw(raw(page))?)?;
+		let referenced = http_match.cached_with_raw_digest()?.expect("digest recorded");
+
+		// Identical raw content is neither extracted nor diffed
+		let extractions = std::cell::Cell::new(0);
+		let extract = |raw: RawHttp| {
+			extractions.set(extractions.get() + 1);
+			http_match.extract_raw(raw)
+		};
+		let comparison = http_match.compare_raw_with(referenced.clone(), raw(page), extract)?;
+		assert!(comparison.is_same());
+		assert_eq!(comparison.current().content, "Guide");
+		assert_eq!(extractions.get(), 0);
+
+		// Any other raw content is, even if it extracts to the same content
+		let footer = "<html><main>Guide</main><footer>v2</footer></html>";
+		assert!(http_match.compare_raw_with(referenced.clone(), raw(footer), extract)?.is_same());
+		assert_eq!(extractions.get(), 1);
+		let changed = "<html><main>Guide, revised</main></html>";
+		assert!(!http_match.compare_raw_with(referenced, raw(changed), extract)?.is_same());
+		assert_eq!(extractions.get(), 2);
+
+		// References cached without a digest go through the cache as usual
+		let mut legacy = http_match.extract_raw(raw(page))?;
+		legacy.metadata.remove("raw_digest");
+		cache.set(http_match.id(), &legacy)?;
+		assert_eq!(http_match.cached_with_raw_digest()?, None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_status_is_checked_before_extraction() -> Result<(), anyhow::Error> {
+		let url = "https://example.invalid/docs/removed";
+		let http_match =
+			HttpMatch::with_match_expression(url, MatchExpression::css_selector("main"))?;
+		let status = |code: u16| reqwest::StatusCode::from_u16(code);
+
+		// A server error fails the citation instead of its error page becoming content
+		let error = http_match.check_status(status(500)?, None, url);
+		assert!(
+			matches!(error, Err(SourceError::Network(message)) if message.contains("status 500"))
+		);
+		assert!(http_match.check_status(status(404)?, None, url).is_err());
+		assert!(http_match.check_status(status(200)?, None, url).is_ok());
+		assert!(http_match.check_status(status(204)?, None, url).is_ok());
+		let redirect = http_match.check_status(status(301)?, Some("/docs/moved"), url);
+		assert!(
+			matches!(redirect, Err(SourceError::Network(message)) if message.contains("/docs/moved"))
+		);
+
+		// Accepted statuses replace the success statuses
+		let gone = http_match.with_accept_status([200, 301, 404]);
+		assert_eq!(gone.accept_status(), &BTreeSet::from([200, 301, 404]));
+		for accepted in [200, 301, 404] {
+			assert!(gone.check_status(status(accepted)?, None, url).is_ok());
+		}
+		for rejected in [204, 302, 500] {
+			assert!(gone.check_status(status(rejected)?, None, url).is_err());
+		}
+		let error = gone.check_status(status(500)?, None, url);
+		assert!(
+			matches!(error, Err(SourceError::Network(message)) if message.contains("accepted statuses 200, 301, 404"))
+		);
+
+		// Other requests of the citation, e.g. for robots.txt or a following page, must succeed
+		for other in ["https://example.invalid/robots.txt", "https://example.invalid/docs?page=2"] {
+			assert!(gone.check_status(status(404)?, None, other).is_err());
+			assert!(gone.check_status(status(204)?, None, other).is_ok());
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_not_modified_reuses_reference() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.invalid/guide",
+			MatchExpression::css_selector("main"),
+		)?
+		.with_cache(cache.clone());
+		let validators = CacheValidators {
+			etag: Some("\"v1\"".to_string()),
+			last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+		};
+		let raw = |page: &str, not_modified: bool| RawHttp {
+			pages: vec![page.to_string()],
+			content_type: None,
+			final_url: None,
+			validators: validators.clone(),
+			not_modified,
+		};
+
+		// The validators the page was served with are persisted with the reference
+		let page = "<html><main>Guide</main></html>";
+		cache.set(http_match.id(), &http_match.extract_raw(raw(page, false))?)?;
+		let referenced = http_match.cached_with_raw_digest()?.expect("reference cached");
+		assert_eq!(referenced.validators(), validators);
+
+		// A page reported as not modified reuses the reference instead of its empty body
+		let extract = |raw: RawHttp| -> Result<CurrentHttp, SourceError> {
+			panic!("a page that was not modified is not extracted: {:?}", raw.pages)
+		};
+		let comparison = http_match.compare_raw_with(referenced.clone(), raw("", true), extract)?;
+		assert!(comparison.is_same());
+		assert_eq!(comparison.current().content, "Guide");
+		assert_eq!(CacheValidators::from_metadata(&comparison.current().metadata), validators);
+
+		// Without the 304, an empty body is drift as usual
+		let extract = |raw: RawHttp| http_match.extract_raw(raw);
+		assert!(!http_match.compare_raw_with(referenced, raw("", false), extract)?.is_same());
+		Ok(())
+	}
+
+	#[test]
+	fn test_redirect_target_change() -> Result<(), anyhow::Error> {
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.invalid/docs/latest",
+			MatchExpression::css_selector("main"),
+		)?
+		.with_redirects(RedirectPolicy::Follow(DEFAULT_MAX_REDIRECTS));
+		let raw = |final_url: &str| RawHttp {
+			pages: vec!["<main>Guide</main>".to_string()],
+			content_type: None,
+			final_url: Some(final_url.to_string()),
+			validators: CacheValidators::default(),
+			not_modified: false,
+		};
+
+		// The URL followed redirects ended at is recorded with the reference
+		let referenced = http_match.extract_raw(raw("https://example.invalid/docs/v2"))?;
+		assert_eq!(
+			referenced.metadata.get("final_url").map(String::as_str),
+			Some("https://example.invalid/docs/v2")
+		);
+		let referenced = ReferencedHttp {
+			content: referenced.content,
+			metadata: referenced.metadata,
+			source_url: referenced.source_url,
+			match_expression: referenced.match_expression,
+		};
+		let extract = |raw: RawHttp| http_match.extract_raw(raw);
+		let same = http_match.compare_raw_with(
+			referenced.clone(),
+			raw("https://example.invalid/docs/v2"),
+			extract,
+		)?;
+		assert!(same.is_same());
+
+		// Redirects ending elsewhere are drift, even though the raw content is identical
+		let moved = http_match.compare_raw_with(
+			referenced,
+			raw("https://example.invalid/docs/v3"),
+			extract,
+		)?;
+		assert!(!moved.is_same());
+		assert_eq!(
+			moved.diff().redirect_change,
+			Some(RedirectChange {
+				referenced: "https://example.invalid/docs/v2".to_string(),
+				current: "https://example.invalid/docs/v3".to_string(),
+			})
+		);
+		assert_eq!(moved.diff().describe_change(), Some(ChangeClass::Structure));
+
+		let pinned = http_match.clone().with_redirects(RedirectPolicy::None);
+		assert_eq!(pinned.redirects(), RedirectPolicy::None);
+
+		// Only redirects followed by an explicit policy record where they end
+		let url = reqwest::Url::parse("https://example.invalid/docs/v2")?;
+		assert_eq!(pinned.recorded_final_url(&url), None);
+		let default = HttpMatch::with_match_expression(
+			"https://example.invalid/docs/latest",
+			MatchExpression::css_selector("main"),
+		)?;
+		assert_eq!(default.redirects(), RedirectPolicy::Follow(DEFAULT_MAX_REDIRECTS));
+		assert_eq!(default.recorded_final_url(&url), None);
+		assert_eq!(
+			http_match.recorded_final_url(&url).as_deref(),
+			Some("https://example.invalid/docs/v2")
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_unreferenced_note() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let http_match = HttpMatch::with_match_expression(
+			"https://example.invalid/guide",
+			MatchExpression::css_selector("main"),
+		)?
+		.with_cache(cache.clone());
+
+		// Without a reference, the citation is only compared against itself
+		let note = http_match.unreferenced_note()?.expect("unreferenced");
+		assert!(note.contains("has no reference yet"));
+		assert!(note.contains("https://example.invalid/guide"));
+
+		// Once a reference exists, it is compared against that instead
+		let current = CurrentHttp {
+			content: "Guide".to_string(),
+			metadata: HashMap::new(),
+			source_url: SourceUrl::new("https://example.invalid/guide")?,
+			match_expression: MatchExpression::css_selector("main"),
+			raw_content: None,
+		};
+		cache.set(http_match.id(), &current)?;
+		assert_eq!(http_match.unreferenced_note()?, None);
+
+		// Citations that do not use the cache are never self-referencing
+		let mut ignored = http_match;
+		ignored.cache_behavior = cite_cache::CacheBehavior::Ignored;
+		assert_eq!(ignored.unreferenced_note()?, None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_signed_urls_share_reference() -> Result<(), anyhow::Error> {
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let signed = |signature: &str| -> Result<HttpMatch, anyhow::Error> {
+			let url = format!(
+				"https://bucket.s3.amazonaws.com/spec.txt?X-Amz-Date=20261015T093000Z&X-Amz-Signature={}",
+				signature
+			);
+			Ok(HttpMatch::with_match_expression(&url, MatchExpression::FullDocument)?
+				.with_signed(true)
+				.with_cache(cache.clone()))
+		};
+		let (first, second) = (signed("4f2a")?, signed("9c1e")?);
+		assert_eq!(first.id(), second.id());
+		assert_ne!(
+			HttpMatch::with_match_expression(
+				first.source_url.as_str(),
+				MatchExpression::FullDocument
+			)?
+			.id(),
+			first.id()
+		);
+
+		// The object is fetched with its signature, and cached without it
+		let body = |url: &str, _headers: &[(String, String)]| {
+			assert!(url.contains("X-Amz-Signature="));
+			Ok("object body".to_string())
+		};
+		cache.set(first.id(), &first.get_current_with(body)?)?;
+
+		// A differently signed URL of the same object compares the same body
+		let referenced = second.cached_referenced().expect("shared cache entry");
+		assert_eq!(referenced.source_url.as_str(), "https://bucket.s3.amazonaws.com/spec.txt");
+		assert!(second.get_current_with(body)?.diff(&referenced)?.is_empty());
+		let changed = second.get_current_with(|_url, _headers| Ok("new body".to_string()))?;
+		assert!(!changed.diff(&referenced)?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn test_reverify_uses_cached_snapshot_time() -> Result<(), anyhow::Error> {
+		use cite_core::{
+			CitationAnnotation, CitationBehavior, CitationGlobal, CitationLevel, ReverifyInterval,
+		};
+
+		let tmp = tempfile::TempDir::new()?;
+		let cache =
+			cite_cache::CacheBuilder::new(tmp.path().to_path_buf(), "cache".into()).build()?;
+		let http_match =
+			HttpMatch::with_match_expression("https://example.com", MatchExpression::FullDocument)?;
+		let behavior = CitationBehavior::new(
+			CitationLevel::Warn,
+			CitationAnnotation::Any,
+			CitationGlobal::Lenient,
+		);
+		let interval = ReverifyInterval::new(180);
+		let now = chrono::Utc::now();
+
+		let snapshot = |age_days: i64| -> Result<ReferencedHttp, anyhow::Error> {
+			let mut metadata = HashMap::new();
+			let fetched_at = now - chrono::Duration::days(age_days);
+			metadata.insert("fetched_at".to_string(), fetched_at.to_rfc3339());
+			let current = CurrentHttp {
+				content: "content".to_string(),
+				metadata,
+				source_url: http_match.source_url.clone(),
+				match_expression: http_match.matches.clone(),
+				raw_content: None,
+			};
+			cache.set(http_match.id(), &current)?;
+			cache
+				.get::<ReferencedHttp>(http_match.id())?
+				.ok_or_else(|| anyhow::anyhow!("cache entry was not written"))
+		};
+
+		// A snapshot older than the interval is due, at the configured level
+		let referenced = snapshot(200)?;
+		let result = interval.validate(referenced.fetched_at(), now, &behavior, None);
+		assert_eq!(result.level(), Some(CitationLevel::Warn));
+		assert!(!result.should_fail_compilation());
+
+		// A recent snapshot is not
+		let referenced = snapshot(10)?;
+		assert!(interval.validate(referenced.fetched_at(), now, &behavior, None).is_valid());
+
+		// A snapshot without a recorded time is always due, since its age is unknown
+		let mut referenced = referenced;
+		referenced.metadata.remove("fetched_at");
+		assert_eq!(referenced.fetched_at(), None);
+		assert!(!interval.validate(referenced.fetched_at(), now, &behavior, None).is_valid());
+		Ok(())
+	}
+
+	#[test]
+	fn test_refresh_requires_editing_citation_references() -> Result<(), anyhow::Error> {
+		let http_match =
+			HttpMatch::with_match_expression("https://example.com", MatchExpression::FullDocument)?;
+
+		for source in [
+			http_match.clone().with_digest(""),
+			http_match.clone().with_any_of(vec!["a".to_string()]),
+			http_match
+				.clone()
+				.with_expect_num(ExpectedNumber::from_str("100").map_err(anyhow::Error::msg)?),
+			http_match
+				.with_expect_len(ExpectedLength::from_str("1..").map_err(anyhow::Error::msg)?),
+		] {
+			assert!(matches!(source.refresh(), Err(SourceError::ManualUpdate(_))));
+		}
+		Ok(())
+	}
+
 	#[test]
 	fn test_cache_hits_with_static_content() -> Result<(), anyhow::Error> {
 		// Test cache behavior with static content (example.com)
@@ -1022,4 +5161,175 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[test]
+	fn test_regex_replace_match_expression() -> Result<(), anyhow::Error> {
+		let expression = MatchExpression::regex_replace(r"v(\d+)_(\d+)_(\d+)", "$1.$2.$3");
+		assert_eq!(expression.extract_from("Latest release: v1_12_3 (stable)")?, "1.12.3");
+		assert_eq!(expression.to_string(), "regex:v(\\d+)_(\\d+)_(\\d+) as \"$1.$2.$3\"");
+
+		// Groups may be named, reordered or repeated
+		let named = MatchExpression::regex_replace(
+			r"(?<month>\d{2})/(?<day>\d{2})/(?<year>\d{4})",
+			"${year}-${month}-${day}",
+		);
+		assert_eq!(named.extract_from("Released 03/14/2025")?, "2025-03-14");
+
+		// Content without a match is a no-match, and several matches are ambiguous
+		assert_eq!(expression.extract_from("No release yet")?, "");
+		assert!(expression.check_unambiguous("v1_2_3 then v1_2_4").is_err());
+		assert!(expression.check_unambiguous("v1_2_3").is_ok());
+
+		// The cache key covers the pattern and the template
+		let url = "https://example.com/releases";
+		let regex =
+			HttpMatch::with_match_expression(url, MatchExpression::regex(r"v(\d+)_(\d+)_(\d+)"))?;
+		let replaced = HttpMatch::with_match_expression(url, expression)?;
+		let reordered = HttpMatch::with_match_expression(
+			url,
+			MatchExpression::regex_replace(r"v(\d+)_(\d+)_(\d+)", "$3-$2-$1"),
+		)?;
+		assert_ne!(regex.id().as_str(), replaced.id().as_str());
+		assert_ne!(replaced.id().as_str(), reordered.id().as_str());
+		assert!(replaced.id().as_str().contains("regex_replace"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_composite_match_expression() -> Result<(), anyhow::Error> {
+		let html = r#"<html><body>
+			<span class="version">1.2.0</span>
+			<time>2025-01-01</time>
+		</body></html>"#;
+
+		let parts = vec![
+			MatchExpression::css_selector("span.version"),
+			MatchExpression::css_selector("time"),
+		];
+		let composite = MatchExpression::composite(parts.clone(), Some("{0} ({1})"));
+		assert_eq!(composite.extract_from(html)?, "1.2.0 (2025-01-01)");
+		assert_eq!(composite.to_string(), "combine:(css:span.version, css:time) as \"{0} ({1})\"");
+
+		// Without a template the results are joined by newlines
+		let joined = MatchExpression::composite(parts, None);
+		assert_eq!(joined.extract_from(html)?, "1.2.0\n2025-01-01");
+
+		// The cache key covers every part and the template
+		let url = "https://example.com/releases";
+		let single = HttpMatch::with_match_expression_and_cache_behavior(
+			url,
+			MatchExpression::css_selector("span.version"),
+			cite_cache::CacheBehavior::Enabled,
+		)?;
+		let combined = HttpMatch::with_match_expression_and_cache_behavior(
+			url,
+			composite,
+			cite_cache::CacheBehavior::Enabled,
+		)?;
+		assert_ne!(single.id().as_str(), combined.id().as_str());
+		assert!(combined.id().as_str().contains("span"));
+		assert!(combined.id().as_str().contains("time"));
+		let joined = HttpMatch::with_match_expression_and_cache_behavior(
+			url,
+			joined,
+			cite_cache::CacheBehavior::Enabled,
+		)?;
+		assert_ne!(combined.id().as_str(), joined.id().as_str());
+
+		// Placeholders must refer to a part; other braces are literal
+		assert!(MatchExpression::validate_template("{0} ({1})", 2).is_ok());
+		assert!(MatchExpression::validate_template("{version: {0}}", 1).is_ok());
+		let error = MatchExpression::validate_template("{0} ({2})", 2).unwrap_err();
+		assert!(error.contains("{2}"), "{}", error);
+		Ok(())
+	}
+
+	#[test]
+	fn test_first_of_match_expression() -> Result<(), anyhow::Error> {
+		// The release id was dropped from the markup, but the class is still there
+		let html = r#"<html><body><span class="version">1.2.0</span></body></html>"#;
+		let first_of = MatchExpression::first_of(vec![
+			MatchExpression::css_selector("#version"),
+			MatchExpression::css_selector(".version"),
+			MatchExpression::regex(r"\d+\.\d+\.\d+"),
+		]);
+		assert_eq!(first_of.extract_from(html)?, "1.2.0");
+		assert_eq!(first_of.extract_alternative(html)?, Some((1, "1.2.0".to_string())));
+
+		// The alternative that matched is recorded in the metadata
+		let http_match = HttpMatch::with_match_expression_and_cache_behavior(
+			"https://example.com/releases",
+			first_of.clone(),
+			cite_cache::CacheBehavior::Ignored,
+		)?;
+		let current = http_match.get_current_with(|_url, _headers| Ok(html.to_string()))?;
+		assert_eq!(current.content, "1.2.0");
+		assert_eq!(
+			current.metadata.get("matched_alternative").map(String::as_str),
+			Some("css:.version")
+		);
+
+		// When every alternative fails there is no match
+		let html = "<html><body><p>No release yet</p></body></html>";
+		assert_eq!(first_of.extract_alternative(html)?, None);
+		assert_eq!(first_of.extract_from(html)?, "");
+		let current = http_match.get_current_with(|_url, _headers| Ok(html.to_string()))?;
+		assert_eq!(current.content, "");
+		assert!(!current.metadata.contains_key("matched_alternative"));
+
+		// Alternatives that cannot apply are passed over, and their error is kept for when none
+		// matches
+		let with_json = MatchExpression::first_of(vec![
+			MatchExpression::json_path("$.version"),
+			MatchExpression::css_selector("p"),
+		]);
+		assert_eq!(with_json.extract_from(html)?, "No release yet");
+		let without_match = MatchExpression::first_of(vec![
+			MatchExpression::json_path("$.version"),
+			MatchExpression::css_selector("span"),
+		]);
+		assert!(without_match.extract_from(html).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_strict_match_rejects_ambiguous_expressions() -> Result<(), anyhow::Error> {
+		let html = r#"<html><body>
+			<p class="note">Requires Rust 1.70 or 1.75</p>
+			<p class="note">Deprecated in 2.0</p>
+			<p id="msrv">MSRV: 1.70</p>
+		</body></html>"#;
+
+		// Lenient extraction quietly picks a region
+		let groups = MatchExpression::regex(r"Rust (\d+)\.(\d+)");
+		assert_eq!(groups.extract_from(html)?, "1");
+		let elements = MatchExpression::css_selector("p.note");
+		assert_eq!(elements.extract_from(html)?, "Requires Rust 1.70 or 1.75\nDeprecated in 2.0");
+
+		// Strict checks name the ambiguity and how to resolve it
+		let error = groups.check_unambiguous(html).unwrap_err().to_string();
+		assert!(error.contains("2 capture groups") && error.contains("(?:...)"), "{}", error);
+		let error = MatchExpression::regex(r"1\.\d+").check_unambiguous(html).unwrap_err();
+		assert!(error.to_string().contains("matches 3 times"), "{}", error);
+		let error = elements.check_unambiguous(html).unwrap_err().to_string();
+		assert!(error.contains("matches 2 elements"), "{}", error);
+
+		// Expressions selecting a single region pass
+		assert!(MatchExpression::regex(r"MSRV: (\d+\.\d+)").check_unambiguous(html).is_ok());
+		assert!(MatchExpression::css_selector("#msrv").check_unambiguous(html).is_ok());
+		let composite = MatchExpression::composite(vec![groups.clone()], None);
+		assert!(composite.check_unambiguous(html).is_err());
+
+		// Strict sources fail at extraction, lenient ones keep extracting
+		let url = "https://example.com/compat";
+		let lenient = HttpMatch::with_match_expression(url, groups)?;
+		let strict = lenient.clone().with_strict_match(true);
+		assert!(strict.strict_match());
+		assert_eq!(lenient.extract_content(html, &mut HashMap::new())?, "1");
+		assert!(matches!(
+			strict.extract_content(html, &mut HashMap::new()),
+			Err(SourceError::ContentParsing(_))
+		));
+		Ok(())
+	}
 }
@@ -0,0 +1,209 @@
+use cite_core::SourceError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Placeholder that redacted content is replaced with
+pub const REDACTED: &str = "[redacted]";
+
+/// A transform of extracted content, applied before it is cached or compared
+///
+/// Normalizers are applied in the order they are given, so that e.g. entities are decoded
+/// before whitespace is collapsed. A chain can be named and shared across a crate as a profile
+/// in `cite.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalizer {
+	/// Collapse runs of whitespace into single spaces and trim the ends
+	Whitespace,
+	/// Trim each line and drop blank lines, keeping the line structure
+	TrimLines,
+	/// Convert `\r\n` and `\r` line endings to `\n`
+	LineEndings,
+	/// Lowercase the content
+	Case,
+	/// Decode HTML entities, e.g. `&amp;` to `&`
+	Entities,
+	/// Replace matches of a regular expression, e.g. dates or build ids, with `[redacted]`
+	Redact(String),
+}
+
+impl Normalizer {
+	/// Parse from string, e.g. `whitespace` or `redact:\d{4}-\d{2}-\d{2}`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		if let Some(pattern) = s.strip_prefix("redact:") {
+			Regex::new(pattern)
+				.map_err(|e| format!("Invalid redact pattern '{}': {}", pattern, e))?;
+			return Ok(Normalizer::Redact(pattern.to_string()));
+		}
+		match s.to_lowercase().as_str() {
+			"whitespace" | "collapse_whitespace" => Ok(Normalizer::Whitespace),
+			"trim_lines" => Ok(Normalizer::TrimLines),
+			"line_endings" => Ok(Normalizer::LineEndings),
+			"case" => Ok(Normalizer::Case),
+			"entities" => Ok(Normalizer::Entities),
+			_ => Err(format!(
+				"Invalid normalizer: '{}'. Valid values: whitespace, trim_lines, line_endings, case, entities, redact:<regex>",
+				s
+			)),
+		}
+	}
+
+	/// Apply the transform to content
+	pub fn apply(&self, content: &str) -> Result<String, SourceError> {
+		Ok(match self {
+			Normalizer::Whitespace => content.split_whitespace().collect::<Vec<_>>().join(" "),
+			Normalizer::TrimLines => content
+				.lines()
+				.map(str::trim)
+				.filter(|line| !line.is_empty())
+				.collect::<Vec<_>>()
+				.join("\n"),
+			Normalizer::LineEndings => content.replace("\r\n", "\n").replace('\r', "\n"),
+			Normalizer::Case => content.to_lowercase(),
+			Normalizer::Entities => html_escape::decode_html_entities(content).into_owned(),
+			Normalizer::Redact(pattern) => Regex::new(pattern)
+				.map_err(|e| {
+					SourceError::ContentParsing(format!(
+						"Invalid redact pattern '{}': {}",
+						pattern, e
+					))
+				})?
+				.replace_all(content, REDACTED)
+				.into_owned(),
+		})
+	}
+}
+
+impl std::fmt::Display for Normalizer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Normalizer::Whitespace => write!(f, "whitespace"),
+			Normalizer::TrimLines => write!(f, "trim_lines"),
+			Normalizer::LineEndings => write!(f, "line_endings"),
+			Normalizer::Case => write!(f, "case"),
+			Normalizer::Entities => write!(f, "entities"),
+			Normalizer::Redact(pattern) => write!(f, "redact:{}", pattern),
+		}
+	}
+}
+
+/// How the whitespace of extracted content is normalized, so that reflowed HTML is not drift
+///
+/// A shorthand for the whitespace normalizers, appended to the chain of a citation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalizeMode {
+	/// Keep the whitespace as extracted
+	#[default]
+	None,
+	/// Collapse runs of whitespace into single spaces and trim the ends
+	CollapseWhitespace,
+	/// Trim each line and drop blank lines
+	TrimLines,
+}
+
+impl NormalizeMode {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.trim().to_lowercase().as_str() {
+			"none" => Ok(NormalizeMode::None),
+			"collapse_whitespace" => Ok(NormalizeMode::CollapseWhitespace),
+			"trim_lines" => Ok(NormalizeMode::TrimLines),
+			_ => Err(format!(
+				"Invalid normalize mode: '{}'. Valid values: none, collapse_whitespace, trim_lines",
+				s
+			)),
+		}
+	}
+
+	/// Check if this is the default mode (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == NormalizeMode::default()
+	}
+
+	/// Get the normalizer applying this mode, if any
+	pub fn normalizer(&self) -> Option<Normalizer> {
+		match self {
+			NormalizeMode::None => None,
+			NormalizeMode::CollapseWhitespace => Some(Normalizer::Whitespace),
+			NormalizeMode::TrimLines => Some(Normalizer::TrimLines),
+		}
+	}
+}
+
+impl std::fmt::Display for NormalizeMode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			NormalizeMode::None => write!(f, "none"),
+			NormalizeMode::CollapseWhitespace => write!(f, "collapse_whitespace"),
+			NormalizeMode::TrimLines => write!(f, "trim_lines"),
+		}
+	}
+}
+
+/// Apply a chain of normalizers to content, in order
+pub fn normalize(normalizers: &[Normalizer], content: &str) -> Result<String, SourceError> {
+	normalizers
+		.iter()
+		.try_fold(content.to_string(), |content, normalizer| normalizer.apply(&content))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_apply() -> Result<(), SourceError> {
+		assert_eq!(Normalizer::Whitespace.apply("  Tom\n\tand  Jerry ")?, "Tom and Jerry");
+		assert_eq!(Normalizer::TrimLines.apply("  Tom\n\n\tand  Jerry \n ")?, "Tom\nand  Jerry");
+		assert_eq!(Normalizer::LineEndings.apply("a\r\nb\rc\n")?, "a\nb\nc\n");
+		assert_eq!(Normalizer::Case.apply("MUST Not")?, "must not");
+		assert_eq!(Normalizer::Entities.apply("Tom &amp; Jerry")?, "Tom & Jerry");
+		assert_eq!(
+			Normalizer::Redact(r"\d{4}-\d{2}-\d{2}".to_string()).apply("Updated 2024-05-01.")?,
+			"Updated [redacted]."
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_normalize_in_order() -> Result<(), SourceError> {
+		let chain = [Normalizer::Entities, Normalizer::Case, Normalizer::Whitespace];
+		assert_eq!(normalize(&chain, "MUST&nbsp;&amp;\n  SHOULD")?, "must & should");
+
+		// Lowercasing first keeps the redaction from matching
+		let redact = Normalizer::Redact("ID-[0-9]+".to_string());
+		assert_eq!(normalize(&[redact.clone(), Normalizer::Case], "ID-42")?, "[redacted]");
+		assert_eq!(normalize(&[Normalizer::Case, redact], "ID-42")?, "id-42");
+		assert_eq!(normalize(&[], "As is ")?, "As is ");
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_str() {
+		assert_eq!(Normalizer::from_str("Whitespace"), Ok(Normalizer::Whitespace));
+		assert_eq!(Normalizer::from_str("line_endings"), Ok(Normalizer::LineEndings));
+		assert_eq!(Normalizer::from_str("collapse_whitespace"), Ok(Normalizer::Whitespace));
+		assert_eq!(Normalizer::from_str("trim_lines"), Ok(Normalizer::TrimLines));
+		assert_eq!(
+			Normalizer::from_str(r"redact:v\d+"),
+			Ok(Normalizer::Redact(r"v\d+".to_string()))
+		);
+		assert!(Normalizer::from_str("redact:(").is_err());
+		assert!(Normalizer::from_str("stem").unwrap_err().contains("Valid values"));
+		assert_eq!(Normalizer::Redact("x".to_string()).to_string(), "redact:x");
+	}
+
+	#[test]
+	fn test_normalize_mode() {
+		assert_eq!(NormalizeMode::from_str("Trim_Lines"), Ok(NormalizeMode::TrimLines));
+		assert_eq!(NormalizeMode::from_str("none").map(|mode| mode.normalizer()), Ok(None));
+		assert_eq!(
+			NormalizeMode::from_str("collapse_whitespace").map(|mode| mode.normalizer()),
+			Ok(Some(Normalizer::Whitespace))
+		);
+		assert!(NormalizeMode::from_str("whitespace").is_err());
+		assert_eq!(NormalizeMode::TrimLines.to_string(), "trim_lines");
+		assert!(NormalizeMode::None.is_default());
+	}
+}
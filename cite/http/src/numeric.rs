@@ -0,0 +1,243 @@
+use cite_core::SourceError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Allowance around an expected number that the current value may drift within
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Tolerance {
+	/// Absolute allowance, e.g. the `10` of `100±10`
+	Absolute(f64),
+	/// Allowance as a percentage of the expected value, e.g. the `10%` of `100 ~ 10%`
+	Percent(f64),
+}
+
+impl Tolerance {
+	/// Parse from string; values ending in `%` are percentages
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		let s = s.trim();
+		let (amount, tolerance): (&str, fn(f64) -> Self) = match s.strip_suffix('%') {
+			Some(percent) => (percent, Tolerance::Percent),
+			None => (s, Tolerance::Absolute),
+		};
+		match amount.trim().parse::<f64>() {
+			Ok(amount) if amount.is_finite() && amount >= 0.0 => Ok(tolerance(amount)),
+			_ => Err(format!(
+				"Invalid tolerance: '{}'. Expected a non-negative number or percentage, e.g. 10 or 10%",
+				s
+			)),
+		}
+	}
+
+	/// Get the absolute allowance around the given expected value
+	pub fn allowance(&self, expected: f64) -> f64 {
+		match self {
+			Tolerance::Absolute(amount) => *amount,
+			Tolerance::Percent(percent) => expected.abs() * percent / 100.0,
+		}
+	}
+}
+
+impl std::fmt::Display for Tolerance {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Tolerance::Absolute(amount) => write!(f, "{}", amount),
+			Tolerance::Percent(percent) => write!(f, "{}%", percent),
+		}
+	}
+}
+
+/// Number a citation expects the extracted value to be, give or take a tolerance
+///
+/// Written as `100`, `100±10` (also `100+-10`) or `100 ~ 10%`, so that a cited value that
+/// fluctuates, such as a latency on a live dashboard, only drifts when it changes by more than
+/// the tolerance.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedNumber {
+	/// The expected value
+	pub value: f64,
+	/// The allowance around the expected value, exact if none
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub tolerance: Option<Tolerance>,
+}
+
+impl ExpectedNumber {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		let invalid = || {
+			format!(
+				"Invalid expected number: '{}'. Expected a number with an optional tolerance, e.g. 100, 100±10 or 100 ~ 10%",
+				s
+			)
+		};
+
+		let (value, tolerance) = match s.split_once(['±', '~']).or_else(|| s.split_once("+-")) {
+			Some((value, tolerance)) => (value, Some(Tolerance::from_str(tolerance)?)),
+			None => (s, None),
+		};
+		let value = value.trim().parse::<f64>().map_err(|_| invalid())?;
+		if !value.is_finite() {
+			return Err(invalid());
+		}
+		Ok(Self { value, tolerance })
+	}
+
+	/// Get the inclusive range of values within the tolerance
+	pub fn range(&self) -> (f64, f64) {
+		let allowance = self.tolerance.map_or(0.0, |tolerance| tolerance.allowance(self.value));
+		(self.value - allowance, self.value + allowance)
+	}
+
+	/// Check if the given value is within the tolerance
+	pub fn contains(&self, value: f64) -> bool {
+		let (low, high) = self.range();
+		(low..=high).contains(&value)
+	}
+}
+
+impl std::fmt::Display for ExpectedNumber {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.tolerance {
+			Some(tolerance @ Tolerance::Percent(_)) => write!(f, "{} ~ {}", self.value, tolerance),
+			Some(tolerance) => write!(f, "{}±{}", self.value, tolerance),
+			None => write!(f, "{}", self.value),
+		}
+	}
+}
+
+/// Expected number compared against the value currently extracted
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberCheck {
+	/// The number the citation expects
+	pub expected: ExpectedNumber,
+	/// The number parsed from the current extracted content
+	pub current: f64,
+}
+
+impl NumberCheck {
+	/// Parse the current number from the extracted content and compare it to the expected one
+	pub fn new(expected: ExpectedNumber, current_content: &str) -> Result<Self, SourceError> {
+		Ok(Self { expected, current: parse_number(current_content)? })
+	}
+
+	/// Check if the current number is within the tolerance
+	pub fn matches(&self) -> bool {
+		self.expected.contains(self.current)
+	}
+
+	/// Get the difference of the current number from the expected one
+	pub fn delta(&self) -> f64 {
+		self.current - self.expected.value
+	}
+}
+
+/// Parse the last number in extracted content, e.g. `1234.5` from `~1,234.5 ms` and `104` from
+/// `p95 latency: 104ms`
+///
+/// The value follows its label, so a number in the label like the `95` of `p95` is passed over.
+/// Patterns with a capture group extract only the group, which picks the number explicitly.
+/// Thousands separators are ignored, so that numbers formatted for display parse as such.
+pub fn parse_number(content: &str) -> Result<f64, SourceError> {
+	let number = Regex::new(r"-?\d[\d,]*(?:\.\d+)?")
+		.map_err(|e| SourceError::ContentParsing(format!("Invalid number pattern: {}", e)))?;
+	number
+		.find_iter(content)
+		.last()
+		.and_then(|number| number.as_str().replace(',', "").parse().ok())
+		.ok_or_else(|| {
+			SourceError::ContentParsing(format!(
+				"No number found in the extracted content: '{}'",
+				content.trim()
+			))
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::MatchExpression;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		let exact = ExpectedNumber::from_str("100")?;
+		assert_eq!(exact, ExpectedNumber { value: 100.0, tolerance: None });
+		assert_eq!(exact.to_string(), "100");
+
+		let absolute = ExpectedNumber::from_str("100±10")?;
+		assert_eq!(absolute.tolerance, Some(Tolerance::Absolute(10.0)));
+		assert_eq!(absolute.to_string(), "100±10");
+		assert_eq!(ExpectedNumber::from_str(" 100 +- 10 ")?, absolute);
+
+		let percent = ExpectedNumber::from_str("100 ~ 10%")?;
+		assert_eq!(percent.tolerance, Some(Tolerance::Percent(10.0)));
+		assert_eq!(percent.to_string(), "100 ~ 10%");
+		assert_eq!(ExpectedNumber::from_str(&percent.to_string())?, percent);
+
+		for invalid in ["", "fast", "100±", "100±-5", "100 ~ ten%", "inf"] {
+			assert!(ExpectedNumber::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_absolute_tolerance() -> Result<(), String> {
+		let expected = ExpectedNumber::from_str("100±10")?;
+		assert_eq!(expected.range(), (90.0, 110.0));
+		assert!(expected.contains(100.0));
+		assert!(expected.contains(90.0));
+		assert!(expected.contains(110.0));
+		assert!(!expected.contains(89.9));
+		assert!(!expected.contains(130.0));
+
+		let exact = ExpectedNumber::from_str("100")?;
+		assert!(exact.contains(100.0));
+		assert!(!exact.contains(100.5));
+		Ok(())
+	}
+
+	#[test]
+	fn test_percent_tolerance() -> Result<(), String> {
+		let expected = ExpectedNumber::from_str("200 ~ 5%")?;
+		assert_eq!(expected.range(), (190.0, 210.0));
+		assert!(expected.contains(195.0));
+		assert!(!expected.contains(211.0));
+		assert!(!expected.contains(150.0));
+
+		// The allowance is a share of the magnitude of negative values too
+		let negative = ExpectedNumber::from_str("-50 ~ 10%")?;
+		assert_eq!(negative.range(), (-55.0, -45.0));
+		Ok(())
+	}
+
+	#[test]
+	fn test_number_check() -> Result<(), SourceError> {
+		let expected = ExpectedNumber { value: 100.0, tolerance: Some(Tolerance::Percent(10.0)) };
+
+		let within = NumberCheck::new(expected, "Latency: ~104ms")?;
+		assert!(within.matches());
+		assert_eq!(within.delta(), 4.0);
+
+		let outside = NumberCheck::new(expected, "1,250 ms")?;
+		assert_eq!(outside.current, 1250.0);
+		assert!(!outside.matches());
+
+		assert_eq!(parse_number("-3.5 degrees")?, -3.5);
+		assert!(NumberCheck::new(expected, "unavailable").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_number_follows_its_label() -> Result<(), SourceError> {
+		let expected = ExpectedNumber { value: 100.0, tolerance: Some(Tolerance::Percent(10.0)) };
+		let page = "<td>p95 latency: 104ms</td><td>p99 latency: 250ms</td>";
+
+		// The example of the README, whose match includes the label
+		let matched = MatchExpression::regex("p95 latency: [0-9]+ms").extract_from(page)?;
+		assert_eq!(matched, "p95 latency: 104ms");
+		assert_eq!(NumberCheck::new(expected, &matched)?.current, 104.0);
+
+		// A capture group extracts the number alone
+		let captured = MatchExpression::regex("p99 latency: ([0-9]+)ms").extract_from(page)?;
+		assert_eq!(parse_number(&captured)?, 250.0);
+		Ok(())
+	}
+}
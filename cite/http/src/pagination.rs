@@ -0,0 +1,180 @@
+use cite_core::SourceError;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Default maximum number of pages followed for paginated sources
+pub const DEFAULT_MAX_PAGES: usize = 10;
+
+/// How to follow documentation that spans multiple paginated pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Pagination {
+	/// Only the cited URL is fetched
+	#[default]
+	None,
+	/// Follow `rel="next"` links (`<link>` or `<a>`) from each page
+	RelNext,
+}
+
+impl Pagination {
+	/// Parse from string
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.to_lowercase().as_str() {
+			"none" => Ok(Pagination::None),
+			"rel-next" => Ok(Pagination::RelNext),
+			_ => Err(format!("Invalid pagination: '{}'. Valid values: none, rel-next", s)),
+		}
+	}
+
+	/// Convert to string representation
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Pagination::None => "none",
+			Pagination::RelNext => "rel-next",
+		}
+	}
+
+	/// Check if this is the default pagination (used to keep serialized sources minimal)
+	pub fn is_default(&self) -> bool {
+		*self == Pagination::default()
+	}
+}
+
+impl std::fmt::Display for Pagination {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+/// Find the `rel="next"` link of a page, resolved against the page's URL
+pub fn next_page_url(raw_content: &str, page_url: &str) -> Option<String> {
+	let document = Html::parse_document(raw_content);
+	let selector = Selector::parse(r#"link[rel~="next"][href], a[rel~="next"][href]"#).ok()?;
+	let href = document.select(&selector).next()?.value().attr("href")?;
+
+	let base = reqwest::Url::parse(page_url).ok()?;
+	base.join(href).ok().map(|url| url.to_string())
+}
+
+/// Fetch a sequence of pages by following `rel="next"` links, starting from `start_url`
+///
+/// At most `max_pages` pages are fetched, and a page that was already fetched ends the
+/// sequence, so link cycles cannot loop forever.
+pub fn fetch_pages<F>(
+	start_url: &str,
+	max_pages: usize,
+	mut fetch: F,
+) -> Result<Vec<String>, SourceError>
+where
+	F: FnMut(&str) -> Result<String, SourceError>,
+{
+	let mut pages = Vec::new();
+	let mut visited = HashSet::new();
+	let mut next = Some(start_url.to_string());
+
+	while let Some(url) = next.take() {
+		if pages.len() >= max_pages || !visited.insert(url.clone()) {
+			break;
+		}
+
+		let raw_content = fetch(&url)?;
+		next = next_page_url(&raw_content, &url);
+		pages.push(raw_content);
+	}
+
+	Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	fn page(body: &str, next: Option<&str>) -> String {
+		match next {
+			Some(next) => format!(
+				"<html><head><link rel=\"next\" href=\"{}\"></head><body>{}</body></html>",
+				next, body
+			),
+			None => format!("<html><body>{}</body></html>", body),
+		}
+	}
+
+	/// Fetch from a fixed set of responses, recording the requested URLs
+	fn fetcher<'a>(
+		responses: &'a HashMap<&str, String>,
+		requested: &'a mut Vec<String>,
+	) -> impl FnMut(&str) -> Result<String, SourceError> + 'a {
+		move |url| {
+			requested.push(url.to_string());
+			responses
+				.get(url)
+				.cloned()
+				.ok_or_else(|| SourceError::Network(format!("unexpected request: {}", url)))
+		}
+	}
+
+	#[test]
+	fn test_pagination_from_str() {
+		assert_eq!(Pagination::from_str("rel-next").unwrap(), Pagination::RelNext);
+		assert_eq!(Pagination::from_str("none").unwrap(), Pagination::None);
+		assert!(Pagination::from_str("template").is_err());
+	}
+
+	#[test]
+	fn test_next_page_url() {
+		let raw_content = page("one", Some("/docs?page=2"));
+		assert_eq!(
+			next_page_url(&raw_content, "https://example.com/docs?page=1").as_deref(),
+			Some("https://example.com/docs?page=2")
+		);
+
+		let raw_content = "<a rel=\"prev\" href=\"/a\">prev</a><a rel=\"next\" href=\"b\">next</a>";
+		assert_eq!(
+			next_page_url(raw_content, "https://example.com/docs/a").as_deref(),
+			Some("https://example.com/docs/b")
+		);
+
+		assert_eq!(next_page_url(&page("last", None), "https://example.com"), None);
+	}
+
+	#[test]
+	fn test_fetch_pages_follows_next_links() -> Result<(), SourceError> {
+		let mut responses = HashMap::new();
+		responses.insert("https://example.com/1", page("one", Some("/2")));
+		responses.insert("https://example.com/2", page("two", Some("/3")));
+		responses.insert("https://example.com/3", page("three", None));
+
+		let mut requested = Vec::new();
+		let pages = fetch_pages("https://example.com/1", 10, fetcher(&responses, &mut requested))?;
+
+		assert_eq!(pages.len(), 3);
+		assert!(pages[2].contains("three"));
+		assert_eq!(
+			requested,
+			vec!["https://example.com/1", "https://example.com/2", "https://example.com/3"]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_fetch_pages_loop_protection() -> Result<(), SourceError> {
+		let mut responses = HashMap::new();
+		responses.insert("https://example.com/1", page("one", Some("/2")));
+		responses.insert("https://example.com/2", page("two", Some("/1")));
+
+		// A cycle ends once a page repeats
+		let mut requested = Vec::new();
+		let pages = fetch_pages("https://example.com/1", 10, fetcher(&responses, &mut requested))?;
+		assert_eq!(pages.len(), 2);
+		assert_eq!(requested.len(), 2);
+
+		// The page cap bounds the sequence
+		let mut requested = Vec::new();
+		let pages = fetch_pages("https://example.com/1", 1, fetcher(&responses, &mut requested))?;
+		assert_eq!(pages.len(), 1);
+		assert_eq!(requested, vec!["https://example.com/1"]);
+		Ok(())
+	}
+}
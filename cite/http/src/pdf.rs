@@ -0,0 +1,149 @@
+use cite_core::SourceError;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Pages of a PDF document whose extracted text is cited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PdfPages {
+	/// Every page of the document
+	#[default]
+	All,
+	/// The pages from `first` through `last`, numbered from 1
+	Range { first: usize, last: usize },
+}
+
+impl PdfPages {
+	/// Parse from string, e.g. `all`, `3` or `3-5`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		let invalid = || {
+			format!("Invalid PDF pages: '{}'. Expected all, a page number or a range, e.g. 3-5", s)
+		};
+		let page = |page: &str| page.trim().parse::<usize>().ok().filter(|page| *page > 0);
+
+		let s = s.trim();
+		if s.eq_ignore_ascii_case("all") {
+			return Ok(PdfPages::All);
+		}
+		let (first, last) = match s.split_once('-') {
+			Some((first, last)) => (page(first), page(last)),
+			None => (page(s), page(s)),
+		};
+		match (first, last) {
+			(Some(first), Some(last)) if first <= last => Ok(PdfPages::Range { first, last }),
+			_ => Err(invalid()),
+		}
+	}
+
+	/// Get the indices of the selected pages in a document with the given number of pages
+	pub fn select(&self, page_count: usize) -> Result<Range<usize>, SourceError> {
+		match *self {
+			PdfPages::All => Ok(0..page_count),
+			PdfPages::Range { first, last } if last <= page_count => Ok(first - 1..last),
+			PdfPages::Range { .. } => Err(SourceError::ContentParsing(format!(
+				"Cannot cite pages {} of a PDF with {} pages",
+				self, page_count
+			))),
+		}
+	}
+}
+
+impl std::fmt::Display for PdfPages {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PdfPages::All => write!(f, "all"),
+			PdfPages::Range { first, last } if first == last => write!(f, "{}", first),
+			PdfPages::Range { first, last } => write!(f, "{}-{}", first, last),
+		}
+	}
+}
+
+/// Extract the text of the given pages of a PDF document, one line per line of text
+///
+/// Text is extracted with `pdf-extract`, which decodes strings through the encodings and
+/// `ToUnicode` maps of their fonts. Runs of whitespace within a line are collapsed and blank
+/// lines dropped, since the spacing of extracted text follows the layout of the page rather
+/// than the text.
+#[cfg(feature = "pdf")]
+pub fn extract_text(pdf: &[u8], pages: PdfPages) -> Result<String, SourceError> {
+	let invalid = |reason: String| {
+		SourceError::ContentParsing(format!("Failed to extract PDF text: {}", reason))
+	};
+
+	// Malformed documents can panic the extractor rather than fail it
+	let document = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(pdf))
+		.map_err(|_| invalid("the document is malformed".to_string()))?
+		.map_err(|e| invalid(e.to_string()))?;
+
+	let text = document[pages.select(document.len())?]
+		.iter()
+		.flat_map(|page| page.lines())
+		.map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+		.filter(|line| !line.is_empty())
+		.collect::<Vec<_>>();
+	Ok(text.join("\n"))
+}
+
+/// Extract the text of the given pages of a PDF document, which requires the `pdf` feature
+#[cfg(not(feature = "pdf"))]
+pub fn extract_text(_pdf: &[u8], _pages: PdfPages) -> Result<String, SourceError> {
+	Err(SourceError::ContentParsing(
+		"Citing PDF documents requires the `pdf` feature of cite".to_string(),
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_pdf_pages_from_str() -> Result<(), String> {
+		assert_eq!(PdfPages::from_str("all")?, PdfPages::All);
+		assert_eq!(PdfPages::from_str("3")?, PdfPages::Range { first: 3, last: 3 });
+		assert_eq!(PdfPages::from_str(" 3 - 5 ")?, PdfPages::Range { first: 3, last: 5 });
+		assert_eq!(PdfPages::from_str("3-5")?.to_string(), "3-5");
+		assert_eq!(PdfPages::from_str("3")?.to_string(), "3");
+
+		for invalid in ["", "0", "5-3", "three", "3-"] {
+			assert!(PdfPages::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_pdf_pages_select() -> Result<(), SourceError> {
+		assert_eq!(PdfPages::All.select(4)?, 0..4);
+		assert_eq!(PdfPages::Range { first: 2, last: 3 }.select(4)?, 1..3);
+		assert!(PdfPages::Range { first: 3, last: 5 }.select(4).is_err());
+		Ok(())
+	}
+
+	#[cfg(feature = "pdf")]
+	#[test]
+	fn test_extract_text() -> Result<(), SourceError> {
+		const SPEC: &[u8] = include_bytes!("../tests/content/spec.pdf");
+
+		// Pages are read whether their content is compressed or not
+		assert_eq!(
+			extract_text(SPEC, PdfPages::All)?,
+			"Cite Protocol Specification\nVersion 1.0\n3. Message Format\nEach message starts \
+			 with a 4-byte length (big endian).\nPayloads are UTF-8 encoded.\n4. Errors\nUnknown \
+			 messages are ignored."
+		);
+		assert_eq!(
+			extract_text(SPEC, PdfPages::Range { first: 3, last: 3 })?,
+			"4. Errors\nUnknown messages are ignored."
+		);
+
+		assert!(extract_text(b"<html></html>", PdfPages::All).is_err());
+		assert!(extract_text(b"%PDF-1.4\n%%EOF\n", PdfPages::All).is_err());
+		assert!(extract_text(SPEC, PdfPages::Range { first: 2, last: 4 }).is_err());
+		Ok(())
+	}
+
+	#[cfg(not(feature = "pdf"))]
+	#[test]
+	fn test_extract_text_requires_feature() {
+		assert!(extract_text(b"%PDF-1.4\n%%EOF\n", PdfPages::All).is_err());
+	}
+}
@@ -0,0 +1,481 @@
+//! Polite fetching of cited pages
+//!
+//! Citations are fetched during builds on every developer machine and CI runner, so a cited site
+//! sees many requests from this crate. Requests identify it with a descriptive user agent. Sources
+//! that respect robots.txt check the rules of a host once per build process, refuse disallowed
+//! paths, and wait out the host's `Crawl-delay` (capped at [MAX_CRAWL_DELAY]) between requests.
+//!
+//! A workspace building from scratch validates its citations all at once. With `CITE_HOST_RPS`,
+//! requests to each host are spaced out to at most that many per second, across every citation
+//! of the build process, while requests to different hosts proceed in parallel. Only requests
+//! count against the limit, so citations compared against cached content cost nothing more.
+
+use cite_core::SourceError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+/// User agent sent with every request, unless overridden
+pub const DEFAULT_USER_AGENT: &str =
+	concat!("cite/", env!("CARGO_PKG_VERSION"), " (+https://github.com/ramate-io/cite)");
+
+/// Environment variable overriding the default user agent
+pub const USER_AGENT_ENV: &str = "CITE_USER_AGENT";
+
+/// Environment variable making every http citation respect robots.txt
+pub const RESPECT_ROBOTS_ENV: &str = "CITE_RESPECT_ROBOTS";
+
+/// Environment variable limiting the requests per second sent to each host
+pub const HOST_RPS_ENV: &str = "CITE_HOST_RPS";
+
+/// Longest delay waited between two requests to the same host
+pub const MAX_CRAWL_DELAY: Duration = Duration::from_secs(5);
+
+/// Get the user agent to send: the given one, that of the environment, or the default
+pub fn user_agent(explicit: Option<&str>) -> String {
+	resolve_user_agent(explicit, std::env::var(USER_AGENT_ENV).ok())
+}
+
+fn resolve_user_agent(explicit: Option<&str>, env: Option<String>) -> String {
+	explicit
+		.map(str::to_string)
+		.or(env)
+		.filter(|user_agent| !user_agent.trim().is_empty())
+		.unwrap_or_else(|| DEFAULT_USER_AGENT.to_string())
+}
+
+/// Check if robots.txt is respected by every http citation, as set by the environment
+pub fn respects_robots_from_env() -> bool {
+	std::env::var(RESPECT_ROBOTS_ENV)
+		.is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+}
+
+/// Get the limit of requests per second to each host set by the environment, if any
+pub fn host_rps_from_env() -> Option<f64> {
+	std::env::var(HOST_RPS_ENV)
+		.ok()
+		.and_then(|rps| rps.trim().parse::<f64>().ok())
+		.filter(|rps| rps.is_finite() && *rps > 0.0)
+}
+
+/// The rules of a robots.txt that apply to one user agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsRules {
+	allow: Vec<String>,
+	disallow: Vec<String>,
+	crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+	/// Parse the rules of a robots.txt for a user agent
+	///
+	/// The group naming the product of the user agent (e.g. `cite`) applies, or else the `*`
+	/// group. Paths are matched by prefix.
+	pub fn parse(robots_txt: &str, user_agent: &str) -> Self {
+		let product = user_agent.split('/').next().unwrap_or_default().trim().to_lowercase();
+		let mut specific = None;
+		let mut wildcard = None;
+
+		let mut agents = Vec::<String>::new();
+		let mut rules = Self::default();
+		let mut in_rules = false;
+
+		let mut finish_group = |agents: &[String], rules: Self| {
+			if agents.iter().any(|agent| *agent == product) {
+				specific.get_or_insert(rules);
+			} else if agents.iter().any(|agent| agent == "*") {
+				wildcard.get_or_insert(rules);
+			}
+		};
+
+		for line in robots_txt.lines() {
+			let line = line.split('#').next().unwrap_or_default();
+			let Some((field, value)) = line.split_once(':') else {
+				continue;
+			};
+			let value = value.trim();
+
+			match field.trim().to_lowercase().as_str() {
+				"user-agent" => {
+					if in_rules {
+						finish_group(&agents, std::mem::take(&mut rules));
+						agents.clear();
+						in_rules = false;
+					}
+					agents.push(value.to_lowercase());
+				}
+				"allow" if !value.is_empty() => {
+					rules.allow.push(value.to_string());
+					in_rules = true;
+				}
+				"disallow" => {
+					// An empty disallow allows everything
+					if !value.is_empty() {
+						rules.disallow.push(value.to_string());
+					}
+					in_rules = true;
+				}
+				"crawl-delay" => {
+					rules.crawl_delay = value
+						.parse::<f64>()
+						.ok()
+						.filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+						.map(|seconds| Duration::from_secs_f64(seconds).min(MAX_CRAWL_DELAY));
+					in_rules = true;
+				}
+				_ => {}
+			}
+		}
+		finish_group(&agents, rules);
+
+		specific.or(wildcard).unwrap_or_default()
+	}
+
+	/// Check if a path may be fetched; the longest matching rule wins, allowing on a tie
+	pub fn is_allowed(&self, path: &str) -> bool {
+		let longest = |rules: &[String]| {
+			rules
+				.iter()
+				.filter(|rule| path.starts_with(rule.as_str()))
+				.map(String::len)
+				.max()
+		};
+
+		match (longest(&self.allow), longest(&self.disallow)) {
+			(_, None) => true,
+			(None, Some(_)) => false,
+			(Some(allow), Some(disallow)) => allow >= disallow,
+		}
+	}
+
+	/// Get the delay between two requests to the host, capped at [MAX_CRAWL_DELAY]
+	pub fn crawl_delay(&self) -> Option<Duration> {
+		self.crawl_delay
+	}
+}
+
+/// What is known about a host within this build process
+///
+/// The rules are fetched once per host, outside of the lock of the hosts, so that fetching the
+/// robots.txt of one host holds up neither other hosts nor the requests they wait on.
+struct HostState {
+	rules: Arc<OnceLock<RobotsRules>>,
+	next_request: Instant,
+}
+
+/// The time a request is scheduled at and waited for, injected so that tests need not sleep
+trait Clock {
+	fn now(&self) -> Instant;
+
+	fn sleep(&self, duration: Duration);
+}
+
+/// The clock of the system, sleeping the current thread
+struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+
+	fn sleep(&self, duration: Duration) {
+		std::thread::sleep(duration);
+	}
+}
+
+fn hosts() -> &'static Mutex<HashMap<String, HostState>> {
+	static HOSTS: OnceLock<Mutex<HashMap<String, HostState>>> = OnceLock::new();
+	HOSTS.get_or_init(Default::default)
+}
+
+/// A token bucket of requests to one host, holding a single token so that requests are evenly
+/// spaced
+///
+/// Tokens are reserved ahead of time: a request taking a token the bucket does not hold yet
+/// leaves it in debt, which later requests wait out in turn.
+struct TokenBucket {
+	tokens: f64,
+	refilled_at: Instant,
+}
+
+impl TokenBucket {
+	/// Take a token, returning how long to wait until it is refilled
+	fn take(&mut self, rps: f64, now: Instant) -> Duration {
+		let refilled = now.saturating_duration_since(self.refilled_at).as_secs_f64() * rps;
+		self.tokens = (self.tokens + refilled).min(1.0) - 1.0;
+		self.refilled_at = now;
+		Duration::from_secs_f64((-self.tokens).max(0.0) / rps)
+	}
+}
+
+fn buckets() -> &'static Mutex<HashMap<String, TokenBucket>> {
+	static BUCKETS: OnceLock<Mutex<HashMap<String, TokenBucket>>> = OnceLock::new();
+	BUCKETS.get_or_init(Default::default)
+}
+
+/// Wait until a request to the host of a URL is within the limit of `CITE_HOST_RPS`, if set
+pub fn throttle(url: &str) {
+	if let Some(rps) = host_rps_from_env() {
+		throttle_at(url, rps);
+	}
+}
+
+/// Wait until a request to the host of a URL is within a limit of requests per second
+///
+/// The wait for a host's bucket is reserved under a lock but slept outside of it, so requests
+/// to other hosts are not held up.
+fn throttle_at(url: &str, rps: f64) {
+	let Ok(parsed) = reqwest::Url::parse(url) else {
+		return;
+	};
+	let origin = parsed.origin().ascii_serialization();
+	let wait = {
+		let mut buckets = buckets().lock().unwrap_or_else(PoisonError::into_inner);
+		let now = Instant::now();
+		buckets
+			.entry(origin)
+			.or_insert(TokenBucket { tokens: 1.0, refilled_at: now })
+			.take(rps, now)
+	};
+	std::thread::sleep(wait);
+}
+
+/// Check if a URL is that of the robots.txt of its host
+pub fn is_robots_txt(url: &str) -> bool {
+	reqwest::Url::parse(url).is_ok_and(|parsed| parsed.path() == "/robots.txt")
+}
+
+/// Check a URL against the robots.txt of its host, waiting out the host's crawl delay
+///
+/// The robots.txt is fetched with `fetch_robots` the first time a host is seen in this process,
+/// so repeated citations of one host check it once. A robots.txt that cannot be fetched has no
+/// rules.
+pub fn before_request<F>(url: &str, user_agent: &str, fetch_robots: F) -> Result<(), SourceError>
+where
+	F: FnOnce(&str) -> Result<String, SourceError>,
+{
+	before_request_with(url, user_agent, fetch_robots, &SystemClock)
+}
+
+fn before_request_with<F>(
+	url: &str,
+	user_agent: &str,
+	fetch_robots: F,
+	clock: &dyn Clock,
+) -> Result<(), SourceError>
+where
+	F: FnOnce(&str) -> Result<String, SourceError>,
+{
+	let Ok(parsed) = reqwest::Url::parse(url) else {
+		return Ok(());
+	};
+	let origin = parsed.origin().ascii_serialization();
+	let robots_url = format!("{}/robots.txt", origin);
+	let path = match parsed.query() {
+		Some(query) => format!("{}?{}", parsed.path(), query),
+		None => parsed.path().to_string(),
+	};
+
+	let rules = {
+		let mut hosts = hosts().lock().unwrap_or_else(PoisonError::into_inner);
+		let host = hosts
+			.entry(origin.clone())
+			.or_insert_with(|| HostState { rules: Arc::default(), next_request: clock.now() });
+		host.rules.clone()
+	};
+	let rules = rules.get_or_init(|| {
+		fetch_robots(&robots_url)
+			.map(|robots_txt| RobotsRules::parse(&robots_txt, user_agent))
+			.unwrap_or_default()
+	});
+
+	if !rules.is_allowed(&path) {
+		return Err(SourceError::Network(format!(
+			"Fetching {} is disallowed by {}",
+			url, robots_url
+		)));
+	}
+
+	let wait = {
+		let mut hosts = hosts().lock().unwrap_or_else(PoisonError::into_inner);
+		let now = clock.now();
+		let host = hosts
+			.entry(origin)
+			.or_insert_with(|| HostState { rules: Arc::default(), next_request: now });
+		let wait = host.next_request.saturating_duration_since(now);
+		host.next_request = now + wait + rules.crawl_delay().unwrap_or_default();
+		wait
+	};
+
+	clock.sleep(wait);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ROBOTS_TXT: &str = "\
+User-agent: *
+Disallow: /private
+Crawl-delay: 0.2
+
+User-agent: cite
+User-agent: other-bot
+Disallow: /drafts/
+Allow: /drafts/published
+Crawl-delay: 60
+";
+
+	#[test]
+	fn test_parse_rules_for_user_agent() {
+		let rules = RobotsRules::parse(ROBOTS_TXT, DEFAULT_USER_AGENT);
+		assert!(rules.is_allowed("/private/page"));
+		assert!(!rules.is_allowed("/drafts/upcoming"));
+		assert!(rules.is_allowed("/drafts/published/guide"));
+		assert_eq!(rules.crawl_delay(), Some(MAX_CRAWL_DELAY));
+
+		// Other agents get the wildcard group
+		let rules = RobotsRules::parse(ROBOTS_TXT, "curl/8.0");
+		assert!(!rules.is_allowed("/private/page"));
+		assert!(rules.is_allowed("/drafts/upcoming"));
+		assert_eq!(rules.crawl_delay(), Some(Duration::from_millis(200)));
+
+		assert!(RobotsRules::parse("", DEFAULT_USER_AGENT).is_allowed("/anything"));
+	}
+
+	#[test]
+	fn test_robots_checked_once_per_host() {
+		let mut fetches = 0;
+		let mut fetch_robots = |robots_url: &str| {
+			fetches += 1;
+			assert_eq!(robots_url, "https://robots-once.example.com/robots.txt");
+			Ok("User-agent: *\nDisallow: /private\n".to_string())
+		};
+
+		let url = "https://robots-once.example.com/docs/guide";
+		assert!(before_request(url, DEFAULT_USER_AGENT, &mut fetch_robots).is_ok());
+		assert!(before_request(url, DEFAULT_USER_AGENT, &mut fetch_robots).is_ok());
+		let private = "https://robots-once.example.com/private/notes";
+		let error = before_request(private, DEFAULT_USER_AGENT, &mut fetch_robots).unwrap_err();
+		assert!(error.to_string().contains("disallowed by"));
+		assert_eq!(fetches, 1);
+
+		// A missing robots.txt allows everything
+		let missing = |_: &str| Err(SourceError::Network("status 404".to_string()));
+		let url = "https://robots-missing.example.com/private/notes";
+		assert!(before_request(url, DEFAULT_USER_AGENT, missing).is_ok());
+
+		assert!(is_robots_txt("https://robots-once.example.com/robots.txt"));
+		assert!(!is_robots_txt("https://robots-once.example.com/docs/robots.txt"));
+	}
+
+	/// A clock that only advances by what is slept on it
+	struct FakeClock {
+		now: Mutex<Instant>,
+		slept: Mutex<Vec<Duration>>,
+	}
+
+	impl FakeClock {
+		fn new() -> Self {
+			Self { now: Mutex::new(Instant::now()), slept: Mutex::new(Vec::new()) }
+		}
+
+		fn advance(&self, duration: Duration) {
+			*self.now.lock().unwrap() += duration;
+		}
+
+		fn slept(&self) -> Vec<Duration> {
+			self.slept.lock().unwrap().clone()
+		}
+	}
+
+	impl Clock for FakeClock {
+		fn now(&self) -> Instant {
+			*self.now.lock().unwrap()
+		}
+
+		fn sleep(&self, duration: Duration) {
+			self.slept.lock().unwrap().push(duration);
+			self.advance(duration);
+		}
+	}
+
+	#[test]
+	fn test_crawl_delay_between_requests() {
+		let fetch_robots = |_: &str| Ok("User-agent: *\nCrawl-delay: 0.2\n".to_string());
+		let url = "https://robots-delay.example.com/docs";
+		let clock = FakeClock::new();
+
+		// The first request goes out at once and the next waits out the crawl delay
+		before_request_with(url, DEFAULT_USER_AGENT, fetch_robots, &clock).unwrap();
+		before_request_with(url, DEFAULT_USER_AGENT, fetch_robots, &clock).unwrap();
+		assert_eq!(clock.slept(), vec![Duration::ZERO, Duration::from_millis(200)]);
+
+		// Time spent elsewhere counts towards the delay
+		clock.advance(Duration::from_millis(150));
+		before_request_with(url, DEFAULT_USER_AGENT, fetch_robots, &clock).unwrap();
+		assert_eq!(clock.slept()[2], Duration::from_millis(50));
+	}
+
+	#[test]
+	fn test_robots_fetched_without_holding_hosts() {
+		let url = "https://robots-slow.example.com/docs";
+		let other = "https://robots-other.example.com/docs";
+
+		// Requests to other hosts go ahead while a robots.txt is being fetched
+		let fetch_robots = |_: &str| {
+			before_request_with(other, DEFAULT_USER_AGENT, |_| Ok(String::new()), &FakeClock::new())
+				.map(|_| String::new())
+		};
+		assert!(
+			before_request_with(url, DEFAULT_USER_AGENT, fetch_robots, &FakeClock::new()).is_ok()
+		);
+	}
+
+	#[test]
+	fn test_throttle_spaces_requests_to_one_host() {
+		let url = "https://throttle-one.example.com/docs";
+
+		// The first request goes out at once, the others one tenth of a second apart
+		let start = Instant::now();
+		throttle_at(url, 10.0);
+		assert!(start.elapsed() < Duration::from_millis(50));
+		let threads = (0..3)
+			.map(|_| std::thread::spawn(move || throttle_at(url, 10.0)))
+			.collect::<Vec<_>>();
+		threads.into_iter().for_each(|thread| thread.join().unwrap());
+		assert!(start.elapsed() >= Duration::from_millis(300));
+
+		// The bucket refills while the host is not requested
+		std::thread::sleep(Duration::from_millis(150));
+		let idle = Instant::now();
+		throttle_at(url, 10.0);
+		assert!(idle.elapsed() < Duration::from_millis(50));
+	}
+
+	#[test]
+	fn test_throttle_lets_hosts_proceed_in_parallel() {
+		let start = Instant::now();
+		let threads = ["https://throttle-a.example.com/", "https://throttle-b.example.com/"]
+			.map(|url| std::thread::spawn(move || (0..3).for_each(|_| throttle_at(url, 5.0))));
+		threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+		// Each host waits 0.4s, rather than both hosts 1s in turn
+		let elapsed = start.elapsed();
+		assert!(elapsed >= Duration::from_millis(400));
+		assert!(elapsed < Duration::from_millis(900), "{:?}", elapsed);
+	}
+
+	#[test]
+	fn test_resolve_user_agent() {
+		assert_eq!(resolve_user_agent(None, None), DEFAULT_USER_AGENT);
+		assert!(DEFAULT_USER_AGENT.starts_with("cite/"));
+		assert_eq!(resolve_user_agent(None, Some("ci-bot/1.0".to_string())), "ci-bot/1.0");
+		assert_eq!(
+			resolve_user_agent(Some("docs-bot/2.0"), Some("ci-bot/1.0".to_string())),
+			"docs-bot/2.0"
+		);
+		assert_eq!(resolve_user_agent(None, Some(String::new())), DEFAULT_USER_AGENT);
+	}
+}
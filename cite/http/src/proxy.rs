@@ -0,0 +1,76 @@
+//! Proxies of cited pages
+//!
+//! Requests go through the proxy reqwest reads from the standard `HTTPS_PROXY`, `HTTP_PROXY` and
+//! `ALL_PROXY` environment variables (or their lowercase forms), or through the proxy of the
+//! citation's `proxy` option, which overrides them. Hosts listed in `NO_PROXY` are always
+//! fetched directly, so that internal citations bypass the proxy. The proxy is chosen for every
+//! request, so a redirect to another host or scheme goes through the proxy of its target.
+
+use cite_core::SourceError;
+use reqwest::blocking::ClientBuilder;
+
+/// Send the requests of a client through the given proxy, except for the hosts of `NO_PROXY`,
+/// or through the proxies of the environment if none is given
+pub fn apply(builder: ClientBuilder, proxy: Option<&str>) -> Result<ClientBuilder, SourceError> {
+	match proxy {
+		Some(proxy) => reqwest::Proxy::all(proxy)
+			.map(|proxy| builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env())))
+			.map_err(|e| SourceError::Network(format!("Invalid proxy {}: {}", proxy, e))),
+		None => Ok(builder),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{BufRead, BufReader, Write};
+	use std::net::TcpListener;
+	use std::sync::{Arc, Mutex};
+
+	#[test]
+	fn test_redirects_go_through_proxy() -> Result<(), anyhow::Error> {
+		// A proxy redirecting the first page to another host, recording the requested URLs
+		let listener = TcpListener::bind("127.0.0.1:0")?;
+		let port = listener.local_addr()?.port();
+		let requested = Arc::new(Mutex::new(Vec::new()));
+		let recorded = requested.clone();
+		std::thread::spawn(move || {
+			for stream in listener.incoming().flatten() {
+				let mut reader = BufReader::new(&stream);
+				let mut request_line = String::new();
+				if reader.read_line(&mut request_line).is_err() {
+					continue;
+				}
+				let mut header = String::new();
+				while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+					header.clear();
+				}
+
+				let url = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+				let response = match url.as_str() {
+					"http://first.invalid/" => "HTTP/1.1 302 Found\r\nLocation: http://second.invalid/page\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+					_ => "HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nproxied",
+				};
+				if let Ok(mut requested) = recorded.lock() {
+					requested.push(url);
+				}
+				let _ = (&stream).write_all(response.as_bytes());
+			}
+		});
+
+		let proxy = format!("http://127.0.0.1:{}", port);
+		let client = apply(reqwest::blocking::Client::builder(), Some(&proxy))?.build()?;
+		assert_eq!(client.get("http://first.invalid/").send()?.text()?, "proxied");
+		assert_eq!(
+			*requested.lock().map_err(|_| anyhow::anyhow!("Poisoned lock"))?,
+			["http://first.invalid/", "http://second.invalid/page"]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_apply_invalid_proxy() {
+		let builder = reqwest::blocking::Client::builder();
+		assert!(apply(builder, Some("not a proxy")).is_err());
+	}
+}
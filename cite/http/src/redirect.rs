@@ -0,0 +1,141 @@
+use cite_core::SourceError;
+use serde::{Deserialize, Serialize};
+
+/// Default number of redirects followed, as many as reqwest follows by default
+pub const DEFAULT_MAX_REDIRECTS: u8 = 10;
+
+/// Whether the cited URL is fetched through redirects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectPolicy {
+	/// Follow at most this many redirects, recording the URL they end at
+	Follow(u8),
+	/// Fail on a redirect instead of following it
+	None,
+}
+
+impl Default for RedirectPolicy {
+	fn default() -> Self {
+		RedirectPolicy::Follow(DEFAULT_MAX_REDIRECTS)
+	}
+}
+
+impl RedirectPolicy {
+	/// Parse from string, e.g. `5`, `follow` or `none`
+	pub fn from_str(s: &str) -> Result<Self, String> {
+		match s.trim().to_lowercase().as_str() {
+			"follow" => Ok(RedirectPolicy::default()),
+			"none" | "0" => Ok(RedirectPolicy::None),
+			max => max.parse::<u8>().map(RedirectPolicy::Follow).map_err(|_| {
+				format!(
+					"Invalid redirect policy: '{}'. Expected the maximum number of redirects (1 to {}), 'follow' or 'none'",
+					s.trim(),
+					u8::MAX
+				)
+			}),
+		}
+	}
+
+	/// Check if this is the default policy
+	pub fn is_default(&self) -> bool {
+		*self == RedirectPolicy::default()
+	}
+
+	/// Check if redirects are followed
+	pub fn follows(&self) -> bool {
+		matches!(self, RedirectPolicy::Follow(_))
+	}
+
+	/// Get the policy of the http client
+	pub fn to_reqwest(self) -> reqwest::redirect::Policy {
+		match self {
+			RedirectPolicy::Follow(max) => reqwest::redirect::Policy::limited(max as usize),
+			RedirectPolicy::None => reqwest::redirect::Policy::none(),
+		}
+	}
+}
+
+impl std::fmt::Display for RedirectPolicy {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			RedirectPolicy::Follow(max) => write!(f, "{}", max),
+			RedirectPolicy::None => write!(f, "none"),
+		}
+	}
+}
+
+/// Error for a redirect the citation does not follow, naming where it leads
+pub fn unfollowed_redirect(url: &str, status: u16, location: Option<&str>) -> SourceError {
+	SourceError::Network(format!(
+		"HTTP request to {} was redirected with status {} to {}, but the citation does not follow redirects",
+		url,
+		status,
+		location.unwrap_or("an unknown location")
+	))
+}
+
+/// Change of the URL the redirects of the cited URL end at, e.g. when a canonical page moved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectChange {
+	/// The final URL when the reference was fetched
+	pub referenced: String,
+	/// The final URL now
+	pub current: String,
+}
+
+impl RedirectChange {
+	/// Compare the final URLs recorded for the reference and the current content
+	///
+	/// References fetched before final URLs were recorded have none, and are not compared.
+	pub fn between(referenced: Option<&String>, current: Option<&String>) -> Option<Self> {
+		match (referenced, current) {
+			(Some(referenced), Some(current)) if referenced != current => {
+				Some(Self { referenced: referenced.clone(), current: current.clone() })
+			}
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() -> Result<(), String> {
+		assert_eq!(RedirectPolicy::from_str("5")?, RedirectPolicy::Follow(5));
+		assert_eq!(RedirectPolicy::from_str(" None ")?, RedirectPolicy::None);
+		assert_eq!(RedirectPolicy::from_str("0")?, RedirectPolicy::None);
+		assert_eq!(RedirectPolicy::from_str("follow")?, RedirectPolicy::default());
+		assert!(RedirectPolicy::default().is_default());
+		assert_eq!(
+			RedirectPolicy::from_str(&RedirectPolicy::Follow(3).to_string())?.to_string(),
+			"3"
+		);
+
+		for invalid in ["", "-1", "256", "always"] {
+			assert!(RedirectPolicy::from_str(invalid).is_err(), "{} should be invalid", invalid);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_unfollowed_redirect() {
+		let error = unfollowed_redirect("https://example.com/old", 301, Some("/new"));
+		assert!(
+			matches!(error, SourceError::Network(message) if message.contains("status 301 to /new"))
+		);
+	}
+
+	#[test]
+	fn test_redirect_change() {
+		let canonical = "https://example.com/docs/v2/".to_string();
+		let moved = "https://example.com/docs/v3/".to_string();
+		assert_eq!(RedirectChange::between(Some(&canonical), Some(&canonical)), None);
+		assert_eq!(RedirectChange::between(None, Some(&moved)), None);
+		assert_eq!(
+			RedirectChange::between(Some(&canonical), Some(&moved)),
+			Some(RedirectChange { referenced: canonical, current: moved })
+		);
+	}
+}
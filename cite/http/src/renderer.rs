@@ -0,0 +1,142 @@
+//! Rendering of pages that build their content with JavaScript
+//!
+//! A plain fetch of a client-side rendered page returns the shell its scripts fill in, which
+//! holds nothing worth citing. Citations with `render = true` are fetched through a [Renderer]
+//! instead, and the rendered HTML goes through the same extraction as a fetched page.
+//!
+//! A renderer is given the URL of a page and the headers of its request, and returns the HTML of
+//! the page once its scripts ran, i.e. the serialized DOM, or an error if the page cannot be
+//! rendered. Unless a source is given its own renderer, pages are rendered by the command in
+//! `CITE_RENDER_COMMAND`, e.g. a headless browser dumping the DOM:
+//!
+//! ```bash
+//! export CITE_RENDER_COMMAND="chromium --headless --dump-dom {url}"
+//! ```
+//!
+//! robots.txt is always fetched as is.
+
+use cite_core::SourceError;
+use std::process::Command;
+
+/// Environment variable holding the command pages are rendered with
+pub const RENDER_COMMAND_ENV: &str = "CITE_RENDER_COMMAND";
+
+/// Environment variable passing the request headers to the render command
+pub const RENDER_HEADERS_ENV: &str = "CITE_RENDER_HEADERS";
+
+/// A fetcher returning pages as rendered by a browser, after their scripts ran
+pub trait Renderer: Send + Sync {
+	/// Render the page at the URL, sending the headers with its request, and return its HTML
+	fn render(&self, url: &str, headers: &[(String, String)]) -> Result<String, SourceError>;
+}
+
+/// Renders pages by running an external command, which prints the rendered HTML to stdout
+///
+/// The command is split on whitespace. An argument of `{url}` is replaced by the URL of the page,
+/// which is appended otherwise. The headers are passed as `Name: value` lines in
+/// `CITE_RENDER_HEADERS`, for commands that send them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRenderer {
+	command: String,
+}
+
+impl CommandRenderer {
+	/// Create a renderer running the given command
+	pub fn new(command: &str) -> Self {
+		Self { command: command.to_string() }
+	}
+
+	/// Create the renderer configured by `CITE_RENDER_COMMAND`, if any
+	pub fn from_env() -> Option<Self> {
+		std::env::var(RENDER_COMMAND_ENV)
+			.ok()
+			.filter(|command| !command.trim().is_empty())
+			.map(|command| Self::new(&command))
+	}
+
+	/// Get the arguments of the command rendering the URL, starting with the program
+	pub fn args(&self, url: &str) -> Vec<String> {
+		let mut args = self.command.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+		if args.iter().any(|arg| arg.contains("{url}")) {
+			args.iter_mut().for_each(|arg| *arg = arg.replace("{url}", url));
+		} else {
+			args.push(url.to_string());
+		}
+		args
+	}
+}
+
+impl Renderer for CommandRenderer {
+	fn render(&self, url: &str, headers: &[(String, String)]) -> Result<String, SourceError> {
+		let args = self.args(url);
+		let (program, args) = args.split_first().ok_or_else(|| {
+			SourceError::Network(format!("{} is an empty command", RENDER_COMMAND_ENV))
+		})?;
+		let headers = headers
+			.iter()
+			.map(|(name, value)| format!("{}: {}", name, value))
+			.collect::<Vec<_>>();
+
+		let output = Command::new(program)
+			.args(args)
+			.env(RENDER_HEADERS_ENV, headers.join("\n"))
+			.output()
+			.map_err(|e| {
+				SourceError::Network(format!("Failed to run render command {}: {}", program, e))
+			})?;
+		if !output.status.success() {
+			return Err(SourceError::Network(format!(
+				"Render command {} failed for {} with {}: {}",
+				program,
+				url,
+				output.status,
+				String::from_utf8_lossy(&output.stderr).trim()
+			)));
+		}
+
+		String::from_utf8(output.stdout).map_err(|_| {
+			SourceError::ContentParsing(format!("Rendered page {} is not valid UTF-8", url))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_command_args() {
+		let renderer = CommandRenderer::new("chromium --headless --dump-dom {url}");
+		assert_eq!(
+			renderer.args("https://example.com/app"),
+			["chromium", "--headless", "--dump-dom", "https://example.com/app"]
+		);
+
+		// Without a placeholder, the URL is the last argument
+		let renderer = CommandRenderer::new("  render-page --wait 500 ");
+		assert_eq!(
+			renderer.args("https://example.com/app"),
+			["render-page", "--wait", "500", "https://example.com/app"]
+		);
+		assert_eq!(
+			CommandRenderer::new("render --url={url}").args("https://example.com"),
+			["render", "--url=https://example.com"]
+		);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_command_render() -> Result<(), SourceError> {
+		// A stand-in for a browser, printing the page and the headers it was given
+		let renderer = CommandRenderer::new("sh -c {url}");
+		let html = renderer.render(
+			r#"echo "<main>Rendered</main>"; echo "$CITE_RENDER_HEADERS""#,
+			&[("Accept-Language".to_string(), "de".to_string())],
+		)?;
+		assert_eq!(html, "<main>Rendered</main>\nAccept-Language: de\n");
+
+		assert!(CommandRenderer::new("sh -c {url}").render("exit 3", &[]).is_err());
+		assert!(CommandRenderer::new("cite-missing-renderer").render("page", &[]).is_err());
+		Ok(())
+	}
+}
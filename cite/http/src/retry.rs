@@ -0,0 +1,180 @@
+//! Retries of transient request failures with exponential backoff
+//!
+//! CI runners see connection resets and gateway errors that are gone a moment later. Citations
+//! opting in with `retries` try again after a delay that doubles with each retry, with jitter so
+//! that the citations of a build do not retry in lockstep.
+
+use cite_core::SourceError;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Default timeout of a request, in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Delay before the first retry, doubled for each further retry
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest delay between two attempts, before jitter
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Why an attempt failed
+#[derive(Debug)]
+pub enum Failure {
+	/// A failure that may be gone on the next attempt, e.g. a reset connection or a 503
+	Transient(SourceError),
+	/// A failure that retrying cannot fix, e.g. an invalid URL
+	Fatal(SourceError),
+}
+
+impl Failure {
+	/// Classify an error sending a request; connection failures and timeouts are transient,
+	/// while e.g. an invalid URL or request fails alike on every attempt
+	pub fn from_send_error(error: reqwest::Error) -> Self {
+		let transient = error.is_connect() || error.is_timeout();
+		let error = SourceError::Network(format!("HTTP request failed: {}", error));
+		if transient {
+			Failure::Transient(error)
+		} else {
+			Failure::Fatal(error)
+		}
+	}
+
+	/// Get the error of the failure, whether transient or not
+	pub fn into_error(self) -> SourceError {
+		match self {
+			Failure::Transient(error) | Failure::Fatal(error) => error,
+		}
+	}
+}
+
+/// Check if a status is a transient gateway failure (502, 503 or 504)
+pub fn is_transient_status(status: u16) -> bool {
+	matches!(status, 502..=504)
+}
+
+/// Get the delay before the given retry, counting from 0: the base delay doubled for each
+/// earlier retry, up to the maximum, plus up to half of it again as jitter
+pub fn backoff_delay(retry: u32) -> Duration {
+	let delay = BASE_DELAY.saturating_mul(2u32.saturating_pow(retry)).min(MAX_DELAY);
+	let jitter_range = delay.as_millis() as u64 / 2 + 1;
+	let jitter = std::collections::hash_map::RandomState::new().build_hasher().finish();
+	delay + Duration::from_millis(jitter % jitter_range)
+}
+
+/// Run an attempt until it succeeds, fails fatally or has been retried `retries` times,
+/// sleeping with the given function before each retry
+///
+/// The attempt is given the number of retries left, so that its last try can treat a transient
+/// failure like any other. Exhausting the retries returns the error of the last attempt.
+pub fn retry_with_backoff<T, F, S>(
+	retries: u32,
+	mut attempt: F,
+	mut sleep: S,
+) -> Result<T, SourceError>
+where
+	F: FnMut(u32) -> Result<T, Failure>,
+	S: FnMut(Duration),
+{
+	let mut retry = 0;
+	loop {
+		match attempt(retries - retry) {
+			Ok(value) => return Ok(value),
+			Err(Failure::Fatal(error)) => return Err(error),
+			Err(Failure::Transient(error)) if retry == retries => return Err(error),
+			Err(Failure::Transient(_error)) => {
+				cite_core::trace_event!(retry, error = %_error, "retrying");
+				sleep(backoff_delay(retry));
+				retry += 1;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn reset() -> Failure {
+		Failure::Transient(SourceError::Network("connection reset by peer".to_string()))
+	}
+
+	#[test]
+	fn test_two_failures_then_success() -> Result<(), SourceError> {
+		let mut attempts = Vec::new();
+		let mut delays = Vec::new();
+		let fetched = retry_with_backoff(
+			3,
+			|retries_left| {
+				attempts.push(retries_left);
+				match attempts.len() {
+					1 | 2 => Err(reset()),
+					_ => Ok("content"),
+				}
+			},
+			|delay| delays.push(delay),
+		)?;
+
+		// The success short-circuits the remaining retry
+		assert_eq!(fetched, "content");
+		assert_eq!(attempts, [3, 2, 1]);
+		assert_eq!(delays.len(), 2);
+		assert!(delays[0] >= BASE_DELAY && delays[0] <= BASE_DELAY * 3 / 2);
+		assert!(delays[1] >= BASE_DELAY * 2 && delays[1] <= BASE_DELAY * 3);
+		Ok(())
+	}
+
+	#[test]
+	fn test_exhausted_and_fatal_failures() {
+		let mut attempts = 0;
+		let result: Result<(), _> = retry_with_backoff(
+			2,
+			|_| {
+				attempts += 1;
+				Err(Failure::Transient(SourceError::Network(format!("attempt {}", attempts))))
+			},
+			|_| {},
+		);
+		assert!(matches!(result, Err(SourceError::Network(message)) if message == "attempt 3"));
+
+		// Fatal failures and the default of no retries fail on the first attempt
+		let mut attempts = 0;
+		let result: Result<(), _> = retry_with_backoff(
+			2,
+			|_| {
+				attempts += 1;
+				Err(Failure::Fatal(SourceError::Network("invalid URL".to_string())))
+			},
+			|_| {},
+		);
+		assert!(result.is_err());
+		assert_eq!(attempts, 1);
+
+		let result: Result<(), _> =
+			retry_with_backoff(0, |_| Err(reset()), |_| panic!("no retries were configured"));
+		assert!(result.is_err());
+	}
+
+	#[test]
+	fn test_send_errors() {
+		// An invalid URL is a builder error, which no retry fixes
+		let error = reqwest::blocking::Client::new().get("http://[::1").send().unwrap_err();
+		assert!(matches!(Failure::from_send_error(error), Failure::Fatal(_)));
+
+		// Nothing listens on a port that was just released, so connecting fails
+		let port = std::net::TcpListener::bind("127.0.0.1:0")
+			.and_then(|listener| listener.local_addr())
+			.map(|address| address.port())
+			.unwrap();
+		let client = reqwest::blocking::Client::builder().no_proxy().build().unwrap();
+		let error = client.get(format!("http://127.0.0.1:{}/", port)).send().unwrap_err();
+		assert!(matches!(Failure::from_send_error(error), Failure::Transient(_)));
+	}
+
+	#[test]
+	fn test_backoff_delay() {
+		assert!(is_transient_status(503));
+		assert!(!is_transient_status(500));
+		assert!(backoff_delay(2) >= BASE_DELAY * 4);
+		assert!(backoff_delay(30) <= MAX_DELAY * 3 / 2);
+	}
+}
@@ -0,0 +1,67 @@
+//! Signed object-store URLs
+//!
+//! Presigned S3 and GCS URLs grant access to an object through query parameters that carry an
+//! expiring signature, so a citation of such a URL gets a new one on every build. With
+//! `signed = true`, the signature parameters are left out of the cache key and of the URL the
+//! content is compared by, while the object is still fetched with the full signed URL.
+
+/// Check if a query parameter is part of an S3 or GCS signature, e.g. `X-Amz-Signature`
+pub fn is_signature_param(name: &str) -> bool {
+	let name = name.to_ascii_lowercase();
+	name.starts_with("x-amz-")
+		|| name.starts_with("x-goog-")
+		|| matches!(name.as_str(), "signature" | "expires" | "awsaccesskeyid" | "googleaccessid")
+}
+
+/// Remove the signature parameters from the query of a URL, keeping its other parameters and
+/// fragment
+pub fn strip_signature(url: &str) -> String {
+	let (url, fragment) = match url.split_once('#') {
+		Some((url, fragment)) => (url, Some(fragment)),
+		None => (url, None),
+	};
+	let mut stripped = match url.split_once('?') {
+		Some((base, query)) => {
+			let query = query
+				.split('&')
+				.filter(|pair| {
+					let name = pair.split_once('=').map_or(*pair, |(name, _)| name);
+					!pair.is_empty() && !is_signature_param(name)
+				})
+				.collect::<Vec<_>>();
+			if query.is_empty() {
+				base.to_string()
+			} else {
+				format!("{}?{}", base, query.join("&"))
+			}
+		}
+		None => url.to_string(),
+	};
+	if let Some(fragment) = fragment {
+		stripped.push('#');
+		stripped.push_str(fragment);
+	}
+	stripped
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_strip_signature() {
+		let s3 = "https://bucket.s3.amazonaws.com/docs/spec.txt?X-Amz-Algorithm=AWS4-HMAC-SHA256\
+			&X-Amz-Credential=AKIA%2F20261015&X-Amz-Date=20261015T093000Z&X-Amz-Expires=3600\
+			&X-Amz-SignedHeaders=host&X-Amz-Signature=4f2a";
+		assert_eq!(strip_signature(s3), "https://bucket.s3.amazonaws.com/docs/spec.txt");
+
+		// Other parameters and the fragment are kept
+		let gcs = "https://storage.googleapis.com/bucket/spec.html?GoogleAccessId=ci%40example.com\
+			&Expires=1760520600&Signature=c2ln&generation=3#usage";
+		assert_eq!(
+			strip_signature(gcs),
+			"https://storage.googleapis.com/bucket/spec.html?generation=3#usage"
+		);
+		assert_eq!(strip_signature("https://example.com/docs"), "https://example.com/docs");
+	}
+}
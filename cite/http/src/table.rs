@@ -0,0 +1,157 @@
+use cite_core::SourceError;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// Identifies a row or column of a table
+///
+/// Rows are named by the text of their first cell and columns by the text of their header
+/// cell. Indices are zero-based and count data rows only, i.e. the header row is skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TableKey {
+	/// Zero-based position
+	Index(usize),
+	/// Header name (columns) or first-cell text (rows)
+	Name(String),
+}
+
+impl TableKey {
+	/// Parse from string; numeric strings are indices
+	pub fn from_str(s: &str) -> Self {
+		match s.trim().parse() {
+			Ok(index) => TableKey::Index(index),
+			Err(_) => TableKey::Name(s.trim().to_string()),
+		}
+	}
+}
+
+impl std::fmt::Display for TableKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TableKey::Index(index) => write!(f, "{}", index),
+			TableKey::Name(name) => write!(f, "{}", name),
+		}
+	}
+}
+
+/// Extract the text of a single table cell
+///
+/// The header row is the first row made up of `<th>` cells; tables without one have no
+/// column names, only indices. A table, row or column that cannot be found yields an empty
+/// string, like a CSS selector without matches.
+pub fn extract_table_cell(
+	content: &str,
+	table_selector: &str,
+	row: &TableKey,
+	column: &TableKey,
+) -> Result<String, SourceError> {
+	let document = Html::parse_document(content);
+	let selector = Selector::parse(table_selector).map_err(|e| {
+		SourceError::ContentParsing(format!("Invalid CSS selector '{}': {:?}", table_selector, e))
+	})?;
+	let row_selector = Selector::parse("tr")
+		.map_err(|e| SourceError::ContentParsing(format!("Invalid CSS selector 'tr': {:?}", e)))?;
+	let cell_selector = Selector::parse("td, th").map_err(|e| {
+		SourceError::ContentParsing(format!("Invalid CSS selector 'td, th': {:?}", e))
+	})?;
+
+	let table = match document.select(&selector).next() {
+		Some(table) => table,
+		None => return Ok(String::new()),
+	};
+
+	let rows = table
+		.select(&row_selector)
+		.map(|row| row.select(&cell_selector).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+
+	let header_index = rows.iter().position(|cells| {
+		!cells.is_empty() && cells.iter().all(|cell| cell.value().name() == "th")
+	});
+	let headers = header_index.map(|index| rows[index].as_slice()).unwrap_or_default();
+	let data_rows = rows
+		.iter()
+		.enumerate()
+		.filter(|(index, _)| Some(*index) != header_index)
+		.map(|(_, cells)| cells)
+		.collect::<Vec<_>>();
+
+	let cells = match row {
+		TableKey::Index(index) => data_rows.get(*index).copied(),
+		TableKey::Name(name) => data_rows
+			.iter()
+			.find(|cells| cells.first().is_some_and(|cell| cell_text(cell) == *name))
+			.copied(),
+	};
+	let column_index = match column {
+		TableKey::Index(index) => Some(*index),
+		TableKey::Name(name) => headers.iter().position(|cell| cell_text(cell) == *name),
+	};
+
+	Ok(cells
+		.zip(column_index)
+		.and_then(|(cells, column_index)| cells.get(column_index))
+		.map(cell_text)
+		.unwrap_or_default())
+}
+
+/// Get the text of a cell, in the style of CSS selector matches
+fn cell_text(cell: &ElementRef) -> String {
+	cell.text().collect::<Vec<_>>().join(" ").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TABLE: &str = r#"
+		<table id="options">
+			<thead><tr><th>Option</th><th>Default value</th><th>Description</th></tr></thead>
+			<tbody>
+				<tr><td>timeout</td><td>30s</td><td>Request timeout</td></tr>
+				<tr><td>retries</td><td><code>3</code></td><td>Retry count</td></tr>
+			</tbody>
+		</table>
+	"#;
+
+	fn cell(row: TableKey, column: TableKey) -> String {
+		extract_table_cell(TABLE, "#options", &row, &column).unwrap()
+	}
+
+	#[test]
+	fn test_cell_by_header_name() {
+		assert_eq!(cell(TableKey::from_str("retries"), TableKey::from_str("Default value")), "3");
+		assert_eq!(
+			cell(TableKey::from_str("timeout"), TableKey::from_str("Description")),
+			"Request timeout"
+		);
+	}
+
+	#[test]
+	fn test_cell_by_index() {
+		assert_eq!(cell(TableKey::Index(0), TableKey::Index(1)), "30s");
+		assert_eq!(cell(TableKey::from_str("1"), TableKey::from_str("0")), "retries");
+	}
+
+	#[test]
+	fn test_missing_cell_is_empty() {
+		assert_eq!(cell(TableKey::from_str("verbose"), TableKey::Index(1)), "");
+		assert_eq!(cell(TableKey::Index(0), TableKey::from_str("Type")), "");
+		assert_eq!(cell(TableKey::Index(5), TableKey::Index(0)), "");
+		assert_eq!(
+			extract_table_cell(TABLE, "#missing", &TableKey::Index(0), &TableKey::Index(0))
+				.unwrap(),
+			""
+		);
+		assert!(extract_table_cell(TABLE, "[[", &TableKey::Index(0), &TableKey::Index(0)).is_err());
+	}
+
+	#[test]
+	fn test_table_without_header_row() {
+		let table = "<table><tr><td>a</td><td>b</td></tr><tr><td>c</td><td>d</td></tr></table>";
+		assert_eq!(
+			extract_table_cell(table, "table", &TableKey::Index(1), &TableKey::Index(1)).unwrap(),
+			"d"
+		);
+	}
+}
@@ -1,5 +1,10 @@
-use crate::{CurrentHttp, HttpDiff, HttpMatch, MatchExpression, ReferencedHttp};
-use cite_core::ui::{AboveDocAttr, SourceUi, SourceUiError};
+use crate::{
+	ContentConstraint, CurrentHttp, Decoding, ExpectedLength, ExpectedNumber, HttpDiff, HttpMatch,
+	JsonFields, MatchExpression, NormalizeMode, Normalizer, Pagination, PdfPages, RedirectPolicy,
+	ReferencedHttp, TableKey,
+};
+use cite_core::ui::{parse_any_of, parse_min_similarity, AboveDocAttr, SourceUi, SourceUiError};
+use cite_core::{DiffGranularity, DiffSensitivity, TextEncoding};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
@@ -49,7 +54,16 @@ impl SourceUi<ReferencedHttp, CurrentHttp, HttpDiff> for HttpMatch {
 			// Direct serde fields
 			"matches" | "source_url" | "cache_path" | "id" | "cache" | "cache_behavior" |
 			// Legacy ergonomic fields
-			"url" | "match" | "pattern" | "selector" | "match_type" | "fragment" |
+			"url" | "match" | "pattern" | "selector" | "match_type" | "fragment" | "sensitivity" |
+			"between_start" | "between_end" | "table_selector" | "row" | "column" | "json_path" |
+			"jsonpath" | "meta" |
+			"sha256" | "digest" | "manifest" | "max_bytes" | "any_of" | "paginate" | "max_pages" |
+			"include_fields" | "exclude_fields" | "cookie_env" | "ignore_moves" | "combine" |
+			"template" | "min_similarity" | "accept_language" | "expect_status" | "user_agent" |
+			"respect_robots" | "strict_match" | "decode" | "encoding" | "expect_num" | "expect_len" |
+			"proxy" | "must_match" | "must_contain" | "schema" | "require_anchor" |
+			"normalize" | "normalize_mode" | "render" | "signed" | "replace" | "headers" | "ref_url" | "header" |
+			"redirects" | "accept_status" | "timeout_secs" | "retries" | "diff_granularity" |
 			// Citation-level fields
 			"src" | "reason" | "level" | "annotation" => true,
 			_ => false,
@@ -58,6 +72,71 @@ impl SourceUi<ReferencedHttp, CurrentHttp, HttpDiff> for HttpMatch {
 }
 
 impl HttpMatch {
+	/// Create a GraphQL schema citation from kwargs, e.g.
+	/// `url = "https://api.example.com/graphql", type = "User", field = "email"`
+	pub fn from_graphql_kwarg_json(kwargs: &HashMap<String, Value>) -> Result<Self, SourceUiError> {
+		let string = |key: &str| match kwargs.get(key) {
+			Some(Value::String(s)) if !s.trim().is_empty() => Ok(Some(s.trim())),
+			Some(value) => Err(SourceUiError::InvalidParameter(format!(
+				"{} must be a non-empty string, got {}",
+				key, value
+			))),
+			None => Ok(None),
+		};
+
+		let url =
+			string("url")?.ok_or_else(|| SourceUiError::MissingParameter("url".to_string()))?;
+		let type_name =
+			string("type")?.ok_or_else(|| SourceUiError::MissingParameter("type".to_string()))?;
+		let field = string("field")?;
+
+		HttpMatch::graphql(url, type_name, field).map_err(|e| SourceUiError::Internal(e.into()))
+	}
+
+	/// Check if a kwarg key is valid for a GraphQL schema citation
+	pub fn is_valid_graphql_attr_key(attr_key: &str) -> bool {
+		matches!(attr_key, "url" | "type" | "field" | "src" | "reason" | "level" | "annotation")
+	}
+
+	/// Create a citation of the text of a PDF document from kwargs, e.g.
+	/// `url = "https://example.com/spec.pdf", pages = "3-5", match = "Each message.*"`
+	///
+	/// The match is a regular expression over the extracted text, the whole text being cited
+	/// without one.
+	pub fn from_pdf_kwarg_json(kwargs: &HashMap<String, Value>) -> Result<Self, SourceUiError> {
+		let string = |key: &str| match kwargs.get(key) {
+			Some(Value::String(s)) if !s.trim().is_empty() => Ok(Some(s.trim())),
+			Some(value) => Err(SourceUiError::InvalidParameter(format!(
+				"{} must be a non-empty string, got {}",
+				key, value
+			))),
+			None => Ok(None),
+		};
+
+		let url =
+			string("url")?.ok_or_else(|| SourceUiError::MissingParameter("url".to_string()))?;
+		let pages = string("pages")?
+			.map(PdfPages::from_str)
+			.transpose()
+			.map_err(SourceUiError::InvalidParameter)?
+			.unwrap_or_default();
+		let expression = match string("match")? {
+			None | Some("full") => MatchExpression::full_document(),
+			Some(pattern) => {
+				MatchExpression::regex(pattern.strip_prefix("regex:").unwrap_or(pattern))
+			}
+		};
+
+		HttpMatch::with_match_expression(url, expression)
+			.map(|source| source.with_pdf(pages))
+			.map_err(|e| SourceUiError::Internal(e.into()))
+	}
+
+	/// Check if a kwarg key is valid for a PDF citation
+	pub fn is_valid_pdf_attr_key(attr_key: &str) -> bool {
+		matches!(attr_key, "url" | "pages" | "match" | "src" | "reason" | "level" | "annotation")
+	}
+
 	/// Try to deserialize HttpMatch directly from kwargs using serde
 	fn try_direct_deserialization(kwargs: &HashMap<String, Value>) -> Result<Self, SourceUiError> {
 		// Convert HashMap to JSON and try to deserialize
@@ -84,22 +163,9 @@ impl HttpMatch {
 		// Extract optional match expression
 		let match_expr = if let Some(match_value) = kwargs.get("match") {
 			match match_value {
-				Value::String(s) => {
-					if s.starts_with("regex:") {
-						MatchExpression::regex(&s[6..])
-					} else if s.starts_with("css:") {
-						MatchExpression::css_selector(&s[4..])
-					} else if s.starts_with("xpath:") {
-						MatchExpression::xpath(&s[6..])
-					} else if s.starts_with("fragment:") {
-						MatchExpression::fragment(&s[9..])
-					} else if s == "full" {
-						MatchExpression::full_document()
-					} else {
-						// Default to CSS selector if no prefix
-						MatchExpression::css_selector(s)
-					}
-				}
+				Value::String(s) => Self::prefixed_expression(s),
+				// A tuple of alternatives, the first that matches being used
+				Value::Array(alternatives) => Self::first_of_expression(alternatives)?,
 				Value::Object(obj) => {
 					// Support structured match expressions
 					if let Some(pattern) = obj.get("pattern").and_then(|v| v.as_str()) {
@@ -109,6 +175,8 @@ impl HttpMatch {
 								"css" => MatchExpression::css_selector(pattern),
 								"xpath" => MatchExpression::xpath(pattern),
 								"fragment" => MatchExpression::fragment(pattern),
+								"jsonpath" => MatchExpression::json_path(pattern),
+								"meta" => MatchExpression::meta(pattern),
 								"full" => MatchExpression::full_document(),
 								_ => {
 									return Err(SourceUiError::InvalidParameter(format!(
@@ -130,8 +198,53 @@ impl HttpMatch {
 					))
 				}
 			}
+		} else if let Some(parts) = kwargs.get("combine") {
+			Self::composite_expression(parts, kwargs.get("template"))?
+		} else if kwargs.contains_key("between_start") || kwargs.contains_key("between_end") {
+			let start = kwargs.get("between_start").and_then(|v| v.as_str());
+			let end = kwargs.get("between_end").and_then(|v| v.as_str());
+			match (start, end) {
+				(Some(start), Some(end)) => MatchExpression::between(start, end),
+				(None, _) => {
+					return Err(SourceUiError::MissingParameter("between_start".to_string()))
+				}
+				(_, None) => {
+					return Err(SourceUiError::MissingParameter("between_end".to_string()))
+				}
+			}
+		} else if let Some(table_selector) = kwargs.get("table_selector").and_then(|v| v.as_str()) {
+			let row = Self::table_key(kwargs, "row")?;
+			let column = Self::table_key(kwargs, "column")?;
+			MatchExpression::table_cell(table_selector, row, column)
+		} else if let Some(path) = kwargs
+			.get("json_path")
+			.or_else(|| kwargs.get("jsonpath"))
+			.and_then(|v| v.as_str())
+		{
+			MatchExpression::json_path(path)
+		} else if let Some(key) = kwargs.get("meta").and_then(|v| v.as_str()) {
+			MatchExpression::meta(key)
+		} else if let Some(pattern) = kwargs.get("pattern").and_then(|v| v.as_str()) {
+			MatchExpression::regex(pattern)
+		} else if let Some(selector) = kwargs.get("selector").and_then(|v| v.as_str()) {
+			MatchExpression::css_selector(selector)
+		} else if let Some(fragment) = kwargs.get("fragment").and_then(|v| v.as_str()) {
+			MatchExpression::fragment(fragment)
 		} else {
-			MatchExpression::full_document()
+			match kwargs.get("match_type").and_then(|v| v.as_str()) {
+				Some(match_type) => Self::match_type_expression(url, match_type)?,
+				None => MatchExpression::full_document(),
+			}
+		};
+
+		if kwargs.contains_key("template") && !kwargs.contains_key("combine") {
+			return Err(SourceUiError::MissingParameter("combine".to_string()));
+		}
+
+		// Expand the groups of a regex match into a replacement template
+		let match_expr = match kwargs.get("replace") {
+			Some(template) => Self::regex_replace_expression(match_expr, template)?,
+			None => match_expr,
 		};
 
 		// Extract optional cache behavior
@@ -145,15 +258,835 @@ impl HttpMatch {
 			})
 			.unwrap_or(cite_cache::CacheBehavior::Enabled);
 
+		// Extract optional diff sensitivity
+		let sensitivity = match kwargs.get("sensitivity").and_then(|v| v.as_str()) {
+			Some(s) => DiffSensitivity::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => DiffSensitivity::default(),
+		};
+
+		// Extract optional pinned content digest
+		let digest = kwargs.get("sha256").or_else(|| kwargs.get("digest")).and_then(|v| v.as_str());
+
+		// Extract optional checksum manifest lookup
+		let manifest = match kwargs.get("manifest") {
+			Some(Value::Bool(manifest)) => *manifest,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"manifest must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional tolerance of moved blocks
+		let ignore_moves = match kwargs.get("ignore_moves") {
+			Some(Value::Bool(ignore_moves)) => *ignore_moves,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"ignore_moves must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional granularity drift is rendered in
+		let diff_granularity = match kwargs.get("diff_granularity") {
+			Some(Value::String(diff_granularity)) => DiffGranularity::from_str(diff_granularity)
+				.map_err(SourceUiError::InvalidParameter)?,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"diff_granularity must be a string, got {}",
+					value
+				)))
+			}
+			None => DiffGranularity::default(),
+		};
+
+		// Extract optional response body size cap
+		let max_bytes = match kwargs.get("max_bytes") {
+			Some(value) => value.as_u64().map(|v| v as usize).ok_or_else(|| {
+				SourceUiError::InvalidParameter(format!(
+					"max_bytes must be a positive integer, got {}",
+					value
+				))
+			})?,
+			None => crate::DEFAULT_MAX_BYTES,
+		};
+
+		// Extract optional accepted values
+		let any_of = match kwargs.get("any_of") {
+			Some(value) => parse_any_of(value)?,
+			None => Vec::new(),
+		};
+
+		// Extract optional pagination
+		let pagination = match kwargs.get("paginate").and_then(|v| v.as_str()) {
+			Some(s) => Pagination::from_str(s).map_err(SourceUiError::InvalidParameter)?,
+			None => Pagination::default(),
+		};
+		let max_pages = match kwargs.get("max_pages") {
+			Some(value) => value.as_u64().map(|v| v as usize).ok_or_else(|| {
+				SourceUiError::InvalidParameter(format!(
+					"max_pages must be a positive integer, got {}",
+					value
+				))
+			})?,
+			None => crate::DEFAULT_MAX_PAGES,
+		};
+
+		// Extract optional JSON field selection
+		let json_fields = JsonFields::new(
+			Self::json_pointers(kwargs, "include_fields")?,
+			Self::json_pointers(kwargs, "exclude_fields")?,
+		)
+		.map_err(SourceUiError::InvalidParameter)?;
+
+		// Extract optional language of the cited variant
+		let accept_language = match kwargs.get("accept_language") {
+			Some(Value::String(accept_language)) if !accept_language.trim().is_empty() => {
+				Some(accept_language.trim())
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"accept_language must be a language tag like \"en-US\", got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		// Extract optional headers sent with every request
+		let headers = match kwargs.get("headers") {
+			Some(value) => Self::parse_headers(value)?,
+			None => HashMap::new(),
+		};
+
+		// Extract optional pinned URL supplying the reference instead of the cited URL
+		let ref_url = match kwargs.get("ref_url") {
+			Some(Value::String(ref_url)) if !ref_url.trim().is_empty() => Some(ref_url.trim()),
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"ref_url must be the URL the reference is fetched from, got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		// Extract optional response header, cited instead of the body
+		let response_header = match kwargs.get("header") {
+			Some(Value::String(header)) if !header.trim().is_empty() => Some(header.trim()),
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"header must be the name of the cited response header, e.g. \"X-Api-Version\", got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		// Extract optional redirect policy of the cited URL
+		let redirects = match kwargs.get("redirects") {
+			Some(value) => Some(Self::parse_redirects(value)?),
+			None => None,
+		};
+
+		// Extract optional statuses accepted instead of any success status
+		let accept_status = match kwargs.get("accept_status") {
+			Some(value) => Self::parse_accept_status(value)?,
+			None => Vec::new(),
+		};
+
+		// Extract optional request timeout and retries of transient failures
+		let timeout_secs = match kwargs.get("timeout_secs") {
+			Some(value) => value.as_u64().filter(|secs| *secs > 0).ok_or_else(|| {
+				SourceUiError::InvalidParameter(format!(
+					"timeout_secs must be a positive integer, got {}",
+					value
+				))
+			})?,
+			None => crate::DEFAULT_TIMEOUT_SECS,
+		};
+		let retries = match kwargs.get("retries") {
+			Some(value) => {
+				value.as_u64().and_then(|retries| u32::try_from(retries).ok()).ok_or_else(|| {
+					SourceUiError::InvalidParameter(format!(
+						"retries must be a non-negative integer, got {}",
+						value
+					))
+				})?
+			}
+			None => 0,
+		};
+
+		// Extract optional expected status code, cited instead of the content
+		let expect_status = match kwargs.get("expect_status") {
+			Some(value) => Some(Self::parse_status(value)?),
+			None => None,
+		};
+
+		// Extract optional expected number with its tolerance, compared instead of the cache
+		let expect_num = match kwargs.get("expect_num") {
+			Some(value) => Some(Self::parse_expect_num(value)?),
+			None => None,
+		};
+
+		// Extract optional expected length in words or characters, counted instead of the cache
+		let expect_len = match kwargs.get("expect_len") {
+			Some(value) => Some(Self::parse_expect_len(value)?),
+			None => None,
+		};
+
+		// Extract optional constraint on the content, checked instead of the cache
+		let constraint = Self::parse_constraint(kwargs)?;
+
+		// Extract optional anchor the page must keep, checked instead of the cache
+		let require_anchor = match kwargs.get("require_anchor") {
+			Some(Value::String(anchor)) if !anchor.trim_start_matches('#').trim().is_empty() => {
+				Some(anchor.trim())
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"require_anchor must be a non-empty fragment id, got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		// Extract optional user agent, overriding the default
+		let user_agent = match kwargs.get("user_agent") {
+			Some(Value::String(user_agent)) if !user_agent.trim().is_empty() => {
+				Some(user_agent.trim())
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"user_agent must be a non-empty string, got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		// Extract optional proxy, overriding those of the environment
+		let proxy = match kwargs.get("proxy") {
+			Some(Value::String(proxy)) if !proxy.trim().is_empty() => Some(proxy.trim()),
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"proxy must be a non-empty proxy URL, got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		// Extract optional politeness towards the robots.txt of the host
+		let respect_robots = match kwargs.get("respect_robots") {
+			Some(Value::Bool(respect_robots)) => *respect_robots,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"respect_robots must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional rendering of pages that build their content with JavaScript
+		let render = match kwargs.get("render") {
+			Some(Value::Bool(render)) => *render,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"render must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional stripping of the signature of a presigned object-store URL
+		let signed = match kwargs.get("signed") {
+			Some(Value::Bool(signed)) => *signed,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"signed must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional rejection of ambiguous match expressions
+		let strict_match = match kwargs.get("strict_match") {
+			Some(Value::Bool(strict_match)) => *strict_match,
+			Some(Value::String(strict_match))
+				if matches!(strict_match.as_str(), "true" | "false") =>
+			{
+				strict_match == "true"
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"strict_match must be a boolean, got {}",
+					value
+				)))
+			}
+			None => false,
+		};
+
+		// Extract optional decoding of the extracted content
+		let decoding = match kwargs.get("decode") {
+			Some(Value::String(decoding)) => {
+				Decoding::from_str(decoding).map_err(SourceUiError::InvalidParameter)?
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"decode must be a string, got {}",
+					value
+				)))
+			}
+			None => Decoding::default(),
+		};
+
+		// Extract optional character encoding of fetched bodies
+		let encoding = match kwargs.get("encoding") {
+			Some(Value::String(encoding)) => {
+				TextEncoding::from_str(encoding).map_err(SourceUiError::InvalidParameter)?
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"encoding must be a string, got {}",
+					value
+				)))
+			}
+			None => TextEncoding::default(),
+		};
+
+		// Extract optional normalizers of the extracted content, applied in order
+		let normalizers = match kwargs.get("normalize") {
+			Some(Value::String(normalizer)) => vec![normalizer.clone()],
+			Some(Value::Array(normalizers)) => normalizers
+				.iter()
+				.map(|normalizer| normalizer.as_str().map(str::to_string))
+				.collect::<Option<Vec<_>>>()
+				.ok_or_else(|| {
+					SourceUiError::InvalidParameter(
+						"normalize must only contain strings".to_string(),
+					)
+				})?,
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"normalize must be a normalizer or a tuple of normalizers, got {}",
+					value
+				)))
+			}
+			None => Vec::new(),
+		}
+		.iter()
+		.map(|normalizer| Normalizer::from_str(normalizer))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(SourceUiError::InvalidParameter)?;
+
+		// Extract optional whitespace normalization, applied after the normalizers
+		let normalize_mode = match kwargs.get("normalize_mode") {
+			Some(Value::String(mode)) => {
+				NormalizeMode::from_str(mode).map_err(SourceUiError::InvalidParameter)?
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"normalize_mode must be a string, e.g. \"trim_lines\", got {}",
+					value
+				)))
+			}
+			None => NormalizeMode::default(),
+		};
+
+		// Extract optional similarity bound for accepting small wording changes
+		let min_similarity = kwargs.get("min_similarity").map(parse_min_similarity).transpose()?;
+
+		// Extract optional session cookie, by the name of the variable holding it
+		let cookie_env = match kwargs.get("cookie_env") {
+			Some(Value::String(cookie_env)) => Some(cookie_env.as_str()),
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"cookie_env must be the name of an environment variable, got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
 		// Create the HttpMatch
-		HttpMatch::with_match_expression_and_cache_behavior(url, match_expr, cache_behavior)
-			.map_err(|e| SourceUiError::Internal(e.into()))
+		let source =
+			HttpMatch::with_match_expression_and_cache_behavior(url, match_expr, cache_behavior)
+				.map_err(|e| SourceUiError::Internal(e.into()))?
+				.with_sensitivity(sensitivity)
+				.with_ignore_moves(ignore_moves)
+				.with_diff_granularity(diff_granularity)
+				.with_max_bytes(max_bytes)
+				.with_any_of(any_of)
+				.with_pagination(pagination, max_pages)
+				.with_json_fields(json_fields)
+				.with_respect_robots(respect_robots)
+				.with_render(render)
+				.with_signed(signed)
+				.with_strict_match(strict_match)
+				.with_decoding(decoding)
+				.with_encoding(encoding)
+				.with_normalizers(normalizers)
+				.with_normalize_mode(normalize_mode)
+				.with_request_headers(headers)
+				.with_accept_status(accept_status)
+				.with_timeout_secs(timeout_secs)
+				.with_retries(retries);
+
+		let source = if manifest { source.with_manifest() } else { source };
+		let source = match redirects {
+			Some(redirects) => source.with_redirects(redirects),
+			None => source,
+		};
+		let source = match cookie_env {
+			Some(cookie_env) => source.with_cookie_env(cookie_env),
+			None => source,
+		};
+		let source = match ref_url {
+			Some(ref_url) => source
+				.with_ref_url(ref_url)
+				.map_err(|e| SourceUiError::InvalidParameter(e.to_string()))?,
+			None => source,
+		};
+		let source = match response_header {
+			Some(response_header) => source
+				.with_response_header(response_header)
+				.map_err(|e| SourceUiError::InvalidParameter(e.to_string()))?,
+			None => source,
+		};
+		let source = match accept_language {
+			Some(accept_language) => source.with_accept_language(accept_language),
+			None => source,
+		};
+		let source = match expect_status {
+			Some(expect_status) => source.with_expect_status(expect_status),
+			None => source,
+		};
+		let source = match expect_num {
+			Some(expect_num) => source.with_expect_num(expect_num),
+			None => source,
+		};
+		let source = match expect_len {
+			Some(expect_len) => source.with_expect_len(expect_len),
+			None => source,
+		};
+		let source = match constraint {
+			Some(constraint) => source.with_constraint(constraint),
+			None => source,
+		};
+		let source = match require_anchor {
+			Some(anchor) => source.with_require_anchor(anchor),
+			None => source,
+		};
+		let source = match user_agent {
+			Some(user_agent) => source.with_user_agent(user_agent),
+			None => source,
+		};
+		let source = match proxy {
+			Some(proxy) => source.with_proxy(proxy),
+			None => source,
+		};
+		let source = match min_similarity {
+			Some(min_similarity) => source.with_min_similarity(min_similarity),
+			None => source,
+		};
+
+		Ok(match digest {
+			Some(digest) => source.with_digest(digest),
+			None => source,
+		})
+	}
+
+	/// Parse a match expression given as a prefixed string, e.g. `css:h1` or `regex:v\d+`
+	fn prefixed_expression(s: &str) -> MatchExpression {
+		if let Some(pattern) = s.strip_prefix("regex:") {
+			MatchExpression::regex(pattern)
+		} else if let Some(selector) = s.strip_prefix("css:") {
+			MatchExpression::css_selector(selector)
+		} else if let Some(xpath) = s.strip_prefix("xpath:") {
+			MatchExpression::xpath(xpath)
+		} else if let Some(fragment) = s.strip_prefix("fragment:") {
+			MatchExpression::fragment(fragment)
+		} else if let Some(path) = s.strip_prefix("jsonpath:") {
+			MatchExpression::json_path(path)
+		} else if let Some(key) = s.strip_prefix("meta:") {
+			MatchExpression::meta(key)
+		} else if s == "full" {
+			MatchExpression::full_document()
+		} else {
+			// Default to CSS selector if no prefix
+			MatchExpression::css_selector(s)
+		}
+	}
+
+	/// Turn a regex match expression into one expanding its groups into the `replace` template
+	fn regex_replace_expression(
+		expression: MatchExpression,
+		template: &Value,
+	) -> Result<MatchExpression, SourceUiError> {
+		let template = template.as_str().ok_or_else(|| {
+			SourceUiError::InvalidParameter(format!(
+				"replace must be a template of capture groups like \"$1.$2.$3\", got {}",
+				template
+			))
+		})?;
+		match expression {
+			MatchExpression::Regex(pattern) => {
+				Ok(MatchExpression::regex_replace(&pattern, template))
+			}
+			expression => Err(SourceUiError::InvalidParameter(format!(
+				"replace expands the groups of a regex match, but the match is {}",
+				expression
+			))),
+		}
+	}
+
+	/// Extract a fallback chain from a tuple of prefixed match expressions
+	fn first_of_expression(alternatives: &[Value]) -> Result<MatchExpression, SourceUiError> {
+		let invalid = || {
+			SourceUiError::InvalidParameter(
+				"match must be a match expression or a tuple of alternatives, e.g. (\"css:#version\", \"css:.version\")"
+					.to_string(),
+			)
+		};
+
+		if alternatives.is_empty() {
+			return Err(invalid());
+		}
+		let alternatives = alternatives
+			.iter()
+			.map(|alternative| {
+				alternative.as_str().map(Self::prefixed_expression).ok_or_else(invalid)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(MatchExpression::first_of(alternatives))
+	}
+
+	/// Extract a composite match expression from a tuple of prefixed match expressions
+	fn composite_expression(
+		parts: &Value,
+		template: Option<&Value>,
+	) -> Result<MatchExpression, SourceUiError> {
+		let invalid = || {
+			SourceUiError::InvalidParameter(
+				"combine must be a tuple of match expressions, e.g. (\"css:h1\", \"css:time\")"
+					.to_string(),
+			)
+		};
+
+		let parts = match parts {
+			Value::Array(parts) if !parts.is_empty() => parts
+				.iter()
+				.map(|part| part.as_str().map(Self::prefixed_expression).ok_or_else(invalid))
+				.collect::<Result<Vec<_>, _>>()?,
+			_ => return Err(invalid()),
+		};
+
+		let template = match template {
+			Some(Value::String(template)) => {
+				MatchExpression::validate_template(template, parts.len())
+					.map_err(SourceUiError::InvalidParameter)?;
+				Some(template.as_str())
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"template must be a string, got {}",
+					value
+				)))
+			}
+			None => None,
+		};
+
+		Ok(MatchExpression::composite(parts, template))
+	}
+
+	/// Extract optional JSON pointers, given as a single pointer or a tuple of pointers
+	fn json_pointers(
+		kwargs: &HashMap<String, Value>,
+		key: &str,
+	) -> Result<Vec<String>, SourceUiError> {
+		let invalid = || {
+			SourceUiError::InvalidParameter(format!(
+				"{} must be a JSON pointer or a tuple of JSON pointers",
+				key
+			))
+		};
+
+		match kwargs.get(key) {
+			None => Ok(Vec::new()),
+			Some(Value::String(pointer)) => Ok(vec![pointer.clone()]),
+			Some(Value::Array(pointers)) => pointers
+				.iter()
+				.map(|pointer| pointer.as_str().map(str::to_string).ok_or_else(invalid))
+				.collect(),
+			Some(_) => Err(invalid()),
+		}
+	}
+
+	/// Parse an HTTP status code, given as a string or a number
+	fn parse_status(value: &Value) -> Result<u16, SourceUiError> {
+		let status = match value {
+			Value::String(s) => s.trim().parse::<u16>().ok(),
+			value => value.as_u64().and_then(|status| u16::try_from(status).ok()),
+		};
+
+		status.filter(|status| (100..=599).contains(status)).ok_or_else(|| {
+			SourceUiError::InvalidParameter(format!(
+				"expect_status must be an HTTP status code between 100 and 599, got {}",
+				value
+			))
+		})
+	}
+
+	/// Parse the accepted statuses, given as `"200,301,404"`, a tuple of codes or a single code
+	fn parse_accept_status(value: &Value) -> Result<Vec<u16>, SourceUiError> {
+		let invalid = || {
+			SourceUiError::InvalidParameter(format!(
+				"accept_status must be HTTP status codes between 100 and 599 like \"200,301,404\", got {}",
+				value
+			))
+		};
+		let status = |status: &str| {
+			status.trim().parse::<u16>().ok().filter(|status| (100..=599).contains(status))
+		};
+
+		let statuses = match value {
+			Value::String(s) => s.split(',').map(status).collect::<Option<Vec<_>>>(),
+			Value::Array(values) => values
+				.iter()
+				.map(|value| match value {
+					Value::String(s) => status(s),
+					value => status(&value.to_string()),
+				})
+				.collect(),
+			Value::Number(code) => status(&code.to_string()).map(|status| vec![status]),
+			_ => None,
+		};
+		statuses.filter(|statuses| !statuses.is_empty()).ok_or_else(invalid)
+	}
+
+	/// Parse the redirect policy, given as the maximum number of redirects or `"none"`
+	fn parse_redirects(value: &Value) -> Result<RedirectPolicy, SourceUiError> {
+		match value {
+			Value::String(s) => {
+				RedirectPolicy::from_str(s).map_err(SourceUiError::InvalidParameter)
+			}
+			Value::Number(max) => {
+				RedirectPolicy::from_str(&max.to_string()).map_err(SourceUiError::InvalidParameter)
+			}
+			value => Err(SourceUiError::InvalidParameter(format!(
+				"redirects must be the maximum number of redirects like \"5\", or \"none\", got {}",
+				value
+			))),
+		}
+	}
+
+	/// Parse headers given as `"Accept: application/json; X-Api-Key: ..."` or as a tuple of
+	/// `"Name: value"` strings
+	///
+	/// In the string form, a `;` only separates headers when a header name follows it, so that
+	/// values like `text/html; charset=utf-8` stay whole.
+	fn parse_headers(value: &Value) -> Result<HashMap<String, String>, SourceUiError> {
+		let invalid = || {
+			SourceUiError::InvalidParameter(format!(
+				"headers must be \"Name: value\" pairs like \"Accept: application/json; X-Api-Key: ...\", got {}",
+				value
+			))
+		};
+		let starts_header = |segment: &str| {
+			segment.split_once(':').is_some_and(|(name, _)| {
+				let name = name.trim();
+				!name.is_empty()
+					&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+			})
+		};
+
+		let lines = match value {
+			Value::String(s) => {
+				let mut lines: Vec<String> = Vec::new();
+				for segment in s.split(';') {
+					match lines.last_mut() {
+						Some(line) if !starts_header(segment) => {
+							line.push(';');
+							line.push_str(segment);
+						}
+						_ => lines.push(segment.to_string()),
+					}
+				}
+				lines
+			}
+			Value::Array(lines) => lines
+				.iter()
+				.map(|line| line.as_str().map(str::to_string))
+				.collect::<Option<Vec<_>>>()
+				.ok_or_else(invalid)?,
+			_ => return Err(invalid()),
+		};
+
+		lines
+			.iter()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| {
+				if !starts_header(line) {
+					return Err(invalid());
+				}
+				let (name, value) = line.split_once(':').ok_or_else(invalid)?;
+				Ok((name.trim().to_string(), value.trim().to_string()))
+			})
+			.collect()
+	}
+
+	/// Parse an expected number with an optional tolerance, given as a string or a number
+	fn parse_expect_num(value: &Value) -> Result<ExpectedNumber, SourceUiError> {
+		match value {
+			Value::String(s) => {
+				ExpectedNumber::from_str(s).map_err(SourceUiError::InvalidParameter)
+			}
+			Value::Number(n) => {
+				ExpectedNumber::from_str(&n.to_string()).map_err(SourceUiError::InvalidParameter)
+			}
+			value => Err(SourceUiError::InvalidParameter(format!(
+				"expect_num must be a number with an optional tolerance like \"100±10\", got {}",
+				value
+			))),
+		}
+	}
+
+	/// Parse an expected length range with an optional unit, e.g. `"200..800 words"`
+	fn parse_expect_len(value: &Value) -> Result<ExpectedLength, SourceUiError> {
+		match value {
+			Value::String(s) => {
+				ExpectedLength::from_str(s).map_err(SourceUiError::InvalidParameter)
+			}
+			value => Err(SourceUiError::InvalidParameter(format!(
+				"expect_len must be a range of words or chars like \"200..800\", got {}",
+				value
+			))),
+		}
+	}
+
+	/// Parse the constraint given as one of `must_match`, `must_contain` or `schema`
+	///
+	/// `must_contain` is a substring or a tuple of substrings, and `schema` a JSON Schema given
+	/// as its JSON text.
+	fn parse_constraint(
+		kwargs: &HashMap<String, Value>,
+	) -> Result<Option<ContentConstraint>, SourceUiError> {
+		let mut constraints = Vec::new();
+		match kwargs.get("must_match") {
+			Some(Value::String(pattern)) => {
+				constraints.push(ContentConstraint::MustMatch(pattern.clone()));
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"must_match must be a regular expression, got {}",
+					value
+				)))
+			}
+			None => {}
+		}
+		match kwargs.get("must_contain") {
+			Some(Value::String(substring)) => {
+				constraints.push(ContentConstraint::MustContain(vec![substring.clone()]));
+			}
+			Some(Value::Array(substrings)) if !substrings.is_empty() => {
+				let substrings = substrings
+					.iter()
+					.map(|substring| substring.as_str().map(str::to_string))
+					.collect::<Option<Vec<_>>>()
+					.ok_or_else(|| {
+						SourceUiError::InvalidParameter(
+							"must_contain must only contain strings".to_string(),
+						)
+					})?;
+				constraints.push(ContentConstraint::MustContain(substrings));
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"must_contain must be a substring or a tuple of substrings, got {}",
+					value
+				)))
+			}
+			None => {}
+		}
+		match kwargs.get("schema") {
+			Some(Value::String(schema)) => constraints.push(
+				ContentConstraint::json_schema(schema).map_err(SourceUiError::InvalidParameter)?,
+			),
+			Some(schema @ Value::Object(_)) => {
+				constraints.push(ContentConstraint::JsonSchema(schema.clone()));
+			}
+			Some(value) => {
+				return Err(SourceUiError::InvalidParameter(format!(
+					"schema must be a JSON Schema, got {}",
+					value
+				)))
+			}
+			None => {}
+		}
+
+		if constraints.len() > 1 {
+			return Err(SourceUiError::InvalidParameter(
+				"only one of must_match, must_contain and schema can be given".to_string(),
+			));
+		}
+		Ok(constraints.pop())
+	}
+
+	/// Extract a required table row or column, given as a name or a zero-based index
+	fn table_key(kwargs: &HashMap<String, Value>, key: &str) -> Result<TableKey, SourceUiError> {
+		match kwargs.get(key) {
+			Some(Value::String(s)) => Ok(TableKey::from_str(s)),
+			Some(value) => {
+				value.as_u64().map(|index| TableKey::Index(index as usize)).ok_or_else(|| {
+					SourceUiError::InvalidParameter(format!(
+						"{} must be a name or a non-negative index, got {}",
+						key, value
+					))
+				})
+			}
+			None => Err(SourceUiError::MissingParameter(key.to_string())),
+		}
+	}
+
+	/// Map the `match_type` kwarg to a match expression
+	///
+	/// - `full`: the full document
+	/// - `auto`: the URL fragment if present, otherwise the full document
+	fn match_type_expression(
+		url: &str,
+		match_type: &str,
+	) -> Result<MatchExpression, SourceUiError> {
+		match match_type {
+			"full" => Ok(MatchExpression::full_document()),
+			"auto" => {
+				let source_url =
+					crate::SourceUrl::new(url).map_err(|e| SourceUiError::Internal(e.into()))?;
+				Ok(match source_url.fragment() {
+					Some(fragment) => MatchExpression::fragment(fragment),
+					None => MatchExpression::full_document(),
+				})
+			}
+			_ => Err(SourceUiError::InvalidParameter(format!(
+				"Invalid match_type: '{}'. Valid values: full, auto",
+				match_type
+			))),
+		}
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::LengthUnit;
 	use serde_json::json;
 
 	#[test]
@@ -295,22 +1228,154 @@ mod tests {
 	}
 
 	#[test]
-	fn test_from_kwarg_json_missing_match_pattern() {
+	fn test_from_kwarg_json_match_type_full() {
 		let mut kwargs = HashMap::new();
-		kwargs.insert("url".to_string(), json!("https://example.com"));
-		kwargs.insert(
-			"match".to_string(),
-			json!({
-				"type": "regex"
-			}),
+		kwargs.insert("url".to_string(), json!("https://example.com#installation"));
+		kwargs.insert("match_type".to_string(), json!("full"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(matches!(http_match.matches, MatchExpression::FullDocument));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_match_type_auto() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com#installation"));
+		kwargs.insert("match_type".to_string(), json!("auto"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(
+			matches!(http_match.matches, MatchExpression::Fragment(ref f) if f == "installation")
 		);
 
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(matches!(http_match.matches, MatchExpression::FullDocument));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_match_type_invalid() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("match_type".to_string(), json!("partial"));
+
 		let result = HttpMatch::from_kwarg_json(&kwargs);
-		assert!(result.is_err());
-		if let Err(SourceUiError::MissingParameter(msg)) = result {
-			assert_eq!(msg, "match.pattern");
+		if let Err(SourceUiError::InvalidParameter(msg)) = result {
+			assert_eq!(msg, "Invalid match_type: 'partial'. Valid values: full, auto");
 		} else {
-			panic!("Expected MissingParameter error for missing pattern");
+			panic!("Expected InvalidParameter error for invalid match_type");
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_between_markers() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("between_start".to_string(), json!("<!-- BEGIN -->"));
+		kwargs.insert("between_end".to_string(), json!("<!-- END -->"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.matches, MatchExpression::between("<!-- BEGIN -->", "<!-- END -->"));
+
+		kwargs.remove("between_end");
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(
+			matches!(result, Err(SourceUiError::MissingParameter(ref p)) if p == "between_end")
+		);
+	}
+
+	#[test]
+	fn test_from_kwarg_json_table_cell() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("table_selector".to_string(), json!("#options"));
+		kwargs.insert("row".to_string(), json!("timeout"));
+		kwargs.insert("column".to_string(), json!(1));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.matches,
+			MatchExpression::table_cell(
+				"#options",
+				TableKey::Name("timeout".to_string()),
+				TableKey::Index(1)
+			)
+		);
+
+		kwargs.remove("column");
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::MissingParameter(ref p)) if p == "column"));
+
+		kwargs.insert("column".to_string(), json!(-1));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_json_path() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/releases.json"));
+		kwargs.insert("json_path".to_string(), json!("$[?(@.name=='stable')].version"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.matches,
+			MatchExpression::json_path("$[?(@.name=='stable')].version")
+		);
+
+		kwargs.remove("json_path");
+		kwargs.insert("jsonpath".to_string(), json!("$.data.version"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.matches, MatchExpression::json_path("$.data.version"));
+
+		kwargs.remove("jsonpath");
+		kwargs.insert("match".to_string(), json!("jsonpath:$.version"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.matches, MatchExpression::json_path("$.version"));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_meta() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("meta".to_string(), json!("og:title"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.matches, MatchExpression::meta("og:title"));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_pattern_and_selector() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("pattern".to_string(), json!("title"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(matches!(http_match.matches, MatchExpression::Regex(ref p) if p == "title"));
+
+		kwargs.remove("pattern");
+		kwargs.insert("selector".to_string(), json!("h1"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(matches!(http_match.matches, MatchExpression::CssSelector(ref s) if s == "h1"));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_missing_match_pattern() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert(
+			"match".to_string(),
+			json!({
+				"type": "regex"
+			}),
+		);
+
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(result.is_err());
+		if let Err(SourceUiError::MissingParameter(msg)) = result {
+			assert_eq!(msg, "match.pattern");
+		} else {
+			panic!("Expected MissingParameter error for missing pattern");
 		}
 	}
 
@@ -563,6 +1628,799 @@ mod tests {
 		assert!(matches!(http_match.matches, MatchExpression::Regex(_)));
 	}
 
+	#[test]
+	fn test_from_kwarg_json_with_sensitivity() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("sensitivity".to_string(), json!("removals"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.sensitivity(), DiffSensitivity::Removals);
+
+		kwargs.insert("sensitivity".to_string(), json!("sideways"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_digest() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("match".to_string(), json!("css:h1"));
+		kwargs.insert("sha256".to_string(), json!("abc123"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.digest(), Some("abc123"));
+
+		kwargs.remove("sha256");
+		kwargs.insert("digest".to_string(), json!(""));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.digest(), Some(""));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_any_of() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("match".to_string(), json!("css:h1"));
+		kwargs.insert("any_of".to_string(), json!(["Hello", "Hi"]));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.any_of(), ["Hello", "Hi"]);
+
+		kwargs.insert("any_of".to_string(), json!("Hello"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_json_fields() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/api/releases"));
+		kwargs
+			.insert("include_fields".to_string(), json!(["/release/version", "/release/channel"]));
+		kwargs.insert("exclude_fields".to_string(), json!("/release/channel/updated_at"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.json_fields().include, ["/release/version", "/release/channel"]);
+		assert_eq!(http_match.json_fields().exclude, ["/release/channel/updated_at"]);
+		assert!(HttpMatch::is_valid_attr_key("include_fields"));
+		assert!(HttpMatch::is_valid_attr_key("exclude_fields"));
+
+		kwargs.insert("exclude_fields".to_string(), json!("timestamp"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+
+		kwargs.insert("exclude_fields".to_string(), json!(3));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_ignore_moves() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/guide"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+
+		assert!(!HttpMatch::from_kwarg_json(&kwargs).unwrap().ignores_moves());
+
+		kwargs.insert("ignore_moves".to_string(), json!(true));
+		assert!(HttpMatch::from_kwarg_json(&kwargs).unwrap().ignores_moves());
+		assert!(HttpMatch::is_valid_attr_key("ignore_moves"));
+
+		kwargs.insert("ignore_moves".to_string(), json!("yes"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_diff_granularity() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/guide"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+
+		let source = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(source.diff_granularity(), DiffGranularity::Lines);
+
+		kwargs.insert("diff_granularity".to_string(), json!("word"));
+		let source = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(source.diff_granularity(), DiffGranularity::Words);
+		assert!(HttpMatch::is_valid_attr_key("diff_granularity"));
+
+		kwargs.insert("diff_granularity".to_string(), json!("sentence"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_combine() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/releases"));
+		kwargs.insert("combine".to_string(), json!(["css:span.version", "regex:\\d{4}-\\d{2}"]));
+		kwargs.insert("template".to_string(), json!("{0} ({1})"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.matches.to_string(),
+			"combine:(css:span.version, regex:\\d{4}-\\d{2}) as \"{0} ({1})\""
+		);
+		assert!(HttpMatch::is_valid_attr_key("combine"));
+		assert!(HttpMatch::is_valid_attr_key("template"));
+
+		// Placeholders must refer to one of the combined expressions
+		kwargs.insert("template".to_string(), json!("{0} ({2})"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+
+		kwargs.insert("combine".to_string(), json!("css:span.version"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+
+		// A template without expressions to combine is an error
+		kwargs.remove("combine");
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::MissingParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_min_similarity() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/guide"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+
+		assert_eq!(HttpMatch::from_kwarg_json(&kwargs).unwrap().min_similarity(), None);
+
+		kwargs.insert("min_similarity".to_string(), json!("0.95"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.min_similarity().map(|bound| bound.bound()), Some(0.95));
+		assert!(HttpMatch::is_valid_attr_key("min_similarity"));
+
+		kwargs.insert("min_similarity".to_string(), json!("1.5"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_accept_language() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://docs.example.com/guide"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+		kwargs.insert("accept_language".to_string(), json!("en-US"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.accept_language(), Some("en-US"));
+		assert!(HttpMatch::is_valid_attr_key("accept_language"));
+
+		kwargs.insert("accept_language".to_string(), json!(""));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_expect_status() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/changelog"));
+		kwargs.insert("expect_status".to_string(), json!("200"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.expect_status(), Some(200));
+		assert!(HttpMatch::is_valid_attr_key("expect_status"));
+
+		kwargs.insert("expect_status".to_string(), json!(404));
+		assert_eq!(HttpMatch::from_kwarg_json(&kwargs).unwrap().expect_status(), Some(404));
+
+		for invalid in [json!("ok"), json!(99), json!(600), json!(true)] {
+			kwargs.insert("expect_status".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_graphql_kwarg_json() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://api.example.com/graphql"));
+		kwargs.insert("type".to_string(), json!("User"));
+		kwargs.insert("field".to_string(), json!("email"));
+
+		let http_match = HttpMatch::from_graphql_kwarg_json(&kwargs).unwrap();
+		assert!(http_match.is_graphql());
+		assert_eq!(http_match.matches, MatchExpression::graphql_schema("User", Some("email")));
+		assert!(http_match.request_body().is_some_and(|body| body.contains("__schema")));
+		assert!(HttpMatch::is_valid_graphql_attr_key("field"));
+		assert!(!HttpMatch::is_valid_graphql_attr_key("selector"));
+
+		// The whole type is cited without a field
+		kwargs.remove("field");
+		let http_match = HttpMatch::from_graphql_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.matches, MatchExpression::graphql_schema("User", None));
+
+		kwargs.remove("type");
+		let result = HttpMatch::from_graphql_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::MissingParameter(_))));
+		kwargs.insert("type".to_string(), json!(1));
+		let result = HttpMatch::from_graphql_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_pdf_kwarg_json() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/spec.pdf"));
+		kwargs.insert("pages".to_string(), json!("3-5"));
+		kwargs.insert("match".to_string(), json!("Each message.*"));
+
+		let http_match = HttpMatch::from_pdf_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.pdf(), Some(PdfPages::Range { first: 3, last: 5 }));
+		assert_eq!(http_match.matches, MatchExpression::regex("Each message.*"));
+		assert!(HttpMatch::is_valid_pdf_attr_key("pages"));
+		assert!(!HttpMatch::is_valid_pdf_attr_key("selector"));
+
+		// The whole text of every page is cited by default
+		kwargs.remove("pages");
+		kwargs.remove("match");
+		let http_match = HttpMatch::from_pdf_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.pdf(), Some(PdfPages::All));
+		assert_eq!(http_match.matches, MatchExpression::full_document());
+
+		kwargs.insert("pages".to_string(), json!("5-3"));
+		let result = HttpMatch::from_pdf_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_normalize() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/terms"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+		kwargs.insert("normalize".to_string(), json!(["entities", r"redact:\d+", "whitespace"]));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.normalizers(),
+			&[Normalizer::Entities, Normalizer::Redact(r"\d+".to_string()), Normalizer::Whitespace]
+		);
+
+		kwargs.insert("normalize".to_string(), json!("case"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.normalizers(), &[Normalizer::Case]);
+
+		kwargs.insert("normalize".to_string(), json!("stemming"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_normalize_mode() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/guide"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.normalize_mode(), NormalizeMode::None);
+
+		kwargs.insert("normalize_mode".to_string(), json!("trim_lines"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.normalize_mode(), NormalizeMode::TrimLines);
+
+		kwargs.insert("normalize_mode".to_string(), json!("reflow"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_require_anchor() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs#installation"));
+		kwargs.insert("require_anchor".to_string(), json!("#installation"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.require_anchor(), Some("installation"));
+		assert!(HttpMatch::is_valid_attr_key("require_anchor"));
+
+		kwargs.insert("require_anchor".to_string(), json!("#"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_constraint() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/version"));
+		kwargs.insert("match".to_string(), json!("css:#version"));
+		kwargs.insert("must_match".to_string(), json!(r"^v\d+"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.constraint(),
+			Some(&ContentConstraint::MustMatch(r"^v\d+".to_string()))
+		);
+		assert!(HttpMatch::is_valid_attr_key("must_match"));
+		assert!(HttpMatch::is_valid_attr_key("schema"));
+
+		kwargs.remove("must_match");
+		kwargs.insert("must_contain".to_string(), json!(["MUST", "SHOULD"]));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.constraint(),
+			Some(&ContentConstraint::MustContain(vec!["MUST".to_string(), "SHOULD".to_string()]))
+		);
+
+		kwargs.remove("must_contain");
+		kwargs.insert("schema".to_string(), json!(r#"{"type": "object", "required": ["name"]}"#));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(matches!(http_match.constraint(), Some(ContentConstraint::JsonSchema(_))));
+
+		// Only one constraint can be given, and it must be well formed
+		kwargs.insert("must_contain".to_string(), json!("name"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		kwargs.remove("must_contain");
+		kwargs.insert("schema".to_string(), json!("{"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_expect_num() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/status"));
+		kwargs.insert("match".to_string(), json!("p95 latency: [0-9]+ms"));
+		kwargs.insert("expect_num".to_string(), json!("100 ~ 10%"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.expect_num().map(|expected| expected.range()), Some((90.0, 110.0)));
+		assert!(HttpMatch::is_valid_attr_key("expect_num"));
+
+		kwargs.insert("expect_num".to_string(), json!(42));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.expect_num().map(|expected| expected.value), Some(42.0));
+
+		for invalid in [json!("fast"), json!("100±ten"), json!(true)] {
+			kwargs.insert("expect_num".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_ref_url() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs/main/api.md"));
+		kwargs.insert("ref_url".to_string(), json!("https://example.com/docs/v1.0/api.md"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.ref_url().map(|ref_url| ref_url.as_str()),
+			Some("https://example.com/docs/v1.0/api.md")
+		);
+		assert!(HttpMatch::is_valid_attr_key("ref_url"));
+
+		for invalid in [json!("ftp://example.com/api.md"), json!(""), json!(true)] {
+			kwargs.insert("ref_url".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_accept_status() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs/removed"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(http_match.accept_status().is_empty());
+
+		for accept_status in
+			[json!("200,301,404"), json!(" 404, 200 ,301"), json!([200, "301", 404])]
+		{
+			kwargs.insert("accept_status".to_string(), accept_status);
+			let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+			assert_eq!(
+				http_match.accept_status().iter().copied().collect::<Vec<_>>(),
+				[200, 301, 404]
+			);
+		}
+		kwargs.insert("accept_status".to_string(), json!(410));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(http_match.accept_status().contains(&410));
+		assert!(HttpMatch::is_valid_attr_key("accept_status"));
+
+		for invalid in [json!(""), json!("200,abc"), json!("600"), json!([]), json!(true)] {
+			kwargs.insert("accept_status".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_timeout_and_retries() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs/flaky"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.timeout_secs(), crate::DEFAULT_TIMEOUT_SECS);
+		assert_eq!(http_match.retries(), 0);
+
+		kwargs.insert("timeout_secs".to_string(), json!(5));
+		kwargs.insert("retries".to_string(), json!(3));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.timeout_secs(), 5);
+		assert_eq!(http_match.retries(), 3);
+		assert!(HttpMatch::is_valid_attr_key("timeout_secs"));
+		assert!(HttpMatch::is_valid_attr_key("retries"));
+
+		for (key, invalid) in [
+			("timeout_secs", json!(0)),
+			("timeout_secs", json!("5")),
+			("retries", json!(-1)),
+			("retries", json!(1.5)),
+		] {
+			let mut kwargs = kwargs.clone();
+			kwargs.insert(key.to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_redirects() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs/latest"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.redirects(), RedirectPolicy::default());
+
+		for (redirects, expected) in [
+			(json!("5"), RedirectPolicy::Follow(5)),
+			(json!(3), RedirectPolicy::Follow(3)),
+			(json!("none"), RedirectPolicy::None),
+		] {
+			kwargs.insert("redirects".to_string(), redirects);
+			let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+			assert_eq!(http_match.redirects(), expected);
+		}
+		assert!(HttpMatch::is_valid_attr_key("redirects"));
+
+		for invalid in [json!("always"), json!(-1), json!(1000), json!(true)] {
+			kwargs.insert("redirects".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_header() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://api.example.com/v2/releases"));
+		kwargs.insert("header".to_string(), json!("X-Api-Version"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.response_header(), Some("x-api-version"));
+		assert!(http_match.cache_path.ends_with("_header_x-api-version"));
+		assert!(HttpMatch::is_valid_attr_key("header"));
+
+		for invalid in [json!("X Api Version"), json!(""), json!(1)] {
+			kwargs.insert("header".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_headers() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://api.example.com/releases"));
+		kwargs.insert(
+			"headers".to_string(),
+			json!("Accept: application/json; charset=utf-8; X-Api-Key: abc123"),
+		);
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.headers().get("Accept").map(String::as_str),
+			Some("application/json; charset=utf-8")
+		);
+		assert_eq!(http_match.headers().get("X-Api-Key").map(String::as_str), Some("abc123"));
+		assert!(HttpMatch::is_valid_attr_key("headers"));
+
+		kwargs.insert("headers".to_string(), json!(["Accept: text/plain"]));
+		let tuple = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(tuple.headers().len(), 1);
+		assert_ne!(tuple.id, http_match.id);
+
+		for invalid in [json!("application/json"), json!([": value"]), json!(42)] {
+			kwargs.insert("headers".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_replace() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/releases"));
+		kwargs.insert("pattern".to_string(), json!(r"v(\d+)_(\d+)_(\d+)"));
+		kwargs.insert("replace".to_string(), json!("$1.$2.$3"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.matches,
+			MatchExpression::regex_replace(r"v(\d+)_(\d+)_(\d+)", "$1.$2.$3")
+		);
+		assert!(HttpMatch::is_valid_attr_key("replace"));
+
+		kwargs.remove("pattern");
+		kwargs.insert("match".to_string(), json!(r"regex:v(\d+)_(\d+)"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(matches!(http_match.matches, MatchExpression::RegexReplace { .. }));
+
+		// Only regex matches have groups to expand
+		kwargs.insert("match".to_string(), json!("css:h1"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_expect_len() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs"));
+		kwargs.insert("match".to_string(), json!("css:#installation"));
+		kwargs.insert("expect_len".to_string(), json!("200..800"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match
+				.expect_len()
+				.map(|expected| (expected.min, expected.max, expected.unit)),
+			Some((Some(200), Some(800), LengthUnit::Words))
+		);
+		assert!(HttpMatch::is_valid_attr_key("expect_len"));
+
+		kwargs.insert("expect_len".to_string(), json!("1000.. chars"));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.expect_len().map(|expected| expected.unit), Some(LengthUnit::Chars));
+
+		for invalid in [json!("800..200"), json!("200..800 lines"), json!(500)] {
+			kwargs.insert("expect_len".to_string(), invalid);
+			let result = HttpMatch::from_kwarg_json(&kwargs);
+			assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+		}
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_user_agent_and_respect_robots() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs"));
+		kwargs.insert("user_agent".to_string(), json!("docs-bot/2.0"));
+		kwargs.insert("respect_robots".to_string(), json!(true));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.user_agent(), "docs-bot/2.0");
+		assert!(http_match.respects_robots());
+		assert!(HttpMatch::is_valid_attr_key("user_agent"));
+		assert!(HttpMatch::is_valid_attr_key("respect_robots"));
+
+		kwargs.insert("respect_robots".to_string(), json!("yes"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+
+		kwargs.remove("respect_robots");
+		kwargs.insert("user_agent".to_string(), json!(" "));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_render() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/app"));
+		let served = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(!served.renders());
+
+		kwargs.insert("render".to_string(), json!(true));
+		let rendered = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(rendered.renders());
+		assert_ne!(rendered.cache_path, served.cache_path);
+		assert!(HttpMatch::is_valid_attr_key("render"));
+
+		kwargs.insert("render".to_string(), json!("chromium"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_signed() {
+		let mut kwargs = HashMap::new();
+		let url = |signature: &str| {
+			format!(
+				"https://bucket.s3.amazonaws.com/spec.txt?X-Amz-Expires=3600&X-Amz-Signature={}",
+				signature
+			)
+		};
+		kwargs.insert("url".to_string(), json!(url("4f2a")));
+		kwargs.insert("signed".to_string(), json!(true));
+		let signed = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(signed.is_signed());
+		assert_eq!(signed.cited_url().as_str(), "https://bucket.s3.amazonaws.com/spec.txt");
+		assert!(HttpMatch::is_valid_attr_key("signed"));
+
+		kwargs.insert("url".to_string(), json!(url("9c1e")));
+		let resigned = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(resigned.cache_path, signed.cache_path);
+
+		kwargs.insert("signed".to_string(), json!("yes"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_proxy() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs"));
+		let direct = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(direct.proxy(), None);
+
+		kwargs.insert("proxy".to_string(), json!("http://proxy.example.com:3128"));
+		let proxied = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(proxied.proxy(), Some("http://proxy.example.com:3128"));
+		assert_eq!(proxied.cache_path, direct.cache_path);
+		assert!(HttpMatch::is_valid_attr_key("proxy"));
+
+		kwargs.insert("proxy".to_string(), json!(3128));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_strict_match() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/compat"));
+		kwargs.insert("pattern".to_string(), json!(r"MSRV: (\d+\.\d+)"));
+		assert!(!HttpMatch::from_kwarg_json(&kwargs).unwrap().strict_match());
+
+		kwargs.insert("strict_match".to_string(), json!("true"));
+		assert!(HttpMatch::from_kwarg_json(&kwargs).unwrap().strict_match());
+		kwargs.insert("strict_match".to_string(), json!(true));
+		assert!(HttpMatch::from_kwarg_json(&kwargs).unwrap().strict_match());
+		assert!(HttpMatch::is_valid_attr_key("strict_match"));
+
+		kwargs.insert("strict_match".to_string(), json!("yes"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_match_alternatives() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/releases"));
+		kwargs.insert("match".to_string(), json!(["css:#version", "css:.version", "regex:v\\d+"]));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(
+			http_match.matches,
+			MatchExpression::first_of(vec![
+				MatchExpression::css_selector("#version"),
+				MatchExpression::css_selector(".version"),
+				MatchExpression::regex("v\\d+"),
+			])
+		);
+		assert_eq!(
+			http_match.matches.to_string(),
+			"first_of:(css:#version, css:.version, regex:v\\d+)"
+		);
+
+		kwargs.insert("match".to_string(), json!([]));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+
+		kwargs.insert("match".to_string(), json!(["css:#version", 3]));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_decode() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert(
+			"url".to_string(),
+			json!("https://api.github.com/repos/owner/repo/contents/Cargo.toml"),
+		);
+		kwargs.insert("match".to_string(), json!("jsonpath:$.content"));
+		let encoded = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(encoded.decoding(), Decoding::None);
+
+		kwargs.insert("decode".to_string(), json!("base64"));
+		let decoded = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(decoded.decoding(), Decoding::Base64);
+		assert_ne!(decoded.cache_path, encoded.cache_path);
+		assert!(HttpMatch::is_valid_attr_key("decode"));
+
+		kwargs.insert("decode".to_string(), json!("rot13"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_encoding() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/legacy.html"));
+		kwargs.insert("match".to_string(), json!("css:h1"));
+		let detected = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(detected.encoding(), TextEncoding::Auto);
+
+		kwargs.insert("encoding".to_string(), json!("latin-1"));
+		let latin1 = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(latin1.encoding(), TextEncoding::Latin1);
+		assert_ne!(latin1.cache_path, detected.cache_path);
+		assert!(HttpMatch::is_valid_attr_key("encoding"));
+
+		kwargs.insert("encoding".to_string(), json!("ebcdic"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_cookie_env() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://docs.example.com/internal"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+		kwargs.insert("cookie_env".to_string(), json!("DOCS_SESSION"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.cookie_env(), Some("DOCS_SESSION"));
+		assert!(HttpMatch::is_valid_attr_key("cookie_env"));
+
+		kwargs.insert("cookie_env".to_string(), json!(true));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_pagination() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/docs"));
+		kwargs.insert("match".to_string(), json!("css:main"));
+		kwargs.insert("paginate".to_string(), json!("rel-next"));
+		kwargs.insert("max_pages".to_string(), json!(3));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.pagination(), Pagination::RelNext);
+		assert_eq!(http_match.max_pages(), 3);
+
+		kwargs.insert("paginate".to_string(), json!("template"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_manifest() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("match".to_string(), json!("css:h1"));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(!http_match.uses_manifest());
+
+		kwargs.insert("manifest".to_string(), json!(true));
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert!(http_match.uses_manifest());
+
+		kwargs.insert("manifest".to_string(), json!("yes"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_from_kwarg_json_with_max_bytes() {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("max_bytes".to_string(), json!(1024));
+
+		let http_match = HttpMatch::from_kwarg_json(&kwargs).unwrap();
+		assert_eq!(http_match.max_bytes(), 1024);
+
+		kwargs.insert("max_bytes".to_string(), json!("large"));
+		let result = HttpMatch::from_kwarg_json(&kwargs);
+		assert!(matches!(result, Err(SourceUiError::InvalidParameter(_))));
+	}
+
 	#[test]
 	fn test_is_valid_attr_key() {
 		// Test valid direct serde fields
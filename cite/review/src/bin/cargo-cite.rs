@@ -0,0 +1,122 @@
+//! `cargo cite check`: validate every citation of a workspace in one pass
+//!
+//! ```text
+//! cargo cite check [--fail-level <error|warn|silent>] [--cite-dir <dir>] [<path>...]
+//! ```
+//!
+//! Validates the citations recorded when the workspace was last built, or those found in the
+//! given paths, without recompiling and prints a report. Exits with a failure if any citation
+//! drifted at or above the fail level (default error), as the macro would report it, or could
+//! not be validated.
+
+use cite_cache::{Cache, CacheBuilder, CitationRegistry, CITATION_REGISTRY_DIR};
+use cite_core::CitationLevel;
+use cite_review::{check_citations, load_citations, CheckOutcome, CheckSummary};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str =
+	"Usage: cargo cite check [--fail-level <error|warn|silent>] [--cite-dir <dir>] [<path>...]";
+
+struct Options {
+	fail_level: CitationLevel,
+	cite_dir: Option<PathBuf>,
+	paths: Vec<PathBuf>,
+}
+
+impl Options {
+	fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+		// Cargo passes the name of the subcommand on to it
+		let mut args = args.peekable();
+		if args.peek().map(String::as_str) == Some("cite") {
+			args.next();
+		}
+		match args.next().as_deref() {
+			Some("check") => {}
+			_ => return Err(USAGE.to_string()),
+		}
+
+		let mut options =
+			Self { fail_level: CitationLevel::Error, cite_dir: None, paths: Vec::new() };
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--fail-level" => {
+					let level = args.next().ok_or("--fail-level requires a level")?;
+					options.fail_level = CitationLevel::from_str(&level)?;
+				}
+				"--cite-dir" => {
+					let dir = args.next().ok_or("--cite-dir requires a directory")?;
+					options.cite_dir = Some(PathBuf::from(dir));
+				}
+				"-h" | "--help" => return Err(USAGE.to_string()),
+				flag if flag.starts_with("--") => return Err(format!("Unknown flag: {}", flag)),
+				path => options.paths.push(PathBuf::from(path)),
+			}
+		}
+
+		Ok(options)
+	}
+
+	fn cache(&self) -> Result<Option<Cache>, String> {
+		self.cite_dir
+			.as_ref()
+			.map(|cite_dir| {
+				CacheBuilder::new(cite_dir.clone(), PathBuf::from("cache"))
+					.build()
+					.map_err(|e| format!("Failed to open cache in {}: {}", cite_dir.display(), e))
+			})
+			.transpose()
+	}
+
+	fn registry(&self) -> CitationRegistry {
+		let cite_dir = self.cite_dir.clone().unwrap_or_else(|| CacheBuilder::default().cite_dir);
+		CitationRegistry::new(cite_dir.join(CITATION_REGISTRY_DIR))
+	}
+}
+
+fn main() -> ExitCode {
+	let options = match Options::parse(std::env::args().skip(1)) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("{}", message);
+			return ExitCode::from(2);
+		}
+	};
+
+	let cache = match options.cache() {
+		Ok(cache) => cache,
+		Err(message) => {
+			eprintln!("{}", message);
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let citations = match load_citations(&options.paths, &options.registry()) {
+		Ok(citations) => citations,
+		Err(error) => {
+			eprintln!("{}", error);
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let outcomes = check_citations(&citations, cache.as_ref());
+	for (citation, outcome) in citations.iter().zip(&outcomes) {
+		match outcome {
+			CheckOutcome::Unchanged => {}
+			CheckOutcome::Drifted { level, diff } => {
+				println!("{}: drifted ({})", citation.location(), level.as_str());
+				println!("{}", diff.trim_end());
+			}
+			CheckOutcome::Failed(message) => {
+				println!("{}: failed: {}", citation.location(), message);
+			}
+		}
+	}
+
+	println!("{}", CheckSummary::new(&outcomes));
+	if outcomes.iter().any(|outcome| outcome.fails_at(options.fail_level)) {
+		ExitCode::FAILURE
+	} else {
+		ExitCode::SUCCESS
+	}
+}
@@ -0,0 +1,214 @@
+//! Validation of every citation of a workspace in one pass
+//!
+//! Unlike the macro, which validates each citation as its item is compiled, checking does not
+//! need a rebuild. Citations of the same source are validated once, and distinct sources are
+//! validated in parallel.
+
+use crate::{check_citation, FoundCitation};
+use cite_cache::Cache;
+use cite_core::CitationLevel;
+use cite_sources::is_citation_level_field;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// What checking a citation found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+	/// The citation has not drifted
+	Unchanged,
+	/// The content has changed; the level is how the macro reports it, silent if the macro does
+	/// not validate the citation
+	Drifted { level: CitationLevel, diff: String },
+	/// The citation could not be validated
+	Failed(String),
+}
+
+impl CheckOutcome {
+	/// Check if the outcome fails a check at the given threshold level
+	///
+	/// Citations that cannot be validated always fail.
+	pub fn fails_at(&self, threshold: CitationLevel) -> bool {
+		match self {
+			CheckOutcome::Unchanged => false,
+			CheckOutcome::Drifted { level, .. } => severity(*level) >= severity(threshold),
+			CheckOutcome::Failed(_) => true,
+		}
+	}
+}
+
+fn severity(level: CitationLevel) -> u8 {
+	match level {
+		CitationLevel::Silent => 0,
+		CitationLevel::Warn => 1,
+		CitationLevel::Error => 2,
+	}
+}
+
+/// Check each citation, returning the outcomes in the order of the citations
+pub fn check_citations(citations: &[FoundCitation], cache: Option<&Cache>) -> Vec<CheckOutcome> {
+	// Citations of the same source share one validation
+	let mut sources = Vec::<&FoundCitation>::new();
+	let mut source_indices = HashMap::new();
+	let source_of = citations
+		.iter()
+		.map(|citation| {
+			*source_indices.entry(source_key(citation)).or_insert_with(|| {
+				sources.push(citation);
+				sources.len() - 1
+			})
+		})
+		.collect::<Vec<_>>();
+
+	let diffs = Mutex::new(vec![None; sources.len()]);
+	let next = AtomicUsize::new(0);
+	let workers = std::thread::available_parallelism().map_or(1, |n| n.get()).min(sources.len());
+	std::thread::scope(|scope| {
+		for _ in 0..workers {
+			scope.spawn(|| loop {
+				let index = next.fetch_add(1, Ordering::SeqCst);
+				let Some(citation) = sources.get(index) else {
+					break;
+				};
+				let diff = check_citation(citation, cache)
+					.map(|drift| drift.map(|drift| drift.diff))
+					.map_err(|error| error.to_string());
+				if let Ok(mut diffs) = diffs.lock() {
+					diffs[index] = Some(diff);
+				}
+			});
+		}
+	});
+	let diffs = diffs.into_inner().unwrap_or_else(|error| error.into_inner());
+
+	citations
+		.iter()
+		.zip(source_of)
+		.map(|(citation, source)| match diffs[source].clone() {
+			Some(Ok(None)) => CheckOutcome::Unchanged,
+			Some(Ok(Some(diff))) => CheckOutcome::Drifted {
+				level: citation.level.unwrap_or(CitationLevel::Silent),
+				diff,
+			},
+			Some(Err(message)) => CheckOutcome::Failed(message),
+			None => CheckOutcome::Failed("Validation did not complete".to_string()),
+		})
+		.collect()
+}
+
+/// Identify the source of a citation, independently of how the citation reports drift
+fn source_key(citation: &FoundCitation) -> String {
+	let kwargs = citation
+		.kwargs
+		.iter()
+		.filter(|(key, _)| !is_citation_level_field(key))
+		.collect::<BTreeMap<_, _>>();
+	format!("{}:{}", citation.src, serde_json::to_string(&kwargs).unwrap_or_default())
+}
+
+/// Counts of the outcomes of a check
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckSummary {
+	pub unchanged: usize,
+	pub drifted: usize,
+	pub failed: usize,
+}
+
+impl CheckSummary {
+	/// Count the outcomes of a check
+	pub fn new(outcomes: &[CheckOutcome]) -> Self {
+		let mut summary = Self::default();
+		for outcome in outcomes {
+			match outcome {
+				CheckOutcome::Unchanged => summary.unchanged += 1,
+				CheckOutcome::Drifted { .. } => summary.drifted += 1,
+				CheckOutcome::Failed(_) => summary.failed += 1,
+			}
+		}
+		summary
+	}
+}
+
+impl std::fmt::Display for CheckSummary {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{} checked: {} drifted, {} failed, {} unchanged",
+			self.unchanged + self.drifted + self.failed,
+			self.drifted,
+			self.failed,
+			self.unchanged
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::scan_source;
+	use cite_core::CitationBehavior;
+	use std::path::Path;
+
+	#[test]
+	fn test_check_citations() {
+		let content = r#"
+			#[cite(mock, same = "content")]
+			fn unchanged() {}
+
+			#[cite(mock, changed = ("old", "new"), level = "WARN")]
+			fn warns() {}
+
+			#[cite(mock, changed = ("old", "new"), reason = "same source, checked once")]
+			fn fails() {}
+
+			#[cite(ftp, url = "ftp://example.com")]
+			fn invalid() {}
+		"#;
+		let citations = scan_source(Path::new("src/lib.rs"), content).unwrap();
+		assert_eq!(source_key(&citations[1]), source_key(&citations[2]));
+
+		// Levels resolve against the behavior of the build, as in the macro
+		let behavior = CitationBehavior::from_features();
+		let outcomes = check_citations(&citations, None);
+		assert_eq!(outcomes[0], CheckOutcome::Unchanged);
+		let diff = "-old\n+new\n".to_string();
+		assert_eq!(
+			outcomes[1],
+			CheckOutcome::Drifted {
+				level: behavior.effective_level(Some(CitationLevel::Warn)),
+				diff: diff.clone()
+			}
+		);
+		assert_eq!(
+			outcomes[2],
+			CheckOutcome::Drifted { level: behavior.effective_level(None), diff: diff.clone() }
+		);
+		assert!(
+			matches!(outcomes[3], CheckOutcome::Failed(ref message) if message.contains("ftp"))
+		);
+
+		// Citations the macro does not validate drift silently
+		let mut unvalidated = citations[1].clone();
+		unvalidated.level = None;
+		assert_eq!(
+			check_citations(&[unvalidated], None)[0],
+			CheckOutcome::Drifted { level: CitationLevel::Silent, diff: diff.clone() }
+		);
+
+		assert_eq!(
+			CheckSummary::new(&outcomes).to_string(),
+			"4 checked: 2 drifted, 1 failed, 1 unchanged"
+		);
+	}
+
+	#[test]
+	fn test_fails_at_threshold() {
+		let drifted = |level| CheckOutcome::Drifted { level, diff: String::new() };
+		assert!(!drifted(CitationLevel::Warn).fails_at(CitationLevel::Error));
+		assert!(drifted(CitationLevel::Warn).fails_at(CitationLevel::Warn));
+		assert!(drifted(CitationLevel::Error).fails_at(CitationLevel::Warn));
+		assert!(!drifted(CitationLevel::Silent).fails_at(CitationLevel::Warn));
+		assert!(CheckOutcome::Failed("unreachable".to_string()).fails_at(CitationLevel::Error));
+		assert!(!CheckOutcome::Unchanged.fails_at(CitationLevel::Silent));
+	}
+}
@@ -0,0 +1,355 @@
+//! Cite Review - Guided triage of drifted citations
+//!
+//! Loads the citations of a workspace from the citation registry the macro records them in
+//! (or finds the `#[cite(...)]` attributes of given paths), validates each citation the way the
+//! macro would, and lets the user accept the current content of drifted citations as their new
+//! reference, instead of deleting cache entries by hand.
+//!
+//! Accepting refreshes the source through [Source::refresh], which updates the reference where
+//! it lives outside of the citation: the cache entry of an http citation, or its digest in the
+//! checksum manifest. Citations whose reference is part of the citation itself (git revisions,
+//! pinned digests and root hashes, accepted values and mocks) are reported as needing a manual
+//! update.
+//!
+//! The `cite-review` binary drives this interactively, or accepts everything with
+//! `--accept-all`. The `cargo-cite` binary only checks, as `cargo cite check`, validating every
+//! citation of a workspace in one CI step without recompiling.
+
+pub mod check;
+pub mod scan;
+
+pub use check::{check_citations, CheckOutcome, CheckSummary};
+pub use scan::{find_citations, load_citations, recorded_citations, scan_source, FoundCitation};
+
+use cite_bundle::BundleDiff;
+use cite_cache::{Cache, CacheError};
+use cite_core::{ChangeClass, Diff, Source, SourceError};
+use cite_http::HttpDiff;
+use cite_sources::{config, AnySource, SourceKwargsError};
+use std::path::PathBuf;
+
+/// Errors thrown while reviewing citations
+#[derive(Debug, thiserror::Error)]
+pub enum ReviewError {
+	#[error("Failed to read {}: {error}", path.display())]
+	Io {
+		path: PathBuf,
+		#[source]
+		error: std::io::Error,
+	},
+
+	#[error("Failed to parse {}: {error}", path.display())]
+	Parse {
+		path: PathBuf,
+		#[source]
+		error: syn::Error,
+	},
+
+	#[error("Failed to read the citation registry: {0}")]
+	Registry(#[source] CacheError),
+
+	#[error("Invalid citation: {0}")]
+	Config(String),
+
+	#[error(transparent)]
+	Source(#[from] SourceKwargsError),
+
+	#[error("Failed to validate citation: {0}")]
+	Validation(#[source] SourceError),
+
+	#[error("Failed to accept citation: {0}")]
+	Accept(#[source] SourceError),
+
+	#[error("{0}")]
+	ManualUpdate(String),
+}
+
+/// A citation whose current content differs from its reference
+pub struct Drift {
+	/// The drifted citation
+	pub citation: FoundCitation,
+	/// The source of the citation
+	pub source: AnySource,
+	/// The rendered diff between the reference and the current content
+	pub diff: String,
+	/// The severity of the change, if the source classifies it
+	pub change: Option<ChangeClass>,
+}
+
+impl Drift {
+	/// Accept the current content as the new reference of the citation
+	pub fn accept(&self) -> Result<(), ReviewError> {
+		self.source.refresh().map_err(|error| match error {
+			SourceError::ManualUpdate(message) => ReviewError::ManualUpdate(message),
+			error => ReviewError::Accept(error),
+		})
+	}
+}
+
+/// Construct the source of a citation, using the given cache for http sources
+///
+/// Without a cache, http sources use the default `.cite/cache` of the working directory.
+/// Targets and normalizer profiles are resolved from the `cite.toml` of the crate containing the
+/// citation, as the macro does.
+pub fn citation_source(
+	citation: &FoundCitation,
+	cache: Option<&Cache>,
+) -> Result<AnySource, ReviewError> {
+	let mut kwargs = citation.kwargs.clone();
+	if kwargs.contains_key("target") || kwargs.contains_key("normalizer") {
+		let manifest_dir = config::crate_dir(&citation.path).unwrap_or_default();
+		config::resolve_target(&manifest_dir, config::active_profile().as_deref(), &mut kwargs)
+			.and_then(|()| config::resolve_normalizer(&citation.src, &manifest_dir, &mut kwargs))
+			.map_err(ReviewError::Config)?;
+	}
+	let source = AnySource::from_kwargs(&citation.src, &kwargs)?;
+	Ok(match (source, cache) {
+		(AnySource::Http(source), Some(cache)) => AnySource::Http(source.with_cache(cache.clone())),
+		(source, _) => source,
+	})
+}
+
+/// Validate a citation, returning its drift if the content has changed
+pub fn check_citation(
+	citation: &FoundCitation,
+	cache: Option<&Cache>,
+) -> Result<Option<Drift>, ReviewError> {
+	let source = citation_source(citation, cache)?;
+
+	let drift = match &source {
+		AnySource::Http(http) => {
+			let comparison = http.get().map_err(ReviewError::Validation)?;
+			let diff = comparison.diff();
+			(!comparison.is_same()).then(|| (render_http_diff(diff), diff.describe_change()))
+		}
+		AnySource::Git(git) => {
+			let comparison = git.get().map_err(ReviewError::Validation)?;
+			let diff = comparison.diff();
+			(!comparison.is_same()).then(|| (diff.diff().to_string(), diff.describe_change()))
+		}
+		AnySource::Bundle(bundle) => {
+			let comparison = bundle.get().map_err(ReviewError::Validation)?;
+			let diff = comparison.diff();
+			(!comparison.is_same()).then(|| (render_bundle_diff(diff), diff.describe_change()))
+		}
+		AnySource::Mock(mock) => {
+			let comparison = mock.get().map_err(ReviewError::Validation)?;
+			let diff = comparison.diff();
+			diff.has_changes
+				.then(|| (format!("-{}\n+{}\n", diff.referenced, diff.current), None))
+		}
+	};
+
+	Ok(drift.map(|(diff, change)| Drift { citation: citation.clone(), source, diff, change }))
+}
+
+fn render_bundle_diff(diff: &BundleDiff) -> String {
+	let changes = diff.changes.iter().flatten().map(|change| format!(" {}\n", change));
+	format!(
+		"-sha256 {}\n+sha256 {}\n{}",
+		diff.expected,
+		diff.current,
+		changes.collect::<Vec<_>>().concat()
+	)
+}
+
+fn render_http_diff(diff: &HttpDiff) -> String {
+	if let Some(pinned) = &diff.pinned_digest {
+		return format!("-digest {}\n+digest {}\n", pinned.expected, pinned.current);
+	}
+	if let Some(check) = &diff.number_check {
+		return format!("-number {}\n+number {}\n", check.expected, check.current);
+	}
+	if let Some(check) = &diff.length_check {
+		return format!(
+			"-length {}\n+length {} {}\n",
+			check.expected, check.current, check.expected.unit
+		);
+	}
+	if let Some(check) = &diff.constraint_check {
+		return format!("-{}\n+{}\n", check.constraint, check.violations.join("\n+"));
+	}
+	if let Some(check) = &diff.anchor_check {
+		return format!("-#{}\n", check.anchor);
+	}
+
+	let redirect = diff
+		.redirect_change
+		.as_ref()
+		.map(|change| format!("-redirect {}\n+redirect {}\n", change.referenced, change.current))
+		.unwrap_or_default();
+	match diff.unified_diff() {
+		Some(unified_diff) => format!("{}{}", redirect, unified_diff),
+		None if diff.content_changed || redirect.is_empty() => {
+			format!("{}-{}\n+{}\n", redirect, diff.referenced_content, diff.current_content)
+		}
+		None => redirect,
+	}
+}
+
+/// What happened to a citation during a review
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewOutcome {
+	/// The citation has not drifted
+	Unchanged,
+	/// The current content was accepted as the new reference
+	Accepted,
+	/// The drift was rejected, leaving the reference as is
+	Rejected,
+	/// The drift was accepted but the reference must be updated in the citation
+	ManualUpdate(String),
+	/// The citation could not be validated or accepted
+	Failed(String),
+}
+
+/// Counts of the outcomes of a review
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewSummary {
+	pub unchanged: usize,
+	pub accepted: usize,
+	pub rejected: usize,
+	pub manual: usize,
+	pub failed: usize,
+}
+
+impl ReviewSummary {
+	/// Record an outcome
+	pub fn record(&mut self, outcome: &ReviewOutcome) {
+		match outcome {
+			ReviewOutcome::Unchanged => self.unchanged += 1,
+			ReviewOutcome::Accepted => self.accepted += 1,
+			ReviewOutcome::Rejected => self.rejected += 1,
+			ReviewOutcome::ManualUpdate(_) => self.manual += 1,
+			ReviewOutcome::Failed(_) => self.failed += 1,
+		}
+	}
+
+	/// Check if every citation is up to date after the review
+	pub fn is_clean(&self) -> bool {
+		self.rejected == 0 && self.manual == 0 && self.failed == 0
+	}
+}
+
+impl std::fmt::Display for ReviewSummary {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{} accepted, {} rejected, {} need a manual update, {} failed, {} unchanged",
+			self.accepted, self.rejected, self.manual, self.failed, self.unchanged
+		)
+	}
+}
+
+/// Review a citation, asking `decide` whether to accept its drift
+pub fn review_citation<F>(
+	citation: &FoundCitation,
+	cache: Option<&Cache>,
+	decide: F,
+) -> ReviewOutcome
+where
+	F: FnOnce(&Drift) -> bool,
+{
+	let drift = match check_citation(citation, cache) {
+		Ok(Some(drift)) => drift,
+		Ok(None) => return ReviewOutcome::Unchanged,
+		Err(error) => return ReviewOutcome::Failed(error.to_string()),
+	};
+
+	if !decide(&drift) {
+		return ReviewOutcome::Rejected;
+	}
+
+	match drift.accept() {
+		Ok(()) => ReviewOutcome::Accepted,
+		Err(ReviewError::ManualUpdate(message)) => ReviewOutcome::ManualUpdate(message),
+		Err(error) => ReviewOutcome::Failed(error.to_string()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::Path;
+
+	fn citation(source: &str) -> FoundCitation {
+		let content = format!("#[cite({})]\nfn cited() {{}}\n", source);
+		let mut citations = scan_source(Path::new("src/lib.rs"), &content).unwrap();
+		citations.remove(0)
+	}
+
+	#[test]
+	fn test_unchanged_citation() {
+		let outcome = review_citation(&citation("mock, same = \"content\""), None, |_| {
+			panic!("unchanged citations are not reviewed")
+		});
+		assert_eq!(outcome, ReviewOutcome::Unchanged);
+	}
+
+	#[test]
+	fn test_mock_drift_needs_manual_update() {
+		let citation = citation("mock, changed = (\"old\", \"new\")");
+
+		let drift = check_citation(&citation, None).unwrap().unwrap();
+		assert_eq!(drift.diff, "-old\n+new\n");
+
+		assert_eq!(review_citation(&citation, None, |_| false), ReviewOutcome::Rejected);
+		assert!(matches!(
+			review_citation(&citation, None, |_| true),
+			ReviewOutcome::ManualUpdate(_)
+		));
+	}
+
+	#[test]
+	fn test_invalid_citation_fails() {
+		let outcome =
+			review_citation(&citation("ftp, url = \"ftp://example.com\""), None, |_| true);
+		assert!(matches!(outcome, ReviewOutcome::Failed(ref message) if message.contains("ftp")));
+
+		let mut summary = ReviewSummary::default();
+		summary.record(&outcome);
+		summary.record(&ReviewOutcome::Accepted);
+		assert!(!summary.is_clean());
+		assert_eq!(
+			summary.to_string(),
+			"1 accepted, 0 rejected, 0 need a manual update, 1 failed, 0 unchanged"
+		);
+	}
+
+	#[test]
+	fn test_citation_source_resolves_config() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		std::fs::write(temp_dir.path().join("Cargo.toml"), "[package]\nname = \"docs\"\n")?;
+		std::fs::write(
+			temp_dir.path().join("cite.toml"),
+			"default_profile = \"prod\"\n[targets.docs]\nprod = { url = \"https://example.com/docs\" }\n[normalizers.prose]\nsteps = [\"entities\", \"whitespace\"]\n",
+		)?;
+		let scan = |source: &str| {
+			let content = format!("#[cite({})]\nfn cited() {{}}\n", source);
+			scan_source(&temp_dir.path().join("src/lib.rs"), &content)
+				.map(|mut found| found.remove(0))
+		};
+
+		let http = scan("http, url = \"https://example.com\", normalizer = \"prose\"")?;
+		match citation_source(&http, None)? {
+			AnySource::Http(source) => assert_eq!(source.normalizers().len(), 2),
+			_ => panic!("expected an http source"),
+		}
+
+		// Targets resolve in the default profile of the crate without CITE_PROFILE
+		if config::active_profile().is_none() {
+			match citation_source(&scan("http, target = \"docs\"")?, None)? {
+				AnySource::Http(source) => {
+					assert_eq!(source.source_url.as_str(), "https://example.com/docs")
+				}
+				_ => panic!("expected an http source"),
+			}
+		}
+
+		let git = scan("git, remote = \"https://example.com/repo.git\", normalizer = \"prose\"")?;
+		assert!(matches!(
+			citation_source(&git, None),
+			Err(ReviewError::Config(ref message)) if message.contains("git citations")
+		));
+		Ok(())
+	}
+}
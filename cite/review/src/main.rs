@@ -0,0 +1,132 @@
+//! `cite-review`: review drifted citations and accept their current content
+//!
+//! ```text
+//! cite-review [--accept-all] [--cite-dir <dir>] [<path>...]
+//! ```
+//!
+//! Reviews the citations recorded when the workspace was last built, or those found in the given
+//! paths, showing the diff of each drifted citation and asking whether to accept it. Exits with
+//! a failure if any drift remains after the review.
+
+use cite_cache::{Cache, CacheBuilder, CitationRegistry, CITATION_REGISTRY_DIR};
+use cite_review::{load_citations, review_citation, ReviewOutcome, ReviewSummary};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: cite-review [--accept-all] [--cite-dir <dir>] [<path>...]";
+
+struct Options {
+	accept_all: bool,
+	cite_dir: Option<PathBuf>,
+	paths: Vec<PathBuf>,
+}
+
+impl Options {
+	fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+		let mut options = Self { accept_all: false, cite_dir: None, paths: Vec::new() };
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--accept-all" => options.accept_all = true,
+				"--cite-dir" => {
+					let dir = args.next().ok_or("--cite-dir requires a directory")?;
+					options.cite_dir = Some(PathBuf::from(dir));
+				}
+				"-h" | "--help" => return Err(USAGE.to_string()),
+				flag if flag.starts_with("--") => return Err(format!("Unknown flag: {}", flag)),
+				path => options.paths.push(PathBuf::from(path)),
+			}
+		}
+
+		Ok(options)
+	}
+
+	fn cache(&self) -> Result<Option<Cache>, String> {
+		self.cite_dir
+			.as_ref()
+			.map(|cite_dir| {
+				CacheBuilder::new(cite_dir.clone(), PathBuf::from("cache"))
+					.build()
+					.map_err(|e| format!("Failed to open cache in {}: {}", cite_dir.display(), e))
+			})
+			.transpose()
+	}
+
+	fn registry(&self) -> CitationRegistry {
+		let cite_dir = self.cite_dir.clone().unwrap_or_else(|| CacheBuilder::default().cite_dir);
+		CitationRegistry::new(cite_dir.join(CITATION_REGISTRY_DIR))
+	}
+}
+
+/// Ask whether to accept a drift, defaulting to no
+fn confirm(stdin: &mut impl BufRead) -> bool {
+	print!("Accept the current content? [y/N] ");
+	let _ = std::io::stdout().flush();
+
+	let mut answer = String::new();
+	match stdin.read_line(&mut answer) {
+		Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+		Err(_) => false,
+	}
+}
+
+fn main() -> ExitCode {
+	let options = match Options::parse(std::env::args().skip(1)) {
+		Ok(options) => options,
+		Err(message) => {
+			eprintln!("{}", message);
+			return ExitCode::from(2);
+		}
+	};
+
+	let cache = match options.cache() {
+		Ok(cache) => cache,
+		Err(message) => {
+			eprintln!("{}", message);
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let citations = match load_citations(&options.paths, &options.registry()) {
+		Ok(citations) => citations,
+		Err(error) => {
+			eprintln!("{}", error);
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let mut stdin = std::io::stdin().lock();
+	let mut summary = ReviewSummary::default();
+
+	for citation in &citations {
+		let outcome = review_citation(citation, cache.as_ref(), |drift| {
+			let change = drift.change.map(|change| format!(" ({} change)", change));
+			println!(
+				"{}: {} has changed{}",
+				citation.location(),
+				drift.source.name(),
+				change.unwrap_or_default()
+			);
+			println!("{}", drift.diff.trim_end());
+			options.accept_all || confirm(&mut stdin)
+		});
+
+		match &outcome {
+			ReviewOutcome::Unchanged => {}
+			ReviewOutcome::Accepted => println!("{}: accepted", citation.location()),
+			ReviewOutcome::Rejected => println!("{}: rejected", citation.location()),
+			ReviewOutcome::ManualUpdate(message) | ReviewOutcome::Failed(message) => {
+				println!("{}: {}", citation.location(), message);
+			}
+		}
+		summary.record(&outcome);
+	}
+
+	println!("{}", summary);
+	if summary.is_clean() {
+		ExitCode::SUCCESS
+	} else {
+		ExitCode::FAILURE
+	}
+}
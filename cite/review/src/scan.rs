@@ -0,0 +1,250 @@
+use crate::ReviewError;
+use cite_cache::{CitationRecord, CitationRegistry};
+use cite_core::{CitationBehavior, CitationDeadline, CitationLevel, RequiredFeature};
+use cite_sources::syntax::{extract_source_type, parse_cite_kwargs};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::{Attribute, Expr, Token};
+
+/// A `#[cite(...)]` attribute found in a source file or recorded in the citation registry
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoundCitation {
+	/// The file containing the citation, or the crate if the registry does not know the file
+	pub path: PathBuf,
+	/// The line of the attribute, starting at 1, or 0 if unknown
+	pub line: usize,
+	/// The source type, as used for the `src` kwarg
+	pub src: String,
+	/// The kwargs of the citation, as seen by the macro
+	pub kwargs: HashMap<String, Value>,
+	/// The level drift of the citation is reported at, or `None` if it is not validated
+	pub level: Option<CitationLevel>,
+}
+
+impl FoundCitation {
+	/// Get the location of the citation, in the style of compiler diagnostics
+	pub fn location(&self) -> String {
+		match self.line {
+			0 => self.path.display().to_string(),
+			line => format!("{}:{}", self.path.display(), line),
+		}
+	}
+}
+
+impl From<CitationRecord> for FoundCitation {
+	fn from(record: CitationRecord) -> Self {
+		Self {
+			path: PathBuf::from(record.file.unwrap_or(record.krate)),
+			line: record.line,
+			src: record.src,
+			kwargs: record.kwargs.into_iter().collect(),
+			level: record.level,
+		}
+	}
+}
+
+/// Find the citations recorded in the registry as the macro expanded them
+pub fn recorded_citations(registry: &CitationRegistry) -> Result<Vec<FoundCitation>, ReviewError> {
+	let records = registry.load().map_err(ReviewError::Registry)?;
+	Ok(records.into_iter().map(FoundCitation::from).collect())
+}
+
+/// Find the citations to review or check
+///
+/// Given paths are searched for citations. Otherwise the citations are those the macro recorded
+/// in the registry when the workspace was last built, falling back to searching the working
+/// directory before any citation is recorded.
+pub fn load_citations(
+	paths: &[PathBuf],
+	registry: &CitationRegistry,
+) -> Result<Vec<FoundCitation>, ReviewError> {
+	if !paths.is_empty() {
+		return find_citations(paths);
+	}
+	match recorded_citations(registry)? {
+		citations if citations.is_empty() => find_citations(&[PathBuf::from(".")]),
+		citations => Ok(citations),
+	}
+}
+
+/// Find the citations in the given files and directories
+///
+/// Directories are searched recursively for `.rs` files, skipping `target` and hidden
+/// directories such as `.git` and `.cite`. Citations are returned in path order.
+pub fn find_citations(paths: &[PathBuf]) -> Result<Vec<FoundCitation>, ReviewError> {
+	let mut files = Vec::new();
+	for path in paths {
+		collect_rust_files(path, &mut files)?;
+	}
+	files.sort();
+
+	let mut citations = Vec::new();
+	for file in files {
+		let content = std::fs::read_to_string(&file)
+			.map_err(|error| ReviewError::Io { path: file.clone(), error })?;
+		citations.extend(scan_source(&file, &content)?);
+	}
+	Ok(citations)
+}
+
+/// Find the citations in the content of a single source file
+///
+/// Citations using the `above` syntax are skipped, since their kwargs live in the doc
+/// comment of the cited item.
+pub fn scan_source(path: &Path, content: &str) -> Result<Vec<FoundCitation>, ReviewError> {
+	let file = syn::parse_file(content)
+		.map_err(|error| ReviewError::Parse { path: path.to_path_buf(), error })?;
+
+	let mut visitor = CitationVisitor { path, citations: Vec::new(), error: None };
+	visitor.visit_file(&file);
+
+	match visitor.error {
+		Some(error) => Err(error),
+		None => Ok(visitor.citations),
+	}
+}
+
+fn collect_rust_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), ReviewError> {
+	if path.is_file() {
+		if path.extension().is_some_and(|extension| extension == "rs") {
+			files.push(path.to_path_buf());
+		}
+		return Ok(());
+	}
+
+	let entries =
+		std::fs::read_dir(path).map_err(|error| ReviewError::Io { path: path.into(), error })?;
+	for entry in entries {
+		let entry = entry.map_err(|error| ReviewError::Io { path: path.into(), error })?;
+		let name = entry.file_name();
+		let name = name.to_string_lossy();
+		if name == "target" || name.starts_with('.') {
+			continue;
+		}
+		collect_rust_files(&entry.path(), files)?;
+	}
+	Ok(())
+}
+
+struct CitationVisitor<'a> {
+	path: &'a Path,
+	citations: Vec<FoundCitation>,
+	error: Option<ReviewError>,
+}
+
+impl<'ast> Visit<'ast> for CitationVisitor<'_> {
+	fn visit_attribute(&mut self, attribute: &'ast Attribute) {
+		if !is_cite_attribute(attribute) || self.error.is_some() {
+			return;
+		}
+
+		let args = match attribute.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+		{
+			Ok(args) => args.into_iter().collect::<Vec<_>>(),
+			Err(error) => {
+				self.error = Some(ReviewError::Parse { path: self.path.to_path_buf(), error });
+				return;
+			}
+		};
+
+		let src = match extract_source_type(&args) {
+			Some(src) if src != "above" => src,
+			_ => return,
+		};
+
+		let kwargs = parse_cite_kwargs(args.get(1..).unwrap_or_default());
+		self.citations.push(FoundCitation {
+			path: self.path.to_path_buf(),
+			line: attribute.span().start().line,
+			src,
+			level: scanned_level(&kwargs),
+			kwargs,
+		});
+	}
+}
+
+/// Resolve the level of a citation found in a source file as the macro would
+///
+/// Unlike the registry, which records the level as the citation was expanded, this resolves
+/// against the environment of the review, in which cargo features are not enabled.
+fn scanned_level(kwargs: &HashMap<String, Value>) -> Option<CitationLevel> {
+	let kwarg = |key: &str| kwargs.get(key).and_then(Value::as_str);
+	let level = kwarg("level").and_then(|level| CitationLevel::from_str(level).ok());
+	let deadline = kwarg("deadline").and_then(|deadline| CitationDeadline::from_str(deadline).ok());
+	let requires_feature =
+		kwarg("requires_feature").and_then(|feature| RequiredFeature::from_str(feature).ok());
+	CitationBehavior::from_features().citation_level(
+		level,
+		deadline.as_ref(),
+		requires_feature.as_ref(),
+	)
+}
+
+/// Check if an attribute is `#[cite(...)]` or `#[cite::cite(...)]`
+fn is_cite_attribute(attribute: &Attribute) -> bool {
+	let segments = &attribute.path().segments;
+	segments.len() <= 2 && segments.iter().all(|segment| segment.ident == "cite")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SOURCE: &str = r#"
+use cite::cite;
+
+#[cite(http, url = "https://example.com", selector = "h1", reason = "title")]
+pub fn first() {}
+
+pub mod nested {
+	#[cite::cite(mock, changed = ("old", "new"))]
+	pub struct Second;
+}
+
+/// <cite above>
+/// src = "mock"
+/// same = "content"
+/// </cite>
+#[cite(above)]
+pub fn third() {}
+
+#[derive(Debug)]
+pub struct NotCited;
+"#;
+
+	#[test]
+	fn test_scan_source() -> Result<(), ReviewError> {
+		let citations = scan_source(Path::new("src/lib.rs"), SOURCE)?;
+		assert_eq!(citations.len(), 2);
+
+		assert_eq!(citations[0].location(), "src/lib.rs:4");
+		assert_eq!(citations[0].src, "http");
+		assert_eq!(citations[0].kwargs.get("url"), Some(&Value::from("https://example.com")));
+		assert_eq!(citations[0].kwargs.get("reason"), Some(&Value::from("title")));
+
+		assert_eq!(citations[1].location(), "src/lib.rs:8");
+		assert_eq!(citations[1].src, "mock");
+		assert_eq!(citations[1].kwargs.get("changed"), Some(&Value::from(vec!["old", "new"])));
+		Ok(())
+	}
+
+	#[test]
+	fn test_find_citations_skips_target_and_hidden_directories() -> Result<(), anyhow::Error> {
+		let temp_dir = tempfile::TempDir::new()?;
+		let citation = "#[cite(mock, same = \"content\")]\nfn cited() {}\n";
+		for dir in ["src", "target", ".cite"] {
+			std::fs::create_dir(temp_dir.path().join(dir))?;
+			std::fs::write(temp_dir.path().join(dir).join("lib.rs"), citation)?;
+		}
+		std::fs::write(temp_dir.path().join("src").join("notes.txt"), citation)?;
+
+		let citations = find_citations(&[temp_dir.path().to_path_buf()])?;
+		assert_eq!(citations.len(), 1);
+		assert_eq!(citations[0].path, temp_dir.path().join("src").join("lib.rs"));
+		Ok(())
+	}
+}
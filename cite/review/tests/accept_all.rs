@@ -0,0 +1,180 @@
+//! Drives the `cite-review` binary against a temp cache, serving pages from a local server
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+
+type Pages = Arc<Mutex<HashMap<String, String>>>;
+
+/// Serve the given pages by path until the test process exits
+fn serve(pages: Pages) -> Result<u16, anyhow::Error> {
+	let listener = TcpListener::bind("127.0.0.1:0")?;
+	let port = listener.local_addr()?.port();
+
+	std::thread::spawn(move || {
+		for stream in listener.incoming().flatten() {
+			let mut reader = BufReader::new(&stream);
+			let mut request_line = String::new();
+			if reader.read_line(&mut request_line).is_err() {
+				continue;
+			}
+
+			// Skip the headers
+			let mut header = String::new();
+			while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+				header.clear();
+			}
+
+			let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+			let body = pages.lock().map(|pages| pages.get(&path).cloned()).unwrap_or_default();
+			let response = match body {
+				Some(body) => format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					body.len(),
+					body
+				),
+				None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+					.to_string(),
+			};
+			let _ = (&stream).write_all(response.as_bytes());
+		}
+	});
+
+	Ok(port)
+}
+
+fn page(title: &str) -> String {
+	format!("<html><body><h1>{}</h1><p>Other content</p></body></html>", title)
+}
+
+fn review(workspace: &Path, args: &[&str], stdin: &str) -> Result<Output, anyhow::Error> {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_cite-review"))
+		.current_dir(workspace)
+		.args(["--cite-dir", ".cite"])
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()?;
+
+	if let Some(mut child_stdin) = child.stdin.take() {
+		child_stdin.write_all(stdin.as_bytes())?;
+	}
+	Ok(child.wait_with_output()?)
+}
+
+fn stdout(output: &Output) -> String {
+	String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_accept_all_refreshes_drifted_entries() -> Result<(), anyhow::Error> {
+	let pages: Pages = Arc::new(Mutex::new(HashMap::new()));
+	for name in ["first", "second", "third"] {
+		pages
+			.lock()
+			.map_err(|_| anyhow::anyhow!("poisoned"))?
+			.insert(format!("/{}", name), page(&format!("{} title", name)));
+	}
+	let port = serve(pages.clone())?;
+
+	let workspace = tempfile::TempDir::new()?;
+	std::fs::create_dir(workspace.path().join("src"))?;
+	let mut source = String::new();
+	for name in ["first", "second", "third"] {
+		source += &format!(
+			"#[cite(http, url = \"http://127.0.0.1:{}/{}\", selector = \"h1\")]\npub fn {}() {{}}\n",
+			port, name, name
+		);
+	}
+	std::fs::write(workspace.path().join("src").join("lib.rs"), source)?;
+
+	// The first pass populates the cache, so nothing has drifted
+	let output = review(workspace.path(), &[], "")?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	assert!(stdout(&output)
+		.contains("0 accepted, 0 rejected, 0 need a manual update, 0 failed, 3 unchanged"));
+
+	// Two of the pages change
+	{
+		let mut pages = pages.lock().map_err(|_| anyhow::anyhow!("poisoned"))?;
+		pages.insert("/first".to_string(), page("first title, revised"));
+		pages.insert("/third".to_string(), page("third title, revised"));
+	}
+
+	// Rejecting leaves the drift in place
+	let output = review(workspace.path(), &[], "n\nn\n")?;
+	assert!(!output.status.success());
+	assert!(stdout(&output).contains("-first title\n+first title, revised"));
+	assert!(stdout(&output).contains("src/lib.rs:5: rejected"));
+	assert!(stdout(&output)
+		.contains("0 accepted, 2 rejected, 0 need a manual update, 0 failed, 1 unchanged"));
+
+	// Accepting everything refreshes both entries
+	let output = review(workspace.path(), &["--accept-all"], "")?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	assert!(stdout(&output).contains("src/lib.rs:1: accepted"));
+	assert!(stdout(&output).contains("src/lib.rs:5: accepted"));
+	assert!(stdout(&output)
+		.contains("2 accepted, 0 rejected, 0 need a manual update, 0 failed, 1 unchanged"));
+
+	// So the next pass is clean
+	let output = review(workspace.path(), &[], "")?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	assert!(stdout(&output)
+		.contains("0 accepted, 0 rejected, 0 need a manual update, 0 failed, 3 unchanged"));
+	Ok(())
+}
+
+#[test]
+fn test_accept_all_refreshes_recorded_citations() -> Result<(), anyhow::Error> {
+	let pages: Pages = Arc::new(Mutex::new(HashMap::new()));
+	pages
+		.lock()
+		.map_err(|_| anyhow::anyhow!("poisoned"))?
+		.insert("/recorded".to_string(), page("recorded title"));
+	let port = serve(pages.clone())?;
+
+	// The citation is only known from the registry, as recorded by the macro
+	let workspace = tempfile::TempDir::new()?;
+	let registry = cite_cache::CitationRegistry::new(
+		workspace.path().join(".cite").join(cite_cache::CITATION_REGISTRY_DIR),
+	);
+	registry.record(
+		"cargo-1",
+		cite_cache::CitationRecord {
+			krate: "docs".to_string(),
+			file: Some("src/docs.rs".to_string()),
+			line: 7,
+			src: "http".to_string(),
+			kwargs: [
+				("url".to_string(), format!("http://127.0.0.1:{}/recorded", port).into()),
+				("selector".to_string(), "h1".into()),
+			]
+			.into_iter()
+			.collect(),
+			level: Some(cite_core::CitationLevel::Warn),
+			change: None,
+		},
+	)?;
+
+	let output = review(workspace.path(), &[], "")?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	assert!(stdout(&output)
+		.contains("0 accepted, 0 rejected, 0 need a manual update, 0 failed, 1 unchanged"));
+
+	pages
+		.lock()
+		.map_err(|_| anyhow::anyhow!("poisoned"))?
+		.insert("/recorded".to_string(), page("recorded title, revised"));
+	let output = review(workspace.path(), &["--accept-all"], "")?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	assert!(stdout(&output).contains("src/docs.rs:7: accepted"));
+
+	let output = review(workspace.path(), &[], "")?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	Ok(())
+}
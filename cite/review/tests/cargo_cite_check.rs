@@ -0,0 +1,61 @@
+//! Drives the `cargo-cite` binary against a temp workspace of mock citations
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn check(workspace: &Path, args: &[&str]) -> Result<Output, anyhow::Error> {
+	Ok(Command::new(env!("CARGO_BIN_EXE_cargo-cite"))
+		.current_dir(workspace)
+		.args(["cite", "check", "--cite-dir", ".cite"])
+		.args(args)
+		.output()?)
+}
+
+fn stdout(output: &Output) -> String {
+	String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn workspace(source: &str) -> Result<tempfile::TempDir, anyhow::Error> {
+	let workspace = tempfile::TempDir::new()?;
+	std::fs::create_dir(workspace.path().join("src"))?;
+	std::fs::write(workspace.path().join("src").join("lib.rs"), source)?;
+	Ok(workspace)
+}
+
+#[test]
+fn test_check_reports_mixed_outcomes() -> Result<(), anyhow::Error> {
+	let workspace = workspace(
+		"#[cite(mock, same = \"content\")]\npub fn unchanged() {}\n\
+		#[cite(mock, changed = (\"old\", \"new\"))]\npub fn drifted() {}\n\
+		#[cite(ftp, url = \"ftp://example.com\")]\npub fn invalid() {}\n",
+	)?;
+
+	// Citations that cannot be validated fail the check at any level
+	let output = check(workspace.path(), &[])?;
+	assert_eq!(output.status.code(), Some(1), "{}", stdout(&output));
+	assert!(stdout(&output).contains("src/lib.rs:3: drifted ("), "{}", stdout(&output));
+	assert!(stdout(&output).contains("-old\n+new"));
+	assert!(stdout(&output).contains("src/lib.rs:5: failed:"));
+	assert!(stdout(&output).contains("3 checked: 1 drifted, 1 failed, 1 unchanged"));
+	Ok(())
+}
+
+#[test]
+fn test_check_exit_code_follows_fail_level() -> Result<(), anyhow::Error> {
+	let clean = workspace("#[cite(mock, same = \"content\")]\npub fn unchanged() {}\n")?;
+	let output = check(clean.path(), &[])?;
+	assert!(output.status.success(), "{}", stdout(&output));
+	assert!(stdout(&output).contains("1 checked: 0 drifted, 0 failed, 1 unchanged"));
+
+	// Drift fails the check once the fail level is at or below the level of the citation
+	let drifted = workspace(
+		"#[cite(mock, changed = (\"old\", \"new\"), level = \"WARN\")]\npub fn drifted() {}\n",
+	)?;
+	let output = check(drifted.path(), &["--fail-level", "silent"])?;
+	assert_eq!(output.status.code(), Some(1), "{}", stdout(&output));
+	assert!(stdout(&output).contains("1 checked: 1 drifted, 0 failed, 0 unchanged"));
+
+	let output = check(drifted.path(), &["--fail-level", "fatal"])?;
+	assert_eq!(output.status.code(), Some(2));
+	Ok(())
+}
@@ -0,0 +1,365 @@
+//! Crate configuration in `cite.toml`, shared by the macro and the tools reviewing citations
+//!
+//! `cite.toml` sits next to the `Cargo.toml` of the crate declaring a citation. It defines
+//! targets: logical names for a cited endpoint or revision whose concrete parameters vary by
+//! environment. A citation references a target by name, with `target = "api-docs"`, and the
+//! profile active in `CITE_PROFILE`, or `default_profile` otherwise, selects the parameters
+//! merged into the citation.
+//!
+//! ```toml
+//! default_profile = "prod"
+//!
+//! [targets.api-docs]
+//! dev = { url = "https://dev.example.com/docs" }
+//! staging = { url = "https://staging.example.com/docs" }
+//! prod = { url = "https://example.com/docs" }
+//! ```
+//!
+//! It also defines normalizer profiles: named chains of normalizers that an http citation
+//! references by name, with `normalizer = "prose"`, instead of repeating them on every citation.
+//!
+//! ```toml
+//! [normalizers.prose]
+//! steps = ["line_endings", "entities", 'redact:\d{4}-\d{2}-\d{2}', "case", "whitespace"]
+//! ```
+//!
+//! Profiles are resolved into the `normalize` chain of the citation, before any normalizers
+//! given on the citation itself.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file, in the directory of the crate's manifest
+pub const CONFIG_FILE: &str = "cite.toml";
+
+/// Environment variable selecting the profile targets are resolved in
+pub const PROFILE_ENV: &str = "CITE_PROFILE";
+
+/// Source types whose content normalizer profiles apply to
+const NORMALIZED_SOURCES: [&str; 3] = ["http", "graphql", "pdf"];
+
+/// Read and parse `cite.toml`, naming what it is read for in errors
+pub fn read_config(manifest_dir: &Path, purpose: &str) -> Result<toml::Table, String> {
+	let path = manifest_dir.join(CONFIG_FILE);
+	let config = std::fs::read_to_string(&path)
+		.map_err(|e| format!("Failed to read {} for {}: {}", path.display(), purpose, e))?;
+	config
+		.parse::<toml::Table>()
+		.map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Get the profile active in the environment, if any
+pub fn active_profile() -> Option<String> {
+	std::env::var(PROFILE_ENV).ok().filter(|profile| !profile.trim().is_empty())
+}
+
+/// Find the directory of the manifest of the crate a file belongs to, where its `cite.toml` is
+pub fn crate_dir(path: &Path) -> Option<PathBuf> {
+	path.ancestors()
+		.skip(1)
+		.find(|dir| dir.join("Cargo.toml").is_file())
+		.map(Path::to_path_buf)
+}
+
+/// Resolve the `target` of a citation, if any, into the parameters of the given profile, or of
+/// the `default_profile` of the crate without one
+pub fn resolve_target(
+	manifest_dir: &Path,
+	profile: Option<&str>,
+	kwargs: &mut HashMap<String, Value>,
+) -> Result<(), String> {
+	let name = match kwargs.remove("target") {
+		Some(Value::String(name)) => name,
+		Some(value) => return Err(format!("target must be a target name, got {}", value)),
+		None => return Ok(()),
+	};
+
+	let config = read_config(manifest_dir, &format!("target '{}'", name))?;
+	let targets = config.get("targets").and_then(toml::Value::as_table);
+	let target = targets.and_then(|targets| targets.get(&name)).ok_or_else(|| {
+		let defined = targets.map(|targets| targets.keys().cloned().collect::<Vec<_>>());
+		format!(
+			"Unknown target '{}'. Targets defined in {}: {}",
+			name,
+			CONFIG_FILE,
+			defined.unwrap_or_default().join(", ")
+		)
+	})?;
+	let target = target
+		.as_table()
+		.ok_or_else(|| format!("Target '{}' must map profiles to parameters", name))?;
+
+	// The profile of the environment takes precedence over the default of the crate
+	let profile = match profile {
+		Some(profile) => profile.trim().to_string(),
+		None => config
+			.get("default_profile")
+			.and_then(toml::Value::as_str)
+			.map(str::to_string)
+			.ok_or_else(|| {
+				format!(
+					"Target '{}' needs an active profile: set {} or default_profile in {}",
+					name, PROFILE_ENV, CONFIG_FILE
+				)
+			})?,
+	};
+	let parameters = target.get(&profile).ok_or_else(|| {
+		format!(
+			"Target '{}' has no mapping for profile '{}'. Profiles mapped in {}: {}",
+			name,
+			profile,
+			CONFIG_FILE,
+			target.keys().cloned().collect::<Vec<_>>().join(", ")
+		)
+	})?;
+	let parameters = parameters.as_table().ok_or_else(|| {
+		format!("Profile '{}' of target '{}' must be a table of parameters", profile, name)
+	})?;
+
+	// Parameters come from either the citation or the target, so that it is clear which applies
+	for (key, value) in parameters {
+		if kwargs.contains_key(key) {
+			return Err(format!(
+				"{} is set by profile '{}' of target '{}' and cannot also be given on the citation",
+				key, profile, name
+			));
+		}
+		let value = serde_json::to_value(value)
+			.map_err(|e| format!("Invalid {} of target '{}': {}", key, name, e))?;
+		kwargs.insert(key.clone(), value);
+	}
+	Ok(())
+}
+
+/// Resolve the `normalizer` profile of a citation of the given source type, if any, into its
+/// `normalize` chain
+///
+/// Profiles normalize fetched content, so citations of other sources, e.g. git, cannot name one.
+pub fn resolve_normalizer(
+	src: &str,
+	manifest_dir: &Path,
+	kwargs: &mut HashMap<String, Value>,
+) -> Result<(), String> {
+	let name = match kwargs.remove("normalizer") {
+		Some(Value::String(name)) => name,
+		Some(value) => return Err(format!("normalizer must be a profile name, got {}", value)),
+		None => return Ok(()),
+	};
+	if !NORMALIZED_SOURCES.contains(&src) {
+		return Err(format!(
+			"normalizer '{}' cannot be used on {} citations: normalizer profiles only apply to {}",
+			name,
+			src,
+			NORMALIZED_SOURCES.join(", ")
+		));
+	}
+
+	let config = read_config(manifest_dir, &format!("normalizer profile '{}'", name))?;
+
+	let profiles = config.get("normalizers").and_then(toml::Value::as_table);
+	let profile = profiles.and_then(|profiles| profiles.get(&name)).ok_or_else(|| {
+		let defined = profiles.map(|profiles| profiles.keys().cloned().collect::<Vec<_>>());
+		format!(
+			"Unknown normalizer profile '{}'. Profiles defined in {}: {}",
+			name,
+			CONFIG_FILE,
+			defined.unwrap_or_default().join(", ")
+		)
+	})?;
+
+	let steps = profile
+		.get("steps")
+		.and_then(toml::Value::as_array)
+		.and_then(|steps| steps.iter().map(toml::Value::as_str).collect::<Option<Vec<_>>>())
+		.ok_or_else(|| {
+			format!("Normalizer profile '{}' must have a list of normalizers as steps", name)
+		})?;
+	for step in &steps {
+		cite_http::Normalizer::from_str(step)
+			.map_err(|e| format!("Normalizer profile '{}': {}", name, e))?;
+	}
+
+	// Normalizers given on the citation itself are applied after the profile
+	let mut normalize = steps.into_iter().map(|step| Value::String(step.to_string())).collect();
+	match kwargs.remove("normalize") {
+		Some(Value::Array(citation)) => normalize = [normalize, citation].concat(),
+		Some(citation) => normalize.push(citation),
+		None => {}
+	}
+	kwargs.insert("normalize".to_string(), Value::Array(normalize));
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cite_core::ui::SourceUi;
+	use cite_http::{normalize::normalize, HttpMatch};
+
+	fn kwargs(value: serde_json::Value) -> HashMap<String, Value> {
+		serde_json::from_value(value).unwrap()
+	}
+
+	const TARGETS: &str = r#"
+default_profile = "prod"
+
+[targets.api-docs]
+dev = { url = "https://dev.example.com/docs" }
+staging = { url = "https://staging.example.com/docs", match = "css:main" }
+prod = { url = "https://example.com/docs" }
+
+[targets.schema]
+staging = { remote = "https://github.com/ramate-io/cite", ref_rev = "v1.0", cur_rev = "staging" }
+prod = { remote = "https://github.com/ramate-io/cite", ref_rev = "v1.0", cur_rev = "main" }
+"#;
+
+	#[test]
+	fn test_resolve_target_by_profile() -> Result<(), anyhow::Error> {
+		let manifest_dir = tempfile::tempdir()?;
+		std::fs::write(manifest_dir.path().join(CONFIG_FILE), TARGETS)?;
+		let resolve = |target: &str, profile: Option<&str>| -> Result<_, anyhow::Error> {
+			let mut citation = kwargs(serde_json::json!({ "src": "http", "target": target }));
+			resolve_target(manifest_dir.path(), profile, &mut citation)
+				.map_err(anyhow::Error::msg)?;
+			assert!(!citation.contains_key("target"));
+			Ok(citation)
+		};
+
+		// Switching profiles switches the resolved endpoint
+		let staging = resolve("api-docs", Some("staging"))?;
+		assert_eq!(staging["url"], "https://staging.example.com/docs");
+		assert_eq!(staging["match"], "css:main");
+		let dev = HttpMatch::from_kwarg_json(&resolve("api-docs", Some("dev"))?)?;
+		assert_eq!(dev.source_url.as_str(), "https://dev.example.com/docs");
+
+		// Without an active profile, the default of the crate applies
+		assert_eq!(resolve("api-docs", None)?["url"], "https://example.com/docs");
+
+		// Revisions resolve alike
+		assert_eq!(resolve("schema", Some("staging"))?["cur_rev"], "staging");
+		assert_eq!(resolve("schema", None)?["cur_rev"], "main");
+		Ok(())
+	}
+
+	#[test]
+	fn test_resolve_target_errors() -> Result<(), anyhow::Error> {
+		let manifest_dir = tempfile::tempdir()?;
+		std::fs::write(manifest_dir.path().join(CONFIG_FILE), TARGETS)?;
+		let resolve = |citation: serde_json::Value, profile: Option<&str>| {
+			resolve_target(manifest_dir.path(), profile, &mut kwargs(citation))
+		};
+
+		// The active profile must be mapped by the target
+		let unmapped = resolve(serde_json::json!({ "target": "schema" }), Some("dev")).unwrap_err();
+		assert!(unmapped.contains("Target 'schema' has no mapping for profile 'dev'"));
+		assert!(unmapped.contains("staging"));
+		assert!(resolve(serde_json::json!({ "target": "docs" }), Some("dev"))
+			.unwrap_err()
+			.contains("Unknown target 'docs'"));
+
+		// Parameters cannot be given by both the target and the citation
+		let url = "https://example.com/other";
+		assert!(resolve(serde_json::json!({ "target": "api-docs", "url": url }), Some("dev"))
+			.unwrap_err()
+			.contains("url is set by profile 'dev'"));
+
+		// Without a default, a profile must be active
+		std::fs::write(
+			manifest_dir.path().join(CONFIG_FILE),
+			TARGETS.replace("default_profile", "#"),
+		)?;
+		assert!(resolve(serde_json::json!({ "target": "api-docs" }), None)
+			.unwrap_err()
+			.contains(PROFILE_ENV));
+		Ok(())
+	}
+
+	#[test]
+	fn test_resolve_normalizer_profile() -> Result<(), anyhow::Error> {
+		let manifest_dir = tempfile::tempdir()?;
+		std::fs::write(
+			manifest_dir.path().join(CONFIG_FILE),
+			r#"
+[normalizers.prose]
+steps = ["line_endings", "entities", 'redact:\d{4}-\d{2}-\d{2}', "case", "whitespace"]
+"#,
+		)?;
+
+		let mut citation = kwargs(serde_json::json!({
+			"src": "http",
+			"url": "https://example.com/terms",
+			"match": "css:main",
+			"normalizer": "prose",
+		}));
+		resolve_normalizer("http", manifest_dir.path(), &mut citation)
+			.map_err(anyhow::Error::msg)?;
+		assert!(!citation.contains_key("normalizer"));
+
+		// The citation applies the composed transforms of the profile
+		let http_match = HttpMatch::from_kwarg_json(&citation)?;
+		assert_eq!(http_match.normalizers().len(), 5);
+		assert_eq!(
+			normalize(
+				http_match.normalizers(),
+				"Terms &amp; Conditions\r\n\r\nUpdated 2024-05-01"
+			)?,
+			"terms & conditions updated [redacted]"
+		);
+
+		// Normalizers of the citation follow those of the profile
+		let mut citation = kwargs(serde_json::json!({
+			"normalizer": "prose",
+			"normalize": r"redact:v\d+",
+		}));
+		resolve_normalizer("http", manifest_dir.path(), &mut citation)
+			.map_err(anyhow::Error::msg)?;
+		assert_eq!(citation["normalize"].as_array().map(Vec::len), Some(6));
+		assert_eq!(citation["normalize"][5], r"redact:v\d+");
+		Ok(())
+	}
+
+	#[test]
+	fn test_resolve_normalizer_errors() -> Result<(), anyhow::Error> {
+		let manifest_dir = tempfile::tempdir()?;
+		let resolve = |src: &str, citation: serde_json::Value| {
+			resolve_normalizer(src, manifest_dir.path(), &mut kwargs(citation))
+		};
+
+		// Without a normalizer nothing is read
+		assert!(resolve("http", serde_json::json!({ "normalize": "case" })).is_ok());
+		assert!(resolve("http", serde_json::json!({ "normalizer": "prose" }))
+			.unwrap_err()
+			.contains("Failed to read"));
+
+		// Git citations are rejected before any profile is read
+		let git = resolve("git", serde_json::json!({ "normalizer": "prose" })).unwrap_err();
+		assert!(git.contains("cannot be used on git citations"), "{}", git);
+
+		std::fs::write(
+			manifest_dir.path().join(CONFIG_FILE),
+			"[normalizers.prose]\nsteps = [\"stemming\"]\n[normalizers.code]\nsteps = \"case\"\n",
+		)?;
+		let unknown = resolve("http", serde_json::json!({ "normalizer": "legal" })).unwrap_err();
+		assert!(unknown.contains("Unknown normalizer profile 'legal'"));
+		assert!(unknown.contains("code, prose") || unknown.contains("prose, code"));
+		assert!(resolve("http", serde_json::json!({ "normalizer": "prose" }))
+			.unwrap_err()
+			.contains("Invalid normalizer"));
+		assert!(resolve("http", serde_json::json!({ "normalizer": "code" }))
+			.unwrap_err()
+			.contains("list of normalizers"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_crate_dir() -> Result<(), anyhow::Error> {
+		let workspace = tempfile::tempdir()?;
+		let crate_dir = workspace.path().join("docs");
+		std::fs::create_dir_all(crate_dir.join("src"))?;
+		std::fs::write(crate_dir.join("Cargo.toml"), "")?;
+		assert_eq!(super::crate_dir(&crate_dir.join("src").join("lib.rs")), Some(crate_dir));
+		assert_eq!(super::crate_dir(&workspace.path().join("lib.rs")), None);
+		Ok(())
+	}
+}
@@ -0,0 +1,281 @@
+//! Translation of the legacy source construction syntax into keyword arguments
+//!
+//! Before keyword arguments, citations constructed their source directly, e.g.
+//! `#[cite(MockSource::same("content"))]`, or with function-like syntax, e.g.
+//! `#[cite(mock(same("content")))]`. Both are translated into the kwargs of the current syntax
+//! here, for mock, http and git sources, so that citations written against the legacy crates
+//! keep validating while their authors migrate them.
+
+use quote::ToTokens;
+use serde_json::Value;
+use std::collections::HashMap;
+use syn::{Expr, Lit};
+
+/// The legacy source types, by their type name and the source type of the keyword syntax
+const LEGACY_SOURCES: [(&str, &str); 3] =
+	[("MockSource", "mock"), ("HttpMatch", "http"), ("GitSource", "git")];
+
+/// Keys rendered first by [keyword_syntax], in order
+const LEADING_KEYS: [&str; 5] = ["url", "remote", "path", "ref_rev", "cur_rev"];
+
+/// Translate a legacy source construction into the kwargs of the current syntax
+///
+/// Recognizes the legacy constructors
+/// - `MockSource::same("content")` and `MockSource::changed("old", "new")`
+/// - `HttpMatch::cached("url", "pattern")`
+/// - `GitSource::try_new("remote", "path", "ref_rev", Some("cur_rev"), None)`
+///
+/// and their function-like forms, e.g. `mock(same("content"))` or `http(cached("url", "pattern"))`.
+/// Returns `None` for any other expression, including the current keyword syntax.
+pub fn legacy_source_kwargs(arg: &Expr) -> Option<HashMap<String, Value>> {
+	let Expr::Call(call) = arg else {
+		return None;
+	};
+	let path = call_path(call)?;
+
+	let (src, constructor, call) = match path.as_slice() {
+		[source, constructor] => {
+			let (_, src) = LEGACY_SOURCES.iter().find(|(name, _)| name == source)?;
+			(*src, constructor.clone(), call)
+		}
+		[source] => {
+			let (_, src) = LEGACY_SOURCES.iter().find(|(_, src)| src == source)?;
+			match call.args.iter().collect::<Vec<_>>().as_slice() {
+				[Expr::Call(inner)] => match call_path(inner)?.as_slice() {
+					[constructor] => (*src, constructor.clone(), inner),
+					_ => return None,
+				},
+				_ => return None,
+			}
+		}
+		_ => return None,
+	};
+
+	let mut kwargs = source_kwargs(src, &constructor, call)?;
+	kwargs.insert("src".to_string(), Value::String(src.to_string()));
+	Some(kwargs)
+}
+
+/// Render kwargs as the keyword syntax that replaces a legacy citation, e.g.
+/// `mock, same = "content"`
+pub fn keyword_syntax(kwargs: &HashMap<String, Value>) -> String {
+	// What is cited leads, in the order the citation is read in
+	let rank = |key: &str| {
+		LEADING_KEYS
+			.iter()
+			.position(|leading| *leading == key)
+			.unwrap_or(LEADING_KEYS.len())
+	};
+	let mut keys: Vec<_> = kwargs.keys().filter(|key| key.as_str() != "src").collect();
+	keys.sort_by(|a, b| (rank(a), a).cmp(&(rank(b), b)));
+
+	let src = kwargs.get("src").and_then(Value::as_str).unwrap_or_default();
+	keys.into_iter().fold(src.to_string(), |syntax, key| {
+		let value = match &kwargs[key] {
+			Value::Array(values) => {
+				format!("({})", values.iter().map(Value::to_string).collect::<Vec<_>>().join(", "))
+			}
+			value => value.to_string(),
+		};
+		format!("{}, {} = {}", syntax, key, value)
+	})
+}
+
+/// Get the segments of the path a call expression calls
+fn call_path(call: &syn::ExprCall) -> Option<Vec<String>> {
+	match &*call.func {
+		Expr::Path(path) => {
+			Some(path.path.segments.iter().map(|segment| segment.ident.to_string()).collect())
+		}
+		_ => None,
+	}
+}
+
+/// Build the kwargs of a source from the arguments of its legacy constructor
+///
+/// Optional arguments, e.g. the current revision of a git source, are only set when given.
+fn source_kwargs(
+	src: &str,
+	constructor: &str,
+	call: &syn::ExprCall,
+) -> Option<HashMap<String, Value>> {
+	let string = |arg: &Expr| string_arg(arg).map(Value::String);
+	let args = call.args.iter().collect::<Vec<_>>();
+
+	let kwargs = match (src, constructor, args.as_slice()) {
+		("mock", "same", [content]) => vec![("same", string(content)?)],
+		("mock", "changed", [referenced, current]) => {
+			vec![("changed", Value::Array(vec![string(referenced)?, string(current)?]))]
+		}
+		("http", "cached", [url, pattern]) => {
+			vec![("url", string(url)?), ("pattern", string(pattern)?)]
+		}
+		("git", "try_new", [remote, path, referenced, current, name]) => {
+			let mut kwargs = vec![
+				("remote", string(remote)?),
+				("path", string(path)?),
+				("ref_rev", string(referenced)?),
+			];
+			kwargs.extend(option_arg(current)?.map(|current| ("cur_rev", Value::String(current))));
+			kwargs.extend(option_arg(name)?.map(|name| ("name", Value::String(name))));
+			kwargs
+		}
+		_ => return None,
+	};
+	Some(kwargs.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+/// Get the string an optional argument of a legacy constructor passes, e.g. `Some("main")`, or
+/// `None` if it passes `None`
+fn option_arg(arg: &Expr) -> Option<Option<String>> {
+	match arg {
+		Expr::Path(path) if path.path.is_ident("None") => Some(None),
+		Expr::Call(call) => match (call_path(call)?.as_slice(), call.args.first()) {
+			([variant], Some(value)) if variant == "Some" && call.args.len() == 1 => {
+				string_arg(value).map(Some)
+			}
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Get the string an argument of a legacy constructor passes, e.g. `"content"` or
+/// `"content".to_string()`
+fn string_arg(arg: &Expr) -> Option<String> {
+	match arg {
+		Expr::Lit(expr_lit) => match &expr_lit.lit {
+			Lit::Str(lit_str) => Some(lit_str.value()),
+			_ => None,
+		},
+		Expr::MethodCall(method_call)
+			if ["to_string", "to_owned", "into"]
+				.contains(&method_call.method.to_token_stream().to_string().as_str()) =>
+		{
+			string_arg(&method_call.receiver)
+		}
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::AnySource;
+	use cite_core::Source;
+	use serde_json::json;
+
+	fn translate(arg: &str) -> Option<HashMap<String, Value>> {
+		legacy_source_kwargs(&syn::parse_str(arg).ok()?)
+	}
+
+	#[test]
+	fn test_legacy_source_kwargs() {
+		let same = translate(r#"MockSource::same("content")"#);
+		assert_eq!(
+			same,
+			Some(HashMap::from([
+				("src".to_string(), json!("mock")),
+				("same".to_string(), json!("content")),
+			]))
+		);
+		assert_eq!(translate(r#"mock(same("content"))"#), same);
+		assert_eq!(translate(r#"MockSource::same("content".to_string())"#), same);
+
+		let changed = translate(r#"MockSource::changed("old", "new")"#);
+		assert_eq!(
+			changed.as_ref().and_then(|kwargs| kwargs.get("changed")),
+			Some(&json!(["old", "new"]))
+		);
+		assert_eq!(translate(r#"mock(changed("old", "new"))"#), changed);
+
+		// Http and git sources translate alike
+		let http = translate(r#"HttpMatch::cached("https://example.com", "v\\d+")"#);
+		assert_eq!(
+			http,
+			Some(HashMap::from([
+				("src".to_string(), json!("http")),
+				("url".to_string(), json!("https://example.com")),
+				("pattern".to_string(), json!(r"v\d+")),
+			]))
+		);
+		assert_eq!(translate(r#"http(cached("https://example.com", "v\\d+"))"#), http);
+
+		let git = translate(
+			r#"GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "v1.0", Some("main"), None)"#,
+		);
+		assert_eq!(
+			git,
+			Some(HashMap::from([
+				("src".to_string(), json!("git")),
+				("remote".to_string(), json!("https://github.com/ramate-io/cite")),
+				("path".to_string(), json!("README.md")),
+				("ref_rev".to_string(), json!("v1.0")),
+				("cur_rev".to_string(), json!("main")),
+			]))
+		);
+		let unpinned = translate(
+			r#"git(try_new("https://github.com/ramate-io/cite", "README.md", "v1.0", None, Some("readme".to_string())))"#,
+		);
+		assert_eq!(unpinned.as_ref().and_then(|kwargs| kwargs.get("cur_rev")), None);
+		assert_eq!(unpinned.as_ref().and_then(|kwargs| kwargs.get("name")), Some(&json!("readme")));
+
+		for current in [
+			"mock",
+			"http",
+			r#"MockSource::same(CONTENT)"#,
+			r#"MockSource::new("a")"#,
+			r#"mock(same("a"), "b")"#,
+			r#"MockSource::same(Some("a"))"#,
+			r#"HttpMatch::cached("https://example.com")"#,
+			r#"GitSource::try_new("remote", "path", "v1.0", "main", None)"#,
+			r#"FileSource::same("a")"#,
+		] {
+			assert_eq!(translate(current), None, "{} is not legacy syntax", current);
+		}
+	}
+
+	#[test]
+	fn test_keyword_syntax() {
+		let kwargs = translate(r#"MockSource::changed("old", "new")"#).unwrap_or_default();
+		assert_eq!(keyword_syntax(&kwargs), r#"mock, changed = ("old", "new")"#);
+
+		let kwargs = translate(r#"MockSource::same("content")"#).unwrap_or_default();
+		assert_eq!(keyword_syntax(&kwargs), r#"mock, same = "content""#);
+
+		// What is cited leads
+		let kwargs = translate(
+			r#"GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "v1.0", Some("main"), Some("readme"))"#,
+		)
+		.unwrap_or_default();
+		assert_eq!(
+			keyword_syntax(&kwargs),
+			r#"git, remote = "https://github.com/ramate-io/cite", path = "README.md", ref_rev = "v1.0", cur_rev = "main", name = "readme""#
+		);
+	}
+
+	#[test]
+	fn test_legacy_citation_validates() -> Result<(), anyhow::Error> {
+		let validate = |arg: &str| -> Result<bool, anyhow::Error> {
+			let kwargs = translate(arg).ok_or(anyhow::anyhow!("{} is not legacy syntax", arg))?;
+			match AnySource::from_kwargs("mock", &kwargs)? {
+				AnySource::Mock(source) => Ok(source.get()?.is_same()),
+				_ => Err(anyhow::anyhow!("{} is not a mock source", arg)),
+			}
+		};
+
+		assert!(validate(r#"MockSource::same("content")"#)?);
+		assert!(!validate(r#"mock(changed("old", "new"))"#)?);
+
+		// Http and git citations construct their sources without fetching
+		let http = translate(r#"HttpMatch::cached("https://example.com", "v\\d+")"#)
+			.ok_or(anyhow::anyhow!("not legacy syntax"))?;
+		assert!(matches!(AnySource::from_kwargs("http", &http)?, AnySource::Http(_)));
+		let git = translate(
+			r#"GitSource::try_new("https://github.com/ramate-io/cite", "README.md", "v1.0", Some("main"), None)"#,
+		)
+		.ok_or(anyhow::anyhow!("not legacy syntax"))?;
+		assert!(matches!(AnySource::from_kwargs("git", &git)?, AnySource::Git(_)));
+		Ok(())
+	}
+}
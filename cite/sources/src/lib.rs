@@ -0,0 +1,370 @@
+//! Cite Sources - Construction of citation sources from keyword arguments
+//!
+//! The `#[cite]` macro describes sources with keyword arguments, e.g.
+//! `#[cite(http, url = "https://example.com", match = "css:h1")]`. This crate owns the
+//! translation from such a kwargs map to a concrete source, so that the macro, the runtime
+//! API, and any tooling built on top of cite (e.g. cache warming) parse kwargs the same way.
+//!
+//! # Design Rationale
+//!
+//! The [cite_core::Source] trait is generic over its content and diff types, so sources of
+//! different kinds cannot share a trait object. [AnySource] instead enumerates the known
+//! source types, which keeps the concrete comparison types available to callers that need
+//! them (e.g. for rendering source-specific diff messages).
+//!
+//! # Usage
+//!
+//! ```rust
+//! use cite_sources::AnySource;
+//! use std::collections::HashMap;
+//!
+//! let mut kwargs = HashMap::new();
+//! kwargs.insert("same".to_string(), "content".to_string());
+//!
+//! let source = AnySource::from_string_kwargs("mock", &kwargs).unwrap();
+//! assert_eq!(source.src(), "mock");
+//! ```
+
+pub mod config;
+pub mod legacy;
+pub mod syntax;
+
+use cite_bundle::BundleSource;
+use cite_core::mock::MockSource;
+use cite_core::ui::{SourceUi, SourceUiError};
+use cite_core::{Id, Source, SourceError};
+use cite_git::GitSource;
+use cite_http::HttpMatch;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Errors thrown when constructing an [AnySource] from kwargs.
+#[derive(Debug, thiserror::Error)]
+pub enum SourceKwargsError {
+	#[error("unknown source type: {0}")]
+	UnknownSourceType(String),
+
+	#[error("Failed to create {source_type} source: {error}")]
+	Construction {
+		source_type: &'static str,
+		#[source]
+		error: SourceUiError,
+	},
+}
+
+/// Any of the source types supported by the cite system.
+#[derive(Clone)]
+pub enum AnySource {
+	/// A git source (`src = "git"`)
+	Git(GitSource),
+	/// An http source (`src = "http"`, `src = "graphql"` for GraphQL schemas or `src = "pdf"` for
+	/// the text of PDF documents)
+	Http(HttpMatch),
+	/// A bundle source, citing the root hash of a directory of files (`src = "bundle"`)
+	Bundle(BundleSource),
+	/// A mock source (`src = "mock"`)
+	Mock(MockSource),
+}
+
+/// Check if a key is a top-level citation field, configuring the citation rather than its
+/// source
+pub fn is_citation_level_field(key: &str) -> bool {
+	const CITATION_LEVEL_FIELDS: [&str; 8] = [
+		"src",
+		"reason",
+		"level",
+		"annotation",
+		"deadline",
+		"reverify_days",
+		"embed",
+		"requires_feature",
+	];
+	CITATION_LEVEL_FIELDS.contains(&key)
+}
+
+impl AnySource {
+	/// Construct a source of the given type from a kwargs map
+	///
+	/// Supports syntax like:
+	/// - `git`: `remote = "...", ref_rev = "...", cur_rev = "...", path = "src/lib.rs#L1-L10"`, where
+	///   `cur_rev` defaults to the default branch of the remote
+	/// - `http`: `url = "https://example.com", match = "css:h1"`
+	/// - `graphql`: `url = "https://api.example.com/graphql", type = "User", field = "email"`
+	/// - `pdf`: `url = "https://example.com/spec.pdf", pages = "3-5"`
+	/// - `bundle`: `root = "fixtures", glob = "**/*.json", sha256 = "..."`
+	/// - `mock`: `same = "content"` or `changed = ["old", "new"]`
+	pub fn from_kwargs(
+		src: &str,
+		kwargs: &HashMap<String, Value>,
+	) -> Result<Self, SourceKwargsError> {
+		match src {
+			"git" => GitSource::from_kwarg_json(kwargs)
+				.map(AnySource::Git)
+				.map_err(|error| SourceKwargsError::Construction { source_type: "Git", error }),
+			"http" => HttpMatch::from_kwarg_json(kwargs)
+				.map(AnySource::Http)
+				.map_err(|error| SourceKwargsError::Construction { source_type: "HTTP", error }),
+			"graphql" => HttpMatch::from_graphql_kwarg_json(kwargs)
+				.map(AnySource::Http)
+				.map_err(|error| SourceKwargsError::Construction { source_type: "GraphQL", error }),
+			"pdf" => HttpMatch::from_pdf_kwarg_json(kwargs)
+				.map(AnySource::Http)
+				.map_err(|error| SourceKwargsError::Construction { source_type: "PDF", error }),
+			"bundle" => BundleSource::from_kwarg_json(kwargs)
+				.map(AnySource::Bundle)
+				.map_err(|error| SourceKwargsError::Construction { source_type: "Bundle", error }),
+			"mock" => MockSource::from_kwarg_json(kwargs)
+				.map(AnySource::Mock)
+				.map_err(|error| SourceKwargsError::Construction { source_type: "Mock", error }),
+			_ => Err(SourceKwargsError::UnknownSourceType(src.to_string())),
+		}
+	}
+
+	/// Construct a source of the given type from a map of string kwargs
+	///
+	/// Convenient for runtime usage and tooling where every value is a string.
+	pub fn from_string_kwargs(
+		src: &str,
+		kwargs: &HashMap<String, String>,
+	) -> Result<Self, SourceKwargsError> {
+		let kwargs = kwargs
+			.iter()
+			.map(|(key, value)| (key.clone(), Value::String(value.clone())))
+			.collect::<HashMap<_, _>>();
+		Self::from_kwargs(src, &kwargs)
+	}
+
+	/// Check if a kwarg key is valid for the given source type
+	///
+	/// Unknown source types accept no keys.
+	pub fn is_valid_attr_key(src: &str, attr_key: &str) -> bool {
+		match src {
+			"git" => <GitSource as SourceUi<_, _, _>>::is_valid_attr_key(attr_key),
+			"http" => <HttpMatch as SourceUi<_, _, _>>::is_valid_attr_key(attr_key),
+			"graphql" => HttpMatch::is_valid_graphql_attr_key(attr_key),
+			"pdf" => HttpMatch::is_valid_pdf_attr_key(attr_key),
+			"bundle" => <BundleSource as SourceUi<_, _, _>>::is_valid_attr_key(attr_key),
+			"mock" => <MockSource as SourceUi<_, _, _>>::is_valid_attr_key(attr_key),
+			_ => false,
+		}
+	}
+
+	/// Get the source type, as used for the `src` kwarg
+	pub fn src(&self) -> &'static str {
+		match self {
+			AnySource::Git(_) => "git",
+			AnySource::Http(source) if source.is_graphql() => "graphql",
+			AnySource::Http(source) if source.pdf().is_some() => "pdf",
+			AnySource::Http(_) => "http",
+			AnySource::Bundle(_) => "bundle",
+			AnySource::Mock(_) => "mock",
+		}
+	}
+
+	/// Get the id of the underlying source
+	pub fn id(&self) -> &Id {
+		match self {
+			AnySource::Git(source) => source.id(),
+			AnySource::Http(source) => source.id(),
+			AnySource::Bundle(source) => source.id(),
+			AnySource::Mock(source) => source.id(),
+		}
+	}
+
+	/// Get the display name of the underlying source
+	pub fn name(&self) -> &str {
+		match self {
+			AnySource::Git(source) => source.name(),
+			AnySource::Http(source) => source.name(),
+			AnySource::Bundle(source) => source.name(),
+			AnySource::Mock(source) => source.name(),
+		}
+	}
+
+	/// Describe the cited target of the underlying source, for message headers
+	pub fn target_description(&self) -> String {
+		match self {
+			AnySource::Git(source) => source.target_description(),
+			AnySource::Http(source) => source.target_description(),
+			AnySource::Bundle(source) => source.target_description(),
+			AnySource::Mock(source) => source.target_description(),
+		}
+	}
+
+	/// Get the documentation link of the underlying source
+	pub fn link(&self) -> &str {
+		match self {
+			AnySource::Git(source) => source.link(),
+			AnySource::Http(source) => source.link(),
+			AnySource::Bundle(source) => source.link(),
+			AnySource::Mock(source) => source.link(),
+		}
+	}
+
+	/// Check if the underlying source can be validated without network access
+	pub fn supports_offline(&self) -> bool {
+		match self {
+			AnySource::Git(source) => source.supports_offline(),
+			AnySource::Http(source) => source.supports_offline(),
+			AnySource::Bundle(source) => source.supports_offline(),
+			AnySource::Mock(source) => source.supports_offline(),
+		}
+	}
+
+	/// Accept the current content of the underlying source as its new reference
+	pub fn refresh(&self) -> Result<(), SourceError> {
+		match self {
+			AnySource::Git(source) => source.refresh(),
+			AnySource::Http(source) => source.refresh(),
+			AnySource::Bundle(source) => source.refresh(),
+			AnySource::Mock(source) => source.refresh(),
+		}
+	}
+
+	/// Get the referenced content without fetching anything, if it is known locally
+	///
+	/// Http sources return their cached reference, bundle sources their pinned root hash, and mock
+	/// sources their literal reference.
+	/// Git references live in the remote repository, so they are never known locally.
+	pub fn referenced_snapshot(&self) -> Option<String> {
+		match self {
+			AnySource::Git(_) => None,
+			AnySource::Http(source) => {
+				source.cached_referenced().map(|referenced| referenced.content)
+			}
+			AnySource::Bundle(source) => source.expected().map(|expected| expected.to_string()),
+			AnySource::Mock(source) => Some(source.referenced_content.clone()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_from_kwargs_git() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("remote".to_string(), json!("https://github.com/ramate-io/cite"));
+		kwargs.insert("ref_rev".to_string(), json!("abc123"));
+		kwargs.insert("cur_rev".to_string(), json!("main"));
+		kwargs.insert("path".to_string(), json!("README.md"));
+
+		let source = AnySource::from_kwargs("git", &kwargs)?;
+		assert_eq!(source.src(), "git");
+		assert!(matches!(source, AnySource::Git(_)));
+		assert_eq!(source.link(), "https://github.com/ramate-io/cite/blob/abc123/README.md");
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_kwargs_http() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com"));
+		kwargs.insert("match".to_string(), json!("css:h1"));
+
+		let source = AnySource::from_kwargs("http", &kwargs)?;
+		assert_eq!(source.src(), "http");
+		assert!(matches!(source, AnySource::Http(_)));
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_kwargs_graphql() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://api.example.com/graphql"));
+		kwargs.insert("type".to_string(), json!("User"));
+		kwargs.insert("field".to_string(), json!("email"));
+
+		let source = AnySource::from_kwargs("graphql", &kwargs)?;
+		assert_eq!(source.src(), "graphql");
+		assert!(matches!(source, AnySource::Http(_)));
+		assert!(AnySource::is_valid_attr_key("graphql", "type"));
+		assert!(!AnySource::is_valid_attr_key("graphql", "selector"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_kwargs_pdf() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("url".to_string(), json!("https://example.com/spec.pdf"));
+		kwargs.insert("pages".to_string(), json!("3-5"));
+
+		let source = AnySource::from_kwargs("pdf", &kwargs)?;
+		assert_eq!(source.src(), "pdf");
+		assert!(matches!(source, AnySource::Http(_)));
+		assert!(AnySource::is_valid_attr_key("pdf", "pages"));
+		assert!(!AnySource::is_valid_attr_key("pdf", "selector"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_kwargs_bundle() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("root".to_string(), json!("fixtures"));
+		kwargs.insert("glob".to_string(), json!("**/*.json"));
+		kwargs.insert("sha256".to_string(), json!("abc123"));
+
+		let source = AnySource::from_kwargs("bundle", &kwargs)?;
+		assert_eq!(source.src(), "bundle");
+		assert!(matches!(source, AnySource::Bundle(_)));
+		assert_eq!(source.referenced_snapshot().as_deref(), Some("abc123"));
+		assert!(AnySource::is_valid_attr_key("bundle", "glob"));
+		assert!(!AnySource::is_valid_attr_key("bundle", "url"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_kwargs_mock() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("changed".to_string(), json!(["old", "new"]));
+
+		let source = AnySource::from_kwargs("mock", &kwargs)?;
+		match source {
+			AnySource::Mock(mock) => {
+				assert_eq!(mock.referenced_content, "old");
+				assert_eq!(mock.current_content, "new");
+			}
+			_ => panic!("expected a mock source"),
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_string_kwargs() -> Result<(), anyhow::Error> {
+		let mut kwargs = HashMap::new();
+		kwargs.insert("same".to_string(), "content".to_string());
+		kwargs.insert("name".to_string(), "custom-name".to_string());
+
+		let source = AnySource::from_string_kwargs("mock", &kwargs)?;
+		assert_eq!(source.name(), "custom-name");
+		Ok(())
+	}
+
+	#[test]
+	fn test_from_kwargs_errors() {
+		let kwargs = HashMap::new();
+
+		let result = AnySource::from_kwargs("ftp", &kwargs);
+		assert!(matches!(result, Err(SourceKwargsError::UnknownSourceType(_))));
+
+		let result = AnySource::from_kwargs("http", &kwargs);
+		match result {
+			Err(error) => assert_eq!(
+				error.to_string(),
+				"Failed to create HTTP source: Missing required parameter: url"
+			),
+			Ok(_) => panic!("expected a construction error"),
+		}
+	}
+
+	#[test]
+	fn test_is_valid_attr_key() {
+		assert!(AnySource::is_valid_attr_key("git", "remote"));
+		assert!(AnySource::is_valid_attr_key("http", "url"));
+		assert!(AnySource::is_valid_attr_key("mock", "same"));
+		assert!(!AnySource::is_valid_attr_key("mock", "url"));
+		assert!(!AnySource::is_valid_attr_key("ftp", "url"));
+	}
+}
@@ -22,7 +22,7 @@ pub fn helper_macro_git(args: TokenStream, input: TokenStream) -> TokenStream {
 		"https://github.com/ramate-io/cite",
 		&format!("tests/helper-macro-git/helper-macro-git/DOC_{}.md", doc_num),
 		"83e6dc0fb4d357c87a89afa7b22c4a62aad0b41",
-		"83e6dc0fb4d357c87a89afa7b22c4a62aad0b41",
+		Some("83e6dc0fb4d357c87a89afa7b22c4a62aad0b41"),
 		None,
 	)
 	.expect("Failed to create GitSource");
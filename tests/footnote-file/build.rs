@@ -0,0 +1,12 @@
+//! Collects the footnotes of the crate's citations in its out directory, where the binary reads
+//! them back from
+
+fn main() {
+	let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR for build scripts");
+	let path = std::path::Path::new(&out_dir).join("CITATIONS.md");
+	// A fresh file, so that it only holds the entries of this build
+	let _ = std::fs::remove_file(&path);
+	println!("cargo:rustc-env=CITE_FOOTNOTE_FILE={}", path.display());
+	println!("cargo:rerun-if-changed=build.rs");
+	println!("cargo:rerun-if-changed=src");
+}
@@ -0,0 +1,22 @@
+//! Cites mock sources while collecting their footnotes in a file, which it prints when run
+
+use cite::cite;
+
+#[cite(mock, same = "content", reason = "Parsing follows the cited grammar")]
+fn parse() {}
+
+#[cite(mock, same = "content", reason = "Rendering keeps the cited layout")]
+struct Renderer;
+
+fn main() {
+	parse();
+	let _renderer = Renderer;
+	let path = env!("CITE_FOOTNOTE_FILE");
+	match std::fs::read_to_string(path) {
+		Ok(citations) => print!("{}", citations),
+		Err(e) => {
+			eprintln!("Failed to read {}: {}", path, e);
+			std::process::exit(1);
+		}
+	}
+}
@@ -0,0 +1,25 @@
+//! Runs the binary built with `CITE_FOOTNOTE_FILE` set and checks the footnotes it collected
+
+use std::process::Command;
+
+#[test]
+fn test_footnote_file_collects_citations() -> Result<(), Box<dyn std::error::Error>> {
+	let output = Command::new(env!("CARGO_BIN_EXE_footnote-file")).output()?;
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	// Entries are located at their attribute where the compiler tells where it is
+	let citations = String::from_utf8(output.stdout)?;
+	let heading = |item: &str, line: usize| {
+		citations.lines().any(|heading| {
+			heading.starts_with(&format!("### `{}` (cite-footnote-file-test", item))
+				&& (heading.ends_with(&format!("src/main.rs:{})", line))
+					|| heading.ends_with("(cite-footnote-file-test)"))
+		})
+	};
+	assert!(heading("fn parse", 5), "{}", citations);
+	assert!(citations.contains("Parsing follows the cited grammar"));
+	assert!(heading("struct Renderer", 8), "{}", citations);
+	assert!(citations.contains("Rendering keeps the cited layout"));
+	assert!(!std::path::Path::new(&format!("{}.lock", env!("CITE_FOOTNOTE_FILE"))).exists());
+	Ok(())
+}
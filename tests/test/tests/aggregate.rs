@@ -0,0 +1,9 @@
+// Integration test for CITE_AGGREGATE, in a test binary of its own so that the variable does not
+// reach the other compile tests
+
+#[test]
+fn test_aggregated_drift_fails_at_each_citation() {
+	std::env::set_var("CITE_AGGREGATE", "1");
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/aggregate/*.rs");
+}
@@ -56,6 +56,12 @@ fn test_wrong_target_fails() {
 	t.compile_fail("tests/ui/fail-syntax/wrong_target.rs");
 }
 
+#[test]
+fn test_legacy_syntax_warns() {
+	let t = trybuild::TestCases::new();
+	t.warn("tests/ui/warn/legacy_syntax.rs");
+}
+
 #[test]
 fn test_module_citation_compiles() {
 	let t = trybuild::TestCases::new();
@@ -79,3 +85,21 @@ fn test_missing_reason_fails() {
 	let t = trybuild::TestCases::new();
 	t.compile_fail("tests/ui/fail-invalid/missing_reason.rs");
 }
+
+#[test]
+fn test_http_invalid_url_fails() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/fail-syntax/http_invalid_url.rs");
+}
+
+#[test]
+fn test_git_missing_kwarg_fails() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/fail-syntax/git_missing_kwarg.rs");
+}
+
+#[test]
+fn test_wrong_target_static_fails() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/fail-syntax/wrong_target_static.rs");
+}
@@ -0,0 +1,14 @@
+// Test that with CITE_AGGREGATE every drifted citation fails at its own span, numbered
+
+use cite::cite;
+
+#[cite(mock, changed = ("old parse", "new parse"), level = "ERROR", reason = "test reason")]
+fn parse() {}
+
+#[cite(mock, changed = ("old lex", "new lex"), level = "ERROR", reason = "test reason")]
+fn lex() {}
+
+fn main() {
+	parse();
+	lex();
+}
@@ -0,0 +1,13 @@
+// Test that a git citation without a required parameter fails to compile
+
+use cite::cite;
+
+// Missing ref_rev parameter
+#[cite(git, remote = "https://github.com/ramate-io/cite", cur_rev = "main", path = "README.md", reason = "test reason")]
+fn test_missing_ref_rev() {
+	println!("This should fail - missing ref_rev");
+}
+
+fn main() {
+	test_missing_ref_rev();
+}
@@ -0,0 +1,12 @@
+// Test that an HTTP citation with a malformed URL fails to compile
+
+use cite::cite;
+
+#[cite(http, url = "not a url", selector = "h1", reason = "test reason")]
+fn test_invalid_url() {
+	println!("This should fail - invalid URL");
+}
+
+fn main() {
+	test_invalid_url();
+}
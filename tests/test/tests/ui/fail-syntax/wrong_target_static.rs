@@ -0,0 +1,9 @@
+// Test that citations on static items fail to compile
+
+use cite::cite;
+
+// This should fail - can't cite a static item
+#[cite(mock, same = "content", reason = "test reason")]
+static INVALID_TARGET: &str = "content";
+
+fn main() {}
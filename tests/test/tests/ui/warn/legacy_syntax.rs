@@ -0,0 +1,12 @@
+// Test that legacy source syntax compiles with a deprecation warning naming its replacement
+
+use cite::cite;
+
+#[cite(MockSource::same("content"), reason = "test reason")]
+fn function_with_legacy_citation() {
+	println!("This function cites its source with the legacy syntax");
+}
+
+fn main() {
+	function_with_legacy_citation();
+}